@@ -1,3 +1,23 @@
+/// Dependencies whose resolved versions are reported by `get_app_capabilities`
+const REPORTED_DEPENDENCIES: &[&str] = &["serialport", "rhai", "notify", "zstd", "zip", "midir"];
+
+/// Export `DEP_VERSION_<NAME>` for each reported dependency, read from Cargo.lock
+fn export_dependency_versions() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    let Ok(lock) = std::fs::read_to_string("Cargo.lock") else { return };
+    let mut name = None;
+    for line in lock.lines() {
+        if let Some(value) = line.strip_prefix("name = ") {
+            name = Some(value.trim_matches('"'));
+        } else if let Some(version) = line.strip_prefix("version = ") {
+            if let Some(dep) = name.take().filter(|dep| REPORTED_DEPENDENCIES.contains(dep)) {
+                println!("cargo:rustc-env=DEP_VERSION_{}={}", dep.to_ascii_uppercase(), version.trim_matches('"'));
+            }
+        }
+    }
+}
+
 fn main() {
+    export_dependency_versions();
     tauri_build::build()
 }
@@ -0,0 +1,13 @@
+//! Fuzz config decoding and validation: format detection, JSON/MessagePack/CBOR
+//! decoding, and `check_config` at every strictness must never panic.
+//!
+//!     cd config-editor/src-tauri/fuzz
+//!     cargo +nightly fuzz run config ../corpus ../../../firmware/dev
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    config_editor_lib::check_bytes(data);
+});
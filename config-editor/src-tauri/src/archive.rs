@@ -0,0 +1,228 @@
+//! Compression for the backup store, and storage usage
+//!
+//! Presets and firmware rollback slots are written zstd-compressed (a `.zst`
+//! suffix on the file name). Files written before compression was added are
+//! still read as-is, so restore paths work with either.
+//!
+//! Configs can also be read straight out of a device snapshot zip with an
+//! `<archive>.zip!/<entry>` path, e.g. `snapshot.zip!/config.json`.
+
+use crate::commands::{app_data_dir, backups_root, ConfigError};
+use crate::crash::crash_dir;
+use crate::download::download_cache_dir;
+use crate::firmware::rollback_root;
+use crate::presets::presets_dir;
+use crate::recovery::recovery_root;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+
+/// Suffix appended to compressed files
+pub const COMPRESSED_SUFFIX: &str = ".zst";
+
+/// Separator between a zip archive and the entry inside it
+pub const ARCHIVE_SEPARATOR: &str = "!/";
+
+/// zstd level: small JSON and .mpy files compress well without the time the
+/// top levels take
+const COMPRESSION_LEVEL: i32 = 9;
+
+pub fn compress(data: &[u8]) -> io::Result<Vec<u8>> {
+    zstd::encode_all(data, COMPRESSION_LEVEL)
+}
+
+pub fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+    zstd::decode_all(data)
+}
+
+/// True if the file name carries the compressed suffix
+pub fn is_compressed(path: &Path) -> bool {
+    path.to_str().is_some_and(|p| p.ends_with(COMPRESSED_SUFFIX))
+}
+
+/// `path` with the compressed suffix appended (`config.json` → `config.json.zst`)
+pub fn compressed_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(COMPRESSED_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Read a file, decompressing it if its name ends in `.zst`
+pub fn read_file(path: &Path) -> io::Result<Vec<u8>> {
+    let data = fs::read(path)?;
+    if is_compressed(path) {
+        decompress(&data)
+    } else {
+        Ok(data)
+    }
+}
+
+/// Split `snapshot.zip!/config.json` into the archive path and entry name.
+/// Returns None for ordinary paths.
+pub fn split_archive_path(path: &str) -> Option<(&Path, &str)> {
+    let (archive, entry) = path.split_once(ARCHIVE_SEPARATOR)?;
+    if !archive.to_ascii_lowercase().ends_with(".zip") || entry.is_empty() {
+        return None;
+    }
+    Some((Path::new(archive), entry))
+}
+
+/// Read one entry from a zip archive
+pub fn read_archive_entry(archive: &Path, entry: &str) -> io::Result<Vec<u8>> {
+    let mut zip = zip::ZipArchive::new(fs::File::open(archive)?).map_err(io::Error::other)?;
+    let mut file = zip.by_name(entry).map_err(|e| {
+        io::Error::new(io::ErrorKind::NotFound, format!("{} not found in {}: {}", entry, archive.display(), e))
+    })?;
+    let mut data = Vec::new();
+    io::Read::read_to_end(&mut file, &mut data)?;
+    Ok(data)
+}
+
+/// Read a file or an `<archive>.zip!/<entry>` path
+pub fn read_path(path: &str) -> io::Result<Vec<u8>> {
+    match split_archive_path(path) {
+        Some((archive, entry)) => read_archive_entry(archive, entry),
+        None => fs::read(path),
+    }
+}
+
+/// Disk usage of one area of the app's storage
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StorageArea {
+    pub name: String,
+    pub path: PathBuf,
+    pub files: usize,
+    /// Bytes on disk (compressed size for compressed files)
+    pub bytes: u64,
+}
+
+/// Disk usage of the app's backup store and caches
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StorageUsage {
+    pub areas: Vec<StorageArea>,
+    pub total_bytes: u64,
+}
+
+/// Count files and bytes under `dir` (recursively; missing dirs are empty)
+pub fn dir_usage(dir: &Path) -> (usize, u64) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (0, 0);
+    };
+    entries.flatten().fold((0, 0), |(files, bytes), entry| match entry.metadata() {
+        Ok(meta) if meta.is_dir() => {
+            let (f, b) = dir_usage(&entry.path());
+            (files + f, bytes + b)
+        }
+        Ok(meta) => (files + 1, bytes + meta.len()),
+        Err(_) => (files, bytes),
+    })
+}
+
+/// Build a usage report for named directories
+pub fn storage_usage(areas: &[(&str, PathBuf)]) -> StorageUsage {
+    let areas: Vec<StorageArea> = areas
+        .iter()
+        .map(|(name, path)| {
+            let (files, bytes) = dir_usage(path);
+            StorageArea {
+                name: name.to_string(),
+                path: path.clone(),
+                files,
+                bytes,
+            }
+        })
+        .collect();
+    let total_bytes = areas.iter().map(|a| a.bytes).sum();
+    StorageUsage { areas, total_bytes }
+}
+
+/// The preset library, rollback slots, download cache, crash reports,
+/// recovery points and config backups
+pub(crate) fn storage_areas(app: &AppHandle) -> Result<Vec<(&'static str, PathBuf)>, ConfigError> {
+    Ok(vec![
+        ("presets", presets_dir(app)?),
+        ("rollback", rollback_root(&app_data_dir(app)?)),
+        ("downloads", download_cache_dir(app)?),
+        ("crashes", crash_dir(app)?),
+        ("recovery", recovery_root(&app_data_dir(app)?)),
+        ("backups", backups_root(&app_data_dir(app)?)),
+    ])
+}
+
+/// Report how much disk the preset library, rollback slots, download cache,
+/// crash reports, recovery points and config backups use
+#[command]
+pub fn get_storage_usage(app: AppHandle) -> Result<StorageUsage, ConfigError> {
+    Ok(storage_usage(&storage_areas(&app)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compressed_path_and_detection() {
+        let path = compressed_path(Path::new("/data/presets/Sunday.json"));
+        assert_eq!(path, PathBuf::from("/data/presets/Sunday.json.zst"));
+        assert!(is_compressed(&path));
+        assert!(!is_compressed(Path::new("/data/presets/Sunday.json")));
+    }
+
+    #[test]
+    fn test_read_file_is_transparent() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("a.json");
+        fs::write(&plain, b"{\"buttons\": []}").unwrap();
+        let packed = compressed_path(&plain);
+        fs::write(&packed, compress(b"{\"buttons\": []}").unwrap()).unwrap();
+
+        assert_eq!(read_file(&plain).unwrap(), read_file(&packed).unwrap());
+    }
+
+    #[test]
+    fn test_split_archive_path() {
+        assert_eq!(
+            split_archive_path("/backups/snapshot.zip!/config.json"),
+            Some((Path::new("/backups/snapshot.zip"), "config.json"))
+        );
+        assert_eq!(
+            split_archive_path("C:\\Backups\\Snap.ZIP!/core/config.py"),
+            Some((Path::new("C:\\Backups\\Snap.ZIP"), "core/config.py"))
+        );
+        assert_eq!(split_archive_path("/Volumes/CIRCUITPY/config.json"), None);
+        assert_eq!(split_archive_path("/backups/notes.txt!/config.json"), None);
+        assert_eq!(split_archive_path("/backups/snapshot.zip!/"), None);
+    }
+
+    #[test]
+    fn test_read_path_from_zip() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("snapshot.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        zip.start_file("config.json", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"{\"buttons\": []}").unwrap();
+        zip.finish().unwrap();
+
+        let path = format!("{}!/config.json", archive.display());
+        assert_eq!(read_path(&path).unwrap(), b"{\"buttons\": []}");
+        let missing = format!("{}!/boot.py", archive.display());
+        assert_eq!(read_path(&missing).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_storage_usage_counts_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("slot/lib")).unwrap();
+        fs::write(dir.path().join("slot/code.py.zst"), [0u8; 10]).unwrap();
+        fs::write(dir.path().join("slot/lib/a.mpy.zst"), [0u8; 5]).unwrap();
+
+        let usage = storage_usage(&[("rollback", dir.path().to_path_buf()), ("missing", dir.path().join("nope"))]);
+        assert_eq!(usage.areas[0].files, 2);
+        assert_eq!(usage.areas[0].bytes, 15);
+        assert_eq!(usage.areas[1].files, 0);
+        assert_eq!(usage.total_bytes, 15);
+    }
+}
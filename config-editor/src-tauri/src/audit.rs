@@ -0,0 +1,206 @@
+//! Device file audit
+//!
+//! Lists files on the device volume that neither the firmware nor the config
+//! layout accounts for — macOS metadata, leftover partial writes, stale `.py`
+//! sources shadowing compiled `.mpy` modules, bank files — each with a cleanup
+//! suggestion. Nothing is removed here; the UI decides what to act on.
+
+use crate::commands::{validate_device_path, ConfigError};
+use crate::firmware::{is_host_metadata, FIRMWARE_ENTRIES};
+use crate::health::resolve_volume;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+/// Top-level entries that belong to the user or to CircuitPython itself.
+/// CircuitPython creates the hidden ones when it formats the drive to keep
+/// macOS from indexing it, so they must not be reported as clutter.
+const KNOWN_ENTRIES: &[&str] = &[
+    "config.json",
+    "boot_out.txt",
+    "settings.toml",
+    "sd",
+    "profiles",
+    ".metadata_never_index",
+    ".Trashes",
+    ".fseventsd",
+    "System Volume Information",
+];
+
+/// Suffixes left behind by interrupted writes and editors
+const LEFTOVER_SUFFIXES: &[&str] = &[".tmp", ".part", ".bak", ".swp", "~"];
+
+/// Why a file was reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanKind {
+    /// `.DS_Store`, `._*` resource forks, `__pycache__`
+    HostMetadata,
+    /// Partial writes and editor backups
+    Leftover,
+    /// `.py` next to a `.mpy` of the same name; CircuitPython loads the `.py`
+    StaleSource,
+    /// `bankN.json` from an older layout; the firmware does not read banks
+    BankFile,
+    /// Anything else the firmware does not use
+    Unknown,
+}
+
+/// A file on the device that is not part of the firmware or config
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OrphanFile {
+    /// Path relative to the volume root
+    pub path: PathBuf,
+    pub kind: OrphanKind,
+    pub bytes: u64,
+    /// True when removing it cannot affect the firmware or the user's config
+    pub safe_to_delete: bool,
+    pub suggestion: String,
+}
+
+fn is_leftover(name: &str) -> bool {
+    LEFTOVER_SUFFIXES.iter().any(|s| name.ends_with(s))
+}
+
+fn is_bank_file(name: &str) -> bool {
+    name.strip_prefix("bank")
+        .and_then(|rest| rest.strip_suffix(".json"))
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn entry_bytes(path: &Path) -> u64 {
+    match fs::metadata(path) {
+        Ok(meta) if meta.is_dir() => crate::archive::dir_usage(path).1,
+        Ok(meta) => meta.len(),
+        Err(_) => 0,
+    }
+}
+
+fn orphan(root: &Path, path: &Path, kind: OrphanKind) -> OrphanFile {
+    let (safe_to_delete, suggestion) = match kind {
+        OrphanKind::HostMetadata => (true, "Created by the host OS; safe to delete."),
+        OrphanKind::Leftover => (true, "Left over from an interrupted write or an editor; safe to delete."),
+        OrphanKind::StaleSource => (
+            true,
+            "Shadows the compiled .mpy with the same name, so CircuitPython runs old code. Delete it.",
+        ),
+        OrphanKind::BankFile => (
+            false,
+            "The firmware does not read bank files. Copy it somewhere safe before deleting if you want to keep it.",
+        ),
+        OrphanKind::Unknown => (false, "Not used by the firmware. Review it before deleting."),
+    };
+    OrphanFile {
+        path: path.strip_prefix(root).unwrap_or(path).to_path_buf(),
+        kind,
+        bytes: entry_bytes(path),
+        safe_to_delete,
+        suggestion: suggestion.to_string(),
+    }
+}
+
+/// Walk a firmware directory for clutter. Firmware contents vary by release,
+/// so only files that are clutter by name are reported.
+fn audit_firmware_dir(root: &Path, dir: &Path, found: &mut Vec<OrphanFile>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if is_host_metadata(&name) || name.ends_with(".pyc") {
+            found.push(orphan(root, &path, OrphanKind::HostMetadata));
+        } else if is_leftover(&name) {
+            found.push(orphan(root, &path, OrphanKind::Leftover));
+        } else if path.is_dir() {
+            audit_firmware_dir(root, &path, found);
+        } else if name.ends_with(".py") && path.with_extension("mpy").is_file() {
+            found.push(orphan(root, &path, OrphanKind::StaleSource));
+        }
+    }
+}
+
+/// Audit the device mounted at `root`
+pub fn audit_volume(root: &Path) -> Vec<OrphanFile> {
+    let mut found = Vec::new();
+    let Ok(entries) = fs::read_dir(root) else { return found };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if FIRMWARE_ENTRIES.contains(&name.as_str()) {
+            if path.is_dir() {
+                audit_firmware_dir(root, &path, &mut found);
+            }
+        } else if KNOWN_ENTRIES.contains(&name.as_str()) {
+            continue;
+        } else if is_host_metadata(&name) {
+            found.push(orphan(root, &path, OrphanKind::HostMetadata));
+        } else if is_leftover(&name) {
+            found.push(orphan(root, &path, OrphanKind::Leftover));
+        } else if is_bank_file(&name) {
+            found.push(orphan(root, &path, OrphanKind::BankFile));
+        } else {
+            found.push(orphan(root, &path, OrphanKind::Unknown));
+        }
+    }
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    found
+}
+
+/// List files on the device not accounted for by the firmware or config,
+/// with a cleanup suggestion for each
+#[command]
+pub fn audit_device_files(device_path: String) -> Result<Vec<OrphanFile>, ConfigError> {
+    let volume = resolve_volume(validate_device_path(&device_path)?);
+    Ok(audit_volume(&volume))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(found: &[OrphanFile]) -> Vec<(String, OrphanKind)> {
+        found
+            .iter()
+            .map(|f| (f.path.to_string_lossy().replace('\\', "/"), f.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_bank_file_names() {
+        assert!(is_bank_file("bank1.json"));
+        assert!(is_bank_file("bank12.json"));
+        assert!(!is_bank_file("bank.json"));
+        assert!(!is_bank_file("banks.json"));
+        assert!(!is_bank_file("bank1.json.bak"));
+    }
+
+    #[test]
+    fn test_audit_classifies_clutter() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in ["code.py", "config.json", "boot_out.txt", ".metadata_never_index", ".DS_Store", "._config.json"] {
+            fs::write(root.join(file), b"x").unwrap();
+        }
+        fs::write(root.join("config.json.tmp"), b"{").unwrap();
+        fs::write(root.join("bank2.json"), b"{}").unwrap();
+        fs::write(root.join("notes.txt"), b"hi").unwrap();
+        fs::create_dir_all(root.join("core/__pycache__")).unwrap();
+        fs::write(root.join("core/config.mpy"), b"m").unwrap();
+        fs::write(root.join("core/config.py"), b"p").unwrap();
+        fs::write(root.join("core/buttons.py"), b"p").unwrap();
+
+        let found = audit_volume(root);
+        assert_eq!(
+            kinds(&found),
+            vec![
+                (".DS_Store".to_string(), OrphanKind::HostMetadata),
+                ("._config.json".to_string(), OrphanKind::HostMetadata),
+                ("bank2.json".to_string(), OrphanKind::BankFile),
+                ("config.json.tmp".to_string(), OrphanKind::Leftover),
+                ("core/__pycache__".to_string(), OrphanKind::HostMetadata),
+                ("core/config.py".to_string(), OrphanKind::StaleSource),
+                ("notes.txt".to_string(), OrphanKind::Unknown),
+            ]
+        );
+        assert!(found.iter().filter(|f| f.kind == OrphanKind::BankFile).all(|f| !f.safe_to_delete));
+    }
+}
@@ -0,0 +1,6 @@
+//! `mcc`: validate, diff, read and push MIDI Captain configs from the
+//! terminal. See cli.rs.
+
+fn main() {
+    std::process::exit(config_editor_lib::cli::main(std::env::args().skip(1).collect()));
+}
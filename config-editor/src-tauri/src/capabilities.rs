@@ -0,0 +1,172 @@
+//! What this build of the app can do
+//!
+//! The frontend hides UI for subsystems that are not available, and bug
+//! reports include the report so build variants can be told apart.
+//! Dependency versions come from Cargo.lock via build.rs.
+//!
+//! The command surface is versioned too. A webview can keep serving a cached
+//! frontend after the app is updated, so the frontend negotiates before its
+//! first command and stops with a clear error if the two don't match.
+
+use tauri::command;
+
+/// Version of the command surface: command names and argument/result shapes.
+/// Bump when a command is removed or a shape changes incompatibly, and keep
+/// `API_VERSION` in src/lib/api.ts in step.
+pub const API_VERSION: u32 = 4;
+
+/// Oldest frontend API version the commands still accept. Raise it to
+/// `API_VERSION` when a bump is not backward compatible.
+pub const MIN_FRONTEND_API_VERSION: u32 = 4;
+
+/// Result of `get_api_version`
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ApiVersion {
+    pub version: u32,
+    pub min_frontend_version: u32,
+}
+
+/// Why a frontend can't use this backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiErrorKind {
+    /// A cached frontend from before an incompatible update; reload it
+    FrontendTooOld,
+    /// A frontend newer than this backend (a partial update or dev build)
+    FrontendTooNew,
+}
+
+/// Returned by `negotiate_api_version` on a mismatch
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ApiVersionError {
+    pub kind: ApiErrorKind,
+    pub message: String,
+    pub backend_version: u32,
+    pub frontend_version: u32,
+}
+
+/// The command surface this backend serves
+pub fn api_version() -> ApiVersion {
+    ApiVersion {
+        version: API_VERSION,
+        min_frontend_version: MIN_FRONTEND_API_VERSION,
+    }
+}
+
+/// Check a frontend's API version against this backend
+pub fn check_api_version(frontend_version: u32) -> Result<ApiVersion, ApiVersionError> {
+    let (kind, message) = if frontend_version < MIN_FRONTEND_API_VERSION {
+        (ApiErrorKind::FrontendTooOld, "The editor window is out of date. Reload it to finish the update.")
+    } else if frontend_version > API_VERSION {
+        (ApiErrorKind::FrontendTooNew, "The editor window is newer than the app. Reinstall the app.")
+    } else {
+        return Ok(api_version());
+    };
+    Err(ApiVersionError {
+        kind,
+        message: message.to_string(),
+        backend_version: API_VERSION,
+        frontend_version,
+    })
+}
+
+/// An optional part of the app
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Subsystem {
+    pub name: String,
+    /// Compiled in and usable on this platform
+    pub available: bool,
+    /// Version of the library behind it, when there is one
+    pub version: Option<String>,
+}
+
+/// Result of `get_app_capabilities`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AppCapabilities {
+    pub app_version: String,
+    pub tauri_version: String,
+    pub os: String,
+    pub arch: String,
+    pub debug_build: bool,
+    pub subsystems: Vec<Subsystem>,
+}
+
+fn subsystem(name: &str, available: bool, version: Option<&str>) -> Subsystem {
+    Subsystem {
+        name: name.to_string(),
+        available,
+        version: version.map(str::to_string),
+    }
+}
+
+/// Build the capability report
+pub fn app_capabilities() -> AppCapabilities {
+    AppCapabilities {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        debug_build: cfg!(debug_assertions),
+        subsystems: vec![
+            // Serial console: boot tracebacks, live parameters, monitors
+            subsystem("serial", true, option_env!("DEP_VERSION_SERIALPORT")),
+            subsystem("scripting", true, option_env!("DEP_VERSION_RHAI")),
+            subsystem("device_watcher", true, option_env!("DEP_VERSION_NOTIFY")),
+            subsystem("compression", true, option_env!("DEP_VERSION_ZSTD")),
+            subsystem("snapshot_archives", true, option_env!("DEP_VERSION_ZIP")),
+            // MIDI output: config-change broadcast
+            subsystem("midi", true, option_env!("DEP_VERSION_MIDIR")),
+            // Not built yet; listed so the frontend can check for them by name
+            subsystem("osc", false, None),
+            subsystem("git_history", false, None),
+            subsystem("local_api", false, None),
+        ],
+    }
+}
+
+/// Report which optional subsystems this build has, with their versions
+#[command]
+pub fn get_app_capabilities() -> AppCapabilities {
+    app_capabilities()
+}
+
+/// Version of the command surface
+#[command]
+pub fn get_api_version() -> ApiVersion {
+    api_version()
+}
+
+/// Check the frontend's API version before it sends any other command
+#[command]
+pub fn negotiate_api_version(frontend_version: u32) -> Result<ApiVersion, ApiVersionError> {
+    check_api_version(frontend_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_name_each_subsystem_once() {
+        let caps = app_capabilities();
+        let mut names: Vec<&str> = caps.subsystems.iter().map(|s| s.name.as_str()).collect();
+        let count = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), count);
+        assert!(caps.subsystems.iter().any(|s| s.name == "serial" && s.available));
+        assert!(!caps.app_version.is_empty());
+    }
+
+    #[test]
+    fn test_api_version_negotiation() {
+        assert_eq!(check_api_version(API_VERSION).unwrap(), api_version());
+        let newer = check_api_version(API_VERSION + 1).unwrap_err();
+        assert_eq!(newer.kind, ApiErrorKind::FrontendTooNew);
+        assert_eq!(newer.frontend_version, API_VERSION + 1);
+        if MIN_FRONTEND_API_VERSION > 0 {
+            let older = check_api_version(MIN_FRONTEND_API_VERSION - 1).unwrap_err();
+            assert_eq!(older.kind, ApiErrorKind::FrontendTooOld);
+        }
+    }
+}
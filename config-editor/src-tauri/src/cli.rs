@@ -0,0 +1,332 @@
+//! The `mcc` command-line tool (src/bin/mcc.rs)
+//!
+//! For scripting and CI of configs kept in a repo: validate them, diff
+//! them, read a device's config, and push one to a device — with the same
+//! checks, backups and locked-config handling as the editor.
+//!
+//! ```text
+//! mcc validate [--strict|--permissive] FILE...
+//! mcc diff FILE_A FILE_B
+//! mcc read [--device auto|PATH] [--format json|yaml|toml]
+//! mcc push [--device auto|PATH] [--force] [--strict|--permissive] FILE
+//! ```
+//!
+//! Local files may be JSON, YAML or TOML, chosen by extension. Exit status
+//! is 0 on success, 1 when a config has problems or two configs differ, and
+//! 2 for usage and I/O errors.
+
+use crate::commands::{check_config, read_config, write_config_raw, ConfigError, Strictness};
+use crate::config::MidiCaptainConfig;
+use crate::confirm::Confirmable;
+use crate::diff::{diff_configs, ChangeKind};
+use crate::interchange::{export_text, import_value, TextFormat};
+use std::io::Write;
+use std::path::Path;
+
+const USAGE: &str = "\
+Usage:
+  mcc validate [--strict|--permissive] FILE...
+  mcc diff FILE_A FILE_B
+  mcc read [--device auto|PATH] [--format json|yaml|toml]
+  mcc push [--device auto|PATH] [--force] [--strict|--permissive] FILE
+
+--device auto (the default) uses the one connected MIDI Captain.
+--force overwrites a config locked in the editor.";
+
+/// Exit status when a config has problems or two configs differ
+pub const EXIT_FAILED: i32 = 1;
+/// Exit status for usage and I/O errors
+pub const EXIT_ERROR: i32 = 2;
+
+/// A parsed command line
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Validate { files: Vec<String>, strictness: Strictness },
+    Diff { a: String, b: String },
+    Read { device: String, format: TextFormat },
+    Push { file: String, device: String, force: bool, strictness: Strictness },
+}
+
+fn parse_format(name: &str) -> Result<TextFormat, String> {
+    match name {
+        "json" => Ok(TextFormat::Json),
+        "yaml" | "yml" => Ok(TextFormat::Yaml),
+        "toml" => Ok(TextFormat::Toml),
+        _ => Err(format!("Unknown format '{}'", name)),
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut device = "auto".to_string();
+    let mut format = TextFormat::Json;
+    let mut force = false;
+    let mut strictness = Strictness::Normal;
+    let mut positional = Vec::new();
+
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| rest.next().cloned())
+                .ok_or_else(|| format!("{} needs a value", flag))
+        };
+        match flag {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--device" => device = value()?,
+            "--format" => format = parse_format(&value()?)?,
+            "--force" => force = true,
+            "--strict" => strictness = Strictness::Strict,
+            "--permissive" => strictness = Strictness::Permissive,
+            _ if flag.starts_with('-') && flag != "-" => return Err(format!("Unknown option {}", flag)),
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let (name, operands) = positional.split_first().ok_or("No command given")?;
+    match (name.as_str(), operands) {
+        ("help", _) => Ok(Command::Help),
+        ("validate", []) => Err("validate needs at least one file".to_string()),
+        ("validate", files) => Ok(Command::Validate { files: files.to_vec(), strictness }),
+        ("diff", [a, b]) => Ok(Command::Diff { a: a.clone(), b: b.clone() }),
+        ("read", []) => Ok(Command::Read { device, format }),
+        ("push", [file]) => Ok(Command::Push { file: file.clone(), device, force, strictness }),
+        ("diff" | "read" | "push", _) => Err(format!("Wrong number of arguments for {}", name)),
+        _ => Err(format!("Unknown command '{}'", name)),
+    }
+}
+
+fn error(message: String) -> ConfigError {
+    ConfigError {
+        message,
+        details: None,
+        code: None,
+    }
+}
+
+/// A local config file as JSON, upgraded to the current schema
+fn load(path: &str) -> Result<serde_json::Value, ConfigError> {
+    let content = if path == "-" {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(path)
+    }
+    .map_err(|e| error(format!("Could not read {}: {}", path, e)))?;
+    import_value(&content, TextFormat::from_path(path))
+}
+
+fn load_config(path: &str) -> Result<MidiCaptainConfig, ConfigError> {
+    serde_json::from_value(load(path)?).map_err(|e| error(format!("{} is not a valid config: {}", path, e)))
+}
+
+/// The config.json for `--device`: the one connected device for `auto`,
+/// otherwise a volume or file path
+fn device_config_path(device: &str) -> Result<String, ConfigError> {
+    if device != "auto" {
+        let path = Path::new(device);
+        let path = if path.is_dir() { path.join("config.json") } else { path.to_path_buf() };
+        return Ok(path.display().to_string());
+    }
+    match crate::device::scan_devices().as_slice() {
+        [device] => Ok(device.config_path.display().to_string()),
+        [] => Err(error("No MIDI Captain found; connect one or pass --device PATH".to_string())),
+        many => Err(ConfigError {
+            message: "More than one MIDI Captain is connected; pick one with --device PATH".to_string(),
+            details: Some(many.iter().map(|d| d.path.display().to_string()).collect()),
+            code: None,
+        }),
+    }
+}
+
+/// An error as printed to the terminal, details indented below it
+fn describe(e: &ConfigError) -> String {
+    let mut text = e.message.clone();
+    for detail in e.details.iter().flatten() {
+        text.push_str("\n  - ");
+        text.push_str(detail);
+    }
+    text
+}
+
+fn validate(files: &[String], strictness: Strictness, out: &mut dyn Write) -> Result<i32, ConfigError> {
+    let mut status = 0;
+    for file in files {
+        match load(file).and_then(|value| check_config(&value, strictness)) {
+            Ok((_, problems)) if problems.is_empty() => writeln!(out, "{}: ok", file)?,
+            Ok((_, problems)) => {
+                // Only permissive mode lets problems through
+                writeln!(out, "{}: ok with {} problem(s)", file, problems.len())?;
+                for problem in problems {
+                    writeln!(out, "  - {}", problem)?;
+                }
+            }
+            Err(e) => {
+                writeln!(out, "{}: {}", file, describe(&e))?;
+                status = EXIT_FAILED;
+            }
+        }
+    }
+    Ok(status)
+}
+
+fn diff(a: &str, b: &str, out: &mut dyn Write) -> Result<i32, ConfigError> {
+    let changes = diff_configs(&load_config(a)?, &load_config(b)?)?;
+    let show = |v: &Option<serde_json::Value>| v.as_ref().map(|v| v.to_string()).unwrap_or_default();
+    for change in &changes {
+        match change.kind {
+            ChangeKind::Added => writeln!(out, "+ {}: {}", change.path, show(&change.after))?,
+            ChangeKind::Removed => writeln!(out, "- {}: {}", change.path, show(&change.before))?,
+            ChangeKind::Changed => writeln!(
+                out,
+                "~ {}: {} -> {}",
+                change.path,
+                show(&change.before),
+                show(&change.after)
+            )?,
+        }
+    }
+    Ok(if changes.is_empty() { 0 } else { EXIT_FAILED })
+}
+
+fn push(file: &str, device: &str, force: bool, strictness: Strictness, out: &mut dyn Write) -> Result<i32, ConfigError> {
+    let json = serde_json::to_string_pretty(&load(file)?)?;
+    let path = device_config_path(device)?;
+    let write = |confirmation| write_config_raw(path.clone(), json.clone(), Some(force), confirmation, Some(strictness), None);
+    // --force is the confirmation for overwriting a locked config
+    if let Confirmable::ConfirmationRequired { token, summary } = write(None)? {
+        writeln!(out, "{}", summary)?;
+        write(Some(token))?;
+    }
+    writeln!(out, "Wrote {} to {}", file, path)?;
+    Ok(0)
+}
+
+fn execute(command: Command, out: &mut dyn Write) -> Result<i32, ConfigError> {
+    match command {
+        Command::Help => {
+            writeln!(out, "{}", USAGE)?;
+            Ok(0)
+        }
+        Command::Validate { files, strictness } => validate(&files, strictness, out),
+        Command::Diff { a, b } => diff(&a, &b, out),
+        Command::Read { device, format } => {
+            let config = read_config(device_config_path(&device)?)?.config;
+            writeln!(out, "{}", export_text(&config, format)?)?;
+            Ok(0)
+        }
+        Command::Push { file, device, force, strictness } => push(&file, &device, force, strictness, out),
+    }
+}
+
+/// Run `mcc` with `args` (without the program name); returns the exit status
+pub fn main(args: Vec<String>) -> i32 {
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("mcc: {}\n\n{}", message, USAGE);
+            return EXIT_ERROR;
+        }
+    };
+    match execute(command, &mut std::io::stdout().lock()) {
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!("mcc: {}", describe(&e));
+            EXIT_ERROR
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const CONFIG: &str = r#"{"device": "mini6", "buttons": [
+        {"label": "A", "cc": 20, "color": "red"}, {"label": "B", "cc": 21, "color": "red"},
+        {"label": "C", "cc": 22, "color": "red"}, {"label": "D", "cc": 23, "color": "red"},
+        {"label": "E", "cc": 24, "color": "red"}, {"label": "F", "cc": 25, "color": "red"}
+    ]}"#;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn run(line: &str) -> (i32, String) {
+        let mut out = Vec::new();
+        let status = execute(parse_args(&args(line)).unwrap(), &mut out).unwrap();
+        (status, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args("push --device=/Volumes/CIRCUITPY --force rig.yaml")).unwrap(),
+            Command::Push {
+                file: "rig.yaml".to_string(),
+                device: "/Volumes/CIRCUITPY".to_string(),
+                force: true,
+                strictness: Strictness::Normal,
+            }
+        );
+        assert_eq!(
+            parse_args(&args("read --format toml")).unwrap(),
+            Command::Read { device: "auto".to_string(), format: TextFormat::Toml }
+        );
+        assert_eq!(
+            parse_args(&args("validate --strict a.json b.json")).unwrap(),
+            Command::Validate { files: args("a.json b.json"), strictness: Strictness::Strict }
+        );
+        assert_eq!(parse_args(&args("--help")).unwrap(), Command::Help);
+        assert!(parse_args(&args("")).is_err());
+        assert!(parse_args(&args("validate")).is_err());
+        assert!(parse_args(&args("diff a.json")).is_err());
+        assert!(parse_args(&args("push --device")).is_err());
+        assert!(parse_args(&args("push --bogus a.json")).is_err());
+        assert!(parse_args(&args("deploy a.json")).is_err());
+    }
+
+    #[test]
+    fn test_validate_reports_each_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good.json");
+        let bad = dir.path().join("bad.json");
+        fs::write(&good, CONFIG).unwrap();
+        fs::write(&bad, CONFIG.replace("\"cc\": 20", "\"cc\": 200")).unwrap();
+
+        let (status, out) = run(&format!("validate {}", good.display()));
+        assert_eq!(status, 0);
+        assert!(out.ends_with(": ok\n"));
+
+        let (status, out) = run(&format!("validate {} {}", good.display(), bad.display()));
+        assert_eq!(status, EXIT_FAILED);
+        assert!(out.contains("bad.json: Validation failed"));
+    }
+
+    #[test]
+    fn test_diff_exit_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.json");
+        let b = dir.path().join("b.json");
+        fs::write(&a, CONFIG).unwrap();
+        fs::write(&b, CONFIG.replace("\"cc\": 20", "\"cc\": 30")).unwrap();
+
+        let (status, out) = run(&format!("diff {} {}", a.display(), a.display()));
+        assert_eq!((status, out.as_str()), (0, ""));
+
+        let (status, out) = run(&format!("diff {} {}", a.display(), b.display()));
+        assert_eq!(status, EXIT_FAILED);
+        assert_eq!(out, "~ buttons[0].cc: 20 -> 30\n");
+    }
+
+    #[test]
+    fn test_device_path_given_as_volume() {
+        let volume = tempfile::tempdir().unwrap();
+        let path = device_config_path(&volume.path().display().to_string()).unwrap();
+        assert_eq!(Path::new(&path), volume.path().join("config.json"));
+    }
+}
@@ -1,17 +1,18 @@
 //! Tauri commands for config file operations
 
-use crate::config::MidiCaptainConfig;
+use crate::confirm::{confirm, Confirmable};
+use crate::config::{DeviceType, MidiCaptainConfig};
+use crate::download::sha256_hex;
+use crate::filesystem::{FileSystem, RealFs};
+use crate::registry::VerifyLevel;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use tauri::command;
+use tauri::{command, AppHandle, Manager};
 
 #[cfg(unix)]
 use std::os::unix::fs::MetadataExt;
 
-/// Known device volume names (for validation)
-const DEVICE_VOLUMES: &[&str] = &["CIRCUITPY", "MIDICAPTAIN"];
-
 /// Get volume name for a path (cross-platform)
 #[cfg(target_os = "windows")]
 fn get_path_volume_name(path: &Path) -> Option<String> {
@@ -73,6 +74,10 @@ fn get_path_volume_name(path: &Path) -> Option<String> {
 pub struct ConfigError {
     pub message: String,
     pub details: Option<Vec<String>>,
+    /// Machine-readable kind for errors the UI handles specially, e.g.
+    /// `read_only_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static str>,
 }
 
 impl From<std::io::Error> for ConfigError {
@@ -80,6 +85,7 @@ impl From<std::io::Error> for ConfigError {
         ConfigError {
             message: e.to_string(),
             details: None,
+            code: None,
         }
     }
 }
@@ -89,12 +95,190 @@ impl From<serde_json::Error> for ConfigError {
         ConfigError {
             message: format!("JSON parse error: {}", e),
             details: None,
+            code: None,
         }
     }
 }
 
+/// On-device config serialization format.
+///
+/// config.json is the only format the firmware reads today; the binary
+/// formats are here so a faster-booting firmware can switch without the
+/// editor changing. Reads auto-detect the format from the bytes, writes
+/// follow the file extension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigFormat {
+    Json,
+    MessagePack,
+    Cbor,
+}
+
+impl ConfigFormat {
+    /// Format for a file extension (`.json`, `.msgpack`/`.mpk`, `.cbor`); JSON otherwise
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+            Some("msgpack") | Some("mpk") => ConfigFormat::MessagePack,
+            Some("cbor") => ConfigFormat::Cbor,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    /// Detect the format from the first byte. A config is always a map, so
+    /// JSON starts with `{` (after whitespace or a BOM), MessagePack with a
+    /// map marker (0x80-0x8f, 0xde, 0xdf) and CBOR with a map (0xa0-0xbf) or
+    /// the self-describe tag (0xd9 0xd9 0xf7).
+    pub fn detect(bytes: &[u8]) -> Self {
+        let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+        match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(0x80..=0x8f) | Some(0xde) | Some(0xdf) => ConfigFormat::MessagePack,
+            Some(0xa0..=0xbf) | Some(0xd9) => ConfigFormat::Cbor,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    /// Decode a value in this format
+    pub fn decode<T: serde::de::DeserializeOwned>(self, bytes: &[u8]) -> Result<T, ConfigError> {
+        let decode_error = |e: String| ConfigError {
+            message: format!("{:?} decode error: {}", self, e),
+            details: None,
+            code: None,
+        };
+        match self {
+            ConfigFormat::Json => Ok(serde_json::from_slice(bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes))?),
+            ConfigFormat::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| decode_error(e.to_string())),
+            ConfigFormat::Cbor => ciborium::from_reader(bytes).map_err(|e| decode_error(e.to_string())),
+        }
+    }
+
+    /// Encode a value in this format (JSON is pretty-printed)
+    pub fn encode<T: serde::Serialize>(self, value: &T) -> Result<Vec<u8>, ConfigError> {
+        let encode_error = |e: String| ConfigError {
+            message: format!("{:?} encode error: {}", self, e),
+            details: None,
+            code: None,
+        };
+        match self {
+            ConfigFormat::Json => Ok(serde_json::to_string_pretty(value)?.into_bytes()),
+            ConfigFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| encode_error(e.to_string())),
+            ConfigFormat::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(value, &mut bytes).map_err(|e| encode_error(e.to_string()))?;
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+/// Read and decode a config file in whichever format it is stored
+pub(crate) fn read_config_file<T: serde::de::DeserializeOwned>(fs: &dyn FileSystem, path: &Path) -> Result<T, ConfigError> {
+    let bytes = fs.read(path)?;
+    ConfigFormat::detect(&bytes).decode(&bytes)
+}
+
+/// Encode a config in the format its path calls for and write it. Returns the bytes written.
+fn write_config_file<T: serde::Serialize>(fs: &dyn FileSystem, path: &Path, value: &T) -> Result<Vec<u8>, ConfigError> {
+    crate::config_cache::invalidate(path);
+    let bytes = ConfigFormat::for_path(path).encode(value)?;
+    fs.write(path, &bytes)?;
+    crate::config_watch::record_write(path, &bytes);
+    Ok(bytes)
+}
+
+fn verification_failed(path: &Path, reason: &str) -> ConfigError {
+    ConfigError {
+        message: format!("Write verification failed for {}", path.display()),
+        details: Some(vec![reason.to_string()]),
+        code: None,
+    }
+}
+
+/// Read a written config back and check it at the given level
+fn verify_write(
+    fs: &dyn FileSystem,
+    path: &Path,
+    written: &[u8],
+    value: &serde_json::Value,
+    level: VerifyLevel,
+) -> Result<(), ConfigError> {
+    let matches = match level {
+        VerifyLevel::None => return Ok(()),
+        VerifyLevel::Hash => sha256_hex(&fs.read(path)?) == sha256_hex(written),
+        VerifyLevel::Full => {
+            let read_back = fs.read(path)?;
+            let decoded: serde_json::Value = ConfigFormat::detect(&read_back)
+                .decode(&read_back)
+                .map_err(|e| verification_failed(path, &e.message))?;
+            decoded == *value
+        }
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(verification_failed(path, "The config read back from the device differs from the one written"))
+    }
+}
+
+/// `write_verified_to` on the host filesystem, skipping the guest-mode
+/// check, for putting back a config the app itself replaced (an expiring
+/// temporary config)
+pub(crate) fn write_verified_in_guest_mode(path: &Path, value: &serde_json::Value) -> Result<(), ConfigError> {
+    write_verified_to(&RealFs, installed_backups().as_deref(), path, value).map(drop)
+}
+
+/// Back up the config at `path` into `backups` (when given), write the new
+/// one, verify it at the device's verification level (see registry.rs) and
+/// record the write in the write log. Returns the new contents' `content_token`.
+pub(crate) fn write_verified_to(
+    fs: &dyn FileSystem,
+    backups: Option<&Path>,
+    path: &Path,
+    value: &serde_json::Value,
+) -> Result<String, ConfigError> {
+    write_verified_logging(fs, backups, path, value, &[])
+}
+
+/// `write_verified_to`, logging the `problems` a permissive write let through
+fn write_verified_logging(
+    fs: &dyn FileSystem,
+    backups: Option<&Path>,
+    path: &Path,
+    value: &serde_json::Value,
+    problems: &[String],
+) -> Result<String, ConfigError> {
+    if let Some(root) = backups {
+        back_up_before_write(fs, root, path)?;
+    }
+    let level = crate::registry::verification_for(path);
+    let result = write_config_file(fs, path, value).and_then(|written| {
+        verify_write(fs, path, &written, value, level)?;
+        crate::midi::broadcast_config_changed(path, &written);
+        Ok(content_token(&written))
+    });
+    crate::registry::log_write(path, level, &result, problems);
+    crate::notifications::deploy_finished(path, &result);
+    result
+}
+
+/// Resolve the app-data directory, creating it if needed.
+///
+/// Host-side state (firmware rollback slots, etc.) lives here rather than on
+/// the 2MB CIRCUITPY volume.
+pub(crate) fn app_data_dir(app: &AppHandle) -> Result<PathBuf, ConfigError> {
+    let dir = app.path().app_data_dir().map_err(|e| ConfigError {
+        message: format!("Could not resolve app data directory: {}", e),
+        details: None,
+        code: None,
+    })?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
 /// Validate that a path is on a recognized MIDI Captain device volume.
 /// Prevents path traversal attacks by ensuring paths are within expected directories.
+/// Returns the path resolved by `sanitize::resolve`; commands work on that
+/// rather than the argument as given.
+///
+/// Network shares and cloud-sync folders are refused outright.
 ///
 /// Accepts:
 /// 1. Volumes with a known name (CIRCUITPY or MIDICAPTAIN), or
@@ -102,24 +286,29 @@ impl From<serde_json::Error> for ConfigError {
 ///    `usb_drive_name` matches the actual volume name (case-insensitive).
 ///    This limits the surface: an arbitrary volume won't pass validation
 ///    just because someone placed a config.json on it.
-fn validate_device_path(path: &str) -> Result<(), ConfigError> {
-    let path = Path::new(path);
+pub(crate) fn validate_device_path(path: &str) -> Result<PathBuf, ConfigError> {
+    // Refuse control characters, normalize, resolve any .. or symlinks
+    let canonical = crate::sanitize::resolve(path)?;
 
-    // Canonicalize to resolve any .. or symlinks
-    let canonical = path.canonicalize().map_err(|e| ConfigError {
-        message: format!("Input watch path is neither a file nor a directory: {}", e),
-        details: None,
-    })?;
+    // A network share or synced folder can carry the device's name but is never the device
+    if let Some(reason) = crate::storage::non_device_location(&canonical) {
+        return Err(ConfigError {
+            message: reason,
+            details: None,
+            code: None,
+        });
+    }
 
     // Check if the path is on a valid device volume
     let volume_name = get_path_volume_name(&canonical).ok_or_else(|| ConfigError {
         message: "Could not determine volume name for path".to_string(),
         details: None,
+        code: None,
     })?;
 
     // Accept well-known volume names
-    if DEVICE_VOLUMES.iter().any(|v| volume_name.eq_ignore_ascii_case(v)) {
-        return Ok(());
+    if crate::device::is_known_volume_name(&volume_name) {
+        return Ok(canonical);
     }
 
     // Accept volumes that contain a valid MIDI Captain config.json.
@@ -131,12 +320,12 @@ fn validate_device_path(path: &str) -> Result<(), ConfigError> {
         let config_path = volume_path.join("config.json");
         if crate::device::is_midi_captain_config(&config_path) {
             match crate::device::parse_midi_captain_config(&config_path) {
-                Some(declared_name) if declared_name.eq_ignore_ascii_case(&volume_name) => {
-                    return Ok(());
+                Some(declared_name) if crate::sanitize::same_name(&declared_name, &volume_name) => {
+                    return Ok(canonical);
                 }
                 None => {
                     // No custom name declared — accept any valid MIDI Captain volume
-                    return Ok(());
+                    return Ok(canonical);
                 }
                 _ => {} // declared name doesn't match this volume
             }
@@ -149,6 +338,7 @@ fn validate_device_path(path: &str) -> Result<(), ConfigError> {
             volume_name
         ),
         details: None,
+        code: None,
     })
 }
 
@@ -176,14 +366,14 @@ fn is_volume_mounted(volume_path: &Path) -> bool {
 /// e.g., /Volumes/CIRCUITPY from /Volumes/CIRCUITPY/config.json on macOS
 /// or C:\ from C:\config.json on Windows
 #[cfg(target_os = "windows")]
-fn get_volume_path(path: &Path) -> Option<PathBuf> {
+pub(crate) fn get_volume_path(path: &Path) -> Option<PathBuf> {
     // On Windows, get the drive root (e.g., C:\)
     let mut components = path.components();
     components.next().map(|c| PathBuf::from(c.as_os_str()))
 }
 
 #[cfg(not(target_os = "windows"))]
-fn get_volume_path(path: &Path) -> Option<PathBuf> {
+pub(crate) fn get_volume_path(path: &Path) -> Option<PathBuf> {
     // On Unix, find the mount point under /Volumes/, /media/, or /run/media/
     path.ancestors()
         .find(|p| {
@@ -200,12 +390,13 @@ fn get_volume_path(path: &Path) -> Option<PathBuf> {
 }
 
 /// Verify the device is still mounted before writing
-fn verify_device_connected(path: &Path) -> Result<(), ConfigError> {
+pub(crate) fn verify_device_connected(path: &Path) -> Result<(), ConfigError> {
     if let Some(volume_path) = get_volume_path(path) {
         if !is_volume_mounted(&volume_path) {
             return Err(ConfigError {
                 message: "Device was disconnected".to_string(),
                 details: None,
+                code: None,
             });
         }
     }
@@ -217,98 +408,958 @@ fn verify_device_connected(path: &Path) -> Result<(), ConfigError> {
 /// `fs::write` closes the file without an explicit fsync, leaving data in the
 /// OS page cache. On a USB-connected FAT32 device (CircuitPython), a power
 /// cycle immediately after save can race the flush and the device boots with
-/// stale data. Keeping the write handle open for `sync_all` before drop
-/// ensures the data reaches the device's flash.
-fn write_sync(path: &Path, data: &[u8]) -> Result<(), std::io::Error> {
-    let mut file = OpenOptions::new().write(true).create(true).truncate(true).open(path)?;
-    file.write_all(data)?;
-    file.sync_all()?;
+/// stale data. Writing in place has a second problem: a cable pulled
+/// mid-write leaves a truncated file. So the data goes to `<name>.tmp` next
+/// to the target, that handle is flushed, and it is renamed over the
+/// original, which is either wholly old or wholly new. The parent directory
+/// is flushed last: FAT keeps a file's size and first cluster in its
+/// directory entry, so a flushed file with an unflushed entry can still come
+/// back empty or stale after eject.
+pub(crate) fn write_sync(path: &Path, data: &[u8]) -> Result<(), std::io::Error> {
+    let tmp = temp_path(path);
+    let result = (|| {
+        let mut file = OpenOptions::new().write(true).create(true).truncate(true).open(&tmp)?;
+        file.write_all(data)?;
+        full_sync(&file)?;
+        drop(file);
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result?;
+    sync_parent_dir(path)
+}
+
+/// Temporary file a write to `path` is staged in, on the same volume
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Flush a file to the device. On macOS `fsync` only reaches the drive's
+/// own cache; `F_FULLFSYNC` asks the drive to write it out.
+#[cfg(target_os = "macos")]
+fn full_sync(file: &fs::File) -> Result<(), std::io::Error> {
+    use std::os::unix::io::AsRawFd;
+    // SAFETY: the descriptor is owned by `file`, which outlives the call
+    if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_FULLFSYNC) } == -1 {
+        // Not every filesystem supports it
+        return file.sync_all();
+    }
     Ok(())
 }
 
-/// Read config from a file path
+#[cfg(not(target_os = "macos"))]
+fn full_sync(file: &fs::File) -> Result<(), std::io::Error> {
+    file.sync_all()
+}
+
+/// Flush the directory entry of `path`
+#[cfg(unix)]
+pub(crate) fn sync_parent_dir(path: &Path) -> Result<(), std::io::Error> {
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    fs::File::open(parent)?.sync_all()
+}
+
+/// Windows flushes the directory entry with the file
+#[cfg(not(unix))]
+pub(crate) fn sync_parent_dir(_path: &Path) -> Result<(), std::io::Error> {
+    Ok(())
+}
+
+/// `ConfigError::code` of a write refused because the file changed on disk
+/// since it was read
+pub const CONFIG_CONFLICT: &str = "config_conflict";
+
+/// Token for a config file's contents. Reads return it; a write given it
+/// fails with `CONFIG_CONFLICT` if the file no longer matches, so an edit
+/// saved from a second machine isn't silently overwritten.
+pub fn content_token(bytes: &[u8]) -> String {
+    sha256_hex(bytes)
+}
+
+/// Fail with `CONFIG_CONFLICT` unless the config at `path` still has the
+/// contents `expected` was issued for. No token, no check.
+fn check_unchanged(fs: &dyn FileSystem, path: &Path, expected: Option<&str>) -> Result<(), ConfigError> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    let current = fs.read(path).ok().map(|bytes| content_token(&bytes));
+    if current.as_deref() == Some(expected) {
+        return Ok(());
+    }
+    Err(ConfigError {
+        message: "The config changed on the device since it was loaded. Reload it, or save again to overwrite the other changes.".to_string(),
+        details: Some(vec![path.display().to_string()]),
+        code: Some(CONFIG_CONFLICT),
+    })
+}
+
+/// Read and decode a config from a device path or, read-only, from inside a
+/// snapshot zip (`snapshot.zip!/config.json`), with its `content_token`.
+/// Device paths are validated.
+fn read_config_source(path: &str) -> Result<(serde_json::Value, String), ConfigError> {
+    if crate::archive::split_archive_path(path).is_some() {
+        let bytes = crate::archive::read_path(path)?;
+        return Ok((ConfigFormat::detect(&bytes).decode(&bytes)?, content_token(&bytes)));
+    }
+    crate::config_cache::read_with_token(&validate_device_path(path)?)
+}
+
+/// A config read from disk, upgraded to the current schema
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MigratedConfig {
+    pub config: MidiCaptainConfig,
+    pub migration: crate::migrations::MigrationReport,
+    /// Pass back to `write_config` to detect conflicting edits
+    pub token: String,
+}
+
+/// A config with the `content_token` of the file it was read from
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReadConfig {
+    pub config: MidiCaptainConfig,
+    pub token: String,
+}
+
+/// A config's raw JSON with its `content_token`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RawConfig {
+    pub json: String,
+    pub token: String,
+}
+
+/// Read a config, upgrading it from an older schema version, and report
+/// which migrations were applied. Nothing is written; the upgrade is saved
+/// with the next write.
 #[command]
-pub fn read_config(path: String) -> Result<MidiCaptainConfig, ConfigError> {
-    validate_device_path(&path)?;
-    let contents = fs::read_to_string(&path)?;
-    let config: MidiCaptainConfig = serde_json::from_str(&contents)?;
-    Ok(config)
+pub fn read_config_migrated(path: String) -> Result<MigratedConfig, ConfigError> {
+    let (mut value, token) = read_config_source(&path)?;
+    let migration = crate::migrations::migrate(&mut value)?;
+    Ok(MigratedConfig {
+        config: serde_json::from_value(value)?,
+        migration,
+        token,
+    })
 }
 
-/// Read raw JSON from a file (for text editor)
+/// Read config from a file path, or from inside a snapshot zip
 #[command]
-pub fn read_config_raw(path: String) -> Result<String, ConfigError> {
-    validate_device_path(&path)?;
-    let contents = fs::read_to_string(&path)?;
-    // Pretty-print the JSON
-    let value: serde_json::Value = serde_json::from_str(&contents)?;
-    let pretty = serde_json::to_string_pretty(&value)?;
-    Ok(pretty)
+pub fn read_config(path: String) -> Result<ReadConfig, ConfigError> {
+    let MigratedConfig { config, token, .. } = read_config_migrated(path)?;
+    Ok(ReadConfig { config, token })
 }
 
-/// Write config to a file path
+/// Read raw JSON from a file (for text editor). Binary formats are shown as
+/// JSON. Shown as stored: no migrations are applied.
 #[command]
-pub fn write_config(path: String, config: MidiCaptainConfig) -> Result<(), ConfigError> {
-    validate_device_path(&path)?;
-    
-    let path_obj = Path::new(&path);
-    
-    // Verify volume is still mounted
-    verify_device_connected(path_obj)?;
-    
-    // Validate before writing
-    if let Err(errors) = config.validate() {
+pub fn read_config_raw(path: String) -> Result<RawConfig, ConfigError> {
+    // Pretty-print the JSON
+    let (value, token) = read_config_source(&path)?;
+    let json = serde_json::to_string_pretty(&value)?;
+    Ok(RawConfig { json, token })
+}
+
+/// True if the config on disk at `path` is marked locked (`_editor.locked`).
+/// Parsed loosely so a locked but otherwise invalid config still counts.
+pub(crate) fn is_locked_on_disk(fs: &dyn FileSystem, path: &Path) -> bool {
+    read_config_file::<serde_json::Value>(fs, path)
+        .ok()
+        .and_then(|v| v.pointer("/_editor/locked").and_then(|l| l.as_bool()))
+        .unwrap_or(false)
+}
+
+/// `ConfigError::code` of a write refused because the config is locked
+pub const CONFIG_LOCKED: &str = "config_locked";
+
+/// Run `write` on the config at `path`. Overwriting a locked config takes
+/// `force` and then a confirmation token (see confirm.rs); without `force`
+/// it fails with `CONFIG_LOCKED`. This is the only lock check a write needs.
+pub(crate) fn guard_locked<T>(
+    fs: &dyn FileSystem,
+    path: &Path,
+    force: Option<bool>,
+    confirmation: Option<&str>,
+    write: impl FnOnce() -> Result<T, ConfigError>,
+) -> Result<Confirmable<T>, ConfigError> {
+    if !is_locked_on_disk(fs, path) {
+        return write().map(Confirmable::done);
+    }
+    if !force.unwrap_or(false) {
         return Err(ConfigError {
-            message: "Validation failed".to_string(),
-            details: Some(errors),
+            message: "Config is locked. Unlock it or force the write to overwrite it.".to_string(),
+            details: Some(vec![path.display().to_string()]),
+            code: Some(CONFIG_LOCKED),
         });
     }
+    let target = path.display().to_string();
+    confirm(
+        &["overwrite_locked", &target],
+        || format!("Overwrite the locked config at {}", target),
+        confirmation,
+        write,
+    )
+}
 
-    let json = serde_json::to_string_pretty(&config)?;
-    write_sync(path_obj, json.as_bytes())?;
+/// How strictly a config is checked before it is validated or written.
+/// The editor keeps the chosen level for the session only; it is never saved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strictness {
+    /// Schema errors, unknown fields and unsuppressed lint findings all fail
+    Strict,
+    /// Schema errors fail (the default)
+    #[default]
+    Normal,
+    /// Nothing fails; problems go in the write log and the written config is flagged
+    /// with `_editor.permissive_write`. For firmware development only.
+    Permissive,
+}
 
-    Ok(())
+fn validation_failed(errors: Vec<String>) -> ConfigError {
+    ConfigError {
+        message: "Validation failed".to_string(),
+        details: Some(errors),
+        code: None,
+    }
+}
+
+/// Check raw config JSON at the given strictness.
+///
+/// Returns the parsed config (when it matches the schema) and the problems
+/// that were let through, which is only ever non-empty in permissive mode.
+pub fn check_config(value: &serde_json::Value, strictness: Strictness) -> Result<(Option<MidiCaptainConfig>, Vec<String>), ConfigError> {
+    let config = match serde_json::from_value::<MidiCaptainConfig>(value.clone()) {
+        Ok(config) => config,
+        Err(e) if strictness == Strictness::Permissive => {
+            return Ok((None, vec![format!("Does not match the config schema: {}", e)]));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut problems = crate::config::issue_messages(config.validate().err().unwrap_or_default());
+    if strictness == Strictness::Strict {
+        let normalized = serde_json::to_value(&config)?;
+        problems.extend(
+            crate::diff::diff_values(value, &normalized)
+                .into_iter()
+                .filter(|c| c.kind == crate::diff::ChangeKind::Removed)
+                .map(|c| format!("Unknown field {}", c.path)),
+        );
+        problems.extend(
+            crate::lint::validation_report(&config)
+                .findings
+                .into_iter()
+                .filter(|f| !f.suppressed && !crate::lint::ADVISORY_RULES.contains(&f.rule.as_str()))
+                .map(|f| f.message),
+        );
+    }
+
+    if problems.is_empty() || strictness == Strictness::Permissive {
+        Ok((Some(config), problems))
+    } else {
+        Err(validation_failed(problems))
+    }
+}
+
+/// Serialize a checked config for writing. Permissive writes that let
+/// problems through keep the JSON as given (so experimental fields survive)
+/// and are flagged with `_editor.permissive_write`; any other write clears
+/// the flag.
+fn prepare_write(
+    value: serde_json::Value,
+    config: Option<MidiCaptainConfig>,
+    problems: &[String],
+) -> Result<serde_json::Value, ConfigError> {
+    let value = match config {
+        Some(mut config) if problems.is_empty() => {
+            if let Some(ref mut editor) = config.editor {
+                editor.permissive_write = None;
+            }
+            config.version = Some(crate::config::CONFIG_SCHEMA_VERSION);
+            serde_json::to_value(&config)?
+        }
+        _ => {
+            let mut value = value;
+            let root = value.as_object_mut().ok_or_else(|| ConfigError {
+                message: "Config is not a JSON object".to_string(),
+                details: None,
+                code: None,
+            })?;
+            let editor = root
+                .entry("_editor")
+                .or_insert_with(|| serde_json::Value::Object(Default::default()));
+            if let Some(editor) = editor.as_object_mut() {
+                editor.insert("permissive_write".to_string(), crate::firmware::unix_now().into());
+            }
+            value
+        }
+    };
+    Ok(value)
+}
+
+/// Lock check, validation and verified write of a config to a device path
+/// that has already been validated and found mounted. Returns the new
+/// `content_token`.
+pub(crate) fn write_config_value(
+    fs: &dyn FileSystem,
+    backups: Option<&Path>,
+    path: &Path,
+    value: serde_json::Value,
+    force: Option<bool>,
+    confirmation: Option<&str>,
+    strictness: Option<Strictness>,
+) -> Result<Confirmable<String>, ConfigError> {
+    let (config, problems) = check_config(&value, strictness.unwrap_or_default())?;
+    let value = prepare_write(value, config, &problems)?;
+    crate::guest::check_writable()?;
+    guard_locked(fs, path, force, confirmation, || write_verified_logging(fs, backups, path, &value, &problems))
 }
 
-/// Write raw JSON to a file (from text editor)
+/// Fail early if the device can't take `value` (full or read-only), rather
+/// than with an IO error halfway through the write
+fn check_space_for(path: &Path, value: &serde_json::Value) -> Result<(), ConfigError> {
+    let size = serde_json::to_vec_pretty(value)?.len() as u64;
+    crate::health::check_before_write(path, size)
+}
+
+/// Write config to a file path. Overwriting a locked config takes `force`
+/// and a `confirmation` token. With `expected_token` (from the read), the
+/// write fails with `CONFIG_CONFLICT` if the file has changed since. Returns
+/// the token for the written config.
 #[command]
-pub fn write_config_raw(path: String, json: String) -> Result<(), ConfigError> {
-    validate_device_path(&path)?;
-    
-    let path_obj = Path::new(&path);
-    
+pub fn write_config(
+    path: String,
+    config: MidiCaptainConfig,
+    force: Option<bool>,
+    confirmation: Option<String>,
+    strictness: Option<Strictness>,
+    expected_token: Option<String>,
+) -> Result<Confirmable<String>, ConfigError> {
+    let path = validate_device_path(&path)?;
+
     // Verify volume is still mounted
-    verify_device_connected(path_obj)?;
-    
-    // Validate JSON is parseable
-    let config: MidiCaptainConfig = serde_json::from_str(&json)?;
+    verify_device_connected(&path)?;
+    check_unchanged(&RealFs, &path, expected_token.as_deref())?;
 
-    // Validate config
-    if let Err(errors) = config.validate() {
-        return Err(ConfigError {
-            message: "Validation failed".to_string(),
-            details: Some(errors),
-        });
+    let value = serde_json::to_value(&config)?;
+    check_space_for(&path, &value)?;
+    write_config_value(
+        &RealFs,
+        installed_backups().as_deref(),
+        &path,
+        value,
+        force,
+        confirmation.as_deref(),
+        strictness,
+    )
+}
+
+/// Write raw JSON to a file (from text editor). Takes and returns tokens
+/// like `write_config`.
+#[command]
+pub fn write_config_raw(
+    path: String,
+    json: String,
+    force: Option<bool>,
+    confirmation: Option<String>,
+    strictness: Option<Strictness>,
+    expected_token: Option<String>,
+) -> Result<Confirmable<String>, ConfigError> {
+    let path = validate_device_path(&path)?;
+
+    // Verify volume is still mounted
+    verify_device_connected(&path)?;
+    check_unchanged(&RealFs, &path, expected_token.as_deref())?;
+
+    // Validate JSON is parseable, then validate config and write it in the
+    // file's format (JSON is pretty-printed)
+    let value: serde_json::Value = serde_json::from_str(&json)?;
+    check_space_for(&path, &value)?;
+    write_config_value(
+        &RealFs,
+        installed_backups().as_deref(),
+        &path,
+        value,
+        force,
+        confirmation.as_deref(),
+        strictness,
+    )
+}
+
+/// Outcome of `push_config_to_all` on one device
+#[derive(Debug, serde::Serialize)]
+pub struct PushResult {
+    pub name: String,
+    /// Volume path of the device
+    pub path: PathBuf,
+    /// Token of the written config, or None if the write failed
+    pub token: Option<String>,
+    pub error: Option<ConfigError>,
+}
+
+/// Write `config` to the device holding `path`, unless the config there is
+/// for another model
+fn push_to(path: &Path, config: &MidiCaptainConfig) -> Result<String, ConfigError> {
+    let path = validate_device_path(&path.to_string_lossy())?;
+    verify_device_connected(&path)?;
+    if RealFs.is_file(&path) {
+        let current: serde_json::Value = read_config_file(&RealFs, &path)?;
+        if let Ok(device) = serde_json::from_value::<DeviceType>(current["device"].clone()) {
+            if device != config.device {
+                return Err(ConfigError {
+                    message: format!(
+                        "Not written: the device is a {} and the config is for a {}",
+                        device.display_name(),
+                        config.device.display_name()
+                    ),
+                    details: None,
+                    code: None,
+                });
+            }
+        }
     }
+    let value = serde_json::to_value(config)?;
+    check_space_for(&path, &value)?;
+    write_config_value(&RealFs, installed_backups().as_deref(), &path, value, None, None, None)?.into_result()
+}
 
-    // Pretty-print and write
-    let pretty = serde_json::to_string_pretty(&config)?;
-    write_sync(path_obj, pretty.as_bytes())?;
+/// Write the same config to every connected device, to keep several
+/// devices in sync. Each device is written (and backed up) on its own, so
+/// one failing (locked, full, another model) doesn't stop the rest.
+#[command]
+pub fn push_config_to_all(config: MidiCaptainConfig) -> Vec<PushResult> {
+    crate::device::connected_devices()
+        .into_iter()
+        .map(|device| {
+            let result = push_to(&device.config_path, &config);
+            PushResult {
+                name: device.name,
+                path: device.path,
+                token: result.as_ref().ok().cloned(),
+                error: result.err(),
+            }
+        })
+        .collect()
+}
 
-    Ok(())
+/// Set `_editor.locked` on the config at `path`, leaving the rest of the
+/// file as-is. Unlocking takes a confirmation token like overwriting a locked
+/// config does, since it makes the next overwrite a plain write. Returns the
+/// token of the written config.
+fn set_locked_in(
+    fs: &dyn FileSystem,
+    backups: Option<&Path>,
+    path: &Path,
+    locked: bool,
+    confirmation: Option<&str>,
+) -> Result<Confirmable<String>, ConfigError> {
+    let mut value: serde_json::Value = read_config_file(fs, path)?;
+    let root = value.as_object_mut().ok_or_else(|| ConfigError {
+        message: "Config is not a JSON object".to_string(),
+        details: None,
+        code: None,
+    })?;
+    let editor = root
+        .entry("_editor")
+        .or_insert_with(|| serde_json::Value::Object(Default::default()));
+    if let Some(editor) = editor.as_object_mut() {
+        editor.insert("locked".to_string(), serde_json::Value::Bool(locked));
+    }
+
+    crate::guest::check_writable()?;
+    let write = || write_verified_to(fs, backups, path, &value);
+    if locked || !is_locked_on_disk(fs, path) {
+        return write().map(Confirmable::done);
+    }
+    let target = path.display().to_string();
+    confirm(
+        &["unlock_config", &target],
+        || format!("Unlock the config at {}", target),
+        confirmation,
+        write,
+    )
+}
+
+/// Lock or unlock the config at `path`. Only `_editor.locked` is changed;
+/// the rest of the file is preserved as-is. Unlocking takes a
+/// `confirmation` token. Returns the token of the written config.
+#[command]
+pub fn set_config_locked(
+    path: String,
+    locked: bool,
+    confirmation: Option<String>,
+) -> Result<Confirmable<String>, ConfigError> {
+    let path = validate_device_path(&path)?;
+    verify_device_connected(&path)?;
+    set_locked_in(&RealFs, installed_backups().as_deref(), &path, locked, confirmation.as_deref())
+}
+
+/// Validate JSON without writing. Returns the problems that were let through
+/// (only ever non-empty in permissive mode).
+#[command]
+pub fn validate_config(json: String, strictness: Option<Strictness>) -> Result<Vec<String>, ConfigError> {
+    let value: serde_json::Value = serde_json::from_str(&json)?;
+    let (_, problems) = check_config(&value, strictness.unwrap_or_default())?;
+    Ok(problems)
 }
 
-/// Validate JSON without writing
+/// A factory-style config for a device type, for initializing a device that
+/// has no config.json. Nothing is written.
 #[command]
-pub fn validate_config(json: String) -> Result<(), ConfigError> {
-    let config: MidiCaptainConfig = serde_json::from_str(&json)?;
+pub fn generate_default_config(device: DeviceType) -> MidiCaptainConfig {
+    crate::config::generate_default_config(device)
+}
 
-    if let Err(errors) = config.validate() {
+/// `config` with every label transliterated to characters the display can
+/// draw (accents stripped, anything else dropped). Nothing is written.
+#[command]
+pub fn sanitize_labels(mut config: MidiCaptainConfig) -> MidiCaptainConfig {
+    config.sanitize_labels();
+    config
+}
+
+/// Directory under the app-data directory holding config backups
+const BACKUPS_DIR: &str = "backups";
+
+/// Backups kept per device; older ones are deleted
+const MAX_BACKUPS_PER_DEVICE: usize = 50;
+
+/// A copy of a device config taken just before it was overwritten
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ConfigBackup {
+    /// `<device>/<backup file>`, passed back to `restore_backup`
+    pub id: String,
+    /// Volume name of the device the config came from
+    pub device: String,
+    /// Name of the config file that was backed up
+    pub file_name: String,
+    /// Unix timestamp (seconds)
+    pub created_at: u64,
+    /// Size of the compressed backup
+    pub size: u64,
+}
+
+pub fn backups_root(data_dir: &Path) -> PathBuf {
+    data_dir.join(BACKUPS_DIR)
+}
+
+/// Backup store of the installed app-data directory (none in tests)
+fn installed_backups() -> Option<PathBuf> {
+    crate::registry::data_dir().map(backups_root)
+}
+
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Backup files are named `<unix millis>-<config file name>.zst`; returns
+/// the backup and its millisecond timestamp
+fn parse_backup(device: &str, name: &str, size: u64) -> Option<(u64, ConfigBackup)> {
+    let (millis, file_name) = name.strip_suffix(".zst")?.split_once('-')?;
+    let millis: u64 = millis.parse().ok()?;
+    Some((millis, ConfigBackup {
+        id: format!("{}/{}", device, name),
+        device: device.to_string(),
+        file_name: file_name.to_string(),
+        created_at: millis / 1000,
+        size,
+    }))
+}
+
+/// Backups for one device, newest first
+pub(crate) fn device_backups(fs: &dyn FileSystem, root: &Path, device: &str) -> Vec<(u64, PathBuf, ConfigBackup)> {
+    let dir = root.join(device);
+    let mut backups: Vec<_> = fs
+        .list_files(&dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, size)| parse_backup(device, &name, size).map(|(millis, backup)| (millis, dir.join(&name), backup)))
+        .collect();
+    backups.sort_by_key(|(millis, _, _)| std::cmp::Reverse(*millis));
+    backups
+}
+
+/// Copy the config at `path` into `root/<device>/`, keeping the newest
+/// `MAX_BACKUPS_PER_DEVICE`. Nothing is backed up when there is no config yet.
+pub fn back_up_config(fs: &dyn FileSystem, root: &Path, device: &str, path: &Path) -> std::io::Result<Option<PathBuf>> {
+    if !fs.is_file(path) {
+        return Ok(None);
+    }
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("config.json");
+    let dir = root.join(device);
+    fs.create_dir_all(&dir)?;
+
+    // Writes can land within the same millisecond; never overwrite a backup
+    let mut millis = unix_millis();
+    let mut backup = dir.join(format!("{}-{}.zst", millis, file_name));
+    while fs.is_file(&backup) {
+        millis += 1;
+        backup = dir.join(format!("{}-{}.zst", millis, file_name));
+    }
+    fs.write(&backup, &crate::archive::compress(&fs.read(path)?)?)?;
+
+    for (_, old, _) in device_backups(fs, root, device).into_iter().skip(MAX_BACKUPS_PER_DEVICE) {
+        fs.remove_file(&old)?;
+    }
+    Ok(Some(backup))
+}
+
+/// Back up the config about to be overwritten into the store at `root`. A
+/// failed backup stops the write.
+fn back_up_before_write(fs: &dyn FileSystem, root: &Path, path: &Path) -> Result<(), ConfigError> {
+    let device = crate::registry::device_key(path).unwrap_or_else(|| "unknown".to_string());
+    back_up_config(fs, root, &device, path).map_err(|e| ConfigError {
+        message: format!("Could not back up the current config, so nothing was written: {}", e),
+        details: Some(vec![path.display().to_string()]),
+        code: None,
+    })?;
+    // A new write starts a new branch of history; what was undone can't be redone
+    crate::history::clear_redo(fs, root, &device, path)?;
+    Ok(())
+}
+
+/// All backups under `root` (or one device's), newest first
+pub fn list_backups_in(fs: &dyn FileSystem, root: &Path, device: Option<&str>) -> Vec<ConfigBackup> {
+    let devices: Vec<String> = match device {
+        Some(device) => vec![device.to_string()],
+        None => fs.list_dirs(root).unwrap_or_default(),
+    };
+    let mut backups: Vec<_> = devices.iter().flat_map(|d| device_backups(fs, root, d)).collect();
+    backups.sort_by_key(|(millis, _, _)| std::cmp::Reverse(*millis));
+    backups.into_iter().map(|(_, _, backup)| backup).collect()
+}
+
+/// Path of the backup with the given id, refusing anything outside `root`
+fn backup_path(fs: &dyn FileSystem, root: &Path, id: &str) -> Result<PathBuf, ConfigError> {
+    let invalid = || ConfigError {
+        message: format!("Invalid backup id '{}'", id),
+        details: None,
+        code: None,
+    };
+    let (device, file) = id.split_once('/').ok_or_else(invalid)?;
+    crate::presets::check_name(device).map_err(|_| invalid())?;
+    crate::presets::check_name(file).map_err(|_| invalid())?;
+    let path = root.join(device).join(file);
+    if !fs.is_file(&path) {
         return Err(ConfigError {
-            message: "Validation failed".to_string(),
-            details: Some(errors),
+            message: format!("Backup '{}' not found", id),
+            details: None,
+            code: None,
         });
     }
+    Ok(path)
+}
 
-    Ok(())
+/// List config backups, newest first; only one device's when `device` is given
+#[command]
+pub fn list_backups(app: AppHandle, device: Option<String>) -> Result<Vec<ConfigBackup>, ConfigError> {
+    Ok(list_backups_in(&RealFs, &backups_root(&app_data_dir(&app)?), device.as_deref()))
+}
+
+/// Write a backed-up config to `path`. Goes through the normal write path,
+/// so the config being replaced is itself backed up first. Replacing a
+/// locked config takes `force` and a `confirmation` token.
+#[command]
+pub fn restore_backup(
+    app: AppHandle,
+    id: String,
+    path: String,
+    force: Option<bool>,
+    confirmation: Option<String>,
+) -> Result<Confirmable<()>, ConfigError> {
+    let path = validate_device_path(&path)?;
+    verify_device_connected(&path)?;
+    restore_backup_from(
+        &RealFs,
+        &backups_root(&app_data_dir(&app)?),
+        &id,
+        &path,
+        force,
+        confirmation.as_deref(),
+    )
+}
+
+fn restore_backup_from(
+    fs: &dyn FileSystem,
+    root: &Path,
+    id: &str,
+    path: &Path,
+    force: Option<bool>,
+    confirmation: Option<&str>,
+) -> Result<Confirmable<()>, ConfigError> {
+    let backup = backup_path(fs, root, id)?;
+    let bytes = crate::archive::decompress(&fs.read(&backup)?)?;
+    let value: serde_json::Value = ConfigFormat::detect(&bytes).decode(&bytes)?;
+    crate::guest::check_writable()?;
+    guard_locked(fs, path, force, confirmation, || write_verified_to(fs, Some(root), path, &value).map(drop))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::InMemoryFs;
+
+    #[test]
+    fn test_locked_config_requires_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{"buttons": [], "_editor": {"locked": true}}"#).unwrap();
+
+        let write = || Ok(());
+        assert_eq!(guard_locked(&RealFs, &path, None, None, write).unwrap_err().code, Some(CONFIG_LOCKED));
+        assert!(guard_locked(&RealFs, &path, Some(false), None, write).is_err());
+        assert!(guard_locked(&RealFs, &path, Some(true), None, write).is_ok());
+    }
+
+    #[test]
+    fn test_unlocked_or_missing_config_is_writable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        assert!(matches!(guard_locked(&RealFs, &path, None, None, || Ok(())), Ok(Confirmable::Done { .. })));
+
+        fs::write(&path, r#"{"buttons": [], "_editor": {"locked": false}}"#).unwrap();
+        assert!(matches!(guard_locked(&RealFs, &path, None, None, || Ok(())), Ok(Confirmable::Done { .. })));
+    }
+
+    #[test]
+    fn test_unlocking_needs_confirmation() {
+        let path = Path::new("/Volumes/CIRCUITPY/config.json");
+        let root = Path::new("/backups");
+        let fs = InMemoryFs::new().with_file(path, r#"{"buttons": [], "device": "mini6"}"#);
+
+        assert!(matches!(set_locked_in(&fs, Some(root), path, true, None).unwrap(), Confirmable::Done { .. }));
+        assert!(is_locked_on_disk(&fs, path));
+
+        let token = match set_locked_in(&fs, Some(root), path, false, None).unwrap() {
+            Confirmable::ConfirmationRequired { token, .. } => token,
+            other => panic!("unlocked without confirmation: {:?}", other),
+        };
+        assert!(is_locked_on_disk(&fs, path));
+        set_locked_in(&fs, Some(root), path, false, Some(&token)).unwrap();
+        assert!(!is_locked_on_disk(&fs, path));
+        let device = crate::registry::device_key(path).unwrap_or_else(|| "unknown".to_string());
+        assert!(!device_backups(&fs, root, &device).is_empty());
+    }
+
+    fn six_buttons(extra: &str) -> serde_json::Value {
+        serde_json::from_str(&format!(
+            r#"{{"device": "mini6", "buttons": [
+                {{"label": "1", "cc": 20, "color": "red"}}, {{"label": "2", "cc": 21, "color": "red"}},
+                {{"label": "3", "cc": 22, "color": "red"}}, {{"label": "4", "cc": 23, "color": "red"}},
+                {{"label": "5", "cc": 24, "color": "red"}}, {{"label": "6", "cc": 25, "color": "red"}}
+            ]{}}}"#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_strictness_levels() {
+        let valid = six_buttons("");
+        for strictness in [Strictness::Strict, Strictness::Normal, Strictness::Permissive] {
+            assert!(check_config(&valid, strictness).unwrap().1.is_empty());
+        }
+
+        // Unknown fields only fail in strict mode
+        let experimental = six_buttons(r#", "tap_tempo": {"cc": 64}"#);
+        assert!(check_config(&experimental, Strictness::Strict).is_err());
+        assert!(check_config(&experimental, Strictness::Normal).is_ok());
+
+        // Schema errors are let through, and reported, only in permissive mode
+        let invalid: serde_json::Value = serde_json::from_str(r#"{"device": "mini6", "buttons": []}"#).unwrap();
+        assert!(check_config(&invalid, Strictness::Normal).is_err());
+        let (config, problems) = check_config(&invalid, Strictness::Permissive).unwrap();
+        assert!(config.is_some());
+        assert_eq!(problems.len(), 1);
+    }
+
+    #[test]
+    fn test_write_rejects_out_of_range_button_channel() {
+        let mut value = six_buttons("");
+        value["buttons"][0]["channel"] = serde_json::json!(9);
+        assert!(check_config(&value, Strictness::Normal).is_ok());
+
+        value["buttons"][0]["channel"] = serde_json::json!(16);
+        let err = check_config(&value, Strictness::Normal).unwrap_err();
+        assert_eq!(err.details.unwrap(), ["Button 1 channel 17 is invalid (must be 1-16)"]);
+    }
+
+    #[test]
+    fn test_permissive_write_is_flagged_and_keeps_fields() {
+        let experimental = serde_json::json!({"device": "mini6", "buttons": [], "tap_tempo": {"cc": 64}});
+        let (config, problems) = check_config(&experimental, Strictness::Permissive).unwrap();
+        let written = prepare_write(experimental, config, &problems).unwrap();
+        assert!(written.pointer("/_editor/permissive_write").is_some());
+        assert_eq!(written["tap_tempo"]["cc"], 64);
+
+        // A clean write clears the flag
+        let mut flagged = six_buttons("");
+        flagged["_editor"] = serde_json::json!({"permissive_write": 1});
+        let (config, problems) = check_config(&flagged, Strictness::Normal).unwrap();
+        let written = prepare_write(flagged, config, &problems).unwrap();
+        assert!(written.pointer("/_editor/permissive_write").is_none());
+    }
+
+    #[test]
+    fn test_format_detection_and_extension() {
+        assert_eq!(ConfigFormat::detect(b"  {\"buttons\": []}"), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::detect(b"\xef\xbb\xbf{}"), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::detect(&[0x82, 0xa6]), ConfigFormat::MessagePack);
+        assert_eq!(ConfigFormat::detect(&[0xa2, 0x66]), ConfigFormat::Cbor);
+        assert_eq!(ConfigFormat::detect(&[0xd9, 0xd9, 0xf7, 0xa1]), ConfigFormat::Cbor);
+
+        assert_eq!(ConfigFormat::for_path(Path::new("/Volumes/CIRCUITPY/config.json")), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::for_path(Path::new("/Volumes/CIRCUITPY/config.msgpack")), ConfigFormat::MessagePack);
+        assert_eq!(ConfigFormat::for_path(Path::new("/Volumes/CIRCUITPY/config.CBOR")), ConfigFormat::Cbor);
+    }
+
+    #[test]
+    fn test_formats_roundtrip_and_autodetect() {
+        let config = six_buttons(r#", "global_channel": 3"#);
+        for format in [ConfigFormat::Json, ConfigFormat::MessagePack, ConfigFormat::Cbor] {
+            let bytes = format.encode(&config).unwrap();
+            assert_eq!(ConfigFormat::detect(&bytes), format);
+            let decoded: MidiCaptainConfig = ConfigFormat::detect(&bytes).decode(&bytes).unwrap();
+            assert_eq!(decoded.global_channel, Some(3));
+            assert_eq!(decoded.buttons.len(), 6);
+        }
+    }
+
+    #[test]
+    fn test_verify_write_levels() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let value = serde_json::json!({"buttons": []});
+        let written = write_config_file(&RealFs, &path, &value).unwrap();
+        for level in [VerifyLevel::None, VerifyLevel::Hash, VerifyLevel::Full] {
+            assert!(verify_write(&RealFs, &path, &written, &value, level).is_ok());
+        }
+
+        // The device kept something else (e.g. a truncated or stale file)
+        fs::write(&path, b"{\"buttons\": [], \"global_channel\": 1}").unwrap();
+        assert!(verify_write(&RealFs, &path, &written, &value, VerifyLevel::None).is_ok());
+        assert!(verify_write(&RealFs, &path, &written, &value, VerifyLevel::Hash).is_err());
+        assert!(verify_write(&RealFs, &path, &written, &value, VerifyLevel::Full).is_err());
+    }
+
+    #[test]
+    fn test_backups_are_kept_newest_first_and_pruned() {
+        let device = tempfile::tempdir().unwrap();
+        let store = tempfile::tempdir().unwrap();
+        let path = device.path().join("config.json");
+        assert_eq!(back_up_config(&RealFs, store.path(), "CIRCUITPY", &path).unwrap(), None);
+
+        for i in 0..(MAX_BACKUPS_PER_DEVICE + 2) {
+            fs::write(&path, format!(r#"{{"buttons": [], "global_channel": {}}}"#, i)).unwrap();
+            back_up_config(&RealFs, store.path(), "CIRCUITPY", &path).unwrap();
+        }
+        let backups = list_backups_in(&RealFs, store.path(), None);
+        assert_eq!(backups.len(), MAX_BACKUPS_PER_DEVICE);
+        assert_eq!(backups[0].device, "CIRCUITPY");
+        assert_eq!(backups[0].file_name, "config.json");
+
+        let newest = backup_path(&RealFs, store.path(), &backups[0].id).unwrap();
+        let bytes = crate::archive::decompress(&fs::read(newest).unwrap()).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(value["global_channel"], MAX_BACKUPS_PER_DEVICE as u64 + 1);
+    }
+
+    #[test]
+    fn test_backup_ids_stay_inside_the_store() {
+        let store = tempfile::tempdir().unwrap();
+        for id in ["../config.json", "CIRCUITPY/../../x", "CIRCUITPY", ".hidden/x"] {
+            assert!(backup_path(&RealFs, store.path(), id).unwrap_err().message.starts_with("Invalid backup id"));
+        }
+        assert!(backup_path(&RealFs, store.path(), "CIRCUITPY/1-config.json.zst").unwrap_err().message.contains("not found"));
+    }
+
+    #[test]
+    fn test_write_backs_up_previous_config_in_memory() {
+        let path = Path::new("/Volumes/CIRCUITPY/config.json");
+        let store = Path::new("/backups");
+        let fs = InMemoryFs::new().with_file(path, six_buttons(r#", "global_channel": 3"#).to_string());
+
+        write_config_value(&fs, Some(store), path, six_buttons(""), None, None, None).unwrap();
+        let written: MidiCaptainConfig = serde_json::from_slice(&fs.read(path).unwrap()).unwrap();
+        assert_eq!(written.global_channel, None);
+        assert_eq!(written.version, Some(crate::config::CONFIG_SCHEMA_VERSION));
+
+        let backups = list_backups_in(&fs, store, None);
+        assert_eq!(backups.len(), 1);
+        let bytes = crate::archive::decompress(&fs.read(&backup_path(&fs, store, &backups[0].id).unwrap()).unwrap()).unwrap();
+        let previous: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(previous["global_channel"], 3);
+    }
+
+    #[test]
+    fn test_locked_config_is_not_written_in_memory() {
+        let path = Path::new("/Volumes/CIRCUITPY/config.json");
+        let locked = r#"{"buttons": [], "_editor": {"locked": true}}"#;
+        let fs = InMemoryFs::new().with_file(path, locked);
+
+        assert!(write_config_value(&fs, None, path, six_buttons(""), None, None, None).is_err());
+        assert_eq!(fs.read(path).unwrap(), locked.as_bytes());
+
+        // Forcing asks for confirmation first and writes only with the token
+        let Confirmable::ConfirmationRequired { token, summary } =
+            write_config_value(&fs, None, path, six_buttons(""), Some(true), None, None).unwrap()
+        else {
+            panic!("forced write ran without confirmation");
+        };
+        assert!(summary.contains("locked"));
+        assert_eq!(fs.read(path).unwrap(), locked.as_bytes());
+        write_config_value(&fs, None, path, six_buttons(""), Some(true), Some(&token), None).unwrap();
+        assert_ne!(fs.read(path).unwrap(), locked.as_bytes());
+    }
+
+    #[test]
+    fn test_write_conflict_detected_in_memory() {
+        let path = Path::new("/Volumes/CIRCUITPY/config.json");
+        let original = six_buttons("").to_string();
+        let fs = InMemoryFs::new().with_file(path, &original);
+        let token = content_token(original.as_bytes());
+        check_unchanged(&fs, path, Some(&token)).unwrap();
+        check_unchanged(&fs, path, None).unwrap();
+
+        // Saved from elsewhere: the token read earlier no longer matches
+        let Confirmable::Done { result: written } =
+            write_config_value(&fs, None, path, six_buttons(r#", "global_channel": 3"#), None, None, None).unwrap()
+        else {
+            panic!("unlocked write asked for confirmation");
+        };
+        assert_eq!(written, content_token(&fs.read(path).unwrap()));
+        assert_eq!(check_unchanged(&fs, path, Some(&token)).unwrap_err().code, Some(CONFIG_CONFLICT));
+        check_unchanged(&fs, path, Some(&written)).unwrap();
+    }
+
+    #[test]
+    fn test_restore_backup_in_memory() {
+        let path = Path::new("/Volumes/CIRCUITPY/config.json");
+        let store = Path::new("/backups");
+        let fs = InMemoryFs::new().with_file(path, six_buttons(r#", "global_channel": 3"#).to_string());
+        write_config_value(&fs, Some(store), path, six_buttons(""), None, None, None).unwrap();
+
+        let id = list_backups_in(&fs, store, None)[0].id.clone();
+        restore_backup_from(&fs, store, &id, path, None, None).unwrap();
+        let restored: MidiCaptainConfig = serde_json::from_slice(&fs.read(path).unwrap()).unwrap();
+        assert_eq!(restored.global_channel, Some(3));
+        // The config the restore replaced was backed up too
+        assert_eq!(list_backups_in(&fs, store, None).len(), 2);
+    }
+
+    #[test]
+    fn test_write_sync_replaces_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        write_sync(&path, b"{\"buttons\": [1, 2, 3]}").unwrap();
+        write_sync(&path, b"{}").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"{}");
+        assert!(!dir.path().join("config.json.tmp").exists());
+    }
 }
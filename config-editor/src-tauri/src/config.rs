@@ -3,11 +3,12 @@
 //! Matches the JSON schema used by the CircuitPython firmware.
 
 use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
 
-/// Valid button colors
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Palette colors (COLORS in core/colors.py)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
-pub enum ButtonColor {
+pub enum NamedColor {
     Red,
     Green,
     Blue,
@@ -19,6 +20,93 @@ pub enum ButtonColor {
     White,
 }
 
+impl NamedColor {
+    pub const ALL: [NamedColor; 9] = [
+        NamedColor::Red,
+        NamedColor::Green,
+        NamedColor::Blue,
+        NamedColor::Yellow,
+        NamedColor::Cyan,
+        NamedColor::Magenta,
+        NamedColor::Orange,
+        NamedColor::Purple,
+        NamedColor::White,
+    ];
+
+    /// Name as written in config.json
+    pub fn name(self) -> &'static str {
+        match self {
+            NamedColor::Red => "red",
+            NamedColor::Green => "green",
+            NamedColor::Blue => "blue",
+            NamedColor::Yellow => "yellow",
+            NamedColor::Cyan => "cyan",
+            NamedColor::Magenta => "magenta",
+            NamedColor::Orange => "orange",
+            NamedColor::Purple => "purple",
+            NamedColor::White => "white",
+        }
+    }
+
+    /// LED value the firmware uses for this color
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            NamedColor::Red => (255, 0, 0),
+            NamedColor::Green => (0, 255, 0),
+            NamedColor::Blue => (0, 0, 255),
+            NamedColor::Yellow => (255, 255, 0),
+            NamedColor::Cyan => (0, 255, 255),
+            NamedColor::Magenta => (255, 0, 255),
+            NamedColor::Orange => (255, 128, 0),
+            NamedColor::Purple => (128, 0, 255),
+            NamedColor::White => (255, 255, 255),
+        }
+    }
+}
+
+/// Button color: a palette name, or any other color as `#RRGGBB`.
+/// Untagged, so palette colors stay plain names in config.json.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum ButtonColor {
+    Named(NamedColor),
+    /// `#RRGGBB`; anything else fails validation
+    Rgb(String),
+}
+
+impl ButtonColor {
+    pub const WHITE: ButtonColor = ButtonColor::Named(NamedColor::White);
+
+    /// Name or hex string as written in config.json
+    pub fn name(&self) -> &str {
+        match self {
+            ButtonColor::Named(named) => named.name(),
+            ButtonColor::Rgb(hex) => hex,
+        }
+    }
+
+    /// LED value, or None for a malformed hex color (the firmware shows white)
+    pub fn rgb(&self) -> Option<(u8, u8, u8)> {
+        match self {
+            ButtonColor::Named(named) => Some(named.rgb()),
+            ButtonColor::Rgb(hex) => parse_hex_color(hex),
+        }
+    }
+}
+
+impl From<NamedColor> for ButtonColor {
+    fn from(named: NamedColor) -> Self {
+        ButtonColor::Named(named)
+    }
+}
+
+/// Parse `#RRGGBB` (either case), as get_color in core/colors.py does
+pub fn parse_hex_color(s: &str) -> Option<(u8, u8, u8)> {
+    let hex = s.strip_prefix('#').filter(|h| h.len() == 6 && h.bytes().all(|b| b.is_ascii_hexdigit()))?;
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some(((value >> 16) as u8, (value >> 8) as u8, value as u8))
+}
+
 /// Button trigger mode
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -26,6 +114,72 @@ pub enum ButtonMode {
     #[default]
     Toggle,
     Momentary,
+    /// Presses set a tempo (`tap_tempo` settings); CC buttons only
+    #[serde(rename = "tap_tempo")]
+    TapTempo,
+}
+
+/// What a tap-tempo button sends
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TapOutput {
+    /// The button's CC (value `cc_on`) on every tap, for devices that time
+    /// the taps themselves
+    #[default]
+    Cc,
+    /// MIDI clock at the tapped tempo
+    Clock,
+}
+
+/// Tempo range the firmware accepts (`BPM_LIMITS` in core/tempo.py)
+pub const MIN_TAP_BPM: u16 = 20;
+pub const MAX_TAP_BPM: u16 = 300;
+/// Tap-tempo range when the config doesn't set one
+pub const DEFAULT_MIN_BPM: u16 = 40;
+pub const DEFAULT_MAX_BPM: u16 = 240;
+
+/// Settings of a tap-tempo button (`_validate_tap_tempo` in core/config.py)
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct TapTempoConfig {
+    #[serde(default)]
+    pub output: TapOutput,
+    /// Slowest tempo; a longer gap between taps starts a new count
+    /// (`DEFAULT_MIN_BPM` when absent)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_bpm: Option<u16>,
+    /// Fastest tempo (`DEFAULT_MAX_BPM` when absent)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bpm: Option<u16>,
+}
+
+impl TapTempoConfig {
+    /// Slowest and fastest tempo, defaults filled in
+    pub fn bpm_range(&self) -> (u16, u16) {
+        (self.min_bpm.unwrap_or(DEFAULT_MIN_BPM), self.max_bpm.unwrap_or(DEFAULT_MAX_BPM))
+    }
+
+    /// Check the tempo range. `path` is the settings' form path, `name` how
+    /// messages refer to the button.
+    fn validate(&self, path: &str, name: &str, errors: &mut Issues) {
+        let field = |f: &str| format!("{}.{}", path, f);
+        for (f, value) in [("min_bpm", self.min_bpm), ("max_bpm", self.max_bpm)] {
+            if let Some(bpm) = value.filter(|b| !(MIN_TAP_BPM..=MAX_TAP_BPM).contains(b)) {
+                errors.error(
+                    field(f),
+                    ISSUE_OUT_OF_RANGE,
+                    format!("{} {} {} out of range ({}-{})", name, f, bpm, MIN_TAP_BPM, MAX_TAP_BPM),
+                );
+            }
+        }
+        let (min, max) = self.bpm_range();
+        if min >= max {
+            errors.error(
+                field("max_bpm"),
+                ISSUE_INVALID_RANGE,
+                format!("{} max_bpm {} must be above min_bpm {}", name, max, min),
+            );
+        }
+    }
 }
 
 /// LED behavior when button is off
@@ -37,6 +191,34 @@ pub enum OffMode {
     Off,
 }
 
+/// A button's LED colors and brightness (percent), taking over from
+/// `off_mode` for the LED; the display keeps showing `color`. Older
+/// firmware ignores it, so `off_mode` still applies there.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct LedConfig {
+    /// The button's color (or its keytimes state's) when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_color: Option<ButtonColor>,
+    /// The on color when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub off_color: Option<ButtonColor>,
+    /// 100 when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_brightness: Option<u8>,
+    /// What `off_mode` gives when absent: 15 for dim, 0 for off
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub off_brightness: Option<u8>,
+}
+
+/// Brightness of a dimmed LED (`dim_color` in core/colors.py)
+pub const DIM_BRIGHTNESS: u8 = 15;
+
+/// `rgb` at `percent` brightness, as `scale_color` in core/colors.py
+pub fn scale_rgb((r, g, b): (u8, u8, u8), percent: u8) -> (u8, u8, u8) {
+    let scale = |c: u8| (u16::from(c) * u16::from(percent.min(100)) / 100) as u8;
+    (scale(r), scale(g), scale(b))
+}
+
 /// Message type for a button
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 #[serde(rename_all = "snake_case")]
@@ -47,8 +229,17 @@ pub enum MessageType {
     Pc,
     PcInc,
     PcDec,
+    /// All Notes Off + All Sound Off (and `reset_ccs`) on every channel
+    Panic,
+    /// `steps` sent in order on press
+    Macro,
 }
 
+/// Channel Mode CCs a panic button sends on every channel (CC_ALL_SOUND_OFF
+/// and CC_ALL_NOTES_OFF in code.py)
+pub const CC_ALL_SOUND_OFF: u8 = 120;
+pub const CC_ALL_NOTES_OFF: u8 = 123;
+
 /// Per-state overrides for keytimes cycling
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct StateOverride {
@@ -74,6 +265,119 @@ pub struct StateOverride {
     pub label: Option<String>,
 }
 
+/// Steps the firmware keeps per macro (`MAX_MACRO_STEPS` in core/macro.py)
+pub const MAX_MACRO_STEPS: usize = 16;
+/// Longest wait before a macro step (`MAX_STEP_DELAY_MS` in core/macro.py)
+pub const MAX_STEP_DELAY_MS: u16 = 5000;
+
+/// Message type of a macro step
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MacroStepType {
+    #[default]
+    Cc,
+    Pc,
+    /// Note on; velocity 0 is a note off
+    Note,
+}
+
+/// One message of a macro button (`_validate_macro_step` in core/config.py)
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct MacroStep {
+    #[serde(rename = "type", default)]
+    pub message_type: MacroStepType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cc: Option<u8>,
+    /// CC value; the firmware sends 127 when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<u8>,
+    /// Note velocity; the firmware sends 127 when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub velocity: Option<u8>,
+    /// The button's channel when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<u8>,
+    /// Wait after the previous step (or the press), 0 when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay_ms: Option<u16>,
+}
+
+impl MacroStep {
+    /// Check ranges and that the message type's number is set. `path` is the
+    /// step's form path, `name` how messages refer to it.
+    fn validate(&self, path: &str, name: &str, errors: &mut Issues) {
+        let field = |f: &str| format!("{}.{}", path, f);
+        for (f, value) in [
+            ("cc", self.cc),
+            ("value", self.value),
+            ("program", self.program),
+            ("note", self.note),
+            ("velocity", self.velocity),
+        ] {
+            if let Some(val) = value.filter(|v| *v > 127) {
+                errors.error(field(f), ISSUE_OUT_OF_RANGE, format!("{} {} {} exceeds 127", name, f, val));
+            }
+        }
+        if let Some(ch) = self.channel.filter(|c| *c > 15) {
+            errors.error(
+                field("channel"),
+                ISSUE_OUT_OF_RANGE,
+                format!("{} channel {} is invalid (must be 1-16)", name, ch + 1),
+            );
+        }
+        if let Some(ms) = self.delay_ms.filter(|ms| *ms > MAX_STEP_DELAY_MS) {
+            errors.error(
+                field("delay_ms"),
+                ISSUE_OUT_OF_RANGE,
+                format!("{} delay {} ms exceeds {} ms", name, ms, MAX_STEP_DELAY_MS),
+            );
+        }
+        match self.message_type {
+            MacroStepType::Cc if self.cc.is_none() => {
+                errors.error(field("cc"), ISSUE_REQUIRED, format!("{} needs a CC number", name));
+            }
+            MacroStepType::Pc if self.program.is_none() => {
+                errors.error(field("program"), ISSUE_REQUIRED, format!("{} needs a program number", name));
+            }
+            MacroStepType::Note if self.note.is_none() => {
+                errors.error(field("note"), ISSUE_REQUIRED, format!("{} needs a note number", name));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Message type of a long-press or double-tap action
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SecondaryType {
+    #[default]
+    Cc,
+    Pc,
+}
+
+/// A button's long-press or double-tap action: one CC or PC message, sent
+/// instead of the button's own message (`SECONDARY_ACTIONS` in core/config.py)
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct SecondaryAction {
+    #[serde(rename = "type", default)]
+    pub message_type: SecondaryType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cc: Option<u8>,
+    /// CC value; the firmware sends 127 when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program: Option<u8>,
+    /// The button's channel when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<u8>,
+}
+
 /// Button configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ButtonConfig {
@@ -83,16 +387,24 @@ pub struct ButtonConfig {
     pub message_type: MessageType,
     #[serde(default)]
     pub mode: ButtonMode,
+    /// Tap-tempo settings, used when `mode` is `tap_tempo`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tap_tempo: Option<TapTempoConfig>,
     #[serde(default, skip_serializing_if = "is_default_off_mode")]
     pub off_mode: OffMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub led: Option<LedConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub channel: Option<u8>,
     // CC fields
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cc: Option<u8>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Value sent when the button turns on (firmware default 127).
+    /// `value_on` is accepted as another name for it.
+    #[serde(alias = "value_on", skip_serializing_if = "Option::is_none")]
     pub cc_on: Option<u8>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Value sent when the button turns off (firmware default 0)
+    #[serde(alias = "value_off", skip_serializing_if = "Option::is_none")]
     pub cc_off: Option<u8>,
     // Note fields
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -107,7 +419,13 @@ pub struct ButtonConfig {
     // PC inc/dec fields
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pc_step: Option<u8>,
-    // PC flash feedback (all PC types)
+    // Panic: CCs also reset to 0 on every channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reset_ccs: Option<Vec<u8>>,
+    // Macro: messages sent in order on press
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<Vec<MacroStep>>,
+    // PC flash feedback (all PC types, panic and macro)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub flash_ms: Option<u16>,
     // Keytimes cycling
@@ -115,6 +433,53 @@ pub struct ButtonConfig {
     pub keytimes: Option<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub states: Option<Vec<StateOverride>>,
+    // Secondary actions; with either set, the button's own message goes out
+    // on release (a tap) instead of on press
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub long_press: Option<SecondaryAction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub double_tap: Option<SecondaryAction>,
+}
+
+impl ButtonConfig {
+    /// LED colors when on and off, as `get_led_colors` in core/colors.py
+    /// draws them for the button's own color (white when malformed)
+    pub fn led_rgb(&self) -> ((u8, u8, u8), (u8, u8, u8)) {
+        let color = self.color.rgb().unwrap_or((255, 255, 255));
+        let default_off = match self.off_mode {
+            OffMode::Dim => DIM_BRIGHTNESS,
+            OffMode::Off => 0,
+        };
+        let Some(led) = &self.led else {
+            return (color, scale_rgb(color, default_off));
+        };
+        let rgb = |c: &Option<ButtonColor>, fallback| c.as_ref().and_then(ButtonColor::rgb).unwrap_or(fallback);
+        let on = rgb(&led.on_color, color);
+        let off = rgb(&led.off_color, on);
+        (scale_rgb(on, led.on_brightness.unwrap_or(100)), scale_rgb(off, led.off_brightness.unwrap_or(default_off)))
+    }
+}
+
+impl LedConfig {
+    /// Check brightness ranges and colors. `path` is the settings' form
+    /// path, `name` how messages refer to the button.
+    fn validate(&self, path: &str, name: &str, errors: &mut Issues) {
+        let field = |f: &str| format!("{}.{}", path, f);
+        for (f, value) in [("on_brightness", self.on_brightness), ("off_brightness", self.off_brightness)] {
+            if let Some(val) = value.filter(|v| *v > 100) {
+                errors.error(field(f), ISSUE_OUT_OF_RANGE, format!("{} LED {} {} exceeds 100", name, f, val));
+            }
+        }
+        for (f, color) in [("on_color", &self.on_color), ("off_color", &self.off_color)] {
+            if let Some(color) = color.as_ref().filter(|c| c.rgb().is_none()) {
+                errors.error(
+                    field(f),
+                    ISSUE_INVALID_COLOR,
+                    format!("{} LED {} '{}' is not a palette color or #RRGGBB", name, f, color.name()),
+                );
+            }
+        }
+    }
 }
 
 fn is_default_off_mode(mode: &OffMode) -> bool {
@@ -125,6 +490,35 @@ fn is_default_message_type(t: &MessageType) -> bool {
     *t == MessageType::Cc
 }
 
+impl SecondaryAction {
+    /// Check ranges and that the message type's number is set. `path` is the
+    /// action's form path, `name` how messages refer to it.
+    fn validate(&self, path: &str, name: &str, errors: &mut Issues) {
+        let field = |f: &str| format!("{}.{}", path, f);
+        for (f, value) in [("cc", self.cc), ("value", self.value), ("program", self.program)] {
+            if let Some(val) = value.filter(|v| *v > 127) {
+                errors.error(field(f), ISSUE_OUT_OF_RANGE, format!("{} {} {} exceeds 127", name, f, val));
+            }
+        }
+        if let Some(ch) = self.channel.filter(|c| *c > 15) {
+            errors.error(
+                field("channel"),
+                ISSUE_OUT_OF_RANGE,
+                format!("{} channel {} is invalid (must be 1-16)", name, ch + 1),
+            );
+        }
+        match self.message_type {
+            SecondaryType::Cc if self.cc.is_none() => {
+                errors.error(field("cc"), ISSUE_REQUIRED, format!("{} needs a CC number", name));
+            }
+            SecondaryType::Pc if self.program.is_none() => {
+                errors.error(field("program"), ISSUE_REQUIRED, format!("{} needs a program number", name));
+            }
+            _ => {}
+        }
+    }
+}
+
 /// Encoder push button configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncoderPush {
@@ -141,6 +535,22 @@ pub struct EncoderPush {
     pub cc_off: Option<u8>,
 }
 
+/// How much faster turns move the encoder value
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Acceleration {
+    /// One step per detent: fine parameter control
+    #[default]
+    Off,
+    Low,
+    /// Large jumps on fast turns, e.g. for scrolling through patches
+    High,
+}
+
+fn is_default_acceleration(acceleration: &Acceleration) -> bool {
+    *acceleration == Acceleration::Off
+}
+
 /// Rotary encoder configuration (STD10 only)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncoderConfig {
@@ -157,6 +567,11 @@ pub struct EncoderConfig {
     pub push: Option<EncoderPush>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub channel: Option<u8>,
+    #[serde(default, skip_serializing_if = "is_default_acceleration")]
+    pub acceleration: Acceleration,
+    /// Wrap around past max/min instead of stopping there
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub wrap: bool,
 }
 
 fn default_max() -> u8 {
@@ -175,6 +590,111 @@ pub enum Polarity {
     Inverted,
 }
 
+/// Named expression pedal response curves
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CurveShape {
+    #[default]
+    Linear,
+    /// Rises quickly at the heel, flattening toward the toe
+    Log,
+    /// Rises slowly at the heel: volume swells that don't jump in the first inch
+    Exp,
+}
+
+/// How pedal travel maps onto the pedal's min..max output: a named shape, or
+/// a breakpoint table of `[travel, output]` pairs (both 0-127) interpolated
+/// linearly, e.g. `[[0, 0], [64, 20], [127, 127]]`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Curve {
+    Shape(CurveShape),
+    Custom(Vec<[u8; 2]>),
+}
+
+/// Why a custom curve's breakpoints can't be used, if they can't
+fn breakpoint_problem(points: &[[u8; 2]]) -> Option<String> {
+    if points.len() < 2 {
+        return Some("needs at least 2 breakpoints".to_string());
+    }
+    if let Some([input, output]) = points.iter().find(|[i, o]| *i > 127 || *o > 127) {
+        return Some(format!("breakpoint [{}, {}] is outside 0-127", input, output));
+    }
+    let (first, last) = (points[0][0], points[points.len() - 1][0]);
+    if first != 0 || last != 127 {
+        return Some(format!("breakpoints must run from travel 0 to 127 (got {} to {})", first, last));
+    }
+    for pair in points.windows(2) {
+        let ([i1, o1], [i2, o2]) = (pair[0], pair[1]);
+        if i2 <= i1 {
+            return Some(format!("travel must increase at every breakpoint ({} then {})", i1, i2));
+        }
+        if o2 < o1 {
+            return Some(format!("output must not decrease ({} at {}, then {} at {})", o1, i1, o2, i2));
+        }
+    }
+    None
+}
+
+/// Which MIDI outputs a control sends to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Destination {
+    #[default]
+    Usb,
+    Din,
+    Both,
+}
+
+impl Destination {
+    pub fn usb(self) -> bool {
+        matches!(self, Destination::Usb | Destination::Both)
+    }
+
+    pub fn din(self) -> bool {
+        matches!(self, Destination::Din | Destination::Both)
+    }
+}
+
+/// MIDI outputs the firmware opens at boot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    #[serde(default = "default_true")]
+    pub usb: bool,
+    /// 5-pin DIN out on the STD10's serial MIDI port (UART TX on GP16)
+    #[serde(default)]
+    pub din: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self { usb: default_true(), din: false }
+    }
+}
+
+/// Longest `global.sleep_minutes` (4 hours)
+pub const MAX_SLEEP_MINUTES: u16 = 240;
+
+/// Device-wide settings. The default MIDI channel stays in the top-level
+/// `global_channel`, where the firmware and every control already read it.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct GlobalConfig {
+    /// LED brightness in percent (firmware default 30)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub led_brightness: Option<u8>,
+    /// Display backlight in percent. The firmware only drives the backlight
+    /// pin when this is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_brightness: Option<u8>,
+    /// Minutes without input before the LEDs and display go dark; absent or 0 = never
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sleep_minutes: Option<u16>,
+}
+
 /// Expression pedal configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExpressionConfig {
@@ -191,6 +711,24 @@ pub struct ExpressionConfig {
     pub threshold: u8,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub channel: Option<u8>,
+    /// Outputs the pedal's CC goes to; USB when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<Destination>,
+    /// Measured ADC travel of the pedal. Without it the firmware learns the
+    /// range from the extremes it sees after boot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<Calibration>,
+    /// Response curve; linear when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub curve: Option<Curve>,
+}
+
+/// Raw ADC readings (0-65535) at the pedal's heel and toe positions,
+/// recorded by `finish_expression_calibration`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Calibration {
+    pub raw_min: u16,
+    pub raw_max: u16,
 }
 
 fn default_threshold() -> u8 {
@@ -211,6 +749,54 @@ pub enum DeviceType {
     #[default]
     Std10,
     Mini6,
+    Nano4,
+    /// The STD10's predecessor: ten switches and pedal jacks, no encoder
+    Blue,
+}
+
+impl DeviceType {
+    pub const ALL: [DeviceType; 4] = [DeviceType::Std10, DeviceType::Mini6, DeviceType::Nano4, DeviceType::Blue];
+
+    /// Number of footswitches
+    pub fn button_count(&self) -> usize {
+        match self {
+            DeviceType::Std10 | DeviceType::Blue => 10,
+            DeviceType::Mini6 => 6,
+            DeviceType::Nano4 => 4,
+        }
+    }
+
+    /// Has the rotary encoder (and its push switch)
+    pub fn has_encoder(&self) -> bool {
+        *self == DeviceType::Std10
+    }
+
+    /// Has the two expression pedal inputs
+    pub fn has_expression(&self) -> bool {
+        matches!(self, DeviceType::Std10 | DeviceType::Blue)
+    }
+
+    /// Has a 5-pin DIN MIDI output
+    pub fn has_din(&self) -> bool {
+        matches!(self, DeviceType::Std10 | DeviceType::Blue)
+    }
+
+    /// The firmware has a device module for it (`firmware/dev/devices/`).
+    /// On any other model it ignores the config's `device` and probes pins,
+    /// so it runs as a Mini6 or STD10.
+    pub fn firmware_supported(&self) -> bool {
+        matches!(self, DeviceType::Std10 | DeviceType::Mini6)
+    }
+
+    /// Name for messages, e.g. "Mini6"
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            DeviceType::Std10 => "STD10",
+            DeviceType::Mini6 => "Mini6",
+            DeviceType::Nano4 => "Nano4",
+            DeviceType::Blue => "Blue",
+        }
+    }
 }
 
 /// Display text size settings
@@ -224,13 +810,54 @@ pub struct DisplayConfig {
     pub expression_text_size: Option<String>,
 }
 
+/// Suppression of a lint finding, kept with the config so the decision travels with it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Suppression {
+    /// Lint rule id, e.g. "duplicate-cc"
+    pub rule: String,
+    /// Limit the suppression to one location (e.g. "buttons[2]"); all locations when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Why the finding is acceptable (required)
+    pub justification: String,
+}
+
+/// Editor-only metadata. The firmware ignores this key.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EditorMetadata {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<Suppression>,
+    /// Locked configs are protected from overwrite unless the write is forced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked: Option<bool>,
+    /// Set (Unix timestamp, seconds) when the config was written in permissive
+    /// mode, i.e. with validation errors or fields the schema doesn't know
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissive_write: Option<u64>,
+    /// Id of the target profile describing the receiving device's CCs
+    /// (see targets.rs); the MIDI standard profile when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_profile: Option<String>,
+}
+
+/// Version of the config schema this editor validates against.
+/// Bump when a field is added, removed or changes meaning (and add a
+/// migration in migrations.rs when old configs need rewriting).
+pub const CONFIG_SCHEMA_VERSION: u32 = 12;
+
 /// Complete MIDI Captain configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MidiCaptainConfig {
     #[serde(default)]
     pub device: DeviceType,
+    /// Schema version the config was written for (`CONFIG_SCHEMA_VERSION`);
+    /// absent in configs from before versioning (see migrations.rs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub global_channel: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub global: Option<GlobalConfig>,
     /// Custom USB volume label (max 11 chars, alphanumeric + underscore).
     /// Applied by boot.py via storage.remount() when the drive is enabled.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -246,159 +873,660 @@ pub struct MidiCaptainConfig {
     pub expression: Option<ExpressionPedals>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display: Option<DisplayConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routing: Option<RoutingConfig>,
+    #[serde(rename = "_editor", skip_serializing_if = "Option::is_none")]
+    pub editor: Option<EditorMetadata>,
+}
+
+/// How serious a validation issue is
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Blocks the write
+    Error,
+    /// Shown, but the config can still be written
+    Warning,
+    /// A suggestion; nothing is wrong
+    Hint,
+}
+
+/// A problem with one field of a config
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValidationIssue {
+    /// Offending field, in the frontend's form paths, e.g. "buttons[3].cc"
+    /// (0-based) or "encoder.range"
+    pub path: String,
+    /// Machine-readable kind, e.g. "out_of_range"
+    pub code: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// A value outside its allowed range
+pub const ISSUE_OUT_OF_RANGE: &str = "out_of_range";
+/// A label longer than the display fits
+pub const ISSUE_TOO_LONG: &str = "too_long";
+/// The button count doesn't match the device
+pub const ISSUE_BUTTON_COUNT: &str = "button_count";
+/// A control the device doesn't have
+pub const ISSUE_UNSUPPORTED: &str = "unsupported";
+/// A min/max/initial combination that can't hold
+pub const ISSUE_INVALID_RANGE: &str = "invalid_range";
+/// A lint suppression without a justification
+pub const ISSUE_MISSING_JUSTIFICATION: &str = "missing_justification";
+/// A control sends to an output the routing block leaves closed
+pub const ISSUE_ROUTING: &str = "routing";
+/// A field the rest of the control needs is missing
+pub const ISSUE_REQUIRED: &str = "required";
+/// A color that is neither a palette name nor `#RRGGBB`
+pub const ISSUE_INVALID_COLOR: &str = "invalid_color";
+/// ON and OFF send the same value, so toggling changes nothing downstream
+pub const ISSUE_SAME_VALUE: &str = "same_value";
+/// A custom response curve whose breakpoints don't rise monotonically across 0-127
+pub const ISSUE_INVALID_CURVE: &str = "invalid_curve";
+/// A label with characters the device's fonts can't draw
+pub const ISSUE_UNSUPPORTED_CHARS: &str = "unsupported_chars";
+
+/// True if the device's fonts can draw `c`: they only cover printable ASCII
+pub fn is_displayable(c: char) -> bool {
+    (' '..='~').contains(&c)
+}
+
+/// The closest spelling of `text` the display can draw: accents stripped,
+/// ligatures and typographic punctuation spelled out in ASCII, anything
+/// else dropped
+pub fn transliterate(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.nfkd() {
+        match c {
+            'ß' => out.push_str("ss"),
+            'æ' => out.push_str("ae"),
+            'Æ' => out.push_str("AE"),
+            'œ' => out.push_str("oe"),
+            'Œ' => out.push_str("OE"),
+            'þ' => out.push_str("th"),
+            'Þ' => out.push_str("TH"),
+            'ø' => out.push('o'),
+            'Ø' => out.push('O'),
+            'ł' => out.push('l'),
+            'Ł' => out.push('L'),
+            'đ' => out.push('d'),
+            'Đ' => out.push('D'),
+            '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{2032}' => out.push('\''),
+            '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{2033}' => out.push('"'),
+            '\u{2010}' | '\u{2013}' | '\u{2014}' | '\u{2212}' => out.push('-'),
+            c if is_displayable(c) => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Messages of a list of issues, for places that report plain strings
+pub fn issue_messages(issues: Vec<ValidationIssue>) -> Vec<String> {
+    issues.into_iter().map(|i| i.message).collect()
+}
+
+/// Collects validation errors
+#[derive(Default)]
+struct Issues(Vec<ValidationIssue>);
+
+impl Issues {
+    fn error(&mut self, path: impl Into<String>, code: &str, message: String) {
+        self.0.push(ValidationIssue {
+            path: path.into(),
+            code: code.to_string(),
+            severity: Severity::Error,
+            message,
+        });
+    }
+
+    /// Flag characters of `label` the display can't draw, suggesting a
+    /// transliteration when there is one
+    fn label_chars(&mut self, path: impl Into<String>, what: &str, label: &str) {
+        let unsupported: String = label.chars().filter(|c| !is_displayable(*c)).collect();
+        if unsupported.is_empty() {
+            return;
+        }
+        let suggestion = transliterate(label);
+        let hint = if suggestion.trim().is_empty() {
+            String::new()
+        } else {
+            format!("; try '{}'", suggestion)
+        };
+        self.error(
+            path,
+            ISSUE_UNSUPPORTED_CHARS,
+            format!("{} label '{}' has characters the display can't show ({}){}", what, label, unsupported, hint),
+        );
+    }
+}
+
+/// LED colors of a factory config, one per switch, in switch order
+const FACTORY_COLORS: [NamedColor; 10] = [
+    NamedColor::Red,
+    NamedColor::Green,
+    NamedColor::Blue,
+    NamedColor::Yellow,
+    NamedColor::Cyan,
+    NamedColor::Magenta,
+    NamedColor::Orange,
+    NamedColor::Purple,
+    NamedColor::White,
+    NamedColor::Red,
+];
+
+/// A plain CC toggle on switch `index` (0-based): labelled with its number,
+/// sending CC 20 + index, as the firmware does when config.json is missing
+pub fn default_button(index: usize) -> ButtonConfig {
+    ButtonConfig {
+        label: (index + 1).to_string(),
+        color: ButtonColor::WHITE,
+        message_type: MessageType::Cc,
+        mode: ButtonMode::Toggle,
+        tap_tempo: None,
+        off_mode: OffMode::Dim,
+        led: None,
+        channel: None,
+        cc: Some(20 + index as u8),
+        cc_on: None,
+        cc_off: None,
+        note: None,
+        velocity_on: None,
+        velocity_off: None,
+        program: None,
+        pc_step: None,
+        reset_ccs: None,
+        steps: None,
+        flash_ms: None,
+        keytimes: None,
+        states: None,
+        long_press: None,
+        double_tap: None,
+    }
+}
+
+fn default_pedal(n: u8, cc: u8) -> ExpressionConfig {
+    ExpressionConfig {
+        enabled: true,
+        cc,
+        label: format!("EXP{}", n),
+        min: 0,
+        max: default_max(),
+        polarity: Polarity::Normal,
+        threshold: default_threshold(),
+        channel: None,
+        destination: None,
+        calibration: None,
+        curve: None,
+    }
+}
+
+/// A complete, valid factory-style config for `device`: CC 20 upwards on
+/// the switches in distinct colors, and on the STD10 the encoder (CC 11,
+/// push CC 14) and pedals (CC 12 and 13) at the firmware's defaults
+pub fn generate_default_config(device: DeviceType) -> MidiCaptainConfig {
+    let buttons = (0..device.button_count())
+        .map(|i| ButtonConfig {
+            color: FACTORY_COLORS[i % FACTORY_COLORS.len()].into(),
+            ..default_button(i)
+        })
+        .collect();
+    let encoder = device.has_encoder().then(|| EncoderConfig {
+        enabled: true,
+        cc: 11,
+        label: "ENC".to_string(),
+        min: 0,
+        max: default_max(),
+        initial: default_initial(),
+        steps: None,
+        push: Some(EncoderPush {
+            enabled: true,
+            cc: 14,
+            label: "PUSH".to_string(),
+            mode: ButtonMode::Momentary,
+            channel: None,
+            cc_on: None,
+            cc_off: None,
+        }),
+        channel: None,
+        acceleration: Acceleration::Off,
+        wrap: false,
+    });
+    let expression = device.has_expression().then(|| ExpressionPedals {
+        exp1: default_pedal(1, 12),
+        exp2: default_pedal(2, 13),
+    });
+    MidiCaptainConfig {
+        device,
+        version: Some(CONFIG_SCHEMA_VERSION),
+        global_channel: None,
+        global: None,
+        usb_drive_name: None,
+        dev_mode: None,
+        buttons,
+        encoder,
+        expression,
+        display: None,
+        routing: None,
+        editor: None,
+    }
 }
 
 impl MidiCaptainConfig {
+    /// Transliterate every label (buttons, keytimes states, encoder, push,
+    /// pedals) to characters the display can draw
+    pub fn sanitize_labels(&mut self) {
+        for button in &mut self.buttons {
+            button.label = transliterate(&button.label);
+            for state in button.states.iter_mut().flatten() {
+                if let Some(label) = state.label.as_mut() {
+                    *label = transliterate(label);
+                }
+            }
+        }
+        if let Some(encoder) = self.encoder.as_mut() {
+            encoder.label = transliterate(&encoder.label);
+            if let Some(push) = encoder.push.as_mut() {
+                push.label = transliterate(&push.label);
+            }
+        }
+        if let Some(expression) = self.expression.as_mut() {
+            for pedal in [&mut expression.exp1, &mut expression.exp2] {
+                pedal.label = transliterate(&pedal.label);
+            }
+        }
+    }
+
     /// Validate the configuration
-    pub fn validate(&self) -> Result<(), Vec<String>> {
-        let mut errors = Vec::new();
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        let mut errors = Issues::default();
 
         // Validate global channel (0-15 internally, display as 1-16)
         if let Some(ch) = self.global_channel {
             if ch > 15 {
-                errors.push(format!("Global channel value {} is invalid (must be 1-16, stored as 0-15)", ch + 1));
+                errors.error(
+                    "global_channel",
+                    ISSUE_OUT_OF_RANGE,
+                    format!("Global channel value {} is invalid (must be 1-16, stored as 0-15)", ch + 1),
+                );
+            }
+        }
+
+        if let Some(global) = &self.global {
+            for (field, value) in [("led_brightness", global.led_brightness), ("display_brightness", global.display_brightness)] {
+                if let Some(val) = value.filter(|v| *v > 100) {
+                    errors.error(
+                        format!("global.{}", field),
+                        ISSUE_OUT_OF_RANGE,
+                        format!("Global {} {} out of range (0-100)", field, val),
+                    );
+                }
+            }
+            if let Some(minutes) = global.sleep_minutes.filter(|m| *m > MAX_SLEEP_MINUTES) {
+                errors.error(
+                    "global.sleep_minutes",
+                    ISSUE_OUT_OF_RANGE,
+                    format!("Global sleep_minutes {} out of range (0-{})", minutes, MAX_SLEEP_MINUTES),
+                );
             }
         }
 
         // Check button count matches device
-        let expected_buttons = match self.device {
-            DeviceType::Std10 => 10,
-            DeviceType::Mini6 => 6,
-        };
+        let expected_buttons = self.device.button_count();
 
         if self.buttons.len() != expected_buttons {
-            errors.push(format!(
-                "Expected {} buttons for {:?}, found {}",
-                expected_buttons,
-                self.device,
-                self.buttons.len()
-            ));
+            errors.error(
+                "device",
+                ISSUE_BUTTON_COUNT,
+                format!(
+                    "Expected {} buttons for {:?}, found {}",
+                    expected_buttons,
+                    self.device,
+                    self.buttons.len()
+                ),
+            );
         }
 
         // Validate CC numbers (0-127) and button-specific fields
         for (i, button) in self.buttons.iter().enumerate() {
+            let path = |field: &str| format!("buttons[{}].{}", i, field);
             if let Some(cc) = button.cc {
                 if cc > 127 {
-                    errors.push(format!("Button {} CC {} exceeds 127", i + 1, cc));
+                    errors.error(path("cc"), ISSUE_OUT_OF_RANGE, format!("Button {} CC {} exceeds 127", i + 1, cc));
                 }
             }
+            if button.color.rgb().is_none() {
+                errors.error(
+                    path("color"),
+                    ISSUE_INVALID_COLOR,
+                    format!("Button {} color '{}' is not a palette color or #RRGGBB", i + 1, button.color.name()),
+                );
+            }
             if button.label.len() > 6 {
-                errors.push(format!(
-                    "Button {} label '{}' exceeds 6 chars",
-                    i + 1,
-                    button.label
-                ));
+                errors.error(
+                    path("label"),
+                    ISSUE_TOO_LONG,
+                    format!("Button {} label '{}' exceeds 6 chars", i + 1, button.label),
+                );
             }
+            errors.label_chars(path("label"), &format!("Button {}", i + 1), &button.label);
             if let Some(ch) = button.channel {
                 if ch > 15 {
-                    errors.push(format!("Button {} channel {} is invalid (must be 1-16)", i + 1, ch + 1));
+                    errors.error(
+                        path("channel"),
+                        ISSUE_OUT_OF_RANGE,
+                        format!("Button {} channel {} is invalid (must be 1-16)", i + 1, ch + 1),
+                    );
                 }
             }
             if let Some(val) = button.cc_on {
                 if val > 127 {
-                    errors.push(format!("Button {} cc_on {} exceeds 127", i + 1, val));
+                    errors.error(path("cc_on"), ISSUE_OUT_OF_RANGE, format!("Button {} cc_on {} exceeds 127", i + 1, val));
                 }
             }
             if let Some(val) = button.cc_off {
                 if val > 127 {
-                    errors.push(format!("Button {} cc_off {} exceeds 127", i + 1, val));
+                    errors.error(path("cc_off"), ISSUE_OUT_OF_RANGE, format!("Button {} cc_off {} exceeds 127", i + 1, val));
+                }
+            }
+            // Tap tempo never sends cc_off, so matching values are fine there
+            let sends_off = matches!(button.mode, ButtonMode::Toggle | ButtonMode::Momentary);
+            if button.message_type == MessageType::Cc && sends_off && (button.cc_on.is_some() || button.cc_off.is_some()) {
+                let (on, off) = (button.cc_on.unwrap_or(127), button.cc_off.unwrap_or(0));
+                if on == off {
+                    errors.error(
+                        path("cc_off"),
+                        ISSUE_SAME_VALUE,
+                        format!("Button {} sends {} for both ON and OFF", i + 1, on),
+                    );
                 }
             }
             if let Some(ms) = button.flash_ms {
                 if ms < 50 || ms > 5000 {
-                    errors.push(format!("Button {} flash_ms {} out of range (50-5000)", i + 1, ms));
+                    errors.error(
+                        path("flash_ms"),
+                        ISSUE_OUT_OF_RANGE,
+                        format!("Button {} flash_ms {} out of range (50-5000)", i + 1, ms),
+                    );
+                }
+            }
+            // Note and PC values, and the same fields in keytimes states, use the
+            // ranges the firmware clamps to (_clamp_state_field in core/config.py)
+            for (field, value) in [
+                ("note", button.note),
+                ("velocity_on", button.velocity_on),
+                ("velocity_off", button.velocity_off),
+                ("program", button.program),
+            ] {
+                if let Some(val) = value.filter(|v| *v > 127) {
+                    errors.error(path(field), ISSUE_OUT_OF_RANGE, format!("Button {} {} {} exceeds 127", i + 1, field, val));
+                }
+            }
+            for (r, cc) in button.reset_ccs.iter().flatten().enumerate() {
+                if *cc > 127 {
+                    errors.error(
+                        format!("buttons[{}].reset_ccs[{}]", i, r),
+                        ISSUE_OUT_OF_RANGE,
+                        format!("Button {} reset CC {} exceeds 127", i + 1, cc),
+                    );
+                }
+            }
+            if button.mode == ButtonMode::TapTempo {
+                if button.message_type != MessageType::Cc {
+                    errors.error(path("mode"), ISSUE_UNSUPPORTED, format!("Button {} tap tempo needs the CC type", i + 1));
+                }
+                let tap = button.tap_tempo.clone().unwrap_or_default();
+                tap.validate(&path("tap_tempo"), &format!("Button {} tap tempo", i + 1), &mut errors);
+            }
+            if button.message_type == MessageType::Macro {
+                let steps = button.steps.as_deref().unwrap_or_default();
+                if steps.is_empty() {
+                    errors.error(path("steps"), ISSUE_REQUIRED, format!("Button {} macro has no steps", i + 1));
+                } else if steps.len() > MAX_MACRO_STEPS {
+                    errors.error(
+                        path("steps"),
+                        ISSUE_OUT_OF_RANGE,
+                        format!("Button {} macro has {} steps; the firmware plays at most {}", i + 1, steps.len(), MAX_MACRO_STEPS),
+                    );
+                }
+                for (n, step) in steps.iter().enumerate() {
+                    step.validate(&format!("buttons[{}].steps[{}]", i, n), &format!("Button {} step {}", i + 1, n + 1), &mut errors);
+                }
+            }
+            if let Some(step) = button.pc_step.filter(|s| *s == 0 || *s > 127) {
+                errors.error(
+                    path("pc_step"),
+                    ISSUE_OUT_OF_RANGE,
+                    format!("Button {} pc_step {} out of range (1-127)", i + 1, step),
+                );
+            }
+            if let Some(led) = &button.led {
+                led.validate(&path("led"), &format!("Button {}", i + 1), &mut errors);
+                let (on, off) = button.led_rgb();
+                if on == off && matches!(button.message_type, MessageType::Cc | MessageType::Note) {
+                    errors.error(
+                        path("led.off_brightness"),
+                        ISSUE_SAME_VALUE,
+                        format!("Button {} LED looks the same on and off", i + 1),
+                    );
+                }
+            }
+            for (key, name, action) in [
+                ("long_press", "long press", &button.long_press),
+                ("double_tap", "double tap", &button.double_tap),
+            ] {
+                if let Some(action) = action {
+                    action.validate(&format!("buttons[{}].{}", i, key), &format!("Button {} {}", i + 1, name), &mut errors);
+                }
+            }
+            for (s, state) in button.states.iter().flatten().enumerate() {
+                let state_path = |field: &str| format!("buttons[{}].states[{}].{}", i, s, field);
+                for (field, value) in [
+                    ("cc", state.cc),
+                    ("cc_on", state.cc_on),
+                    ("cc_off", state.cc_off),
+                    ("note", state.note),
+                    ("velocity_on", state.velocity_on),
+                    ("velocity_off", state.velocity_off),
+                    ("program", state.program),
+                ] {
+                    if let Some(val) = value.filter(|v| *v > 127) {
+                        errors.error(
+                            state_path(field),
+                            ISSUE_OUT_OF_RANGE,
+                            format!("Button {} state {} {} {} exceeds 127", i + 1, s + 1, field, val),
+                        );
+                    }
+                }
+                if let Some(step) = state.pc_step.filter(|s| *s == 0 || *s > 127) {
+                    errors.error(
+                        state_path("pc_step"),
+                        ISSUE_OUT_OF_RANGE,
+                        format!("Button {} state {} pc_step {} out of range (1-127)", i + 1, s + 1, step),
+                    );
+                }
+                if let Some(color) = state.color.as_ref().filter(|c| c.rgb().is_none()) {
+                    errors.error(
+                        state_path("color"),
+                        ISSUE_INVALID_COLOR,
+                        format!("Button {} state {} color '{}' is not a palette color or #RRGGBB", i + 1, s + 1, color.name()),
+                    );
+                }
+                if let Some(label) = &state.label {
+                    errors.label_chars(state_path("label"), &format!("Button {} state {}", i + 1, s + 1), label);
                 }
             }
         }
 
         // Validate encoder if present
         if let Some(ref enc) = self.encoder {
-            // Mini6 does not support encoder
-            if self.device == DeviceType::Mini6 {
-                errors.push("Mini6 does not support encoder".to_string());
+            // Only the STD10 has an encoder
+            if !self.device.has_encoder() {
+                errors.error(
+                    "encoder.enabled",
+                    ISSUE_UNSUPPORTED,
+                    format!("{} does not support encoder", self.device.display_name()),
+                );
             }
             if enc.cc > 127 {
-                errors.push(format!("Encoder CC {} exceeds 127", enc.cc));
+                errors.error("encoder.cc", ISSUE_OUT_OF_RANGE, format!("Encoder CC {} exceeds 127", enc.cc));
             }
             if enc.label.len() > 8 {
-                errors.push(format!("Encoder label '{}' exceeds 8 chars", enc.label));
+                errors.error("encoder.label", ISSUE_TOO_LONG, format!("Encoder label '{}' exceeds 8 chars", enc.label));
             }
+            errors.label_chars("encoder.label", "Encoder", &enc.label);
             if enc.max < enc.min {
-                errors.push(format!("Encoder max ({}) must be >= min ({})", enc.max, enc.min));
+                errors.error(
+                    "encoder.range",
+                    ISSUE_INVALID_RANGE,
+                    format!("Encoder max ({}) must be >= min ({})", enc.max, enc.min),
+                );
             }
             if enc.initial < enc.min || enc.initial > enc.max {
-                errors.push(format!("Encoder initial ({}) must be between min ({}) and max ({})", enc.initial, enc.min, enc.max));
+                errors.error(
+                    "encoder.initial",
+                    ISSUE_INVALID_RANGE,
+                    format!("Encoder initial ({}) must be between min ({}) and max ({})", enc.initial, enc.min, enc.max),
+                );
+            }
+            if enc.wrap && enc.steps.is_none() && enc.max == enc.min {
+                errors.error(
+                    "encoder.wrap",
+                    ISSUE_INVALID_RANGE,
+                    format!("Encoder wrap needs max above min (both {})", enc.min),
+                );
             }
             if let Some(ch) = enc.channel {
                 if ch > 15 {
-                    errors.push(format!("Encoder channel {} is invalid (must be 1-16)", ch + 1));
+                    errors.error(
+                        "encoder.channel",
+                        ISSUE_OUT_OF_RANGE,
+                        format!("Encoder channel {} is invalid (must be 1-16)", ch + 1),
+                    );
                 }
             }
             if let Some(ref push) = enc.push {
                 if push.cc > 127 {
-                    errors.push(format!("Encoder push CC {} exceeds 127", push.cc));
+                    errors.error("encoder.push.cc", ISSUE_OUT_OF_RANGE, format!("Encoder push CC {} exceeds 127", push.cc));
                 }
                 if push.label.len() > 8 {
-                    errors.push(format!("Encoder push label '{}' exceeds 8 chars", push.label));
+                    errors.error(
+                        "encoder.push.label",
+                        ISSUE_TOO_LONG,
+                        format!("Encoder push label '{}' exceeds 8 chars", push.label),
+                    );
+                }
+                errors.label_chars("encoder.push.label", "Encoder push", &push.label);
+                if push.mode == ButtonMode::TapTempo {
+                    errors.error("encoder.push.mode", ISSUE_UNSUPPORTED, "Encoder push does not support tap tempo".to_string());
                 }
                 if let Some(ch) = push.channel {
                     if ch > 15 {
-                        errors.push(format!("Encoder push channel {} is invalid (must be 1-16)", ch + 1));
+                        errors.error(
+                            "encoder.push.channel",
+                            ISSUE_OUT_OF_RANGE,
+                            format!("Encoder push channel {} is invalid (must be 1-16)", ch + 1),
+                        );
                     }
                 }
                 if let Some(val) = push.cc_on {
                     if val > 127 {
-                        errors.push(format!("Encoder push cc_on {} exceeds 127", val));
+                        errors.error("encoder.push.cc_on", ISSUE_OUT_OF_RANGE, format!("Encoder push cc_on {} exceeds 127", val));
                     }
                 }
                 if let Some(val) = push.cc_off {
                     if val > 127 {
-                        errors.push(format!("Encoder push cc_off {} exceeds 127", val));
+                        errors.error("encoder.push.cc_off", ISSUE_OUT_OF_RANGE, format!("Encoder push cc_off {} exceeds 127", val));
                     }
                 }
             }
         }
 
+        let routing = self.routing.clone().unwrap_or_default();
+        if routing.din && !self.device.has_din() {
+            errors.error(
+                "routing.din",
+                ISSUE_UNSUPPORTED,
+                format!("{} has no DIN MIDI output", self.device.display_name()),
+            );
+        }
+
         // Validate expression pedals if present
         if let Some(ref exp) = self.expression {
-            // Mini6 does not support expression pedals
-            if self.device == DeviceType::Mini6 {
-                errors.push("Mini6 does not support expression pedals".to_string());
-            }
-            if exp.exp1.cc > 127 {
-                errors.push(format!("EXP1 CC {} exceeds 127", exp.exp1.cc));
+            if !self.device.has_expression() {
+                errors.error(
+                    "expression",
+                    ISSUE_UNSUPPORTED,
+                    format!("{} does not support expression pedals", self.device.display_name()),
+                );
             }
-            if exp.exp1.label.len() > 8 {
-                errors.push(format!("EXP1 label '{}' exceeds 8 chars", exp.exp1.label));
-            }
-            if exp.exp1.max < exp.exp1.min {
-                errors.push(format!("EXP1 max ({}) must be >= min ({})", exp.exp1.max, exp.exp1.min));
-            }
-            if let Some(ch) = exp.exp1.channel {
-                if ch > 15 {
-                    errors.push(format!("EXP1 channel {} is invalid (must be 1-16)", ch + 1));
+            for (key, name, pedal) in [("exp1", "EXP1", &exp.exp1), ("exp2", "EXP2", &exp.exp2)] {
+                let path = |field: &str| format!("expression.{}.{}", key, field);
+                if pedal.cc > 127 {
+                    errors.error(path("cc"), ISSUE_OUT_OF_RANGE, format!("{} CC {} exceeds 127", name, pedal.cc));
+                }
+                if pedal.label.len() > 8 {
+                    errors.error(path("label"), ISSUE_TOO_LONG, format!("{} label '{}' exceeds 8 chars", name, pedal.label));
+                }
+                errors.label_chars(path("label"), name, &pedal.label);
+                if pedal.max < pedal.min {
+                    errors.error(
+                        path("range"),
+                        ISSUE_INVALID_RANGE,
+                        format!("{} max ({}) must be >= min ({})", name, pedal.max, pedal.min),
+                    );
+                }
+                if let Some(ch) = pedal.channel {
+                    if ch > 15 {
+                        errors.error(
+                            path("channel"),
+                            ISSUE_OUT_OF_RANGE,
+                            format!("{} channel {} is invalid (must be 1-16)", name, ch + 1),
+                        );
+                    }
+                }
+                if let Some(cal) = pedal.calibration {
+                    if cal.raw_min >= cal.raw_max {
+                        errors.error(
+                            path("calibration"),
+                            ISSUE_INVALID_RANGE,
+                            format!("{} calibration raw_min ({}) must be below raw_max ({})", name, cal.raw_min, cal.raw_max),
+                        );
+                    }
+                }
+                if let Some(Curve::Custom(ref points)) = pedal.curve {
+                    if let Some(problem) = breakpoint_problem(points) {
+                        errors.error(path("curve"), ISSUE_INVALID_CURVE, format!("{} curve {}", name, problem));
+                    }
+                }
+                let destination = pedal.destination.unwrap_or_default();
+                if pedal.enabled && destination.usb() && !routing.usb {
+                    errors.error(path("destination"), ISSUE_ROUTING, format!("{} sends to USB but routing.usb is off", name));
+                }
+                if pedal.enabled && destination.din() && !routing.din {
+                    errors.error(path("destination"), ISSUE_ROUTING, format!("{} sends to DIN but routing.din is off", name));
                 }
             }
-            if exp.exp2.cc > 127 {
-                errors.push(format!("EXP2 CC {} exceeds 127", exp.exp2.cc));
-            }
-            if exp.exp2.label.len() > 8 {
-                errors.push(format!("EXP2 label '{}' exceeds 8 chars", exp.exp2.label));
-            }
-            if exp.exp2.max < exp.exp2.min {
-                errors.push(format!("EXP2 max ({}) must be >= min ({})", exp.exp2.max, exp.exp2.min));
-            }
-            if let Some(ch) = exp.exp2.channel {
-                if ch > 15 {
-                    errors.push(format!("EXP2 channel {} is invalid (must be 1-16)", ch + 1));
+        }
+
+        if let Some(ref editor) = self.editor {
+            for (i, suppression) in editor.suppressions.iter().enumerate() {
+                if suppression.justification.trim().is_empty() {
+                    errors.error(
+                        format!("_editor.suppressions[{}].justification", i),
+                        ISSUE_MISSING_JUSTIFICATION,
+                        format!("Suppression of '{}' needs a justification", suppression.rule),
+                    );
                 }
             }
         }
 
-        if errors.is_empty() {
+        if errors.0.is_empty() {
             Ok(())
         } else {
-            Err(errors)
+            Err(errors.0)
         }
     }
 }
@@ -476,6 +1604,45 @@ mod tests {
         assert_eq!(btn2.velocity_on, Some(100));
     }
 
+    #[test]
+    fn test_roundtrip_button_channel() {
+        // Stored 0-15, shown as 1-16; unset means the global channel
+        let json = r#"{
+            "buttons": [
+                {"label": "AMP", "cc": 20, "color": "red", "channel": 0},
+                {"label": "SYNTH", "cc": 21, "color": "blue", "channel": 15},
+                {"label": "GLOBAL", "cc": 22, "color": "green"}
+            ]
+        }"#;
+
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let reserialized = serde_json::to_string(&config).unwrap();
+        let config2: MidiCaptainConfig = serde_json::from_str(&reserialized).unwrap();
+        let channels: Vec<Option<u8>> = config2.buttons.iter().map(|b| b.channel).collect();
+        assert_eq!(channels, [Some(0), Some(15), None]);
+        let value: serde_json::Value = serde_json::from_str(&reserialized).unwrap();
+        assert!(value["buttons"][2].get("channel").is_none());
+    }
+
+    #[test]
+    fn test_button_channel_out_of_range() {
+        let mut config: MidiCaptainConfig = serde_json::from_str(
+            r#"{"device": "mini6", "buttons": [
+                {"label": "1", "color": "red"}, {"label": "2", "color": "red"}, {"label": "3", "color": "red"},
+                {"label": "4", "color": "red"}, {"label": "5", "color": "red"}, {"label": "6", "color": "red"}
+            ]}"#,
+        )
+        .unwrap();
+        config.buttons[2].channel = Some(15);
+        assert!(config.validate().is_ok());
+        config.buttons[2].channel = Some(16);
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors[0].path, "buttons[2].channel");
+        assert_eq!(errors[0].code, ISSUE_OUT_OF_RANGE);
+        assert_eq!(errors[0].severity, Severity::Error);
+        assert_eq!(issue_messages(errors), ["Button 3 channel 17 is invalid (must be 1-16)"]);
+    }
+
     #[test]
     fn test_roundtrip_pc_button() {
         let json = r#"{
@@ -494,6 +1661,78 @@ mod tests {
         assert_eq!(config2.buttons[0].program, Some(42));
     }
 
+    #[test]
+    fn test_message_type_value_ranges() {
+        let json = r#"{
+            "device": "mini6",
+            "buttons": [
+                {"label": "N", "type": "note", "note": 128, "velocity_on": 200, "color": "blue"},
+                {"label": "P", "type": "pc", "program": 127, "color": "red"},
+                {"label": "P2", "type": "pc", "program": 130, "color": "red"},
+                {"label": "UP", "type": "pc_inc", "pc_step": 0, "color": "green"},
+                {"label": "CY", "keytimes": 2, "color": "white", "states": [{"cc_on": 64}, {"cc_on": 255}]},
+                {"label": "OK", "type": "pc_dec", "pc_step": 127, "color": "green"}
+            ]
+        }"#;
+
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let errors = config.validate().unwrap_err();
+        let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "buttons[0].note",
+                "buttons[0].velocity_on",
+                "buttons[2].program",
+                "buttons[3].pc_step",
+                "buttons[4].states[1].cc_on",
+            ]
+        );
+        assert_eq!(
+            issue_messages(errors),
+            [
+                "Button 1 note 128 exceeds 127",
+                "Button 1 velocity_on 200 exceeds 127",
+                "Button 3 program 130 exceeds 127",
+                "Button 4 pc_step 0 out of range (1-127)",
+                "Button 5 state 2 cc_on 255 exceeds 127",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_panic_button() {
+        let json = r#"{
+            "buttons": [
+                {"label": "PANIC", "type": "panic", "reset_ccs": [121, 64], "color": "red"}
+            ]
+        }"#;
+
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.buttons[0].message_type, MessageType::Panic);
+
+        let reserialized = serde_json::to_string(&config).unwrap();
+        let config2: MidiCaptainConfig = serde_json::from_str(&reserialized).unwrap();
+        assert_eq!(config2.buttons[0].message_type, MessageType::Panic);
+        assert_eq!(config2.buttons[0].reset_ccs, Some(vec![121, 64]));
+    }
+
+    #[test]
+    fn test_panic_reset_ccs_range() {
+        let mut config: MidiCaptainConfig = serde_json::from_str(
+            r#"{"device": "mini6", "buttons": [
+                {"label": "1", "color": "red"}, {"label": "2", "color": "red"}, {"label": "3", "color": "red"},
+                {"label": "4", "color": "red"}, {"label": "5", "color": "red"},
+                {"label": "PANIC", "type": "panic", "reset_ccs": [64, 200], "color": "red"}
+            ]}"#,
+        )
+        .unwrap();
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors[0].path, "buttons[5].reset_ccs[1]");
+        config.buttons[5].reset_ccs = Some(vec![64]);
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_roundtrip_pc_inc_dec_buttons() {
         let json = r#"{
@@ -541,14 +1780,14 @@ mod tests {
         assert_eq!(states.len(), 3);
         assert_eq!(states[0].cc, Some(1));
         assert_eq!(states[0].label.as_deref(), Some("ONE"));
-        assert_eq!(states[1].color, Some(ButtonColor::Red));
+        assert_eq!(states[1].color, Some(NamedColor::Red.into()));
         assert_eq!(states[2].cc_off, Some(64));
 
         let reserialized = serde_json::to_string(&config).unwrap();
         let config2: MidiCaptainConfig = serde_json::from_str(&reserialized).unwrap();
         let states2 = config2.buttons[0].states.as_ref().unwrap();
         assert_eq!(states2[0].cc, Some(1));
-        assert_eq!(states2[1].color, Some(ButtonColor::Red));
+        assert_eq!(states2[1].color, Some(NamedColor::Red.into()));
     }
 
     #[test]
@@ -616,4 +1855,500 @@ mod tests {
         let reserialized = serde_json::to_string(&config).unwrap();
         assert!(!reserialized.contains("dev_mode"));
     }
+
+    #[test]
+    fn test_roundtrip_editor_suppressions() {
+        let json = r#"{
+            "buttons": [],
+            "_editor": {
+                "suppressions": [
+                    {"rule": "duplicate-cc", "target": "buttons[3]", "justification": "Same CC on purpose: both toggle the looper"}
+                ]
+            }
+        }"#;
+
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let reserialized = serde_json::to_string(&config).unwrap();
+        assert!(reserialized.contains("\"_editor\""));
+        let config2: MidiCaptainConfig = serde_json::from_str(&reserialized).unwrap();
+        let suppressions = &config2.editor.unwrap().suppressions;
+        assert_eq!(suppressions.len(), 1);
+        assert_eq!(suppressions[0].target.as_deref(), Some("buttons[3]"));
+    }
+
+    #[test]
+    fn test_suppression_requires_justification() {
+        let json = r#"{
+            "device": "mini6",
+            "buttons": [
+                {"label": "1", "cc": 20, "color": "red"},
+                {"label": "2", "cc": 21, "color": "red"},
+                {"label": "3", "cc": 22, "color": "red"},
+                {"label": "4", "cc": 23, "color": "red"},
+                {"label": "5", "cc": 24, "color": "red"},
+                {"label": "6", "cc": 25, "color": "red"}
+            ],
+            "_editor": {"suppressions": [{"rule": "duplicate-cc", "justification": " "}]}
+        }"#;
+
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors[0].path, "_editor.suppressions[0].justification");
+        assert_eq!(issue_messages(errors), vec!["Suppression of 'duplicate-cc' needs a justification".to_string()]);
+    }
+
+    #[test]
+    fn test_roundtrip_editor_locked() {
+        let json = r#"{ "buttons": [], "_editor": { "locked": true } }"#;
+
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.editor.as_ref().unwrap().locked, Some(true));
+
+        let reserialized = serde_json::to_string(&config).unwrap();
+        let config2: MidiCaptainConfig = serde_json::from_str(&reserialized).unwrap();
+        assert_eq!(config2.editor.unwrap().locked, Some(true));
+    }
+
+    #[test]
+    fn test_roundtrip_editor_permissive_write() {
+        let json = r#"{ "buttons": [], "_editor": { "permissive_write": 1767312000 } }"#;
+
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.editor.as_ref().unwrap().permissive_write, Some(1_767_312_000));
+
+        let reserialized = serde_json::to_string(&config).unwrap();
+        let config2: MidiCaptainConfig = serde_json::from_str(&reserialized).unwrap();
+        assert_eq!(config2.editor.unwrap().permissive_write, Some(1_767_312_000));
+    }
+
+    #[test]
+    fn test_roundtrip_editor_target_profile() {
+        let json = r#"{ "buttons": [], "_editor": { "target_profile": "midi-standard" } }"#;
+
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.editor.as_ref().unwrap().target_profile.as_deref(), Some("midi-standard"));
+
+        let reserialized = serde_json::to_string(&config).unwrap();
+        let config2: MidiCaptainConfig = serde_json::from_str(&reserialized).unwrap();
+        assert_eq!(config2.editor.unwrap().target_profile.as_deref(), Some("midi-standard"));
+    }
+
+    #[test]
+    fn test_expression_destination_needs_routing() {
+        let json = r#"{
+            "buttons": [],
+            "expression": {
+                "exp1": {"enabled": true, "cc": 12, "label": "EXP1", "destination": "both"},
+                "exp2": {"enabled": false, "cc": 13, "label": "EXP2", "destination": "din"}
+            }
+        }"#;
+        let routing_errors = |config: &MidiCaptainConfig| -> Vec<ValidationIssue> {
+            config.validate().err().unwrap_or_default().into_iter().filter(|e| e.code == ISSUE_ROUTING).collect()
+        };
+
+        let mut config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let errors = routing_errors(&config);
+        assert_eq!(errors.len(), 1, "a disabled pedal isn't checked: {:?}", errors);
+        assert_eq!(errors[0].path, "expression.exp1.destination");
+        assert_eq!(errors[0].message, "EXP1 sends to DIN but routing.din is off");
+
+        config.routing = Some(RoutingConfig { usb: true, din: true });
+        assert!(routing_errors(&config).is_empty());
+
+        let reserialized = serde_json::to_string(&config).unwrap();
+        let config2: MidiCaptainConfig = serde_json::from_str(&reserialized).unwrap();
+        assert_eq!(config2.expression.unwrap().exp1.destination, Some(Destination::Both));
+        assert!(config2.routing.unwrap().din);
+    }
+
+    #[test]
+    fn test_mini6_has_no_din_output() {
+        let json = r#"{"device": "mini6", "buttons": [], "routing": {"din": true}}"#;
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        assert!(config.routing.as_ref().unwrap().usb);
+        let errors = config.validate().unwrap_err();
+        assert!(errors.iter().any(|e| e.path == "routing.din" && e.code == ISSUE_UNSUPPORTED));
+    }
+
+    #[test]
+    fn test_generate_default_config_is_valid() {
+        for device in DeviceType::ALL {
+            let config = generate_default_config(device.clone());
+            assert!(config.validate().is_ok(), "{:?}: {:?}", device, config.validate());
+            assert_eq!(config.buttons.len(), device.button_count());
+            let ccs: Vec<Option<u8>> = config.buttons.iter().map(|b| b.cc).collect();
+            assert_eq!(ccs[..3], [Some(20), Some(21), Some(22)]);
+            assert_eq!(config.buttons[1].color, NamedColor::Green.into());
+        }
+        let mini6 = generate_default_config(DeviceType::Mini6);
+        assert!(mini6.encoder.is_none() && mini6.expression.is_none());
+        let std10 = generate_default_config(DeviceType::Std10);
+        assert_eq!(std10.expression.unwrap().exp2.cc, 13);
+        let blue = generate_default_config(DeviceType::Blue);
+        assert!(blue.encoder.is_none() && blue.expression.is_some());
+    }
+
+    #[test]
+    fn test_controls_checked_against_the_model() {
+        let std10 = generate_default_config(DeviceType::Std10);
+        let on = |device: DeviceType| {
+            let mut config = std10.clone();
+            config.buttons.truncate(device.button_count());
+            config.device = device;
+            config.routing = serde_json::from_str(r#"{"din": true}"#).unwrap();
+            config.validate().err().unwrap_or_default().into_iter().map(|e| e.message).collect::<Vec<_>>()
+        };
+        assert!(on(DeviceType::Std10).is_empty());
+        assert_eq!(on(DeviceType::Blue), ["Blue does not support encoder"]);
+        assert_eq!(
+            on(DeviceType::Nano4),
+            [
+                "Nano4 does not support encoder",
+                "Nano4 has no DIN MIDI output",
+                "Nano4 does not support expression pedals"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_secondary_actions() {
+        let json = r#"{
+            "buttons": [{
+                "label": "DLY", "cc": 20, "color": "blue",
+                "long_press": {"cc": 64, "value": 0},
+                "double_tap": {"type": "pc", "program": 5, "channel": 2}
+            }]
+        }"#;
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let btn = &config.buttons[0];
+        assert_eq!(btn.long_press.as_ref().unwrap().message_type, SecondaryType::Cc);
+        assert_eq!(btn.long_press.as_ref().unwrap().value, Some(0));
+        assert_eq!(btn.double_tap.as_ref().unwrap().program, Some(5));
+
+        let value = serde_json::to_value(&config).unwrap();
+        assert_eq!(value["buttons"][0]["long_press"], serde_json::json!({"type": "cc", "cc": 64, "value": 0}));
+        assert_eq!(
+            value["buttons"][0]["double_tap"],
+            serde_json::json!({"type": "pc", "program": 5, "channel": 2})
+        );
+        assert!(serde_json::to_value(&default_button(0)).unwrap().get("long_press").is_none());
+    }
+
+    #[test]
+    fn test_secondary_action_validation() {
+        let json = r#"{
+            "buttons": [{
+                "label": "A", "cc": 20, "color": "red",
+                "long_press": {"cc": 200, "value": 128, "channel": 16},
+                "double_tap": {"type": "pc"}
+            }]
+        }"#;
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let errors = config.validate().unwrap_err();
+        let found: Vec<(&str, &str)> = errors.iter().map(|e| (e.path.as_str(), e.code.as_str())).collect();
+        for expected in [
+            ("buttons[0].long_press.cc", ISSUE_OUT_OF_RANGE),
+            ("buttons[0].long_press.value", ISSUE_OUT_OF_RANGE),
+            ("buttons[0].long_press.channel", ISSUE_OUT_OF_RANGE),
+            ("buttons[0].double_tap.program", ISSUE_REQUIRED),
+        ] {
+            assert!(found.contains(&expected), "{:?} missing from {:?}", expected, found);
+        }
+    }
+
+    #[test]
+    fn test_tap_tempo() {
+        let json = r#"{"label": "TAP", "cc": 64, "color": "red", "mode": "tap_tempo", "tap_tempo": {"output": "clock", "max_bpm": 200}}"#;
+        let button: ButtonConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(button.mode, ButtonMode::TapTempo);
+        let tap = button.tap_tempo.as_ref().unwrap();
+        assert_eq!((tap.output, tap.bpm_range()), (TapOutput::Clock, (DEFAULT_MIN_BPM, 200)));
+        let out = serde_json::to_string(&button).unwrap();
+        assert!(out.contains(r#""mode":"tap_tempo","tap_tempo":{"output":"clock","max_bpm":200}"#), "{}", out);
+
+        let json = r#"{
+            "buttons": [
+                {"label": "A", "cc": 20, "color": "red", "mode": "tap_tempo", "tap_tempo": {"min_bpm": 10, "max_bpm": 400}},
+                {"label": "B", "cc": 21, "color": "red", "mode": "tap_tempo", "tap_tempo": {"min_bpm": 120, "max_bpm": 100}},
+                {"label": "C", "type": "pc", "program": 1, "color": "red", "mode": "tap_tempo"},
+                {"label": "D", "cc": 23, "color": "red", "mode": "tap_tempo", "cc_on": 64, "cc_off": 64},
+                {"label": "E", "cc": 24, "color": "red", "mode": "momentary", "cc_on": 64, "cc_off": 64}
+            ],
+            "encoder": {"enabled": true, "cc": 11, "label": "ENC", "push": {"enabled": true, "cc": 14, "label": "PUSH", "mode": "tap_tempo"}}
+        }"#;
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let errors = config.validate().unwrap_err();
+        let found: Vec<(&str, &str)> = errors.iter().map(|e| (e.path.as_str(), e.code.as_str())).collect();
+        for expected in [
+            ("buttons[0].tap_tempo.min_bpm", ISSUE_OUT_OF_RANGE),
+            ("buttons[0].tap_tempo.max_bpm", ISSUE_OUT_OF_RANGE),
+            ("buttons[1].tap_tempo.max_bpm", ISSUE_INVALID_RANGE),
+            ("buttons[2].mode", ISSUE_UNSUPPORTED),
+            ("buttons[4].cc_off", ISSUE_SAME_VALUE),
+            ("encoder.push.mode", ISSUE_UNSUPPORTED),
+        ] {
+            assert!(found.contains(&expected), "{:?} missing from {:?}", expected, found);
+        }
+        // Tap tempo never sends cc_off, so matching values are no problem
+        assert!(!found.iter().any(|(path, _)| path.starts_with("buttons[3]")), "{:?}", found);
+    }
+
+    #[test]
+    fn test_macro_steps() {
+        let json = r#"{"label": "GO", "type": "macro", "color": "red", "steps": [
+            {"type": "pc", "program": 5},
+            {"cc": 64, "value": 127, "delay_ms": 50}
+        ]}"#;
+        let button: ButtonConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(button.message_type, MessageType::Macro);
+        let steps = button.steps.as_ref().unwrap();
+        assert_eq!(steps[0].message_type, MacroStepType::Pc);
+        assert_eq!((steps[1].message_type, steps[1].delay_ms), (MacroStepType::Cc, Some(50)));
+        let out = serde_json::to_string(&button).unwrap();
+        assert!(out.contains(r#""steps":[{"type":"pc","program":5},{"type":"cc","cc":64,"value":127,"delay_ms":50}]"#), "{}", out);
+
+        let too_many: Vec<String> = (0..=MAX_MACRO_STEPS).map(|n| format!(r#"{{"type": "pc", "program": {}}}"#, n)).collect();
+        let json = format!(
+            r#"{{"buttons": [
+                {{"label": "A", "type": "macro", "color": "red"}},
+                {{"label": "B", "type": "macro", "color": "red", "steps": [{{"type": "note", "delay_ms": 6000}}, {{"cc": 1, "channel": 16}}]}},
+                {{"label": "C", "type": "macro", "color": "red", "steps": [{}]}}
+            ]}}"#,
+            too_many.join(", ")
+        );
+        let config: MidiCaptainConfig = serde_json::from_str(&json).unwrap();
+        let errors = config.validate().unwrap_err();
+        let found: Vec<(&str, &str)> = errors.iter().map(|e| (e.path.as_str(), e.code.as_str())).collect();
+        for expected in [
+            ("buttons[0].steps", ISSUE_REQUIRED),
+            ("buttons[1].steps[0].note", ISSUE_REQUIRED),
+            ("buttons[1].steps[0].delay_ms", ISSUE_OUT_OF_RANGE),
+            ("buttons[1].steps[1].channel", ISSUE_OUT_OF_RANGE),
+            ("buttons[2].steps", ISSUE_OUT_OF_RANGE),
+        ] {
+            assert!(found.contains(&expected), "{:?} missing from {:?}", expected, found);
+        }
+    }
+
+    #[test]
+    fn test_led_settings() {
+        let json = r#"{"label": "A", "cc": 20, "color": "red", "led": {"off_color": "white", "off_brightness": 10}}"#;
+        let button: ButtonConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(button.led_rgb(), ((255, 0, 0), (25, 25, 25)));
+        let out = serde_json::to_string(&button).unwrap();
+        assert!(out.contains(r#""led":{"off_color":"white","off_brightness":10}"#), "{}", out);
+
+        // Without `led`, off_mode decides as before
+        let button: ButtonConfig = serde_json::from_str(r#"{"label": "A", "color": "red", "off_mode": "off"}"#).unwrap();
+        assert_eq!(button.led_rgb(), ((255, 0, 0), (0, 0, 0)));
+
+        let json = r##"{
+            "buttons": [
+                {"label": "A", "cc": 20, "color": "red", "led": {"on_brightness": 101, "off_color": "#12"}},
+                {"label": "B", "cc": 21, "color": "red", "led": {"off_brightness": 100}}
+            ]
+        }"##;
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let errors = config.validate().unwrap_err();
+        let found: Vec<(&str, &str)> = errors.iter().map(|e| (e.path.as_str(), e.code.as_str())).collect();
+        for expected in [
+            ("buttons[0].led.on_brightness", ISSUE_OUT_OF_RANGE),
+            ("buttons[0].led.off_color", ISSUE_INVALID_COLOR),
+            ("buttons[1].led.off_brightness", ISSUE_SAME_VALUE),
+        ] {
+            assert!(found.contains(&expected), "{:?} missing from {:?}", expected, found);
+        }
+    }
+
+    #[test]
+    fn test_on_off_values() {
+        let json = r#"{
+            "buttons": [
+                {"label": "A", "cc": 20, "color": "red", "value_on": 64, "value_off": 0},
+                {"label": "B", "cc": 21, "color": "red", "cc_on": 64, "cc_off": 64},
+                {"label": "C", "cc": 22, "color": "red", "cc_off": 127},
+                {"label": "D", "type": "note", "color": "red", "cc_off": 127}
+            ]
+        }"#;
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        assert_eq!((config.buttons[0].cc_on, config.buttons[0].cc_off), (Some(64), Some(0)));
+        // Written back under the firmware's names
+        let written = serde_json::to_value(&config.buttons[0]).unwrap();
+        assert_eq!(written["cc_on"], 64);
+        assert!(written.get("value_on").is_none());
+
+        let errors = config.validate().unwrap_err();
+        let found: Vec<(&str, &str)> = errors.iter().map(|e| (e.path.as_str(), e.code.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![("buttons[1].cc_off", ISSUE_SAME_VALUE), ("buttons[2].cc_off", ISSUE_SAME_VALUE)]
+        );
+    }
+
+    #[test]
+    fn test_hex_colors() {
+        let json = r##"{
+            "buttons": [
+                {"label": "A", "cc": 20, "color": "red"},
+                {"label": "B", "cc": 21, "color": "#FF8800"},
+                {"label": "C", "cc": 22, "color": "#12345", "keytimes": 2, "states": [{"color": "pink"}]}
+            ]
+        }"##;
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.buttons[0].color, ButtonColor::Named(NamedColor::Red));
+        assert_eq!(config.buttons[1].color.rgb(), Some((255, 136, 0)));
+        // Palette colors are still written as plain names
+        assert_eq!(serde_json::to_value(&config.buttons[0].color).unwrap(), "red");
+        assert_eq!(serde_json::to_value(&config.buttons[1].color).unwrap(), "#FF8800");
+
+        let errors = config.validate().unwrap_err();
+        let found: Vec<(&str, &str)> = errors.iter().map(|e| (e.path.as_str(), e.code.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![("buttons[2].color", ISSUE_INVALID_COLOR), ("buttons[2].states[0].color", ISSUE_INVALID_COLOR)]
+        );
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#0a141E"), Some((10, 20, 30)));
+        for bad in ["0a141e", "#0a141", "#0a141e0", "#gg0000", "#+a141e"] {
+            assert_eq!(parse_hex_color(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_global_settings() {
+        let json = r#"{
+            "global": {"led_brightness": 80, "display_brightness": 101, "sleep_minutes": 241},
+            "buttons": []
+        }"#;
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let global = config.global.as_ref().unwrap();
+        assert_eq!(global.led_brightness, Some(80));
+
+        let errors = config.validate().unwrap_err();
+        let found: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert!(found.contains(&"global.display_brightness"), "{:?}", found);
+        assert!(found.contains(&"global.sleep_minutes"), "{:?}", found);
+        assert!(!found.contains(&"global.led_brightness"), "{:?}", found);
+
+        // Absent by default, and not written
+        let default = generate_default_config(DeviceType::Std10);
+        assert!(serde_json::to_value(&default).unwrap().get("global").is_none());
+    }
+
+    #[test]
+    fn test_encoder_acceleration_and_wrap() {
+        let json = r#"{
+            "buttons": [],
+            "encoder": {"enabled": true, "cc": 11, "label": "ENC", "min": 5, "max": 5, "initial": 5,
+                        "steps": null, "push": null, "acceleration": "high", "wrap": true}
+        }"#;
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let enc = config.encoder.as_ref().unwrap();
+        assert_eq!((enc.acceleration, enc.wrap), (Acceleration::High, true));
+        let errors = config.validate().unwrap_err();
+        assert!(errors.iter().any(|e| e.path == "encoder.wrap" && e.code == ISSUE_INVALID_RANGE), "{:?}", errors);
+
+        // Defaults are left out of the written config
+        let default = serde_json::to_value(&generate_default_config(DeviceType::Std10).encoder.unwrap()).unwrap();
+        assert!(default.get("acceleration").is_none());
+        assert!(default.get("wrap").is_none());
+    }
+
+    #[test]
+    fn test_expression_calibration() {
+        let json = r#"{
+            "device": "std10",
+            "buttons": [],
+            "expression": {
+                "exp1": {"enabled": true, "cc": 12, "label": "EXP1", "calibration": {"raw_min": 1200, "raw_max": 64000}},
+                "exp2": {"enabled": true, "cc": 13, "label": "EXP2", "calibration": {"raw_min": 5000, "raw_max": 5000}}
+            }
+        }"#;
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let exp = config.expression.as_ref().unwrap();
+        assert_eq!(exp.exp1.calibration, Some(Calibration { raw_min: 1200, raw_max: 64000 }));
+        let errors = config.validate().unwrap_err();
+        let calibration: Vec<_> = errors.iter().filter(|e| e.path.ends_with(".calibration")).collect();
+        assert_eq!(calibration.len(), 1, "{:?}", errors);
+        assert_eq!(calibration[0].path, "expression.exp2.calibration");
+        assert_eq!(calibration[0].code, ISSUE_INVALID_RANGE);
+
+        // Uncalibrated pedals leave the field out
+        let default = serde_json::to_value(&generate_default_config(DeviceType::Std10).expression.unwrap()).unwrap();
+        assert!(default["exp1"].get("calibration").is_none());
+    }
+
+    #[test]
+    fn test_transliterate() {
+        assert_eq!(transliterate("Café"), "Cafe");
+        assert_eq!(transliterate("Straße"), "Strasse");
+        assert_eq!(transliterate("\u{201c}Lead\u{201d} \u{2013} Ø"), "\"Lead\" - O");
+        assert_eq!(transliterate("\u{fb01}x\u{2026}"), "fix...");
+        assert_eq!(transliterate("🎸"), "");
+    }
+
+    #[test]
+    fn test_label_charset() {
+        let mut config = generate_default_config(DeviceType::Std10);
+        config.buttons[0].label = "Café".to_string();
+        config.buttons[1].label = "🎸".to_string();
+        config.encoder.as_mut().unwrap().label = "Ω".to_string();
+        let errors = config.validate().unwrap_err();
+        let issues: Vec<(&str, &str)> = errors.iter().map(|e| (e.path.as_str(), e.code.as_str())).collect();
+        assert_eq!(
+            issues,
+            vec![
+                ("buttons[0].label", ISSUE_UNSUPPORTED_CHARS),
+                ("buttons[1].label", ISSUE_UNSUPPORTED_CHARS),
+                ("encoder.label", ISSUE_UNSUPPORTED_CHARS),
+            ]
+        );
+        assert_eq!(errors[0].message, "Button 1 label 'Café' has characters the display can't show (é); try 'Cafe'");
+        assert_eq!(errors[1].message, "Button 2 label '🎸' has characters the display can't show (🎸)");
+
+        config.sanitize_labels();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.buttons[0].label, "Cafe");
+    }
+
+    #[test]
+    fn test_expression_curves() {
+        let json = r#"{
+            "device": "std10",
+            "buttons": [],
+            "expression": {
+                "exp1": {"enabled": true, "cc": 12, "label": "EXP1", "curve": "exp"},
+                "exp2": {"enabled": true, "cc": 13, "label": "EXP2", "curve": [[0, 0], [64, 20], [127, 127]]}
+            }
+        }"#;
+        let config: MidiCaptainConfig = serde_json::from_str(json).unwrap();
+        let exp = config.expression.as_ref().unwrap();
+        assert_eq!(exp.exp1.curve, Some(Curve::Shape(CurveShape::Exp)));
+        assert_eq!(exp.exp2.curve, Some(Curve::Custom(vec![[0, 0], [64, 20], [127, 127]])));
+        assert!(config.validate().err().unwrap_or_default().iter().all(|e| e.code != ISSUE_INVALID_CURVE));
+
+        let reserialized = serde_json::to_value(&config).unwrap();
+        assert_eq!(reserialized["expression"]["exp1"]["curve"], "exp");
+        assert_eq!(reserialized["expression"]["exp2"]["curve"][1], serde_json::json!([64, 20]));
+    }
+
+    #[test]
+    fn test_curve_breakpoints_must_be_monotonic() {
+        assert_eq!(breakpoint_problem(&[[0, 0], [64, 20], [127, 127]]), None);
+        assert_eq!(breakpoint_problem(&[[0, 10], [127, 10]]), None, "flat runs are allowed");
+        assert!(breakpoint_problem(&[[0, 0]]).unwrap().contains("at least 2"));
+        assert!(breakpoint_problem(&[[0, 0], [64, 90], [64, 100], [127, 127]]).unwrap().contains("travel must increase"));
+        assert!(breakpoint_problem(&[[0, 0], [64, 90], [127, 60]]).unwrap().contains("must not decrease"));
+        assert!(breakpoint_problem(&[[10, 0], [127, 127]]).unwrap().contains("from travel 0 to 127"));
+        assert!(breakpoint_problem(&[[0, 0], [127, 200]]).unwrap().contains("outside 0-127"));
+
+        let mut config = generate_default_config(DeviceType::Std10);
+        config.expression.as_mut().unwrap().exp1.curve = Some(Curve::Custom(vec![[0, 100], [127, 0]]));
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!((errors[0].path.as_str(), errors[0].code.as_str()), ("expression.exp1.curve", ISSUE_INVALID_CURVE));
+    }
 }
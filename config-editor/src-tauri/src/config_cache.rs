@@ -0,0 +1,123 @@
+//! In-memory cache of parsed device configs
+//!
+//! The UI re-reads the device config on tab switches and diff views, and each
+//! read goes over slow USB mass storage. Entries are keyed by path and
+//! validated against the file's modification time and size, so an edit made
+//! outside the editor is picked up on the next read. Our own writes and device
+//! disconnects drop entries explicitly, since FAT timestamps only have
+//! two-second resolution.
+
+use crate::commands::{content_token, ConfigError, ConfigFormat};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Parsed config plus the file stamp it was read at
+struct Entry {
+    modified: SystemTime,
+    len: u64,
+    value: Value,
+    /// `content_token` of the bytes read
+    token: String,
+}
+
+static CACHE: Mutex<Option<HashMap<PathBuf, Entry>>> = Mutex::new(None);
+
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Read a config, from the cache when the file hasn't changed since it was cached
+pub fn read(path: &Path) -> Result<Value, ConfigError> {
+    Ok(read_with_token(path)?.0)
+}
+
+/// `read`, plus the `content_token` of the file
+pub fn read_with_token(path: &Path) -> Result<(Value, String), ConfigError> {
+    let stamp = stamp(path);
+    if let Some((modified, len)) = stamp {
+        let cache = CACHE.lock().unwrap();
+        if let Some(entry) = cache.as_ref().and_then(|c| c.get(path)) {
+            if entry.modified == modified && entry.len == len {
+                return Ok((entry.value.clone(), entry.token.clone()));
+            }
+        }
+    }
+
+    let bytes = fs::read(path)?;
+    let value: Value = ConfigFormat::detect(&bytes).decode(&bytes)?;
+    let token = content_token(&bytes);
+    if let Some((modified, len)) = stamp {
+        CACHE.lock().unwrap().get_or_insert_with(HashMap::new).insert(
+            path.to_path_buf(),
+            Entry {
+                modified,
+                len,
+                value: value.clone(),
+                token: token.clone(),
+            },
+        );
+    }
+    Ok((value, token))
+}
+
+/// Drop the entry for one file (after writing it)
+pub fn invalidate(path: &Path) {
+    if let Some(cache) = CACHE.lock().unwrap().as_mut() {
+        cache.remove(path);
+    }
+}
+
+/// Drop every entry under a volume (on device disconnect)
+pub fn invalidate_under(root: &Path) {
+    if let Some(cache) = CACHE.lock().unwrap().as_mut() {
+        cache.retain(|path, _| !path.starts_with(root));
+    }
+}
+
+/// Drop every entry
+pub fn clear() {
+    *CACHE.lock().unwrap() = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(path: &Path) -> bool {
+        CACHE.lock().unwrap().as_ref().is_some_and(|c| c.contains_key(path))
+    }
+
+    #[test]
+    fn test_read_caches_and_invalidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{"buttons": []}"#).unwrap();
+
+        assert_eq!(read(&path).unwrap(), serde_json::json!({"buttons": []}));
+        assert!(cached(&path));
+
+        invalidate(&path);
+        assert!(!cached(&path));
+
+        read(&path).unwrap();
+        invalidate_under(dir.path());
+        assert!(!cached(&path));
+    }
+
+    #[test]
+    fn test_changed_file_is_reread() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{"buttons": []}"#).unwrap();
+        read(&path).unwrap();
+
+        // Different size, so the stamp changes even within the same second
+        fs::write(&path, r#"{"buttons": [], "global_channel": 2}"#).unwrap();
+        assert_eq!(read(&path).unwrap()["global_channel"], 2);
+    }
+}
@@ -0,0 +1,109 @@
+//! Detection of config.json changes made outside the editor
+//!
+//! The device watcher (device.rs) reports every change to a connected
+//! device's config.json here. The content hash of each config is remembered
+//! from the editor's own writes and the last change seen, so a change only
+//! counts as external when the bytes on disk differ from both — our own
+//! writes and repeated filesystem events for one save are filtered out.
+
+use crate::download::sha256_hex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Event emitted when a device's config.json changes outside the editor
+pub const EVENT: &str = "config-changed-externally";
+
+/// Payload of `config-changed-externally`
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ExternalConfigChange {
+    pub path: String,
+    /// SHA-256 of the new contents, hex; None when the file was deleted
+    pub hash: Option<String>,
+}
+
+/// Last known content hash per config path (None: known to be absent)
+static KNOWN: Mutex<Option<HashMap<PathBuf, Option<String>>>> = Mutex::new(None);
+
+fn hash_of(path: &Path) -> Option<String> {
+    std::fs::read(path).ok().map(|bytes| sha256_hex(&bytes))
+}
+
+/// Remember the contents the editor just wrote to `path`
+pub fn record_write(path: &Path, bytes: &[u8]) {
+    KNOWN
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(path.to_path_buf(), Some(sha256_hex(bytes)));
+}
+
+/// Start tracking `path` at its current contents (on device connect)
+pub fn track(path: &Path) {
+    let hash = hash_of(path);
+    KNOWN.lock().unwrap().get_or_insert_with(HashMap::new).insert(path.to_path_buf(), hash);
+}
+
+/// Stop tracking every config under a volume (on device disconnect)
+pub fn forget_under(root: &Path) {
+    if let Some(known) = KNOWN.lock().unwrap().as_mut() {
+        known.retain(|path, _| !path.starts_with(root));
+    }
+}
+
+/// Check `path` after a filesystem event. Returns the change when its
+/// contents differ from the last known ones, and remembers them.
+pub fn check(path: &Path) -> Option<ExternalConfigChange> {
+    let hash = hash_of(path);
+    let mut known = KNOWN.lock().unwrap();
+    let previous = known.get_or_insert_with(HashMap::new).insert(path.to_path_buf(), hash.clone());
+    if previous == Some(hash.clone()) {
+        return None;
+    }
+    crate::config_cache::invalidate(path);
+    Some(ExternalConfigChange {
+        path: path.display().to_string(),
+        hash,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_only_changes_from_elsewhere_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, "{}").unwrap();
+        track(&path);
+        assert_eq!(check(&path), None);
+
+        // Our own write
+        fs::write(&path, r#"{"buttons": []}"#).unwrap();
+        record_write(&path, br#"{"buttons": []}"#);
+        assert_eq!(check(&path), None);
+
+        // Another program
+        fs::write(&path, r#"{"buttons": [], "global_channel": 2}"#).unwrap();
+        let change = check(&path).unwrap();
+        assert_eq!(change.hash, Some(sha256_hex(br#"{"buttons": [], "global_channel": 2}"#)));
+        // A second event for the same save
+        assert_eq!(check(&path), None);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(check(&path).unwrap().hash, None);
+    }
+
+    #[test]
+    fn test_forget_under_volume() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, "{}").unwrap();
+        track(&path);
+        forget_under(dir.path());
+        // Untracked: the first look reports the file as new
+        assert!(check(&path).is_some());
+    }
+}
@@ -0,0 +1,179 @@
+//! Confirmation tokens for destructive commands
+//!
+//! Commands that can destroy what is on a device — restoring a recovery
+//! point, rolling back firmware, overwriting a locked config, deleting a
+//! recovery point — run in two steps. Called without a token they change
+//! nothing and return a token with a summary of what would happen; called
+//! again with that token they go ahead. A token is good for one call of the
+//! same operation on the same target within `TOKEN_TTL`, so a single stray
+//! or forged IPC message can't wipe a device.
+
+use crate::commands::ConfigError;
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::hash::BuildHasher;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// `ConfigError::code` of a token that is unknown, used or expired
+pub const CONFIRMATION_INVALID: &str = "confirmation_invalid";
+
+/// How long a token stays valid
+const TOKEN_TTL: Duration = Duration::from_secs(120);
+
+/// Outcome of a destructive command
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Confirmable<T> {
+    /// Nothing was done; call again with `token` to go ahead
+    ConfirmationRequired { token: String, summary: String },
+    Done { result: T },
+}
+
+impl<T> Confirmable<T> {
+    pub fn done(result: T) -> Self {
+        Confirmable::Done { result }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Confirmable<U> {
+        match self {
+            Confirmable::ConfirmationRequired { token, summary } => Confirmable::ConfirmationRequired { token, summary },
+            Confirmable::Done { result } => Confirmable::done(f(result)),
+        }
+    }
+
+    /// The result, for callers that can't ask anyone (scripts, the
+    /// quick-deploy hotkey); a pending confirmation is an error there
+    pub fn into_result(self) -> Result<T, ConfigError> {
+        match self {
+            Confirmable::Done { result } => Ok(result),
+            Confirmable::ConfirmationRequired { summary, .. } => Err(ConfigError {
+                message: format!("Needs confirmation in the editor: {}", summary),
+                details: None,
+                code: None,
+            }),
+        }
+    }
+}
+
+struct Pending {
+    action: String,
+    expires: Instant,
+}
+
+/// Issued tokens not yet used
+static PENDING: Mutex<BTreeMap<String, Pending>> = Mutex::new(BTreeMap::new());
+
+/// 128 unpredictable bits, hex-encoded. `RandomState` keys are seeded from
+/// the OS's random source.
+fn new_token() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let high = RandomState::new().hash_one(nanos);
+    let low = RandomState::new().hash_one(nanos);
+    format!("{:016x}{:016x}", high, low)
+}
+
+/// Issue a token for `action` (the operation and its target)
+fn issue(action: &str) -> String {
+    let token = new_token();
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    pending.retain(|_, p| p.expires > now);
+    pending.insert(
+        token.clone(),
+        Pending {
+            action: action.to_string(),
+            expires: now + TOKEN_TTL,
+        },
+    );
+    token
+}
+
+/// Use up `token`; true if it was issued for `action` and hasn't expired
+fn redeem(token: &str, action: &str) -> bool {
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    match pending.remove(token) {
+        Some(p) if p.action == action && p.expires > Instant::now() => true,
+        // A token for another operation stays usable for that one
+        Some(p) if p.action != action => {
+            pending.insert(token.to_string(), p);
+            false
+        }
+        _ => false,
+    }
+}
+
+/// Run `run` if `token` was issued for `action`; without a token, issue one
+/// along with `summary` and do nothing. `action` names the operation and its
+/// target, e.g. `["rollback_firmware", "/Volumes/CIRCUITPY"]`.
+pub fn confirm<T>(
+    action: &[&str],
+    summary: impl FnOnce() -> String,
+    token: Option<&str>,
+    run: impl FnOnce() -> Result<T, ConfigError>,
+) -> Result<Confirmable<T>, ConfigError> {
+    let action = action.join("\n");
+    match token {
+        None => Ok(Confirmable::ConfirmationRequired {
+            token: issue(&action),
+            summary: summary(),
+        }),
+        Some(token) if redeem(token, &action) => run().map(Confirmable::done),
+        Some(_) => Err(ConfigError {
+            message: "Confirmation expired or doesn't match this operation; nothing was changed. Try again.".to_string(),
+            details: None,
+            code: Some(CONFIRMATION_INVALID),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_of<T>(outcome: Confirmable<T>) -> String {
+        match outcome {
+            Confirmable::ConfirmationRequired { token, .. } => token,
+            Confirmable::Done { .. } => panic!("ran without confirmation"),
+        }
+    }
+
+    #[test]
+    fn test_runs_only_with_token() {
+        let mut runs = 0;
+        let first = confirm(&["wipe", "a"], || "Wipe a".to_string(), None, || {
+            runs += 1;
+            Ok(())
+        });
+        let token = token_of(first.unwrap());
+        assert_eq!(runs, 0);
+
+        let second = confirm(&["wipe", "a"], String::new, Some(&token), || {
+            runs += 1;
+            Ok(())
+        });
+        assert_eq!(second.unwrap(), Confirmable::done(()));
+        assert_eq!(runs, 1);
+
+        // Single use
+        let again = confirm(&["wipe", "a"], String::new, Some(&token), || Ok(()));
+        assert_eq!(again.unwrap_err().code, Some(CONFIRMATION_INVALID));
+    }
+
+    #[test]
+    fn test_token_is_bound_to_its_target() {
+        let token = token_of(confirm(&["wipe", "a"], String::new, None, || Ok(())).unwrap());
+        assert!(confirm(&["wipe", "b"], String::new, Some(&token), || Ok(())).is_err());
+        assert!(confirm(&["wipe", "a"], String::new, Some(&token), || Ok(())).is_ok());
+        assert!(confirm(&["wipe", "a"], String::new, Some("made-up"), || Ok(())).is_err());
+    }
+
+    #[test]
+    fn test_tokens_are_distinct() {
+        assert_ne!(new_token(), new_token());
+        assert_eq!(new_token().len(), 32);
+    }
+}
@@ -0,0 +1,255 @@
+//! Crash-report capture for the backend
+//!
+//! A panic hook writes each backend panic to a JSON file under the app-data
+//! directory, tagged with the command that was running and a summary of its
+//! arguments (keys and value types only — no config contents or paths), so a
+//! failure the user can't reproduce still leaves something to debug.
+//!
+//! Command context is tracked per thread by the invoke handler wrapper, so it
+//! covers synchronous commands; panics in background threads are recorded
+//! without it.
+
+use crate::commands::{app_data_dir, ConfigError};
+use crate::firmware::unix_now;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::OnceLock;
+use tauri::{command, AppHandle};
+
+/// Directory under the app-data directory holding crash reports
+const CRASH_DIR: &str = "crashes";
+
+/// Reports kept on disk; older ones are deleted when a new one is written
+const MAX_REPORTS: usize = 20;
+
+/// Where the panic hook writes reports (set once at startup)
+static REPORT_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Distinguishes reports written within the same second
+static REPORT_SEQ: AtomicU32 = AtomicU32::new(0);
+
+thread_local! {
+    /// Command currently executing on this thread: (name, args summary)
+    static CURRENT_COMMAND: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+}
+
+/// A captured backend panic
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CrashReport {
+    /// Unix timestamp (seconds)
+    pub timestamp: u64,
+    pub app_version: String,
+    pub message: String,
+    /// Source location, e.g. "src/serial.rs:120:9"
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub command: Option<String>,
+    pub args_summary: Option<String>,
+    pub backtrace: String,
+}
+
+/// Describe a value's shape without its contents, e.g. `string(24)` or `object(3 keys)`
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(_) => "bool".to_string(),
+        Value::Number(_) => "number".to_string(),
+        Value::String(s) => format!("string({})", s.len()),
+        Value::Array(a) => format!("array({})", a.len()),
+        Value::Object(o) => format!("object({} keys)", o.len()),
+    }
+}
+
+/// Summarize command arguments as `name: shape` pairs
+pub fn summarize_args(args: &Value) -> String {
+    match args {
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k, describe(v)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => describe(other),
+    }
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    if let Some(s) = info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Build a report for a panic on the current thread
+fn build_report(info: &PanicHookInfo) -> CrashReport {
+    let context = CURRENT_COMMAND.with(|c| c.borrow().clone());
+    let (command, args_summary) = match context {
+        Some((name, args)) => (Some(name), Some(args)),
+        None => (None, None),
+    };
+    CrashReport {
+        timestamp: unix_now(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        message: panic_message(info),
+        location: info.location().map(|l| l.to_string()),
+        thread: std::thread::current().name().map(|n| n.to_string()),
+        command,
+        args_summary,
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+    }
+}
+
+/// Write a report into `dir`, pruning the oldest beyond `MAX_REPORTS`
+pub fn write_report(dir: &Path, report: &CrashReport) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let seq = REPORT_SEQ.fetch_add(1, Ordering::Relaxed);
+    let path = dir.join(format!("crash-{}-{}.json", report.timestamp, seq));
+    fs::write(&path, serde_json::to_string_pretty(report)?)?;
+
+    let mut files = report_files(dir);
+    while files.len() > MAX_REPORTS {
+        let _ = fs::remove_file(files.remove(0));
+    }
+    Ok(path)
+}
+
+/// Sort key for `crash-<timestamp>-<seq>.json`
+fn report_key(path: &Path) -> Option<(u64, u32)> {
+    let stem = path.file_stem()?.to_str()?.strip_prefix("crash-")?;
+    let (timestamp, seq) = stem.split_once('-')?;
+    Some((timestamp.parse().ok()?, seq.parse().ok()?))
+}
+
+/// Report files in `dir`, oldest first
+fn report_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<(u64, u32, PathBuf)> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter_map(|p| report_key(&p).map(|(ts, seq)| (ts, seq, p)))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files.into_iter().map(|(_, _, p)| p).collect()
+}
+
+/// Load all reports in `dir`, newest first
+pub fn load_reports(dir: &Path) -> Vec<CrashReport> {
+    report_files(dir)
+        .iter()
+        .rev()
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .filter_map(|s| serde_json::from_str(&s).ok())
+        .collect()
+}
+
+/// Install the panic hook. Reports go to `dir`; the default hook still runs
+/// afterwards so panics continue to reach stderr.
+pub fn install(dir: PathBuf) {
+    let _ = REPORT_DIR.set(dir);
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if let Some(dir) = REPORT_DIR.get() {
+            let _ = write_report(dir, &build_report(info));
+        }
+        previous(info);
+    }));
+}
+
+/// Record the running command for this thread while `f` runs
+pub fn with_command_context<T>(name: &str, args: &Value, f: impl FnOnce() -> T) -> T {
+    CURRENT_COMMAND.with(|c| *c.borrow_mut() = Some((name.to_string(), summarize_args(args))));
+    let result = f();
+    CURRENT_COMMAND.with(|c| *c.borrow_mut() = None);
+    result
+}
+
+/// Invoke handler wrapper: tags panics with the command context and keeps a
+/// panicking command from taking down the whole app.
+pub fn guard_invoke(invoke: tauri::ipc::Invoke, handler: &(dyn Fn(tauri::ipc::Invoke) -> bool + Send + Sync)) -> bool {
+    let name = invoke.message.command().to_string();
+    let args = match invoke.message.payload() {
+        tauri::ipc::InvokeBody::Json(value) => value.clone(),
+        tauri::ipc::InvokeBody::Raw(bytes) => Value::String(format!("<{} raw bytes>", bytes.len())),
+    };
+    with_command_context(&name, &args, || {
+        // The report was already written by the hook; swallow the unwind
+        panic::catch_unwind(AssertUnwindSafe(|| handler(invoke))).unwrap_or(true)
+    })
+}
+
+/// Crash-report directory under the app-data directory
+pub(crate) fn crash_dir(app: &AppHandle) -> Result<PathBuf, ConfigError> {
+    Ok(app_data_dir(app)?.join(CRASH_DIR))
+}
+
+/// List captured crash reports, newest first
+#[command]
+pub fn get_crash_reports(app: AppHandle) -> Result<Vec<CrashReport>, ConfigError> {
+    Ok(load_reports(&crash_dir(&app)?))
+}
+
+/// Delete all captured crash reports
+#[command]
+pub fn clear_crash_reports(app: AppHandle) -> Result<(), ConfigError> {
+    let dir = crash_dir(&app)?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn report(timestamp: u64) -> CrashReport {
+        CrashReport {
+            timestamp,
+            app_version: "0.1.0".to_string(),
+            message: "boom".to_string(),
+            location: Some("src/serial.rs:1:1".to_string()),
+            thread: Some("main".to_string()),
+            command: Some("write_config".to_string()),
+            args_summary: Some("path: string(24)".to_string()),
+            backtrace: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_summarize_args_hides_values() {
+        let summary = summarize_args(&json!({"path": "/Volumes/CIRCUITPY/config.json", "force": true, "config": {"buttons": [], "device": "std10"}}));
+        assert_eq!(summary, "config: object(2 keys), force: bool, path: string(30)");
+        assert!(!summary.contains("CIRCUITPY"));
+    }
+
+    #[test]
+    fn test_command_context_is_cleared() {
+        with_command_context("read_config", &json!({"path": "x"}), || {
+            let ctx = CURRENT_COMMAND.with(|c| c.borrow().clone());
+            assert_eq!(ctx, Some(("read_config".to_string(), "path: string(1)".to_string())));
+        });
+        assert!(CURRENT_COMMAND.with(|c| c.borrow().is_none()));
+    }
+
+    #[test]
+    fn test_reports_roundtrip_newest_first_and_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        for ts in 0..(MAX_REPORTS as u64 + 3) {
+            write_report(dir.path(), &report(ts)).unwrap();
+        }
+        let reports = load_reports(dir.path());
+        assert_eq!(reports.len(), MAX_REPORTS);
+        assert!(reports[0].timestamp > reports[1].timestamp);
+        assert_eq!(reports[0].command.as_deref(), Some("write_config"));
+    }
+}
@@ -0,0 +1,362 @@
+//! CircuitPython library dependency resolver
+//!
+//! Parses the firmware's imports and compares them against the device's `lib/`
+//! folder, reporting libraries that are missing or older than the firmware needs.
+
+use crate::commands::{validate_device_path, ConfigError};
+use crate::firmware::read_circuitpython_version;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use tauri::command;
+
+/// Modules built into CircuitPython (never installed in `lib/`)
+const BUILTIN_MODULES: &[&str] = &[
+    "alarm", "analogio", "array", "atexit", "audiobusio", "audiocore", "audiomixer",
+    "audiopwmio", "binascii", "bitbangio", "bitmaptools", "board", "busio", "collections",
+    "countio", "digitalio", "displayio", "errno", "fontio", "framebufferio", "gc",
+    "hashlib", "io", "json", "keypad", "math", "microcontroller", "micropython", "msgpack",
+    "neopixel_write", "nvm", "os", "pulseio", "pwmio", "rainbowio", "random", "re",
+    "rotaryio", "rp2pio", "rtc", "select", "storage", "struct", "supervisor", "sys",
+    "terminalio", "time", "touchio", "traceback", "ulab", "usb_cdc", "usb_hid",
+    "usb_midi", "vectorio", "watchdog", "zlib",
+];
+
+/// A library the firmware needs, with the oldest version known to work.
+///
+/// Minimum versions match the libraries shipped in `firmware/dev/lib/`.
+pub struct LibraryRequirement {
+    pub name: &'static str,
+    pub min_version: &'static str,
+    /// Libraries this one imports internally (not visible from code.py)
+    pub depends_on: &'static [&'static str],
+}
+
+pub const REQUIRED_LIBRARIES: &[LibraryRequirement] = &[
+    LibraryRequirement { name: "adafruit_midi", min_version: "1.4.16", depends_on: &[] },
+    LibraryRequirement { name: "adafruit_display_text", min_version: "2.28.3", depends_on: &[] },
+    LibraryRequirement { name: "adafruit_bitmap_font", min_version: "2.0.1", depends_on: &[] },
+    LibraryRequirement { name: "adafruit_st7789", min_version: "1.5.17", depends_on: &[] },
+    LibraryRequirement { name: "neopixel", min_version: "6.3.9", depends_on: &["adafruit_pixelbuf"] },
+    LibraryRequirement { name: "adafruit_pixelbuf", min_version: "2.0.2", depends_on: &[] },
+    LibraryRequirement { name: "adafruit_debouncer", min_version: "2.0.6", depends_on: &["adafruit_ticks"] },
+    LibraryRequirement { name: "adafruit_ticks", min_version: "1.0.11", depends_on: &[] },
+];
+
+/// Adafruit library bundle release the minimum versions above were taken from.
+/// Pinned so installs are reproducible; bump deliberately alongside `firmware/dev/lib/`.
+pub const ADAFRUIT_BUNDLE_TAG: &str = "20231010";
+
+/// State of a single library on the device
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LibraryState {
+    Ok,
+    Missing,
+    Outdated,
+    /// Installed, but no version string could be read from the .mpy
+    Unknown,
+}
+
+/// Status of one library the firmware imports
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LibraryStatus {
+    pub name: String,
+    pub state: LibraryState,
+    pub installed_version: Option<String>,
+    pub required_version: Option<String>,
+    /// Firmware files (or other libraries) that import this library
+    pub required_by: Vec<String>,
+}
+
+/// Result of `check_firmware_dependencies`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DependencyReport {
+    pub circuitpython_version: Option<String>,
+    pub libraries: Vec<LibraryStatus>,
+    pub missing: Vec<String>,
+    pub outdated: Vec<String>,
+    /// Adafruit bundle matching the device's CircuitPython major version
+    pub bundle_url: Option<String>,
+}
+
+/// Extract top-level module names from Python `import` / `from ... import` lines.
+/// Relative imports (`from . import x`) are ignored.
+pub fn parse_imports(source: &str) -> BTreeSet<String> {
+    let mut modules = BTreeSet::new();
+    for line in source.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("import ") {
+            for part in rest.split(',') {
+                if let Some(name) = part.split_whitespace().next() {
+                    if let Some(top) = name.split('.').next().filter(|s| !s.is_empty()) {
+                        modules.insert(top.to_string());
+                    }
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix("from ") {
+            if let Some(name) = rest.split_whitespace().next() {
+                if !name.starts_with('.') {
+                    if let Some(top) = name.split('.').next() {
+                        modules.insert(top.to_string());
+                    }
+                }
+            }
+        }
+    }
+    modules
+}
+
+/// Parse a dotted `major.minor.patch` version
+fn parse_version(v: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = v.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    let patch = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+/// True if `installed` is older than `required`. Unparseable versions are never "outdated".
+pub fn is_outdated(installed: &str, required: &str) -> bool {
+    match (parse_version(installed), parse_version(required)) {
+        (Some(i), Some(r)) => i < r,
+        _ => false,
+    }
+}
+
+/// Find the embedded `__version__` string in compiled .mpy bytes.
+///
+/// Adafruit libraries store their version as a plain string constant, so the
+/// first `x.y.z` run of ASCII digits and dots is the library version.
+pub fn extract_mpy_version(bytes: &[u8]) -> Option<String> {
+    bytes
+        .split(|b| !(b.is_ascii_digit() || *b == b'.'))
+        .filter_map(|run| std::str::from_utf8(run).ok())
+        .find(|s| parse_version(s).is_some())
+        .map(|s| s.to_string())
+}
+
+/// Read the installed version of a library in `lib/` (single .mpy or package dir)
+fn installed_library_version(lib_dir: &Path, name: &str) -> Option<Option<String>> {
+    let single = lib_dir.join(format!("{}.mpy", name));
+    if single.is_file() {
+        return Some(fs::read(&single).ok().and_then(|b| extract_mpy_version(&b)));
+    }
+    if lib_dir.join(format!("{}.py", name)).is_file() {
+        return Some(None);
+    }
+    let package = lib_dir.join(name);
+    if package.is_dir() {
+        let init = package.join("__init__.mpy");
+        let version = fs::read(&init).ok().and_then(|b| extract_mpy_version(&b)).or_else(|| {
+            fs::read_dir(&package).ok()?.flatten().find_map(|e| {
+                let path = e.path();
+                if path.extension().is_some_and(|x| x == "mpy") {
+                    fs::read(&path).ok().and_then(|b| extract_mpy_version(&b))
+                } else {
+                    None
+                }
+            })
+        });
+        return Some(version);
+    }
+    None
+}
+
+/// Names of modules provided by the firmware itself (root files and package dirs).
+/// Includes modules inside those packages, since boot.py imports `config` from /core directly.
+fn local_modules(device_root: &Path) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    add_module_stems(device_root, &mut names);
+    if let Ok(entries) = fs::read_dir(device_root) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() && name != "lib" && !name.starts_with('.') {
+                add_module_stems(&path, &mut names);
+                names.insert(name);
+            }
+        }
+    }
+    names
+}
+
+/// Add the stem of every .py/.mpy file in `dir` to `names`
+fn add_module_stems(dir: &Path, names: &mut BTreeSet<String>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|x| x == "py" || x == "mpy") {
+                if let Some(stem) = path.file_stem() {
+                    names.insert(stem.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+}
+
+/// Python sources shipped with the firmware (code.py, boot.py, and package modules)
+fn firmware_sources(device_root: &Path) -> Vec<(String, String)> {
+    let mut sources = Vec::new();
+    for name in ["code.py", "boot.py"] {
+        if let Ok(src) = fs::read_to_string(device_root.join(name)) {
+            sources.push((name.to_string(), src));
+        }
+    }
+    for dir in ["core", "devices"] {
+        if let Ok(entries) = fs::read_dir(device_root.join(dir)) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|x| x == "py") {
+                    if let Ok(src) = fs::read_to_string(&path) {
+                        let file = format!("{}/{}", dir, entry.file_name().to_string_lossy());
+                        sources.push((file, src));
+                    }
+                }
+            }
+        }
+    }
+    sources
+}
+
+/// Adafruit bundle URL for a CircuitPython version (e.g. "7.3.3" → 7.x mpy bundle)
+pub fn adafruit_bundle_url(circuitpython_version: &str, tag: &str) -> Option<String> {
+    let major = circuitpython_version.split('.').next()?.parse::<u32>().ok()?;
+    Some(format!(
+        "https://github.com/adafruit/Adafruit_CircuitPython_Bundle/releases/download/{tag}/adafruit-circuitpython-bundle-{major}.x-mpy-{tag}.zip"
+    ))
+}
+
+/// Build a dependency report for the firmware installed at `device_root`
+pub fn check_dependencies(device_root: &Path) -> DependencyReport {
+    let local = local_modules(device_root);
+    let lib_dir = device_root.join("lib");
+
+    // library name → files that import it
+    let mut needed: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (file, source) in firmware_sources(device_root) {
+        for module in parse_imports(&source) {
+            if BUILTIN_MODULES.contains(&module.as_str()) || local.contains(&module) {
+                continue;
+            }
+            needed.entry(module).or_default().insert(file.clone());
+        }
+    }
+
+    // Pull in libraries that required libraries import internally
+    let mut pending: Vec<String> = needed.keys().cloned().collect();
+    while let Some(name) = pending.pop() {
+        if let Some(req) = REQUIRED_LIBRARIES.iter().find(|r| r.name == name) {
+            for dep in req.depends_on {
+                let entry = needed.entry(dep.to_string()).or_default();
+                if entry.insert(name.clone()) && entry.len() == 1 {
+                    pending.push(dep.to_string());
+                }
+            }
+        }
+    }
+
+    let mut libraries = Vec::new();
+    let mut missing = Vec::new();
+    let mut outdated = Vec::new();
+    for (name, required_by) in needed {
+        let required_version = REQUIRED_LIBRARIES
+            .iter()
+            .find(|r| r.name == name)
+            .map(|r| r.min_version.to_string());
+        let (state, installed_version) = match installed_library_version(&lib_dir, &name) {
+            None => (LibraryState::Missing, None),
+            Some(None) => (LibraryState::Unknown, None),
+            Some(Some(v)) => match &required_version {
+                Some(req) if is_outdated(&v, req) => (LibraryState::Outdated, Some(v)),
+                _ => (LibraryState::Ok, Some(v)),
+            },
+        };
+        match state {
+            LibraryState::Missing => missing.push(name.clone()),
+            LibraryState::Outdated => outdated.push(name.clone()),
+            _ => {}
+        }
+        libraries.push(LibraryStatus {
+            name,
+            state,
+            installed_version,
+            required_version,
+            required_by: required_by.into_iter().collect(),
+        });
+    }
+
+    let circuitpython_version = read_circuitpython_version(device_root);
+    let bundle_url = circuitpython_version
+        .as_deref()
+        .and_then(|v| adafruit_bundle_url(v, ADAFRUIT_BUNDLE_TAG));
+
+    DependencyReport {
+        circuitpython_version,
+        libraries,
+        missing,
+        outdated,
+        bundle_url,
+    }
+}
+
+/// Report missing or outdated CircuitPython libraries on a device
+#[command]
+pub fn check_firmware_dependencies(device_path: String) -> Result<DependencyReport, ConfigError> {
+    Ok(check_dependencies(&validate_device_path(&device_path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_imports() {
+        let src = "import board\nimport digitalio as dio, time\nfrom adafruit_midi.note_on import NoteOn\nfrom . import local\n    import json\n";
+        let imports = parse_imports(src);
+        let expected: BTreeSet<String> =
+            ["board", "digitalio", "time", "adafruit_midi", "json"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(imports, expected);
+    }
+
+    #[test]
+    fn test_extract_mpy_version() {
+        let bytes = b"C\x05\x02\x1f*adafruit_debouncer.py\x00\x0a2.0.6\x00__version__";
+        assert_eq!(extract_mpy_version(bytes).as_deref(), Some("2.0.6"));
+        assert_eq!(extract_mpy_version(b"no version here 1.2"), None);
+    }
+
+    #[test]
+    fn test_is_outdated() {
+        assert!(is_outdated("1.4.9", "1.4.16"));
+        assert!(!is_outdated("2.0.0", "1.4.16"));
+        assert!(!is_outdated("garbage", "1.0.0"));
+    }
+
+    #[test]
+    fn test_bundle_url_uses_major_version() {
+        let url = adafruit_bundle_url("7.3.3", "20231010").unwrap();
+        assert!(url.ends_with("adafruit-circuitpython-bundle-7.x-mpy-20231010.zip"));
+    }
+
+    #[test]
+    fn test_check_dependencies_reports_missing_and_outdated() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("code.py"), "import board\nimport neopixel\nfrom adafruit_midi.note_on import NoteOn\nfrom core.config import load\n").unwrap();
+        fs::create_dir_all(root.join("core")).unwrap();
+        fs::write(root.join("core").join("config.py"), "import json\n").unwrap();
+        fs::create_dir_all(root.join("lib").join("adafruit_midi")).unwrap();
+        fs::write(root.join("lib").join("adafruit_midi").join("__init__.mpy"), b"\x00\x061.0.0\x00").unwrap();
+        fs::write(root.join("lib").join("neopixel.mpy"), b"\x00\x066.3.9\x00").unwrap();
+
+        let report = check_dependencies(root);
+        assert_eq!(report.outdated, vec!["adafruit_midi".to_string()]);
+        // neopixel pulls in adafruit_pixelbuf, which is absent
+        assert_eq!(report.missing, vec!["adafruit_pixelbuf".to_string()]);
+        assert!(!report.libraries.iter().any(|l| l.name == "core" || l.name == "board"));
+    }
+}
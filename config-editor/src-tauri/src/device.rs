@@ -2,23 +2,104 @@
 
 #[cfg(not(target_os = "windows"))]
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher, Event, EventKind};
-use std::path::PathBuf;
+use crate::config::{DeviceType, MidiCaptainConfig, CONFIG_SCHEMA_VERSION};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Sender, Receiver};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use std::time::Duration;
+use crate::commands::{app_data_dir, ConfigError};
 use tauri::{command, AppHandle, Emitter};
 
-#[cfg(target_os = "windows")]
-use std::collections::HashSet;
+/// Volume names a device mounts as out of the box; always known
+const DEFAULT_VOLUMES: &[&str] = &["CIRCUITPY", "MIDICAPTAIN"];
+
+/// File under the app-data directory holding the volume names the user added
+const VOLUMES_FILE: &str = "volumes.json";
+
+/// Volume names the user added (renamed drives, custom firmware labels)
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+struct VolumeSettings {
+    #[serde(default)]
+    names: Vec<String>,
+}
+
+fn load_volume_settings(data_dir: &Path) -> VolumeSettings {
+    std::fs::read_to_string(data_dir.join(VOLUMES_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// The default volume names followed by those added in `data_dir`
+fn known_volumes_in(data_dir: Option<&Path>) -> Vec<String> {
+    let mut names: Vec<String> = DEFAULT_VOLUMES.iter().map(|v| v.to_string()).collect();
+    names.extend(data_dir.map(load_volume_settings).unwrap_or_default().names);
+    names
+}
+
+/// Volume names recognized as a device without looking at its config
+pub fn known_volumes() -> Vec<String> {
+    known_volumes_in(crate::registry::data_dir())
+}
+
+/// True if `name` is one of `known_volumes()`
+pub(crate) fn is_known_volume_name(name: &str) -> bool {
+    known_volumes().iter().any(|v| crate::sanitize::same_name(name, v))
+}
+
+/// Add `name` to the volume names in `data_dir`; a name already known
+/// (ignoring case and Unicode composition) is not added twice
+fn add_volume_in(data_dir: &Path, name: &str) -> Result<Vec<String>, ConfigError> {
+    let name = crate::sanitize::nfc(name.trim());
+    if name.is_empty() || name.chars().any(|c| c.is_control() || std::path::is_separator(c)) {
+        return Err(ConfigError {
+            message: format!("'{}' is not a volume name", name.escape_debug()),
+            details: None,
+            code: None,
+        });
+    }
+    if !known_volumes_in(Some(data_dir)).iter().any(|v| crate::sanitize::same_name(&name, v)) {
+        let mut settings = load_volume_settings(data_dir);
+        settings.names.push(name);
+        std::fs::write(data_dir.join(VOLUMES_FILE), serde_json::to_string_pretty(&settings)?)?;
+    }
+    Ok(known_volumes_in(Some(data_dir)))
+}
+
+/// Remove an added volume name from `data_dir`. The defaults stay.
+fn remove_volume_in(data_dir: &Path, name: &str) -> Result<Vec<String>, ConfigError> {
+    let mut settings = load_volume_settings(data_dir);
+    settings.names.retain(|v| !crate::sanitize::same_name(name, v));
+    std::fs::write(data_dir.join(VOLUMES_FILE), serde_json::to_string_pretty(&settings)?)?;
+    Ok(known_volumes_in(Some(data_dir)))
+}
+
+/// Volume names recognized as a device: CIRCUITPY and MIDICAPTAIN, then
+/// any added with `add_known_volume`
+#[command]
+pub fn get_known_volumes(app: AppHandle) -> Result<Vec<String>, ConfigError> {
+    Ok(known_volumes_in(Some(&app_data_dir(&app)?)))
+}
+
+/// Recognize volumes named `name` as devices (a renamed drive, or custom
+/// firmware with another label). Returns the updated list.
+#[command]
+pub fn add_known_volume(app: AppHandle, name: String) -> Result<Vec<String>, ConfigError> {
+    add_volume_in(&app_data_dir(&app)?, &name)
+}
 
-/// Known device volume names
-const DEVICE_VOLUMES: &[&str] = &["CIRCUITPY", "MIDICAPTAIN"];
+/// Stop recognizing an added volume name. Returns the updated list.
+#[command]
+pub fn remove_known_volume(app: AppHandle, name: String) -> Result<Vec<String>, ConfigError> {
+    remove_volume_in(&app_data_dir(&app)?, &name)
+}
 
 /// Check if a path contains a recognizable MIDI Captain config.json by
-/// looking for the "device" key with value "std10" or "mini6".
+/// looking for the "device" key naming one of the `DeviceType` models.
 ///
-/// Used as a fallback when the volume name is not in `DEVICE_VOLUMES` —
+/// Used as a fallback when the volume name is not a known one —
 /// i.e., the user has configured a custom `usb_drive_name` in their config.
 pub fn is_midi_captain_config(config_path: &std::path::Path) -> bool {
     if !config_path.exists() {
@@ -26,7 +107,7 @@ pub fn is_midi_captain_config(config_path: &std::path::Path) -> bool {
     }
     let Ok(contents) = std::fs::read_to_string(config_path) else { return false };
     let Ok(value) = serde_json::from_str::<serde_json::Value>(&contents) else { return false };
-    matches!(value.get("device").and_then(|v| v.as_str()), Some("std10") | Some("mini6"))
+    value.get("device").is_some_and(|d| serde_json::from_value::<DeviceType>(d.clone()).is_ok())
 }
 
 /// Parse a config.json and return the explicitly declared `usb_drive_name` if set,
@@ -41,10 +122,7 @@ pub fn parse_midi_captain_config(config_path: &std::path::Path) -> Option<String
     }
     let contents = std::fs::read_to_string(config_path).ok()?;
     let value: serde_json::Value = serde_json::from_str(&contents).ok()?;
-    let device = value.get("device").and_then(|v| v.as_str())?;
-    if device != "std10" && device != "mini6" {
-        return None;
-    }
+    serde_json::from_value::<DeviceType>(value.get("device")?.clone()).ok()?;
     // Return usb_drive_name only if explicitly set — no default fallback
     value
         .get("usb_drive_name")
@@ -81,25 +159,71 @@ fn get_volumes_path() -> PathBuf {
     }
 }
 
-/// Scan for devices on Windows by checking all drive letters
+/// How long a single drive may take to answer before the scan moves on
 #[cfg(target_os = "windows")]
-fn scan_windows_drives() -> Vec<DetectedDevice> {
-    let mut devices = Vec::new();
-    
-    // Check drive letters A-Z
-    for letter in b'A'..=b'Z' {
-        let drive = format!("{}:\\", letter as char);
-        let path = PathBuf::from(&drive);
-        
-        if path.exists() {
-            // Get volume label
-            if let Some(device) = check_volume(&path) {
-                devices.push(device);
+const DRIVE_SCAN_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Drive letters present in a `GetLogicalDrives` bitmask (bit 0 = A:)
+#[cfg(target_os = "windows")]
+fn drive_letters(mask: u32) -> Vec<char> {
+    (0..26u8)
+        .filter(|i| mask & (1 << i) != 0)
+        .map(|i| (b'A' + i) as char)
+        .collect()
+}
+
+/// True if the drive is removable (CIRCUITPY mounts as a removable drive).
+/// Optical, network and fixed drives are skipped without touching them.
+#[cfg(target_os = "windows")]
+fn is_removable_drive(root: &str) -> bool {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+
+    let root: Vec<u16> = OsStr::new(root).encode_wide().chain(Some(0)).collect();
+    let drive_type = unsafe { winapi::um::fileapi::GetDriveTypeW(root.as_ptr()) };
+    drive_type == winapi::um::winbase::DRIVE_REMOVABLE
+}
+
+/// Scan for devices on Windows by checking all drive letters, calling
+/// `on_found` as each device answers.
+///
+/// Drives are probed in parallel, and a drive that doesn't answer within
+/// `DRIVE_SCAN_TIMEOUT` (a sleeping card reader, a stale mapping) is left
+/// behind so it can't stall the scan or the watcher thread.
+#[cfg(target_os = "windows")]
+fn scan_windows_drives(on_found: &mut dyn FnMut(DetectedDevice)) {
+    let mask = unsafe { winapi::um::fileapi::GetLogicalDrives() };
+    let (tx, rx) = mpsc::channel();
+    let mut pending = 0;
+
+    for letter in drive_letters(mask) {
+        let drive = format!("{}:\\", letter);
+        if !is_removable_drive(&drive) {
+            continue;
+        }
+        let tx = tx.clone();
+        pending += 1;
+        std::thread::spawn(move || {
+            let path = PathBuf::from(&drive);
+            let device = if path.exists() { check_volume(&path) } else { None };
+            let _ = tx.send(device);
+        });
+    }
+    drop(tx);
+
+    let deadline = std::time::Instant::now() + DRIVE_SCAN_TIMEOUT;
+    while pending > 0 {
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        match rx.recv_timeout(remaining) {
+            Ok(device) => {
+                pending -= 1;
+                if let Some(device) = device {
+                    on_found(device);
+                }
             }
+            Err(_) => break,
         }
     }
-    
-    devices
 }
 
 /// Detected device info
@@ -109,11 +233,161 @@ pub struct DetectedDevice {
     pub path: PathBuf,
     pub config_path: PathBuf,
     pub has_config: bool,
+    /// True if CircuitPython booted into safe mode (code.py is not running)
+    pub safe_mode: bool,
+    pub safe_mode_reason: Option<String>,
+    /// Config check run when the device connects (None from a plain scan or without a config)
+    pub validation: Option<ValidationSummary>,
+    /// Hardware model, as far as the files on the drive tell; None when they don't
+    pub model: Option<DeviceType>,
+    /// What `model` was read from; None when it is unknown
+    pub model_source: Option<ModelSource>,
+}
+
+/// Model named by a config `device` value or a board ID fragment
+fn model_named(name: &str) -> Option<DeviceType> {
+    let name = name.to_ascii_lowercase();
+    DeviceType::ALL
+        .into_iter()
+        .find(|model| name.contains(&model.display_name().to_ascii_lowercase()))
+}
+
+/// Model with `count` NeoPixels (three per switch). The Blue has as many as
+/// the STD10, which is taken as the likelier.
+fn model_with_leds(count: u32) -> Option<DeviceType> {
+    DeviceType::ALL
+        .into_iter()
+        .find(|model| model.button_count() as u32 * 3 == count)
+}
+
+/// Where a device's model was read from, most reliable first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelSource {
+    /// The `device` field of config.json
+    Config,
+    /// The board ID in boot_out.txt (MIDI Captain board definitions)
+    BootOut,
+    /// The LED count in a single-model code.py (the stock firmware)
+    Firmware,
+}
+
+/// `LED_count = N` / `LED_COUNT = N` in code.py. This firmware reads it from
+/// devices/*.py at runtime, so only single-model firmware has one.
+fn firmware_led_count(code: &str) -> Option<u32> {
+    code.lines().find_map(|line| {
+        let (name, value) = line.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("led_count") {
+            return None;
+        }
+        value.split('#').next()?.trim().parse().ok()
+    })
+}
+
+/// Work out which model is mounted at `volume` from config.json's `device`,
+/// then boot_out.txt's board ID, then code.py. None when none of them tell.
+pub fn detect_model(volume: &Path) -> Option<(DeviceType, ModelSource)> {
+    let read = |name: &str| std::fs::read_to_string(volume.join(name)).ok();
+    let from_config = read("config.json")
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+        .and_then(|config| config["device"].as_str().and_then(model_named));
+    let from_boot_out = || {
+        read("boot_out.txt")
+            .and_then(|text| crate::device_info::parse_boot_out(&text).board_id)
+            .filter(|id| id.contains("captain"))
+            .and_then(|id| model_named(&id))
+    };
+    let from_firmware = || {
+        read("code.py")
+            .and_then(|code| firmware_led_count(&code))
+            .and_then(model_with_leds)
+    };
+    from_config
+        .map(|m| (m, ModelSource::Config))
+        .or_else(|| from_boot_out().map(|m| (m, ModelSource::BootOut)))
+        .or_else(|| from_firmware().map(|m| (m, ModelSource::Firmware)))
+}
+
+/// Result of validating a device's config.json on connect
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ValidationSummary {
+    pub valid: bool,
+    pub error_count: usize,
+    pub errors: Vec<String>,
+    /// Schema version the config was validated against
+    pub schema_version: u32,
+}
+
+/// Read and validate a device config (through the read cache)
+pub fn validate_device_config(config_path: &Path) -> ValidationSummary {
+    let errors = match crate::config_cache::read(config_path).and_then(|mut value| {
+        crate::migrations::migrate(&mut value)?;
+        Ok(serde_json::from_value::<MidiCaptainConfig>(value)?)
+    }) {
+        Ok(config) => crate::config::issue_messages(config.validate().err().unwrap_or_default()),
+        Err(e) => vec![e.message],
+    };
+    ValidationSummary {
+        valid: errors.is_empty(),
+        error_count: errors.len(),
+        errors,
+        schema_version: CONFIG_SCHEMA_VERSION,
+    }
+}
+
+/// Attach a validation summary before announcing a connected device
+fn with_validation(mut device: DetectedDevice) -> DetectedDevice {
+    if device.has_config {
+        device.validation = Some(validate_device_config(&device.config_path));
+    }
+    device
+}
+
+/// Marker a `safemode.py` (CircuitPython 8.1+) can write with
+/// `supervisor.runtime.safe_mode_reason` before the drive is mounted.
+const SAFE_MODE_MARKER: &str = "safemode.txt";
+
+/// Extract the safe-mode reason from boot_out.txt or console output, if the
+/// text says the board is in safe mode.
+///
+/// CircuitPython prints "You are in safe mode because:" followed by the reason
+/// on the next non-empty line.
+pub fn parse_safe_mode_reason(text: &str) -> Option<String> {
+    let mut lines = text.lines().map(|l| l.trim());
+    while let Some(line) = lines.next() {
+        let lower = line.to_ascii_lowercase();
+        if !lower.contains("safe mode") {
+            continue;
+        }
+        // Reason may follow on the same line ("...safe mode because: <reason>")
+        if let Some((_, rest)) = line.split_once("because:") {
+            if !rest.trim().is_empty() {
+                return Some(rest.trim().to_string());
+            }
+        }
+        let reason = lines
+            .find(|l| !l.is_empty())
+            .unwrap_or("Unknown reason")
+            .to_string();
+        return Some(reason);
+    }
+    None
+}
+
+/// Detect whether the device on `volume` is running in CircuitPython safe mode.
+/// Returns the reason if it is.
+pub fn detect_safe_mode(volume: &std::path::Path) -> Option<String> {
+    if let Ok(marker) = std::fs::read_to_string(volume.join(SAFE_MODE_MARKER)) {
+        let reason = marker.trim();
+        return Some(if reason.is_empty() { "Unknown reason".to_string() } else { reason.to_string() });
+    }
+    let boot_out = std::fs::read_to_string(volume.join("boot_out.txt")).ok()?;
+    parse_safe_mode_reason(&boot_out)
 }
 
 /// Get the volume name for a given path
 #[cfg(target_os = "windows")]
-fn get_volume_name(path: &PathBuf) -> Option<String> {
+pub(crate) fn get_volume_name(path: &Path) -> Option<String> {
     use std::os::windows::ffi::OsStrExt;
     use std::ffi::OsString;
     use std::os::windows::ffi::OsStringExt;
@@ -150,7 +424,7 @@ fn get_volume_name(path: &PathBuf) -> Option<String> {
 }
 
 #[cfg(not(target_os = "windows"))]
-fn get_volume_name(path: &PathBuf) -> Option<String> {
+pub(crate) fn get_volume_name(path: &Path) -> Option<String> {
     path.file_name()?.to_str().map(|s| s.to_string())
 }
 
@@ -159,57 +433,162 @@ fn get_volume_name(path: &PathBuf) -> Option<String> {
 /// Accepts:
 /// 1. Volumes with a known name (CIRCUITPY, MIDICAPTAIN), or
 /// 2. Volumes whose config.json identifies as a MIDI Captain device
-///    (has `"device": "std10"` or `"mini6"`).
+///    (its `"device"` names a model, e.g. `"std10"`).
 ///    This covers user-renamed drives (e.g. renamed in Finder) where the
 ///    volume name no longer matches the default "MIDICAPTAIN".
-fn check_volume(path: &PathBuf) -> Option<DetectedDevice> {
+fn check_volume(path: &Path) -> Option<DetectedDevice> {
     let name = get_volume_name(path)?;
     let config_path = path.join("config.json");
     let has_config = config_path.exists();
 
-    let is_known_name = DEVICE_VOLUMES.iter().any(|v| name.eq_ignore_ascii_case(v));
+    let is_known_name = is_known_volume_name(&name);
 
     if is_known_name || is_midi_captain_config(&config_path) {
+        let safe_mode_reason = detect_safe_mode(path);
+        let (model, model_source) = detect_model(path).unzip();
         Some(DetectedDevice {
             name: name.to_string(),
-            path: path.clone(),
+            path: path.to_path_buf(),
             config_path,
             has_config,
+            safe_mode: safe_mode_reason.is_some(),
+            safe_mode_reason,
+            validation: None,
+            model,
+            model_source,
         })
     } else {
         None
     }
 }
 
+/// Check every volume mounted under `volumes`, calling `on_found` for each device
+#[cfg(not(target_os = "windows"))]
+fn scan_volumes_dir(volumes: &Path, on_found: &mut dyn FnMut(DetectedDevice)) {
+    if let Ok(entries) = std::fs::read_dir(volumes) {
+        for entry in entries.flatten() {
+            if let Some(device) = check_volume(&entry.path()) {
+                on_found(device);
+            }
+        }
+    }
+}
+
+/// Probe every candidate volume or drive, calling `on_found` as each device
+/// is found
+fn scan_each(on_found: &mut dyn FnMut(DetectedDevice)) {
+    #[cfg(target_os = "windows")]
+    scan_windows_drives(on_found);
+
+    #[cfg(not(target_os = "windows"))]
+    scan_volumes_dir(&get_volumes_path(), on_found);
+}
+
 /// Scan for connected devices
 #[command]
 pub fn scan_devices() -> Vec<DetectedDevice> {
+    let mut devices = Vec::new();
+    scan_each(&mut |device| devices.push(device));
+    // Drives answer in any order; keep drive-letter order
     #[cfg(target_os = "windows")]
-    {
-        scan_windows_drives()
-    }
-    
-    #[cfg(not(target_os = "windows"))]
-    {
-        let volumes_path = get_volumes_path();
-        let mut devices = Vec::new();
-        
-        if let Ok(entries) = std::fs::read_dir(&volumes_path) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if let Some(device) = check_volume(&path) {
-                    devices.push(device);
-                }
-            }
-        }
-        
-        devices
-    }
+    devices.sort_by(|a, b| a.path.cmp(&b.path));
+    devices
+}
+
+/// A device found by `scan_devices_async`, emitted as "device-found"
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeviceFound {
+    pub scan_id: String,
+    pub device: DetectedDevice,
+}
+
+/// End of a `scan_devices_async` scan, emitted as "device-scan-complete"
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeviceScanComplete {
+    pub scan_id: String,
+    /// Devices found (one "device-found" event each)
+    pub found: usize,
+}
+
+/// Scan for connected devices in the background.
+///
+/// Returns at once; each device is emitted as "device-found" as soon as its
+/// volume has been probed, then "device-scan-complete" follows. `scan_id` is
+/// echoed in every event so the UI can drop results from a superseded scan.
+#[command]
+pub fn scan_devices_async(app: AppHandle, scan_id: String) {
+    std::thread::spawn(move || {
+        let mut found = 0;
+        scan_each(&mut |device| {
+            found += 1;
+            let _ = app.emit(
+                "device-found",
+                DeviceFound {
+                    scan_id: scan_id.clone(),
+                    device,
+                },
+            );
+        });
+        let _ = app.emit("device-scan-complete", DeviceScanComplete { scan_id, found });
+    });
 }
 
 // Global flag to prevent multiple watchers
 static WATCHER_STARTED: AtomicBool = AtomicBool::new(false);
 
+/// Devices the watcher knows are connected, by volume path: two devices can
+/// share a volume name (two "MIDICAPTAIN" drives on Windows)
+static CONNECTED: Mutex<BTreeMap<PathBuf, DetectedDevice>> = Mutex::new(BTreeMap::new());
+
+/// Payload of "device-disconnected"
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeviceDisconnected {
+    pub name: String,
+    /// Volume path, as in the device's "device-connected" event
+    pub path: PathBuf,
+}
+
+fn mark_connected(device: &DetectedDevice) {
+    if let Ok(mut connected) = CONNECTED.lock() {
+        connected.insert(device.path.clone(), device.clone());
+    }
+}
+
+/// Announce a newly connected device
+fn announce_connected(app: &AppHandle, device: DetectedDevice) {
+    mark_connected(&device);
+    crate::notifications::device_connected(&device);
+    let _ = app.emit("device-connected", with_validation(device));
+}
+
+/// Announce that the device at `path` is gone, dropping what was cached
+/// for it
+fn announce_disconnected(app: &AppHandle, path: &Path) {
+    crate::config_cache::invalidate_under(path);
+    crate::config_watch::forget_under(path);
+    let known = CONNECTED.lock().ok().and_then(|mut connected| connected.remove(path));
+    let name = known
+        .map(|d| d.name)
+        .or_else(|| path.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_default();
+    let _ = app.emit("device-disconnected", DeviceDisconnected { name, path: path.to_path_buf() });
+}
+
+/// Devices connected now: as the watcher tracks them, or a fresh scan when
+/// it isn't running
+pub fn connected_devices() -> Vec<DetectedDevice> {
+    if !WATCHER_STARTED.load(Ordering::SeqCst) {
+        return scan_devices();
+    }
+    CONNECTED.lock().map(|connected| connected.values().cloned().collect()).unwrap_or_default()
+}
+
+/// List every connected device, in volume path order
+#[command]
+pub fn list_connected_devices() -> Vec<DetectedDevice> {
+    connected_devices()
+}
+
 // Global shutdown signal sender (allows stopping the watcher thread)
 static SHUTDOWN_TX: Mutex<Option<Sender<()>>> = Mutex::new(None);
 
@@ -246,11 +625,14 @@ fn start_windows_watcher(app: AppHandle) -> Result<(), String> {
     
     // Spawn polling thread
     std::thread::spawn(move || {
-        let mut known_devices: HashSet<String> = HashSet::new();
+        // Keyed by drive, not volume name: two devices can share a name
+        let mut known_devices: HashSet<PathBuf> = HashSet::new();
         
         // Initial scan
-        for device in scan_windows_drives() {
-            known_devices.insert(device.name.clone());
+        for device in scan_devices() {
+            crate::config_watch::track(&device.config_path);
+            known_devices.insert(device.path.clone());
+            mark_connected(&device);
         }
         
         loop {
@@ -260,28 +642,31 @@ fn start_windows_watcher(app: AppHandle) -> Result<(), String> {
             }
             
             // Scan for devices
-            let current_devices = scan_windows_drives();
-            let current_names: HashSet<String> = 
-                current_devices.iter().map(|d| d.name.clone()).collect();
+            let current_devices = scan_devices();
+            let current_paths: HashSet<PathBuf> = 
+                current_devices.iter().map(|d| d.path.clone()).collect();
             
-            // Check for newly connected devices
+            // Check for newly connected devices and changed configs
             for device in current_devices {
-                if !known_devices.contains(&device.name) {
-                    let name = device.name.clone();
-                    let _ = app.emit("device-connected", device);
-                    known_devices.insert(name);
+                if known_devices.contains(&device.path) {
+                    if let Some(change) = crate::config_watch::check(&device.config_path) {
+                        let _ = app.emit(crate::config_watch::EVENT, change);
+                    }
+                } else {
+                    crate::config_watch::track(&device.config_path);
+                    known_devices.insert(device.path.clone());
+                    announce_connected(&app, device);
                 }
             }
             
             // Check for disconnected devices
-            let disconnected: Vec<String> = known_devices
-                .difference(&current_names)
+            let disconnected: Vec<PathBuf> = known_devices
+                .difference(&current_paths)
                 .cloned()
                 .collect();
-            
-            for name in disconnected {
-                let _ = app.emit("device-disconnected", name.clone());
-                known_devices.remove(&name);
+            for path in disconnected {
+                announce_disconnected(&app, &path);
+                known_devices.remove(&path);
             }
             
             // Poll every 2 seconds
@@ -295,17 +680,22 @@ fn start_windows_watcher(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-/// Unix-specific watcher using filesystem events
+/// A device coming or going, as reported by the Unix watcher
 #[cfg(not(target_os = "windows"))]
-fn start_unix_watcher(app: AppHandle) -> Result<(), String> {
+#[derive(Debug)]
+enum VolumeEvent {
+    Connected(DetectedDevice),
+    /// Volume path of a device reported connected (or already mounted) earlier
+    Disconnected(PathBuf),
+    /// Something wrote, replaced or deleted a device's config.json
+    ConfigTouched(PathBuf),
+}
+
+/// Watch `volumes` (`get_volumes_path()`, or a temp directory in tests) for
+/// volumes appearing and disappearing
+#[cfg(not(target_os = "windows"))]
+fn watch_volumes(volumes: &Path) -> Result<(RecommendedWatcher, Receiver<Event>), String> {
     let (tx, rx) = mpsc::channel();
-    let (shutdown_tx, shutdown_rx): (Sender<()>, Receiver<()>) = mpsc::channel();
-    
-    // Store shutdown sender for later use
-    if let Ok(mut guard) = SHUTDOWN_TX.lock() {
-        *guard = Some(shutdown_tx);
-    }
-    
     let mut watcher = RecommendedWatcher::new(
         move |res: Result<Event, notify::Error>| {
             if let Ok(event) = res {
@@ -315,66 +705,119 @@ fn start_unix_watcher(app: AppHandle) -> Result<(), String> {
         // Configure for lower latency on macOS FSEvents
         Config::default().with_poll_interval(Duration::from_millis(500)),
     ).map_err(|e| e.to_string())?;
-    
-    let volumes_path = get_volumes_path();
-    watcher.watch(
-        &volumes_path,
-        RecursiveMode::NonRecursive,
-    ).map_err(|e| e.to_string())?;
-    
-    // Spawn thread to handle events
-    std::thread::spawn(move || {
-        // Keep watcher alive
-        let _watcher = watcher;
-        
-        // Track which paths we have emitted "device-connected" for,
-        // so we can emit the matching "device-disconnected" even when
-        // the volume has a custom name not in DEVICE_VOLUMES.
-        let mut known_midi_captain_paths = std::collections::HashSet::new();
-        
-        loop {
-            // Check for shutdown signal (non-blocking)
-            if shutdown_rx.try_recv().is_ok() {
-                break;
-            }
-            
-            // Check for filesystem events (with timeout to allow shutdown checks)
-            match rx.recv_timeout(std::time::Duration::from_millis(100)) {
-                Ok(event) => {
-                    match event.kind {
-                        EventKind::Create(_) => {
-                            // Volume mounted - check if it's a device
-                            for path in &event.paths {
-                                if let Some(device) = check_volume(path) {
-                                    known_midi_captain_paths.insert(path.clone());
-                                    let _ = app.emit("device-connected", device);
+    watcher.watch(volumes, RecursiveMode::NonRecursive).map_err(|e| e.to_string())?;
+    Ok((watcher, rx))
+}
+
+/// Turn filesystem events into device events until `shutdown` fires or the
+/// event channel closes. `mounted` are the device volumes already mounted,
+/// whose ejects are reported too.
+#[cfg(not(target_os = "windows"))]
+fn run_volume_events(
+    events: Receiver<Event>,
+    shutdown: Receiver<()>,
+    mounted: HashSet<PathBuf>,
+    mut on_event: impl FnMut(VolumeEvent),
+) {
+    // Track which paths we have emitted "device-connected" for,
+    // so we can emit the matching "device-disconnected" even when
+    // the volume has a custom name that isn't a known one.
+    let mut known_midi_captain_paths = mounted;
+
+    loop {
+        // Check for shutdown signal (non-blocking)
+        if shutdown.try_recv().is_ok() {
+            break;
+        }
+
+        // Check for filesystem events (with timeout to allow shutdown checks)
+        match events.recv_timeout(Duration::from_millis(100)) {
+            Ok(event) => {
+                // Device roots are watched too, for their config.json
+                let (configs, paths): (Vec<PathBuf>, Vec<PathBuf>) = event
+                    .paths
+                    .into_iter()
+                    .partition(|p| p.file_name().is_some_and(|n| n == "config.json"));
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                    for path in configs {
+                        on_event(VolumeEvent::ConfigTouched(path));
+                    }
+                }
+                match event.kind {
+                    EventKind::Create(_) => {
+                        // Volume mounted - check if it's a device. A repeated
+                        // create for a connected volume is not a second connect.
+                        for path in &paths {
+                            if let Some(device) = check_volume(path) {
+                                if known_midi_captain_paths.insert(path.clone()) {
+                                    on_event(VolumeEvent::Connected(device));
                                 }
                             }
                         }
-                        EventKind::Remove(_) => {
-                            // Volume unmounted - emit disconnect if we previously detected it
-                            for path in &event.paths {
-                                if known_midi_captain_paths.remove(path) {
-                                    let name_str = path
-                                        .file_name()
-                                        .map(|n| n.to_string_lossy().to_string())
-                                        .unwrap_or_default();
-                                    let _ = app.emit("device-disconnected", name_str);
-                                }
+                    }
+                    EventKind::Remove(_) => {
+                        // Volume unmounted - emit disconnect if we previously detected it
+                        for path in &paths {
+                            if known_midi_captain_paths.remove(path) {
+                                on_event(VolumeEvent::Disconnected(path.clone()));
                             }
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
-                Err(mpsc::RecvTimeoutError::Timeout) => {
-                    // No event, continue loop (allows shutdown check)
-                }
-                Err(mpsc::RecvTimeoutError::Disconnected) => {
-                    // Channel closed, exit thread
-                    break;
-                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                // No event, continue loop (allows shutdown check)
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                // Channel closed, exit thread
+                break;
             }
         }
+    }
+}
+
+/// Also watch a device's root so changes to its config.json are reported.
+/// The root rather than the file: saves replace config.json by renaming.
+#[cfg(not(target_os = "windows"))]
+fn watch_config(watcher: &mut RecommendedWatcher, device: &DetectedDevice) {
+    crate::config_watch::track(&device.config_path);
+    let _ = watcher.watch(&device.path, RecursiveMode::NonRecursive);
+}
+
+/// Unix-specific watcher using filesystem events
+#[cfg(not(target_os = "windows"))]
+fn start_unix_watcher(app: AppHandle) -> Result<(), String> {
+    let (shutdown_tx, shutdown_rx): (Sender<()>, Receiver<()>) = mpsc::channel();
+    
+    // Store shutdown sender for later use
+    if let Ok(mut guard) = SHUTDOWN_TX.lock() {
+        *guard = Some(shutdown_tx);
+    }
+    
+    let (mut watcher, rx) = watch_volumes(&get_volumes_path())?;
+    // Devices already mounted get their config watched as well
+    let mut mounted = HashSet::new();
+    for device in scan_devices() {
+        watch_config(&mut watcher, &device);
+        mark_connected(&device);
+        mounted.insert(device.path);
+    }
+    
+    // Spawn thread to handle events
+    std::thread::spawn(move || {
+        run_volume_events(rx, shutdown_rx, mounted, |event| match event {
+            VolumeEvent::Connected(device) => {
+                watch_config(&mut watcher, &device);
+                announce_connected(&app, device);
+            }
+            VolumeEvent::Disconnected(path) => announce_disconnected(&app, &path),
+            VolumeEvent::ConfigTouched(path) => {
+                if let Some(change) = crate::config_watch::check(&path) {
+                    let _ = app.emit(crate::config_watch::EVENT, change);
+                }
+            }
+        });
         
         // Reset flag so watcher can be restarted if needed
         WATCHER_STARTED.store(false, Ordering::SeqCst);
@@ -391,6 +834,11 @@ pub fn stop_device_watcher() -> Result<(), String> {
             let _ = tx.send(());
         }
     }
+    // Without the watcher, disconnects no longer invalidate cached configs
+    crate::config_cache::clear();
+    if let Ok(mut connected) = CONNECTED.lock() {
+        connected.clear();
+    }
     Ok(())
 }
 
@@ -398,6 +846,20 @@ pub fn stop_device_watcher() -> Result<(), String> {
 mod tests {
     use super::*;
     
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_scan_volumes_dir_reports_each_device() {
+        let volumes = tempfile::tempdir().unwrap();
+        std::fs::create_dir(volumes.path().join("CIRCUITPY")).unwrap();
+        std::fs::create_dir(volumes.path().join("BACKUP")).unwrap();
+        std::fs::create_dir(volumes.path().join("MIDICAPTAIN")).unwrap();
+
+        let mut names = Vec::new();
+        scan_volumes_dir(volumes.path(), &mut |device| names.push(device.name));
+        names.sort();
+        assert_eq!(names, ["CIRCUITPY", "MIDICAPTAIN"]);
+    }
+
     #[test]
     #[cfg(not(target_os = "windows"))]
     fn test_check_volume_circuitpy() {
@@ -411,6 +873,27 @@ mod tests {
         assert!(!device.has_config); // No actual config file exists in test
     }
     
+    #[test]
+    fn test_detect_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let volume = dir.path();
+        assert_eq!(detect_model(volume), None);
+
+        // Stock firmware hardcodes one model's LED count
+        std::fs::write(volume.join("code.py"), "neo_pin = board.GP7\nLED_count = 18  # 6 switches\n").unwrap();
+        assert_eq!(detect_model(volume), Some((DeviceType::Mini6, ModelSource::Firmware)));
+
+        // A Pico board ID says nothing about the model; a MIDI Captain one does
+        std::fs::write(volume.join("boot_out.txt"), "Board ID:raspberry_pi_pico\n").unwrap();
+        assert_eq!(detect_model(volume).map(|(_, source)| source), Some(ModelSource::Firmware));
+        std::fs::write(volume.join("boot_out.txt"), "Board ID:paintaudio_midi_captain_nano4\n").unwrap();
+        assert_eq!(detect_model(volume), Some((DeviceType::Nano4, ModelSource::BootOut)));
+
+        // The config wins
+        std::fs::write(volume.join("config.json"), r#"{"device": "std10", "buttons": []}"#).unwrap();
+        assert_eq!(detect_model(volume), Some((DeviceType::Std10, ModelSource::Config)));
+    }
+
     #[test]
     #[cfg(not(target_os = "windows"))]
     fn test_check_volume_midicaptain() {
@@ -445,9 +928,26 @@ mod tests {
     #[test]
     fn test_device_volumes_list() {
         // Verify the expected device names are in the list
-        assert!(DEVICE_VOLUMES.contains(&"CIRCUITPY"));
-        assert!(DEVICE_VOLUMES.contains(&"MIDICAPTAIN"));
-        assert!(!DEVICE_VOLUMES.contains(&"Macintosh HD"));
+        assert!(DEFAULT_VOLUMES.contains(&"CIRCUITPY"));
+        assert!(DEFAULT_VOLUMES.contains(&"MIDICAPTAIN"));
+        assert!(!DEFAULT_VOLUMES.contains(&"Macintosh HD"));
+    }
+
+    #[test]
+    fn test_added_volume_names_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(known_volumes_in(Some(dir.path())), ["CIRCUITPY", "MIDICAPTAIN"]);
+
+        assert_eq!(add_volume_in(dir.path(), " PEDALBOARD ").unwrap(), ["CIRCUITPY", "MIDICAPTAIN", "PEDALBOARD"]);
+        // Known names, in any case, are not added twice
+        assert_eq!(add_volume_in(dir.path(), "pedalboard").unwrap().len(), 3);
+        assert_eq!(add_volume_in(dir.path(), "circuitpy").unwrap().len(), 3);
+        assert!(add_volume_in(dir.path(), "a/b").is_err());
+        assert!(add_volume_in(dir.path(), "  ").is_err());
+
+        // The defaults can't be removed
+        assert_eq!(remove_volume_in(dir.path(), "CIRCUITPY").unwrap().len(), 3);
+        assert_eq!(remove_volume_in(dir.path(), "Pedalboard").unwrap(), ["CIRCUITPY", "MIDICAPTAIN"]);
     }
 
     #[test]
@@ -513,7 +1013,7 @@ mod tests {
         )
         .unwrap();
 
-        let result = check_volume(&dir.path().to_path_buf());
+        let result = check_volume(dir.path());
         assert!(result.is_some(), "Custom-named volume should be detected when config name matches");
         let device = result.unwrap();
         assert!(device.has_config);
@@ -533,7 +1033,7 @@ mod tests {
         )
         .unwrap();
 
-        let result = check_volume(&dir.path().to_path_buf());
+        let result = check_volume(dir.path());
         assert!(result.is_some(), "Volume with valid MIDI Captain config should be accepted regardless of name");
     }
 
@@ -543,7 +1043,7 @@ mod tests {
         // A volume with an unknown name AND no config.json should NOT be detected
         let dir = tempfile::TempDir::with_prefix("RANDOMDRIVE").unwrap();
         // No config.json written
-        let result = check_volume(&dir.path().to_path_buf());
+        let result = check_volume(dir.path());
         assert!(result.is_none(), "Unknown volume with no config should not be detected");
     }
 
@@ -570,4 +1070,224 @@ mod tests {
         std::fs::write(&path, r#"{"device": "unknown"}"#).unwrap();
         assert!(parse_midi_captain_config(&path).is_none());
     }
+
+    #[test]
+    fn test_parse_safe_mode_reason_next_line() {
+        let boot_out = "Adafruit CircuitPython 7.3.3 on 2022-08-29; Raspberry Pi Pico with rp2040\nYou are in safe mode because:\nCircuitPython core code crashed hard. Whoops!\n";
+        assert_eq!(
+            parse_safe_mode_reason(boot_out).as_deref(),
+            Some("CircuitPython core code crashed hard. Whoops!")
+        );
+    }
+
+    #[test]
+    fn test_parse_safe_mode_reason_normal_boot() {
+        let boot_out = "Adafruit CircuitPython 7.3.3 on 2022-08-29; Raspberry Pi Pico with rp2040\nBoard ID:raspberry_pi_pico\n";
+        assert_eq!(parse_safe_mode_reason(boot_out), None);
+    }
+
+    #[test]
+    fn test_detect_safe_mode_from_marker() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("safemode.txt"), "BROWNOUT\n").unwrap();
+        assert_eq!(detect_safe_mode(dir.path()).as_deref(), Some("BROWNOUT"));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_check_volume_reports_safe_mode() {
+        let dir = tempfile::TempDir::with_prefix("CIRCUITPY").unwrap();
+        std::fs::write(dir.path().join("config.json"), r#"{"device": "std10", "buttons": []}"#).unwrap();
+        std::fs::write(dir.path().join("boot_out.txt"), "You are in safe mode because:\nYou pressed the reset button during boot.\n").unwrap();
+        let device = check_volume(dir.path()).unwrap();
+        assert!(device.safe_mode);
+        assert_eq!(device.safe_mode_reason.as_deref(), Some("You pressed the reset button during boot."));
+    }
+
+    #[test]
+    fn test_validate_device_config_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+
+        std::fs::write(&path, r#"{"device": "mini6", "buttons": []}"#).unwrap();
+        let summary = validate_device_config(&path);
+        assert!(!summary.valid);
+        assert_eq!(summary.error_count, 1);
+        assert_eq!(summary.schema_version, CONFIG_SCHEMA_VERSION);
+
+        std::fs::write(&path, "{ not json").unwrap();
+        let summary = validate_device_config(&path);
+        assert!(!summary.valid);
+        assert_eq!(summary.error_count, 1);
+    }
+
+    /// A temp "volumes root" watched the way `/Volumes` is: mounting a
+    /// volume is creating a directory in it, ejecting is removing it
+    #[cfg(not(target_os = "windows"))]
+    struct MountHarness {
+        root: tempfile::TempDir,
+        events: Receiver<VolumeEvent>,
+        shutdown: Sender<()>,
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    impl MountHarness {
+        fn start() -> Self {
+            Self::start_with(&[], &[])
+        }
+
+        /// Start with `mounted` already mounted before the watcher, which
+        /// is told about those in `known`
+        fn start_with(mounted: &[&str], known: &[&str]) -> Self {
+            let root = tempfile::tempdir().unwrap();
+            for name in mounted {
+                std::fs::create_dir(root.path().join(name)).unwrap();
+            }
+            let known = known.iter().map(|name| root.path().join(name)).collect();
+            let (watcher, rx) = watch_volumes(root.path()).unwrap();
+            let (tx, events) = mpsc::channel();
+            let (shutdown, shutdown_rx) = mpsc::channel();
+            std::thread::spawn(move || {
+                let _watcher = watcher;
+                run_volume_events(rx, shutdown_rx, known, |event| {
+                    let _ = tx.send(event);
+                });
+            });
+            Self { root, events, shutdown }
+        }
+
+        fn mount(&self, name: &str) {
+            std::fs::create_dir(self.root.path().join(name)).unwrap();
+        }
+
+        fn eject(&self, name: &str) {
+            std::fs::remove_dir_all(self.root.path().join(name)).unwrap();
+        }
+
+        /// Wait for `expected` ("connected NAME" / "disconnected NAME"), then
+        /// check nothing else follows
+        fn expect(&self, expected: &[&str]) {
+            let mut seen = Vec::new();
+            while seen.len() < expected.len() {
+                match self.events.recv_timeout(Duration::from_secs(5)) {
+                    Ok(event) => seen.push(describe(event)),
+                    Err(_) => break,
+                }
+            }
+            if let Ok(extra) = self.events.recv_timeout(Duration::from_millis(300)) {
+                seen.push(describe(extra));
+            }
+            assert_eq!(seen, expected);
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    impl Drop for MountHarness {
+        fn drop(&mut self) {
+            let _ = self.shutdown.send(());
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn describe(event: VolumeEvent) -> String {
+        match event {
+            VolumeEvent::Connected(device) => format!("connected {}", device.name),
+            VolumeEvent::Disconnected(path) => format!("disconnected {}", path.file_name().unwrap().to_string_lossy()),
+            VolumeEvent::ConfigTouched(path) => format!("config {}", path.display()),
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_watcher_reports_config_writes_on_watched_device() {
+        let root = tempfile::tempdir().unwrap();
+        let device = root.path().join("CIRCUITPY");
+        std::fs::create_dir(&device).unwrap();
+        let (mut watcher, rx) = watch_volumes(root.path()).unwrap();
+        watcher.watch(&device, RecursiveMode::NonRecursive).unwrap();
+        let (tx, events) = mpsc::channel();
+        let (_shutdown, shutdown_rx) = mpsc::channel::<()>();
+        std::thread::spawn(move || {
+            let _watcher = watcher;
+            run_volume_events(rx, shutdown_rx, HashSet::new(), |event| {
+                let _ = tx.send(event);
+            });
+        });
+
+        std::fs::write(device.join("code.py"), "").unwrap();
+        std::fs::write(device.join("config.json"), "{}").unwrap();
+        // One save can raise several events; all are for config.json
+        let first = events.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(describe(first), format!("config {}", device.join("config.json").display()));
+        while let Ok(event) = events.recv_timeout(Duration::from_millis(300)) {
+            assert!(matches!(event, VolumeEvent::ConfigTouched(_)));
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_watcher_reports_mount_and_eject() {
+        let harness = MountHarness::start();
+        harness.mount("CIRCUITPY");
+        harness.mount("BACKUP");
+        harness.expect(&["connected CIRCUITPY"]);
+        harness.eject("BACKUP");
+        harness.eject("CIRCUITPY");
+        harness.expect(&["disconnected CIRCUITPY"]);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_watcher_tracks_devices_independently() {
+        let harness = MountHarness::start();
+        harness.mount("CIRCUITPY");
+        harness.expect(&["connected CIRCUITPY"]);
+        harness.mount("MIDICAPTAIN");
+        harness.expect(&["connected MIDICAPTAIN"]);
+        harness.eject("CIRCUITPY");
+        harness.expect(&["disconnected CIRCUITPY"]);
+        harness.eject("MIDICAPTAIN");
+        harness.expect(&["disconnected MIDICAPTAIN"]);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_watcher_rapid_reconnects_stay_paired() {
+        let harness = MountHarness::start();
+        for _ in 0..5 {
+            harness.mount("CIRCUITPY");
+            harness.eject("CIRCUITPY");
+        }
+        harness.mount("CIRCUITPY");
+
+        let mut expected = Vec::new();
+        for _ in 0..5 {
+            expected.extend(["connected CIRCUITPY", "disconnected CIRCUITPY"]);
+        }
+        expected.push("connected CIRCUITPY");
+        harness.expect(&expected);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_watcher_ignores_eject_of_volume_mounted_before_start() {
+        // A device already mounted is found by scan_devices, not the watcher,
+        // so unless the watcher is told about it its eject has no connect to
+        // pair with
+        let harness = MountHarness::start_with(&["CIRCUITPY"], &[]);
+        harness.eject("CIRCUITPY");
+        harness.expect(&[]);
+        harness.mount("CIRCUITPY");
+        harness.expect(&["connected CIRCUITPY"]);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_watcher_reports_eject_of_devices_mounted_before_start() {
+        let harness = MountHarness::start_with(&["CIRCUITPY", "MIDICAPTAIN"], &["CIRCUITPY", "MIDICAPTAIN"]);
+        harness.eject("MIDICAPTAIN");
+        harness.expect(&["disconnected MIDICAPTAIN"]);
+        harness.eject("CIRCUITPY");
+        harness.expect(&["disconnected CIRCUITPY"]);
+    }
 }
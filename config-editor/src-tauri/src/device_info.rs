@@ -0,0 +1,169 @@
+//! Device firmware info
+//!
+//! CircuitPython writes `boot_out.txt` to the drive on every boot:
+//!
+//! ```text
+//! Adafruit CircuitPython 7.3.3 on 2022-08-29; Raspberry Pi Pico with rp2040
+//! Board ID:raspberry_pi_pico
+//! UID:E660583883265F2A
+//! ```
+//!
+//! `get_device_info` reads it together with the MIDI Captain firmware's
+//! `VERSION` file and the config's schema version, and warns when the config
+//! may use settings the firmware predates.
+
+use crate::commands::{validate_device_path, ConfigError};
+use crate::config::CONFIG_SCHEMA_VERSION;
+use crate::firmware::{is_outdated, parse_circuitpython_version, read_firmware_version};
+use crate::health::resolve_volume;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+/// What `boot_out.txt` says about the board
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct BootInfo {
+    /// e.g. "7.3.3"
+    pub circuitpython_version: Option<String>,
+    /// CircuitPython build date, e.g. "2022-08-29"
+    pub build_date: Option<String>,
+    /// e.g. "Raspberry Pi Pico with rp2040"
+    pub board: Option<String>,
+    /// e.g. "raspberry_pi_pico"
+    pub board_id: Option<String>,
+    /// Unique ID of the microcontroller
+    pub uid: Option<String>,
+}
+
+/// Result of `get_device_info`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeviceInfo {
+    pub volume_path: PathBuf,
+    /// `None` when boot_out.txt is missing
+    pub boot: Option<BootInfo>,
+    /// MIDI Captain firmware version (`VERSION`), e.g. "1.5.0"
+    pub firmware_version: Option<String>,
+    /// Schema version of the device's config.json (0 = unversioned); `None`
+    /// without a readable config
+    pub config_version: Option<u32>,
+    /// Mismatches between the config schema and the firmware
+    pub warnings: Vec<String>,
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Parse boot_out.txt contents
+pub fn parse_boot_out(text: &str) -> BootInfo {
+    let mut info = BootInfo {
+        circuitpython_version: parse_circuitpython_version(text),
+        ..BootInfo::default()
+    };
+    if let Some(line) = text.lines().find(|l| l.contains("CircuitPython")) {
+        let (build, board) = line.split_once(';').unwrap_or((line, ""));
+        info.build_date = build.split(" on ").nth(1).and_then(non_empty);
+        info.board = non_empty(board);
+    }
+    for line in text.lines() {
+        if let Some(id) = line.strip_prefix("Board ID:") {
+            info.board_id = non_empty(id);
+        } else if let Some(uid) = line.strip_prefix("UID:") {
+            info.uid = non_empty(uid);
+        }
+    }
+    info
+}
+
+/// Warnings for a config at schema `config_version` on firmware `firmware`;
+/// `shipped` is this editor's release, which carries the current schema
+pub fn schema_warnings(firmware: Option<&str>, config_version: Option<u32>, shipped: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    match firmware {
+        Some(firmware) if is_outdated(firmware, shipped) => warnings.push(format!(
+            "Firmware {} is older than this editor ({}). Settings added since may be ignored by the device; update the firmware.",
+            firmware, shipped
+        )),
+        None => warnings.push(
+            "Firmware version unknown (no VERSION file). The firmware may not support every setting the editor writes."
+                .to_string(),
+        ),
+        _ => {}
+    }
+    if let Some(version) = config_version.filter(|v| *v > CONFIG_SCHEMA_VERSION) {
+        warnings.push(format!(
+            "config.json is schema version {}, newer than this editor's {}. Update the editor before saving.",
+            version, CONFIG_SCHEMA_VERSION
+        ));
+    }
+    warnings
+}
+
+/// Info for the device mounted at `volume`
+pub fn device_info(volume: &Path) -> DeviceInfo {
+    let boot = fs::read_to_string(volume.join("boot_out.txt")).ok().map(|text| parse_boot_out(&text));
+    let firmware_version = read_firmware_version(volume);
+    let config_version = fs::read(volume.join("config.json"))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .map(|value| crate::migrations::config_version(&value));
+    let warnings = schema_warnings(firmware_version.as_deref(), config_version, env!("CARGO_PKG_VERSION"));
+    DeviceInfo {
+        volume_path: volume.to_path_buf(),
+        boot,
+        firmware_version,
+        config_version,
+        warnings,
+    }
+}
+
+/// Firmware, board and config schema info for a device
+#[command]
+pub fn get_device_info(path: String) -> Result<DeviceInfo, ConfigError> {
+    let volume = resolve_volume(validate_device_path(&path)?);
+    Ok(device_info(&volume))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_boot_out() {
+        let text = "Adafruit CircuitPython 7.3.3 on 2022-08-29; Raspberry Pi Pico with rp2040\r\nBoard ID:raspberry_pi_pico\r\nUID:E660583883265F2A\r\nboot.py output:\r\n";
+        let info = parse_boot_out(text);
+        assert_eq!(info.circuitpython_version.as_deref(), Some("7.3.3"));
+        assert_eq!(info.build_date.as_deref(), Some("2022-08-29"));
+        assert_eq!(info.board.as_deref(), Some("Raspberry Pi Pico with rp2040"));
+        assert_eq!(info.board_id.as_deref(), Some("raspberry_pi_pico"));
+        assert_eq!(info.uid.as_deref(), Some("E660583883265F2A"));
+    }
+
+    #[test]
+    fn test_parse_partial_boot_out() {
+        let info = parse_boot_out("Adafruit CircuitPython 7.3.3 on 2022-08-29\n");
+        assert_eq!(info.build_date.as_deref(), Some("2022-08-29"));
+        assert_eq!(info.board, None);
+        assert_eq!(info.uid, None);
+        assert_eq!(parse_boot_out(""), BootInfo::default());
+    }
+
+    #[test]
+    fn test_schema_warnings() {
+        assert!(schema_warnings(Some("1.5.0"), Some(CONFIG_SCHEMA_VERSION), "1.5.0").is_empty());
+        assert!(schema_warnings(Some("1.4.0"), Some(CONFIG_SCHEMA_VERSION), "1.5.0")[0].contains("older than this editor"));
+        assert!(schema_warnings(None, None, "1.5.0")[0].contains("unknown"));
+        assert_eq!(schema_warnings(Some("1.5.0"), Some(CONFIG_SCHEMA_VERSION + 1), "1.5.0").len(), 1);
+    }
+
+    #[test]
+    fn test_device_info_reads_volume() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("boot_out.txt"), "Adafruit CircuitPython 7.3.3 on 2022-08-29; Raspberry Pi Pico with rp2040\nUID:E660583883265F2A\n").unwrap();
+        let info = device_info(dir.path());
+        assert_eq!(info.boot.and_then(|b| b.uid).as_deref(), Some("E660583883265F2A"));
+        assert_eq!(info.firmware_version, None);
+        assert_eq!(info.config_version, None);
+    }
+}
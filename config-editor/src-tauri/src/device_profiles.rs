@@ -0,0 +1,205 @@
+//! Named profiles stored on the device
+//!
+//! For switching rigs between bands: the device's `profiles/` directory
+//! holds any number of complete configs (`profiles/<name>.json`), and
+//! activating one copies it over `config.json` through the normal write
+//! path (validation, backup, verification). The firmware only ever reads
+//! `config.json`, so profiles cost nothing but drive space.
+//!
+//! Unlike presets (presets.rs), which live on the host, profiles travel with
+//! the pedal.
+
+use crate::commands::{validate_device_path, verify_device_connected, write_config_raw, write_sync, ConfigError};
+use crate::config::{issue_messages, MidiCaptainConfig, CONFIG_SCHEMA_VERSION};
+use crate::confirm::{confirm, Confirmable};
+use crate::health::{check_before_write, resolve_volume};
+use crate::presets::check_name;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::command;
+
+/// Directory on the device volume holding the profiles
+pub const PROFILES_DIR: &str = "profiles";
+
+/// A profile on the device
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct DeviceProfile {
+    pub name: String,
+    pub bytes: u64,
+    /// Unix timestamp (seconds) of the last change
+    pub modified: Option<u64>,
+    /// config.json currently holds this profile
+    pub active: bool,
+}
+
+fn profile_path(volume: &Path, name: &str) -> Result<PathBuf, ConfigError> {
+    check_name(name)?;
+    Ok(volume.join(PROFILES_DIR).join(format!("{}.json", name)))
+}
+
+fn not_found(name: &str) -> ConfigError {
+    ConfigError {
+        message: format!("Profile '{}' not found on the device", name),
+        details: None,
+        code: None,
+    }
+}
+
+/// A config in canonical form for comparison, without the schema version
+/// and editor metadata that a write adds
+fn comparable(bytes: &[u8]) -> Option<serde_json::Value> {
+    let mut config: MidiCaptainConfig = serde_json::from_slice(bytes).ok()?;
+    config.version = None;
+    config.editor = None;
+    serde_json::to_value(&config).ok()
+}
+
+/// Profiles on the device mounted at `volume`, sorted by name
+pub fn profiles_on(volume: &Path) -> Vec<DeviceProfile> {
+    let current = fs::read(volume.join("config.json")).ok().and_then(|b| comparable(&b));
+    let mut profiles: Vec<DeviceProfile> = fs::read_dir(volume.join(PROFILES_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_str()?.to_string();
+            let name = file_name.strip_suffix(".json").filter(|n| !n.starts_with('.'))?;
+            let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+            let active = current.is_some()
+                && fs::read(entry.path()).ok().and_then(|b| comparable(&b)) == current;
+            Some(DeviceProfile {
+                name: name.to_string(),
+                bytes: meta.len(),
+                modified: meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs()),
+                active,
+            })
+        })
+        .collect();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    profiles
+}
+
+/// Validate `config` and store it as profile `name` on the device at `volume`
+pub fn save_profile_to(volume: &Path, name: &str, mut config: MidiCaptainConfig) -> Result<(), ConfigError> {
+    let path = profile_path(volume, name)?;
+    config.validate().map_err(|errors| ConfigError {
+        message: "Validation failed".to_string(),
+        details: Some(issue_messages(errors)),
+        code: None,
+    })?;
+    config.version = Some(CONFIG_SCHEMA_VERSION);
+    let json = serde_json::to_string_pretty(&config)?;
+    check_before_write(volume, json.len() as u64)?;
+    fs::create_dir_all(volume.join(PROFILES_DIR))?;
+    write_sync(&path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Profiles stored on a device
+#[command]
+pub fn list_profiles(device_path: String) -> Result<Vec<DeviceProfile>, ConfigError> {
+    let volume = resolve_volume(validate_device_path(&device_path)?);
+    Ok(profiles_on(&volume))
+}
+
+/// Make a profile the device's config.json. Refused like any other write
+/// when the current config is locked.
+#[command]
+pub fn activate_profile(device_path: String, name: String) -> Result<(), ConfigError> {
+    let volume = resolve_volume(validate_device_path(&device_path)?);
+    let path = profile_path(&volume, &name)?;
+    let json = fs::read_to_string(&path).map_err(|_| not_found(&name))?;
+    let config_path = volume.join("config.json").display().to_string();
+    write_config_raw(config_path, json, None, None, None, None)?.into_result().map(drop)
+}
+
+/// Store a config as a named profile on the device, replacing one with the
+/// same name
+#[command]
+pub fn save_profile(device_path: String, name: String, config: MidiCaptainConfig) -> Result<(), ConfigError> {
+    let volume = resolve_volume(validate_device_path(&device_path)?);
+    verify_device_connected(&volume)?;
+    crate::guest::check_writable()?;
+    save_profile_to(&volume, &name, config)
+}
+
+/// Delete a profile from the device. Takes a `confirmation` token: there is
+/// no backup of a profile.
+#[command]
+pub fn delete_profile(
+    device_path: String,
+    name: String,
+    confirmation: Option<String>,
+) -> Result<Confirmable<()>, ConfigError> {
+    let volume = resolve_volume(validate_device_path(&device_path)?);
+    verify_device_connected(&volume)?;
+    let path = profile_path(&volume, &name)?;
+    if !path.is_file() {
+        return Err(not_found(&name));
+    }
+    let target = path.display().to_string();
+    confirm(
+        &["delete_profile", &target],
+        || format!("Delete profile '{}' from the device", name),
+        confirmation.as_deref(),
+        || Ok(fs::remove_file(&path)?),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{"device": "mini6", "buttons": [
+        {"label": "A", "cc": 20, "color": "red"}, {"label": "B", "cc": 21, "color": "red"},
+        {"label": "C", "cc": 22, "color": "red"}, {"label": "D", "cc": 23, "color": "red"},
+        {"label": "E", "cc": 24, "color": "red"}, {"label": "F", "cc": 25, "color": "red"}
+    ]}"#;
+
+    #[test]
+    fn test_profiles_listed_with_active_marked() {
+        let volume = tempfile::tempdir().unwrap();
+        let dir = volume.path().join(PROFILES_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("band-a.json"), CONFIG).unwrap();
+        fs::write(dir.join("band-b.json"), CONFIG.replace("\"cc\": 20", "\"cc\": 30")).unwrap();
+        fs::write(dir.join("._band-a.json"), "").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+        // Written through the editor: same config plus version and metadata
+        fs::write(
+            volume.path().join("config.json"),
+            CONFIG.replacen('{', r#"{"version": 3, "_editor": {"locked": false},"#, 1),
+        )
+        .unwrap();
+
+        let profiles = profiles_on(volume.path());
+        let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["band-a", "band-b"]);
+        assert!(profiles[0].active);
+        assert!(!profiles[1].active);
+    }
+
+    #[test]
+    fn test_no_profiles_directory() {
+        let volume = tempfile::tempdir().unwrap();
+        assert!(profiles_on(volume.path()).is_empty());
+    }
+
+    #[test]
+    fn test_save_profile_validates_and_checks_name() {
+        let volume = tempfile::tempdir().unwrap();
+        let config: MidiCaptainConfig = serde_json::from_str(CONFIG).unwrap();
+        save_profile_to(volume.path(), "band-a", config.clone()).unwrap();
+        assert_eq!(profiles_on(volume.path())[0].name, "band-a");
+
+        assert!(save_profile_to(volume.path(), "../config", config.clone()).is_err());
+        let mut invalid = config;
+        invalid.buttons[0].cc = Some(200);
+        assert!(save_profile_to(volume.path(), "broken", invalid).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! Structured config diff
+//!
+//! Compares two configs field by field and reports each change with a
+//! readable path (`buttons[2].color`), so "what's different between my May
+//! backup and now?" has a precise answer. `diff_config` compares the device's
+//! config with the editor's unsaved state for a review-before-write screen.
+
+use crate::archive::read_path;
+use crate::commands::ConfigError;
+use crate::config::MidiCaptainConfig;
+use serde_json::{Map, Value};
+use std::path::Path;
+use tauri::command;
+
+/// Kind of difference at a path
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One difference between two configs
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ConfigChange {
+    /// Location of the change, e.g. `buttons[2].color` or `encoder.push.cc`
+    pub path: String,
+    pub kind: ChangeKind,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+fn diff_at(path: &str, before: Option<&Value>, after: Option<&Value>, changes: &mut Vec<ConfigChange>) {
+    match (before, after) {
+        (Some(Value::Object(a)), Some(Value::Object(b))) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                diff_at(&join_key(path, key), a.get(key), b.get(key), changes);
+            }
+        }
+        (Some(Value::Array(a)), Some(Value::Array(b))) => {
+            for i in 0..a.len().max(b.len()) {
+                diff_at(&format!("{}[{}]", path, i), a.get(i), b.get(i), changes);
+            }
+        }
+        (Some(a), Some(b)) if a == b => {}
+        (None, None) => {}
+        (before, after) => changes.push(ConfigChange {
+            path: path.to_string(),
+            kind: match (before, after) {
+                (None, _) => ChangeKind::Added,
+                (_, None) => ChangeKind::Removed,
+                _ => ChangeKind::Changed,
+            },
+            before: before.cloned(),
+            after: after.cloned(),
+        }),
+    }
+}
+
+/// Diff two JSON values, returning changes in path order
+pub fn diff_values(before: &Value, after: &Value) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    diff_at("", Some(before), Some(after), &mut changes);
+    changes
+}
+
+/// Diff two configs. Both are normalized through `MidiCaptainConfig` first,
+/// so an omitted field and its explicit default don't show up as a change.
+pub fn diff_configs(before: &MidiCaptainConfig, after: &MidiCaptainConfig) -> Result<Vec<ConfigChange>, serde_json::Error> {
+    Ok(diff_values(&serde_json::to_value(before)?, &serde_json::to_value(after)?))
+}
+
+/// Load a config from any path (device, local file, backup, or an entry in a
+/// snapshot zip such as `snapshot.zip!/config.json`) for comparison
+pub fn load_config_file(path: &str) -> Result<MidiCaptainConfig, ConfigError> {
+    let contents = read_path(path).map_err(|e| ConfigError {
+        message: format!("Could not read {}: {}", path, e),
+        details: None,
+        code: None,
+    })?;
+    serde_json::from_slice(&contents).map_err(|e| ConfigError {
+        message: format!("{} is not a valid config: {}", path, e),
+        details: None,
+        code: None,
+    })
+}
+
+/// Compare two config files from anywhere. Read-only: neither file is modified.
+#[command]
+pub fn diff_files(path_a: String, path_b: String) -> Result<Vec<ConfigChange>, ConfigError> {
+    let a = load_config_file(&path_a)?;
+    let b = load_config_file(&path_b)?;
+    Ok(diff_configs(&a, &b)?)
+}
+
+/// Compare the config at `path` (normally the device's config.json) with
+/// the editor's unsaved state, given as JSON. A missing file counts as an
+/// empty config, so a first write shows every field as added.
+#[command]
+pub fn diff_config(path: String, candidate_json: String) -> Result<Vec<ConfigChange>, ConfigError> {
+    let candidate: MidiCaptainConfig = serde_json::from_str(&candidate_json).map_err(|e| ConfigError {
+        message: format!("The editor's config is not valid: {}", e),
+        details: None,
+        code: None,
+    })?;
+    if !Path::new(&path).exists() {
+        return Ok(diff_values(&Value::Object(Map::new()), &serde_json::to_value(&candidate)?));
+    }
+    Ok(diff_configs(&load_config_file(&path)?, &candidate)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_identical_values_have_no_changes() {
+        let v = json!({"buttons": [{"label": "A", "cc": 20}]});
+        assert!(diff_values(&v, &v).is_empty());
+    }
+
+    #[test]
+    fn test_changed_added_removed_paths() {
+        let a = json!({"buttons": [{"label": "A", "cc": 20}], "global_channel": 0});
+        let b = json!({"buttons": [{"label": "A", "cc": 21}, {"label": "B"}], "dev_mode": true});
+        let changes = diff_values(&a, &b);
+        let summary: Vec<(&str, ChangeKind)> = changes.iter().map(|c| (c.path.as_str(), c.kind.clone())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("buttons[0].cc", ChangeKind::Changed),
+                ("buttons[1]", ChangeKind::Added),
+                ("dev_mode", ChangeKind::Added),
+                ("global_channel", ChangeKind::Removed),
+            ]
+        );
+        assert_eq!(changes[0].before, Some(json!(20)));
+        assert_eq!(changes[0].after, Some(json!(21)));
+    }
+
+    #[test]
+    fn test_diff_files_normalizes_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.json");
+        let b = dir.path().join("b.json");
+        fs::write(&a, r#"{"buttons": [{"label": "A", "cc": 20, "color": "red"}]}"#).unwrap();
+        fs::write(&b, r#"{"device": "std10", "buttons": [{"label": "A", "cc": 20, "color": "blue", "off_mode": "dim"}]}"#).unwrap();
+
+        let changes = diff_files(a.to_string_lossy().to_string(), b.to_string_lossy().to_string()).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "buttons[0].color");
+    }
+
+    #[test]
+    fn test_diff_config_against_editor_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{"buttons": [{"label": "A", "cc": 20, "color": "red"}]}"#).unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let candidate = r#"{"buttons": [{"label": "A", "cc": 64, "color": "red"}]}"#.to_string();
+        let changes = diff_config(path.clone(), candidate).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "buttons[0].cc");
+        assert_eq!(changes[0].before, Some(json!(20)));
+        assert_eq!(changes[0].after, Some(json!(64)));
+
+        assert!(diff_config(path, "{".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_diff_config_without_device_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("config.json").to_string_lossy().to_string();
+        let changes = diff_config(missing, r#"{"buttons": []}"#.to_string()).unwrap();
+        assert!(changes.iter().all(|c| c.kind == ChangeKind::Added));
+        assert!(changes.iter().any(|c| c.path == "buttons"));
+    }
+
+    #[test]
+    fn test_diff_files_reports_unreadable_file() {
+        let err = diff_files("/nonexistent/a.json".to_string(), "/nonexistent/b.json".to_string()).unwrap_err();
+        assert!(err.message.contains("/nonexistent/a.json"));
+    }
+}
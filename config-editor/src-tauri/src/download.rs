@@ -0,0 +1,361 @@
+//! Download manager for firmware and library bundles
+//!
+//! Downloads land in an on-disk cache under the app cache directory. Partial
+//! downloads are resumed with HTTP Range requests, and every file is verified
+//! against its SHA-256 before use, so a dropped venue Wi-Fi connection can't
+//! leave a corrupt bundle behind. Verified files are reused offline.
+
+use crate::commands::{validate_device_path, ConfigError};
+use crate::dependencies::{check_dependencies, ADAFRUIT_BUNDLE_TAG};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{command, AppHandle, Manager};
+
+/// Suffix for in-progress downloads
+const PARTIAL_SUFFIX: &str = ".part";
+
+/// Suffix for the sidecar file recording a verified download's SHA-256
+const HASH_SUFFIX: &str = ".sha256";
+
+/// A verified file in the download cache
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CachedDownload {
+    pub url: Option<String>,
+    pub path: PathBuf,
+    pub sha256: String,
+    pub size: u64,
+    /// True when served from the cache without touching the network
+    pub from_cache: bool,
+}
+
+/// Lowercase hex SHA-256 of a byte slice
+pub fn sha256_hex(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    to_hex(&hasher.finalize())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Lowercase hex SHA-256 of a file, streamed so large bundles aren't loaded into memory
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Cache file name for a URL: a short URL hash plus the URL's last path segment,
+/// so two bundles with the same file name from different hosts don't collide.
+pub fn cache_file_name(url: &str) -> String {
+    let last = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(url)
+        .rsplit('/')
+        .next()
+        .unwrap_or("download");
+    let safe: String = last
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}-{}", &sha256_hex(url.as_bytes())[..12], safe)
+}
+
+fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Fetch the published SHA-256 of `url` from `<url>.sha256` (`sha256sum`
+/// output: the hash, optionally followed by the file name)
+pub fn fetch_checksum(url: &str) -> io::Result<String> {
+    let checksum_url = format!("{}{}", url, HASH_SUFFIX);
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(15))
+        .timeout_read(Duration::from_secs(30))
+        .build();
+    let text = agent
+        .get(&checksum_url)
+        .call()
+        .map_err(|e| io::Error::other(format!("Could not fetch {}: {}", checksum_url, e)))?
+        .into_string()?;
+    parse_checksum(&text).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{} does not hold a SHA-256 checksum", checksum_url))
+    })
+}
+
+/// The hash from a `.sha256` file
+pub fn parse_checksum(text: &str) -> Option<String> {
+    let hash = text.split_whitespace().next()?;
+    (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())).then(|| hash.to_ascii_lowercase())
+}
+
+/// Return the cached file for `url` if it is complete and matches `expected_sha256`
+fn lookup_cached(cache_dir: &Path, url: &str, expected_sha256: Option<&str>) -> Option<CachedDownload> {
+    let path = cache_dir.join(cache_file_name(url));
+    let recorded = fs::read_to_string(sidecar(&path, HASH_SUFFIX)).ok()?;
+    let recorded = recorded.trim().to_string();
+    if let Some(expected) = expected_sha256 {
+        if !recorded.eq_ignore_ascii_case(expected) {
+            return None;
+        }
+    }
+    // Re-hash: the sidecar only proves the file was good when it was written
+    let actual = sha256_file(&path).ok()?;
+    if actual != recorded {
+        return None;
+    }
+    Some(CachedDownload {
+        url: Some(url.to_string()),
+        size: fs::metadata(&path).ok()?.len(),
+        path,
+        sha256: actual,
+        from_cache: true,
+    })
+}
+
+/// Fetch `url` into `cache_dir`, resuming a partial download if one exists.
+///
+/// If the network is unavailable but a verified copy is cached, the cached copy
+/// is returned. When `expected_sha256` is given, a mismatching download is
+/// discarded (so the next attempt starts clean) and an error is returned.
+pub fn fetch(cache_dir: &Path, url: &str, expected_sha256: Option<&str>) -> io::Result<CachedDownload> {
+    if let Some(cached) = lookup_cached(cache_dir, url, expected_sha256) {
+        return Ok(cached);
+    }
+    fs::create_dir_all(cache_dir)?;
+
+    let path = cache_dir.join(cache_file_name(url));
+    let partial = sidecar(&path, PARTIAL_SUFFIX);
+    let resume_from = fs::metadata(&partial).map(|m| m.len()).unwrap_or(0);
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(15))
+        .timeout_read(Duration::from_secs(30))
+        .build();
+    let mut request = agent.get(url);
+    if resume_from > 0 {
+        request = request.set("Range", &format!("bytes={}-", resume_from));
+    }
+
+    let response = match request.call() {
+        Ok(response) => response,
+        // 416: the partial file already holds the whole body
+        Err(ureq::Error::Status(416, _)) if resume_from > 0 => return finish(url, &partial, &path, expected_sha256),
+        Err(ureq::Error::Status(code, _)) => {
+            return Err(io::Error::other(format!("Download failed: HTTP {} for {}", code, url)));
+        }
+        Err(e) => return Err(io::Error::other(format!("Download failed: {}", e))),
+    };
+
+    // 206 means the server honoured the Range header; anything else is a full body
+    let mut file = if response.status() == 206 {
+        OpenOptions::new().append(true).open(&partial)?
+    } else {
+        File::create(&partial)?
+    };
+    io::copy(&mut response.into_reader(), &mut file)?;
+    file.sync_all()?;
+    drop(file);
+
+    finish(url, &partial, &path, expected_sha256)
+}
+
+/// Verify a completed partial download and move it into place
+fn finish(url: &str, partial: &Path, path: &Path, expected_sha256: Option<&str>) -> io::Result<CachedDownload> {
+    let actual = sha256_file(partial)?;
+    if let Some(expected) = expected_sha256 {
+        if !actual.eq_ignore_ascii_case(expected) {
+            let _ = fs::remove_file(partial);
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Checksum mismatch for {}: expected {}, got {}", url, expected, actual),
+            ));
+        }
+    }
+    fs::rename(partial, path)?;
+    fs::write(sidecar(path, HASH_SUFFIX), &actual)?;
+    Ok(CachedDownload {
+        url: Some(url.to_string()),
+        size: fs::metadata(path)?.len(),
+        path: path.to_path_buf(),
+        sha256: actual,
+        from_cache: false,
+    })
+}
+
+/// List verified files in the cache
+pub fn list_cached(cache_dir: &Path) -> Vec<CachedDownload> {
+    let mut items = Vec::new();
+    let Ok(entries) = fs::read_dir(cache_dir) else { return items };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.ends_with(HASH_SUFFIX) || name.ends_with(PARTIAL_SUFFIX) {
+            continue;
+        }
+        if let Ok(sha256) = fs::read_to_string(sidecar(&path, HASH_SUFFIX)) {
+            items.push(CachedDownload {
+                url: None,
+                size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                path,
+                sha256: sha256.trim().to_string(),
+                from_cache: true,
+            });
+        }
+    }
+    items
+}
+
+/// Remove a cached download along with its checksum and partial sidecars
+pub(crate) fn remove_cached(path: &Path) -> io::Result<()> {
+    for file in [path.to_path_buf(), sidecar(path, HASH_SUFFIX), sidecar(path, PARTIAL_SUFFIX)] {
+        if file.exists() {
+            fs::remove_file(file)?;
+        }
+    }
+    Ok(())
+}
+
+/// Download cache directory under the app cache dir
+pub(crate) fn download_cache_dir(app: &AppHandle) -> Result<PathBuf, ConfigError> {
+    let dir = app.path().app_cache_dir().map_err(|e| ConfigError {
+        message: format!("Could not resolve app cache directory: {}", e),
+        details: None,
+        code: None,
+    })?;
+    Ok(dir.join("downloads"))
+}
+
+fn download_error(e: io::Error) -> ConfigError {
+    ConfigError {
+        message: e.to_string(),
+        details: None,
+        code: None,
+    }
+}
+
+/// Download a firmware or library bundle into the cache (or return the cached copy)
+#[command]
+pub fn download_bundle(app: AppHandle, url: String, sha256: Option<String>) -> Result<CachedDownload, ConfigError> {
+    let cache_dir = download_cache_dir(&app)?;
+    fetch(&cache_dir, &url, sha256.as_deref()).map_err(download_error)
+}
+
+/// Download the Adafruit library bundle matching the device's CircuitPython version
+#[command]
+pub fn fetch_library_bundle(app: AppHandle, device_path: String) -> Result<CachedDownload, ConfigError> {
+    let report = check_dependencies(&validate_device_path(&device_path)?);
+    let url = report.bundle_url.ok_or_else(|| ConfigError {
+        message: "Could not determine the device's CircuitPython version (boot_out.txt missing?)".to_string(),
+        details: Some(vec![format!("Bundle release: {}", ADAFRUIT_BUNDLE_TAG)]),
+        code: None,
+    })?;
+    let cache_dir = download_cache_dir(&app)?;
+    fetch(&cache_dir, &url, None).map_err(download_error)
+}
+
+/// List verified downloads available offline
+#[command]
+pub fn list_download_cache(app: AppHandle) -> Result<Vec<CachedDownload>, ConfigError> {
+    Ok(list_cached(&download_cache_dir(&app)?))
+}
+
+/// Delete every cached and partial download
+#[command]
+pub fn clear_download_cache(app: AppHandle) -> Result<(), ConfigError> {
+    let dir = download_cache_dir(&app)?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://example.com/bundles/adafruit-circuitpython-bundle-7.x-mpy-20231010.zip";
+
+    #[test]
+    fn test_sha256_hex_known_value() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_cache_file_name_is_stable_and_safe() {
+        let name = cache_file_name(URL);
+        assert_eq!(name, cache_file_name(URL));
+        assert!(name.ends_with("-adafruit-circuitpython-bundle-7.x-mpy-20231010.zip"));
+        assert_ne!(name, cache_file_name("https://mirror.example.org/adafruit-circuitpython-bundle-7.x-mpy-20231010.zip"));
+        assert!(!cache_file_name("https://x/a b?c=d").contains(' '));
+    }
+
+    #[test]
+    fn test_partial_download_is_verified_and_promoted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(cache_file_name(URL));
+        fs::write(sidecar(&path, PARTIAL_SUFFIX), b"bundle bytes").unwrap();
+        let expected = sha256_hex(b"bundle bytes");
+
+        let result = finish(URL, &sidecar(&path, PARTIAL_SUFFIX), &path, Some(&expected)).unwrap();
+        assert_eq!(result.sha256, expected);
+        assert!(path.exists());
+
+        // Served from cache on the next request, no network needed
+        let cached = fetch(dir.path(), URL, Some(&expected)).unwrap();
+        assert!(cached.from_cache);
+        assert_eq!(list_cached(dir.path()).len(), 1);
+    }
+
+    #[test]
+    fn test_checksum_mismatch_discards_partial() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(cache_file_name(URL));
+        let partial = sidecar(&path, PARTIAL_SUFFIX);
+        fs::write(&partial, b"corrupted").unwrap();
+
+        let err = finish(URL, &partial, &path, Some(&sha256_hex(b"bundle bytes"))).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(!partial.exists());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_tampered_cache_entry_is_not_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(cache_file_name(URL));
+        fs::write(&path, b"bundle bytes").unwrap();
+        fs::write(sidecar(&path, HASH_SUFFIX), sha256_hex(b"bundle bytes")).unwrap();
+        assert!(lookup_cached(dir.path(), URL, None).is_some());
+
+        fs::write(&path, b"tampered").unwrap();
+        assert!(lookup_cached(dir.path(), URL, None).is_none());
+    }
+
+    #[test]
+    fn test_parse_checksum() {
+        let hash = sha256_hex(b"profiles");
+        assert_eq!(parse_checksum(&format!("{}  profiles.json\n", hash.to_uppercase())), Some(hash.clone()));
+        assert_eq!(parse_checksum(&hash), Some(hash));
+        assert_eq!(parse_checksum("<html>Not found</html>"), None);
+        assert_eq!(parse_checksum(""), None);
+    }
+}
@@ -0,0 +1,377 @@
+//! Plain-language explanations of a single control
+//!
+//! Describes what a button, the encoder or an expression pedal does on the
+//! device — messages sent, trigger mode, LED behavior, how incoming MIDI moves
+//! it — using the same defaults the firmware fills in (core/config.py and
+//! code.py). CC meanings come from the config's target profile. Powers the
+//! inspector panel and the cheat-sheet generator.
+
+use crate::commands::ConfigError;
+use crate::config::{
+    ButtonColor, ButtonConfig, ButtonMode, EncoderConfig, ExpressionConfig, MacroStepType, MessageType, MidiCaptainConfig, OffMode, Polarity, TapOutput,
+    DIM_BRIGHTNESS,
+};
+use crate::targets::{profile_for, SwitchBehavior, TargetProfile};
+use tauri::command;
+
+/// PC flash duration when `flash_ms` is unset (PC_FLASH_DURATION_MS in code.py)
+const DEFAULT_FLASH_MS: u16 = 200;
+
+/// Explanation of one control
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ControlExplanation {
+    /// Control id as given, e.g. "buttons[2]" or "encoder.push"
+    pub control_id: String,
+    /// Short name, e.g. "Switch 3 (DELAY)"
+    pub title: String,
+    /// One sentence on what the control sends
+    pub summary: String,
+    /// Further behavior, one fact per line
+    pub details: Vec<String>,
+}
+
+/// What the target profile says the CC does
+fn cc_meaning(profile: Option<&TargetProfile>, cc: u8) -> String {
+    match profile.and_then(|p| p.cc(cc).map(|f| (p, f))) {
+        Some((profile, function)) => match function.behavior {
+            Some(behavior) => format!(
+                "CC {} is {} on {} ({} switch).",
+                cc,
+                function.name,
+                profile.name,
+                match behavior {
+                    SwitchBehavior::Latching => "latching",
+                    SwitchBehavior::Momentary => "momentary",
+                }
+            ),
+            None => format!("CC {} is {} on {}.", cc, function.name, profile.name),
+        },
+        None => format!("CC {} has no standard meaning; it does whatever the receiving device maps it to.", cc),
+    }
+}
+
+fn color_name(color: &ButtonColor) -> String {
+    color.name().to_string()
+}
+
+fn channel_text(channel: Option<u8>, global: u8) -> String {
+    match channel {
+        Some(ch) => format!("channel {}", u16::from(ch) + 1),
+        None => format!("channel {} (global)", u16::from(global) + 1),
+    }
+}
+
+fn explain_button(index: usize, button: &ButtonConfig, global: u8, profile: Option<&TargetProfile>) -> (String, String, Vec<String>) {
+    let title = if button.label.trim().is_empty() {
+        format!("Switch {}", index + 1)
+    } else {
+        format!("Switch {} ({})", index + 1, button.label)
+    };
+    let channel = channel_text(button.channel, global);
+    let mut details = Vec::new();
+
+    let summary = match button.message_type {
+        MessageType::Cc => {
+            let cc = button.cc.unwrap_or((20 + index).min(127) as u8);
+            let (on, off) = (button.cc_on.unwrap_or(127), button.cc_off.unwrap_or(0));
+            details.push(cc_meaning(profile, cc));
+            details.push(format!("Incoming CC {} on {} turns the switch on or off to match.", cc, channel));
+            match button.mode {
+                ButtonMode::Momentary => format!("Sends CC {}={} on press and CC {}={} on release, on {}.", cc, on, cc, off, channel),
+                ButtonMode::Toggle => format!("Each press alternates CC {}={} and CC {}={}, on {}.", cc, on, cc, off, channel),
+                ButtonMode::TapTempo => {
+                    let tap = button.tap_tempo.clone().unwrap_or_default();
+                    let (min, max) = tap.bpm_range();
+                    details.push(format!(
+                        "Tempo is the average of the last taps, kept within {}-{} BPM; a gap longer than a beat at {} BPM starts over.",
+                        min, max, min
+                    ));
+                    match tap.output {
+                        TapOutput::Cc => format!("Tap tempo: sends CC {}={} on {} on every tap.", cc, on, channel),
+                        TapOutput::Clock => "Tap tempo: sends MIDI clock at the tapped tempo.".to_string(),
+                    }
+                }
+            }
+        }
+        MessageType::Note => {
+            let note = button.note.unwrap_or(60);
+            let (on, off) = (button.velocity_on.unwrap_or(127), button.velocity_off.unwrap_or(0));
+            details.push(format!("Incoming note {} on {} turns the switch on or off to match.", note, channel));
+            match button.mode {
+                ButtonMode::Momentary => format!(
+                    "Sends Note On {} (velocity {}) on press and Note Off (velocity {}) on release, on {}.",
+                    note, on, off, channel
+                ),
+                ButtonMode::Toggle | ButtonMode::TapTempo => format!(
+                    "Each press alternates Note On {} (velocity {}) and Note Off (velocity {}), on {}.",
+                    note, on, off, channel
+                ),
+            }
+        }
+        MessageType::Pc => format!("Sends Program Change {} on {} when pressed.", button.program.unwrap_or(0), channel),
+        MessageType::PcInc | MessageType::PcDec => {
+            let step = button.pc_step.unwrap_or(1);
+            let direction = if button.message_type == MessageType::PcInc { "up" } else { "down" };
+            details.push(format!(
+                "The current program is shared by every PC up/down switch on {} and follows incoming Program Change.",
+                channel
+            ));
+            format!("Steps the program {} by {} and sends it as a Program Change on {}.", direction, step, channel)
+        }
+        MessageType::Panic => {
+            let reset = button.reset_ccs.as_deref().unwrap_or_default();
+            if !reset.is_empty() {
+                let ccs: Vec<String> = reset.iter().map(|cc| cc.to_string()).collect();
+                details.push(format!("Also sets CC {} to 0 on every channel.", ccs.join(", ")));
+            }
+            details.push("Note switches that were on are switched off.".to_string());
+            "Sends All Notes Off and All Sound Off on all 16 channels when pressed.".to_string()
+        }
+        MessageType::Macro => {
+            let steps = button.steps.as_deref().unwrap_or_default();
+            for (n, step) in steps.iter().enumerate() {
+                let message = match step.message_type {
+                    MacroStepType::Cc => format!("CC {}={}", step.cc.unwrap_or(0), step.value.unwrap_or(127)),
+                    MacroStepType::Pc => format!("Program Change {}", step.program.unwrap_or(0)),
+                    MacroStepType::Note => format!("Note On {} (velocity {})", step.note.unwrap_or(60), step.velocity.unwrap_or(127)),
+                };
+                let delay = match step.delay_ms.filter(|ms| *ms > 0) {
+                    Some(ms) => format!("{} ms later, ", ms),
+                    None => String::new(),
+                };
+                details.push(format!("Step {}: {}{} on {}.", n + 1, delay, message, channel_text(step.channel.or(button.channel), global)));
+            }
+            format!("Sends a sequence of {} messages when pressed.", steps.len())
+        }
+    };
+
+    let color = color_name(&button.color);
+    match button.message_type {
+        MessageType::Cc | MessageType::Note => {
+            let on = match button.led.as_ref().and_then(|led| led.on_color.as_ref()) {
+                Some(on_color) => color_name(on_color),
+                None => color.clone(),
+            };
+            let off = match &button.led {
+                Some(_) if button.led_rgb().1 == (0, 0, 0) => "dark".to_string(),
+                Some(led) => {
+                    let brightness = led.off_brightness.unwrap_or(DIM_BRIGHTNESS);
+                    match &led.off_color {
+                        Some(off_color) => format!("{} at {}%", color_name(off_color), brightness),
+                        None => format!("dimmed to {}%", brightness),
+                    }
+                }
+                None => match button.off_mode {
+                    OffMode::Dim => "dimmed".to_string(),
+                    OffMode::Off => "dark".to_string(),
+                },
+            };
+            details.push(format!("LED: {} when on, {} when off.", on, off));
+        }
+        _ => details.push(format!(
+            "LED: flashes {} for {} ms on each press.",
+            color,
+            button.flash_ms.unwrap_or(DEFAULT_FLASH_MS)
+        )),
+    }
+
+    if let Some(keytimes) = button.keytimes.filter(|k| *k > 1) {
+        details.push(format!(
+            "Cycles through {} states, one per press, and stays lit while cycling.",
+            keytimes
+        ));
+        for (i, state) in button.states.iter().flatten().enumerate() {
+            let mut parts = Vec::new();
+            if let Some(label) = &state.label {
+                parts.push(format!("label {}", label));
+            }
+            for (name, value) in [
+                ("CC", state.cc),
+                ("on value", state.cc_on),
+                ("off value", state.cc_off),
+                ("note", state.note),
+                ("program", state.program),
+                ("step", state.pc_step),
+            ] {
+                if let Some(value) = value {
+                    parts.push(format!("{} {}", name, value));
+                }
+            }
+            if let Some(color) = &state.color {
+                parts.push(color_name(color));
+            }
+            if !parts.is_empty() {
+                details.push(format!("State {}: {}.", i + 1, parts.join(", ")));
+            }
+        }
+    }
+
+    (title, summary, details)
+}
+
+fn explain_encoder(encoder: &EncoderConfig, global: u8, profile: Option<&TargetProfile>) -> (String, String, Vec<String>) {
+    let channel = channel_text(encoder.channel, global);
+    let mut details = vec![
+        cc_meaning(profile, encoder.cc),
+        format!("Starts at {} after power-up.", encoder.initial),
+    ];
+    if let Some(steps) = encoder.steps {
+        details.push(format!("The range is divided into {} steps.", steps));
+    }
+    if !encoder.enabled {
+        details.insert(0, "Disabled: turning the encoder sends nothing.".to_string());
+    }
+    (
+        format!("Encoder ({})", encoder.label),
+        format!("Turning sends CC {} from {} to {} on {}.", encoder.cc, encoder.min, encoder.max, channel),
+        details,
+    )
+}
+
+fn explain_push(encoder: &EncoderConfig, global: u8, profile: Option<&TargetProfile>) -> Option<(String, String, Vec<String>)> {
+    let push = encoder.push.as_ref()?;
+    let channel = channel_text(push.channel, global);
+    let (on, off) = (push.cc_on.unwrap_or(127), push.cc_off.unwrap_or(0));
+    let summary = match push.mode {
+        ButtonMode::Momentary | ButtonMode::TapTempo => {
+            format!("Sends CC {}={} on press and CC {}={} on release, on {}.", push.cc, on, push.cc, off, channel)
+        }
+        ButtonMode::Toggle => format!("Each press alternates CC {}={} and CC {}={}, on {}.", push.cc, on, push.cc, off, channel),
+    };
+    let mut details = vec![cc_meaning(profile, push.cc)];
+    if !push.enabled {
+        details.insert(0, "Disabled: pressing the encoder sends nothing.".to_string());
+    }
+    Some((format!("Encoder push ({})", push.label), summary, details))
+}
+
+fn explain_pedal(name: &str, pedal: &ExpressionConfig, global: u8, profile: Option<&TargetProfile>) -> (String, String, Vec<String>) {
+    let channel = channel_text(pedal.channel, global);
+    let (heel, toe) = match pedal.polarity {
+        Polarity::Normal => (pedal.min, pedal.max),
+        Polarity::Inverted => (pedal.max, pedal.min),
+    };
+    let mut details = vec![
+        cc_meaning(profile, pedal.cc),
+        format!("Changes smaller than {} are ignored to avoid jitter.", pedal.threshold),
+    ];
+    if !pedal.enabled {
+        details.insert(0, "Disabled: moving the pedal sends nothing.".to_string());
+    }
+    (
+        format!("Expression {} ({})", name.trim_start_matches("exp"), pedal.label),
+        format!("Sends CC {} from {} (heel) to {} (toe) on {}.", pedal.cc, heel, toe, channel),
+        details,
+    )
+}
+
+fn unknown_control(control_id: &str) -> ConfigError {
+    ConfigError {
+        message: format!("Unknown control '{}'", control_id),
+        details: None,
+        code: None,
+    }
+}
+
+/// Explain a control by id: "buttons[N]" (0-based, as in lint targets),
+/// "encoder", "encoder.push", "expression.exp1" or "expression.exp2"
+pub fn explain(config: &MidiCaptainConfig, control_id: &str) -> Result<ControlExplanation, ConfigError> {
+    let global = config.global_channel.unwrap_or(0);
+    let profile = profile_for(config);
+    let profile = profile.as_ref();
+    let explained = if let Some(index) = control_id
+        .strip_prefix("buttons[")
+        .and_then(|rest| rest.strip_suffix(']'))
+        .and_then(|n| n.parse::<usize>().ok())
+    {
+        config.buttons.get(index).map(|button| explain_button(index, button, global, profile))
+    } else {
+        match control_id {
+            "encoder" => config.encoder.as_ref().map(|e| explain_encoder(e, global, profile)),
+            "encoder.push" => config.encoder.as_ref().and_then(|e| explain_push(e, global, profile)),
+            "expression.exp1" => config.expression.as_ref().map(|x| explain_pedal("exp1", &x.exp1, global, profile)),
+            "expression.exp2" => config.expression.as_ref().map(|x| explain_pedal("exp2", &x.exp2, global, profile)),
+            _ => None,
+        }
+    };
+    let (title, summary, details) = explained.ok_or_else(|| unknown_control(control_id))?;
+    Ok(ControlExplanation {
+        control_id: control_id.to_string(),
+        title,
+        summary,
+        details,
+    })
+}
+
+/// Describe in plain language what one control does
+#[command]
+pub fn explain_control(config: MidiCaptainConfig, control_id: String) -> Result<ControlExplanation, ConfigError> {
+    explain(&config, &control_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> MidiCaptainConfig {
+        serde_json::from_str(
+            r#"{
+                "global_channel": 1,
+                "buttons": [
+                    {"label": "DRIVE", "color": "red", "cc": 64, "mode": "momentary"},
+                    {"label": "LEAD", "color": "blue", "type": "pc", "program": 5},
+                    {"label": "UP", "color": "green", "type": "pc_inc", "pc_step": 2, "channel": 3},
+                    {"label": "SCENE", "color": "white", "cc": 30, "keytimes": 3,
+                     "states": [{"cc_on": 0}, {"cc_on": 64, "color": "cyan"}, {"cc_on": 127}]}
+                ],
+                "expression": {
+                    "exp1": {"enabled": true, "cc": 11, "label": "VOL", "polarity": "inverted"},
+                    "exp2": {"enabled": false, "cc": 12, "label": "WAH"}
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_explain_momentary_cc_button() {
+        let e = explain(&config(), "buttons[0]").unwrap();
+        assert_eq!(e.title, "Switch 1 (DRIVE)");
+        assert_eq!(e.summary, "Sends CC 64=127 on press and CC 64=0 on release, on channel 2 (global).");
+        assert!(e.details.iter().any(|d| d.contains("Sustain Pedal")));
+        assert!(e.details.iter().any(|d| d == "LED: red when on, dimmed when off."));
+    }
+
+    #[test]
+    fn test_explain_program_change_buttons() {
+        let pc = explain(&config(), "buttons[1]").unwrap();
+        assert_eq!(pc.summary, "Sends Program Change 5 on channel 2 (global) when pressed.");
+        assert!(pc.details.iter().any(|d| d == "LED: flashes blue for 200 ms on each press."));
+
+        let inc = explain(&config(), "buttons[2]").unwrap();
+        assert!(inc.summary.starts_with("Steps the program up by 2"));
+        assert!(inc.summary.ends_with("on channel 4."));
+    }
+
+    #[test]
+    fn test_explain_keytimes_states() {
+        let e = explain(&config(), "buttons[3]").unwrap();
+        assert!(e.details.iter().any(|d| d.starts_with("Cycles through 3 states")));
+        assert!(e.details.iter().any(|d| d == "State 2: on value 64, cyan."));
+    }
+
+    #[test]
+    fn test_explain_pedals() {
+        let exp1 = explain(&config(), "expression.exp1").unwrap();
+        assert_eq!(exp1.summary, "Sends CC 11 from 127 (heel) to 0 (toe) on channel 2 (global).");
+        let exp2 = explain(&config(), "expression.exp2").unwrap();
+        assert!(exp2.details[0].starts_with("Disabled"));
+    }
+
+    #[test]
+    fn test_unknown_control() {
+        assert!(explain(&config(), "buttons[9]").is_err());
+        assert!(explain(&config(), "encoder").is_err());
+        assert!(explain(&config(), "knob").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! File access behind a trait
+//!
+//! The config write path (read, lock check, backup, write, verify) takes a
+//! `&dyn FileSystem` instead of calling `std::fs`, so it can be tested
+//! against `InMemoryFs` without a mounted volume. Commands pass `RealFs`.
+//! Mount checks (`validate_device_path`, `verify_device_connected`) stay
+//! outside: they are about the OS, not the files.
+
+use std::io;
+use std::path::Path;
+
+#[cfg(test)]
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(test)]
+use std::path::PathBuf;
+#[cfg(test)]
+use std::sync::Mutex;
+
+pub trait FileSystem: Send + Sync {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+    /// Replace `path` with `data`, all or nothing, flushed to storage
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()>;
+    fn is_file(&self, path: &Path) -> bool;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    /// Name and size of each file directly in `dir`
+    fn list_files(&self, dir: &Path) -> io::Result<Vec<(String, u64)>>;
+    /// Names of the directories directly in `dir`
+    fn list_dirs(&self, dir: &Path) -> io::Result<Vec<String>>;
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+}
+
+/// The host's filesystem
+pub struct RealFs;
+
+impl FileSystem for RealFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        crate::commands::write_sync(path, data)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn list_files(&self, dir: &Path) -> io::Result<Vec<(String, u64)>> {
+        Ok(std::fs::read_dir(dir)?
+            .flatten()
+            .filter_map(|e| {
+                let metadata = e.metadata().ok().filter(|m| m.is_file())?;
+                Some((e.file_name().to_str()?.to_string(), metadata.len()))
+            })
+            .collect())
+    }
+
+    fn list_dirs(&self, dir: &Path) -> io::Result<Vec<String>> {
+        Ok(std::fs::read_dir(dir)?
+            .flatten()
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+            .filter_map(|e| e.file_name().to_str().map(str::to_string))
+            .collect())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Files held in memory. Test-only until a caller without OS mounts (a
+/// device simulator) needs it.
+#[cfg(test)]
+#[derive(Default)]
+pub struct InMemoryFs {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+    dirs: Mutex<BTreeSet<PathBuf>>,
+}
+
+#[cfg(test)]
+impl InMemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file (and its parent directories)
+    pub fn with_file(self, path: impl AsRef<Path>, data: impl AsRef<[u8]>) -> Self {
+        self.write(path.as_ref(), data.as_ref()).unwrap();
+        self
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, format!("{} not found", path.display()))
+    }
+}
+
+#[cfg(test)]
+impl FileSystem for InMemoryFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files.lock().unwrap().get(path).cloned().ok_or_else(|| Self::not_found(path))
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)?;
+        }
+        self.files.lock().unwrap().insert(path.to_path_buf(), data.to_vec());
+        Ok(())
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.dirs.lock().unwrap().extend(path.ancestors().map(Path::to_path_buf));
+        Ok(())
+    }
+
+    fn list_files(&self, dir: &Path) -> io::Result<Vec<(String, u64)>> {
+        if !self.dirs.lock().unwrap().contains(dir) {
+            return Err(Self::not_found(dir));
+        }
+        Ok(self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(path, _)| path.parent() == Some(dir))
+            .filter_map(|(path, data)| Some((path.file_name()?.to_str()?.to_string(), data.len() as u64)))
+            .collect())
+    }
+
+    fn list_dirs(&self, dir: &Path) -> io::Result<Vec<String>> {
+        Ok(self
+            .dirs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|path| path.parent() == Some(dir))
+            .filter_map(|path| Some(path.file_name()?.to_str()?.to_string()))
+            .collect())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.files.lock().unwrap().remove(path).map(|_| ()).ok_or_else(|| Self::not_found(path))
+    }
+}
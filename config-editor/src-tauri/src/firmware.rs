@@ -0,0 +1,438 @@
+//! Firmware payload management: rollback slot for code.py/lib
+//!
+//! Before new firmware lands on a device, the current payload is stashed in a
+//! per-device slot under the app-data directory. `rollback_firmware` copies it
+//! back, giving one-command recovery after a bad update. Slot files are stored
+//! zstd-compressed; slots captured before that are restored as plain copies.
+
+use crate::archive::{compress, compressed_path, decompress, COMPRESSED_SUFFIX};
+use crate::commands::{app_data_dir, validate_device_path, verify_device_connected, write_sync, ConfigError};
+use crate::confirm::{confirm, Confirmable};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{command, AppHandle};
+
+/// Files and directories that make up the firmware payload on the device.
+/// `config.json` is deliberately excluded — it belongs to the user, not the firmware.
+pub const FIRMWARE_ENTRIES: &[&str] = &[
+    "boot.py",
+    "code.py",
+    "VERSION",
+    "firmware.md5",
+    "core",
+    "devices",
+    "fonts",
+    "lib",
+];
+
+/// Directories whose contents are mirrored exactly on restore (stale files removed),
+/// matching the `--delete` rsync flags in deploy.sh. Leaving an old `.py` next to a
+/// new `.mpy` makes CircuitPython load the wrong one.
+pub(crate) const MIRRORED_DIRS: &[&str] = &["core", "devices"];
+
+/// Name of the metadata file written into each rollback slot
+const SLOT_MANIFEST: &str = "slot.json";
+
+/// Metadata describing a stashed firmware payload
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FirmwareSlot {
+    /// Firmware version from the device's VERSION file, if present
+    pub version: Option<String>,
+    /// Unix timestamp (seconds) when the slot was captured
+    pub created_at: u64,
+    /// Top-level entries captured (subset of `FIRMWARE_ENTRIES`)
+    pub entries: Vec<String>,
+    /// Files are stored with a `.zst` suffix (false for older slots)
+    #[serde(default)]
+    pub compressed: bool,
+}
+
+/// How file contents change as they are copied between device and slot
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Transfer {
+    Plain,
+    Compress,
+    Decompress,
+}
+
+impl Transfer {
+    /// Source and destination paths for a file called `name`
+    fn file_paths(self, src: &Path, dest: &Path, name: &str) -> (PathBuf, PathBuf) {
+        match self {
+            Transfer::Plain => (src.join(name), dest.join(name)),
+            Transfer::Compress => (src.join(name), compressed_path(&dest.join(name))),
+            Transfer::Decompress => (compressed_path(&src.join(name)), dest.join(name)),
+        }
+    }
+
+    fn apply(self, data: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        match self {
+            Transfer::Plain => Ok(data),
+            Transfer::Compress => compress(&data),
+            Transfer::Decompress => decompress(&data),
+        }
+    }
+}
+
+/// Current Unix time in seconds
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Read the firmware version string from a device or payload directory
+pub fn read_firmware_version(root: &Path) -> Option<String> {
+    let version = fs::read_to_string(root.join("VERSION")).ok()?;
+    let version = version.trim();
+    if version.is_empty() {
+        None
+    } else {
+        Some(version.to_string())
+    }
+}
+
+/// Numeric parts of a version such as "v1.5.0-11-gabc1234" ([1, 5, 0]);
+/// `None` for a bare commit hash
+pub fn parse_version(version: &str) -> Option<Vec<u32>> {
+    let core = version.trim().trim_start_matches('v').split(['-', '+']).next()?;
+    core.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// True if firmware `device` is older than `shipped`. Unparseable versions
+/// (dev builds) never count as outdated.
+pub fn is_outdated(device: &str, shipped: &str) -> bool {
+    match (parse_version(device), parse_version(shipped)) {
+        (Some(device), Some(shipped)) => device < shipped,
+        _ => false,
+    }
+}
+
+/// Parse the CircuitPython version from boot_out.txt contents,
+/// e.g. "Adafruit CircuitPython 7.3.3 on 2022-08-29; Raspberry Pi Pico with rp2040" → "7.3.3"
+pub fn parse_circuitpython_version(boot_out: &str) -> Option<String> {
+    let line = boot_out.lines().find(|l| l.contains("CircuitPython"))?;
+    let rest = line.split("CircuitPython").nth(1)?;
+    rest.split_whitespace().next().map(|v| v.to_string())
+}
+
+/// Read the CircuitPython version the device booted with (from boot_out.txt)
+pub fn read_circuitpython_version(root: &Path) -> Option<String> {
+    let contents = fs::read_to_string(root.join("boot_out.txt")).ok()?;
+    parse_circuitpython_version(&contents)
+}
+
+/// True for host-side clutter that never belongs in a firmware copy: hidden files
+/// (`.DS_Store`, `._*` resource forks) and `__pycache__`, matching the excludes
+/// used by deploy.sh.
+pub(crate) fn is_host_metadata(name: &str) -> bool {
+    name.starts_with('.') || name == "__pycache__"
+}
+
+/// Recursively copy a directory tree, syncing each file to storage, skipping
+/// host metadata (see `is_host_metadata`).
+fn copy_dir_synced(src: &Path, dest: &Path, transfer: Transfer) -> std::io::Result<()> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name_str = name.to_string_lossy();
+        if is_host_metadata(&name_str) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            copy_dir_synced(&entry.path(), &dest.join(&name), transfer)?;
+        } else {
+            let name_str = match transfer {
+                Transfer::Decompress => name_str.strip_suffix(COMPRESSED_SUFFIX).unwrap_or(&name_str),
+                _ => &name_str,
+            };
+            copy_file(src, dest, name_str, transfer)?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_file(src: &Path, dest: &Path, name: &str, transfer: Transfer) -> std::io::Result<()> {
+    let (from, to) = transfer.file_paths(src, dest, name);
+    write_sync(&to, &transfer.apply(fs::read(&from)?)?)
+}
+
+/// Copy a single firmware entry (file or directory) from `src_root` to `dest_root`
+fn copy_entry(src_root: &Path, dest_root: &Path, entry: &str, transfer: Transfer) -> std::io::Result<()> {
+    let from = src_root.join(entry);
+    if from.is_dir() {
+        copy_dir_synced(&from, &dest_root.join(entry), transfer)
+    } else {
+        copy_file(src_root, dest_root, entry, transfer)
+    }
+}
+
+/// Capture the firmware payload currently on `device_root` into `slot_dir`,
+/// replacing whatever the slot held before.
+pub fn stash_firmware(device_root: &Path, slot_dir: &Path) -> std::io::Result<FirmwareSlot> {
+    if slot_dir.exists() {
+        fs::remove_dir_all(slot_dir)?;
+    }
+    fs::create_dir_all(slot_dir)?;
+
+    let mut entries = Vec::new();
+    for entry in FIRMWARE_ENTRIES {
+        if device_root.join(entry).exists() {
+            copy_entry(device_root, slot_dir, entry, Transfer::Compress)?;
+            entries.push(entry.to_string());
+        }
+    }
+
+    let slot = FirmwareSlot {
+        version: read_firmware_version(device_root),
+        created_at: unix_now(),
+        entries,
+        compressed: true,
+    };
+    let json = serde_json::to_string_pretty(&slot)?;
+    fs::write(slot_dir.join(SLOT_MANIFEST), json)?;
+    Ok(slot)
+}
+
+/// Load the metadata for a rollback slot, if one has been captured
+pub fn read_slot(slot_dir: &Path) -> Option<FirmwareSlot> {
+    let contents = fs::read_to_string(slot_dir.join(SLOT_MANIFEST)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Copy a stashed payload from `slot_dir` back onto `device_root`.
+///
+/// Dependencies go first and `code.py` last, so the entry point never runs
+/// against a half-written library set.
+pub fn restore_firmware(slot_dir: &Path, device_root: &Path) -> std::io::Result<FirmwareSlot> {
+    restore_slot(slot_dir, device_root, false)
+}
+
+/// Like `restore_firmware`, but leaves the firmware exactly as captured:
+/// every captured directory is mirrored, not just `MIRRORED_DIRS`, and
+/// firmware entries the slot doesn't hold are removed. Used by recovery points.
+pub fn restore_firmware_exact(slot_dir: &Path, device_root: &Path) -> std::io::Result<FirmwareSlot> {
+    restore_slot(slot_dir, device_root, true)
+}
+
+fn restore_slot(slot_dir: &Path, device_root: &Path, exact: bool) -> std::io::Result<FirmwareSlot> {
+    let slot = read_slot(slot_dir).ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "No firmware stashed in rollback slot")
+    })?;
+
+    if exact {
+        for entry in FIRMWARE_ENTRIES.iter().filter(|e| !slot.entries.iter().any(|s| s == *e)) {
+            let target = device_root.join(entry);
+            if target.is_dir() {
+                fs::remove_dir_all(&target)?;
+            } else if target.exists() {
+                fs::remove_file(&target)?;
+            }
+        }
+    }
+
+    let transfer = if slot.compressed { Transfer::Decompress } else { Transfer::Plain };
+    let (last, rest): (Vec<&String>, Vec<&String>) =
+        slot.entries.iter().partition(|e| e.as_str() == "code.py");
+
+    for entry in rest.into_iter().chain(last) {
+        if exact || MIRRORED_DIRS.contains(&entry.as_str()) {
+            let target = device_root.join(entry);
+            if target.is_dir() {
+                fs::remove_dir_all(&target)?;
+            }
+        }
+        copy_entry(slot_dir, device_root, entry, transfer)?;
+    }
+
+    Ok(slot)
+}
+
+/// Directory holding every device's rollback slot
+pub fn rollback_root(data_dir: &Path) -> PathBuf {
+    data_dir.join("rollback")
+}
+
+/// Rollback slot directory for a device, keyed by its volume name
+pub fn rollback_slot_dir(data_dir: &Path, device_root: &Path) -> PathBuf {
+    let key = device_root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "device".to_string());
+    rollback_root(data_dir).join(key)
+}
+
+/// Stash the device's current firmware so it can be restored with `rollback_firmware`.
+/// Called before installing new firmware.
+#[command]
+pub fn stash_firmware_for_rollback(app: AppHandle, device_path: String) -> Result<FirmwareSlot, ConfigError> {
+    let device_root = &validate_device_path(&device_path)?;
+    let slot_dir = rollback_slot_dir(&app_data_dir(&app)?, device_root);
+    Ok(stash_firmware(device_root, &slot_dir)?)
+}
+
+/// Describe the firmware held in the device's rollback slot, if any
+#[command]
+pub fn get_rollback_info(app: AppHandle, device_path: String) -> Result<Option<FirmwareSlot>, ConfigError> {
+    let slot_dir = rollback_slot_dir(&app_data_dir(&app)?, Path::new(&device_path));
+    Ok(read_slot(&slot_dir))
+}
+
+/// Restore the previously stashed firmware onto the device. Takes a
+/// `confirmation` token: the firmware on the device is replaced.
+#[command]
+pub fn rollback_firmware(
+    app: AppHandle,
+    device_path: String,
+    confirmation: Option<String>,
+) -> Result<Confirmable<FirmwareSlot>, ConfigError> {
+    let device_root = &validate_device_path(&device_path)?;
+    verify_device_connected(device_root)?;
+
+    let slot_dir = rollback_slot_dir(&app_data_dir(&app)?, device_root);
+    let slot = read_slot(&slot_dir).ok_or_else(|| ConfigError {
+        message: "No firmware is stashed for this device, so there is nothing to roll back to".to_string(),
+        details: None,
+        code: None,
+    })?;
+    let target = device_root.display().to_string();
+    confirm(
+        &["rollback_firmware", &target],
+        || {
+            format!(
+                "Replace the firmware on {} (version {}) with the stashed firmware (version {})",
+                target,
+                read_firmware_version(device_root).as_deref().unwrap_or("unknown"),
+                slot.version.as_deref().unwrap_or("unknown")
+            )
+        },
+        confirmation.as_deref(),
+        || {
+            restore_firmware(&slot_dir, device_root).map_err(|e| ConfigError {
+                message: format!("Rollback failed: {}", e),
+                details: None,
+                code: None,
+            })
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_payload(root: &Path, version: &str) {
+        fs::write(root.join("code.py"), format!("# code {}", version)).unwrap();
+        fs::write(root.join("boot.py"), "# boot").unwrap();
+        fs::write(root.join("VERSION"), version).unwrap();
+        fs::create_dir_all(root.join("core")).unwrap();
+        fs::write(root.join("core").join("config.py"), "# config").unwrap();
+        fs::create_dir_all(root.join("lib").join("adafruit_midi")).unwrap();
+        fs::write(root.join("lib").join("adafruit_midi").join("__init__.mpy"), [0x43, 0x05]).unwrap();
+        fs::write(root.join("config.json"), r#"{"device": "std10", "buttons": []}"#).unwrap();
+    }
+
+    #[test]
+    fn test_stash_captures_firmware_but_not_config() {
+        let device = tempfile::tempdir().unwrap();
+        let slot = tempfile::tempdir().unwrap();
+        make_payload(device.path(), "1.2.0");
+
+        let info = stash_firmware(device.path(), slot.path()).unwrap();
+        assert_eq!(info.version.as_deref(), Some("1.2.0"));
+        assert!(info.entries.contains(&"code.py".to_string()));
+        assert!(info.entries.contains(&"lib".to_string()));
+        assert!(info.compressed);
+        assert!(slot.path().join("lib/adafruit_midi/__init__.mpy.zst").exists());
+        assert!(!slot.path().join("config.json").exists());
+    }
+
+    #[test]
+    fn test_restore_returns_previous_version() {
+        let device = tempfile::tempdir().unwrap();
+        let slot = tempfile::tempdir().unwrap();
+        make_payload(device.path(), "1.2.0");
+        stash_firmware(device.path(), slot.path()).unwrap();
+
+        // Simulate a bad update that also leaves a stale module behind
+        make_payload(device.path(), "1.3.0-broken");
+        fs::write(device.path().join("core").join("stale.py"), "# stale").unwrap();
+
+        let restored = restore_firmware(slot.path(), device.path()).unwrap();
+        assert_eq!(restored.version.as_deref(), Some("1.2.0"));
+        assert_eq!(read_firmware_version(device.path()).as_deref(), Some("1.2.0"));
+        assert!(!device.path().join("core").join("stale.py").exists());
+        // User config is untouched by rollback
+        assert!(device.path().join("config.json").exists());
+    }
+
+    #[test]
+    fn test_exact_restore_mirrors_everything() {
+        let device = tempfile::tempdir().unwrap();
+        let slot = tempfile::tempdir().unwrap();
+        make_payload(device.path(), "1.2.0");
+        stash_firmware(device.path(), slot.path()).unwrap();
+
+        make_payload(device.path(), "1.3.0");
+        fs::write(device.path().join("lib").join("extra.mpy"), [0x43]).unwrap();
+        fs::create_dir_all(device.path().join("fonts")).unwrap();
+
+        restore_firmware_exact(slot.path(), device.path()).unwrap();
+        assert_eq!(read_firmware_version(device.path()).as_deref(), Some("1.2.0"));
+        assert!(!device.path().join("lib").join("extra.mpy").exists());
+        assert!(!device.path().join("fonts").exists());
+        assert!(device.path().join("config.json").exists());
+    }
+
+    #[test]
+    fn test_restore_uncompressed_slot() {
+        let device = tempfile::tempdir().unwrap();
+        let slot = tempfile::tempdir().unwrap();
+        make_payload(slot.path(), "1.1.0");
+        let manifest = r#"{"version": "1.1.0", "created_at": 0, "entries": ["code.py", "VERSION", "lib"]}"#;
+        fs::write(slot.path().join(SLOT_MANIFEST), manifest).unwrap();
+
+        let restored = restore_firmware(slot.path(), device.path()).unwrap();
+        assert!(!restored.compressed);
+        assert_eq!(read_firmware_version(device.path()).as_deref(), Some("1.1.0"));
+        assert!(device.path().join("lib/adafruit_midi/__init__.mpy").exists());
+    }
+
+    #[test]
+    fn test_restore_empty_slot_fails() {
+        let device = tempfile::tempdir().unwrap();
+        let slot = tempfile::tempdir().unwrap();
+        assert!(restore_firmware(slot.path(), device.path()).is_err());
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("v1.5.0-11-gabc1234"), Some(vec![1, 5, 0]));
+        assert_eq!(parse_version("1.2.0\n"), Some(vec![1, 2, 0]));
+        assert_eq!(parse_version("abc1234"), None);
+    }
+
+    #[test]
+    fn test_outdated_firmware() {
+        assert!(is_outdated("1.2.0", "1.10.0"));
+        assert!(!is_outdated("1.10.0", "1.10.0"));
+        assert!(!is_outdated("2.0.0", "1.10.0"));
+        assert!(!is_outdated("abc1234", "1.10.0"));
+    }
+
+    #[test]
+    fn test_parse_circuitpython_version() {
+        let boot_out = "Adafruit CircuitPython 7.3.3 on 2022-08-29; Raspberry Pi Pico with rp2040\nBoard ID:raspberry_pi_pico\n";
+        assert_eq!(parse_circuitpython_version(boot_out).as_deref(), Some("7.3.3"));
+        assert_eq!(parse_circuitpython_version("garbage"), None);
+    }
+
+    #[test]
+    fn test_rollback_slot_dir_keyed_by_volume() {
+        let dir = rollback_slot_dir(Path::new("/data"), Path::new("/Volumes/CIRCUITPY"));
+        assert_eq!(dir, PathBuf::from("/data/rollback/CIRCUITPY"));
+    }
+}
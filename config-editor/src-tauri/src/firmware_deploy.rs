@@ -0,0 +1,288 @@
+//! Firmware deployment: install code.py and its libraries on a device
+//!
+//! `deploy_firmware` takes a firmware bundle — the release zip built by CI
+//! (`midicaptain-firmware-<version>.zip`) or an unpacked copy of it, such as
+//! `firmware/dev` — and copies its payload (`FIRMWARE_ENTRIES`) onto the
+//! device the way deploy.sh does:
+//!
+//! - the current firmware is stashed in the rollback slot first, so a bad
+//!   update is one `rollback_firmware` away;
+//! - files already identical on the device are skipped;
+//! - `core/` and `devices/` are mirrored (stale files removed), so an old
+//!   `.py` never shadows a new `.mpy`;
+//! - `code.py` is written last, so it never runs against half-written libs;
+//! - every written file is read back and compared.
+//!
+//! `config.json` is never touched: it belongs to the user. Progress is
+//! emitted as "firmware-deploy-progress".
+
+use crate::commands::{app_data_dir, validate_device_path, verify_device_connected, write_sync, ConfigError};
+use crate::firmware::{
+    is_host_metadata, read_firmware_version, rollback_slot_dir, stash_firmware, FIRMWARE_ENTRIES, MIRRORED_DIRS,
+};
+use crate::health::resolve_volume;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path};
+use tauri::{command, AppHandle, Emitter};
+
+/// Firmware payload files by path relative to the device root ("lib/neopixel.mpy")
+type Payload = BTreeMap<String, Vec<u8>>;
+
+/// One file handled, emitted as "firmware-deploy-progress"
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeployProgress {
+    /// Path relative to the device root
+    pub file: String,
+    /// Files handled so far, including this one
+    pub done: usize,
+    pub total: usize,
+    /// True when the device already had this file unchanged
+    pub skipped: bool,
+}
+
+/// Result of `deploy_firmware`
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct FirmwareDeploy {
+    /// Version of the installed bundle (its VERSION file)
+    pub version: Option<String>,
+    /// Version on the device before, now in the rollback slot
+    pub previous_version: Option<String>,
+    /// Files written (and verified)
+    pub copied: Vec<String>,
+    /// Files already up to date
+    pub unchanged: usize,
+    /// Stale files removed from mirrored directories
+    pub removed: Vec<String>,
+}
+
+fn deploy_error(message: String) -> ConfigError {
+    ConfigError {
+        message,
+        details: None,
+        code: None,
+    }
+}
+
+/// True for a relative path made of plain names, none of them host metadata
+fn is_payload_path(path: &str) -> bool {
+    let path = Path::new(path);
+    path.components().all(|c| match c {
+        Component::Normal(name) => !is_host_metadata(&name.to_string_lossy()),
+        _ => false,
+    })
+}
+
+/// Keep the firmware entries under `prefix` (the directory holding code.py)
+fn select_payload(files: Payload) -> Result<Payload, ConfigError> {
+    let prefix = files
+        .keys()
+        .filter_map(|name| name.strip_suffix("code.py"))
+        .filter(|prefix| prefix.is_empty() || prefix.ends_with('/'))
+        .min_by_key(|prefix| prefix.len())
+        .map(str::to_string)
+        .ok_or_else(|| deploy_error("Not a firmware bundle: no code.py found".to_string()))?;
+    Ok(files
+        .into_iter()
+        .filter_map(|(name, data)| {
+            let relative = name.strip_prefix(&prefix)?.to_string();
+            let top = relative.split('/').next()?;
+            (FIRMWARE_ENTRIES.contains(&top) && is_payload_path(&relative)).then_some((relative, data))
+        })
+        .collect())
+}
+
+fn read_dir_files(root: &Path, dir: &Path, files: &mut Payload) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            read_dir_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let name = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+            files.insert(name, fs::read(&path)?);
+        }
+    }
+    Ok(())
+}
+
+fn read_zip_files(archive: &Path) -> io::Result<Payload> {
+    let mut zip = zip::ZipArchive::new(fs::File::open(archive)?).map_err(io::Error::other)?;
+    let names: Vec<String> = zip.file_names().filter(|n| !n.ends_with('/')).map(str::to_string).collect();
+    let mut files = Payload::new();
+    for name in names {
+        let mut file = zip.by_name(&name).map_err(io::Error::other)?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        files.insert(name, data);
+    }
+    Ok(files)
+}
+
+/// Load the firmware payload of a bundle (zip or directory)
+pub fn load_bundle(bundle: &Path) -> Result<Payload, ConfigError> {
+    let files = if bundle.is_dir() {
+        let mut files = Payload::new();
+        read_dir_files(bundle, bundle, &mut files)?;
+        files
+    } else {
+        read_zip_files(bundle).map_err(|e| deploy_error(format!("Could not read firmware bundle: {}", e)))?
+    };
+    select_payload(files)
+}
+
+/// Version recorded in a payload's VERSION file
+fn payload_version(payload: &Payload) -> Option<String> {
+    let version = String::from_utf8_lossy(payload.get("VERSION")?).trim().to_string();
+    (!version.is_empty()).then_some(version)
+}
+
+/// Files under the mirrored directories on the device that the payload
+/// doesn't have. A directory the payload doesn't ship is left alone.
+fn stale_files(device_root: &Path, payload: &Payload) -> io::Result<Vec<String>> {
+    let mut on_device = Payload::new();
+    let shipped = |dir: &&&str| payload.keys().any(|name| name.starts_with(&format!("{}/", dir)));
+    for dir in MIRRORED_DIRS.iter().filter(shipped) {
+        let path = device_root.join(dir);
+        if path.is_dir() {
+            let mut files = Payload::new();
+            read_dir_files(device_root, &path, &mut files)?;
+            on_device.extend(files);
+        }
+    }
+    Ok(on_device.into_keys().filter(|name| !payload.contains_key(name)).collect())
+}
+
+/// Copy `payload` onto `device_root`, calling `on_progress` per file
+pub fn install_payload(
+    payload: &Payload,
+    device_root: &Path,
+    mut on_progress: impl FnMut(DeployProgress),
+) -> Result<FirmwareDeploy, ConfigError> {
+    let mut report = FirmwareDeploy {
+        version: payload_version(payload),
+        ..FirmwareDeploy::default()
+    };
+    for name in stale_files(device_root, payload)? {
+        fs::remove_file(device_root.join(&name))?;
+        report.removed.push(name);
+    }
+
+    let (last, rest): (Vec<_>, Vec<_>) = payload.iter().partition(|(name, _)| name.as_str() == "code.py");
+    let total = payload.len();
+    for (done, (name, data)) in rest.into_iter().chain(last).enumerate() {
+        let target = device_root.join(name);
+        let skipped = fs::read(&target).is_ok_and(|current| current == *data);
+        if !skipped {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_sync(&target, data)?;
+            if fs::read(&target)? != *data {
+                return Err(ConfigError {
+                    message: format!("{} did not verify after copying; the firmware on the device is incomplete", name),
+                    details: Some(vec!["Roll back the firmware or deploy again.".to_string()]),
+                    code: None,
+                });
+            }
+            report.copied.push(name.clone());
+        } else {
+            report.unchanged += 1;
+        }
+        on_progress(DeployProgress {
+            file: name.clone(),
+            done: done + 1,
+            total,
+            skipped,
+        });
+    }
+    Ok(report)
+}
+
+/// Version of the MIDI Captain firmware on a device (its VERSION file)
+#[command]
+pub fn get_installed_firmware_version(device_path: String) -> Result<Option<String>, ConfigError> {
+    let device_root = resolve_volume(validate_device_path(&device_path)?);
+    Ok(read_firmware_version(&device_root))
+}
+
+/// Install the firmware in `bundle_path` (a release zip or a directory) on a
+/// device, stashing the current firmware for rollback first
+#[command]
+pub fn deploy_firmware(app: AppHandle, device_path: String, bundle_path: String) -> Result<FirmwareDeploy, ConfigError> {
+    let device_root = resolve_volume(validate_device_path(&device_path)?);
+    verify_device_connected(&device_root)?;
+    let payload = load_bundle(Path::new(&bundle_path))?;
+
+    let slot = stash_firmware(&device_root, &rollback_slot_dir(&app_data_dir(&app)?, &device_root)).map_err(|e| {
+        deploy_error(format!("Could not stash the current firmware, so nothing was installed: {}", e))
+    })?;
+    let mut report = install_payload(&payload, &device_root, |progress| {
+        let _ = app.emit("firmware-deploy-progress", progress);
+    })?;
+    report.previous_version = slot.version;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(files: &[(&str, &str)]) -> Payload {
+        files.iter().map(|(name, data)| (name.to_string(), data.as_bytes().to_vec())).collect()
+    }
+
+    #[test]
+    fn test_select_payload_finds_code_py_and_filters() {
+        let files = payload(&[
+            ("firmware/code.py", "main"),
+            ("firmware/VERSION", "1.5.0\n"),
+            ("firmware/lib/neopixel.mpy", "lib"),
+            ("firmware/lib/__pycache__/x.pyc", "cache"),
+            ("firmware/config.json", "{}"),
+            ("firmware/INSTALL.md", "docs"),
+            ("deploy.sh", "script"),
+        ]);
+        let selected = select_payload(files).unwrap();
+        let names: Vec<&str> = selected.keys().map(String::as_str).collect();
+        assert_eq!(names, ["VERSION", "code.py", "lib/neopixel.mpy"]);
+        assert_eq!(payload_version(&selected).as_deref(), Some("1.5.0"));
+
+        assert!(select_payload(payload(&[("README.md", "")])).is_err());
+        assert!(!is_payload_path("lib/../../etc/passwd"));
+    }
+
+    #[test]
+    fn test_install_skips_unchanged_mirrors_and_writes_code_py_last() {
+        let device = tempfile::tempdir().unwrap();
+        fs::create_dir_all(device.path().join("core")).unwrap();
+        fs::write(device.path().join("core/old.py"), "stale").unwrap();
+        fs::write(device.path().join("boot.py"), "boot").unwrap();
+        fs::write(device.path().join("config.json"), "{\"buttons\": []}").unwrap();
+
+        let bundle = payload(&[("boot.py", "boot"), ("code.py", "main"), ("core/new.mpy", "new"), ("VERSION", "1.5.0")]);
+        let mut order = Vec::new();
+        let report = install_payload(&bundle, device.path(), |p| order.push(p.file)).unwrap();
+
+        assert_eq!(order.last().map(String::as_str), Some("code.py"));
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(report.copied.len(), 3);
+        assert_eq!(report.removed, ["core/old.py"]);
+        assert_eq!(report.version.as_deref(), Some("1.5.0"));
+        assert!(!device.path().join("core/old.py").exists());
+        assert_eq!(fs::read_to_string(device.path().join("core/new.mpy")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(device.path().join("config.json")).unwrap(), "{\"buttons\": []}");
+    }
+
+    #[test]
+    fn test_load_bundle_from_directory() {
+        let bundle = tempfile::tempdir().unwrap();
+        fs::create_dir_all(bundle.path().join("lib")).unwrap();
+        fs::write(bundle.path().join("code.py"), "main").unwrap();
+        fs::write(bundle.path().join("lib/adafruit_midi.mpy"), "lib").unwrap();
+        fs::write(bundle.path().join(".DS_Store"), "").unwrap();
+        let payload = load_bundle(bundle.path()).unwrap();
+        assert_eq!(payload.keys().collect::<Vec<_>>(), ["code.py", "lib/adafruit_midi.mpy"]);
+    }
+}
@@ -0,0 +1,278 @@
+//! Read-only guest mode
+//!
+//! For handing the laptop to a bandmate: while guest mode is on, every
+//! command that writes to a device, deploys a preset, touches firmware or
+//! changes saved data (presets, settings, recovery points, exported files) is
+//! refused in the backend with a `read_only_mode` error, whatever the UI
+//! shows. Config writes are also refused at the write itself, which covers
+//! deploys that don't come through a command (the global quick-deploy
+//! hotkey, scripts).
+//!
+//! Turning guest mode on sets a passphrase, and turning it off again needs
+//! the same passphrase, so the guest can't simply switch it off. Only a hash
+//! of the passphrase is saved. The setting is saved too, so restarting the
+//! app doesn't leave guest mode; an owner who forgets the passphrase can
+//! delete `guest.json` from the app data directory.
+
+use crate::commands::{app_data_dir, ConfigError};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{command, AppHandle};
+
+/// File under the app-data directory holding the guest mode setting
+const SETTINGS_FILE: &str = "guest.json";
+
+/// `ConfigError::code` of a refused command
+pub const READ_ONLY_MODE: &str = "read_only_mode";
+
+/// `ConfigError::code` when the guest mode passphrase is missing or wrong
+pub const GUEST_PASSPHRASE_INVALID: &str = "guest_passphrase_invalid";
+
+/// Commands refused in guest mode
+const WRITE_COMMANDS: &[&str] = &[
+    "write_config",
+    "write_config_raw",
+    "set_config_locked",
+    "restore_backup",
+    "quick_deploy",
+    "push_preset",
+    "deploy_temporary",
+    "keep_temporary",
+    "stash_firmware_for_rollback",
+    "rollback_firmware",
+    "deploy_firmware",
+    "activate_profile",
+    "save_profile",
+    "delete_profile",
+    "restore_recovery_point",
+    "delete_recovery_point",
+    "run_storage_stress_test",
+    "export_paintaudio",
+    "set_live_parameter",
+    "connect_serial",
+    "send_repl",
+    "send_repl_key",
+    "run_script",
+    "save_preset",
+    "delete_preset",
+    "set_quick_deploy_slot",
+    "set_deploy_hotkey",
+    "clear_deploy_hotkey",
+    "delete_channel_remap",
+    "clear_crash_reports",
+    "run_maintenance",
+    "reboot_device",
+    "push_config_to_all",
+    "preview_label",
+    "start_expression_calibration",
+    "finish_expression_calibration",
+    "save_channel_remap",
+    "save_preset_from_device",
+    "set_preset_metadata",
+    "add_known_volume",
+    "remove_known_volume",
+    "clear_download_cache",
+    "set_device_settings",
+    "set_maintenance_settings",
+    "set_profile_update_settings",
+    "update_profiles",
+    "set_favorite",
+    "create_recovery_point",
+    "set_midi_broadcast",
+    "set_notification_settings",
+    "export_python_fixture",
+];
+
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct GuestSettings {
+    #[serde(default)]
+    read_only: bool,
+    /// SHA-256 of the passphrase set when guest mode was turned on
+    #[serde(default)]
+    passphrase_hash: Option<String>,
+}
+
+fn load_settings(data_dir: &Path) -> GuestSettings {
+    fs::read_to_string(data_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Restore the saved setting. Called once at startup.
+pub fn install(data_dir: &Path) {
+    READ_ONLY.store(load_settings(data_dir).read_only, Ordering::SeqCst);
+}
+
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::SeqCst)
+}
+
+fn refused(read_only: bool, command: &str) -> bool {
+    read_only && WRITE_COMMANDS.contains(&command)
+}
+
+/// True if `command` must be refused right now
+pub fn refuses(command: &str) -> bool {
+    refused(is_read_only(), command)
+}
+
+pub fn read_only_error() -> ConfigError {
+    ConfigError {
+        message: "Guest mode is on, so nothing can be written. Turn off guest mode to make changes.".to_string(),
+        details: None,
+        code: Some(READ_ONLY_MODE),
+    }
+}
+
+/// Fail with `read_only_error` while guest mode is on
+pub fn check_writable() -> Result<(), ConfigError> {
+    if is_read_only() {
+        Err(read_only_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Whether guest (read-only) mode is on
+#[command]
+pub fn get_guest_mode() -> bool {
+    is_read_only()
+}
+
+fn passphrase_error() -> ConfigError {
+    ConfigError {
+        message: "Wrong guest mode passphrase.".to_string(),
+        details: None,
+        code: Some(GUEST_PASSPHRASE_INVALID),
+    }
+}
+
+/// The settings after turning guest mode on or off with `passphrase`.
+/// Turning it on sets the passphrase; while it is on, any change needs it.
+fn change_mode(current: &GuestSettings, enabled: bool, passphrase: &str) -> Result<GuestSettings, ConfigError> {
+    if passphrase.is_empty() {
+        return Err(passphrase_error());
+    }
+    let hash = crate::download::sha256_hex(passphrase.as_bytes());
+    if current.read_only && current.passphrase_hash.as_deref() != Some(hash.as_str()) {
+        return Err(passphrase_error());
+    }
+    Ok(GuestSettings {
+        read_only: enabled,
+        passphrase_hash: enabled.then_some(hash),
+    })
+}
+
+/// Turn guest (read-only) mode on or off. Turning it on sets `passphrase`,
+/// and turning it off needs the same one.
+#[command]
+pub fn set_guest_mode(app: AppHandle, enabled: bool, passphrase: String) -> Result<(), ConfigError> {
+    let data_dir = app_data_dir(&app)?;
+    let settings = change_mode(&load_settings(&data_dir), enabled, &passphrase)?;
+    let json = serde_json::to_string_pretty(&settings)?;
+    fs::write(data_dir.join(SETTINGS_FILE), json)?;
+    READ_ONLY.store(settings.read_only, Ordering::SeqCst);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The global flag is left alone: other tests write configs in parallel
+
+    /// Commands guest mode lets through: they read, render or check guest mode
+    /// themselves (`undo_last_write`, `redo`, `set_guest_mode`). The rest
+    /// that change anything are harmless: `set_hotkey_target` only picks, in
+    /// memory, what the hotkey would deploy, and the deploy itself is refused;
+    /// `apply_template` returns a config for the editor and writes nothing;
+    /// `download_bundle` and `fetch_library_bundle` only fill the download
+    /// cache with copies of published bundles.
+    /// Every registered command is in this list or in `WRITE_COMMANDS`, so a
+    /// new command can't be left out of guest mode by accident.
+    const ALLOWED_COMMANDS: &[&str] = &[
+        "read_config", "read_config_raw", "validate_config", "scan_devices", "start_device_watcher",
+        "stop_device_watcher", "get_rollback_info", "check_firmware_dependencies", "download_bundle",
+        "fetch_library_bundle", "list_download_cache", "watch_device_boot", "list_console_ports",
+        "check_device_health", "apply_channel_remap", "list_channel_remaps", "remap_channels",
+        "diff_files", "get_validation_report", "start_exp_monitor", "stop_exp_monitor",
+        "start_encoder_monitor", "stop_encoder_monitor", "get_crash_reports", "get_library_health",
+        "list_presets", "load_preset", "get_library_index", "get_storage_usage",
+        "get_maintenance_settings", "audit_device_files", "run_self_test", "get_app_capabilities",
+        "explain_control", "get_deploy_hotkey", "check_hotkey_conflict", "set_hotkey_target",
+        "list_target_profiles", "get_device_settings", "get_write_log",
+        "list_recovery_points", "scan_devices_async", "import_paintaudio", "get_api_version",
+        "negotiate_api_version", "list_midi_outputs", "get_midi_broadcast",
+        "list_backups", "scan_midi_ports", "start_midi_monitor",
+        "stop_midi_monitor", "export_setlist_sheet", "send_test_message", "diff_config",
+        "get_guest_mode", "set_guest_mode", "generate_default_config", "get_temporary", "repair_config",
+        "read_config_migrated", "get_notification_settings",
+        "get_device_info", "get_profile_update_settings", "get_installed_firmware_version",
+        "list_profiles", "search_presets", "export_config", "import_config", "export_midi_chart",
+        "disconnect_serial", "undo_last_write", "redo", "get_write_history", "list_templates",
+        "apply_template", "validate_config_detailed", "sanitize_labels", "list_connected_devices",
+        "get_known_volumes",
+    ];
+
+    /// Command names passed to `generate_handler!` in lib.rs
+    fn registered_commands() -> Vec<&'static str> {
+        let lib = include_str!("lib.rs");
+        let start = lib.find("generate_handler![").expect("lib.rs registers commands") + "generate_handler![".len();
+        let end = start + lib[start..].find(']').unwrap();
+        lib[start..end].split(',').map(str::trim).filter(|c| !c.is_empty()).collect()
+    }
+
+    #[test]
+    fn test_every_command_is_classified() {
+        let registered = registered_commands();
+        for command in &registered {
+            let refused = WRITE_COMMANDS.contains(command);
+            let allowed = ALLOWED_COMMANDS.contains(command);
+            assert!(refused != allowed, "{} must be in exactly one of WRITE_COMMANDS and ALLOWED_COMMANDS", command);
+        }
+        for command in WRITE_COMMANDS.iter().chain(ALLOWED_COMMANDS) {
+            assert!(registered.contains(command), "{} is not a registered command", command);
+        }
+    }
+
+    #[test]
+    fn test_guest_mode_refuses_writes_only() {
+        assert!(refused(true, "write_config"));
+        assert!(refused(true, "rollback_firmware"));
+        assert!(refused(true, "send_repl"));
+        assert!(!refused(true, "read_config"));
+        assert!(!refused(false, "write_config"));
+        assert_eq!(read_only_error().code, Some(READ_ONLY_MODE));
+    }
+
+    #[test]
+    fn test_settings_default_to_off() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!load_settings(dir.path()).read_only);
+    }
+
+    #[test]
+    fn test_leaving_guest_mode_needs_passphrase() {
+        let on = change_mode(&GuestSettings::default(), true, "owner secret").unwrap();
+        assert!(on.read_only);
+
+        for passphrase in ["", "guess"] {
+            let err = change_mode(&on, false, passphrase).unwrap_err();
+            assert_eq!(err.code, Some(GUEST_PASSPHRASE_INVALID));
+        }
+        // Nor can the guest set a passphrase of their own
+        assert!(change_mode(&on, true, "guess").is_err());
+
+        assert!(!change_mode(&on, false, "owner secret").unwrap().read_only);
+    }
+
+    #[test]
+    fn test_turning_guest_mode_on_needs_passphrase() {
+        let err = change_mode(&GuestSettings::default(), true, "").unwrap_err();
+        assert_eq!(err.code, Some(GUEST_PASSPHRASE_INVALID));
+    }
+}
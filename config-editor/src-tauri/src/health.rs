@@ -0,0 +1,286 @@
+//! Device health checks
+//!
+//! Inspects a mounted device for conditions that stop the firmware from
+//! running or a write from succeeding, with a recovery suggestion for each.
+//!
+//! The CIRCUITPY drive is only 2MB, so a write can fail for lack of space;
+//! `check_before_write` turns that (and a read-only drive or config.json)
+//! into an actionable error before anything is written.
+
+use crate::commands::{get_volume_path, validate_device_path, ConfigError};
+use crate::device::detect_safe_mode;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+/// `ConfigError::code` when a config doesn't fit on the device
+pub const DEVICE_FULL: &str = "device_full";
+
+/// `ConfigError::code` when the device or its config.json is read-only
+pub const DEVICE_READ_ONLY: &str = "device_read_only";
+
+/// Free space needed beyond the config itself: the write is staged in a temp
+/// file next to config.json, and FAT allocates whole clusters
+const WRITE_MARGIN_BYTES: u64 = 8 * 1024;
+
+/// Result of `check_device_health`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeviceHealth {
+    pub volume_path: PathBuf,
+    pub safe_mode: bool,
+    pub safe_mode_reason: Option<String>,
+    /// Free space on the volume; `None` when the OS doesn't report it
+    pub free_bytes: Option<u64>,
+    /// The volume is mounted read-only
+    pub read_only: bool,
+    /// config.json can be written (or created, if missing)
+    pub config_writable: bool,
+    /// Human-readable recovery steps, most relevant first
+    pub suggestions: Vec<String>,
+}
+
+/// Free space and mount state of a volume
+struct VolumeStatus {
+    free_bytes: Option<u64>,
+    read_only: bool,
+}
+
+#[cfg(unix)]
+fn volume_status(volume: &Path) -> VolumeStatus {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let unknown = VolumeStatus {
+        free_bytes: None,
+        read_only: false,
+    };
+    let Ok(path) = CString::new(volume.as_os_str().as_bytes()) else {
+        return unknown;
+    };
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return unknown;
+    }
+    VolumeStatus {
+        free_bytes: Some(stat.f_bavail as u64 * stat.f_frsize as u64),
+        read_only: stat.f_flag & libc::ST_RDONLY != 0,
+    }
+}
+
+#[cfg(windows)]
+fn volume_status(volume: &Path) -> VolumeStatus {
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+    use winapi::um::fileapi::{GetDiskFreeSpaceExW, GetVolumeInformationW};
+    use winapi::um::winnt::{FILE_READ_ONLY_VOLUME, ULARGE_INTEGER};
+
+    let root: Vec<u16> = volume.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut free: ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+    let has_free = unsafe { GetDiskFreeSpaceExW(root.as_ptr(), &mut free, null_mut(), null_mut()) } != 0;
+    let mut flags = 0;
+    let has_flags = unsafe {
+        GetVolumeInformationW(root.as_ptr(), null_mut(), 0, null_mut(), null_mut(), &mut flags, null_mut(), 0)
+    } != 0;
+    VolumeStatus {
+        free_bytes: has_free.then(|| unsafe { *free.QuadPart() }),
+        read_only: has_flags && flags & FILE_READ_ONLY_VOLUME != 0,
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn volume_status(_volume: &Path) -> VolumeStatus {
+    VolumeStatus {
+        free_bytes: None,
+        read_only: false,
+    }
+}
+
+/// True unless the volume is read-only or config.json is marked read-only
+fn config_writable(volume: &Path, read_only: bool) -> bool {
+    let locked = fs::metadata(volume.join("config.json")).is_ok_and(|m| m.permissions().readonly());
+    !read_only && !locked
+}
+
+/// Why a config of `size` bytes can't be written to the device in `health`,
+/// if it can't
+pub fn write_blocker(health: &DeviceHealth, size: u64) -> Option<ConfigError> {
+    if health.read_only {
+        return Some(ConfigError {
+            message: "The device is mounted read-only, so the config can't be saved".to_string(),
+            details: Some(vec![
+                "Eject the device, power-cycle it and reconnect it.".to_string(),
+                "If it stays read-only, the drive may need repair: back up its files and run a disk check on it.".to_string(),
+            ]),
+            code: Some(DEVICE_READ_ONLY),
+        });
+    }
+    if !health.config_writable {
+        return Some(ConfigError {
+            message: "config.json on the device is read-only, so the config can't be saved".to_string(),
+            details: Some(vec![
+                "Clear the read-only flag on config.json (file properties in Finder or Explorer, or chmod u+w).".to_string(),
+            ]),
+            code: Some(DEVICE_READ_ONLY),
+        });
+    }
+    let needed = size + WRITE_MARGIN_BYTES;
+    match health.free_bytes {
+        Some(free) if free < needed => Some(ConfigError {
+            message: format!(
+                "Not enough space on the device: the config needs {} KB, {} KB is free",
+                needed.div_ceil(1024),
+                free / 1024
+            ),
+            details: Some(vec![
+                "Delete files you don't need from the device (old backups, unused fonts or libraries).".to_string(),
+                "Empty the trash: macOS keeps deleted files in a hidden .Trashes folder on the drive until it is emptied."
+                    .to_string(),
+            ]),
+            code: Some(DEVICE_FULL),
+        }),
+        _ => None,
+    }
+}
+
+/// Recovery steps tailored to a CircuitPython safe-mode reason
+pub fn safe_mode_suggestions(reason: &str) -> Vec<String> {
+    let lower = reason.to_ascii_lowercase();
+    let mut suggestions = Vec::new();
+    if lower.contains("reset button") || lower.contains("requested") {
+        suggestions.push("Safe mode was entered on purpose (reset pressed during boot). Power-cycle the device to leave it.".to_string());
+    } else if lower.contains("brownout") || lower.contains("power dipped") || lower.contains("power") {
+        suggestions.push("The supply voltage dipped during boot. Try a different USB cable or port, or a powered hub.".to_string());
+    } else if lower.contains("heap") || lower.contains("stack") || lower.contains("memory") {
+        suggestions.push("The firmware exhausted memory. Roll back to the previous firmware or reduce the config size.".to_string());
+    } else if lower.contains("crashed hard") || lower.contains("hardfault") || lower.contains("hard fault") {
+        suggestions.push("CircuitPython itself crashed. Roll back the firmware; if that fails, reinstall CircuitPython (.uf2) and then the firmware.".to_string());
+    } else {
+        suggestions.push("Power-cycle the device. If it returns to safe mode, roll back to the previous firmware.".to_string());
+    }
+    suggestions.push("code.py does not run in safe mode, so config changes will not take effect until it boots normally.".to_string());
+    suggestions
+}
+
+/// Check the health of the device mounted at `volume`
+pub fn device_health(volume: &Path) -> DeviceHealth {
+    let safe_mode_reason = detect_safe_mode(volume);
+    let status = volume_status(volume);
+    let mut health = DeviceHealth {
+        volume_path: volume.to_path_buf(),
+        safe_mode: safe_mode_reason.is_some(),
+        suggestions: safe_mode_reason
+            .as_deref()
+            .map(safe_mode_suggestions)
+            .unwrap_or_default(),
+        safe_mode_reason,
+        free_bytes: status.free_bytes,
+        read_only: status.read_only,
+        config_writable: config_writable(volume, status.read_only),
+    };
+    if let Some(blocker) = write_blocker(&health, 0) {
+        health.suggestions.push(blocker.message);
+        health.suggestions.extend(blocker.details.unwrap_or_default());
+    }
+    health
+}
+
+/// Fail early, with recovery steps, if `size` bytes of config can't be
+/// written to the device holding `path`
+pub fn check_before_write(path: &Path, size: u64) -> Result<(), ConfigError> {
+    let health = device_health(&resolve_volume(path.to_path_buf()));
+    write_blocker(&health, size).map_or(Ok(()), Err)
+}
+
+/// Volume root for a resolved path on the device (the root itself or a file on it)
+pub(crate) fn resolve_volume(canonical: PathBuf) -> PathBuf {
+    if let Some(volume) = get_volume_path(&canonical) {
+        return volume;
+    }
+    if canonical.is_file() {
+        if let Some(parent) = canonical.parent() {
+            return parent.to_path_buf();
+        }
+    }
+    canonical
+}
+
+/// Report device health (safe mode, free space, read-only state) with
+/// recovery suggestions
+#[command]
+pub fn check_device_health(path: String) -> Result<DeviceHealth, ConfigError> {
+    let volume = resolve_volume(validate_device_path(&path)?);
+    Ok(device_health(&volume))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggestions_for_reset_button() {
+        let s = safe_mode_suggestions("You pressed the reset button during boot.");
+        assert!(s[0].contains("Power-cycle"));
+    }
+
+    #[test]
+    fn test_suggestions_for_hard_crash_mention_rollback() {
+        let s = safe_mode_suggestions("CircuitPython core code crashed hard. Whoops!");
+        assert!(s[0].contains("Roll back"));
+    }
+
+    #[test]
+    fn test_healthy_device_has_no_suggestions() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("boot_out.txt"), "Adafruit CircuitPython 7.3.3 on 2022-08-29\n").unwrap();
+        let health = device_health(dir.path());
+        assert!(!health.safe_mode);
+        assert!(!health.read_only);
+        assert!(health.config_writable);
+        assert!(health.suggestions.is_empty());
+    }
+
+    fn health(free_bytes: Option<u64>, read_only: bool, config_writable: bool) -> DeviceHealth {
+        DeviceHealth {
+            volume_path: PathBuf::from("/Volumes/CIRCUITPY"),
+            safe_mode: false,
+            safe_mode_reason: None,
+            free_bytes,
+            read_only,
+            config_writable,
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_write_blocked_on_full_device() {
+        let err = write_blocker(&health(Some(10 * 1024), false, true), 4 * 1024).unwrap();
+        assert_eq!(err.code, Some(DEVICE_FULL));
+        assert!(err.message.contains("12 KB"));
+        assert!(write_blocker(&health(Some(64 * 1024), false, true), 4 * 1024).is_none());
+        // Unknown free space doesn't block
+        assert!(write_blocker(&health(None, false, true), 4 * 1024).is_none());
+    }
+
+    #[test]
+    fn test_write_blocked_on_read_only_device_or_config() {
+        assert_eq!(write_blocker(&health(Some(1 << 20), true, false), 0).unwrap().code, Some(DEVICE_READ_ONLY));
+        let err = write_blocker(&health(Some(1 << 20), false, false), 0).unwrap();
+        assert!(err.message.contains("config.json"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_only_config_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config.json");
+        std::fs::write(&config, "{}").unwrap();
+        let mut permissions = std::fs::metadata(&config).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&config, permissions).unwrap();
+
+        let health = device_health(dir.path());
+        assert!(!health.config_writable);
+        assert!(health.suggestions[0].contains("read-only"));
+        assert!(health.free_bytes.is_some());
+    }
+}
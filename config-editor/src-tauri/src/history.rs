@@ -0,0 +1,242 @@
+//! Undo and redo of config writes
+//!
+//! Every write backs up the config it replaces (`back_up_config` in
+//! commands.rs), so a device's backups of a config file are its undo stack,
+//! newest first. Undoing writes the newest one back and moves the config it
+//! replaced into the device's redo store (`backups/<device>/redo/`, same file
+//! naming); redoing does the reverse. Any other write clears the redo store.
+
+use crate::commands::{
+    app_data_dir, back_up_config, backups_root, device_backups, guard_locked, read_config_file, validate_device_path,
+    verify_device_connected, write_verified_to, ConfigError, ConfigFormat,
+};
+use crate::confirm::Confirmable;
+use crate::diff::{diff_values, ConfigChange};
+use crate::filesystem::{FileSystem, RealFs};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+
+/// Subdirectory of a device's backups holding undone configs
+const REDO_DIR: &str = "redo";
+
+/// One revision of a config that undo or redo can bring back
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HistoryEntry {
+    /// When it was replaced (unix seconds)
+    pub created_at: u64,
+    /// What restoring it would change in the config on the device now
+    pub changes: Vec<ConfigChange>,
+}
+
+/// Revisions of one config file, nearest first
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WriteHistory {
+    /// What each successive `undo_last_write` brings back
+    pub undo: Vec<HistoryEntry>,
+    /// What each successive `redo` brings back
+    pub redo: Vec<HistoryEntry>,
+}
+
+/// Which stack a step takes from
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    Undo,
+    Redo,
+}
+
+fn device_of(path: &Path) -> String {
+    crate::registry::device_key(path).unwrap_or_else(|| "unknown".to_string())
+}
+
+fn redo_key(device: &str) -> String {
+    format!("{}/{}", device, REDO_DIR)
+}
+
+/// Stored revisions of the config at `path` under `key`, newest first
+fn revisions(fs: &dyn FileSystem, root: &Path, key: &str, path: &Path) -> Vec<(u64, PathBuf)> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("config.json");
+    device_backups(fs, root, key)
+        .into_iter()
+        .filter(|(_, _, backup)| backup.file_name == file_name)
+        .map(|(_, stored, backup)| (backup.created_at, stored))
+        .collect()
+}
+
+fn read_revision(fs: &dyn FileSystem, stored: &Path) -> Result<Value, ConfigError> {
+    let bytes = crate::archive::decompress(&fs.read(stored)?)?;
+    ConfigFormat::detect(&bytes).decode(&bytes)
+}
+
+/// Forget what was undone for the config at `path`, after a new write
+pub(crate) fn clear_redo(fs: &dyn FileSystem, root: &Path, device: &str, path: &Path) -> Result<(), ConfigError> {
+    for (_, stored) in revisions(fs, root, &redo_key(device), path) {
+        fs.remove_file(&stored)?;
+    }
+    Ok(())
+}
+
+/// Undo and redo stacks of the config at `path`, each entry diffed against
+/// the config there now
+pub fn history_in(fs: &dyn FileSystem, root: &Path, path: &Path) -> Result<WriteHistory, ConfigError> {
+    let device = device_of(path);
+    let current: Value = if fs.is_file(path) {
+        read_config_file(fs, path)?
+    } else {
+        Value::Object(Default::default())
+    };
+    let entries = |key: &str| -> Result<Vec<HistoryEntry>, ConfigError> {
+        revisions(fs, root, key, path)
+            .into_iter()
+            .map(|(created_at, stored)| {
+                Ok(HistoryEntry {
+                    created_at,
+                    changes: diff_values(&current, &read_revision(fs, &stored)?),
+                })
+            })
+            .collect()
+    };
+    Ok(WriteHistory {
+        undo: entries(&device)?,
+        redo: entries(&redo_key(&device))?,
+    })
+}
+
+/// Put back the nearest revision from one stack, moving the config it
+/// replaces onto the other. Returns the token of the written config.
+fn step_in(
+    fs: &dyn FileSystem,
+    root: &Path,
+    path: &Path,
+    step: Step,
+    force: Option<bool>,
+    confirmation: Option<&str>,
+) -> Result<Confirmable<String>, ConfigError> {
+    let device = device_of(path);
+    let (from, to) = match step {
+        Step::Undo => (device.clone(), redo_key(&device)),
+        Step::Redo => (redo_key(&device), device.clone()),
+    };
+    let Some((_, stored)) = revisions(fs, root, &from, path).into_iter().next() else {
+        return Err(ConfigError {
+            message: match step {
+                Step::Undo => "Nothing to undo: no earlier version of this config is stored".to_string(),
+                Step::Redo => "Nothing to redo".to_string(),
+            },
+            details: None,
+            code: None,
+        });
+    };
+    let value = read_revision(fs, &stored)?;
+    crate::guest::check_writable()?;
+    guard_locked(fs, path, force, confirmation, || {
+        back_up_config(fs, root, &to, path)?;
+        let token = write_verified_to(fs, None, path, &value)?;
+        fs.remove_file(&stored)?;
+        Ok(token)
+    })
+}
+
+/// Put back the config as it was before the last write to `path`. The
+/// config replaced can be brought back with `redo`. Replacing a locked
+/// config takes `force` and a `confirmation` token. Returns the token of
+/// the restored config.
+#[command]
+pub fn undo_last_write(
+    app: AppHandle,
+    path: String,
+    force: Option<bool>,
+    confirmation: Option<String>,
+) -> Result<Confirmable<String>, ConfigError> {
+    let path = validate_device_path(&path)?;
+    verify_device_connected(&path)?;
+    let root = backups_root(&app_data_dir(&app)?);
+    step_in(&RealFs, &root, &path, Step::Undo, force, confirmation.as_deref())
+}
+
+/// Reapply the write last undone at `path`. Takes and returns what
+/// `undo_last_write` does.
+#[command]
+pub fn redo(
+    app: AppHandle,
+    path: String,
+    force: Option<bool>,
+    confirmation: Option<String>,
+) -> Result<Confirmable<String>, ConfigError> {
+    let path = validate_device_path(&path)?;
+    verify_device_connected(&path)?;
+    let root = backups_root(&app_data_dir(&app)?);
+    step_in(&RealFs, &root, &path, Step::Redo, force, confirmation.as_deref())
+}
+
+/// Revisions undo and redo can bring back for the config at `path`, with
+/// timestamps and what each would change
+#[command]
+pub fn get_write_history(app: AppHandle, path: String) -> Result<WriteHistory, ConfigError> {
+    let path = validate_device_path(&path)?;
+    history_in(&RealFs, &backups_root(&app_data_dir(&app)?), &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::write_config_value;
+    use crate::filesystem::InMemoryFs;
+
+    fn config(channel: u8) -> Value {
+        let buttons: Vec<String> = (0..6)
+            .map(|i| format!(r#"{{"label": "{}", "cc": {}, "color": "red"}}"#, i + 1, 20 + i))
+            .collect();
+        serde_json::from_str(&format!(
+            r#"{{"device": "mini6", "global_channel": {}, "buttons": [{}]}}"#,
+            channel,
+            buttons.join(", ")
+        ))
+        .unwrap()
+    }
+
+    fn channel_on_device(fs: &InMemoryFs, path: &Path) -> u64 {
+        let value: Value = serde_json::from_slice(&fs.read(path).unwrap()).unwrap();
+        value["global_channel"].as_u64().unwrap()
+    }
+
+    #[test]
+    fn test_undo_and_redo_walk_the_history() {
+        let path = Path::new("/Volumes/CIRCUITPY/config.json");
+        let root = Path::new("/backups");
+        let fs = InMemoryFs::new().with_file(path, config(1).to_string());
+        write_config_value(&fs, Some(root), path, config(2), None, None, None).unwrap();
+        write_config_value(&fs, Some(root), path, config(3), None, None, None).unwrap();
+
+        let history = history_in(&fs, root, path).unwrap();
+        assert_eq!((history.undo.len(), history.redo.len()), (2, 0));
+        assert_eq!(history.undo[0].changes[0].path, "global_channel");
+        assert_eq!(history.undo[0].changes[0].after, Some(serde_json::json!(2)));
+
+        step_in(&fs, root, path, Step::Undo, None, None).unwrap();
+        assert_eq!(channel_on_device(&fs, path), 2);
+        step_in(&fs, root, path, Step::Undo, None, None).unwrap();
+        assert_eq!(channel_on_device(&fs, path), 1);
+        assert!(step_in(&fs, root, path, Step::Undo, None, None).unwrap_err().message.starts_with("Nothing to undo"));
+
+        step_in(&fs, root, path, Step::Redo, None, None).unwrap();
+        assert_eq!(channel_on_device(&fs, path), 2);
+        let history = history_in(&fs, root, path).unwrap();
+        assert_eq!((history.undo.len(), history.redo.len()), (1, 1));
+    }
+
+    #[test]
+    fn test_new_write_clears_redo() {
+        let path = Path::new("/Volumes/CIRCUITPY/config.json");
+        let root = Path::new("/backups");
+        let fs = InMemoryFs::new().with_file(path, config(1).to_string());
+        write_config_value(&fs, Some(root), path, config(2), None, None, None).unwrap();
+        step_in(&fs, root, path, Step::Undo, None, None).unwrap();
+        assert_eq!(history_in(&fs, root, path).unwrap().redo.len(), 1);
+
+        write_config_value(&fs, Some(root), path, config(4), None, None, None).unwrap();
+        let history = history_in(&fs, root, path).unwrap();
+        assert!(history.redo.is_empty());
+        assert!(step_in(&fs, root, path, Step::Redo, None, None).unwrap_err().message.starts_with("Nothing to redo"));
+    }
+}
@@ -0,0 +1,320 @@
+//! Global deploy hotkey
+//!
+//! An optional system-wide keyboard shortcut that writes the preset selected
+//! in the UI to the connected device, so presets can be switched during
+//! rehearsal without bringing the editor to the front. The shortcut is
+//! registered from the Rust side and remembered across restarts; the UI only
+//! tells us what to deploy (`set_hotkey_target`).
+//!
+//! Each press emits "hotkey-deploy" with the outcome.
+
+use crate::commands::{app_data_dir, ConfigError};
+use crate::presets::deploy_preset;
+use crate::confirm::Confirmable;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Emitter};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// File under the app-data directory holding the saved shortcut
+const SETTINGS_FILE: &str = "hotkey.json";
+
+/// Shortcuts the OS or every app already uses, in normalized form, with what
+/// they do. Registering one globally would break it everywhere.
+#[cfg(target_os = "macos")]
+const RESERVED_SHORTCUTS: &[(&str, &str)] = &[
+    ("super+q", "Quit"),
+    ("super+w", "Close window"),
+    ("super+h", "Hide"),
+    ("super+m", "Minimize"),
+    ("super+tab", "App switcher"),
+    ("super+space", "Spotlight"),
+    ("ctrl+super+q", "Lock screen"),
+    ("shift+super+3", "Screenshot"),
+    ("shift+super+4", "Screenshot"),
+    ("shift+super+5", "Screenshot"),
+    ("super+a", "Select all"),
+    ("super+c", "Copy"),
+    ("super+s", "Save"),
+    ("super+v", "Paste"),
+    ("super+x", "Cut"),
+    ("super+z", "Undo"),
+];
+
+#[cfg(not(target_os = "macos"))]
+const RESERVED_SHORTCUTS: &[(&str, &str)] = &[
+    ("alt+f4", "Close window"),
+    ("alt+tab", "Window switcher"),
+    ("ctrl+alt+delete", "Security screen"),
+    ("ctrl+shift+escape", "Task manager"),
+    ("super+d", "Show desktop"),
+    ("super+e", "File manager"),
+    ("super+l", "Lock screen"),
+    ("super+r", "Run"),
+    ("ctrl+a", "Select all"),
+    ("ctrl+c", "Copy"),
+    ("ctrl+s", "Save"),
+    ("ctrl+v", "Paste"),
+    ("ctrl+x", "Cut"),
+    ("ctrl+z", "Undo"),
+];
+
+/// Saved hotkey
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HotkeySettings {
+    /// Shortcut in accelerator form, e.g. "CmdOrCtrl+Shift+D"; none when disabled
+    pub shortcut: Option<String>,
+    /// Why the saved shortcut couldn't be registered at startup (another
+    /// application holds it). Only reported by `get_deploy_hotkey`, never saved.
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Why `register_saved` failed, until the hotkey is set or cleared
+static STARTUP_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+fn set_startup_error(error: Option<String>) {
+    *STARTUP_ERROR.lock().unwrap_or_else(|e| e.into_inner()) = error;
+}
+
+/// What a hotkey press deploys
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HotkeyTarget {
+    /// Preset name in the library
+    pub preset: String,
+    /// Device config path, as for `write_config`
+    pub device: String,
+}
+
+/// Outcome of a hotkey press, emitted as "hotkey-deploy"
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HotkeyDeploy {
+    pub preset: Option<String>,
+    pub device: Option<String>,
+    /// Set when nothing was written
+    pub error: Option<String>,
+}
+
+static TARGET: Mutex<Option<HotkeyTarget>> = Mutex::new(None);
+
+/// Normalize an accelerator for comparison: lowercase, modifier aliases
+/// folded (`CmdOrCtrl` resolves per platform), modifiers in a fixed order
+pub fn normalize_shortcut(shortcut: &str) -> String {
+    const ORDER: &[&str] = &["ctrl", "alt", "shift", "super"];
+    let mut modifiers = Vec::new();
+    let mut keys = Vec::new();
+    for part in shortcut.split('+').map(|p| p.trim().to_ascii_lowercase()) {
+        let part = match part.as_str() {
+            "cmdorctrl" | "commandorcontrol" if cfg!(target_os = "macos") => "super",
+            "cmdorctrl" | "commandorcontrol" | "control" => "ctrl",
+            "cmd" | "command" | "meta" | "win" => "super",
+            "option" => "alt",
+            "del" => "delete",
+            "esc" => "escape",
+            other => other,
+        }
+        .to_string();
+        if ORDER.contains(&part.as_str()) {
+            modifiers.push(part);
+        } else {
+            keys.push(part);
+        }
+    }
+    modifiers.sort_by_key(|m| ORDER.iter().position(|o| o == m));
+    modifiers.dedup();
+    modifiers.extend(keys);
+    modifiers.join("+")
+}
+
+/// What the OS uses the shortcut for, if it is reserved
+pub fn reserved_conflict(shortcut: &str) -> Option<&'static str> {
+    let normalized = normalize_shortcut(shortcut);
+    RESERVED_SHORTCUTS
+        .iter()
+        .find(|(reserved, _)| *reserved == normalized)
+        .map(|(_, what)| *what)
+}
+
+fn conflict_error(shortcut: &str, reason: String) -> ConfigError {
+    ConfigError {
+        message: format!("Shortcut '{}' is not available", shortcut),
+        details: Some(vec![reason]),
+        code: None,
+    }
+}
+
+fn parse(shortcut: &str) -> Result<Shortcut, ConfigError> {
+    shortcut.parse().map_err(|e| ConfigError {
+        message: format!("Invalid shortcut '{}': {}", shortcut, e),
+        details: None,
+        code: None,
+    })
+}
+
+fn load_settings(data_dir: &Path) -> HotkeySettings {
+    fs::read_to_string(data_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings(data_dir: &Path, settings: &HotkeySettings) -> Result<(), ConfigError> {
+    fs::write(data_dir.join(SETTINGS_FILE), serde_json::to_string_pretty(settings)?)?;
+    Ok(())
+}
+
+/// Deploy the current target. Runs off the event loop: the write goes over
+/// USB mass storage and can take a while.
+fn deploy_target(app: &AppHandle) {
+    let app = app.clone();
+    let target = TARGET.lock().unwrap().clone();
+    std::thread::spawn(move || {
+        let result = match &target {
+            // Never forced: a locked config stays protected
+            Some(t) => deploy_preset(&app, &t.preset, t.device.clone(), None, None)
+                .and_then(Confirmable::into_result)
+                .err()
+                .map(|e| e.message),
+            None => Some("No preset selected for the deploy hotkey".to_string()),
+        };
+        let _ = app.emit(
+            "hotkey-deploy",
+            HotkeyDeploy {
+                preset: target.as_ref().map(|t| t.preset.clone()),
+                device: target.map(|t| t.device),
+                error: result,
+            },
+        );
+    });
+}
+
+/// Register the shortcut with the OS. Fails if it is reserved or another
+/// application already holds it.
+fn register(app: &AppHandle, shortcut: &str) -> Result<(), ConfigError> {
+    if let Some(what) = reserved_conflict(shortcut) {
+        return Err(conflict_error(shortcut, format!("Reserved by the system ({})", what)));
+    }
+    app.global_shortcut()
+        .on_shortcut(parse(shortcut)?, |app, _, event| {
+            if event.state() == ShortcutState::Pressed {
+                deploy_target(app);
+            }
+        })
+        .map_err(|e| conflict_error(shortcut, format!("Already in use by another application: {}", e)))
+}
+
+fn unregister(app: &AppHandle, shortcut: &str) {
+    if let Ok(parsed) = parse(shortcut) {
+        let _ = app.global_shortcut().unregister(parsed);
+    }
+}
+
+/// Register the saved shortcut, if any (at startup)
+pub fn register_saved(app: &AppHandle) {
+    let Ok(dir) = app_data_dir(app) else { return };
+    if let Some(shortcut) = load_settings(&dir).shortcut {
+        if let Err(e) = register(app, &shortcut) {
+            let error = match e.details {
+                Some(details) => format!("{}: {}", e.message, details.join("; ")),
+                None => e.message,
+            };
+            set_startup_error(Some(error));
+        }
+    }
+}
+
+/// Set (or replace) the deploy hotkey and remember it across restarts
+#[command]
+pub fn set_deploy_hotkey(app: AppHandle, shortcut: String) -> Result<(), ConfigError> {
+    let dir = app_data_dir(&app)?;
+    let previous = load_settings(&dir).shortcut;
+    if let Some(ref previous) = previous {
+        unregister(&app, previous);
+    }
+    if let Err(e) = register(&app, &shortcut) {
+        // Keep the old hotkey working if the new one can't be had
+        if let Some(ref previous) = previous {
+            let _ = register(&app, previous);
+        }
+        return Err(e);
+    }
+    set_startup_error(None);
+    save_settings(
+        &dir,
+        &HotkeySettings {
+            shortcut: Some(shortcut),
+            ..Default::default()
+        },
+    )
+}
+
+/// Remove the deploy hotkey
+#[command]
+pub fn clear_deploy_hotkey(app: AppHandle) -> Result<(), ConfigError> {
+    let dir = app_data_dir(&app)?;
+    if let Some(shortcut) = load_settings(&dir).shortcut {
+        unregister(&app, &shortcut);
+    }
+    set_startup_error(None);
+    save_settings(&dir, &HotkeySettings::default())
+}
+
+/// The saved deploy hotkey, with why it isn't registered if that failed at
+/// startup
+#[command]
+pub fn get_deploy_hotkey(app: AppHandle) -> Result<HotkeySettings, ConfigError> {
+    Ok(HotkeySettings {
+        error: STARTUP_ERROR.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+        ..load_settings(&app_data_dir(&app)?)
+    })
+}
+
+/// Check a shortcut against the system's reserved shortcuts before offering
+/// it. Shortcuts held by other applications are only found on registration.
+#[command]
+pub fn check_hotkey_conflict(shortcut: String) -> Option<String> {
+    reserved_conflict(&shortcut).map(|what| format!("Reserved by the system ({})", what))
+}
+
+/// Set what the hotkey deploys (the preset selected in the UI and the
+/// connected device), or clear it
+#[command]
+pub fn set_hotkey_target(target: Option<HotkeyTarget>) {
+    *TARGET.lock().unwrap() = target;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_orders_modifiers_and_folds_aliases() {
+        assert_eq!(normalize_shortcut("Shift+Control+D"), "ctrl+shift+d");
+        assert_eq!(normalize_shortcut("Command + Option + F5"), "alt+super+f5");
+        assert_eq!(normalize_shortcut("Ctrl+Alt+Del"), "ctrl+alt+delete");
+        let cmd_or_ctrl = if cfg!(target_os = "macos") { "shift+super+p" } else { "ctrl+shift+p" };
+        assert_eq!(normalize_shortcut("CmdOrCtrl+Shift+P"), cmd_or_ctrl);
+    }
+
+    #[test]
+    fn test_reserved_shortcuts_conflict() {
+        assert_eq!(reserved_conflict("CmdOrCtrl+C"), Some("Copy"));
+        assert_eq!(reserved_conflict("CmdOrCtrl+Shift+D"), None);
+        assert!(check_hotkey_conflict("CmdOrCtrl+V".to_string()).is_some());
+    }
+
+    #[test]
+    fn test_settings_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load_settings(dir.path()), HotkeySettings::default());
+        let mut settings = HotkeySettings {
+            shortcut: Some("CmdOrCtrl+Shift+D".to_string()),
+            error: Some("Already in use".to_string()),
+        };
+        save_settings(dir.path(), &settings).unwrap();
+        // The startup error is reported, never saved
+        settings.error = None;
+        assert_eq!(load_settings(dir.path()), settings);
+    }
+}
@@ -0,0 +1,130 @@
+//! Config import and export as text: JSON, YAML or TOML
+//!
+//! For configs kept outside the device, e.g. in a dotfiles repo. Exports are
+//! plain renderings of `MidiCaptainConfig`; imports are upgraded to the
+//! current schema like any config read from disk, but not validated — that
+//! happens when the config is written to a device.
+
+use crate::commands::{read_config, ConfigError};
+use crate::config::MidiCaptainConfig;
+use tauri::command;
+
+/// Text format for `export_config` / `import_config`
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+fn format_error(format: TextFormat, action: &str, e: impl std::fmt::Display) -> ConfigError {
+    ConfigError {
+        message: format!("{:?} {} error: {}", format, action, e),
+        details: None,
+        code: None,
+    }
+}
+
+/// Render a config in `format`
+pub fn export_text(config: &MidiCaptainConfig, format: TextFormat) -> Result<String, ConfigError> {
+    match format {
+        TextFormat::Json => Ok(serde_json::to_string_pretty(config)?),
+        TextFormat::Yaml => serde_yaml::to_string(config).map_err(|e| format_error(format, "export", e)),
+        TextFormat::Toml => toml::to_string_pretty(config).map_err(|e| format_error(format, "export", e)),
+    }
+}
+
+impl TextFormat {
+    /// Format implied by a file name's extension; JSON for anything unknown
+    pub fn from_path(path: &str) -> Self {
+        let ext = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("yaml" | "yml") => TextFormat::Yaml,
+            Some("toml") => TextFormat::Toml,
+            _ => TextFormat::Json,
+        }
+    }
+}
+
+/// Parse a config written in `format` into JSON, upgraded to the current
+/// schema but not yet checked against it
+pub fn import_value(content: &str, format: TextFormat) -> Result<serde_json::Value, ConfigError> {
+    let mut value: serde_json::Value = match format {
+        TextFormat::Json => serde_json::from_str(content)?,
+        TextFormat::Yaml => serde_yaml::from_str(content).map_err(|e| format_error(format, "parse", e))?,
+        TextFormat::Toml => toml::from_str(content).map_err(|e| format_error(format, "parse", e))?,
+    };
+    crate::migrations::migrate(&mut value)?;
+    Ok(value)
+}
+
+/// Parse a config written in `format`, upgrading it to the current schema
+pub fn import_text(content: &str, format: TextFormat) -> Result<MidiCaptainConfig, ConfigError> {
+    Ok(serde_json::from_value(import_value(content, format)?)?)
+}
+
+/// The config at `path` rendered as JSON, YAML or TOML
+#[command]
+pub fn export_config(path: String, format: TextFormat) -> Result<String, ConfigError> {
+    export_text(&read_config(path)?.config, format)
+}
+
+/// Parse a JSON, YAML or TOML config. Nothing is written.
+#[command]
+pub fn import_config(content: String, format: TextFormat) -> Result<MidiCaptainConfig, ConfigError> {
+    import_text(&content, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{"device": "mini6", "global_channel": 2, "buttons": [
+        {"label": "OD", "cc": 20, "color": "red"}, {"label": "DLY", "cc": 21, "color": "blue", "mode": "momentary"},
+        {"label": "3", "cc": 22, "color": "red"}, {"label": "4", "cc": 23, "color": "red"},
+        {"label": "5", "cc": 24, "color": "red"}, {"label": "6", "cc": 25, "color": "red"}
+    ]}"#;
+
+    #[test]
+    fn test_round_trip_through_every_format() {
+        let config = import_text(CONFIG, TextFormat::Json).unwrap();
+        for format in [TextFormat::Json, TextFormat::Yaml, TextFormat::Toml] {
+            let text = export_text(&config, format).unwrap();
+            let back = import_text(&text, format).unwrap();
+            assert_eq!(
+                serde_json::to_value(&back).unwrap(),
+                serde_json::to_value(&config).unwrap(),
+                "{:?}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn test_import_hand_written_toml() {
+        let toml = r#"
+            device = "mini6"
+
+            [[buttons]]
+            label = "OD"
+            cc = 20
+            color = "red"
+        "#;
+        let config = import_text(toml, TextFormat::Toml).unwrap();
+        assert_eq!(config.buttons[0].label, "OD");
+        assert!(import_text("buttons: [", TextFormat::Yaml).is_err());
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(TextFormat::from_path("rig/config.YAML"), TextFormat::Yaml);
+        assert_eq!(TextFormat::from_path("rig.yml"), TextFormat::Yaml);
+        assert_eq!(TextFormat::from_path("rig.toml"), TextFormat::Toml);
+        assert_eq!(TextFormat::from_path("config.json"), TextFormat::Json);
+        assert_eq!(TextFormat::from_path("config"), TextFormat::Json);
+    }
+}
@@ -1,26 +1,269 @@
+mod archive;
+mod audit;
+mod capabilities;
+pub mod cli;
 mod commands;
 mod config;
+mod config_cache;
+mod config_watch;
+mod confirm;
+mod crash;
+mod dependencies;
 mod device;
+mod device_info;
+mod device_profiles;
+mod diff;
+mod download;
+mod explain;
+mod filesystem;
+mod firmware;
+mod firmware_deploy;
+mod guest;
+mod health;
+mod history;
+mod hotkey;
+mod interchange;
+mod lint;
+mod maintenance;
+mod midi;
+mod midi_chart;
+mod migrations;
+mod monitor;
+mod notifications;
+mod paintaudio;
+mod presets;
+mod profile_updates;
+mod pyfixture;
+mod recovery;
+mod registry;
+mod remap;
+mod repair;
+mod sanitize;
+mod scripting;
+mod selftest;
+mod serial;
+mod setlist;
+mod storage;
+mod stress;
+mod targets;
+mod templates;
+mod temporary;
 
-use commands::{read_config, read_config_raw, validate_config, write_config, write_config_raw};
-use device::{scan_devices, start_device_watcher, stop_device_watcher};
+use archive::get_storage_usage;
+use audit::audit_device_files;
+use capabilities::{get_api_version, get_app_capabilities, negotiate_api_version};
+use commands::{generate_default_config, list_backups, push_config_to_all, read_config, read_config_migrated, read_config_raw, restore_backup, sanitize_labels, set_config_locked, validate_config, write_config, write_config_raw};
+use crash::{clear_crash_reports, get_crash_reports};
+use dependencies::check_firmware_dependencies;
+use device::{add_known_volume, get_known_volumes, list_connected_devices, remove_known_volume, scan_devices, scan_devices_async, start_device_watcher, stop_device_watcher};
+use device_info::get_device_info;
+use device_profiles::{activate_profile, delete_profile, list_profiles, save_profile};
+use diff::{diff_config, diff_files};
+use download::{clear_download_cache, download_bundle, fetch_library_bundle, list_download_cache};
+use explain::explain_control;
+use firmware::{get_rollback_info, rollback_firmware, stash_firmware_for_rollback};
+use firmware_deploy::{deploy_firmware, get_installed_firmware_version};
+use guest::{get_guest_mode, set_guest_mode};
+use health::check_device_health;
+use history::{get_write_history, redo, undo_last_write};
+use hotkey::{check_hotkey_conflict, clear_deploy_hotkey, get_deploy_hotkey, set_deploy_hotkey, set_hotkey_target};
+use interchange::{export_config, import_config};
+use lint::{get_validation_report, validate_config_detailed};
+use maintenance::{get_maintenance_settings, run_maintenance, set_maintenance_settings};
+use midi::{get_midi_broadcast, list_midi_outputs, scan_midi_ports, send_test_message, set_midi_broadcast, start_midi_monitor, stop_midi_monitor};
+use midi_chart::export_midi_chart;
+use monitor::{finish_expression_calibration, start_encoder_monitor, start_exp_monitor, start_expression_calibration, stop_encoder_monitor, stop_exp_monitor};
+use notifications::{get_notification_settings, set_notification_settings};
+use paintaudio::{export_paintaudio, import_paintaudio};
+use presets::{delete_preset, get_library_health, get_library_index, list_presets, load_preset, push_preset, quick_deploy, save_preset, save_preset_from_device, search_presets, set_favorite, set_preset_metadata, set_quick_deploy_slot};
+use profile_updates::{get_profile_update_settings, set_profile_update_settings, update_profiles};
+use pyfixture::export_python_fixture;
+use recovery::{create_recovery_point, delete_recovery_point, list_recovery_points, restore_recovery_point};
+use registry::{get_device_settings, get_write_log, set_device_settings};
+use remap::{apply_channel_remap, delete_channel_remap, list_channel_remaps, remap_channels, save_channel_remap};
+use repair::repair_config;
+use scripting::run_script;
+use selftest::run_self_test;
+use serial::{connect_serial, disconnect_serial, list_console_ports, preview_label, reboot_device, send_repl, send_repl_key, set_live_parameter, watch_device_boot};
+use setlist::export_setlist_sheet;
+use stress::run_storage_stress_test;
+use targets::list_target_profiles;
+use templates::{apply_template, list_templates};
+use temporary::{deploy_temporary, get_temporary, keep_temporary};
+
+#[cfg(feature = "fuzzing")]
+pub use selftest::check_bytes;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let handler: Box<dyn Fn(tauri::ipc::Invoke) -> bool + Send + Sync> = Box::new(tauri::generate_handler![
+        read_config,
+        read_config_raw,
+        write_config,
+        write_config_raw,
+        validate_config,
+        scan_devices,
+        start_device_watcher,
+        stop_device_watcher,
+        stash_firmware_for_rollback,
+        get_rollback_info,
+        rollback_firmware,
+        check_firmware_dependencies,
+        download_bundle,
+        fetch_library_bundle,
+        list_download_cache,
+        clear_download_cache,
+        watch_device_boot,
+        list_console_ports,
+        check_device_health,
+        set_live_parameter,
+        run_script,
+        apply_channel_remap,
+        delete_channel_remap,
+        list_channel_remaps,
+        remap_channels,
+        save_channel_remap,
+        diff_files,
+        get_validation_report,
+        set_config_locked,
+        start_exp_monitor,
+        stop_exp_monitor,
+        start_encoder_monitor,
+        stop_encoder_monitor,
+        preview_label,
+        get_crash_reports,
+        clear_crash_reports,
+        delete_preset,
+        get_library_health,
+        list_presets,
+        load_preset,
+        save_preset,
+        get_library_index,
+        quick_deploy,
+        set_favorite,
+        set_quick_deploy_slot,
+        get_storage_usage,
+        get_maintenance_settings,
+        run_maintenance,
+        set_maintenance_settings,
+        audit_device_files,
+        run_self_test,
+        get_app_capabilities,
+        explain_control,
+        set_deploy_hotkey,
+        clear_deploy_hotkey,
+        get_deploy_hotkey,
+        check_hotkey_conflict,
+        set_hotkey_target,
+        list_target_profiles,
+        get_device_settings,
+        set_device_settings,
+        get_write_log,
+        create_recovery_point,
+        list_recovery_points,
+        restore_recovery_point,
+        delete_recovery_point,
+        scan_devices_async,
+        export_paintaudio,
+        import_paintaudio,
+        get_api_version,
+        negotiate_api_version,
+        list_midi_outputs,
+        get_midi_broadcast,
+        set_midi_broadcast,
+        list_backups,
+        restore_backup,
+        run_storage_stress_test,
+        export_python_fixture,
+        scan_midi_ports,
+        start_midi_monitor,
+        stop_midi_monitor,
+        export_setlist_sheet,
+        set_preset_metadata,
+        send_test_message,
+        diff_config,
+        get_guest_mode,
+        set_guest_mode,
+        generate_default_config,
+        deploy_temporary,
+        keep_temporary,
+        get_temporary,
+        repair_config,
+        read_config_migrated,
+        get_notification_settings,
+        set_notification_settings,
+        get_device_info,
+        update_profiles,
+        get_profile_update_settings,
+        set_profile_update_settings,
+        get_installed_firmware_version,
+        deploy_firmware,
+        list_profiles,
+        activate_profile,
+        save_profile,
+        delete_profile,
+        save_preset_from_device,
+        search_presets,
+        push_preset,
+        export_config,
+        import_config,
+        export_midi_chart,
+        start_expression_calibration,
+        finish_expression_calibration,
+        connect_serial,
+        disconnect_serial,
+        send_repl,
+        send_repl_key,
+        reboot_device,
+        undo_last_write,
+        redo,
+        get_write_history,
+        list_templates,
+        apply_template,
+        validate_config_detailed,
+        sanitize_labels,
+        list_connected_devices,
+        push_config_to_all,
+        get_known_volumes,
+        add_known_volume,
+        remove_known_volume
+    ]);
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
-        .invoke_handler(tauri::generate_handler![
-            read_config,
-            read_config_raw,
-            write_config,
-            write_config_raw,
-            validate_config,
-            scan_devices,
-            start_device_watcher,
-            stop_device_watcher
-        ])
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
+        .setup(|app| {
+            if let Ok(dir) = crash::crash_dir(app.handle()) {
+                crash::install(dir);
+            }
+            if let Ok(dir) = commands::app_data_dir(app.handle()) {
+                guest::install(&dir);
+                profile_updates::install_saved(&dir);
+                registry::install(dir);
+            }
+            presets::spawn_startup_check(app.handle().clone());
+            maintenance::spawn_scheduled(app.handle().clone());
+            profile_updates::spawn_scheduled(app.handle().clone());
+            hotkey::register_saved(app.handle());
+            notifications::install(app.handle().clone());
+            Ok(())
+        })
+        .invoke_handler(move |invoke| {
+            if let tauri::ipc::InvokeBody::Json(args) = invoke.message.payload() {
+                if let Err(e) = sanitize::check_invoke_args(args) {
+                    invoke.resolver.reject(e);
+                    return true;
+                }
+            }
+            if guest::refuses(invoke.message.command()) {
+                invoke.resolver.reject(guest::read_only_error());
+                return true;
+            }
+            crash::guard_invoke(invoke, handler.as_ref())
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,487 @@
+//! Config lint warnings
+//!
+//! Unlike `MidiCaptainConfig::validate` (hard errors that block a write),
+//! lint findings flag configs that are valid but probably not what the user
+//! meant. A finding can be suppressed in the config's `_editor` metadata with
+//! a justification; suppressed findings stay in the report, marked as such.
+//! Findings are warnings, or hints for the advisory rules.
+
+use crate::config::{
+    generate_default_config, ButtonMode, DeviceType, MessageType, MidiCaptainConfig, Severity, Suppression, ValidationIssue,
+};
+use crate::targets::{profile_for, SwitchBehavior, TargetProfile};
+use std::collections::HashMap;
+use tauri::command;
+
+/// Two controls (buttons, encoder, encoder push, pedals) send the same CC on
+/// the same channel
+pub const RULE_DUPLICATE_CC: &str = "duplicate-cc";
+/// Two buttons send the same note on the same channel
+pub const RULE_DUPLICATE_NOTE: &str = "duplicate-note";
+/// A button has no label, so nothing is shown on the display
+pub const RULE_EMPTY_LABEL: &str = "empty-label";
+/// A switch's mode doesn't match what the target profile expects for its CC
+/// (e.g. toggle on a CC the amp treats as momentary)
+pub const RULE_SWITCH_MODE: &str = "switch-mode";
+
+/// A label is wider than its switch's box at the button text size, so the
+/// display cuts it off
+pub const RULE_LABEL_TRUNCATED: &str = "label-truncated";
+
+/// The config is for a model the firmware can't run yet (see
+/// `DeviceType::firmware_supported`)
+pub const RULE_UNSUPPORTED_DEVICE: &str = "unsupported-device";
+
+/// No switch is a panic button
+pub const RULE_NO_PANIC: &str = "no-panic";
+/// The encoder is enabled but still has its factory CC and label
+pub const RULE_UNUSED_ENCODER: &str = "unused-encoder";
+
+/// Rules that suggest rather than flag a likely mistake; their findings are
+/// hints, and strict mode doesn't fail on them
+pub const ADVISORY_RULES: &[&str] = &[RULE_NO_PANIC, RULE_UNUSED_ENCODER];
+
+/// A lint finding
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Finding {
+    pub rule: String,
+    /// Location the finding refers to, e.g. "buttons[3]"
+    pub target: String,
+    pub message: String,
+    /// Warning, or hint for advisory rules
+    pub severity: Severity,
+    pub suppressed: bool,
+    /// Justification from the matching suppression
+    pub justification: Option<String>,
+}
+
+/// Validation errors plus lint findings for a config
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ValidationReport {
+    pub errors: Vec<ValidationIssue>,
+    pub findings: Vec<Finding>,
+}
+
+/// A config's problems by severity. Only errors block a save; suppressed
+/// findings are left out.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct DetailedValidation {
+    /// The firmware would reject or misread the config
+    pub errors: Vec<ValidationIssue>,
+    /// Probably not what was meant (duplicate CCs, labels cut off)
+    pub warnings: Vec<ValidationIssue>,
+    /// Suggestions (an encoder left at its factory settings)
+    pub hints: Vec<ValidationIssue>,
+}
+
+/// Approximate width (px) of a label character at each button text size, in
+/// the firmware's fonts (terminalio, PTSans 20, PTSans Bold 60)
+fn char_width(text_size: &str) -> usize {
+    match text_size {
+        "small" => 6,
+        "large" => 30,
+        _ => 10,
+    }
+}
+
+/// Label characters that fit inside a switch's box: the firmware draws them
+/// 70px wide in a row of three (Mini6) and 46px in a row of five (STD10),
+/// with a 1px border, and uses medium text when the config doesn't say
+fn label_room(config: &MidiCaptainConfig) -> usize {
+    let box_width = match config.device {
+        DeviceType::Mini6 | DeviceType::Nano4 => 70,
+        DeviceType::Std10 | DeviceType::Blue => 46,
+    };
+    let text_size = config.display.as_ref().and_then(|d| d.button_text_size.as_deref()).unwrap_or("medium");
+    (box_width - 2) / char_width(text_size)
+}
+
+fn mode_name(mode: &ButtonMode) -> &'static str {
+    match mode {
+        ButtonMode::Toggle => "toggle",
+        ButtonMode::Momentary => "momentary",
+        ButtonMode::TapTempo => "tap tempo",
+    }
+}
+
+/// Finding for a switch whose mode contradicts the profile's behavior for its CC
+fn switch_mode_finding(profile: &TargetProfile, target: String, what: &str, cc: u8, mode: &ButtonMode) -> Option<Finding> {
+    // A tap-tempo switch sends one value per tap, whatever the CC's behavior
+    if *mode == ButtonMode::TapTempo {
+        return None;
+    }
+    let function = profile.cc(cc)?;
+    let (expected, behavior) = match function.behavior? {
+        SwitchBehavior::Latching => (ButtonMode::Toggle, "latching"),
+        SwitchBehavior::Momentary => (ButtonMode::Momentary, "momentary"),
+    };
+    if *mode == expected {
+        return None;
+    }
+    Some(finding(
+        RULE_SWITCH_MODE,
+        target,
+        format!(
+            "{} is set to {}, but {} treats CC {} ({}) as {}; set it to {}",
+            what,
+            mode_name(mode),
+            profile.name,
+            cc,
+            function.name,
+            behavior,
+            mode_name(&expected)
+        ),
+    ))
+}
+
+fn finding(rule: &str, target: String, message: String) -> Finding {
+    Finding {
+        rule: rule.to_string(),
+        target,
+        message,
+        severity: if ADVISORY_RULES.contains(&rule) { Severity::Hint } else { Severity::Warning },
+        suppressed: false,
+        justification: None,
+    }
+}
+
+/// Flag `control` if another control already sends the same CC or note on
+/// the same channel. `seen` maps (rule, number, channel) to the first
+/// control's name.
+fn check_duplicate(
+    seen: &mut HashMap<(&'static str, u8, u8), String>,
+    findings: &mut Vec<Finding>,
+    key: (&'static str, u8, u8),
+    target: String,
+    control: &str,
+) {
+    if let Some(first) = seen.get(&key) {
+        let what = if key.0 == RULE_DUPLICATE_CC { "CC" } else { "note" };
+        findings.push(finding(
+            key.0,
+            target,
+            format!("{} sends the same {} {} on channel {} as {}", control, what, key.1, key.2 + 1, first),
+        ));
+    } else {
+        seen.insert(key, control.to_lowercase());
+    }
+}
+
+/// Run all lint rules, without applying suppressions
+pub fn lint(config: &MidiCaptainConfig) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let default_channel = config.global_channel.unwrap_or(0);
+    let mut seen = HashMap::new();
+    let profile = profile_for(config);
+    let room = label_room(config);
+
+    if !config.device.firmware_supported() {
+        findings.push(finding(
+            RULE_UNSUPPORTED_DEVICE,
+            "device".to_string(),
+            format!(
+                "The firmware doesn't support the {} yet: it ignores this config's device and runs as a Mini6 or STD10",
+                config.device.display_name()
+            ),
+        ));
+    }
+
+    for (i, button) in config.buttons.iter().enumerate() {
+        let target = format!("buttons[{}]", i);
+        if button.label.trim().is_empty() {
+            findings.push(finding(RULE_EMPTY_LABEL, target.clone(), format!("Button {} has no label", i + 1)));
+        }
+        // Labels over 6 characters are validation errors already
+        let length = button.label.chars().count();
+        if length > room && length <= 6 {
+            findings.push(finding(
+                RULE_LABEL_TRUNCATED,
+                target.clone(),
+                format!("Button {} label '{}' may be cut off: about {} characters fit at this text size", i + 1, button.label, room),
+            ));
+        }
+        // Keytimes buttons stay on while cycling, so their mode has no latching meaning
+        let cycling = button.keytimes.is_some_and(|k| k > 1);
+        if let (Some(profile), MessageType::Cc, Some(cc), false) = (&profile, &button.message_type, button.cc, cycling) {
+            findings.extend(switch_mode_finding(profile, target.clone(), &format!("Button {}", i + 1), cc, &button.mode));
+        }
+
+        let channel = button.channel.unwrap_or(default_channel);
+        let key = match (&button.message_type, button.cc, button.note) {
+            (MessageType::Cc, Some(cc), _) => (RULE_DUPLICATE_CC, cc, channel),
+            (MessageType::Note, _, Some(note)) => (RULE_DUPLICATE_NOTE, note, channel),
+            _ => continue,
+        };
+        check_duplicate(&mut seen, &mut findings, key, target, &format!("Button {}", i + 1));
+    }
+
+    // The encoder, its push switch and the pedals send CCs too (when enabled)
+    let mut controls = Vec::new();
+    if let Some(encoder) = config.encoder.as_ref() {
+        if encoder.enabled {
+            controls.push(("encoder", "The encoder", encoder.cc, encoder.channel));
+        }
+        if let Some(push) = encoder.push.as_ref().filter(|p| p.enabled) {
+            controls.push(("encoder.push", "The encoder push", push.cc, push.channel));
+        }
+    }
+    if let Some(expression) = config.expression.as_ref() {
+        for (target, name, pedal) in [
+            ("expression.exp1", "Expression pedal 1", &expression.exp1),
+            ("expression.exp2", "Expression pedal 2", &expression.exp2),
+        ] {
+            if pedal.enabled {
+                controls.push((target, name, pedal.cc, pedal.channel));
+            }
+        }
+    }
+    for (target, name, cc, channel) in controls {
+        let key = (RULE_DUPLICATE_CC, cc, channel.unwrap_or(default_channel));
+        check_duplicate(&mut seen, &mut findings, key, target.to_string(), name);
+    }
+
+    if let (Some(profile), Some(push)) = (&profile, config.encoder.as_ref().and_then(|e| e.push.as_ref())) {
+        findings.extend(switch_mode_finding(profile, "encoder.push".to_string(), "The encoder push", push.cc, &push.mode));
+    }
+
+    if !config.buttons.iter().any(|b| b.message_type == MessageType::Panic) {
+        findings.push(finding(
+            RULE_NO_PANIC,
+            "buttons".to_string(),
+            "No switch is a panic button; for live use, dedicate one so stuck notes can be silenced".to_string(),
+        ));
+    }
+
+    let factory = generate_default_config(DeviceType::Std10).encoder;
+    if let (Some(encoder), Some(factory)) = (config.encoder.as_ref().filter(|e| e.enabled), factory) {
+        if encoder.cc == factory.cc && encoder.label == factory.label {
+            findings.push(finding(
+                RULE_UNUSED_ENCODER,
+                "encoder".to_string(),
+                format!("The encoder is enabled with its factory CC {}; disable it if nothing listens for it", encoder.cc),
+            ));
+        }
+    }
+    findings
+}
+
+fn matches(suppression: &Suppression, finding: &Finding) -> bool {
+    suppression.rule == finding.rule
+        && suppression.target.as_deref().is_none_or(|t| t == finding.target)
+}
+
+/// Mark findings covered by a suppression. Findings are never removed.
+pub fn apply_suppressions(findings: &mut [Finding], suppressions: &[Suppression]) {
+    for finding in findings.iter_mut() {
+        if let Some(s) = suppressions.iter().find(|s| matches(s, finding)) {
+            finding.suppressed = true;
+            finding.justification = Some(s.justification.clone());
+        }
+    }
+}
+
+/// Build the full validation report for a config
+pub fn validation_report(config: &MidiCaptainConfig) -> ValidationReport {
+    let mut findings = lint(config);
+    if let Some(ref editor) = config.editor {
+        apply_suppressions(&mut findings, &editor.suppressions);
+    }
+    ValidationReport {
+        errors: config.validate().err().unwrap_or_default(),
+        findings,
+    }
+}
+
+/// Validate a config and report lint findings (suppressed ones are marked, not hidden)
+#[command]
+pub fn get_validation_report(config: MidiCaptainConfig) -> ValidationReport {
+    validation_report(&config)
+}
+
+/// Split a config's validation errors and unsuppressed lint findings by severity
+pub fn detailed_validation(config: &MidiCaptainConfig) -> DetailedValidation {
+    let report = validation_report(config);
+    let mut detailed = DetailedValidation {
+        errors: report.errors,
+        ..Default::default()
+    };
+    for finding in report.findings.into_iter().filter(|f| !f.suppressed) {
+        let issue = ValidationIssue {
+            path: finding.target,
+            code: finding.rule,
+            severity: finding.severity,
+            message: finding.message,
+        };
+        match issue.severity {
+            Severity::Error => detailed.errors.push(issue),
+            Severity::Warning => detailed.warnings.push(issue),
+            Severity::Hint => detailed.hints.push(issue),
+        }
+    }
+    detailed
+}
+
+/// Validation errors, warnings and hints for a config; the config can be
+/// saved when there are no errors
+#[command]
+pub fn validate_config_detailed(config: MidiCaptainConfig) -> DetailedValidation {
+    detailed_validation(&config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(editor: &str) -> MidiCaptainConfig {
+        serde_json::from_str(&format!(
+            r#"{{
+                "device": "mini6",
+                "buttons": [
+                    {{"label": "LOOP", "cc": 20, "color": "red"}},
+                    {{"label": "LOOP2", "cc": 20, "color": "red"}},
+                    {{"label": "", "cc": 22, "color": "red"}},
+                    {{"label": "CH2", "cc": 20, "color": "red", "channel": 1}},
+                    {{"label": "N", "type": "note", "note": 60, "color": "red"}},
+                    {{"label": "N2", "type": "note", "note": 60, "color": "red"}}
+                ]
+                {}
+            }}"#,
+            editor
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_lint_rules() {
+        let findings = lint(&config(""));
+        let rules: Vec<(&str, &str)> = findings.iter().map(|f| (f.rule.as_str(), f.target.as_str())).collect();
+        assert_eq!(
+            rules,
+            vec![
+                (RULE_DUPLICATE_CC, "buttons[1]"),
+                (RULE_EMPTY_LABEL, "buttons[2]"),
+                (RULE_DUPLICATE_NOTE, "buttons[5]"),
+                (RULE_NO_PANIC, "buttons"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_switch_mode_checked_against_target_profile() {
+        let config: MidiCaptainConfig = serde_json::from_str(
+            r#"{
+                "device": "mini6",
+                "buttons": [
+                    {"label": "SUS", "cc": 64, "color": "red"},
+                    {"label": "SUS2", "cc": 64, "color": "red", "mode": "momentary", "channel": 1},
+                    {"label": "PORT", "cc": 65, "color": "red", "mode": "momentary"},
+                    {"label": "CYC", "cc": 66, "color": "red", "keytimes": 2},
+                    {"label": "FX", "cc": 20, "color": "red", "mode": "momentary"},
+                    {"label": "FX2", "cc": 21, "color": "red"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let findings: Vec<Finding> = lint(&config).into_iter().filter(|f| f.rule == RULE_SWITCH_MODE).collect();
+        let targets: Vec<&str> = findings.iter().map(|f| f.target.as_str()).collect();
+        assert_eq!(targets, vec!["buttons[0]", "buttons[2]"]);
+        assert_eq!(
+            findings[0].message,
+            "Button 1 is set to toggle, but MIDI standard controllers treats CC 64 (Sustain Pedal) as momentary; set it to momentary"
+        );
+        assert!(findings[1].message.ends_with("as latching; set it to toggle"));
+    }
+
+    #[test]
+    fn test_suppressed_findings_are_marked_not_hidden() {
+        let report = validation_report(&config(
+            r#", "_editor": {"suppressions": [{"rule": "duplicate-cc", "target": "buttons[1]", "justification": "Both buttons toggle the looper"}]}"#,
+        ));
+        assert!(report.errors.is_empty());
+        assert_eq!(report.findings.len(), 4);
+        let dup = report.findings.iter().find(|f| f.rule == RULE_DUPLICATE_CC).unwrap();
+        assert!(dup.suppressed);
+        assert_eq!(dup.justification.as_deref(), Some("Both buttons toggle the looper"));
+        assert!(report.findings.iter().filter(|f| f.rule != RULE_DUPLICATE_CC).all(|f| !f.suppressed));
+    }
+
+    #[test]
+    fn test_targeted_suppression_only_matches_its_target() {
+        let report = validation_report(&config(
+            r#", "_editor": {"suppressions": [{"rule": "empty-label", "target": "buttons[0]", "justification": "n/a"}]}"#,
+        ));
+        let empty = report.findings.iter().find(|f| f.rule == RULE_EMPTY_LABEL).unwrap();
+        assert!(!empty.suppressed);
+    }
+
+    #[test]
+    fn test_duplicate_cc_across_encoder_and_pedals() {
+        let config: MidiCaptainConfig = serde_json::from_str(
+            r#"{
+                "device": "mini6",
+                "buttons": [
+                    {"label": "1", "cc": 11, "color": "red"}, {"label": "2", "cc": 21, "color": "red"},
+                    {"label": "3", "cc": 22, "color": "red"}, {"label": "4", "cc": 23, "color": "red"},
+                    {"label": "5", "cc": 24, "color": "red"}, {"label": "6", "cc": 25, "color": "red"}
+                ],
+                "encoder": {"enabled": true, "cc": 11, "label": "ENC", "push": {"enabled": true, "cc": 21, "label": "PUSH"}},
+                "expression": {
+                    "exp1": {"enabled": true, "cc": 12, "label": "EXP1", "channel": 3},
+                    "exp2": {"enabled": false, "cc": 12, "label": "EXP2", "channel": 3}
+                }
+            }"#,
+        )
+        .unwrap();
+        let findings: Vec<Finding> = lint(&config).into_iter().filter(|f| f.rule == RULE_DUPLICATE_CC).collect();
+        let targets: Vec<&str> = findings.iter().map(|f| f.target.as_str()).collect();
+        assert_eq!(targets, vec!["encoder", "encoder.push"]);
+        assert_eq!(findings[0].message, "The encoder sends the same CC 11 on channel 1 as button 1");
+    }
+
+    #[test]
+    fn test_labels_cut_off_at_the_text_size() {
+        let mut config = config("");
+        assert!(lint(&config).iter().all(|f| f.rule != RULE_LABEL_TRUNCATED));
+
+        config.display = serde_json::from_str(r#"{"button_text_size": "large"}"#).unwrap();
+        let targets: Vec<String> = lint(&config)
+            .into_iter()
+            .filter(|f| f.rule == RULE_LABEL_TRUNCATED)
+            .map(|f| f.target)
+            .collect();
+        assert_eq!(targets, ["buttons[0]", "buttons[1]", "buttons[3]"]);
+    }
+
+    #[test]
+    fn test_detailed_validation_tiers() {
+        let mut std10 = generate_default_config(DeviceType::Std10);
+        std10.buttons[0].label = "TUNER".to_string();
+        std10.buttons[1].label = "TOOLONG".to_string();
+        let detailed = detailed_validation(&std10);
+        let paths = |issues: &[ValidationIssue]| issues.iter().map(|i| i.path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths(&detailed.errors), ["buttons[1].label"]);
+        assert_eq!(paths(&detailed.warnings), ["buttons[0]"]);
+        assert_eq!(paths(&detailed.hints), ["buttons", "encoder"]);
+        assert!(detailed.hints.iter().all(|h| h.severity == Severity::Hint));
+
+        std10.encoder.as_mut().unwrap().cc = 30;
+        assert_eq!(paths(&detailed_validation(&std10).hints), ["buttons"]);
+    }
+
+    #[test]
+    fn test_models_without_firmware_support_are_flagged() {
+        for device in DeviceType::ALL {
+            let flagged = lint(&generate_default_config(device.clone())).iter().any(|f| f.rule == RULE_UNSUPPORTED_DEVICE);
+            assert_eq!(flagged, !device.firmware_supported(), "{:?}", device);
+        }
+        let detailed = detailed_validation(&generate_default_config(DeviceType::Nano4));
+        assert!(detailed.warnings.iter().any(|w| w.path == "device" && w.message.contains("Nano4")));
+    }
+
+    #[test]
+    fn test_panic_button_satisfies_no_panic_rule() {
+        let mut config = config("");
+        config.buttons[5].message_type = MessageType::Panic;
+        assert!(lint(&config).iter().all(|f| f.rule != RULE_NO_PANIC));
+    }
+}
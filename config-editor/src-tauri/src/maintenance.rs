@@ -0,0 +1,279 @@
+//! Backup store maintenance
+//!
+//! Keeps the app's storage under a quota by removing the oldest rollback
+//! slots, cached downloads and crash reports. Anything newer than the
+//! retention window is kept even when over quota, and presets are never
+//! removed — they are the user's own data.
+//!
+//! Runs at startup and then daily; `run_maintenance` runs it on demand.
+
+use crate::archive::{dir_usage, storage_areas, storage_usage};
+use crate::commands::{app_data_dir, ConfigError};
+use crate::download::{list_cached, remove_cached};
+use crate::firmware::{read_slot, unix_now};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::{command, AppHandle, Emitter};
+
+/// File under the app-data directory holding maintenance settings
+const SETTINGS_FILE: &str = "maintenance.json";
+
+/// Time between scheduled runs
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// Quota and retention for the backup store
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MaintenanceSettings {
+    /// Total storage to stay under, in bytes
+    pub quota_bytes: u64,
+    /// Items younger than this are never pruned
+    pub retention_days: u64,
+}
+
+impl Default for MaintenanceSettings {
+    fn default() -> Self {
+        MaintenanceSettings {
+            quota_bytes: 500 * 1024 * 1024,
+            retention_days: 30,
+        }
+    }
+}
+
+/// Something maintenance may remove
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PruneCandidate {
+    /// Storage area, e.g. "rollback"
+    pub area: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// Unix timestamp (seconds) the item was created
+    pub created_at: u64,
+}
+
+/// What a maintenance run did
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MaintenanceReport {
+    pub removed: Vec<PruneCandidate>,
+    pub freed_bytes: u64,
+    /// Storage used after the run
+    pub total_bytes: u64,
+    pub quota_bytes: u64,
+}
+
+/// Load settings from the app-data directory
+pub fn load_settings(data_dir: &Path) -> MaintenanceSettings {
+    fs::read_to_string(data_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Rollback slots, one per device directory under `root`
+pub fn rollback_candidates(root: &Path) -> Vec<PruneCandidate> {
+    let Ok(entries) = fs::read_dir(root) else { return Vec::new() };
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .map(|path| PruneCandidate {
+            area: "rollback".to_string(),
+            bytes: dir_usage(&path).1,
+            created_at: read_slot(&path).map(|s| s.created_at).unwrap_or_else(|| modified_secs(&path)),
+            path,
+        })
+        .collect()
+}
+
+/// Plain files directly under `dir`
+pub fn file_candidates(area: &str, dir: &Path) -> Vec<PruneCandidate> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .map(|path| PruneCandidate {
+            area: area.to_string(),
+            bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            created_at: modified_secs(&path),
+            path,
+        })
+        .collect()
+}
+
+/// Pick what to remove: oldest first, skipping anything inside the retention
+/// window, until `total_bytes` is under the quota
+pub fn plan_pruning(
+    mut candidates: Vec<PruneCandidate>,
+    total_bytes: u64,
+    settings: &MaintenanceSettings,
+    now: u64,
+) -> Vec<PruneCandidate> {
+    candidates.sort_by_key(|c| c.created_at);
+    let cutoff = now.saturating_sub(settings.retention_days * SECS_PER_DAY);
+    let mut total = total_bytes;
+    let mut plan = Vec::new();
+    for candidate in candidates {
+        if total <= settings.quota_bytes {
+            break;
+        }
+        if candidate.created_at > cutoff {
+            continue;
+        }
+        total = total.saturating_sub(candidate.bytes);
+        plan.push(candidate);
+    }
+    plan
+}
+
+fn remove(candidate: &PruneCandidate) -> std::io::Result<()> {
+    match candidate.area.as_str() {
+        "rollback" => fs::remove_dir_all(&candidate.path),
+        "downloads" => remove_cached(&candidate.path),
+        _ => fs::remove_file(&candidate.path),
+    }
+}
+
+/// Run maintenance over the app's storage areas
+pub fn maintain(app: &AppHandle) -> Result<MaintenanceReport, ConfigError> {
+    let settings = load_settings(&app_data_dir(app)?);
+    let areas = storage_areas(app)?;
+    let total_bytes = storage_usage(&areas).total_bytes;
+
+    let mut candidates = Vec::new();
+    for (name, dir) in &areas {
+        match *name {
+            "rollback" => candidates.extend(rollback_candidates(dir)),
+            // Only verified downloads; their sidecars go with them
+            "downloads" => candidates.extend(list_cached(dir).into_iter().map(|d| PruneCandidate {
+                area: name.to_string(),
+                bytes: d.size,
+                created_at: modified_secs(&d.path),
+                path: d.path,
+            })),
+            "crashes" => candidates.extend(file_candidates(name, dir)),
+            _ => {}
+        }
+    }
+
+    let mut removed = Vec::new();
+    for candidate in plan_pruning(candidates, total_bytes, &settings, unix_now()) {
+        if remove(&candidate).is_ok() {
+            removed.push(candidate);
+        }
+    }
+    Ok(MaintenanceReport {
+        freed_bytes: removed.iter().map(|c| c.bytes).sum(),
+        removed,
+        total_bytes: storage_usage(&areas).total_bytes,
+        quota_bytes: settings.quota_bytes,
+    })
+}
+
+/// Scheduled task: run at startup and then daily, emitting
+/// "maintenance-report" whenever something was removed
+pub fn spawn_scheduled(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        if let Ok(report) = maintain(&app) {
+            if !report.removed.is_empty() {
+                let _ = app.emit("maintenance-report", report);
+            }
+        }
+        std::thread::sleep(MAINTENANCE_INTERVAL);
+    });
+}
+
+/// Prune the backup store now and report what was removed
+#[command]
+pub fn run_maintenance(app: AppHandle) -> Result<MaintenanceReport, ConfigError> {
+    maintain(&app)
+}
+
+/// Current quota and retention
+#[command]
+pub fn get_maintenance_settings(app: AppHandle) -> Result<MaintenanceSettings, ConfigError> {
+    Ok(load_settings(&app_data_dir(&app)?))
+}
+
+/// Change the quota and retention used by maintenance
+#[command]
+pub fn set_maintenance_settings(app: AppHandle, settings: MaintenanceSettings) -> Result<(), ConfigError> {
+    let json = serde_json::to_string_pretty(&settings)?;
+    fs::write(app_data_dir(&app)?.join(SETTINGS_FILE), json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = SECS_PER_DAY;
+
+    fn candidate(name: &str, bytes: u64, created_at: u64) -> PruneCandidate {
+        PruneCandidate {
+            area: "rollback".to_string(),
+            path: PathBuf::from(name),
+            bytes,
+            created_at,
+        }
+    }
+
+    fn names(plan: &[PruneCandidate]) -> Vec<&str> {
+        plan.iter().map(|c| c.path.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_prunes_oldest_until_under_quota() {
+        let settings = MaintenanceSettings {
+            quota_bytes: 100,
+            retention_days: 30,
+        };
+        let now = 100 * DAY;
+        let candidates = vec![candidate("b", 40, 20 * DAY), candidate("a", 40, 10 * DAY), candidate("c", 40, 30 * DAY)];
+        let plan = plan_pruning(candidates, 170, &settings, now);
+        assert_eq!(names(&plan), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_retention_window_is_kept_even_over_quota() {
+        let settings = MaintenanceSettings {
+            quota_bytes: 10,
+            retention_days: 30,
+        };
+        let now = 100 * DAY;
+        let candidates = vec![candidate("old", 40, 10 * DAY), candidate("recent", 40, 90 * DAY)];
+        let plan = plan_pruning(candidates, 200, &settings, now);
+        assert_eq!(names(&plan), vec!["old"]);
+    }
+
+    #[test]
+    fn test_nothing_pruned_under_quota() {
+        let plan = plan_pruning(vec![candidate("a", 40, 0)], 40, &MaintenanceSettings::default(), 100 * DAY);
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn test_rollback_candidates_use_slot_timestamp() {
+        let root = tempfile::tempdir().unwrap();
+        let slot = root.path().join("CIRCUITPY");
+        fs::create_dir_all(&slot).unwrap();
+        fs::write(slot.join("slot.json"), r#"{"created_at": 1234, "entries": []}"#).unwrap();
+        fs::write(slot.join("code.py.zst"), [0u8; 8]).unwrap();
+
+        let candidates = rollback_candidates(root.path());
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].created_at, 1234);
+        assert!(candidates[0].bytes >= 8);
+    }
+}
@@ -0,0 +1,381 @@
+//! MIDI output from the host
+//!
+//! After a config write lands on the device, the editor can broadcast a
+//! "config changed" SysEx on a chosen MIDI output, so DAW templates or
+//! companion apps listening for it can re-sync their idea of the pedal's
+//! mapping. Off until a port is chosen.
+//!
+//! Message layout:
+//!
+//! ```text
+//! F0 7D 4D 43 01 <schema> <hash x5> <name...> F7
+//! ```
+//!
+//! - `7D`: the non-commercial manufacturer ID, `4D 43`: "MC"
+//! - `01`: config changed
+//! - `<schema>`: `CONFIG_SCHEMA_VERSION` (low 7 bits)
+//! - `<hash>`: the first 32 bits of the written file's SHA-256, as five
+//!   7-bit bytes, most significant first
+//! - `<name>`: the device's volume name, ASCII
+//!
+//! `start_midi_monitor` listens on one of the device's MIDI ports and emits
+//! every CC, program change and note it sends as a "midi-message" event, so
+//! pressing a footswitch shows what it sent while the config is open.
+//!
+//! `send_test_message` sends one of those messages to a host output, so a
+//! DAW or amp mapping can be checked before the config is written.
+//!
+//! `scan_midi_ports` also looks for the device's own USB MIDI ports. In
+//! performance mode boot.py hides the drive, so the device shows up only as
+//! MIDI; the port scan is how the app tells that apart from "not plugged in".
+
+use crate::commands::{app_data_dir, ConfigError};
+use crate::config::CONFIG_SCHEMA_VERSION;
+use crate::download::sha256_hex;
+use midir::{Ignore, MidiInput, MidiOutput};
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Emitter};
+
+/// File under the app-data directory holding the broadcast settings
+const SETTINGS_FILE: &str = "midi.json";
+
+/// Client name shown to other MIDI apps
+const CLIENT_NAME: &str = "MIDI Captain Config Editor";
+
+/// SysEx header: non-commercial manufacturer ID and "MC"
+const SYSEX_HEADER: [u8; 4] = [0xF0, 0x7D, 0x4D, 0x43];
+
+/// Message id: config changed
+const MSG_CONFIG_CHANGED: u8 = 0x01;
+
+/// Shutdown signal for the running MIDI monitor
+static MIDI_MONITOR_TX: Mutex<Option<Sender<()>>> = Mutex::new(None);
+
+/// Port name fragments (lowercase) that identify the device. CircuitPython
+/// names its MIDI ports after itself unless the firmware renames them.
+const DEVICE_PORT_NAMES: &[&str] = &["circuitpython", "midi captain", "midicaptain"];
+
+/// How the device is attached over USB
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsbMode {
+    /// Neither a drive nor MIDI ports were found
+    Disconnected,
+    /// MIDI ports only: the drive is hidden (performance mode)
+    MidiOnly,
+    /// Drive only: MIDI ports weren't found (or MIDI is unavailable on the host)
+    DriveOnly,
+    /// Drive and MIDI ports, as in dev mode or a switch-1 boot
+    MidiAndDrive,
+}
+
+/// Result of `scan_midi_ports`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MidiPortScan {
+    /// The device's MIDI input ports (what it sends)
+    pub inputs: Vec<String>,
+    /// The device's MIDI output ports (what it receives)
+    pub outputs: Vec<String>,
+    pub mode: UsbMode,
+}
+
+/// Payload of the "midi-message" event, and the message `send_test_message`
+/// sends. Channels are 0-15, like the config.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MidiMessage {
+    Cc { channel: u8, cc: u8, value: u8 },
+    Pc { channel: u8, program: u8 },
+    /// Note On, or Note Off (`on: false`). Note On with velocity 0 counts as off.
+    Note { channel: u8, note: u8, velocity: u8, on: bool },
+}
+
+/// Where to broadcast config changes
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MidiBroadcastSettings {
+    /// Output port name; no broadcast when unset
+    pub port: Option<String>,
+}
+
+fn load_settings(data_dir: &Path) -> MidiBroadcastSettings {
+    fs::read_to_string(data_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings(data_dir: &Path, settings: &MidiBroadcastSettings) -> Result<(), ConfigError> {
+    fs::write(data_dir.join(SETTINGS_FILE), serde_json::to_string_pretty(settings)?)?;
+    Ok(())
+}
+
+/// Build the "config changed" SysEx for a config with the given hash
+pub fn config_changed_sysex(hash: u32, device: &str) -> Vec<u8> {
+    let mut msg = SYSEX_HEADER.to_vec();
+    msg.push(MSG_CONFIG_CHANGED);
+    msg.push((CONFIG_SCHEMA_VERSION & 0x7F) as u8);
+    msg.extend((0..5).rev().map(|i| ((hash >> (7 * i)) & 0x7F) as u8));
+    msg.extend(device.bytes().filter(|b| b.is_ascii() && !b.is_ascii_control()));
+    msg.push(0xF7);
+    msg
+}
+
+/// Names of the host's MIDI output ports
+pub fn output_ports() -> Result<Vec<String>, String> {
+    let output = MidiOutput::new(CLIENT_NAME).map_err(|e| e.to_string())?;
+    Ok(output.ports().iter().filter_map(|p| output.port_name(p).ok()).collect())
+}
+
+/// Names of the host's MIDI input ports
+pub fn input_ports() -> Result<Vec<String>, String> {
+    let input = MidiInput::new(CLIENT_NAME).map_err(|e| e.to_string())?;
+    Ok(input.ports().iter().filter_map(|p| input.port_name(p).ok()).collect())
+}
+
+/// True if a MIDI port name looks like one of the device's
+pub fn is_device_port(name: &str) -> bool {
+    let name = name.to_lowercase();
+    DEVICE_PORT_NAMES.iter().any(|n| name.contains(n))
+}
+
+impl MidiMessage {
+    /// Encode as raw bytes, rejecting values that don't fit in MIDI
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let (status, channel, data) = match *self {
+            MidiMessage::Cc { channel, cc, value } => (0xB0, channel, vec![cc, value]),
+            MidiMessage::Pc { channel, program } => (0xC0, channel, vec![program]),
+            MidiMessage::Note { channel, note, velocity, on } => {
+                (if on { 0x90 } else { 0x80 }, channel, vec![note, velocity])
+            }
+        };
+        if channel > 15 {
+            return Err(format!("Channel {} is invalid (must be 1-16)", u16::from(channel) + 1));
+        }
+        if let Some(value) = data.iter().find(|b| **b > 127) {
+            return Err(format!("Value {} exceeds 127", value));
+        }
+        let mut bytes = vec![status | channel];
+        bytes.extend(data);
+        Ok(bytes)
+    }
+}
+
+/// Decode a channel message; anything the monitor doesn't show is `None`
+pub fn parse_midi_message(bytes: &[u8]) -> Option<MidiMessage> {
+    let (&status, data) = bytes.split_first()?;
+    let channel = status & 0x0F;
+    match (status & 0xF0, data) {
+        (0xB0, &[cc, value, ..]) => Some(MidiMessage::Cc { channel, cc, value }),
+        (0xC0, &[program, ..]) => Some(MidiMessage::Pc { channel, program }),
+        (0x90, &[note, velocity, ..]) => Some(MidiMessage::Note { channel, note, velocity, on: velocity > 0 }),
+        (0x80, &[note, velocity, ..]) => Some(MidiMessage::Note { channel, note, velocity, on: false }),
+        _ => None,
+    }
+}
+
+fn usb_mode(has_midi: bool, has_drive: bool) -> UsbMode {
+    match (has_midi, has_drive) {
+        (false, false) => UsbMode::Disconnected,
+        (true, false) => UsbMode::MidiOnly,
+        (false, true) => UsbMode::DriveOnly,
+        (true, true) => UsbMode::MidiAndDrive,
+    }
+}
+
+/// Send one message to the output port named `port`
+pub fn send(port: &str, message: &[u8]) -> Result<(), String> {
+    let output = MidiOutput::new(CLIENT_NAME).map_err(|e| e.to_string())?;
+    let target = output
+        .ports()
+        .into_iter()
+        .find(|p| output.port_name(p).is_ok_and(|name| name == port))
+        .ok_or_else(|| format!("MIDI output '{}' not found", port))?;
+    let mut conn = output.connect(&target, CLIENT_NAME).map_err(|e| e.to_string())?;
+    conn.send(message).map_err(|e| e.to_string())
+}
+
+/// Broadcast that `written` is now the config at `path`, if a port is set.
+/// Runs on its own thread so a slow MIDI driver can't hold up the write; a
+/// failed broadcast is dropped, since the write itself has succeeded.
+pub fn broadcast_config_changed(path: &Path, written: &[u8]) {
+    let Some(dir) = crate::registry::data_dir() else { return };
+    let Some(port) = load_settings(dir).port else { return };
+    let hash = u32::from_str_radix(&sha256_hex(written)[..8], 16).unwrap_or(0);
+    let device = crate::registry::device_key(path).unwrap_or_default();
+    std::thread::spawn(move || {
+        let _ = send(&port, &config_changed_sysex(hash, &device));
+    });
+}
+
+/// List the host's MIDI output ports
+#[command]
+pub fn list_midi_outputs() -> Result<Vec<String>, String> {
+    output_ports()
+}
+
+/// Find the device's USB MIDI ports and report whether it is attached as
+/// MIDI, as a drive, or both
+#[command]
+pub fn scan_midi_ports() -> Result<MidiPortScan, String> {
+    let device_ports = |ports: Vec<String>| ports.into_iter().filter(|p| is_device_port(p)).collect::<Vec<_>>();
+    let inputs = device_ports(input_ports()?);
+    let outputs = device_ports(output_ports()?);
+    let has_drive = !crate::device::scan_devices().is_empty();
+    let mode = usb_mode(!inputs.is_empty() || !outputs.is_empty(), has_drive);
+    Ok(MidiPortScan { inputs, outputs, mode })
+}
+
+/// Listen on the input port named `port` until `shutdown` fires, emitting
+/// "midi-message" for every message `parse_midi_message` accepts
+fn run_midi_monitor(app: AppHandle, port: &str, shutdown: mpsc::Receiver<()>) -> Result<(), String> {
+    let mut input = MidiInput::new(CLIENT_NAME).map_err(|e| e.to_string())?;
+    input.ignore(Ignore::All);
+    let target = input
+        .ports()
+        .into_iter()
+        .find(|p| input.port_name(p).is_ok_and(|name| name == port))
+        .ok_or_else(|| format!("MIDI input '{}' not found", port))?;
+    let emitter = app.clone();
+    let _conn = input
+        .connect(
+            &target,
+            CLIENT_NAME,
+            move |_, bytes, _| {
+                if let Some(message) = parse_midi_message(bytes) {
+                    let _ = emitter.emit("midi-message", message);
+                }
+            },
+            (),
+        )
+        .map_err(|e| e.to_string())?;
+    // The connection closes when dropped; hold it until told to stop
+    let _ = shutdown.recv();
+    Ok(())
+}
+
+/// Stream the device's outgoing CC/PC/note messages as "midi-message" events.
+/// Listens on `port`, or on the first of the device's input ports when unset.
+/// Returns the port being monitored.
+#[command]
+pub fn start_midi_monitor(app: AppHandle, port: Option<String>) -> Result<String, String> {
+    let port_name = match port {
+        Some(port) => port,
+        None => input_ports()?
+            .into_iter()
+            .find(|p| is_device_port(p))
+            .ok_or_else(|| "No MIDI Captain MIDI port found".to_string())?,
+    };
+    let (tx, rx) = mpsc::channel();
+    if let Ok(mut guard) = MIDI_MONITOR_TX.lock() {
+        if let Some(old) = guard.replace(tx) {
+            let _ = old.send(());
+        }
+    }
+
+    let watched = port_name.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = run_midi_monitor(app.clone(), &watched, rx) {
+            let _ = app.emit("monitor-error", e);
+        }
+    });
+    Ok(port_name)
+}
+
+/// Stop the MIDI monitor
+#[command]
+pub fn stop_midi_monitor() -> Result<(), String> {
+    if let Ok(mut guard) = MIDI_MONITOR_TX.lock() {
+        if let Some(tx) = guard.take() {
+            let _ = tx.send(());
+        }
+    }
+    Ok(())
+}
+
+/// Send one CC, PC or note to the host output `port`, to check what a
+/// mapping does before writing the config
+#[command]
+pub fn send_test_message(port: String, message: MidiMessage) -> Result<(), String> {
+    send(&port, &message.to_bytes()?)
+}
+
+/// The config-change broadcast settings
+#[command]
+pub fn get_midi_broadcast(app: AppHandle) -> Result<MidiBroadcastSettings, ConfigError> {
+    Ok(load_settings(&app_data_dir(&app)?))
+}
+
+/// Choose the port config changes are broadcast on, or turn broadcasting off
+#[command]
+pub fn set_midi_broadcast(app: AppHandle, settings: MidiBroadcastSettings) -> Result<(), ConfigError> {
+    save_settings(&app_data_dir(&app)?, &settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_changed_sysex_layout() {
+        let msg = config_changed_sysex(0xDEAD_BEEF, "MIDICAPTAIN");
+        assert_eq!(&msg[..6], &[0xF0, 0x7D, 0x4D, 0x43, 0x01, CONFIG_SCHEMA_VERSION as u8]);
+        let hash = msg[6..11].iter().fold(0u64, |acc, b| (acc << 7) | u64::from(*b));
+        assert_eq!(hash, 0xDEAD_BEEF);
+        assert_eq!(&msg[11..msg.len() - 1], b"MIDICAPTAIN");
+        assert_eq!(msg.last(), Some(&0xF7));
+        // Every data byte must be 7-bit
+        assert!(msg[1..msg.len() - 1].iter().all(|b| *b < 0x80));
+    }
+
+    #[test]
+    fn test_device_port_names() {
+        assert!(is_device_port("CircuitPython usb_midi.ports[0]"));
+        assert!(is_device_port("MIDI Captain MIDI 1"));
+        assert!(!is_device_port("IAC Driver Bus 1"));
+    }
+
+    #[test]
+    fn test_parse_midi_message() {
+        assert_eq!(parse_midi_message(&[0xB3, 20, 127]), Some(MidiMessage::Cc { channel: 3, cc: 20, value: 127 }));
+        assert_eq!(parse_midi_message(&[0xC0, 5]), Some(MidiMessage::Pc { channel: 0, program: 5 }));
+        assert_eq!(
+            parse_midi_message(&[0x9F, 60, 0]),
+            Some(MidiMessage::Note { channel: 15, note: 60, velocity: 0, on: false })
+        );
+        assert_eq!(
+            parse_midi_message(&[0x80, 60, 64]),
+            Some(MidiMessage::Note { channel: 0, note: 60, velocity: 64, on: false })
+        );
+        // Pitch bend and truncated messages aren't shown
+        assert_eq!(parse_midi_message(&[0xE0, 0, 64]), None);
+        assert_eq!(parse_midi_message(&[0xB0, 20]), None);
+        assert_eq!(parse_midi_message(&[]), None);
+    }
+
+    #[test]
+    fn test_test_message_bytes() {
+        let cc = MidiMessage::Cc { channel: 3, cc: 20, value: 127 };
+        assert_eq!(cc.to_bytes().unwrap(), vec![0xB3, 20, 127]);
+        assert_eq!(parse_midi_message(&cc.to_bytes().unwrap()), Some(cc));
+        assert_eq!(MidiMessage::Pc { channel: 0, program: 5 }.to_bytes().unwrap(), vec![0xC0, 5]);
+        assert!(MidiMessage::Pc { channel: 16, program: 5 }.to_bytes().is_err());
+        assert!(MidiMessage::Cc { channel: 0, cc: 128, value: 0 }.to_bytes().is_err());
+    }
+
+    #[test]
+    fn test_usb_mode() {
+        assert_eq!(usb_mode(true, false), UsbMode::MidiOnly);
+        assert_eq!(usb_mode(false, true), UsbMode::DriveOnly);
+        assert_eq!(usb_mode(true, true), UsbMode::MidiAndDrive);
+        assert_eq!(usb_mode(false, false), UsbMode::Disconnected);
+    }
+
+    #[test]
+    fn test_settings_default_to_off() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load_settings(dir.path()).port, None);
+    }
+}
@@ -0,0 +1,307 @@
+//! Printable MIDI implementation chart
+//!
+//! `export_midi_chart` lists what every footswitch, the encoder and the
+//! expression pedals send — message, number, values, channel — as a cheat
+//! sheet to tape to the pedalboard. Defaults are filled in as the firmware
+//! does (see explain.rs); disabled controls are left out.
+
+use crate::commands::ConfigError;
+use crate::config::{ButtonMode, MacroStepType, MessageType, MidiCaptainConfig, Polarity, SecondaryType, TapOutput};
+use crate::setlist::escape_html;
+use std::fmt::Write;
+use tauri::command;
+
+/// Output format of a MIDI chart
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartFormat {
+    Markdown,
+    /// For spreadsheets
+    Csv,
+    /// Standalone HTML page, for printing from a browser
+    Html,
+}
+
+/// One control in the chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartRow {
+    /// e.g. "Switch 3", "Encoder", "Exp 1"
+    pub control: String,
+    pub label: String,
+    /// "CC", "Note", "PC", "PC +", "PC -" or "Panic"
+    pub message: String,
+    /// CC, note or program number
+    pub number: Option<u8>,
+    /// Values sent, e.g. "127 / 0" or "0-127"
+    pub values: String,
+    /// 1-16
+    pub channel: u16,
+    /// "toggle", "momentary", "press", "turn" or "sweep"
+    pub action: String,
+}
+
+const HEADERS: [&str; 7] = ["Control", "Label", "Message", "Number", "Values", "Channel", "Action"];
+
+impl ChartRow {
+    fn cells(&self) -> [String; 7] {
+        [
+            self.control.clone(),
+            self.label.clone(),
+            self.message.clone(),
+            self.number.map(|n| n.to_string()).unwrap_or_default(),
+            self.values.clone(),
+            self.channel.to_string(),
+            self.action.clone(),
+        ]
+    }
+}
+
+fn mode_name(mode: &ButtonMode) -> &'static str {
+    match mode {
+        ButtonMode::Toggle => "toggle",
+        ButtonMode::Momentary => "momentary",
+        ButtonMode::TapTempo => "tap tempo",
+    }
+}
+
+/// The chart rows for a config, in device order
+pub fn chart_rows(config: &MidiCaptainConfig) -> Vec<ChartRow> {
+    let global = config.global_channel.unwrap_or(0);
+    let channel = |ch: Option<u8>| u16::from(ch.unwrap_or(global)) + 1;
+    let mut rows = Vec::new();
+
+    for (i, button) in config.buttons.iter().enumerate() {
+        let (message, number, values, action) = match button.message_type {
+            MessageType::Cc if button.mode == ButtonMode::TapTempo => {
+                let tap = button.tap_tempo.clone().unwrap_or_default();
+                let (min, max) = tap.bpm_range();
+                let range = format!("{}-{} BPM", min, max);
+                match tap.output {
+                    TapOutput::Cc => ("CC", Some(button.cc.unwrap_or((20 + i).min(127) as u8)), range, "tap tempo"),
+                    TapOutput::Clock => ("Clock", None, range, "tap tempo"),
+                }
+            }
+            MessageType::Cc => (
+                "CC",
+                Some(button.cc.unwrap_or((20 + i).min(127) as u8)),
+                format!("{} / {}", button.cc_on.unwrap_or(127), button.cc_off.unwrap_or(0)),
+                mode_name(&button.mode),
+            ),
+            MessageType::Note => (
+                "Note",
+                Some(button.note.unwrap_or(60)),
+                format!("vel {} / {}", button.velocity_on.unwrap_or(127), button.velocity_off.unwrap_or(0)),
+                mode_name(&button.mode),
+            ),
+            MessageType::Pc => ("PC", Some(button.program.unwrap_or(0)), String::new(), "press"),
+            MessageType::PcInc => ("PC +", None, format!("step {}", button.pc_step.unwrap_or(1)), "press"),
+            MessageType::PcDec => ("PC -", None, format!("step {}", button.pc_step.unwrap_or(1)), "press"),
+            MessageType::Panic => ("Panic", None, "all channels".to_string(), "press"),
+            MessageType::Macro => ("Macro", None, format!("{} steps", button.steps.as_ref().map_or(0, Vec::len)), "press"),
+        };
+        rows.push(ChartRow {
+            control: format!("Switch {}", i + 1),
+            label: button.label.clone(),
+            message: message.to_string(),
+            number,
+            values,
+            channel: channel(button.channel),
+            action: action.to_string(),
+        });
+        for (n, step) in button.steps.iter().flatten().enumerate().filter(|_| button.message_type == MessageType::Macro) {
+            let (message, number, values) = match step.message_type {
+                MacroStepType::Cc => ("CC", step.cc, step.value.unwrap_or(127).to_string()),
+                MacroStepType::Pc => ("PC", step.program, String::new()),
+                MacroStepType::Note => ("Note", step.note, format!("vel {}", step.velocity.unwrap_or(127))),
+            };
+            rows.push(ChartRow {
+                control: format!("Switch {}", i + 1),
+                label: button.label.clone(),
+                message: message.to_string(),
+                number,
+                values,
+                channel: channel(step.channel.or(button.channel)),
+                action: match step.delay_ms.filter(|ms| *ms > 0) {
+                    Some(ms) => format!("step {} (+{} ms)", n + 1, ms),
+                    None => format!("step {}", n + 1),
+                },
+            });
+        }
+        for (gesture, secondary) in [("long press", &button.long_press), ("double tap", &button.double_tap)] {
+            let Some(secondary) = secondary else { continue };
+            let (message, number, values) = match secondary.message_type {
+                SecondaryType::Cc => ("CC", secondary.cc, secondary.value.unwrap_or(127).to_string()),
+                SecondaryType::Pc => ("PC", secondary.program, String::new()),
+            };
+            rows.push(ChartRow {
+                control: format!("Switch {}", i + 1),
+                label: button.label.clone(),
+                message: message.to_string(),
+                number,
+                values,
+                channel: channel(secondary.channel.or(button.channel)),
+                action: gesture.to_string(),
+            });
+        }
+    }
+
+    if let Some(encoder) = config.encoder.as_ref().filter(|e| e.enabled) {
+        rows.push(ChartRow {
+            control: "Encoder".to_string(),
+            label: encoder.label.clone(),
+            message: "CC".to_string(),
+            number: Some(encoder.cc),
+            values: format!("{}-{}", encoder.min, encoder.max),
+            channel: channel(encoder.channel),
+            action: "turn".to_string(),
+        });
+        if let Some(push) = encoder.push.as_ref().filter(|p| p.enabled) {
+            rows.push(ChartRow {
+                control: "Encoder push".to_string(),
+                label: push.label.clone(),
+                message: "CC".to_string(),
+                number: Some(push.cc),
+                values: format!("{} / {}", push.cc_on.unwrap_or(127), push.cc_off.unwrap_or(0)),
+                channel: channel(push.channel),
+                action: mode_name(&push.mode).to_string(),
+            });
+        }
+    }
+
+    if let Some(pedals) = &config.expression {
+        for (i, pedal) in [&pedals.exp1, &pedals.exp2].into_iter().enumerate() {
+            if !pedal.enabled {
+                continue;
+            }
+            let (heel, toe) = match pedal.polarity {
+                Polarity::Normal => (pedal.min, pedal.max),
+                Polarity::Inverted => (pedal.max, pedal.min),
+            };
+            rows.push(ChartRow {
+                control: format!("Exp {}", i + 1),
+                label: pedal.label.clone(),
+                message: "CC".to_string(),
+                number: Some(pedal.cc),
+                values: format!("{}-{} (heel-toe)", heel, toe),
+                channel: channel(pedal.channel),
+                action: "sweep".to_string(),
+            });
+        }
+    }
+    rows
+}
+
+fn render_markdown(rows: &[ChartRow]) -> String {
+    let mut out = String::from("# MIDI chart\n\n");
+    let _ = writeln!(out, "| {} |", HEADERS.join(" | "));
+    let _ = writeln!(out, "|{}", "---|".repeat(HEADERS.len()));
+    for row in rows {
+        let cells = row.cells().map(|c| c.replace('|', "\\|"));
+        let _ = writeln!(out, "| {} |", cells.join(" | "));
+    }
+    out
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn render_csv(rows: &[ChartRow]) -> String {
+    let mut out = HEADERS.join(",");
+    out.push('\n');
+    for row in rows {
+        out.push_str(&row.cells().map(|c| csv_field(&c)).join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn render_html(rows: &[ChartRow]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>MIDI chart</title>\n\
+         <style>body{font-family:sans-serif;font-size:14pt}table{border-collapse:collapse}\
+         th,td{border:1px solid #888;padding:.3em .6em;text-align:left}</style>\n</head>\n<body>\n<h1>MIDI chart</h1>\n<table>\n",
+    );
+    let _ = writeln!(out, "<tr><th>{}</th></tr>", HEADERS.join("</th><th>"));
+    for row in rows {
+        let cells = row.cells().map(|c| escape_html(&c));
+        let _ = writeln!(out, "<tr><td>{}</td></tr>", cells.join("</td><td>"));
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
+
+/// Render chart rows in `format`
+pub fn render_chart(rows: &[ChartRow], format: ChartFormat) -> String {
+    match format {
+        ChartFormat::Markdown => render_markdown(rows),
+        ChartFormat::Csv => render_csv(rows),
+        ChartFormat::Html => render_html(rows),
+    }
+}
+
+/// Render the config's MIDI assignments as a printable chart. Returns the
+/// chart for the frontend to save or print.
+#[command]
+pub fn export_midi_chart(config: MidiCaptainConfig, format: ChartFormat) -> Result<String, ConfigError> {
+    Ok(render_chart(&chart_rows(&config), format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{generate_default_config, DeviceType};
+
+    fn config() -> MidiCaptainConfig {
+        let mut config = generate_default_config(DeviceType::Std10);
+        config.global_channel = Some(1);
+        config.buttons[0].label = "OD, boost".to_string();
+        config.buttons[0].cc = Some(64);
+        config.buttons[1].message_type = MessageType::Pc;
+        config.buttons[1].program = Some(5);
+        config.buttons[2].message_type = MessageType::PcDec;
+        config.buttons[2].channel = Some(9);
+        if let Some(pedals) = config.expression.as_mut() {
+            pedals.exp2.enabled = false;
+            pedals.exp1.polarity = Polarity::Inverted;
+        }
+        config
+    }
+
+    #[test]
+    fn test_rows_fill_in_firmware_defaults() {
+        let rows = chart_rows(&config());
+        // 10 switches, encoder, push and one enabled pedal
+        assert_eq!(rows.len(), 13);
+        assert_eq!((rows[0].message.as_str(), rows[0].number, rows[0].channel), ("CC", Some(64), 2));
+        assert_eq!((rows[1].message.as_str(), rows[1].number), ("PC", Some(5)));
+        assert_eq!((rows[2].message.as_str(), rows[2].channel), ("PC -", 10));
+        assert_eq!((rows[10].control.as_str(), rows[10].number), ("Encoder", Some(11)));
+        assert_eq!(rows[11].action, "momentary");
+        assert_eq!((rows[12].control.as_str(), rows[12].values.as_str()), ("Exp 1", "127-0 (heel-toe)"));
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let chart = render_chart(&chart_rows(&config()), ChartFormat::Csv);
+        assert!(chart.starts_with("Control,Label,Message,Number,Values,Channel,Action\n"));
+        assert!(chart.contains("Switch 1,\"OD, boost\",CC,64,127 / 0,2,toggle\n"));
+    }
+
+    #[test]
+    fn test_markdown_and_html_tables() {
+        let rows = chart_rows(&config());
+        let markdown = render_chart(&rows, ChartFormat::Markdown);
+        assert_eq!(markdown.lines().filter(|l| l.starts_with('|')).count(), rows.len() + 2);
+
+        let mut rows = rows;
+        rows[0].label = "<b>".to_string();
+        let html = render_chart(&rows, ChartFormat::Html);
+        assert!(html.contains("<td>&lt;b&gt;</td>"));
+        assert_eq!(html.matches("<tr>").count(), rows.len() + 1);
+    }
+}
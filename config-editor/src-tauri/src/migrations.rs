@@ -0,0 +1,216 @@
+//! Config schema migrations
+//!
+//! Configs carry the schema version they were written for in `version`
+//! (`CONFIG_SCHEMA_VERSION`, stamped on every write). Configs from before
+//! versioning, including every config written by hand or for older
+//! firmware, have none and count as version 0.
+//!
+//! `migrate` runs on the raw JSON before it is deserialized: each migration
+//! whose `from` is at or above the config's version runs in order, then the
+//! config is stamped with the current version. Versions whose bump only
+//! added optional fields or values (2: panic buttons, 3: routing, 4: long-press
+//! and double-tap actions, 5: `#RRGGBB` colors, 6: global settings, 7: encoder
+//! acceleration and wrap, 8: expression pedal calibration, 9: expression
+//! response curves) need no migration.
+//!
+//! When a schema change renames, removes or changes the meaning of a field,
+//! bump `CONFIG_SCHEMA_VERSION` and add a migration from the old version.
+
+use crate::commands::ConfigError;
+use crate::config::{parse_hex_color, DeviceType, CONFIG_SCHEMA_VERSION};
+use serde_json::{Map, Value};
+
+/// Palette colors the editor knows (`NamedColor`)
+const COLORS: &[&str] = &["red", "green", "blue", "yellow", "cyan", "magenta", "orange", "purple", "white"];
+
+/// Button types the editor knows (`MessageType`)
+const TYPES: &[&str] = &["cc", "note", "pc", "pc_inc", "pc_dec", "panic"];
+
+/// One upgrade step
+struct Migration {
+    /// Configs at this version or older get this migration
+    from: u32,
+    name: &'static str,
+    /// Change the config in place; returns what was changed
+    apply: fn(&mut Map<String, Value>) -> Vec<String>,
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    name: "firmware_defaults",
+    apply: firmware_defaults,
+}];
+
+/// A migration that changed a config
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct AppliedMigration {
+    pub name: String,
+    /// What was changed, e.g. "buttons[3].color: \"Pink\" -> \"white\""
+    pub changes: Vec<String>,
+}
+
+/// What `migrate` did to a config
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct MigrationReport {
+    /// Version the config was written for (0 = unversioned)
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Migrations that changed something; empty when the config was current
+    pub applied: Vec<AppliedMigration>,
+}
+
+/// Schema version of a raw config (0 when unversioned)
+pub fn config_version(value: &Value) -> u32 {
+    value.get("version").and_then(Value::as_u64).map_or(0, |v| v as u32)
+}
+
+/// Upgrade a raw config to `CONFIG_SCHEMA_VERSION` in place. Fails for a
+/// config written by a newer editor, which may hold fields this one would
+/// drop on save.
+pub fn migrate(value: &mut Value) -> Result<MigrationReport, ConfigError> {
+    let from_version = config_version(value);
+    if from_version > CONFIG_SCHEMA_VERSION {
+        return Err(ConfigError {
+            message: format!(
+                "Config is schema version {}; this editor reads up to version {}. Update the editor to open it.",
+                from_version, CONFIG_SCHEMA_VERSION
+            ),
+            details: None,
+            code: None,
+        });
+    }
+    let Some(obj) = value.as_object_mut() else {
+        return Err(ConfigError {
+            message: "Config is not a JSON object".to_string(),
+            details: None,
+            code: None,
+        });
+    };
+    let applied = MIGRATIONS
+        .iter()
+        .filter(|m| m.from >= from_version)
+        .filter_map(|m| {
+            let changes = (m.apply)(obj);
+            (!changes.is_empty()).then(|| AppliedMigration {
+                name: m.name.to_string(),
+                changes,
+            })
+        })
+        .collect();
+    obj.insert("version".to_string(), CONFIG_SCHEMA_VERSION.into());
+    Ok(MigrationReport {
+        from_version,
+        to_version: CONFIG_SCHEMA_VERSION,
+        applied,
+    })
+}
+
+/// Version 0 -> 1: apply the fallbacks the firmware applies on load but the
+/// editor's schema doesn't, so a config the device runs also opens in the
+/// editor: missing buttons added (and extras the firmware ignores dropped),
+/// missing labels and colors filled in, color names lowercased and unknown
+/// colors and types replaced the way the firmware treats them, an invalid
+/// global channel removed.
+fn firmware_defaults(obj: &mut Map<String, Value>) -> Vec<String> {
+    let mut changes = Vec::new();
+    let device: DeviceType = obj
+        .get("device")
+        .and_then(|d| serde_json::from_value(d.clone()).ok())
+        .unwrap_or_default();
+    let count = device.button_count();
+
+    if obj.get("global_channel").is_some_and(|c| c.as_u64().is_none_or(|c| c > 15)) {
+        let channel = obj.remove("global_channel").unwrap_or_default();
+        changes.push(format!("global_channel: {} removed (the firmware uses channel 1)", channel));
+    }
+
+    let buttons = obj.entry("buttons").or_insert_with(|| Value::Array(Vec::new()));
+    let Some(buttons) = buttons.as_array_mut() else {
+        return changes;
+    };
+    if buttons.len() > count {
+        changes.push(format!("buttons: {} extra button(s) removed (the firmware ignores them)", buttons.len() - count));
+        buttons.truncate(count);
+    }
+    if buttons.len() < count {
+        changes.push(format!("buttons: {} missing button(s) added", count - buttons.len()));
+        buttons.resize_with(count, || Value::Object(Map::new()));
+    }
+
+    for (i, button) in buttons.iter_mut().enumerate() {
+        let Some(button) = button.as_object_mut() else { continue };
+        if !button.get("label").is_some_and(Value::is_string) {
+            let label = (i + 1).to_string();
+            changes.push(format!("buttons[{}].label: set to \"{}\"", i, label));
+            button.insert("label".to_string(), label.into());
+        }
+        let color = button.get("color").and_then(Value::as_str).map(str::to_string);
+        let fixed = match color.as_deref() {
+            Some(c) if COLORS.contains(&c) || parse_hex_color(c).is_some() => None,
+            Some(c) if COLORS.contains(&c.to_lowercase().as_str()) => Some(c.to_lowercase()),
+            _ => Some("white".to_string()),
+        };
+        if let Some(fixed) = fixed {
+            match color {
+                Some(c) => changes.push(format!("buttons[{}].color: \"{}\" -> \"{}\"", i, c, fixed)),
+                None => changes.push(format!("buttons[{}].color: set to \"{}\"", i, fixed)),
+            }
+            button.insert("color".to_string(), fixed.into());
+        }
+        if let Some(kind) = button.get("type").filter(|t| !t.as_str().is_some_and(|t| TYPES.contains(&t))) {
+            changes.push(format!("buttons[{}].type: {} -> \"cc\"", i, kind));
+            button.insert("type".to_string(), "cc".into());
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MidiCaptainConfig;
+    use serde_json::json;
+
+    #[test]
+    fn test_firmware_era_config_migrates() {
+        // Shape of firmware/dev/experiments/config.json, plus the fallbacks
+        // the firmware applies silently
+        let mut value = json!({
+            "device": "mini6",
+            "global_channel": 20,
+            "buttons": [
+                {"label": "TSC", "cc": 20, "color": "Green"},
+                {"cc": 21, "color": "pink", "type": "sysex"},
+            ]
+        });
+        let report = migrate(&mut value).unwrap();
+        assert_eq!(report.from_version, 0);
+        assert_eq!(report.to_version, CONFIG_SCHEMA_VERSION);
+        assert_eq!(report.applied.len(), 1);
+        let changes = &report.applied[0].changes;
+        assert!(changes.contains(&"buttons[0].color: \"Green\" -> \"green\"".to_string()));
+        assert!(changes.contains(&"buttons[1].label: set to \"2\"".to_string()));
+        assert!(changes.contains(&"buttons[1].type: \"sysex\" -> \"cc\"".to_string()));
+
+        let config: MidiCaptainConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(config.version, Some(CONFIG_SCHEMA_VERSION));
+        assert_eq!(config.buttons.len(), 6);
+        assert_eq!(config.global_channel, None);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_current_config_is_left_alone() {
+        let mut value = serde_json::to_value(&crate::config::generate_default_config(DeviceType::Std10)).unwrap();
+        let before = value.clone();
+        let report = migrate(&mut value).unwrap();
+        assert!(report.applied.is_empty());
+        assert_eq!(value, before);
+    }
+
+    #[test]
+    fn test_newer_config_is_refused() {
+        let mut value = json!({"version": CONFIG_SCHEMA_VERSION + 1, "buttons": []});
+        assert!(migrate(&mut value).unwrap_err().message.contains("Update the editor"));
+    }
+}
@@ -0,0 +1,325 @@
+//! Live hardware monitors streamed from the device's serial console
+//!
+//! The firmware prints readings on request (see firmware/dev/core/live.py);
+//! a background thread parses them and forwards each one to the frontend as
+//! an event, so users can watch pedal behavior while tuning the config.
+//! Expression calibration rides on the pedal monitor, recording the raw
+//! extremes it sees until the user finishes and they are written to the
+//! config.
+
+use crate::commands::{self, ConfigError};
+use crate::config::Calibration;
+use crate::serial::{open_port, resolve_port, send_live_command};
+use serde::Serialize;
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Emitter};
+
+/// Shutdown signal for the running expression monitor
+static EXP_MONITOR_TX: Mutex<Option<Sender<()>>> = Mutex::new(None);
+
+/// Shutdown signal for the running encoder monitor
+static ENC_MONITOR_TX: Mutex<Option<Sender<()>>> = Mutex::new(None);
+
+/// Raw extremes seen by the running calibration, per pedal (exp1, exp2)
+static CALIBRATION: Mutex<[Option<Calibration>; 2]> = Mutex::new([None, None]);
+
+/// Smallest raw span accepted as a calibration; less means the pedal was
+/// not swept (or is not plugged in)
+pub const MIN_CALIBRATION_SPAN: u16 = 1024;
+
+/// One expression pedal reading
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PedalReading {
+    /// ADC value (0-65535)
+    pub raw: u16,
+    /// Value after calibration, polarity and min/max mapping (0-127)
+    pub mapped: u8,
+    /// Last value actually sent, after the threshold (hysteresis) filter
+    pub sent: u8,
+}
+
+/// Payload of the "exp-monitor" event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExpSample {
+    pub exp1: PedalReading,
+    pub exp2: PedalReading,
+}
+
+/// Parse an `@exp raw1 mapped1 sent1 raw2 mapped2 sent2` line
+pub fn parse_exp_line(line: &str) -> Option<ExpSample> {
+    let rest = line.trim().strip_prefix("@exp ")?;
+    let values: Vec<u32> = rest.split_whitespace().map(|v| v.parse().ok()).collect::<Option<_>>()?;
+    if values.len() != 6 {
+        return None;
+    }
+    let reading = |i: usize| -> Option<PedalReading> {
+        Some(PedalReading {
+            raw: u16::try_from(values[i]).ok()?,
+            mapped: u8::try_from(values[i + 1]).ok()?,
+            sent: u8::try_from(values[i + 2]).ok()?,
+        })
+    };
+    Some(ExpSample {
+        exp1: reading(0)?,
+        exp2: reading(3)?,
+    })
+}
+
+/// `range` widened to include `raw`
+fn widen(range: Option<Calibration>, raw: u16) -> Calibration {
+    match range {
+        Some(c) => Calibration { raw_min: c.raw_min.min(raw), raw_max: c.raw_max.max(raw) },
+        None => Calibration { raw_min: raw, raw_max: raw },
+    }
+}
+
+/// Parse an `@exp` line and fold its raw values into the calibration
+fn record_exp_line(line: &str) -> Option<ExpSample> {
+    let sample = parse_exp_line(line)?;
+    if let Ok(mut ranges) = CALIBRATION.lock() {
+        ranges[0] = Some(widen(ranges[0], sample.exp1.raw));
+        ranges[1] = Some(widen(ranges[1], sample.exp2.raw));
+    }
+    Some(sample)
+}
+
+/// Payload of the "encoder-monitor" event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EncoderSample {
+    /// Raw rotary position (detents since boot)
+    pub position: i32,
+    /// Internal value (0-127)
+    pub value: u8,
+    /// Current slot in stepped mode; `None` before the first change or when not stepped
+    pub slot: Option<u8>,
+    /// Number of slots, when stepped
+    pub steps: Option<u8>,
+}
+
+/// Parse an `@enc position value slot steps` line
+pub fn parse_enc_line(line: &str) -> Option<EncoderSample> {
+    let rest = line.trim().strip_prefix("@enc ")?;
+    let values: Vec<i32> = rest.split_whitespace().map(|v| v.parse().ok()).collect::<Option<_>>()?;
+    let [position, value, slot, steps] = values[..] else { return None };
+    Some(EncoderSample {
+        position,
+        value: u8::try_from(value).ok()?,
+        slot: u8::try_from(slot).ok(),
+        steps: u8::try_from(steps).ok().filter(|s| *s > 1),
+    })
+}
+
+/// Run a monitor on `port_name` until `shutdown` fires or the port fails.
+///
+/// Turns the firmware switch `param` on, emits `event` for every line `parse`
+/// accepts, and turns the switch off again on the way out.
+fn run_monitor<T: Serialize + Clone>(
+    app: &AppHandle,
+    port_name: &str,
+    param: &str,
+    event: &str,
+    parse: fn(&str) -> Option<T>,
+    shutdown: Receiver<()>,
+) -> io::Result<()> {
+    let mut serial = open_port(port_name).map_err(io::Error::other)?;
+    send_live_command(serial.as_mut(), param, &serde_json::Value::Bool(true))?;
+
+    let mut pending = String::new();
+    let mut buf = [0u8; 1024];
+    let result = loop {
+        match shutdown.try_recv() {
+            Err(TryRecvError::Empty) => {}
+            _ => break Ok(()),
+        }
+        match serial.read(&mut buf) {
+            Ok(n) => {
+                pending.push_str(&String::from_utf8_lossy(&buf[..n]));
+                while let Some(pos) = pending.find('\n') {
+                    let line: String = pending.drain(..=pos).collect();
+                    if let Some(sample) = parse(&line) {
+                        let _ = app.emit(event, sample);
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+            Err(e) => break Err(e),
+        }
+    };
+
+    let _ = send_live_command(serial.as_mut(), param, &serde_json::Value::Bool(false));
+    result
+}
+
+/// Replace the monitor stored in `slot` with a new one, stopping the old one
+fn start_monitor<T: Serialize + Clone + 'static>(
+    slot: &'static Mutex<Option<Sender<()>>>,
+    app: AppHandle,
+    port: Option<String>,
+    param: &'static str,
+    event: &'static str,
+    parse: fn(&str) -> Option<T>,
+) -> Result<String, String> {
+    let port_name = resolve_port(port)?;
+    let (tx, rx) = mpsc::channel();
+    if let Ok(mut guard) = slot.lock() {
+        if let Some(old) = guard.replace(tx) {
+            let _ = old.send(());
+        }
+    }
+
+    let watched = port_name.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = run_monitor(&app, &watched, param, event, parse, rx) {
+            let _ = app.emit("monitor-error", e.to_string());
+        }
+    });
+    Ok(port_name)
+}
+
+fn stop_monitor(slot: &Mutex<Option<Sender<()>>>) {
+    if let Ok(mut guard) = slot.lock() {
+        if let Some(tx) = guard.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+/// Stream expression pedal raw/mapped/sent values (~30 Hz) as "exp-monitor" events.
+/// Returns the port being monitored.
+#[command]
+pub fn start_exp_monitor(app: AppHandle, port: Option<String>) -> Result<String, String> {
+    start_monitor(&EXP_MONITOR_TX, app, port, "monitor.exp", "exp-monitor", parse_exp_line)
+}
+
+/// Stop the expression pedal monitor
+#[command]
+pub fn stop_exp_monitor() -> Result<(), String> {
+    stop_monitor(&EXP_MONITOR_TX);
+    Ok(())
+}
+
+/// Start calibrating the expression pedals: forget earlier extremes and
+/// stream readings as "exp-monitor" events while the user sweeps each pedal
+/// heel to toe. Replaces a running expression monitor. Returns the port.
+#[command]
+pub fn start_expression_calibration(app: AppHandle, port: Option<String>) -> Result<String, String> {
+    if let Ok(mut ranges) = CALIBRATION.lock() {
+        *ranges = [None, None];
+    }
+    start_monitor(&EXP_MONITOR_TX, app, port, "monitor.exp", "exp-monitor", record_exp_line)
+}
+
+/// Stop calibrating and write the extremes recorded for `pedal` ("exp1" or
+/// "exp2") into the config at `device` as its `calibration`. Fails when the
+/// pedal barely moved; `expected_token` is as for `write_config`.
+#[command]
+pub fn finish_expression_calibration(
+    device: String,
+    pedal: String,
+    expected_token: Option<String>,
+) -> Result<Calibration, ConfigError> {
+    stop_monitor(&EXP_MONITOR_TX);
+    let index = match pedal.as_str() {
+        "exp1" => 0,
+        "exp2" => 1,
+        _ => {
+            return Err(ConfigError {
+                message: format!("Unknown pedal '{}' (expected exp1 or exp2)", pedal),
+                details: None,
+                code: None,
+            })
+        }
+    };
+    let recorded = CALIBRATION.lock().ok().and_then(|ranges| ranges[index]);
+    let calibration = check_calibration(&pedal, recorded)?;
+
+    let mut config = commands::read_config(device.clone())?.config;
+    let Some(pedals) = config.expression.as_mut() else {
+        return Err(ConfigError {
+            message: "Config has no expression pedals to calibrate".to_string(),
+            details: None,
+            code: None,
+        });
+    };
+    let target = if index == 0 { &mut pedals.exp1 } else { &mut pedals.exp2 };
+    target.calibration = Some(calibration);
+    commands::write_config(device, config, None, None, None, expected_token)?.into_result()?;
+    Ok(calibration)
+}
+
+/// The recorded range, if it is wide enough to use
+fn check_calibration(pedal: &str, recorded: Option<Calibration>) -> Result<Calibration, ConfigError> {
+    match recorded {
+        Some(c) if c.raw_max > c.raw_min && c.raw_max - c.raw_min >= MIN_CALIBRATION_SPAN => Ok(c),
+        Some(c) => Err(ConfigError {
+            message: format!(
+                "{} only moved between {} and {}; sweep the pedal fully heel to toe and try again",
+                pedal, c.raw_min, c.raw_max
+            ),
+            details: None,
+            code: None,
+        }),
+        None => Err(ConfigError {
+            message: format!("No readings from {}; start calibration and sweep the pedal first", pedal),
+            details: None,
+            code: None,
+        }),
+    }
+}
+
+/// Stream the encoder's position/value/slot (~30 Hz) as "encoder-monitor" events.
+/// Returns the port being monitored.
+#[command]
+pub fn start_encoder_monitor(app: AppHandle, port: Option<String>) -> Result<String, String> {
+    start_monitor(&ENC_MONITOR_TX, app, port, "monitor.enc", "encoder-monitor", parse_enc_line)
+}
+
+/// Stop the encoder monitor
+#[command]
+pub fn stop_encoder_monitor() -> Result<(), String> {
+    stop_monitor(&ENC_MONITOR_TX);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_exp_line() {
+        let sample = parse_exp_line("@exp 1024 0 0 65535 127 126\r\n").unwrap();
+        assert_eq!(sample.exp1, PedalReading { raw: 1024, mapped: 0, sent: 0 });
+        assert_eq!(sample.exp2, PedalReading { raw: 65535, mapped: 127, sent: 126 });
+    }
+
+    #[test]
+    fn test_parse_exp_line_rejects_other_output() {
+        assert_eq!(parse_exp_line("[EXP1] Ch1 CC12=64"), None);
+        assert_eq!(parse_exp_line("@exp 1 2 3"), None);
+        assert_eq!(parse_exp_line("@exp 70000 0 0 0 0 0"), None);
+    }
+
+    #[test]
+    fn test_calibration_range() {
+        let range = [40000, 900, 65000, 30000].into_iter().fold(None, |r, raw| Some(widen(r, raw)));
+        assert_eq!(range, Some(Calibration { raw_min: 900, raw_max: 65000 }));
+        assert_eq!(check_calibration("exp1", range).unwrap(), Calibration { raw_min: 900, raw_max: 65000 });
+
+        let unswept = Some(Calibration { raw_min: 30000, raw_max: 30500 });
+        assert!(check_calibration("exp2", unswept).unwrap_err().message.contains("sweep the pedal"));
+        assert!(check_calibration("exp2", None).unwrap_err().message.contains("No readings"));
+    }
+
+    #[test]
+    fn test_parse_enc_line() {
+        let sample = parse_enc_line("@enc 12 76 2 5\r\n").unwrap();
+        assert_eq!(sample, EncoderSample { position: 12, value: 76, slot: Some(2), steps: Some(5) });
+
+        let unstepped = parse_enc_line("@enc -3 61 -1 0").unwrap();
+        assert_eq!(unstepped.slot, None);
+        assert_eq!(unstepped.steps, None);
+        assert_eq!(parse_enc_line("@enc 1 2"), None);
+    }
+}
@@ -0,0 +1,137 @@
+//! Native OS notifications
+//!
+//! Raised from the Rust side for events worth hearing about while working in
+//! another app: a device connecting, a config write finishing or failing, and
+//! a device running older firmware than this editor ships with. Each event
+//! can be turned off in the settings, and none is shown while the editor's
+//! window has focus — the UI already shows the same thing there.
+
+use crate::commands::{app_data_dir, ConfigError};
+use crate::device::DetectedDevice;
+use crate::firmware::{is_outdated, read_firmware_version};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use tauri::{command, AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// File under the app-data directory holding notification settings
+const SETTINGS_FILE: &str = "notifications.json";
+
+/// Set once at startup; notifications are skipped without it (tests)
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// Which events raise a notification
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub device_connected: bool,
+    pub deploy_finished: bool,
+    pub deploy_failed: bool,
+    pub firmware_update: bool,
+    /// Play the system notification sound
+    pub sound: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        NotificationSettings {
+            device_connected: true,
+            deploy_finished: true,
+            deploy_failed: true,
+            firmware_update: true,
+            sound: true,
+        }
+    }
+}
+
+/// Load settings from the app-data directory
+pub fn load_settings(data_dir: &Path) -> NotificationSettings {
+    fs::read_to_string(data_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Called once from `setup`
+pub fn install(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+/// Show a notification if `enabled` picks the event out of the settings
+/// and the editor is in the background
+fn notify(enabled: fn(&NotificationSettings) -> bool, title: &str, body: &str) {
+    let Some(app) = APP.get() else { return };
+    let settings = crate::registry::data_dir().map(load_settings).unwrap_or_default();
+    if !enabled(&settings) {
+        return;
+    }
+    let focused = app
+        .get_webview_window("main")
+        .and_then(|w| w.is_focused().ok())
+        .unwrap_or(false);
+    if focused {
+        return;
+    }
+    let mut builder = app.notification().builder().title(title).body(body);
+    if settings.sound {
+        builder = builder.sound("default");
+    }
+    // Nothing else can be told if the notification itself fails
+    let _ = builder.show();
+}
+
+/// A device was connected; also reports outdated firmware on it. The editor
+/// is released together with the firmware, so its version is the newest
+/// firmware available.
+pub fn device_connected(device: &DetectedDevice) {
+    notify(|s| s.device_connected, "MIDI Captain connected", &device.name);
+
+    let shipped = env!("CARGO_PKG_VERSION");
+    if let Some(version) = read_firmware_version(&device.path).filter(|v| is_outdated(v, shipped)) {
+        notify(
+            |s| s.firmware_update,
+            "Firmware update available",
+            &format!("{} runs firmware {}; version {} is available", device.name, version, shipped),
+        );
+    }
+}
+
+/// A config write finished (successfully or not)
+pub fn deploy_finished<T>(path: &Path, result: &Result<T, ConfigError>) {
+    let device = crate::registry::device_key(path).unwrap_or_else(|| path.display().to_string());
+    match result {
+        Ok(_) => notify(|s| s.deploy_finished, "Config saved", &format!("Written to {}", device)),
+        Err(e) => notify(|s| s.deploy_failed, "Config write failed", &format!("{}: {}", device, e.message)),
+    }
+}
+
+/// Current notification settings
+#[command]
+pub fn get_notification_settings(app: AppHandle) -> Result<NotificationSettings, ConfigError> {
+    Ok(load_settings(&app_data_dir(&app)?))
+}
+
+/// Turn individual notifications on or off
+#[command]
+pub fn set_notification_settings(app: AppHandle, settings: NotificationSettings) -> Result<(), ConfigError> {
+    let json = serde_json::to_string_pretty(&settings)?;
+    fs::write(app_data_dir(&app)?.join(SETTINGS_FILE), json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_default_when_missing_or_partial() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load_settings(dir.path()), NotificationSettings::default());
+
+        fs::write(dir.path().join(SETTINGS_FILE), r#"{"deploy_finished": false}"#).unwrap();
+        let settings = load_settings(dir.path());
+        assert!(!settings.deploy_finished);
+        assert!(settings.deploy_failed);
+    }
+}
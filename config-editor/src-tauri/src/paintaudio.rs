@@ -0,0 +1,855 @@
+//! Conversion to and from the stock PaintAudio firmware's setup files
+//!
+//! The stock "Super Mode" firmware reads one `supersetup/pageN.txt` per page
+//! (see docs/PaintAudio_OEM/FW-SuperMode-4.0-BriefGuide.txt). A page has a
+//! `[PAGE]` section for the expression pedals and encoder and a `[keyN]`
+//! section per footswitch. Each trigger (`short_dw1`, `short_up1`, …) holds
+//! commands of four bracketed fields, e.g. `[1][CC][69][127]`.
+//!
+//! One page converts to one config. Whatever the other side can't express is
+//! left out or approximated, and listed in the report instead of failing the
+//! conversion.
+
+use crate::commands::ConfigError;
+use crate::config::{
+    default_button, Acceleration, ButtonColor, ButtonConfig, ButtonMode, DeviceType, EncoderConfig, ExpressionConfig, ExpressionPedals,
+    MacroStepType, MessageType, MidiCaptainConfig, NamedColor, OffMode, Polarity, StateOverride, TapOutput, CC_ALL_NOTES_OFF,
+    CC_ALL_SOUND_OFF, CONFIG_SCHEMA_VERSION,
+};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use tauri::command;
+
+/// Most presses a stock key can cycle through (`keytimes`)
+const MAX_KEYTIMES: u8 = 9;
+
+/// Largest step of the stock `incN`/`decN` program commands
+const MAX_PC_STEP: u8 = 5;
+
+/// Page name written on export (the stock firmware recommends 4 letters)
+const PAGE_NAME: &str = "MAIN";
+
+/// How a setting fared in the conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueKind {
+    /// Left out: the target format has no equivalent
+    Dropped,
+    /// Converted to the closest equivalent, which behaves differently
+    Approximated,
+}
+
+/// One setting that did not convert exactly
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InteropIssue {
+    /// Where in the source, e.g. "buttons[2]" or "key2"
+    pub location: String,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+/// A stock page converted to a config, for the UI to review before saving
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PaintAudioImport {
+    pub config: MidiCaptainConfig,
+    pub issues: Vec<InteropIssue>,
+}
+
+fn issue(issues: &mut Vec<InteropIssue>, kind: IssueKind, location: &str, message: String) {
+    issues.push(InteropIssue {
+        location: location.to_string(),
+        kind,
+        message,
+    });
+}
+
+fn rgb(color: &ButtonColor) -> (u8, u8, u8) {
+    color.rgb().unwrap_or((255, 255, 255))
+}
+
+/// Nearest palette color, and whether it is an exact match
+fn nearest_color(rgb: (u8, u8, u8)) -> (ButtonColor, bool) {
+    let distance = |(r, g, b): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+        d(r, rgb.0) + d(g, rgb.1) + d(b, rgb.2)
+    };
+    let color = NamedColor::ALL.into_iter().min_by_key(|c| distance(c.rgb())).unwrap();
+    (color.into(), distance(color.rgb()) == 0)
+}
+
+/// `ledcolorN` value: the same color on all three ring segments
+fn led_segments((r, g, b): (u8, u8, u8)) -> String {
+    format!("[0x{:02x}{:02x}{:02x}]", r, g, b).repeat(3)
+}
+
+// ---------------------------------------------------------------------------
+// Export
+// ---------------------------------------------------------------------------
+
+/// One press of a stock key: LED color, command on press, command on release
+struct Press {
+    color: (u8, u8, u8),
+    down: String,
+    up: Option<String>,
+}
+
+/// Stock command for one press of a button (`on`) or its release
+fn press_command(index: usize, button: &ButtonConfig, state: Option<&StateOverride>, channel: u16, on: bool) -> String {
+    let pick = |f: fn(&StateOverride) -> Option<u8>, base: Option<u8>| state.and_then(f).or(base);
+    match button.message_type {
+        MessageType::Cc => {
+            let cc = pick(|s| s.cc, button.cc).unwrap_or(20 + index as u8);
+            let value = if on {
+                pick(|s| s.cc_on, button.cc_on).unwrap_or(127)
+            } else {
+                pick(|s| s.cc_off, button.cc_off).unwrap_or(0)
+            };
+            format!("[{}][CC][{}][{}]", channel, cc, value)
+        }
+        MessageType::Note => {
+            let note = pick(|s| s.note, button.note).unwrap_or(60);
+            // The stock firmware has no note off; velocity 0 ends the note
+            let velocity = if on { pick(|s| s.velocity_on, button.velocity_on).unwrap_or(127) } else { 0 };
+            format!("[{}][NT][{}][{}]", channel, note, velocity)
+        }
+        MessageType::Pc => {
+            format!("[{}][PC][{}][-]", channel, pick(|s| s.program, button.program).unwrap_or(0))
+        }
+        MessageType::PcInc | MessageType::PcDec => {
+            let step = pick(|s| s.pc_step, button.pc_step).unwrap_or(1).clamp(1, MAX_PC_STEP);
+            let dir = if button.message_type == MessageType::PcInc { "inc" } else { "dec" };
+            format!("[{}][PC][{}{}][-]", channel, dir, step)
+        }
+        // The stock firmware sends any number of commands per press
+        MessageType::Panic => (1..=16)
+            .flat_map(|ch| {
+                [CC_ALL_NOTES_OFF, CC_ALL_SOUND_OFF]
+                    .into_iter()
+                    .chain(button.reset_ccs.iter().flatten().copied())
+                    .map(move |cc| format!("[{}][CC][{}][0]", ch, cc))
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+        MessageType::Macro => button
+            .steps
+            .iter()
+            .flatten()
+            .map(|step| {
+                let ch = step.channel.map_or(channel, |c| u16::from(c) + 1);
+                match step.message_type {
+                    MacroStepType::Cc => format!("[{}][CC][{}][{}]", ch, step.cc.unwrap_or(0), step.value.unwrap_or(127)),
+                    MacroStepType::Pc => format!("[{}][PC][{}][-]", ch, step.program.unwrap_or(0)),
+                    MacroStepType::Note => format!("[{}][NT][{}][{}]", ch, step.note.unwrap_or(60), step.velocity.unwrap_or(127)),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+fn export_button(index: usize, button: &ButtonConfig, global: u8, out: &mut String, issues: &mut Vec<InteropIssue>) {
+    let loc = format!("buttons[{}]", index);
+    let channel = u16::from(button.channel.unwrap_or(global)) + 1;
+    // A tap-tempo switch sends its CC on every press
+    let sends_off = matches!(button.message_type, MessageType::Cc | MessageType::Note) && button.mode != ButtonMode::TapTempo;
+    let momentary = button.mode == ButtonMode::Momentary && sends_off;
+
+    let mut keytimes = button.keytimes.unwrap_or(1).max(1);
+    if keytimes > MAX_KEYTIMES {
+        issue(issues, IssueKind::Approximated, &loc,
+            format!("keytimes {} cut to {}, the most the stock firmware cycles through", keytimes, MAX_KEYTIMES));
+        keytimes = MAX_KEYTIMES;
+    }
+    if button.flash_ms.is_some() {
+        issue(issues, IssueKind::Dropped, &loc, "flash_ms has no stock equivalent".to_string());
+    }
+    if button.mode == ButtonMode::TapTempo {
+        if button.tap_tempo.as_ref().is_some_and(|t| t.output == TapOutput::Clock) {
+            issue(issues, IssueKind::Dropped, &loc, "Tap tempo MIDI clock has no stock equivalent".to_string());
+        } else {
+            issue(issues, IssueKind::Approximated, &loc,
+                "Tap tempo becomes a CC sent on every press; the receiving device times the taps".to_string());
+        }
+    }
+    if button.message_type == MessageType::Macro && button.steps.iter().flatten().any(|s| s.delay_ms.unwrap_or(0) > 0) {
+        issue(issues, IssueKind::Approximated, &loc, "Macro steps are sent together; the stock firmware has no step delays".to_string());
+    }
+    let mut steps = std::iter::once(button.pc_step)
+        .chain(button.states.iter().flatten().map(|s| s.pc_step))
+        .flatten();
+    if matches!(button.message_type, MessageType::PcInc | MessageType::PcDec) && steps.any(|s| s > MAX_PC_STEP) {
+        issue(issues, IssueKind::Approximated, &loc,
+            format!("Program steps above {} are sent as {}", MAX_PC_STEP, MAX_PC_STEP));
+    }
+
+    let presses: Vec<Press> = if keytimes > 1 {
+        (0..usize::from(keytimes))
+            .map(|n| {
+                let state = button.states.as_ref().and_then(|s| s.get(n));
+                Press {
+                    color: rgb(state.and_then(|s| s.color.as_ref()).unwrap_or(&button.color)),
+                    down: press_command(index, button, state, channel, true),
+                    up: momentary.then(|| press_command(index, button, state, channel, false)),
+                }
+            })
+            .collect()
+    } else if momentary {
+        issue(issues, IssueKind::Approximated, &loc,
+            "Momentary: the stock LED stays lit after release".to_string());
+        vec![Press {
+            color: rgb(&button.color),
+            down: press_command(index, button, None, channel, true),
+            up: Some(press_command(index, button, None, channel, false)),
+        }]
+    } else if sends_off {
+        // Toggle: a two-press cycle alternating on and off, in the LED
+        // colors the firmware draws (`led_rgb`)
+        let (on, off) = button.led_rgb();
+        vec![
+            Press {
+                color: on,
+                down: press_command(index, button, None, channel, true),
+                up: None,
+            },
+            Press {
+                color: off,
+                down: press_command(index, button, None, channel, false),
+                up: None,
+            },
+        ]
+    } else {
+        vec![Press {
+            color: rgb(&button.color),
+            down: press_command(index, button, None, channel, true),
+            up: None,
+        }]
+    };
+
+    let _ = writeln!(out, "\n[key{}]", index);
+    let _ = writeln!(out, "keytimes = [{}]", presses.len());
+    let _ = writeln!(out, "ledmode = [normal]");
+    for (n, press) in presses.iter().enumerate() {
+        let n = n + 1;
+        let _ = writeln!(out, "ledcolor{} = {}", n, led_segments(press.color));
+        let _ = writeln!(out, "short_dw{} = {}", n, press.down);
+        if let Some(ref up) = press.up {
+            let _ = writeln!(out, "short_up{} = {}", n, up);
+        }
+    }
+}
+
+/// Convert a config to a stock page file, with what didn't carry over
+pub fn export_page(config: &MidiCaptainConfig) -> (String, Vec<InteropIssue>) {
+    let mut out = String::new();
+    let mut issues = Vec::new();
+    let global = config.global_channel.unwrap_or(0);
+
+    let _ = writeln!(out, "[PAGE]");
+    let _ = writeln!(out, "page_name = [{}]", PAGE_NAME);
+
+    if let Some(ref exp) = config.expression {
+        for (n, pedal) in [(1, &exp.exp1), (2, &exp.exp2)] {
+            let loc = format!("expression.exp{}", n);
+            if !pedal.enabled {
+                issue(&mut issues, IssueKind::Dropped, &loc,
+                    "Disabled pedal: the stock firmware always sends its pedals".to_string());
+                continue;
+            }
+            let _ = writeln!(out, "exp{}_CH = [{}]", n, u16::from(pedal.channel.unwrap_or(global)) + 1);
+            let _ = writeln!(out, "exp{}_CC = [{}]", n, pedal.cc);
+            if pedal.min != 0 || pedal.max != 127 || pedal.polarity != Polarity::Normal || pedal.threshold != 2 {
+                issue(&mut issues, IssueKind::Dropped, &loc,
+                    "Range, polarity and threshold have no stock equivalent".to_string());
+            }
+        }
+    }
+
+    if let Some(ref enc) = config.encoder {
+        if enc.enabled {
+            let _ = writeln!(out, "encoder_CC = [{}]", enc.cc);
+            let _ = writeln!(out, "encoder_NAME = [{}]", enc.label);
+            if enc.channel.unwrap_or(global) != 0 {
+                issue(&mut issues, IssueKind::Dropped, "encoder",
+                    "The stock encoder has no channel setting".to_string());
+            }
+            if enc.min != 0 || enc.max != 127 || enc.initial != 64 || enc.steps.is_some() {
+                issue(&mut issues, IssueKind::Dropped, "encoder",
+                    "Range, initial value and steps have no stock equivalent".to_string());
+            }
+            // The firmware enables the push button unless it is turned off
+            if enc.push.as_ref().is_none_or(|p| p.enabled) {
+                issue(&mut issues, IssueKind::Dropped, "encoder.push",
+                    "The stock format has no encoder push setting".to_string());
+            }
+        } else {
+            issue(&mut issues, IssueKind::Dropped, "encoder",
+                "Disabled encoder: the stock firmware always sends it".to_string());
+        }
+    }
+
+    let _ = writeln!(out, "midithrough = [off]");
+
+    if config.display.is_some() {
+        issue(&mut issues, IssueKind::Dropped, "display", "Text sizes have no stock equivalent".to_string());
+    }
+    if config.usb_drive_name.is_some() || config.dev_mode.is_some() {
+        issue(&mut issues, IssueKind::Dropped, "usb_drive_name",
+            "USB drive settings are not part of a stock page".to_string());
+    }
+    let labelled = config.buttons.iter().filter(|b| !b.label.trim().is_empty()).count();
+    if labelled > 0 {
+        issue(&mut issues, IssueKind::Dropped, "buttons",
+            format!("{} button label(s) left out: stock keys have no labels", labelled));
+    }
+
+    for (index, button) in config.buttons.iter().enumerate() {
+        export_button(index, button, global, &mut out, &mut issues);
+    }
+    (out, issues)
+}
+
+// ---------------------------------------------------------------------------
+// Import
+// ---------------------------------------------------------------------------
+
+/// Bracketed fields of a value: `[1][CC][69][127]` → ["1", "CC", "69", "127"]
+fn fields(value: &str) -> Vec<String> {
+    value
+        .split('[')
+        .skip(1)
+        .filter_map(|part| part.split_once(']').map(|(field, _)| field.trim().to_string()))
+        .collect()
+}
+
+/// Sections of a page file: name → key → fields. Sections are `[PAGE]`,
+/// `[key0]`, …; keys are as written (the stock firmware is case-sensitive).
+pub fn parse_page(text: &str) -> BTreeMap<String, BTreeMap<String, Vec<String>>> {
+    let mut sections: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+    let mut current = None;
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        match line.split_once('=') {
+            Some((key, value)) => {
+                if let Some(ref section) = current {
+                    sections
+                        .entry(String::clone(section))
+                        .or_default()
+                        .insert(key.trim().to_string(), fields(value));
+                }
+            }
+            None if line.starts_with('[') && line.ends_with(']') => {
+                let name = line[1..line.len() - 1].trim().to_string();
+                sections.entry(name.clone()).or_default();
+                current = Some(name);
+            }
+            None => {}
+        }
+    }
+    sections
+}
+
+/// One stock command: `[channel or action][kind][number][value]`
+#[derive(Debug, Clone, PartialEq)]
+struct StockCommand {
+    target: String,
+    kind: String,
+    number: String,
+    value: String,
+}
+
+/// MIDI channel of a command, stored 0-15
+fn command_channel(cmd: &StockCommand) -> Option<u8> {
+    cmd.target.parse::<u8>().ok().filter(|ch| (1..=16).contains(ch)).map(|ch| ch - 1)
+}
+
+fn data_byte(field: &str) -> Option<u8> {
+    field.parse::<u8>().ok().filter(|v| *v <= 127)
+}
+
+/// The MIDI command of a trigger. HID and delay commands are dropped; of
+/// several MIDI commands only the first is kept.
+fn trigger_command(loc: &str, key: &str, fields: &[String], issues: &mut Vec<InteropIssue>) -> Option<StockCommand> {
+    let commands: Vec<StockCommand> = fields
+        .chunks(4)
+        .filter(|c| c.len() == 4)
+        .map(|c| StockCommand {
+            target: c[0].clone(),
+            kind: c[1].clone(),
+            number: c[2].clone(),
+            value: c[3].clone(),
+        })
+        .collect();
+    let (midi, other): (Vec<_>, Vec<_>) = commands.into_iter().partition(|c| c.kind != "HID");
+    if !other.is_empty() {
+        issue(issues, IssueKind::Dropped, loc,
+            format!("{}: keyboard/mouse and delay commands have no equivalent", key));
+    }
+    if midi.len() > 1 {
+        issue(issues, IssueKind::Approximated, loc,
+            format!("{}: only the first of {} MIDI commands is kept", key, midi.len()));
+    }
+    midi.into_iter().next()
+}
+
+/// LED of one stock press: the first ring segment, and whether all three match
+#[derive(Clone, Copy)]
+struct StockLed {
+    rgb: (u8, u8, u8),
+    uniform: bool,
+}
+
+fn parse_led(fields: &[String]) -> Option<StockLed> {
+    let parse = |f: &String| u32::from_str_radix(f.trim_start_matches("0x").trim_start_matches("0X"), 16).ok();
+    let first = parse(fields.first()?)?;
+    Some(StockLed {
+        rgb: ((first >> 16) as u8, (first >> 8) as u8, first as u8),
+        uniform: fields.iter().all(|f| parse(f) == Some(first)),
+    })
+}
+
+/// Palette color for a press's LED, reporting when it isn't an exact match
+fn palette_color(loc: &str, led: Option<StockLed>, issues: &mut Vec<InteropIssue>) -> Option<ButtonColor> {
+    let led = led?;
+    let (color, exact) = nearest_color(led.rgb);
+    if !exact || !led.uniform {
+        issue(issues, IssueKind::Approximated, loc,
+            format!("LED 0x{:02x}{:02x}{:02x} shown as {}, the nearest palette color",
+                led.rgb.0, led.rgb.1, led.rgb.2, color.name()));
+    }
+    Some(color)
+}
+
+/// One stock press: LED and the commands on press and release
+struct StockPress {
+    led: Option<StockLed>,
+    down: StockCommand,
+    up: Option<StockCommand>,
+}
+
+fn import_key(index: usize, entries: &BTreeMap<String, Vec<String>>, issues: &mut Vec<InteropIssue>) -> ButtonConfig {
+    let loc = format!("key{}", index);
+    let mut button = default_button(index);
+    let keytimes = entries
+        .get("keytimes")
+        .and_then(|f| f.first())
+        .and_then(|f| f.parse::<u8>().ok())
+        .unwrap_or(1)
+        .clamp(1, MAX_KEYTIMES);
+
+    if let Some(mode) = entries.get("ledmode").and_then(|f| f.first()) {
+        if mode != "normal" {
+            issue(issues, IssueKind::Approximated, &loc,
+                format!("ledmode {}: LEDs follow the button state instead", mode));
+        }
+    }
+    for key in entries.keys().filter(|k| k.starts_with("long")) {
+        issue(issues, IssueKind::Dropped, &loc, format!("{}: long-press triggers have no equivalent", key));
+    }
+
+    let mut presses = Vec::new();
+    for n in 1..=keytimes {
+        let mut command = |key: String| {
+            entries.get(&key).and_then(|f| trigger_command(&loc, &key, f, issues))
+        };
+        let (down, up) = match (command(format!("short_dw{}", n)), command(format!("short_up{}", n))) {
+            (Some(down), up) => (down, up),
+            // Only a release trigger: treat it as the press
+            (None, Some(up)) => (up, None),
+            (None, None) => continue,
+        };
+        let led = entries.get(&format!("ledcolor{}", n)).and_then(|f| parse_led(f));
+        presses.push(StockPress { led, down, up });
+    }
+
+    let Some(first) = presses.first() else {
+        issue(issues, IssueKind::Dropped, &loc, "No MIDI command; left at the firmware default".to_string());
+        return button;
+    };
+    let kind = first.down.kind.clone();
+    if presses.iter().any(|p| p.down.kind != kind) {
+        issue(issues, IssueKind::Approximated, &loc,
+            format!("Mixed message types; only the {} presses are kept", kind));
+        presses.retain(|p| p.down.kind == kind);
+    }
+    let channel = command_channel(&presses[0].down);
+    if presses.iter().any(|p| command_channel(&p.down) != channel) {
+        issue(issues, IssueKind::Approximated, &loc,
+            "Presses on different channels; all sent on the first press's channel".to_string());
+    }
+    button.channel = channel;
+    if let Some(color) = palette_color(&loc, presses[0].led, issues) {
+        button.color = color;
+    }
+
+    let converted = match kind.as_str() {
+        "CC" | "NT" => {
+            import_cc_or_note(&loc, &mut button, &presses, kind == "NT", issues);
+            true
+        }
+        "PC" => import_pc(&loc, &mut button, &presses, issues),
+        other => {
+            issue(issues, IssueKind::Dropped, &loc, format!("Unknown command type {}", other));
+            false
+        }
+    };
+    if converted {
+        button
+    } else {
+        default_button(index)
+    }
+}
+
+fn import_cc_or_note(loc: &str, button: &mut ButtonConfig, presses: &[StockPress], note: bool, issues: &mut Vec<InteropIssue>) {
+    let number = |c: &StockCommand| data_byte(&c.number);
+    let value = |c: &StockCommand| data_byte(&c.value);
+    let first = &presses[0];
+    let same_number = presses.iter().all(|p| number(&p.down) == number(&first.down));
+
+    if note {
+        button.message_type = MessageType::Note;
+        button.cc = None;
+    }
+    let set = |button: &mut ButtonConfig, num: Option<u8>, on: Option<u8>, off: Option<u8>| {
+        if note {
+            (button.note, button.velocity_on, button.velocity_off) = (num, on, off);
+        } else {
+            (button.cc, button.cc_on, button.cc_off) = (num, on, off);
+        }
+    };
+
+    if presses.len() == 1 {
+        if let Some(ref up) = first.up {
+            button.mode = ButtonMode::Momentary;
+            set(button, number(&first.down), value(&first.down), value(up));
+        } else if note {
+            issue(issues, IssueKind::Approximated, loc,
+                "A repeated note on becomes a toggle alternating note on and off".to_string());
+            set(button, number(&first.down), value(&first.down), None);
+        } else {
+            // Every press sends the same value: a toggle whose on and off match
+            set(button, number(&first.down), value(&first.down), value(&first.down));
+        }
+    } else if presses.len() == 2 && same_number && presses.iter().all(|p| p.up.is_none()) {
+        // Two-press cycle: on, then off
+        set(button, number(&first.down), value(&first.down), value(&presses[1].down));
+        if presses[1].led.is_some_and(|led| led.rgb == (0, 0, 0)) {
+            button.off_mode = OffMode::Off;
+        }
+    } else {
+        button.keytimes = Some(presses.len() as u8);
+        if presses.iter().any(|p| p.up.is_some()) {
+            button.mode = ButtonMode::Momentary;
+        }
+        set(button, number(&first.down), value(&first.down), first.up.as_ref().and_then(value));
+        button.states = Some(
+            presses
+                .iter()
+                .map(|p| {
+                    let mut state = StateOverride {
+                        color: palette_color(loc, p.led, issues),
+                        ..Default::default()
+                    };
+                    let off = p.up.as_ref().and_then(value);
+                    if note {
+                        (state.note, state.velocity_on, state.velocity_off) = (number(&p.down), value(&p.down), off);
+                    } else {
+                        (state.cc, state.cc_on, state.cc_off) = (number(&p.down), value(&p.down), off);
+                    }
+                    state
+                })
+                .collect(),
+        );
+    }
+}
+
+/// Fill in a program change button. False when the command has no equivalent.
+fn import_pc(loc: &str, button: &mut ButtonConfig, presses: &[StockPress], issues: &mut Vec<InteropIssue>) -> bool {
+    let first = &presses[0].down;
+    button.cc = None;
+    let step = |prefix: &str| {
+        first.number.strip_prefix(prefix).and_then(|s| s.parse::<u8>().ok()).filter(|s| (1..=MAX_PC_STEP).contains(s))
+    };
+    if let Some(s) = step("inc") {
+        button.message_type = MessageType::PcInc;
+        button.pc_step = Some(s);
+    } else if let Some(s) = step("dec") {
+        button.message_type = MessageType::PcDec;
+        button.pc_step = Some(s);
+    } else if let Some(program) = data_byte(&first.number) {
+        button.message_type = MessageType::Pc;
+        button.program = Some(program);
+        if presses.len() > 1 {
+            button.keytimes = Some(presses.len() as u8);
+            button.states = Some(
+                presses
+                    .iter()
+                    .map(|p| StateOverride {
+                        program: data_byte(&p.down.number),
+                        color: palette_color(loc, p.led, issues),
+                        ..Default::default()
+                    })
+                    .collect(),
+            );
+        }
+        return true;
+    } else {
+        issue(issues, IssueKind::Dropped, loc,
+            format!("PC {} (random/auto bank) has no equivalent; left at the firmware default", first.number));
+        return false;
+    }
+    if presses.len() > 1 {
+        issue(issues, IssueKind::Approximated, loc,
+            "Program up/down cycles keep only the first press".to_string());
+    }
+    true
+}
+
+/// Convert a stock page file to a config, with what didn't carry over
+pub fn import_page(text: &str) -> PaintAudioImport {
+    let mut sections = parse_page(text);
+    let mut issues = Vec::new();
+    let page = sections.remove("PAGE").unwrap_or_default();
+
+    let mut keys: BTreeMap<usize, ButtonConfig> = BTreeMap::new();
+    for (name, entries) in &sections {
+        match name.strip_prefix("key").and_then(|n| n.parse::<usize>().ok()) {
+            Some(index) => {
+                keys.insert(index, import_key(index, entries, &mut issues));
+            }
+            None => issue(&mut issues, IssueKind::Dropped, name, "Unknown section".to_string()),
+        }
+    }
+
+    let number = |key: &str| page.get(key).and_then(|f| f.first()).and_then(|f| data_byte(f));
+    let pedal_channel = |key: &str| number(key).filter(|ch| (1..=16).contains(ch)).map(|ch| ch - 1);
+    let pedal = |n: u8| {
+        number(&format!("exp{}_CC", n)).map(|cc| ExpressionConfig {
+            enabled: true,
+            cc,
+            label: format!("EXP{}", n),
+            min: 0,
+            max: 127,
+            polarity: Polarity::Normal,
+            threshold: 2,
+            channel: pedal_channel(&format!("exp{}_CH", n)),
+            destination: None,
+            calibration: None,
+            curve: None,
+        })
+    };
+    let (exp1, exp2) = (pedal(1), pedal(2));
+    let expression = (exp1.is_some() || exp2.is_some()).then(|| {
+        // A pedal the page doesn't set keeps the firmware's default CC
+        let unset = |n: u8, cc: u8| ExpressionConfig {
+            enabled: true,
+            cc,
+            label: format!("EXP{}", n),
+            min: 0,
+            max: 127,
+            polarity: Polarity::Normal,
+            threshold: 2,
+            channel: None,
+            destination: None,
+            calibration: None,
+            curve: None,
+        };
+        ExpressionPedals {
+            exp1: exp1.unwrap_or_else(|| unset(1, 12)),
+            exp2: exp2.unwrap_or_else(|| unset(2, 13)),
+        }
+    });
+    let encoder = number("encoder_CC").map(|cc| EncoderConfig {
+        enabled: true,
+        cc,
+        label: page
+            .get("encoder_NAME")
+            .and_then(|f| f.first())
+            .cloned()
+            .unwrap_or_else(|| "ENC".to_string()),
+        min: 0,
+        max: 127,
+        initial: 64,
+        steps: None,
+        push: None,
+        channel: None,
+        acceleration: Acceleration::Off,
+        wrap: false,
+    });
+
+    const HANDLED: &[&str] = &["exp1_CH", "exp1_CC", "exp2_CH", "exp2_CC", "encoder_CC", "encoder_NAME"];
+    for (key, value) in page.iter().filter(|(k, _)| !HANDLED.contains(&k.as_str())) {
+        if key == "midithrough" && value.first().map(String::as_str) != Some("on") {
+            continue;
+        }
+        issue(&mut issues, IssueKind::Dropped, "PAGE", format!("{} has no equivalent", key));
+    }
+
+    let count = keys.keys().next_back().map_or(0, |last| last + 1);
+    let device = if count > 6 || expression.is_some() || encoder.is_some() {
+        DeviceType::Std10
+    } else {
+        DeviceType::Mini6
+    };
+    let expected = device.button_count();
+    for index in keys.keys().filter(|i| **i >= expected) {
+        issue(&mut issues, IssueKind::Dropped, &format!("key{}", index),
+            format!("The device has only {} footswitches", expected));
+    }
+    let buttons = (0..expected).map(|i| keys.remove(&i).unwrap_or_else(|| default_button(i))).collect();
+
+    PaintAudioImport {
+        config: MidiCaptainConfig {
+            device,
+            version: Some(CONFIG_SCHEMA_VERSION),
+            global_channel: None,
+            global: None,
+            usb_drive_name: None,
+            dev_mode: None,
+            buttons,
+            encoder,
+            expression,
+            display: None,
+            routing: None,
+            editor: None,
+        },
+        issues,
+    }
+}
+
+/// Write a config as a stock page file (e.g. `supersetup/page0.txt`).
+/// Returns what didn't carry over.
+#[command]
+pub fn export_paintaudio(config: MidiCaptainConfig, path: String) -> Result<Vec<InteropIssue>, ConfigError> {
+    let (page, issues) = export_page(&config);
+    fs::write(&path, page)?;
+    Ok(issues)
+}
+
+/// Read a stock page file as a config. Nothing is written; the UI shows the
+/// config and the report for review.
+#[command]
+pub fn import_paintaudio(path: String) -> Result<PaintAudioImport, ConfigError> {
+    Ok(import_page(&fs::read_to_string(&path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STOCK_PAGE: &str = "\
+[PAGE]
+page_name = [HXLP]
+exp1_CH = [2]
+exp1_CC = [1]
+encoder_CC = [90]
+encoder_NAME = [MYCC]
+midithrough = [on]
+
+[key0]
+keytimes = [1]
+ledmode = [normal]
+ledcolor1 = [0xff0000][0xff0000][0xff0000]
+short_dw1 = [1][CC][64][127]
+short_up1 = [1][CC][64][0]
+
+[key1]
+keytimes = [3]
+ledcolor1 = [0x666666][0x666666][0xff0000]
+short_dw1 = [1][CC][69][0]
+short_dw2 = [1][CC][69][1]
+short_dw3 = [1][CC][69][2]
+
+[key2]
+short_dw1 = [3][PC][inc2][-] [send][HID][-][A]
+
+[key3]
+short_dw1 = [1][PC][auto][bank_inc]
+long1 = [1][CC][10][127]
+";
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(fields("[1][CC][69][127]"), ["1", "CC", "69", "127"]);
+        assert_eq!(fields(" [0xff0000] [ 0x00ff00 ]"), ["0xff0000", "0x00ff00"]);
+    }
+
+    #[test]
+    fn test_import_stock_page() {
+        let import = import_page(STOCK_PAGE);
+        let config = &import.config;
+        assert_eq!(config.device, DeviceType::Std10);
+        assert_eq!(config.buttons.len(), 10);
+
+        let sustain = &config.buttons[0];
+        assert_eq!(sustain.mode, ButtonMode::Momentary);
+        assert_eq!((sustain.cc, sustain.cc_on, sustain.cc_off), (Some(64), Some(127), Some(0)));
+        assert_eq!(sustain.color, NamedColor::Red.into());
+
+        let cycle = &config.buttons[1];
+        assert_eq!(cycle.keytimes, Some(3));
+        let states = cycle.states.as_ref().unwrap();
+        assert_eq!(states.iter().map(|s| s.cc_on).collect::<Vec<_>>(), [Some(0), Some(1), Some(2)]);
+
+        let inc = &config.buttons[2];
+        assert_eq!(inc.message_type, MessageType::PcInc);
+        assert_eq!((inc.pc_step, inc.channel), (Some(2), Some(2)));
+
+        // Unsupported key falls back to the firmware default
+        assert_eq!(config.buttons[3].cc, Some(23));
+        assert_eq!(config.buttons[9].label, "10");
+
+        let exp = config.expression.as_ref().unwrap();
+        assert_eq!((exp.exp1.cc, exp.exp1.channel), (1, Some(1)));
+        assert_eq!(exp.exp2.cc, 13);
+        assert_eq!(config.encoder.as_ref().unwrap().label, "MYCC");
+        assert!(config.validate().is_ok());
+
+        let has = |location: &str, kind: IssueKind| import.issues.iter().any(|i| i.location == location && i.kind == kind);
+        assert!(has("key1", IssueKind::Approximated)); // gray, two-color ring
+        assert!(has("key2", IssueKind::Dropped)); // HID command
+        assert!(has("key3", IssueKind::Dropped)); // auto bank, long press
+        assert!(has("PAGE", IssueKind::Dropped)); // page name, midithrough
+    }
+
+    #[test]
+    fn test_export_toggle_and_momentary() {
+        let mut config = import_page("[key0]\n").config;
+        config.buttons[0].cc = Some(80);
+        config.buttons[1].mode = ButtonMode::Momentary;
+        config.buttons[1].channel = Some(4);
+        config.buttons[2].flash_ms = Some(200);
+
+        let (page, issues) = export_page(&config);
+        let sections = parse_page(&page);
+        let toggle = &sections["key0"];
+        assert_eq!(toggle["keytimes"], ["2"]);
+        assert_eq!(toggle["short_dw1"], ["1", "CC", "80", "127"]);
+        assert_eq!(toggle["short_dw2"], ["1", "CC", "80", "0"]);
+        assert_eq!(toggle["ledcolor2"][0], "0x262626");
+
+        let momentary = &sections["key1"];
+        assert_eq!(momentary["short_up1"], ["5", "CC", "21", "0"]);
+
+        assert!(issues.iter().any(|i| i.location == "buttons[2]" && i.message.contains("flash_ms")));
+        assert!(issues.iter().any(|i| i.location == "buttons" && i.kind == IssueKind::Dropped));
+    }
+
+    #[test]
+    fn test_export_then_import_keeps_midi() {
+        let mut config = import_page(STOCK_PAGE).config;
+        config.buttons[4].message_type = MessageType::Pc;
+        config.buttons[4].cc = None;
+        config.buttons[4].program = Some(7);
+
+        let (page, _) = export_page(&config);
+        let back = import_page(&page).config;
+        for (a, b) in config.buttons.iter().zip(&back.buttons) {
+            assert_eq!(a.message_type, b.message_type);
+            assert_eq!((a.cc, a.program, a.pc_step), (b.cc, b.program, b.pc_step));
+            if a.message_type == MessageType::Cc {
+                assert_eq!(a.cc_on.unwrap_or(127), b.cc_on.unwrap_or(127));
+            }
+        }
+        assert_eq!(back.encoder.unwrap().cc, 90);
+    }
+}
@@ -0,0 +1,742 @@
+//! Preset library
+//!
+//! Presets are whole configs saved under the app-data directory, one
+//! `<name>.json.zst` file each (zstd-compressed; plain `<name>.json` files
+//! from before compression still load), so a rig can be stored and
+//! redeployed later.
+//!
+//! At startup every stored preset is checked against the current schema, so a
+//! preset saved by an older editor is flagged before the user tries to open it
+//! mid-soundcheck rather than failing when they do.
+//!
+//! Favorites and numbered quick-deploy slots (1-9) live in a separate index
+//! file, so a preset can be pushed to a device with one keystroke. The index
+//! also holds each preset's metadata: song, key, tempo and notes, which
+//! setlist sheets print (see setlist.rs), and a description and tags that
+//! `search_presets` matches on.
+
+use crate::archive::{compress, compressed_path, read_file, COMPRESSED_SUFFIX};
+use crate::commands::{self, app_data_dir, ConfigError};
+use crate::confirm::Confirmable;
+use crate::config::{issue_messages, MidiCaptainConfig};
+use crate::diff::{diff_values, ChangeKind};
+use crate::firmware::unix_now;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Emitter};
+
+/// Directory under the app-data directory holding presets
+const PRESETS_DIR: &str = "presets";
+
+/// File under the app-data directory holding favorites and quick-deploy slots
+const LIBRARY_INDEX_FILE: &str = "preset_library.json";
+
+/// Highest quick-deploy slot number (slots are 1-9, one key each)
+pub const MAX_SLOT: u8 = 9;
+
+/// Tempo range accepted in preset metadata (BPM)
+pub const TEMPO_RANGE: std::ops::RangeInclusive<u16> = 20..=400;
+
+/// Result of the last library check (set by the startup task)
+static LIBRARY_HEALTH: Mutex<Option<LibraryHealth>> = Mutex::new(None);
+
+/// Health of a single stored preset
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresetStatus {
+    /// Loads and validates against the current schema
+    Ok,
+    /// Loads, but fields would be dropped or rewritten when saved again
+    NeedsMigration,
+    /// Unreadable, not a config, or fails validation
+    NeedsRepair,
+}
+
+/// Health report for one preset
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PresetHealth {
+    pub name: String,
+    pub status: PresetStatus,
+    /// What needs migrating or repairing
+    pub details: Vec<String>,
+    /// Saved from a permissive-mode write (`_editor.permissive_write`)
+    pub permissive: bool,
+}
+
+/// Health report for the whole preset library
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LibraryHealth {
+    /// Unix timestamp (seconds) of the check
+    pub checked_at: u64,
+    pub presets: Vec<PresetHealth>,
+}
+
+/// What a preset is used for on stage
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PresetMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Free-form labels, e.g. "worship", "band-b"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub song: Option<String>,
+    /// Musical key, e.g. "E", "F#m"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// BPM
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tempo: Option<u16>,
+    /// Pedal notes for the song, e.g. "stomp DLY before the bridge"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl PresetMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// True if `query` (lowercase) appears in the description, song, notes or
+    /// a tag
+    fn mentions(&self, query: &str) -> bool {
+        [&self.description, &self.song, &self.notes]
+            .into_iter()
+            .flatten()
+            .chain(&self.tags)
+            .any(|text| text.to_lowercase().contains(query))
+    }
+
+    fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+/// A preset with its index entries, as listed by `search_presets`
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PresetEntry {
+    pub name: String,
+    pub favorite: bool,
+    /// Quick-deploy slot holding it, if any
+    pub slot: Option<u8>,
+    pub metadata: PresetMetadata,
+}
+
+/// Favorites, quick-deploy slots and song metadata for the preset library
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct LibraryIndex {
+    /// Favorite preset names, in the order they were pinned
+    #[serde(default)]
+    pub favorites: Vec<String>,
+    /// Quick-deploy slot (1-9) → preset name
+    #[serde(default)]
+    pub slots: BTreeMap<u8, String>,
+    /// Preset name → song metadata
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, PresetMetadata>,
+}
+
+impl LibraryIndex {
+    /// Pin or unpin a preset
+    pub fn set_favorite(&mut self, name: &str, favorite: bool) {
+        self.favorites.retain(|f| f != name);
+        if favorite {
+            self.favorites.push(name.to_string());
+        }
+    }
+
+    /// Assign a preset to a slot, or clear the slot with `None`
+    pub fn set_slot(&mut self, slot: u8, name: Option<String>) -> Result<(), ConfigError> {
+        if slot == 0 || slot > MAX_SLOT {
+            return Err(ConfigError {
+                message: format!("Quick-deploy slot {} is invalid (must be 1-{})", slot, MAX_SLOT),
+                details: None,
+                code: None,
+            });
+        }
+        match name {
+            Some(name) => self.slots.insert(slot, name),
+            None => self.slots.remove(&slot),
+        };
+        Ok(())
+    }
+
+    /// Set a preset's metadata; empty metadata removes the entry. Tags are
+    /// trimmed and de-duplicated (ignoring case).
+    pub fn set_metadata(&mut self, name: &str, mut metadata: PresetMetadata) -> Result<(), ConfigError> {
+        if let Some(tempo) = metadata.tempo.filter(|t| !TEMPO_RANGE.contains(t)) {
+            return Err(ConfigError {
+                message: format!(
+                    "Tempo {} is invalid (must be {}-{} BPM)",
+                    tempo,
+                    TEMPO_RANGE.start(),
+                    TEMPO_RANGE.end()
+                ),
+                details: None,
+                code: None,
+            });
+        }
+        let mut tags: Vec<String> = Vec::new();
+        for tag in metadata.tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                tags.push(tag.to_string());
+            }
+        }
+        metadata.tags = tags;
+        if metadata.is_empty() {
+            self.metadata.remove(name);
+        } else {
+            self.metadata.insert(name.to_string(), metadata);
+        }
+        Ok(())
+    }
+
+    /// Presets among `names` whose name or metadata contains `query`
+    /// (ignoring case) and that carry every one of `tags`
+    pub fn search(&self, names: &[String], query: &str, tags: &[String]) -> Vec<PresetEntry> {
+        let query = query.trim().to_lowercase();
+        names
+            .iter()
+            .map(|name| PresetEntry {
+                name: name.clone(),
+                favorite: self.favorites.contains(name),
+                slot: self.slots.iter().find(|(_, n)| *n == name).map(|(slot, _)| *slot),
+                metadata: self.metadata.get(name).cloned().unwrap_or_default(),
+            })
+            .filter(|entry| {
+                (entry.name.to_lowercase().contains(&query) || entry.metadata.mentions(&query))
+                    && tags.iter().all(|tag| entry.metadata.has_tag(tag))
+            })
+            .collect()
+    }
+
+    /// Drop every reference to a deleted preset
+    pub fn forget(&mut self, name: &str) {
+        self.favorites.retain(|f| f != name);
+        self.slots.retain(|_, n| n != name);
+        self.metadata.remove(name);
+    }
+}
+
+impl LibraryHealth {
+    /// Number of presets that need migration or repair
+    pub fn issue_count(&self) -> usize {
+        self.presets.iter().filter(|p| p.status != PresetStatus::Ok).count()
+    }
+}
+
+/// Presets directory under the app-data directory
+pub(crate) fn presets_dir(app: &AppHandle) -> Result<PathBuf, ConfigError> {
+    let dir = app_data_dir(app)?.join(PRESETS_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Load the favorites/slots index from the app-data directory
+pub fn load_index(data_dir: &Path) -> LibraryIndex {
+    fs::read_to_string(data_dir.join(LIBRARY_INDEX_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(data_dir: &Path, index: &LibraryIndex) -> Result<(), ConfigError> {
+    let json = serde_json::to_string_pretty(index)?;
+    fs::write(data_dir.join(LIBRARY_INDEX_FILE), json)?;
+    Ok(())
+}
+
+/// Reject names that could escape the presets (or recovery points) directory
+pub(crate) fn check_name(name: &str) -> Result<(), ConfigError> {
+    if name.trim().is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(ConfigError {
+            message: format!("Invalid name '{}'", name),
+            details: None,
+            code: None,
+        });
+    }
+    Ok(())
+}
+
+/// Uncompressed preset path, as written before compression was added
+fn plain_preset_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.json", name))
+}
+
+/// Path of the stored preset: the compressed file, or an older plain one
+fn preset_path(dir: &Path, name: &str) -> PathBuf {
+    let compressed = compressed_path(&plain_preset_path(dir, name));
+    if compressed.exists() {
+        compressed
+    } else {
+        plain_preset_path(dir, name)
+    }
+}
+
+/// Read a preset's JSON, decompressing if needed
+fn read_preset_file(dir: &Path, name: &str) -> std::io::Result<String> {
+    String::from_utf8(read_file(&preset_path(dir, name))?)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Names of all stored presets, sorted
+pub fn preset_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+                .filter_map(|f| {
+                    let f = f.strip_suffix(COMPRESSED_SUFFIX).unwrap_or(&f);
+                    f.strip_suffix(".json").map(|n| n.to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Check one preset's raw contents against the current schema
+pub fn check_preset(name: &str, contents: &str) -> PresetHealth {
+    let raw: Value = match serde_json::from_str(contents) {
+        Ok(v) => v,
+        Err(e) => {
+            return PresetHealth {
+                name: name.to_string(),
+                status: PresetStatus::NeedsRepair,
+                details: vec![format!("Not valid JSON: {}", e)],
+                permissive: false,
+            };
+        }
+    };
+    let permissive = raw.pointer("/_editor/permissive_write").is_some();
+    let health = |status, details| PresetHealth {
+        name: name.to_string(),
+        status,
+        details,
+        permissive,
+    };
+
+    let config: MidiCaptainConfig = match serde_json::from_value(raw.clone()) {
+        Ok(c) => c,
+        Err(e) => return health(PresetStatus::NeedsRepair, vec![format!("Does not match the config schema: {}", e)]),
+    };
+    if let Err(errors) = config.validate() {
+        return health(PresetStatus::NeedsRepair, issue_messages(errors));
+    }
+
+    // Defaults filled in on load are harmless; anything dropped or rewritten
+    // would be lost on the next save
+    let normalized = match serde_json::to_value(&config) {
+        Ok(v) => v,
+        Err(e) => return health(PresetStatus::NeedsRepair, vec![e.to_string()]),
+    };
+    let details: Vec<String> = diff_values(&raw, &normalized)
+        .into_iter()
+        .filter_map(|c| match c.kind {
+            ChangeKind::Added => None,
+            ChangeKind::Removed => Some(format!("{}: no longer supported", c.path)),
+            ChangeKind::Changed => Some(format!("{}: will be rewritten", c.path)),
+        })
+        .collect();
+    if details.is_empty() {
+        health(PresetStatus::Ok, details)
+    } else {
+        health(PresetStatus::NeedsMigration, details)
+    }
+}
+
+/// Check every preset in `dir`
+pub fn check_library(dir: &Path) -> LibraryHealth {
+    let presets = preset_names(dir)
+        .into_iter()
+        .map(|name| match read_preset_file(dir, &name) {
+            Ok(contents) => check_preset(&name, &contents),
+            Err(e) => PresetHealth {
+                name,
+                status: PresetStatus::NeedsRepair,
+                details: vec![format!("Could not read file: {}", e)],
+                permissive: false,
+            },
+        })
+        .collect();
+    LibraryHealth {
+        checked_at: unix_now(),
+        presets,
+    }
+}
+
+/// Startup task: check the library in the background and emit
+/// "library-health" if anything needs attention
+pub fn spawn_startup_check(app: AppHandle) {
+    std::thread::spawn(move || {
+        let Ok(dir) = presets_dir(&app) else { return };
+        let health = check_library(&dir);
+        if health.issue_count() > 0 {
+            let _ = app.emit("library-health", health.clone());
+        }
+        *LIBRARY_HEALTH.lock().unwrap() = Some(health);
+    });
+}
+
+/// List stored presets
+#[command]
+pub fn list_presets(app: AppHandle) -> Result<Vec<String>, ConfigError> {
+    Ok(preset_names(&presets_dir(&app)?))
+}
+
+pub(crate) fn read_preset(dir: &Path, name: &str) -> Result<MidiCaptainConfig, ConfigError> {
+    check_name(name)?;
+    let contents = read_preset_file(dir, name).map_err(|e| ConfigError {
+        message: format!("Could not read preset '{}': {}", name, e),
+        details: None,
+        code: None,
+    })?;
+    let mut value = serde_json::from_str(&contents)?;
+    crate::migrations::migrate(&mut value)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Load a stored preset
+#[command]
+pub fn load_preset(app: AppHandle, name: String) -> Result<MidiCaptainConfig, ConfigError> {
+    read_preset(&presets_dir(&app)?, &name)
+}
+
+fn store_preset(dir: &Path, name: &str, config: &MidiCaptainConfig) -> Result<(), ConfigError> {
+    check_name(name)?;
+    config.validate().map_err(|errors| ConfigError {
+        message: "Validation failed".to_string(),
+        details: Some(issue_messages(errors)),
+        code: None,
+    })?;
+    let json = serde_json::to_string_pretty(config)?;
+    fs::write(compressed_path(&plain_preset_path(dir, name)), compress(json.as_bytes())?)?;
+    // Replace any uncompressed copy from before compression was added
+    let plain = plain_preset_path(dir, name);
+    if plain.exists() {
+        fs::remove_file(plain)?;
+    }
+    Ok(())
+}
+
+/// Save a config as a preset, replacing one with the same name
+#[command]
+pub fn save_preset(app: AppHandle, name: String, config: MidiCaptainConfig) -> Result<(), ConfigError> {
+    store_preset(&presets_dir(&app)?, &name, &config)
+}
+
+/// Save the config currently on a device as a preset. `device` is the config
+/// path, as for `read_config`.
+#[command]
+pub fn save_preset_from_device(app: AppHandle, name: String, device: String) -> Result<(), ConfigError> {
+    let config = commands::read_config(device)?.config;
+    store_preset(&presets_dir(&app)?, &name, &config)
+}
+
+/// Presets whose name, description, song, notes or tags contain `query`
+/// and that carry all of `tags`; every preset when both are empty
+#[command]
+pub fn search_presets(
+    app: AppHandle,
+    query: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<Vec<PresetEntry>, ConfigError> {
+    let names = preset_names(&presets_dir(&app)?);
+    let index = load_index(&app_data_dir(&app)?);
+    Ok(index.search(&names, query.as_deref().unwrap_or(""), &tags.unwrap_or_default()))
+}
+
+/// Delete a stored preset
+#[command]
+pub fn delete_preset(app: AppHandle, name: String) -> Result<(), ConfigError> {
+    check_name(&name)?;
+    let plain = plain_preset_path(&presets_dir(&app)?, &name);
+    for path in [compressed_path(&plain), plain] {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
+    let data_dir = app_data_dir(&app)?;
+    let mut index = load_index(&data_dir);
+    index.forget(&name);
+    save_index(&data_dir, &index)
+}
+
+/// Favorites and quick-deploy slots
+#[command]
+pub fn get_library_index(app: AppHandle) -> Result<LibraryIndex, ConfigError> {
+    Ok(load_index(&app_data_dir(&app)?))
+}
+
+/// Pin or unpin a preset as a favorite
+#[command]
+pub fn set_favorite(app: AppHandle, name: String, favorite: bool) -> Result<LibraryIndex, ConfigError> {
+    check_name(&name)?;
+    let data_dir = app_data_dir(&app)?;
+    let mut index = load_index(&data_dir);
+    index.set_favorite(&name, favorite);
+    save_index(&data_dir, &index)?;
+    Ok(index)
+}
+
+/// Assign a preset to a quick-deploy slot (1-9), or clear the slot
+#[command]
+pub fn set_quick_deploy_slot(app: AppHandle, slot: u8, name: Option<String>) -> Result<LibraryIndex, ConfigError> {
+    if let Some(ref name) = name {
+        check_name(name)?;
+        if !preset_path(&presets_dir(&app)?, name).exists() {
+            return Err(ConfigError {
+                message: format!("No preset named '{}'", name),
+                details: None,
+                code: None,
+            });
+        }
+    }
+    let data_dir = app_data_dir(&app)?;
+    let mut index = load_index(&data_dir);
+    index.set_slot(slot, name)?;
+    save_index(&data_dir, &index)?;
+    Ok(index)
+}
+
+/// Set a preset's song, key, tempo and notes; empty metadata clears them
+#[command]
+pub fn set_preset_metadata(app: AppHandle, name: String, metadata: PresetMetadata) -> Result<LibraryIndex, ConfigError> {
+    check_name(&name)?;
+    if !preset_path(&presets_dir(&app)?, &name).exists() {
+        return Err(ConfigError {
+            message: format!("No preset named '{}'", name),
+            details: None,
+            code: None,
+        });
+    }
+    let data_dir = app_data_dir(&app)?;
+    let mut index = load_index(&data_dir);
+    index.set_metadata(&name, metadata)?;
+    save_index(&data_dir, &index)?;
+    Ok(index)
+}
+
+/// Write a preset to a device's config.json. `device` is the config path, as
+/// for `write_config`.
+pub(crate) fn deploy_preset(
+    app: &AppHandle,
+    name: &str,
+    device: String,
+    force: Option<bool>,
+    confirmation: Option<String>,
+) -> Result<Confirmable<()>, ConfigError> {
+    let config = read_preset(&presets_dir(app)?, name)?;
+    Ok(commands::write_config(device, config, force, confirmation, None, None)?.map(drop))
+}
+
+/// Write a stored preset to a device's config.json. `device` is the config
+/// path, as for `write_config`; overwriting a locked config takes `force`
+/// and a `confirmation` token.
+#[command]
+pub fn push_preset(
+    app: AppHandle,
+    name: String,
+    device: String,
+    force: Option<bool>,
+    confirmation: Option<String>,
+) -> Result<Confirmable<()>, ConfigError> {
+    deploy_preset(&app, &name, device, force, confirmation)
+}
+
+/// Write the preset in a quick-deploy slot to a device's config.json.
+/// `device` is the config path, as for `write_config`. Returns the preset name.
+#[command]
+pub fn quick_deploy(
+    app: AppHandle,
+    slot: u8,
+    device: String,
+    force: Option<bool>,
+    confirmation: Option<String>,
+) -> Result<Confirmable<String>, ConfigError> {
+    let name = load_index(&app_data_dir(&app)?)
+        .slots
+        .get(&slot)
+        .cloned()
+        .ok_or_else(|| ConfigError {
+            message: format!("Quick-deploy slot {} is empty", slot),
+            details: None,
+            code: None,
+        })?;
+    Ok(deploy_preset(&app, &name, device, force, confirmation)?.map(|()| name))
+}
+
+/// Health of the preset library. Returns the startup check unless `refresh`
+/// is set or it hasn't finished yet, in which case the library is rechecked.
+#[command]
+pub fn get_library_health(app: AppHandle, refresh: Option<bool>) -> Result<LibraryHealth, ConfigError> {
+    if !refresh.unwrap_or(false) {
+        if let Some(health) = LIBRARY_HEALTH.lock().unwrap().clone() {
+            return Ok(health);
+        }
+    }
+    let health = check_library(&presets_dir(&app)?);
+    *LIBRARY_HEALTH.lock().unwrap() = Some(health.clone());
+    Ok(health)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"{
+        "device": "mini6",
+        "buttons": [
+            {"label": "1", "cc": 20, "color": "red"},
+            {"label": "2", "cc": 21, "color": "red"},
+            {"label": "3", "cc": 22, "color": "red"},
+            {"label": "4", "cc": 23, "color": "red"},
+            {"label": "5", "cc": 24, "color": "red"},
+            {"label": "6", "cc": 25, "color": "red"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_current_preset_is_ok() {
+        let health = check_preset("ok", VALID);
+        assert_eq!(health.status, PresetStatus::Ok, "{:?}", health.details);
+        assert!(!health.permissive);
+    }
+
+    #[test]
+    fn test_permissive_presets_are_flagged() {
+        let flagged = VALID.replacen(r#""device": "mini6","#, r#""device": "mini6", "_editor": {"permissive_write": 1},"#, 1);
+        assert!(check_preset("dev", &flagged).permissive);
+    }
+
+    #[test]
+    fn test_dropped_fields_need_migration() {
+        let old = VALID.replacen(r#""device": "mini6","#, r#""device": "mini6", "led_mode": "legacy","#, 1);
+        let health = check_preset("old", &old);
+        assert_eq!(health.status, PresetStatus::NeedsMigration);
+        assert_eq!(health.details, vec!["led_mode: no longer supported".to_string()]);
+    }
+
+    #[test]
+    fn test_broken_presets_need_repair() {
+        assert_eq!(check_preset("a", "{not json").status, PresetStatus::NeedsRepair);
+        assert_eq!(check_preset("b", r#"{"device": "mini6", "buttons": []}"#).status, PresetStatus::NeedsRepair);
+    }
+
+    #[test]
+    fn test_check_library_reads_every_preset() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("good.json"), VALID).unwrap();
+        fs::write(dir.path().join("bad.json"), "[]").unwrap();
+        fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+        fs::write(dir.path().join("packed.json.zst"), compress(VALID.as_bytes()).unwrap()).unwrap();
+        let health = check_library(dir.path());
+        let names: Vec<&str> = health.presets.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["bad", "good", "packed"]);
+        assert_eq!(health.issue_count(), 1);
+    }
+
+    #[test]
+    fn test_index_favorites_and_slots() {
+        let mut index = LibraryIndex::default();
+        index.set_favorite("Sunday", true);
+        index.set_favorite("Gig", true);
+        index.set_favorite("Sunday", true);
+        assert_eq!(index.favorites, vec!["Gig".to_string(), "Sunday".to_string()]);
+
+        index.set_slot(1, Some("Sunday".to_string())).unwrap();
+        index.set_slot(2, Some("Gig".to_string())).unwrap();
+        assert!(index.set_slot(0, Some("Gig".to_string())).is_err());
+        assert!(index.set_slot(MAX_SLOT + 1, None).is_err());
+
+        index.forget("Sunday");
+        assert_eq!(index.favorites, vec!["Gig".to_string()]);
+        assert_eq!(index.slots, BTreeMap::from([(2, "Gig".to_string())]));
+
+        index.set_slot(2, None).unwrap();
+        assert!(index.slots.is_empty());
+    }
+
+    #[test]
+    fn test_index_metadata() {
+        let mut index = LibraryIndex::default();
+        let metadata = PresetMetadata {
+            song: Some("Gloria".to_string()),
+            key: Some("E".to_string()),
+            tempo: Some(132),
+            notes: None,
+            ..Default::default()
+        };
+        index.set_metadata("Sunday", metadata.clone()).unwrap();
+        assert_eq!(index.metadata["Sunday"], metadata);
+
+        let too_fast = PresetMetadata { tempo: Some(900), ..metadata };
+        assert!(index.set_metadata("Sunday", too_fast).is_err());
+
+        index.set_metadata("Sunday", PresetMetadata::default()).unwrap();
+        assert!(index.metadata.is_empty());
+
+        index.set_metadata("Gig", PresetMetadata { key: Some("A".to_string()), ..Default::default() }).unwrap();
+        index.forget("Gig");
+        assert!(index.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_metadata_tags_are_normalized() {
+        let mut index = LibraryIndex::default();
+        let metadata = PresetMetadata {
+            tags: vec![" Worship ".to_string(), "worship".to_string(), "".to_string(), "band-b".to_string()],
+            ..Default::default()
+        };
+        index.set_metadata("Sunday", metadata).unwrap();
+        assert_eq!(index.metadata["Sunday"].tags, ["Worship", "band-b"]);
+    }
+
+    #[test]
+    fn test_search_matches_name_metadata_and_tags() {
+        let mut index = LibraryIndex::default();
+        let names = vec!["Gig".to_string(), "Sunday".to_string(), "Rehearsal".to_string()];
+        let sunday = PresetMetadata {
+            description: Some("Acoustic set with the choir".to_string()),
+            tags: vec!["worship".to_string()],
+            ..Default::default()
+        };
+        index.set_metadata("Sunday", sunday).unwrap();
+        let gig = PresetMetadata {
+            song: Some("Gloria".to_string()),
+            tags: vec!["band-b".to_string(), "Worship".to_string()],
+            ..Default::default()
+        };
+        index.set_metadata("Gig", gig).unwrap();
+        index.set_favorite("Gig", true);
+        index.set_slot(3, Some("Gig".to_string())).unwrap();
+
+        let found = |query: &str, tags: &[&str]| -> Vec<String> {
+            let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+            index.search(&names, query, &tags).into_iter().map(|e| e.name).collect()
+        };
+        assert_eq!(found("", &[]), ["Gig", "Sunday", "Rehearsal"]);
+        assert_eq!(found("CHOIR", &[]), ["Sunday"]);
+        assert_eq!(found("gloria", &[]), ["Gig"]);
+        assert_eq!(found("hears", &[]), ["Rehearsal"]);
+        assert_eq!(found("", &["worship"]), ["Gig", "Sunday"]);
+        assert_eq!(found("", &["worship", "band-b"]), ["Gig"]);
+
+        let gig = &index.search(&names, "gig", &[])[0];
+        assert!(gig.favorite);
+        assert_eq!(gig.slot, Some(3));
+    }
+
+    #[test]
+    fn test_preset_names_cannot_escape_dir() {
+        assert!(check_name("Sunday set").is_ok());
+        assert!(check_name("../config").is_err());
+        assert!(check_name("").is_err());
+    }
+}
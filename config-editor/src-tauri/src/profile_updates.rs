@@ -0,0 +1,232 @@
+//! Target profile updates
+//!
+//! Built-in target profiles (targets.rs) only change with an app release,
+//! but the CC maps of amps and modellers change with their firmware. This
+//! service fetches a refreshed set from a configurable URL, checks it
+//! against the SHA-256 published next to it (`<url>.sha256`), keeps the last
+//! good set in the app-data directory and swaps it into `targets`, so
+//! validation and lookups use it at once.
+//!
+//! The file at the URL is a JSON array of profiles in the built-in format.
+//! Runs at startup and then every `interval_hours` while a URL is set;
+//! `update_profiles` runs it on demand. A download that fails its checksum
+//! or its checks leaves the current profiles in place.
+
+use crate::commands::{app_data_dir, ConfigError};
+use crate::download::{download_cache_dir, fetch, fetch_checksum};
+use crate::firmware::unix_now;
+use crate::targets::{check_profiles, install_downloaded, TargetProfile};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tauri::{command, AppHandle, Emitter};
+
+/// File under the app-data directory holding update settings
+const SETTINGS_FILE: &str = "profile_updates.json";
+
+/// File under the app-data directory holding the last good download
+const PROFILES_FILE: &str = "downloaded_profiles.json";
+
+/// Shortest time between scheduled checks
+const MIN_INTERVAL_HOURS: u64 = 1;
+
+/// Where and how often to look for new profiles
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ProfileUpdateSettings {
+    /// https URL of the profiles file; no updates while unset
+    pub url: Option<String>,
+    pub interval_hours: u64,
+}
+
+impl Default for ProfileUpdateSettings {
+    fn default() -> Self {
+        ProfileUpdateSettings {
+            url: None,
+            interval_hours: 24,
+        }
+    }
+}
+
+/// Outcome of an update, emitted as "profiles-updated" when it changed
+/// something
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ProfileUpdate {
+    pub sha256: String,
+    /// Profiles in the downloaded set
+    pub count: usize,
+    /// False when the download matched the profiles already installed
+    pub changed: bool,
+    /// Unix timestamp (seconds)
+    pub updated_at: u64,
+}
+
+fn update_error(message: String) -> ConfigError {
+    ConfigError {
+        message,
+        details: None,
+        code: None,
+    }
+}
+
+/// Load settings from the app-data directory
+pub fn load_settings(data_dir: &Path) -> ProfileUpdateSettings {
+    fs::read_to_string(data_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn check_settings(settings: &ProfileUpdateSettings) -> Result<(), ConfigError> {
+    if let Some(url) = &settings.url {
+        if !url.starts_with("https://") {
+            return Err(update_error(format!("Profile update URL must use https: {}", url)));
+        }
+    }
+    if settings.interval_hours < MIN_INTERVAL_HOURS {
+        return Err(update_error(format!(
+            "Update interval must be at least {} hour(s)",
+            MIN_INTERVAL_HOURS
+        )));
+    }
+    Ok(())
+}
+
+/// Parse and check a downloaded profiles file
+pub fn parse_profiles(bytes: &[u8]) -> Result<Vec<TargetProfile>, ConfigError> {
+    let profiles: Vec<TargetProfile> = serde_json::from_slice(bytes).map_err(|e| {
+        update_error(format!("Downloaded profiles are not a list of target profiles: {}", e))
+    })?;
+    let problems = check_profiles(&profiles);
+    if !problems.is_empty() {
+        return Err(ConfigError {
+            message: "Downloaded profiles failed their checks".to_string(),
+            details: Some(problems),
+            code: None,
+        });
+    }
+    Ok(profiles)
+}
+
+/// Install a verified download and keep it for the next start
+pub fn apply_download(data_dir: &Path, bytes: &[u8], sha256: &str) -> Result<ProfileUpdate, ConfigError> {
+    let profiles = parse_profiles(bytes)?;
+    let path = data_dir.join(PROFILES_FILE);
+    let changed = fs::read(&path).ok().as_deref() != Some(bytes);
+    if changed {
+        crate::commands::write_sync(&path, bytes)?;
+    }
+    let count = profiles.len();
+    install_downloaded(profiles);
+    Ok(ProfileUpdate {
+        sha256: sha256.to_string(),
+        count,
+        changed,
+        updated_at: unix_now(),
+    })
+}
+
+/// Install the profiles kept from the last update, if any. Called once from
+/// `setup`; a kept file that no longer passes is ignored.
+pub fn install_saved(data_dir: &Path) {
+    let Ok(bytes) = fs::read(data_dir.join(PROFILES_FILE)) else { return };
+    if let Ok(profiles) = parse_profiles(&bytes) {
+        install_downloaded(profiles);
+    }
+}
+
+/// Download, verify and install the profiles at the configured URL
+pub fn update(app: &AppHandle) -> Result<ProfileUpdate, ConfigError> {
+    let data_dir = app_data_dir(app)?;
+    let url = load_settings(&data_dir)
+        .url
+        .ok_or_else(|| update_error("No profile update URL is set".to_string()))?;
+    let sha256 = fetch_checksum(&url)?;
+    let downloaded = fetch(&download_cache_dir(app)?, &url, Some(&sha256))?;
+    let bytes = fs::read(&downloaded.path)?;
+    apply_download(&data_dir, &bytes, &downloaded.sha256)
+}
+
+/// Scheduled task: update at startup and then every `interval_hours` while a
+/// URL is set, emitting "profiles-updated" when the profiles changed and
+/// "profile-update-failed" (with the error message) when an update fails
+pub fn spawn_scheduled(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let settings = app_data_dir(&app).map(|dir| load_settings(&dir)).unwrap_or_default();
+        if settings.url.is_some() {
+            match update(&app) {
+                Ok(update) if update.changed => {
+                    let _ = app.emit("profiles-updated", update);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    let _ = app.emit("profile-update-failed", e.message);
+                }
+            }
+        }
+        let hours = settings.interval_hours.max(MIN_INTERVAL_HOURS);
+        std::thread::sleep(Duration::from_secs(hours * 60 * 60));
+    });
+}
+
+/// Fetch the profiles at the configured URL now
+#[command]
+pub fn update_profiles(app: AppHandle) -> Result<ProfileUpdate, ConfigError> {
+    update(&app)
+}
+
+/// Current update URL and interval
+#[command]
+pub fn get_profile_update_settings(app: AppHandle) -> Result<ProfileUpdateSettings, ConfigError> {
+    Ok(load_settings(&app_data_dir(&app)?))
+}
+
+/// Change the update URL and interval; takes effect at the next check
+#[command]
+pub fn set_profile_update_settings(app: AppHandle, settings: ProfileUpdateSettings) -> Result<(), ConfigError> {
+    check_settings(&settings)?;
+    let json = serde_json::to_string_pretty(&settings)?;
+    fs::write(app_data_dir(&app)?.join(SETTINGS_FILE), json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMP: &str = r#"[{"id": "test-amp", "name": "Test Amp", "controls": [{"cc": 80, "name": "Boost", "behavior": "latching"}]}]"#;
+
+    #[test]
+    fn test_download_is_checked_before_install() {
+        assert!(parse_profiles(b"<html>").is_err());
+        assert!(parse_profiles(b"[]").unwrap_err().details.is_some());
+        let bad_cc = AMP.replace("\"cc\": 80", "\"cc\": 200");
+        assert!(parse_profiles(bad_cc.as_bytes()).is_err());
+        assert_eq!(parse_profiles(AMP.as_bytes()).unwrap()[0].id, "test-amp");
+    }
+
+    #[test]
+    fn test_apply_download_installs_and_keeps_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let update = apply_download(dir.path(), AMP.as_bytes(), "abc").unwrap();
+        assert!(update.changed);
+        assert_eq!(update.count, 1);
+        assert!(crate::targets::find_profile("test-amp").is_some());
+        assert!(crate::targets::find_profile(crate::targets::DEFAULT_PROFILE).is_some());
+
+        // Same file again: nothing changed
+        assert!(!apply_download(dir.path(), AMP.as_bytes(), "abc").unwrap().changed);
+        assert_eq!(fs::read(dir.path().join(PROFILES_FILE)).unwrap(), AMP.as_bytes());
+    }
+
+    #[test]
+    fn test_settings_checks() {
+        let mut settings = ProfileUpdateSettings::default();
+        assert!(check_settings(&settings).is_ok());
+        settings.url = Some("http://example.com/profiles.json".to_string());
+        assert!(check_settings(&settings).is_err());
+        settings.url = Some("https://example.com/profiles.json".to_string());
+        settings.interval_hours = 0;
+        assert!(check_settings(&settings).is_err());
+    }
+}
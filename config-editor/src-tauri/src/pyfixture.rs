@@ -0,0 +1,246 @@
+//! Export a config as a firmware test fixture
+//!
+//! Writes a pytest module that embeds the config and, for every button, the
+//! values the editor expects the firmware to fill in (the defaults in
+//! core/config.py's `validate_button`). Dropped into the repo's `tests/`
+//! directory, it runs the config through the firmware's own
+//! `validate_config` against the mock CircuitPython modules from
+//! `tests/conftest.py`, so a mismatch between editor and firmware shows up
+//! as a failing test.
+
+use crate::commands::ConfigError;
+use crate::config::{
+    ButtonConfig, ButtonMode, MacroStepType, MessageType, MidiCaptainConfig, OffMode, DEFAULT_MAX_BPM, DEFAULT_MIN_BPM,
+    DIM_BRIGHTNESS, MAX_MACRO_STEPS, MAX_STEP_DELAY_MS, MAX_TAP_BPM, MIN_TAP_BPM,
+};
+use serde_json::{json, Value};
+use std::fs;
+use tauri::command;
+
+/// What `validate_button` should return for `button`
+fn expected_button(index: usize, button: &ButtonConfig, global_channel: u8) -> Result<Value, ConfigError> {
+    let keytimes = button.keytimes.unwrap_or(1).clamp(1, 99);
+    let mut expected = json!({
+        "label": button.label,
+        "color": button.color,
+        "mode": button.mode,
+        "off_mode": button.off_mode,
+        "channel": button.channel.unwrap_or(global_channel),
+        "type": button.message_type,
+        "keytimes": keytimes,
+    });
+    let fields = expected.as_object_mut().expect("literal object");
+    match button.message_type {
+        MessageType::Cc => {
+            fields.insert("cc".into(), button.cc.unwrap_or((20 + index).min(127) as u8).into());
+            fields.insert("cc_on".into(), button.cc_on.unwrap_or(127).into());
+            fields.insert("cc_off".into(), button.cc_off.unwrap_or(0).into());
+        }
+        MessageType::Note => {
+            fields.insert("note".into(), button.note.unwrap_or(60).into());
+            fields.insert("velocity_on".into(), button.velocity_on.unwrap_or(127).into());
+            fields.insert("velocity_off".into(), button.velocity_off.unwrap_or(0).into());
+        }
+        MessageType::Pc => {
+            fields.insert("program".into(), button.program.unwrap_or(0).into());
+        }
+        MessageType::PcInc | MessageType::PcDec => {
+            fields.insert("pc_step".into(), button.pc_step.unwrap_or(1).into());
+        }
+        MessageType::Panic => {
+            fields.insert("reset_ccs".into(), serde_json::to_value(button.reset_ccs.as_deref().unwrap_or_default())?);
+        }
+        MessageType::Macro => {
+            let channel = button.channel.unwrap_or(global_channel);
+            let steps: Vec<Value> = button
+                .steps
+                .iter()
+                .flatten()
+                // Steps missing their number are dropped
+                .filter(|step| match step.message_type {
+                    MacroStepType::Cc => step.cc.is_some(),
+                    MacroStepType::Pc => step.program.is_some(),
+                    MacroStepType::Note => step.note.is_some(),
+                })
+                .take(MAX_MACRO_STEPS)
+                .map(|step| {
+                    let mut expected_step = json!({
+                        "type": step.message_type,
+                        "channel": step.channel.filter(|c| *c <= 15).unwrap_or(channel),
+                        "delay_ms": step.delay_ms.unwrap_or(0).min(MAX_STEP_DELAY_MS),
+                    });
+                    let step_fields = expected_step.as_object_mut().expect("literal object");
+                    match step.message_type {
+                        MacroStepType::Cc => {
+                            step_fields.insert("cc".into(), step.cc.unwrap_or(0).min(127).into());
+                            step_fields.insert("value".into(), step.value.unwrap_or(127).min(127).into());
+                        }
+                        MacroStepType::Pc => {
+                            step_fields.insert("program".into(), step.program.unwrap_or(0).min(127).into());
+                        }
+                        MacroStepType::Note => {
+                            step_fields.insert("note".into(), step.note.unwrap_or(60).min(127).into());
+                            step_fields.insert("velocity".into(), step.velocity.unwrap_or(127).min(127).into());
+                        }
+                    }
+                    expected_step
+                })
+                .collect();
+            fields.insert("steps".into(), steps.into());
+        }
+    }
+    if button.mode == ButtonMode::TapTempo {
+        // Out-of-range tempos are clamped, a reversed range replaced
+        let tap = button.tap_tempo.clone().unwrap_or_default();
+        let (min, max) = tap.bpm_range();
+        let clamp = |bpm: u16| bpm.clamp(MIN_TAP_BPM, MAX_TAP_BPM);
+        let (min, max) = match (clamp(min), clamp(max)) {
+            (min, max) if min < max => (min, max),
+            _ => (DEFAULT_MIN_BPM, DEFAULT_MAX_BPM),
+        };
+        fields.insert("tap_tempo".into(), json!({"output": tap.output, "min_bpm": min, "max_bpm": max}));
+    }
+    if let Some(led) = &button.led {
+        let off_default = match button.off_mode {
+            OffMode::Dim => DIM_BRIGHTNESS,
+            OffMode::Off => 0,
+        };
+        let mut expected_led = json!({
+            "on_brightness": led.on_brightness.unwrap_or(100).min(100),
+            "off_brightness": led.off_brightness.unwrap_or(off_default).min(100),
+        });
+        let led_fields = expected_led.as_object_mut().expect("literal object");
+        for (key, color) in [("on_color", &led.on_color), ("off_color", &led.off_color)] {
+            if let Some(color) = color {
+                led_fields.insert(key.into(), color.name().into());
+            }
+        }
+        fields.insert("led".into(), expected_led);
+    }
+    if let Some(states) = button.states.as_ref().filter(|s| keytimes > 1 && !s.is_empty()) {
+        fields.insert("states".into(), serde_json::to_value(states)?);
+    }
+    Ok(expected)
+}
+
+/// JSON text that can sit inside a Python `r'''...'''` literal
+fn python_json(value: &Value) -> Result<String, ConfigError> {
+    // `'` is still a quote to json.loads but can't end the literal
+    Ok(serde_json::to_string_pretty(value)?.replace('\'', "\\u0027"))
+}
+
+/// Render the pytest module for `config`
+pub fn render_fixture(config: &MidiCaptainConfig) -> Result<String, ConfigError> {
+    let global_channel = config.global_channel.filter(|ch| *ch <= 15).unwrap_or(0);
+    let count = config.device.button_count();
+    let expected = (0..count)
+        .map(|i| match config.buttons.get(i) {
+            Some(button) => expected_button(i, button, global_channel),
+            // The firmware pads missing buttons with its own defaults
+            None => Ok(json!({"label": (i + 1).to_string(), "channel": global_channel})),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(format!(
+        r#""""
+Firmware test fixture exported by the MIDI Captain config editor.
+
+Save this file in the repo's tests/ directory and run `pytest`. It checks
+that the firmware's validate_config() reads the config the way the editor
+does.
+"""
+
+import copy
+import json
+import sys
+from pathlib import Path
+
+import pytest
+
+FIRMWARE_DIR = Path(__file__).resolve().parent.parent / "firmware" / "dev"
+sys.path.insert(0, str(FIRMWARE_DIR))
+
+from core.config import validate_config
+
+BUTTON_COUNT = {count}
+
+EXPECTED_GLOBAL_CHANNEL = {global_channel}
+
+CONFIG = json.loads(r'''
+{config}
+''')
+
+# What the editor expects validate_button() to return for each button
+EXPECTED_BUTTONS = json.loads(r'''
+{expected}
+''')
+
+
+@pytest.fixture
+def validated():
+    return validate_config(copy.deepcopy(CONFIG), button_count=BUTTON_COUNT)
+
+
+def test_global_channel(validated):
+    assert validated["global_channel"] == EXPECTED_GLOBAL_CHANNEL
+
+
+@pytest.mark.parametrize("index", range(BUTTON_COUNT))
+def test_button_matches_editor(validated, index):
+    button = validated["buttons"][index]
+    for key, value in EXPECTED_BUTTONS[index].items():
+        assert button.get(key) == value, f"buttons[{{index}}].{{key}}"
+"#,
+        count = count,
+        global_channel = global_channel,
+        config = python_json(&serde_json::to_value(config)?)?,
+        expected = python_json(&Value::Array(expected))?,
+    ))
+}
+
+/// Export the config as a pytest module for the firmware's test suite
+#[command]
+pub fn export_python_fixture(config: MidiCaptainConfig, path: String) -> Result<(), ConfigError> {
+    fs::write(&path, render_fixture(&config)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> MidiCaptainConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_expected_buttons_use_firmware_defaults() {
+        let config = config(
+            r#"{"device": "mini6", "global_channel": 4, "buttons": [
+                {"label": "DLY", "color": "red"},
+                {"label": "SYN", "color": "blue", "type": "note", "channel": 9},
+                {"label": "UP", "color": "green", "type": "pc_inc", "pc_step": 2}
+            ]}"#,
+        );
+        let dly = expected_button(0, &config.buttons[0], 4).unwrap();
+        assert_eq!(dly["cc"], 20);
+        assert_eq!(dly["channel"], 4);
+        assert_eq!(dly["mode"], "toggle");
+        let syn = expected_button(1, &config.buttons[1], 4).unwrap();
+        assert_eq!(syn["note"], 60);
+        assert_eq!(syn["channel"], 9);
+        assert!(syn.get("cc").is_none());
+        let up = expected_button(2, &config.buttons[2], 4).unwrap();
+        assert_eq!(up["type"], "pc_inc");
+        assert_eq!(up["pc_step"], 2);
+    }
+
+    #[test]
+    fn test_fixture_literals_survive_quotes() {
+        let config = config(r#"{"device": "mini6", "buttons": [{"label": "it'''s", "color": "red"}]}"#);
+        let fixture = render_fixture(&config).unwrap();
+        assert!(fixture.contains("BUTTON_COUNT = 6"));
+        assert!(fixture.contains(r"it\u0027\u0027\u0027s"));
+        assert!(!fixture.contains("it'''s"));
+    }
+}
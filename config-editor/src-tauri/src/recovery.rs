@@ -0,0 +1,267 @@
+//! Named recovery points
+//!
+//! A recovery point captures a device's firmware and `config.json` under a
+//! name ("Friday gig") so the pedal can be put back exactly as it was. Unlike
+//! the rollback slot, which holds only the previous firmware and is replaced
+//! on every install, recovery points restore both firmware and config and are
+//! kept until deleted; maintenance never prunes them.
+//!
+//! Each point is a directory under `recovery/` in the app-data directory: a
+//! firmware slot (as for rollback), the compressed config and a manifest.
+
+use crate::archive::{compress, compressed_path, decompress};
+use crate::commands::{app_data_dir, validate_device_path, verify_device_connected, write_sync, ConfigError};
+use crate::confirm::{confirm, Confirmable};
+use crate::device::get_volume_name;
+use crate::firmware::{restore_firmware_exact, stash_firmware, unix_now};
+use crate::presets::check_name;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+
+/// Directory under the app-data directory holding recovery points
+const RECOVERY_DIR: &str = "recovery";
+
+/// Manifest file inside each point
+const MANIFEST_FILE: &str = "recovery.json";
+
+/// Firmware slot inside each point
+const FIRMWARE_DIR: &str = "firmware";
+
+const CONFIG_FILE: &str = "config.json";
+
+/// A named capture of a device's firmware and config
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecoveryPoint {
+    pub name: String,
+    /// Volume name of the device it was captured from
+    pub device: Option<String>,
+    /// Unix timestamp (seconds)
+    pub created_at: u64,
+    /// Firmware version from the device's VERSION file, if present
+    pub firmware_version: Option<String>,
+    /// The device had a config.json
+    pub has_config: bool,
+}
+
+pub fn recovery_root(data_dir: &Path) -> PathBuf {
+    data_dir.join(RECOVERY_DIR)
+}
+
+fn read_point(point_dir: &Path) -> Option<RecoveryPoint> {
+    let manifest = fs::read_to_string(point_dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&manifest).ok()
+}
+
+/// Capture `device_root` into `point_dir`, replacing any point already there.
+/// The capture is staged next to it so a failure leaves the old point intact.
+pub fn create_point(device_root: &Path, point_dir: &Path, name: &str) -> io::Result<RecoveryPoint> {
+    // Names can't start with '.', so the staging directory never collides with a point
+    let staging = point_dir.with_file_name(format!(".{}.partial", name));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+
+    let slot = stash_firmware(device_root, &staging.join(FIRMWARE_DIR))?;
+    let config = device_root.join(CONFIG_FILE);
+    let has_config = config.is_file();
+    if has_config {
+        fs::write(
+            compressed_path(&staging.join(CONFIG_FILE)),
+            compress(&fs::read(&config)?)?,
+        )?;
+    }
+
+    let point = RecoveryPoint {
+        name: name.to_string(),
+        device: get_volume_name(device_root),
+        created_at: unix_now(),
+        firmware_version: slot.version,
+        has_config,
+    };
+    fs::write(staging.join(MANIFEST_FILE), serde_json::to_string_pretty(&point)?)?;
+
+    if point_dir.exists() {
+        fs::remove_dir_all(point_dir)?;
+    }
+    fs::rename(&staging, point_dir)?;
+    Ok(point)
+}
+
+/// Put `device_root` back to the state captured in `point_dir`.
+///
+/// The config goes first and the firmware after it (with `code.py` last), so
+/// the device never boots new firmware against the old config.
+pub fn restore_point(point_dir: &Path, device_root: &Path) -> io::Result<RecoveryPoint> {
+    let point = read_point(point_dir)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Recovery point not found"))?;
+
+    let config = device_root.join(CONFIG_FILE);
+    if point.has_config {
+        let bytes = decompress(&fs::read(compressed_path(&point_dir.join(CONFIG_FILE)))?)?;
+        write_sync(&config, &bytes)?;
+    } else if config.exists() {
+        fs::remove_file(&config)?;
+    }
+    crate::config_cache::invalidate(&config);
+
+    restore_firmware_exact(&point_dir.join(FIRMWARE_DIR), device_root)?;
+    Ok(point)
+}
+
+/// Recovery points under `root`, newest first
+pub fn list_points(root: &Path) -> Vec<RecoveryPoint> {
+    let mut points: Vec<RecoveryPoint> = fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| read_point(&e.path()))
+        .collect();
+    points.sort_by_key(|p| std::cmp::Reverse(p.created_at));
+    points
+}
+
+fn point_dir(app: &AppHandle, name: &str) -> Result<PathBuf, ConfigError> {
+    check_name(name)?;
+    Ok(recovery_root(&app_data_dir(app)?).join(name))
+}
+
+/// Capture the device's firmware and config as a named recovery point
+#[command]
+pub fn create_recovery_point(app: AppHandle, device_path: String, name: String) -> Result<RecoveryPoint, ConfigError> {
+    let device_root = &validate_device_path(&device_path)?;
+    verify_device_connected(device_root)?;
+
+    create_point(device_root, &point_dir(&app, &name)?, &name).map_err(|e| ConfigError {
+        message: format!("Failed to create recovery point: {}", e),
+        details: None,
+        code: None,
+    })
+}
+
+/// List recovery points, newest first
+#[command]
+pub fn list_recovery_points(app: AppHandle) -> Result<Vec<RecoveryPoint>, ConfigError> {
+    Ok(list_points(&recovery_root(&app_data_dir(&app)?)))
+}
+
+/// Return the device to exactly the state captured in a recovery point.
+/// Takes a `confirmation` token: everything on the device is replaced.
+#[command]
+pub fn restore_recovery_point(
+    app: AppHandle,
+    device_path: String,
+    name: String,
+    confirmation: Option<String>,
+) -> Result<Confirmable<RecoveryPoint>, ConfigError> {
+    let device_root = &validate_device_path(&device_path)?;
+    verify_device_connected(device_root)?;
+    let dir = point_dir(&app, &name)?;
+    let point = read_point(&dir).ok_or_else(|| not_found(&name))?;
+
+    let target = device_root.display().to_string();
+    confirm(
+        &["restore_recovery_point", &name, &target],
+        || {
+            format!(
+                "Replace the firmware and config on {} with recovery point '{}' (firmware {})",
+                target,
+                name,
+                point.firmware_version.as_deref().unwrap_or("of unknown version")
+            )
+        },
+        confirmation.as_deref(),
+        || {
+            restore_point(&dir, device_root).map_err(|e| ConfigError {
+                message: format!("Failed to restore recovery point '{}': {}", name, e),
+                details: None,
+                code: None,
+            })
+        },
+    )
+}
+
+fn not_found(name: &str) -> ConfigError {
+    ConfigError {
+        message: format!("Recovery point '{}' not found", name),
+        details: None,
+        code: None,
+    }
+}
+
+/// Delete a recovery point. Takes a `confirmation` token.
+#[command]
+pub fn delete_recovery_point(
+    app: AppHandle,
+    name: String,
+    confirmation: Option<String>,
+) -> Result<Confirmable<()>, ConfigError> {
+    let dir = point_dir(&app, &name)?;
+    if !dir.exists() {
+        return Err(not_found(&name));
+    }
+    confirm(
+        &["delete_recovery_point", &name],
+        || format!("Delete recovery point '{}'", name),
+        confirmation.as_deref(),
+        || Ok(fs::remove_dir_all(&dir)?),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_device(root: &Path, version: &str, config: &str) {
+        fs::write(root.join("code.py"), format!("# code {}", version)).unwrap();
+        fs::write(root.join("VERSION"), version).unwrap();
+        fs::create_dir_all(root.join("core")).unwrap();
+        fs::write(root.join("core").join("config.py"), "# config").unwrap();
+        fs::write(root.join(CONFIG_FILE), config).unwrap();
+    }
+
+    #[test]
+    fn test_restore_returns_device_to_captured_state() {
+        let device = tempfile::tempdir().unwrap();
+        let store = tempfile::tempdir().unwrap();
+        let point_dir = store.path().join("Friday gig");
+        make_device(device.path(), "1.2.0", r#"{"buttons": []}"#);
+        let point = create_point(device.path(), &point_dir, "Friday gig").unwrap();
+        assert_eq!(point.firmware_version.as_deref(), Some("1.2.0"));
+        assert!(point.has_config);
+
+        make_device(device.path(), "1.3.0", r#"{"buttons": [{"label": "NEW"}]}"#);
+        fs::write(device.path().join("core").join("added.py"), "# new").unwrap();
+
+        restore_point(&point_dir, device.path()).unwrap();
+        assert_eq!(fs::read_to_string(device.path().join("VERSION")).unwrap(), "1.2.0");
+        assert_eq!(fs::read_to_string(device.path().join(CONFIG_FILE)).unwrap(), r#"{"buttons": []}"#);
+        assert!(!device.path().join("core").join("added.py").exists());
+    }
+
+    #[test]
+    fn test_recreate_replaces_point_and_list_skips_staging() {
+        let device = tempfile::tempdir().unwrap();
+        let store = tempfile::tempdir().unwrap();
+        make_device(device.path(), "1.2.0", "{}");
+        create_point(device.path(), &store.path().join("gig"), "gig").unwrap();
+        make_device(device.path(), "1.3.0", "{}");
+        create_point(device.path(), &store.path().join("gig"), "gig").unwrap();
+        fs::create_dir_all(store.path().join(".other.partial")).unwrap();
+
+        let points = list_points(store.path());
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].firmware_version.as_deref(), Some("1.3.0"));
+    }
+
+    #[test]
+    fn test_missing_point_is_not_found() {
+        let device = tempfile::tempdir().unwrap();
+        let store = tempfile::tempdir().unwrap();
+        let err = restore_point(&store.path().join("nope"), device.path()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
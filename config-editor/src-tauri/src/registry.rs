@@ -0,0 +1,201 @@
+//! Per-device settings and the write log
+//!
+//! Settings for each device the editor writes to, keyed by volume name, in
+//! `devices.json` under the app-data directory. Every config write is also
+//! appended to `write_log.jsonl` with the verification level it used.
+//!
+//! Writes also come from scripts, which have no app handle, so the app-data
+//! directory is installed once at startup (as for crash reports). Until then
+//! (and in tests) devices get the default settings and nothing is logged.
+
+use crate::commands::{app_data_dir, get_volume_path, ConfigError};
+use crate::device::get_volume_name;
+use crate::firmware::unix_now;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{command, AppHandle};
+
+/// File under the app-data directory holding per-device settings
+const REGISTRY_FILE: &str = "devices.json";
+
+/// File under the app-data directory holding the write log (one JSON entry per line)
+const WRITE_LOG_FILE: &str = "write_log.jsonl";
+
+/// Entries kept in the write log; older ones are dropped
+const MAX_LOG_ENTRIES: usize = 500;
+
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// How a config write is checked after it lands on the device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerifyLevel {
+    /// Trust the write
+    None,
+    /// Read the file back and compare its SHA-256 with what was written
+    #[default]
+    Hash,
+    /// Read the file back, decode it and compare it with the written config
+    Full,
+}
+
+/// Settings for one device
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DeviceSettings {
+    #[serde(default)]
+    pub verification: VerifyLevel,
+}
+
+/// One config write
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WriteLogEntry {
+    /// Unix timestamp (seconds)
+    pub timestamp: u64,
+    pub path: PathBuf,
+    /// Volume name of the device written to
+    pub device: Option<String>,
+    pub verification: VerifyLevel,
+    pub ok: bool,
+    pub error: Option<String>,
+    /// Problems a permissive-mode write let through
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+}
+
+/// Set the app-data directory used by the write path (once, at startup)
+pub fn install(data_dir: PathBuf) {
+    let _ = DATA_DIR.set(data_dir);
+}
+
+/// The app-data directory, once installed
+pub fn data_dir() -> Option<&'static Path> {
+    DATA_DIR.get().map(PathBuf::as_path)
+}
+
+/// Registry key for the device holding `path`: its volume name
+pub fn device_key(path: &Path) -> Option<String> {
+    get_volume_name(&get_volume_path(path)?)
+}
+
+pub fn load_registry(data_dir: &Path) -> BTreeMap<String, DeviceSettings> {
+    fs::read_to_string(data_dir.join(REGISTRY_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_registry(data_dir: &Path, registry: &BTreeMap<String, DeviceSettings>) -> Result<(), ConfigError> {
+    fs::write(data_dir.join(REGISTRY_FILE), serde_json::to_string_pretty(registry)?)?;
+    Ok(())
+}
+
+/// Verification level for a write to `path`
+pub fn verification_for(path: &Path) -> VerifyLevel {
+    let (Some(dir), Some(key)) = (DATA_DIR.get(), device_key(path)) else {
+        return VerifyLevel::default();
+    };
+    load_registry(dir).remove(&key).unwrap_or_default().verification
+}
+
+/// Append an entry to the write log in `data_dir`, trimming the oldest
+pub fn append_write_log(data_dir: &Path, entry: &WriteLogEntry) -> std::io::Result<()> {
+    let path = data_dir.join(WRITE_LOG_FILE);
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    drop(file);
+
+    let contents = fs::read_to_string(&path)?;
+    let lines: Vec<&str> = contents.lines().collect();
+    if lines.len() > MAX_LOG_ENTRIES {
+        let kept = lines[lines.len() - MAX_LOG_ENTRIES..].join("\n");
+        fs::write(&path, kept + "\n")?;
+    }
+    Ok(())
+}
+
+/// Record a config write (when the app-data directory is installed), with
+/// the problems a permissive write let through. The log is best-effort: a
+/// write isn't failed for want of a log entry.
+pub fn log_write<T>(path: &Path, verification: VerifyLevel, result: &Result<T, ConfigError>, problems: &[String]) {
+    let Some(dir) = DATA_DIR.get() else { return };
+    let entry = WriteLogEntry {
+        timestamp: unix_now(),
+        path: path.to_path_buf(),
+        device: device_key(path),
+        verification,
+        ok: result.is_ok(),
+        error: result.as_ref().err().map(|e| e.message.clone()),
+        problems: problems.to_vec(),
+    };
+    let _ = append_write_log(dir, &entry);
+}
+
+/// Load the write log in `data_dir`, newest first
+pub fn load_write_log(data_dir: &Path) -> Vec<WriteLogEntry> {
+    let contents = fs::read_to_string(data_dir.join(WRITE_LOG_FILE)).unwrap_or_default();
+    contents.lines().rev().filter_map(|line| serde_json::from_str(line).ok()).collect()
+}
+
+/// Settings for a device, by volume name
+#[command]
+pub fn get_device_settings(app: AppHandle, device: String) -> Result<DeviceSettings, ConfigError> {
+    Ok(load_registry(&app_data_dir(&app)?).remove(&device).unwrap_or_default())
+}
+
+/// Change the settings for a device, by volume name
+#[command]
+pub fn set_device_settings(app: AppHandle, device: String, settings: DeviceSettings) -> Result<(), ConfigError> {
+    let dir = app_data_dir(&app)?;
+    let mut registry = load_registry(&dir);
+    registry.insert(device, settings);
+    save_registry(&dir, &registry)
+}
+
+/// Config writes, newest first
+#[command]
+pub fn get_write_log(app: AppHandle) -> Result<Vec<WriteLogEntry>, ConfigError> {
+    Ok(load_write_log(&app_data_dir(&app)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: u64) -> WriteLogEntry {
+        WriteLogEntry {
+            timestamp,
+            path: PathBuf::from("/Volumes/CIRCUITPY/config.json"),
+            device: Some("CIRCUITPY".to_string()),
+            verification: VerifyLevel::Full,
+            ok: true,
+            error: None,
+            problems: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_write_log_is_newest_first_and_trimmed() {
+        let dir = tempfile::tempdir().unwrap();
+        for t in 0..(MAX_LOG_ENTRIES as u64 + 3) {
+            append_write_log(dir.path(), &entry(t)).unwrap();
+        }
+        let log = load_write_log(dir.path());
+        assert_eq!(log.len(), MAX_LOG_ENTRIES);
+        assert_eq!(log[0].timestamp, MAX_LOG_ENTRIES as u64 + 2);
+        assert_eq!(log[0].verification, VerifyLevel::Full);
+        assert_eq!(log.last().unwrap().timestamp, 3);
+    }
+
+    #[test]
+    fn test_registry_defaults_to_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_registry(dir.path()).is_empty());
+        fs::write(dir.path().join(REGISTRY_FILE), r#"{"SLOWPEDAL": {"verification": "none"}, "MIDICAPTAIN": {}}"#).unwrap();
+        let registry = load_registry(dir.path());
+        assert_eq!(registry["SLOWPEDAL"].verification, VerifyLevel::None);
+        assert_eq!(registry["MIDICAPTAIN"].verification, VerifyLevel::Hash);
+    }
+}
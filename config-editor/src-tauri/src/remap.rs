@@ -0,0 +1,254 @@
+//! MIDI channel remapping
+//!
+//! Touring rigs sometimes have to move to a different channel at a venue
+//! ("everything on 1 goes to 4 tonight"). `remap_channels` rewrites every
+//! control's channel in one step, and named remaps are stored in the app-data
+//! directory so they can be applied and reverted without editing each control.
+//!
+//! Channels use the stored 0-15 values, as in config.json.
+
+use crate::commands::{app_data_dir, ConfigError};
+use crate::config::MidiCaptainConfig;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tauri::{command, AppHandle};
+
+/// File under the app-data directory holding saved remaps
+const REMAPS_FILE: &str = "channel_remaps.json";
+
+/// Channel the firmware uses when a control has no explicit channel
+const DEFAULT_CHANNEL: u8 = 0;
+
+/// A saved, named channel remap (e.g. "Venue B: 1→4")
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ChannelRemap {
+    pub name: String,
+    /// Source channel → target channel (0-15)
+    pub mapping: BTreeMap<u8, u8>,
+}
+
+/// Check that every channel in a mapping is 0-15
+pub fn validate_mapping(mapping: &BTreeMap<u8, u8>) -> Result<(), Vec<String>> {
+    let errors: Vec<String> = mapping
+        .iter()
+        .filter(|(from, to)| **from > 15 || **to > 15)
+        .map(|(from, to)| format!("Channel mapping {}→{} is invalid (must be 1-16)", from + 1, to + 1))
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Invert a mapping so a remap can be reverted.
+/// Fails if two source channels map to the same target.
+pub fn invert_mapping(mapping: &BTreeMap<u8, u8>) -> Result<BTreeMap<u8, u8>, String> {
+    let mut inverse = BTreeMap::new();
+    for (from, to) in mapping {
+        if let Some(other) = inverse.insert(*to, *from) {
+            return Err(format!(
+                "Cannot revert: channels {} and {} both map to {}",
+                other + 1,
+                from + 1,
+                to + 1
+            ));
+        }
+    }
+    Ok(inverse)
+}
+
+/// Remap a control's channel. Unset channels are treated as the firmware
+/// default, so "1→4" also moves controls that never set a channel.
+fn remap(channel: &mut Option<u8>, mapping: &BTreeMap<u8, u8>) {
+    if let Some(to) = mapping.get(&channel.unwrap_or(DEFAULT_CHANNEL)) {
+        *channel = Some(*to);
+    }
+}
+
+/// Apply a channel mapping to every control in a config
+pub fn apply_mapping(config: &MidiCaptainConfig, mapping: &BTreeMap<u8, u8>) -> MidiCaptainConfig {
+    let mut config = config.clone();
+    // Buttons without a channel inherit global_channel (see core/config.py),
+    // so when it is set they follow its remap instead of being pinned
+    let buttons_inherit_global = config.global_channel.is_some();
+    if let Some(ch) = config.global_channel {
+        if let Some(to) = mapping.get(&ch) {
+            config.global_channel = Some(*to);
+        }
+    }
+    for button in &mut config.buttons {
+        if button.channel.is_some() || !buttons_inherit_global {
+            remap(&mut button.channel, mapping);
+        }
+        // Secondary actions without a channel follow the button
+        for action in [&mut button.long_press, &mut button.double_tap].into_iter().flatten() {
+            if action.channel.is_some() {
+                remap(&mut action.channel, mapping);
+            }
+        }
+    }
+    if let Some(enc) = &mut config.encoder {
+        remap(&mut enc.channel, mapping);
+        if let Some(push) = &mut enc.push {
+            remap(&mut push.channel, mapping);
+        }
+    }
+    if let Some(exp) = &mut config.expression {
+        remap(&mut exp.exp1.channel, mapping);
+        remap(&mut exp.exp2.channel, mapping);
+    }
+    config
+}
+
+fn mapping_error(errors: Vec<String>) -> ConfigError {
+    ConfigError {
+        message: "Invalid channel mapping".to_string(),
+        details: Some(errors),
+        code: None,
+    }
+}
+
+/// Load saved remaps from the app-data directory
+pub fn load_remaps(data_dir: &Path) -> Vec<ChannelRemap> {
+    fs::read_to_string(data_dir.join(REMAPS_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_remaps(data_dir: &Path, remaps: &[ChannelRemap]) -> Result<(), ConfigError> {
+    let json = serde_json::to_string_pretty(remaps)?;
+    fs::write(data_dir.join(REMAPS_FILE), json)?;
+    Ok(())
+}
+
+/// Remap MIDI channels across a config (returns the new config; nothing is written)
+#[command]
+pub fn remap_channels(config: MidiCaptainConfig, mapping: BTreeMap<u8, u8>) -> Result<MidiCaptainConfig, ConfigError> {
+    validate_mapping(&mapping).map_err(mapping_error)?;
+    Ok(apply_mapping(&config, &mapping))
+}
+
+/// List saved channel remaps
+#[command]
+pub fn list_channel_remaps(app: AppHandle) -> Result<Vec<ChannelRemap>, ConfigError> {
+    Ok(load_remaps(&app_data_dir(&app)?))
+}
+
+/// Save a named channel remap, replacing one with the same name
+#[command]
+pub fn save_channel_remap(app: AppHandle, remap: ChannelRemap) -> Result<(), ConfigError> {
+    validate_mapping(&remap.mapping).map_err(mapping_error)?;
+    let data_dir = app_data_dir(&app)?;
+    let mut remaps = load_remaps(&data_dir);
+    remaps.retain(|r| r.name != remap.name);
+    remaps.push(remap);
+    save_remaps(&data_dir, &remaps)
+}
+
+/// Delete a saved channel remap
+#[command]
+pub fn delete_channel_remap(app: AppHandle, name: String) -> Result<(), ConfigError> {
+    let data_dir = app_data_dir(&app)?;
+    let mut remaps = load_remaps(&data_dir);
+    remaps.retain(|r| r.name != name);
+    save_remaps(&data_dir, &remaps)
+}
+
+/// Apply (or with `revert`, undo) a saved channel remap to a config
+#[command]
+pub fn apply_channel_remap(
+    app: AppHandle,
+    config: MidiCaptainConfig,
+    name: String,
+    revert: Option<bool>,
+) -> Result<MidiCaptainConfig, ConfigError> {
+    let remap = load_remaps(&app_data_dir(&app)?)
+        .into_iter()
+        .find(|r| r.name == name)
+        .ok_or_else(|| ConfigError {
+            message: format!("No saved channel remap named '{}'", name),
+            details: None,
+            code: None,
+        })?;
+    let mapping = if revert.unwrap_or(false) {
+        invert_mapping(&remap.mapping).map_err(|e| mapping_error(vec![e]))?
+    } else {
+        remap.mapping
+    };
+    Ok(apply_mapping(&config, &mapping))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> MidiCaptainConfig {
+        serde_json::from_str(
+            r#"{
+                "device": "mini6",
+                "buttons": [
+                    {"label": "A", "cc": 20, "color": "red"},
+                    {"label": "B", "cc": 21, "color": "red", "channel": 1},
+                    {"label": "C", "cc": 22, "color": "red", "channel": 0}
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_remap_moves_explicit_and_default_channels() {
+        let mapping = BTreeMap::from([(0, 3)]);
+        let remapped = apply_mapping(&config(), &mapping);
+        assert_eq!(remapped.buttons[0].channel, Some(3));
+        assert_eq!(remapped.buttons[1].channel, Some(1));
+        assert_eq!(remapped.buttons[2].channel, Some(3));
+    }
+
+    #[test]
+    fn test_unset_button_channels_follow_global() {
+        let mut cfg = config();
+        cfg.global_channel = Some(0);
+        let remapped = apply_mapping(&cfg, &BTreeMap::from([(0, 3)]));
+        assert_eq!(remapped.global_channel, Some(3));
+        assert_eq!(remapped.buttons[0].channel, None);
+        assert_eq!(remapped.buttons[2].channel, Some(3));
+    }
+
+    #[test]
+    fn test_revert_restores_channels() {
+        let mapping = BTreeMap::from([(0, 3), (1, 4)]);
+        let remapped = apply_mapping(&config(), &mapping);
+        let reverted = apply_mapping(&remapped, &invert_mapping(&mapping).unwrap());
+        assert_eq!(reverted.buttons[0].channel, Some(0));
+        assert_eq!(reverted.buttons[1].channel, Some(1));
+    }
+
+    #[test]
+    fn test_non_invertible_mapping_rejected() {
+        let mapping = BTreeMap::from([(0, 3), (1, 3)]);
+        assert!(invert_mapping(&mapping).is_err());
+    }
+
+    #[test]
+    fn test_validate_mapping_range() {
+        assert!(validate_mapping(&BTreeMap::from([(0, 15)])).is_ok());
+        assert!(validate_mapping(&BTreeMap::from([(0, 16)])).is_err());
+    }
+
+    #[test]
+    fn test_remaps_roundtrip_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let remaps = vec![ChannelRemap {
+            name: "Venue B".to_string(),
+            mapping: BTreeMap::from([(0, 3)]),
+        }];
+        save_remaps(dir.path(), &remaps).unwrap();
+        let loaded = load_remaps(dir.path());
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].mapping.get(&0), Some(&3));
+    }
+}
@@ -0,0 +1,360 @@
+//! Repair of corrupted config files
+//!
+//! Hand-edited configs on the device break in a few predictable ways:
+//! trailing commas, comments, a save cut short, a misspelled key, a value of
+//! the wrong type. `repair_config` reads such a file leniently, fixes what it
+//! can and lists every fix, so the user reviews the result before writing
+//! it. Nothing is written here.
+//!
+//! Fixes, in order:
+//! 1. Text: byte-order mark, `//` and `/* */` comments and trailing commas
+//!    removed; an unterminated string and unclosed brackets closed.
+//! 2. Structure: unknown fields and fields with an unusable value removed
+//!    (their defaults apply), a missing `device` inferred from the button
+//!    count, missing or broken buttons replaced with factory defaults and the
+//!    button count matched to the device.
+
+use crate::commands::{validate_device_path, ConfigError};
+use crate::config::{default_button, generate_default_config, ButtonConfig, DeviceType, MidiCaptainConfig};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::fs;
+use tauri::command;
+
+/// Top-level fields of a config
+const CONFIG_FIELDS: &[&str] = &[
+    "device",
+    "version",
+    "global_channel",
+    "usb_drive_name",
+    "dev_mode",
+    "buttons",
+    "encoder",
+    "expression",
+    "display",
+    "routing",
+    "_editor",
+];
+
+/// Fields of a button
+const BUTTON_FIELDS: &[&str] = &[
+    "label",
+    "color",
+    "type",
+    "mode",
+    "off_mode",
+    "channel",
+    "cc",
+    "cc_on",
+    "cc_off",
+    "note",
+    "velocity_on",
+    "velocity_off",
+    "program",
+    "pc_step",
+    "reset_ccs",
+    "flash_ms",
+    "keytimes",
+    "states",
+];
+
+/// One thing `repair_config` changed
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ConfigFix {
+    /// Where: "line 12" for text fixes, a field path such as `buttons[3].cc`
+    /// for the rest
+    pub location: String,
+    pub message: String,
+}
+
+/// A repaired config, for the UI to review before saving
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RepairedConfig {
+    pub config: MidiCaptainConfig,
+    /// Empty when the file was fine
+    pub fixes: Vec<ConfigFix>,
+}
+
+fn fix(fixes: &mut Vec<ConfigFix>, location: impl Into<String>, message: impl Into<String>) {
+    fixes.push(ConfigFix {
+        location: location.into(),
+        message: message.into(),
+    });
+}
+
+/// Drop a comma followed only by whitespace from the end of `out`
+fn drop_trailing_comma(out: &mut String) -> bool {
+    let trimmed = out.trim_end().len();
+    if out[..trimmed].ends_with(',') {
+        out.remove(trimmed - 1);
+        true
+    } else {
+        false
+    }
+}
+
+/// Turn relaxed JSON into strict JSON (text fixes, step 1)
+pub fn relax_json(text: &str, fixes: &mut Vec<ConfigFix>) -> String {
+    let text = match text.strip_prefix('\u{feff}') {
+        Some(rest) => {
+            fix(fixes, "line 1", "Removed byte-order mark");
+            rest
+        }
+        None => text,
+    };
+    let mut out = String::with_capacity(text.len());
+    let mut open: Vec<char> = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            line += 1;
+        }
+        if in_string {
+            out.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                fix(fixes, format!("line {}", line), "Removed comment");
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                fix(fixes, format!("line {}", line), "Removed comment");
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '{' | '[' => {
+                open.push(if c == '{' { '}' } else { ']' });
+                out.push(c);
+            }
+            '}' | ']' => {
+                if drop_trailing_comma(&mut out) {
+                    fix(fixes, format!("line {}", line), "Removed trailing comma");
+                }
+                if open.last() == Some(&c) {
+                    open.pop();
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+
+    if in_string {
+        out.push('"');
+        fix(fixes, "end of file", "Closed unterminated string");
+    }
+    if !open.is_empty() {
+        drop_trailing_comma(&mut out);
+        fix(fixes, "end of file", format!("Closed {} unclosed bracket(s); the file looks cut short", open.len()));
+        while let Some(close) = open.pop() {
+            out.push(close);
+        }
+    }
+    out
+}
+
+fn is_valid<T: DeserializeOwned>(value: Value) -> bool {
+    serde_json::from_value::<T>(value).is_ok()
+}
+
+/// Remove keys of `obj` not in `known`, and keys whose value `T` can't take
+/// when set on `base` (a valid value of `T`)
+fn drop_bad_fields<T: DeserializeOwned>(
+    obj: &mut Map<String, Value>,
+    known: &[&str],
+    base: &Value,
+    prefix: &str,
+    fixes: &mut Vec<ConfigFix>,
+) {
+    let keys: Vec<String> = obj.keys().cloned().collect();
+    for key in keys {
+        let location = format!("{}{}", prefix, key);
+        if !known.contains(&key.as_str()) {
+            obj.remove(&key);
+            fix(fixes, location, "Removed unknown field");
+            continue;
+        }
+        let mut candidate = base.clone();
+        candidate[key.as_str()] = obj[&key].clone();
+        if !is_valid::<T>(candidate) {
+            obj.remove(&key);
+            fix(fixes, location, "Removed invalid value; the default applies");
+        }
+    }
+}
+
+/// Device named by the config, or inferred from its buttons
+fn repair_device(obj: &mut Map<String, Value>, fixes: &mut Vec<ConfigFix>) -> DeviceType {
+    if let Some(device) = obj.get("device").and_then(|d| serde_json::from_value::<DeviceType>(d.clone()).ok()) {
+        return device;
+    }
+    let buttons = obj.get("buttons").and_then(Value::as_array).map_or(0, Vec::len);
+    let device = DeviceType::ALL
+        .into_iter()
+        .find(|d| d.button_count() == buttons)
+        .unwrap_or_default();
+    let name = serde_json::to_value(&device).unwrap_or_default();
+    fix(fixes, "device", format!("Missing or unknown device; set to {} from the button count", name));
+    obj.insert("device".to_string(), name);
+    device
+}
+
+fn repair_buttons(obj: &mut Map<String, Value>, device: &DeviceType, fixes: &mut Vec<ConfigFix>) -> Result<(), ConfigError> {
+    let count = device.button_count();
+    let mut buttons = match obj.remove("buttons") {
+        Some(Value::Array(buttons)) => buttons,
+        _ => {
+            fix(fixes, "buttons", "Missing buttons; replaced with factory defaults");
+            Vec::new()
+        }
+    };
+    if buttons.len() > count {
+        fix(fixes, "buttons", format!("Removed {} button(s) beyond the device's {}", buttons.len() - count, count));
+        buttons.truncate(count);
+    }
+    for (i, button) in buttons.iter_mut().enumerate() {
+        let prefix = format!("buttons[{}].", i);
+        let default = serde_json::to_value(&default_button(i))?;
+        let Value::Object(fields) = button else {
+            fix(fixes, format!("buttons[{}]", i), "Not a button; replaced with the default");
+            *button = default;
+            continue;
+        };
+        drop_bad_fields::<ButtonConfig>(fields, BUTTON_FIELDS, &default, &prefix, fixes);
+        for key in ["label", "color"] {
+            if !fields.contains_key(key) {
+                fields.insert(key.to_string(), default[key].clone());
+                fix(fixes, format!("{}{}", prefix, key), format!("Missing; set to {}", default[key]));
+            }
+        }
+    }
+    if buttons.len() < count {
+        if !buttons.is_empty() {
+            fix(fixes, "buttons", format!("Added {} default button(s) to make {}", count - buttons.len(), count));
+        }
+        for i in buttons.len()..count {
+            buttons.push(serde_json::to_value(&default_button(i))?);
+        }
+    }
+    obj.insert("buttons".to_string(), Value::Array(buttons));
+    Ok(())
+}
+
+/// Repair a config's text (steps 1 and 2). Fails only when the text isn't
+/// a JSON object even after the text fixes.
+pub fn repair(text: &str) -> Result<RepairedConfig, ConfigError> {
+    let mut fixes = Vec::new();
+    let strict = relax_json(text, &mut fixes);
+    let value: Value = serde_json::from_str(&strict).map_err(|e| ConfigError {
+        message: format!("Config could not be repaired: {}", e),
+        details: Some(fixes.iter().map(|f| format!("{}: {}", f.location, f.message)).collect()),
+        code: None,
+    })?;
+    let Value::Object(mut obj) = value else {
+        return Err(ConfigError {
+            message: "Config could not be repaired: it is not a JSON object".to_string(),
+            details: None,
+            code: None,
+        });
+    };
+
+    let device = repair_device(&mut obj, &mut fixes);
+    repair_buttons(&mut obj, &device, &mut fixes)?;
+    let mut base = serde_json::to_value(&generate_default_config(device))?;
+    base["buttons"] = obj["buttons"].clone();
+    drop_bad_fields::<MidiCaptainConfig>(&mut obj, CONFIG_FIELDS, &base, "", &mut fixes);
+
+    let config = serde_json::from_value(Value::Object(obj))?;
+    Ok(RepairedConfig { config, fixes })
+}
+
+/// Read a (possibly broken) config and repair it. Nothing is written; the
+/// UI shows the config and the fixes for review before saving.
+#[command]
+pub fn repair_config(path: String) -> Result<RepairedConfig, ConfigError> {
+    let path = validate_device_path(&path)?;
+    let bytes = fs::read(&path)?;
+    repair(&String::from_utf8_lossy(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locations(fixes: &[ConfigFix]) -> Vec<&str> {
+        fixes.iter().map(|f| f.location.as_str()).collect()
+    }
+
+    #[test]
+    fn test_relax_trailing_commas_and_comments() {
+        let mut fixes = Vec::new();
+        let text = "{\n  // my pedalboard\n  \"a\": [1, 2,],\n  \"b\": \"x // not a comment,}\",\n}\n";
+        let strict = relax_json(text, &mut fixes);
+        assert_eq!(strict, "{\n  \n  \"a\": [1, 2],\n  \"b\": \"x // not a comment,}\"\n}\n");
+        assert_eq!(locations(&fixes), vec!["line 2", "line 3", "line 5"]);
+    }
+
+    #[test]
+    fn test_relax_closes_truncated_file() {
+        let mut fixes = Vec::new();
+        let strict = relax_json("{\"buttons\": [{\"label\": \"OD", &mut fixes);
+        assert_eq!(strict, "{\"buttons\": [{\"label\": \"OD\"}]}");
+        assert_eq!(fixes.len(), 2);
+    }
+
+    #[test]
+    fn test_repair_fills_and_drops_fields() {
+        let text = r#"{
+            "device": "mini6",
+            "buttons": [
+                {"label": "OD", "color": "red", "cc": 300, "colour": "blue"},
+                {"color": "green", "cc": 21},
+            ],
+            "encoder": "yes",
+        }"#;
+        let repaired = repair(text).unwrap();
+        let config = repaired.config;
+        assert_eq!(config.buttons.len(), 6);
+        assert_eq!(config.buttons[0].label, "OD");
+        assert_eq!(config.buttons[0].cc, None);
+        assert_eq!(config.buttons[1].label, "2");
+        assert!(config.encoder.is_none());
+        let fixed = locations(&repaired.fixes);
+        for location in ["buttons[0].cc", "buttons[0].colour", "buttons[1].label", "buttons", "encoder"] {
+            assert!(fixed.contains(&location), "no fix at {}", location);
+        }
+    }
+
+    #[test]
+    fn test_valid_config_needs_no_fixes() {
+        let text = serde_json::to_string_pretty(&generate_default_config(DeviceType::Std10)).unwrap();
+        assert!(repair(&text).unwrap().fixes.is_empty());
+    }
+}
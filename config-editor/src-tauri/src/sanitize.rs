@@ -0,0 +1,123 @@
+//! Path argument sanitation
+//!
+//! Two layers, both ahead of any per-command logic:
+//!
+//! - `check_invoke_args` runs in the invoke handler for every command and
+//!   refuses path arguments carrying NUL or other control characters, so no
+//!   command ever sees one.
+//! - `resolve` turns a path argument into the one path a command works on:
+//!   control characters refused, Unicode NFC-normalized (macOS hands out
+//!   decomposed names, users type composed ones), trailing separators
+//!   stripped and symlinks resolved. `validate_device_path` starts here and
+//!   returns the resolved path, so the path that was checked is the path
+//!   that is written.
+
+use crate::commands::ConfigError;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+/// `ConfigError::code` of a refused path
+pub const INVALID_PATH: &str = "invalid_path";
+
+/// Command arguments (as the frontend names them) that carry a path or a
+/// volume name
+const PATH_ARGS: &[&str] = &["path", "devicePath", "pathA", "pathB", "device", "bundlePath"];
+
+fn invalid_path(message: String) -> ConfigError {
+    ConfigError {
+        message,
+        details: None,
+        code: Some(INVALID_PATH),
+    }
+}
+
+/// Refuse NUL and other control characters (tabs and newlines included:
+/// no volume or file on the device has them)
+pub fn check_chars(path: &str) -> Result<(), ConfigError> {
+    match path.chars().find(|c| c.is_control()) {
+        Some(c) => Err(invalid_path(format!(
+            "Path contains a control character (U+{:04X}): {}",
+            c as u32,
+            path.escape_debug()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// NFC form of `text`, for comparing names typed by the user with names
+/// read from the filesystem
+pub fn nfc(text: &str) -> String {
+    text.nfc().collect()
+}
+
+/// True if two volume names are the same, ignoring ASCII case and Unicode
+/// composition
+pub fn same_name(a: &str, b: &str) -> bool {
+    nfc(a).eq_ignore_ascii_case(&nfc(b))
+}
+
+/// Check characters, normalize to NFC and strip trailing separators (a
+/// bare root such as "/" or "C:\" is left alone)
+pub fn normalize(path: &str) -> Result<String, ConfigError> {
+    check_chars(path)?;
+    let mut path = nfc(path);
+    while path.len() > 1 && path.ends_with(std::path::is_separator) && !path.ends_with(":\\") {
+        path.pop();
+    }
+    Ok(path)
+}
+
+/// `normalize`, then resolve `..` and symlinks. The path must exist.
+pub fn resolve(path: &str) -> Result<PathBuf, ConfigError> {
+    let normalized = normalize(path)?;
+    Path::new(&normalized).canonicalize().map_err(|e| ConfigError {
+        message: format!("Path is neither a file nor a directory: {}", e),
+        details: None,
+        code: None,
+    })
+}
+
+/// Invoke middleware: refuse a command whose path arguments carry control
+/// characters
+pub fn check_invoke_args(args: &Value) -> Result<(), ConfigError> {
+    for name in PATH_ARGS {
+        if let Some(path) = args.get(*name).and_then(Value::as_str) {
+            check_chars(path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_control_characters_refused() {
+        for path in ["/Volumes/CIRCUITPY\0/config.json", "/Volumes/CIRCUITPY/\nconfig.json", "\u{7f}"] {
+            assert_eq!(check_chars(path).unwrap_err().code, Some(INVALID_PATH));
+        }
+        assert!(check_chars("/Volumes/MIDI CAPTAIN/config.json").is_ok());
+    }
+
+    #[test]
+    fn test_trailing_separators_stripped() {
+        assert_eq!(normalize("/Volumes/CIRCUITPY//").unwrap(), "/Volumes/CIRCUITPY");
+        assert_eq!(normalize("/").unwrap(), "/");
+    }
+
+    #[test]
+    fn test_names_compare_composed_and_decomposed() {
+        // "Café" typed (composed) vs as read from an HFS+ volume (decomposed)
+        assert!(same_name("Caf\u{e9}", "CAFe\u{301}"));
+        assert!(!same_name("CIRCUITPY", "MIDICAPTAIN"));
+    }
+
+    #[test]
+    fn test_invoke_args_checked() {
+        assert!(check_invoke_args(&json!({"devicePath": "/Volumes/CIRCUITPY\0"})).is_err());
+        assert!(check_invoke_args(&json!({"path": "/Volumes/CIRCUITPY/config.json", "name": "a\tb"})).is_ok());
+    }
+}
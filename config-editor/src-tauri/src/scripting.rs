@@ -0,0 +1,170 @@
+//! Embedded automation scripts (Rhai)
+//!
+//! Power users can chain the editor's operations in a small script instead of
+//! clicking through the UI, e.g. "on Fridays, load the worship setlist onto
+//! every connected board". The engine is sandboxed: no file or network access
+//! beyond the functions registered here, and hard limits on operations, call
+//! depth and data size so a runaway script can't hang the app.
+//!
+//! Script API:
+//! - `devices()` → array of detected devices (same shape as `scan_devices`)
+//! - `read_config(path)` → config map
+//! - `write_config(path, config)` → validates, then writes to the device (locked configs are refused)
+//! - `validate(config)` → array of error strings (empty when valid)
+//! - `weekday()` → "monday" … "sunday" (UTC)
+//! - `print(..)` → appended to the script output
+
+use crate::commands::{self, ConfigError};
+use crate::confirm::Confirmable;
+use crate::config::MidiCaptainConfig;
+use crate::device::scan_devices;
+use crate::firmware::unix_now;
+use rhai::serde::{from_dynamic, to_dynamic};
+use rhai::{Array, Dynamic, Engine, EvalAltResult};
+use std::sync::{Arc, Mutex};
+use tauri::command;
+
+/// Maximum operations a single script run may perform
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Maximum function call nesting
+const MAX_CALL_LEVELS: usize = 32;
+
+/// Maximum size of any string, array or map a script builds
+const MAX_DATA_SIZE: usize = 64 * 1024;
+
+const WEEKDAYS: &[&str] = &["thursday", "friday", "saturday", "sunday", "monday", "tuesday", "wednesday"];
+
+/// Result of `run_script`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ScriptOutput {
+    /// Value of the script's last expression (null for unit)
+    pub result: serde_json::Value,
+    /// Lines written with `print`/`debug`
+    pub output: Vec<String>,
+}
+
+fn script_error(e: ConfigError) -> Box<EvalAltResult> {
+    match e.details {
+        Some(details) if !details.is_empty() => format!("{}: {}", e.message, details.join("; ")).into(),
+        _ => e.message.into(),
+    }
+}
+
+fn config_from_dynamic(value: &Dynamic) -> Result<MidiCaptainConfig, Box<EvalAltResult>> {
+    from_dynamic(value).map_err(|e| format!("Invalid config: {}", e).into())
+}
+
+/// Day of the week (UTC) for a Unix timestamp
+pub fn weekday_name(unix_secs: u64) -> &'static str {
+    // 1970-01-01 was a Thursday
+    WEEKDAYS[((unix_secs / 86_400) % 7) as usize]
+}
+
+/// Build a sandboxed engine with the editor API registered.
+/// Printed lines are appended to `output`.
+pub fn build_engine(output: Arc<Mutex<Vec<String>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_string_size(MAX_DATA_SIZE)
+        .set_max_array_size(MAX_DATA_SIZE)
+        .set_max_map_size(MAX_DATA_SIZE);
+    engine.disable_symbol("eval");
+
+    let print_output = output.clone();
+    engine.on_print(move |s| print_output.lock().unwrap().push(s.to_string()));
+    engine.on_debug(move |s, _, _| output.lock().unwrap().push(s.to_string()));
+
+    engine.register_fn("devices", || -> Result<Dynamic, Box<EvalAltResult>> { to_dynamic(scan_devices()) });
+    engine.register_fn("read_config", |path: &str| -> Result<Dynamic, Box<EvalAltResult>> {
+        let config = commands::read_config(path.to_string()).map_err(script_error)?.config;
+        to_dynamic(config)
+    });
+    engine.register_fn("write_config", |path: &str, config: Dynamic| -> Result<(), Box<EvalAltResult>> {
+        let config = config_from_dynamic(&config)?;
+        commands::write_config(path.to_string(), config, None, None, None, None)
+            .and_then(Confirmable::into_result)
+            .map(drop)
+            .map_err(script_error)
+    });
+    engine.register_fn("validate", |config: Dynamic| -> Result<Array, Box<EvalAltResult>> {
+        let config = config_from_dynamic(&config)?;
+        Ok(match config.validate() {
+            Ok(()) => Array::new(),
+            Err(errors) => errors.into_iter().map(|e| Dynamic::from(e.message)).collect(),
+        })
+    });
+    engine.register_fn("weekday", || weekday_name(unix_now()).to_string());
+    engine
+}
+
+/// Evaluate a script, returning its result and printed output
+pub fn evaluate(source: &str) -> Result<ScriptOutput, ConfigError> {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let engine = build_engine(output.clone());
+    let result = engine.eval::<Dynamic>(source);
+    let output = output.lock().unwrap().clone();
+
+    let value = result.map_err(|e| ConfigError {
+        message: format!("Script error: {}", e),
+        details: Some(output.clone()),
+        code: None,
+    })?;
+    let result = if value.is_unit() {
+        serde_json::Value::Null
+    } else {
+        from_dynamic(&value).map_err(|e| ConfigError {
+            message: format!("Script returned an unsupported value: {}", e),
+            details: None,
+            code: None,
+        })?
+    };
+    Ok(ScriptOutput { result, output })
+}
+
+/// Run an automation script in the sandboxed engine
+#[command]
+pub fn run_script(source: String) -> Result<ScriptOutput, ConfigError> {
+    evaluate(&source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weekday_name() {
+        assert_eq!(weekday_name(0), "thursday");
+        // 2026-01-02 00:00 UTC
+        assert_eq!(weekday_name(1_767_312_000), "friday");
+    }
+
+    #[test]
+    fn test_print_is_captured_and_result_returned() {
+        let out = evaluate(r#"print("hello"); 40 + 2"#).unwrap();
+        assert_eq!(out.output, vec!["hello".to_string()]);
+        assert_eq!(out.result, serde_json::json!(42));
+    }
+
+    #[test]
+    fn test_validate_reports_config_errors() {
+        let out = evaluate(r#"validate(#{ device: "mini6", buttons: [] })"#).unwrap();
+        let errors = out.result.as_array().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].as_str().unwrap().contains("Expected 6 buttons"));
+    }
+
+    #[test]
+    fn test_runaway_script_is_stopped() {
+        let err = evaluate("loop { }").unwrap_err();
+        assert!(err.message.starts_with("Script error"));
+    }
+
+    #[test]
+    fn test_write_rejects_paths_off_device() {
+        let err = evaluate(r#"write_config("/tmp/config.json", #{ device: "mini6", buttons: [] })"#).unwrap_err();
+        assert!(err.message.starts_with("Script error"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Config corpus self-test
+//!
+//! Real-world configs are embedded in the binary so an installed app can
+//! verify it still parses everything it should (`run_self_test`), and so the
+//! same files serve as unit-test fixtures and fuzzing seeds. The corpus is the
+//! firmware's example configs plus anonymized user submissions under
+//! `src-tauri/corpus/` (see the README there).
+
+use crate::commands::{check_config, ConfigFormat, Strictness};
+use serde_json::Value;
+use tauri::command;
+
+/// Embedded corpus: (name, contents)
+pub const CORPUS: &[(&str, &str)] = &[
+    ("firmware/config.json", include_str!("../../../firmware/dev/config.json")),
+    ("firmware/config-mini6.json", include_str!("../../../firmware/dev/config-mini6.json")),
+    ("firmware/config-example-all-types.json", include_str!("../../../firmware/dev/config-example-all-types.json")),
+    ("firmware/config-example-channel.json", include_str!("../../../firmware/dev/config-example-channel.json")),
+    ("firmware/config-example-keytimes.json", include_str!("../../../firmware/dev/config-example-keytimes.json")),
+    (
+        "firmware/config-example-mini6-keytimes.json",
+        include_str!("../../../firmware/dev/config-example-mini6-keytimes.json"),
+    ),
+    ("firmware/config-test-off-mode.json", include_str!("../../../firmware/dev/config-test-off-mode.json")),
+    ("firmware/experiments/config.json", include_str!("../../../firmware/dev/experiments/config.json")),
+];
+
+const FORMATS: &[ConfigFormat] = &[ConfigFormat::Json, ConfigFormat::MessagePack, ConfigFormat::Cbor];
+
+/// A corpus file the app failed on
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SelfTestFailure {
+    pub name: String,
+    pub errors: Vec<String>,
+}
+
+/// Result of `run_self_test`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SelfTestReport {
+    pub total: usize,
+    pub passed: usize,
+    pub failures: Vec<SelfTestFailure>,
+}
+
+/// Check one config: it must pass normal validation and survive a round trip
+/// through every storage format unchanged
+pub fn check_corpus_entry(contents: &str) -> Vec<String> {
+    let value: Value = match serde_json::from_str(contents) {
+        Ok(value) => value,
+        Err(e) => return vec![format!("Invalid JSON: {}", e)],
+    };
+    let config = match check_config(&value, Strictness::Normal) {
+        Ok((Some(config), _)) => config,
+        Ok((None, problems)) => return problems,
+        Err(e) => return e.details.unwrap_or_else(|| vec![e.message]),
+    };
+    let expected = match serde_json::to_value(&config) {
+        Ok(expected) => expected,
+        Err(e) => return vec![format!("Serialize failed: {}", e)],
+    };
+
+    let mut errors = Vec::new();
+    for format in FORMATS {
+        match format.encode(&config).and_then(|bytes| format.decode::<Value>(&bytes)) {
+            Ok(decoded) if decoded == expected => {}
+            Ok(_) => errors.push(format!("{:?} round trip changed the config", format)),
+            Err(e) => errors.push(e.message),
+        }
+    }
+    errors
+}
+
+/// Feed arbitrary bytes through format detection, decoding and validation at
+/// every strictness. Must never panic; used by the fuzz target.
+#[cfg(any(feature = "fuzzing", test))]
+pub fn check_bytes(data: &[u8]) {
+    let Ok(value) = ConfigFormat::detect(data).decode::<Value>(data) else {
+        return;
+    };
+    for strictness in [Strictness::Strict, Strictness::Normal, Strictness::Permissive] {
+        if let Ok((Some(config), _)) = check_config(&value, strictness) {
+            for format in FORMATS {
+                let _ = format.encode(&config);
+            }
+        }
+    }
+}
+
+/// Run every corpus config through the installed app's parser and validator
+#[command]
+pub fn run_self_test() -> SelfTestReport {
+    let failures: Vec<SelfTestFailure> = CORPUS
+        .iter()
+        .filter_map(|(name, contents)| {
+            let errors = check_corpus_entry(contents);
+            (!errors.is_empty()).then(|| SelfTestFailure {
+                name: name.to_string(),
+                errors,
+            })
+        })
+        .collect();
+    SelfTestReport {
+        total: CORPUS.len(),
+        passed: CORPUS.len() - failures.len(),
+        failures,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corpus_passes() {
+        let report = run_self_test();
+        assert!(report.failures.is_empty(), "{:?}", report.failures);
+        assert_eq!(report.passed, CORPUS.len());
+    }
+
+    #[test]
+    fn test_invalid_entry_is_reported() {
+        assert!(!check_corpus_entry("{\"buttons\": ").is_empty());
+        assert!(!check_corpus_entry("{\"buttons\": [{\"cc\": 300}]}").is_empty());
+    }
+
+    #[test]
+    fn test_check_bytes_tolerates_garbage() {
+        for data in [&b""[..], b"\xde\xff", b"\xa1\x61", b"{\"buttons\": 5}", b"\xef\xbb\xbf[]"] {
+            check_bytes(data);
+        }
+    }
+}
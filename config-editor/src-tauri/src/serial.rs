@@ -0,0 +1,600 @@
+//! CircuitPython USB CDC serial console access
+//!
+//! The device exposes its REPL/console as a USB CDC serial port alongside the
+//! CIRCUITPY drive. Watching it after a write or firmware install is the only
+//! way to see a traceback when code.py crashes on boot. An interactive REPL
+//! session (`connect_serial`) keeps the port open for live troubleshooting.
+
+use serialport::{SerialPort, SerialPortType};
+use std::io;
+use std::sync::mpsc::{self, Sender, TryRecvError};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter};
+
+/// USB vendor IDs used by CircuitPython builds for RP2040 boards (Adafruit, Raspberry Pi)
+pub const CIRCUITPYTHON_USB_VIDS: &[u16] = &[0x239A, 0x2E8A];
+
+/// CDC ignores the baud rate, but the OS API requires one
+const BAUD_RATE: u32 = 115_200;
+
+/// Read timeout for a single poll of the port
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Default time to wait for a traceback before declaring the boot healthy
+const DEFAULT_BOOT_TIMEOUT_MS: u64 = 8000;
+
+const TRACEBACK_HEADER: &str = "Traceback (most recent call last):";
+
+/// Line prefix of the firmware's live-parameter protocol (firmware/dev/core/live.py)
+const LIVE_PREFIX: &str = "@live";
+
+/// How long to wait for the firmware to acknowledge a live parameter
+const LIVE_REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What CircuitPython prints each time it (re)starts code.py
+const CODE_START_MARKER: &str = "code.py output:";
+
+/// Default time to wait for code.py to restart after `reboot_device`
+const DEFAULT_REBOOT_TIMEOUT_MS: u64 = 10_000;
+
+/// File in the device root the firmware polls; a change in its content makes
+/// the firmware reload (see `check_reboot_sentinel` in firmware/dev/code.py)
+pub const REBOOT_SENTINEL: &str = ".reboot";
+
+/// The open REPL session, if any
+static REPL: Mutex<Option<ReplSession>> = Mutex::new(None);
+
+/// Id of the next REPL session, so a reader thread can tell its own session
+/// from one opened after it on the same port
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// An open REPL connection: the write half, and the signal that stops the
+/// thread streaming the read half as "serial-output" events
+struct ReplSession {
+    id: u64,
+    writer: Box<dyn SerialPort>,
+    shutdown: Sender<()>,
+}
+
+/// Control keys the REPL session can send
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplKey {
+    /// Ctrl-C: stop code.py and drop to the `>>>` prompt
+    Interrupt,
+    /// Ctrl-D: soft reboot (reruns code.py, or leaves the `>>>` prompt)
+    SoftReboot,
+}
+
+impl ReplKey {
+    pub fn byte(self) -> u8 {
+        match self {
+            ReplKey::Interrupt => 0x03,
+            ReplKey::SoftReboot => 0x04,
+        }
+    }
+}
+
+/// A serial port that looks like a CircuitPython console
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ConsolePort {
+    pub port_name: String,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+}
+
+/// List serial ports belonging to CircuitPython devices
+pub fn find_console_ports() -> Vec<ConsolePort> {
+    let Ok(ports) = serialport::available_ports() else { return Vec::new() };
+    ports
+        .into_iter()
+        .filter_map(|p| match p.port_type {
+            SerialPortType::UsbPort(info) if CIRCUITPYTHON_USB_VIDS.contains(&info.vid) => Some(ConsolePort {
+                port_name: p.port_name,
+                product: info.product,
+                serial_number: info.serial_number,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Use the given port, or the first CircuitPython console found
+pub fn resolve_port(port: Option<String>) -> Result<String, String> {
+    match port {
+        Some(name) => Ok(name),
+        None => find_console_ports()
+            .into_iter()
+            .next()
+            .map(|p| p.port_name)
+            .ok_or_else(|| "No CircuitPython serial console found".to_string()),
+    }
+}
+
+/// Open a console port with a short read timeout
+pub fn open_port(port_name: &str) -> Result<Box<dyn SerialPort>, String> {
+    serialport::new(port_name, BAUD_RATE)
+        .timeout(READ_TIMEOUT)
+        .open()
+        .map_err(|e| format!("Could not open {}: {}", port_name, e))
+}
+
+/// Interrupt the running program (Ctrl-C) and soft-reload (Ctrl-D).
+///
+/// boot.py disables autoreload, so after a write the firmware keeps running
+/// the old code until something triggers a reload.
+pub fn soft_reload(port: &mut dyn SerialPort) -> io::Result<()> {
+    port.write_all(b"\x03")?;
+    std::thread::sleep(Duration::from_millis(200));
+    port.write_all(b"\x04")?;
+    port.flush()
+}
+
+/// What the console output says about the boot so far
+#[derive(Debug, Clone, PartialEq)]
+pub enum BootOutcome {
+    /// No failure seen (yet)
+    Pending,
+    /// code.py raised an uncaught exception
+    Crashed { traceback: String, error: String },
+}
+
+/// Scan accumulated console output for a complete CircuitPython traceback.
+///
+/// A traceback is complete once the unindented `SomeError: message` line
+/// following the indented `File ...` lines has arrived.
+pub fn scan_boot_output(output: &str) -> BootOutcome {
+    let Some(start) = output.rfind(TRACEBACK_HEADER) else { return BootOutcome::Pending };
+    let tail = &output[start..];
+    let mut lines = Vec::new();
+    for line in tail.lines() {
+        let line = line.trim_end_matches('\r');
+        lines.push(line);
+        if lines.len() > 1 && !line.is_empty() && !line.starts_with(char::is_whitespace) {
+            return BootOutcome::Crashed {
+                traceback: lines.join("\n"),
+                error: line.to_string(),
+            };
+        }
+    }
+    BootOutcome::Pending
+}
+
+/// Payload of the "boot-failed" event
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BootFailure {
+    pub port: String,
+    pub traceback: String,
+    pub error: String,
+    pub suggestion: String,
+}
+
+/// Suggested recovery for a crash, based on the exception type
+pub fn boot_failure_suggestion(error: &str) -> String {
+    if error.starts_with("ImportError") {
+        "A library or firmware module is missing. Check firmware dependencies, or roll back to the previous firmware.".to_string()
+    } else if error.starts_with("MemoryError") {
+        "The device ran out of memory. Reduce the config size, or roll back to the previous firmware.".to_string()
+    } else if error.starts_with("ValueError") || error.starts_with("KeyError") || error.starts_with("TypeError") {
+        "The firmware rejected the config. Restore a previous config, or roll back to the previous firmware.".to_string()
+    } else {
+        "The firmware crashed on boot. Roll back to the previous firmware to recover.".to_string()
+    }
+}
+
+/// Read console output until a traceback appears or the deadline passes
+fn watch_port(port: &mut dyn SerialPort, timeout: Duration) -> io::Result<BootOutcome> {
+    let deadline = Instant::now() + timeout;
+    let mut output = String::new();
+    let mut buf = [0u8; 1024];
+    while Instant::now() < deadline {
+        match port.read(&mut buf) {
+            Ok(0) => {}
+            Ok(n) => {
+                output.push_str(&String::from_utf8_lossy(&buf[..n]));
+                let outcome = scan_boot_output(&output);
+                if outcome != BootOutcome::Pending {
+                    return Ok(outcome);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(BootOutcome::Pending)
+}
+
+/// Watch the device's serial console after a write or firmware install.
+///
+/// Runs in the background and emits "boot-failed" (with the captured traceback
+/// and a recovery suggestion) if code.py crashes within the timeout, otherwise
+/// "boot-ok". With `reload`, a soft reload is triggered first so the new
+/// firmware/config actually runs. Returns the port being watched.
+#[command]
+pub fn watch_device_boot(
+    app: AppHandle,
+    port: Option<String>,
+    timeout_ms: Option<u64>,
+    reload: Option<bool>,
+) -> Result<String, String> {
+    let port_name = resolve_port(port)?;
+    let mut serial = open_port(&port_name)?;
+    if reload.unwrap_or(false) {
+        soft_reload(serial.as_mut()).map_err(|e| e.to_string())?;
+    }
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_BOOT_TIMEOUT_MS));
+
+    let watched = port_name.clone();
+    std::thread::spawn(move || match watch_port(serial.as_mut(), timeout) {
+        Ok(BootOutcome::Crashed { traceback, error }) => {
+            let _ = app.emit(
+                "boot-failed",
+                BootFailure {
+                    port: watched,
+                    suggestion: boot_failure_suggestion(&error),
+                    traceback,
+                    error,
+                },
+            );
+        }
+        Ok(BootOutcome::Pending) => {
+            let _ = app.emit("boot-ok", watched);
+        }
+        Err(e) => {
+            let _ = app.emit("boot-watch-error", e.to_string());
+        }
+    });
+
+    Ok(port_name)
+}
+
+/// Build the console line that sets a live parameter, e.g. `@live led.brightness 0.5`
+pub fn live_command_line(path: &str, value: &serde_json::Value) -> String {
+    format!("{} {} {}\r\n", LIVE_PREFIX, path, value)
+}
+
+/// Write a live-parameter line to the console without waiting for the reply
+pub fn send_live_command(port: &mut dyn SerialPort, path: &str, value: &serde_json::Value) -> io::Result<()> {
+    port.write_all(live_command_line(path, value).as_bytes())?;
+    port.flush()
+}
+
+/// Find the firmware's acknowledgement for `path` in console output.
+///
+/// Returns `None` until a reply arrives, then `Ok` or the firmware's error message.
+pub fn parse_live_reply(output: &str, path: &str) -> Option<Result<(), String>> {
+    let ok = format!("{} ok {}", LIVE_PREFIX, path);
+    let err = format!("{} err {} ", LIVE_PREFIX, path);
+    for line in output.lines() {
+        let line = line.trim_end_matches('\r');
+        if line == ok {
+            return Some(Ok(()));
+        }
+        if let Some(message) = line.strip_prefix(&err) {
+            return Some(Err(format!("{}: {}", path, message)));
+        }
+    }
+    None
+}
+
+/// Change a parameter on the running firmware without writing config.json,
+/// so a setting (e.g. `led.brightness`, `buttons.3.color`) can be auditioned
+/// before it is saved. The change is lost on the next reload.
+#[command]
+pub fn set_live_parameter(port: Option<String>, path: String, value: serde_json::Value) -> Result<(), String> {
+    send_live_and_wait(port, &path, &value)
+}
+
+/// Show a candidate label on the device's screen for a few seconds without
+/// saving it, to check legibility at stage distance. `control` is a button
+/// path such as `buttons.3`.
+#[command]
+pub fn preview_label(port: Option<String>, control: String, text: String) -> Result<(), String> {
+    let path = format!("preview.{}.label", control);
+    send_live_and_wait(port, &path, &serde_json::Value::String(text))
+}
+
+/// Send a live-parameter line and wait for the firmware's acknowledgement
+fn send_live_and_wait(port: Option<String>, path: &str, value: &serde_json::Value) -> Result<(), String> {
+    let port_name = resolve_port(port)?;
+    let mut serial = open_port(&port_name)?;
+    send_live_command(serial.as_mut(), path, value).map_err(|e| e.to_string())?;
+
+    let deadline = Instant::now() + LIVE_REPLY_TIMEOUT;
+    let mut output = String::new();
+    let mut buf = [0u8; 256];
+    while Instant::now() < deadline {
+        match serial.read(&mut buf) {
+            Ok(n) if n > 0 => {
+                output.push_str(&String::from_utf8_lossy(&buf[..n]));
+                if let Some(result) = parse_live_reply(&output, path) {
+                    return result;
+                }
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Err("No reply from firmware (is code.py running a version with live parameter support?)".to_string())
+}
+
+/// The line sent for a REPL command; a trailing newline is added if missing
+pub fn repl_line(command: &str) -> String {
+    format!("{}\r\n", command.trim_end_matches(['\r', '\n']))
+}
+
+/// Open an interactive session on the device's console, replacing any open
+/// one. Everything the device prints arrives as "serial-output" events (text
+/// chunks, not whole lines); "serial-disconnected" fires if the port goes away.
+/// While connected, the port is busy for boot watching, live parameters and
+/// monitors. Returns the port connected.
+#[command]
+pub fn connect_serial(app: AppHandle, port: Option<String>) -> Result<String, String> {
+    let port_name = resolve_port(port)?;
+    disconnect_serial();
+    let writer = open_port(&port_name)?;
+    let mut reader = writer.try_clone().map_err(|e| format!("Could not open {}: {}", port_name, e))?;
+    let (shutdown, stop) = mpsc::channel();
+    let id = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+    // Stored before the reader starts, so a read error always finds it
+    let session = ReplSession {
+        id,
+        writer,
+        shutdown,
+    };
+    *REPL.lock().map_err(|e| e.to_string())? = Some(session);
+
+    let watched = port_name.clone();
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 1024];
+        let mut tail = String::new();
+        loop {
+            match stop.try_recv() {
+                Err(TryRecvError::Empty) => {}
+                _ => return,
+            }
+            match reader.read(&mut buf) {
+                Ok(n) if n > 0 => {
+                    let text = String::from_utf8_lossy(&buf[..n]).into_owned();
+                    let restarted = saw_restart(&mut tail, &text);
+                    let _ = app.emit("serial-output", text);
+                    if restarted {
+                        let _ = app.emit("device-rebooted", watched.clone());
+                    }
+                }
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+                Err(e) => {
+                    // Only if this thread's session is still the open one: after
+                    // a reconnect, the old reader must not close the new session
+                    let ours = REPL.lock().is_ok_and(|mut session| {
+                        let ours = session.as_ref().is_some_and(|s| s.id == id);
+                        if ours {
+                            session.take();
+                        }
+                        ours
+                    });
+                    if ours {
+                        let _ = app.emit("serial-disconnected", e.to_string());
+                    }
+                    return;
+                }
+            }
+        }
+    });
+
+    Ok(port_name)
+}
+
+/// Close the REPL session, if one is open
+#[command]
+pub fn disconnect_serial() {
+    if let Ok(mut session) = REPL.lock() {
+        if let Some(session) = session.take() {
+            let _ = session.shutdown.send(());
+        }
+    }
+}
+
+/// Write to the open REPL session
+fn write_repl(bytes: &[u8]) -> Result<(), String> {
+    let mut guard = REPL.lock().map_err(|e| e.to_string())?;
+    let session = guard.as_mut().ok_or("Not connected to the serial console")?;
+    session.writer.write_all(bytes).and_then(|_| session.writer.flush()).map_err(|e| e.to_string())
+}
+
+/// Send a line (e.g. `import os; os.listdir('/')` at the `>>>` prompt) to
+/// the open REPL session; the reply arrives as "serial-output" events
+#[command]
+pub fn send_repl(command: String) -> Result<(), String> {
+    write_repl(repl_line(&command).as_bytes())
+}
+
+/// Send a control key to the open REPL session: Ctrl-C to stop code.py and
+/// get a prompt, Ctrl-D to soft reboot
+#[command]
+pub fn send_repl_key(key: ReplKey) -> Result<(), String> {
+    write_repl(&[key.byte()])
+}
+
+/// Fold `chunk` into `tail` (the end of the output so far) and report
+/// whether code.py just (re)started. Only enough of the output to catch a
+/// marker split across chunks is kept.
+fn saw_restart(tail: &mut String, chunk: &str) -> bool {
+    tail.push_str(chunk);
+    if tail.contains(CODE_START_MARKER) {
+        tail.clear();
+        return true;
+    }
+    let keep_from = tail.len().saturating_sub(CODE_START_MARKER.len());
+    let cut = (keep_from..=tail.len()).find(|&i| tail.is_char_boundary(i)).unwrap_or(0);
+    tail.drain(..cut);
+    false
+}
+
+/// Wait until the console shows code.py restarting
+fn wait_for_restart(port: &mut dyn SerialPort, timeout: Duration) -> io::Result<bool> {
+    let deadline = Instant::now() + timeout;
+    let mut tail = String::new();
+    let mut buf = [0u8; 1024];
+    while Instant::now() < deadline {
+        match port.read(&mut buf) {
+            Ok(n) if n > 0 => {
+                if saw_restart(&mut tail, &String::from_utf8_lossy(&buf[..n])) {
+                    return Ok(true);
+                }
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(false)
+}
+
+/// How `reboot_device` restarted the firmware
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RebootMethod {
+    /// Ctrl-C, Ctrl-D on the console; "device-rebooted" follows when code.py restarts
+    Serial,
+    /// No console found, so the reboot sentinel file on the drive was touched;
+    /// the firmware reloads within a few seconds but nothing reports back
+    Sentinel,
+}
+
+/// Restart the firmware so it picks up a newly written config.json.
+///
+/// Soft-reboots over the console (the open REPL session, or `port` / the
+/// first console found) and emits "device-rebooted" with the port once
+/// code.py is running again, or "device-reboot-failed" if it doesn't restart
+/// within the timeout. Without a console, falls back to touching the reboot
+/// sentinel on the drive holding `device` (a config path, as for
+/// `write_config`).
+#[command]
+pub fn reboot_device(
+    app: AppHandle,
+    device: Option<String>,
+    port: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<RebootMethod, String> {
+    // The REPL session holds the port; its reader reports the restart
+    if REPL.lock().map_err(|e| e.to_string())?.is_some() {
+        write_repl(&[ReplKey::Interrupt.byte()])?;
+        std::thread::sleep(Duration::from_millis(200));
+        write_repl(&[ReplKey::SoftReboot.byte()])?;
+        return Ok(RebootMethod::Serial);
+    }
+
+    match resolve_port(port) {
+        Ok(port_name) => {
+            let mut serial = open_port(&port_name)?;
+            soft_reload(serial.as_mut()).map_err(|e| e.to_string())?;
+            let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_REBOOT_TIMEOUT_MS));
+            std::thread::spawn(move || match wait_for_restart(serial.as_mut(), timeout) {
+                Ok(true) => {
+                    let _ = app.emit("device-rebooted", port_name);
+                }
+                Ok(false) => {
+                    let _ = app.emit("device-reboot-failed", format!("code.py did not restart within {} s", timeout.as_secs()));
+                }
+                Err(e) => {
+                    let _ = app.emit("device-reboot-failed", e.to_string());
+                }
+            });
+            Ok(RebootMethod::Serial)
+        }
+        Err(no_console) => {
+            let Some(device) = device else { return Err(no_console) };
+            let config_path = crate::commands::validate_device_path(&device).map_err(|e| e.message)?;
+            let root = crate::commands::get_volume_path(&config_path)
+                .ok_or_else(|| "Could not find the device's drive".to_string())?;
+            std::fs::write(root.join(REBOOT_SENTINEL), crate::firmware::unix_now().to_string())
+                .map_err(|e| format!("Could not write the reboot sentinel: {}", e))?;
+            Ok(RebootMethod::Sentinel)
+        }
+    }
+}
+
+/// List CircuitPython serial consoles
+#[command]
+pub fn list_console_ports() -> Vec<ConsolePort> {
+    find_console_ports()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRASH: &str = "code.py output:\r\n\r\n=== MIDI CAPTAIN MAX ===\r\n\r\nTraceback (most recent call last):\r\n  File \"code.py\", line 41, in <module>\r\nImportError: no module named 'adafruit_midi'\r\n\r\nCode done running.\r\n";
+
+    #[test]
+    fn test_scan_detects_complete_traceback() {
+        match scan_boot_output(CRASH) {
+            BootOutcome::Crashed { traceback, error } => {
+                assert_eq!(error, "ImportError: no module named 'adafruit_midi'");
+                assert!(traceback.starts_with(TRACEBACK_HEADER));
+                assert!(traceback.contains("line 41"));
+            }
+            other => panic!("expected crash, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_scan_waits_for_error_line() {
+        let partial = "Traceback (most recent call last):\r\n  File \"code.py\", line 41, in <module>\r\n";
+        assert_eq!(scan_boot_output(partial), BootOutcome::Pending);
+    }
+
+    #[test]
+    fn test_scan_healthy_output() {
+        assert_eq!(scan_boot_output("=== MIDI CAPTAIN MAX ===\r\nDevice: std10\r\n"), BootOutcome::Pending);
+    }
+
+    #[test]
+    fn test_live_command_line_encodes_json() {
+        assert_eq!(live_command_line("led.brightness", &serde_json::json!(0.5)), "@live led.brightness 0.5\r\n");
+        assert_eq!(live_command_line("buttons.3.color", &serde_json::json!("red")), "@live buttons.3.color \"red\"\r\n");
+    }
+
+    #[test]
+    fn test_parse_live_reply() {
+        assert_eq!(parse_live_reply("[SW1] CC20=127\r\n", "led.brightness"), None);
+        assert_eq!(parse_live_reply("@live ok led.brightness\r\n", "led.brightness"), Some(Ok(())));
+        assert_eq!(
+            parse_live_reply("@live err buttons.9.color button out of range 1-6\r\n", "buttons.9.color"),
+            Some(Err("buttons.9.color: button out of range 1-6".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_repl_line() {
+        assert_eq!(repl_line("print(1)"), "print(1)\r\n");
+        assert_eq!(repl_line("print(1)\n"), "print(1)\r\n");
+        assert_eq!(repl_line(""), "\r\n");
+        let key: ReplKey = serde_json::from_str("\"soft_reboot\"").unwrap();
+        assert_eq!(key.byte(), 0x04);
+    }
+
+    #[test]
+    fn test_saw_restart_across_chunks() {
+        let mut tail = String::new();
+        assert!(!saw_restart(&mut tail, "soft reboot\r\n\r\ncode.py out"));
+        assert!(saw_restart(&mut tail, "put:\r\n=== MIDI CAPTAIN MAX ===\r\n"));
+        assert!(!saw_restart(&mut tail, "[SW1] CC20=127\r\n"));
+        assert!(tail.len() <= CODE_START_MARKER.len());
+
+        // Multi-byte text is trimmed on a character boundary
+        let mut tail = String::new();
+        assert!(!saw_restart(&mut tail, &"é".repeat(40)));
+    }
+
+    #[test]
+    fn test_suggestion_mentions_rollback() {
+        assert!(boot_failure_suggestion("ImportError: no module named 'x'").contains("roll back"));
+        assert!(boot_failure_suggestion("RuntimeError: boom").contains("Roll back"));
+    }
+}
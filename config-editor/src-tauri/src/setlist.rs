@@ -0,0 +1,208 @@
+//! Printable setlist sheets
+//!
+//! A setlist is an ordered list of preset names. `export_setlist_sheet`
+//! renders it as a running order to tape to the floor: for each song its
+//! key, tempo and pedal notes (from the preset metadata in the library
+//! index) and what each footswitch is labelled in that song's preset.
+
+use crate::commands::{app_data_dir, ConfigError};
+use crate::config::{MessageType, MidiCaptainConfig};
+use crate::presets::{load_index, presets_dir, read_preset, PresetMetadata};
+use std::fmt::Write;
+use tauri::{command, AppHandle};
+
+/// Output format of a setlist sheet
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SheetFormat {
+    /// Plain text, for printing from any editor
+    Text,
+    Markdown,
+    /// Standalone HTML page, for printing from a browser
+    Html,
+}
+
+/// One song in the running order
+#[derive(Debug, Clone)]
+pub struct SetlistEntry {
+    pub preset: String,
+    pub metadata: PresetMetadata,
+    /// "<switch> <label>" for each footswitch, e.g. "1 DLY"
+    pub switches: Vec<String>,
+}
+
+impl SetlistEntry {
+    /// The song name, or the preset name when no song is set
+    fn title(&self) -> &str {
+        self.metadata.song.as_deref().unwrap_or(&self.preset)
+    }
+
+    /// "Key E · 120 BPM", or "" when neither is set
+    fn key_and_tempo(&self) -> String {
+        let key = self.metadata.key.as_ref().map(|k| format!("Key {}", k));
+        let tempo = self.metadata.tempo.map(|t| format!("{} BPM", t));
+        key.into_iter().chain(tempo).collect::<Vec<_>>().join(" · ")
+    }
+}
+
+/// Footswitch labels of a preset, skipping unlabelled switches
+pub fn switch_labels(config: &MidiCaptainConfig) -> Vec<String> {
+    config
+        .buttons
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| !b.label.trim().is_empty())
+        .map(|(i, b)| match b.message_type {
+            MessageType::Panic => format!("{} {} (panic)", i + 1, b.label),
+            _ => format!("{} {}", i + 1, b.label),
+        })
+        .collect()
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn render_text(entries: &[SetlistEntry]) -> String {
+    let mut out = String::from("SETLIST\n\n");
+    for (i, entry) in entries.iter().enumerate() {
+        let _ = writeln!(out, "{:>2}. {}", i + 1, entry.title());
+        let key_and_tempo = entry.key_and_tempo();
+        if !key_and_tempo.is_empty() {
+            let _ = writeln!(out, "    {}", key_and_tempo);
+        }
+        let _ = writeln!(out, "    Preset: {}", entry.preset);
+        if !entry.switches.is_empty() {
+            let _ = writeln!(out, "    Switches: {}", entry.switches.join(" | "));
+        }
+        if let Some(notes) = &entry.metadata.notes {
+            let _ = writeln!(out, "    Notes: {}", notes);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn render_markdown(entries: &[SetlistEntry]) -> String {
+    let mut out = String::from("# Setlist\n\n");
+    for (i, entry) in entries.iter().enumerate() {
+        let _ = writeln!(out, "## {}. {}\n", i + 1, entry.title());
+        let key_and_tempo = entry.key_and_tempo();
+        if !key_and_tempo.is_empty() {
+            let _ = writeln!(out, "- {}", key_and_tempo);
+        }
+        let _ = writeln!(out, "- Preset: {}", entry.preset);
+        if !entry.switches.is_empty() {
+            let _ = writeln!(out, "- Switches: {}", entry.switches.join(" | "));
+        }
+        if let Some(notes) = &entry.metadata.notes {
+            let _ = writeln!(out, "- Notes: {}", notes);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn render_html(entries: &[SetlistEntry]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Setlist</title>\n\
+         <style>body{font-family:sans-serif;font-size:18pt}li{margin-bottom:1em}\
+         .meta,.switches{color:#444}.notes{font-style:italic}</style>\n</head>\n<body>\n<h1>Setlist</h1>\n<ol>\n",
+    );
+    for entry in entries {
+        let _ = write!(out, "<li><strong>{}</strong>", escape_html(entry.title()));
+        let key_and_tempo = entry.key_and_tempo();
+        if !key_and_tempo.is_empty() {
+            let _ = write!(out, " <span class=\"meta\">{}</span>", escape_html(&key_and_tempo));
+        }
+        let _ = write!(out, "<br><span class=\"meta\">Preset: {}</span>", escape_html(&entry.preset));
+        if !entry.switches.is_empty() {
+            let _ = write!(out, "<br><span class=\"switches\">{}</span>", escape_html(&entry.switches.join(" | ")));
+        }
+        if let Some(notes) = &entry.metadata.notes {
+            let _ = write!(out, "<br><span class=\"notes\">{}</span>", escape_html(notes));
+        }
+        out.push_str("</li>\n");
+    }
+    out.push_str("</ol>\n</body>\n</html>\n");
+    out
+}
+
+/// Render a running order in `format`
+pub fn render_sheet(entries: &[SetlistEntry], format: SheetFormat) -> String {
+    match format {
+        SheetFormat::Text => render_text(entries),
+        SheetFormat::Markdown => render_markdown(entries),
+        SheetFormat::Html => render_html(entries),
+    }
+}
+
+/// Render a printable running order for `setlist` (preset names, in order).
+/// Returns the sheet for the frontend to save or print.
+#[command]
+pub fn export_setlist_sheet(app: AppHandle, setlist: Vec<String>, format: SheetFormat) -> Result<String, ConfigError> {
+    let dir = presets_dir(&app)?;
+    let index = load_index(&app_data_dir(&app)?);
+    let entries = setlist
+        .into_iter()
+        .map(|preset| {
+            let config = read_preset(&dir, &preset)?;
+            Ok(SetlistEntry {
+                metadata: index.metadata.get(&preset).cloned().unwrap_or_default(),
+                switches: switch_labels(&config),
+                preset,
+            })
+        })
+        .collect::<Result<Vec<_>, ConfigError>>()?;
+    Ok(render_sheet(&entries, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<SetlistEntry> {
+        vec![
+            SetlistEntry {
+                preset: "Opener".to_string(),
+                metadata: PresetMetadata {
+                    song: Some("Gloria".to_string()),
+                    key: Some("E".to_string()),
+                    tempo: Some(132),
+                    notes: Some("DLY on for the solo".to_string()),
+                    ..Default::default()
+                },
+                switches: vec!["1 OD".to_string(), "2 DLY".to_string()],
+            },
+            SetlistEntry {
+                preset: "Ballad <slow>".to_string(),
+                metadata: PresetMetadata::default(),
+                switches: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_text_sheet() {
+        let sheet = render_sheet(&entries(), SheetFormat::Text);
+        assert!(sheet.contains(" 1. Gloria\n    Key E · 132 BPM\n    Preset: Opener\n"));
+        assert!(sheet.contains("    Switches: 1 OD | 2 DLY\n    Notes: DLY on for the solo\n"));
+        // Without a song the preset name is the title
+        assert!(sheet.contains(" 2. Ballad <slow>\n    Preset: Ballad <slow>\n\n"));
+    }
+
+    #[test]
+    fn test_markdown_sheet() {
+        let sheet = render_sheet(&entries(), SheetFormat::Markdown);
+        assert!(sheet.starts_with("# Setlist\n\n## 1. Gloria\n\n- Key E · 132 BPM\n"));
+        assert!(sheet.contains("## 2. Ballad <slow>\n"));
+    }
+
+    #[test]
+    fn test_html_sheet_escapes_names() {
+        let sheet = render_sheet(&entries(), SheetFormat::Html);
+        assert!(sheet.contains("<li><strong>Ballad &lt;slow&gt;</strong>"));
+        assert!(!sheet.contains("<slow>"));
+        assert_eq!(sheet.matches("<li>").count(), 2);
+    }
+}
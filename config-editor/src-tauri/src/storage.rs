@@ -0,0 +1,182 @@
+//! Network share and cloud-sync folder detection
+//!
+//! A mapped network drive or a synced folder can carry the CIRCUITPY name (or
+//! a copied config.json) without being the pedal. Writing there "succeeds" but
+//! the device never sees the change, so device paths on such locations are
+//! refused.
+
+use std::path::{Component, Path};
+
+/// Folder names used by cloud-sync clients
+const CLOUD_SYNC_FOLDERS: &[&str] = &[
+    "Dropbox",
+    "Google Drive",
+    "iCloud Drive",
+    "Mobile Documents",
+    "CloudStorage",
+    "Box",
+    "Box Sync",
+    "pCloud Drive",
+    "MEGA",
+];
+
+/// Filesystem types that live on another machine
+#[cfg(not(target_os = "windows"))]
+const NETWORK_FS_TYPES: &[&str] = &["smbfs", "afpfs", "nfs", "nfs4", "cifs", "smb3", "webdav", "davfs", "fuse.sshfs", "9p"];
+
+/// Name of the cloud-sync folder `path` sits in, if any
+pub fn cloud_sync_folder(path: &Path) -> Option<String> {
+    path.components().find_map(|c| match c {
+        Component::Normal(name) => {
+            let name = name.to_str()?;
+            let synced = CLOUD_SYNC_FOLDERS.iter().any(|f| name.eq_ignore_ascii_case(f))
+                // OneDrive folders are "OneDrive" or "OneDrive - <Org>"
+                || name.starts_with("OneDrive");
+            synced.then(|| name.to_string())
+        }
+        _ => None,
+    })
+}
+
+/// Filesystem type of the mount containing `path`, from a mount table in
+/// `/proc/mounts` format (`<source> <mount point> <type> ...`)
+#[cfg(any(target_os = "linux", test))]
+pub fn fs_type_from_proc_mounts(mounts: &str, path: &Path) -> Option<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            path.starts_with(&mount_point).then(|| (mount_point.len(), fs_type.to_string()))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, fs_type)| fs_type)
+}
+
+/// Filesystem type of the mount containing `path`, from `mount` output on
+/// macOS (`<source> on <mount point> (<type>, <options>)`)
+#[cfg(any(target_os = "macos", test))]
+pub fn fs_type_from_mount_output(output: &str, path: &Path) -> Option<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (mount_point, options) = rest.rsplit_once(" (")?;
+            let fs_type = options.split([',', ')']).next()?.trim();
+            path.starts_with(mount_point).then(|| (mount_point.len(), fs_type.to_string()))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, fs_type)| fs_type)
+}
+
+#[cfg(target_os = "linux")]
+fn mount_fs_type(path: &Path) -> Option<String> {
+    fs_type_from_proc_mounts(&std::fs::read_to_string("/proc/mounts").ok()?, path)
+}
+
+#[cfg(target_os = "macos")]
+fn mount_fs_type(path: &Path) -> Option<String> {
+    let output = std::process::Command::new("/sbin/mount").output().ok()?;
+    fs_type_from_mount_output(&String::from_utf8_lossy(&output.stdout), path)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn mount_fs_type(_path: &Path) -> Option<String> {
+    None
+}
+
+/// True if `path` is on a network share
+#[cfg(not(target_os = "windows"))]
+pub fn is_network_path(path: &Path) -> bool {
+    mount_fs_type(path).is_some_and(|t| NETWORK_FS_TYPES.contains(&t.as_str()))
+}
+
+/// True if `path` is on a network share (UNC path or mapped network drive)
+#[cfg(target_os = "windows")]
+pub fn is_network_path(path: &Path) -> bool {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Prefix;
+
+    let Some(Component::Prefix(prefix)) = path.components().next() else {
+        return false;
+    };
+    match prefix.kind() {
+        Prefix::UNC(..) | Prefix::VerbatimUNC(..) => true,
+        Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+            let root: Vec<u16> = OsStr::new(&format!("{}:\\", letter as char))
+                .encode_wide()
+                .chain(Some(0))
+                .collect();
+            let drive_type = unsafe { winapi::um::fileapi::GetDriveTypeW(root.as_ptr()) };
+            drive_type == winapi::um::winbase::DRIVE_REMOTE
+        }
+        _ => false,
+    }
+}
+
+/// Why `path` can't be a MIDI Captain device, if it is a network share or
+/// inside a cloud-sync folder
+pub fn non_device_location(path: &Path) -> Option<String> {
+    if let Some(folder) = cloud_sync_folder(path) {
+        return Some(format!(
+            "{} is inside the cloud-sync folder '{}', not on a MIDI Captain",
+            path.display(),
+            folder
+        ));
+    }
+    if is_network_path(path) {
+        return Some(format!("{} is on a network share, not on a MIDI Captain", path.display()));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cloud_sync_folders() {
+        assert_eq!(
+            cloud_sync_folder(Path::new("/Users/max/Dropbox/CIRCUITPY/config.json")),
+            Some("Dropbox".to_string())
+        );
+        assert_eq!(
+            cloud_sync_folder(Path::new("/Users/max/OneDrive - Church/CIRCUITPY")),
+            Some("OneDrive - Church".to_string())
+        );
+        assert_eq!(cloud_sync_folder(Path::new("/Volumes/CIRCUITPY/config.json")), None);
+    }
+
+    #[test]
+    fn test_fs_type_from_proc_mounts_uses_longest_mount() {
+        let mounts = "/dev/sda1 / ext4 rw 0 0\n\
+                      //nas/share /media/max/CIRCUITPY cifs rw 0 0\n\
+                      /dev/sdb1 /media/max/MIDI\\040CAPTAIN vfat rw 0 0\n";
+        assert_eq!(
+            fs_type_from_proc_mounts(mounts, Path::new("/media/max/CIRCUITPY/config.json")).as_deref(),
+            Some("cifs")
+        );
+        assert_eq!(
+            fs_type_from_proc_mounts(mounts, Path::new("/media/max/MIDI CAPTAIN/config.json")).as_deref(),
+            Some("vfat")
+        );
+        assert_eq!(fs_type_from_proc_mounts(mounts, Path::new("/home/max")).as_deref(), Some("ext4"));
+    }
+
+    #[test]
+    fn test_fs_type_from_mount_output() {
+        let output = "/dev/disk3s1 on / (apfs, local, journaled)\n\
+                      //max@nas/share on /Volumes/CIRCUITPY (smbfs, nodev, nosuid, mounted by max)\n\
+                      /dev/disk5s1 on /Volumes/MIDICAPTAIN (msdos, local, nodev, nosuid, noowners)\n";
+        assert_eq!(
+            fs_type_from_mount_output(output, Path::new("/Volumes/CIRCUITPY/config.json")).as_deref(),
+            Some("smbfs")
+        );
+        assert_eq!(
+            fs_type_from_mount_output(output, Path::new("/Volumes/MIDICAPTAIN/config.json")).as_deref(),
+            Some("msdos")
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Device flash stress test
+//!
+//! Counterfeit or worn flash often accepts writes and hands back something
+//! else later, which shows up as a config that "saved" but came back
+//! corrupted. `run_storage_stress_test` writes a scratch file through the
+//! same path config writes use, reads it back, compares and erases it, over
+//! and over, and reports throughput and every fault it saw.
+//!
+//! The host may serve reads from its own cache, so a clean run doesn't prove
+//! the flash is good; a faulty one does prove it is bad.
+
+use crate::commands::{validate_device_path, verify_device_connected, write_sync, ConfigError};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tauri::command;
+
+/// Scratch file written to the device root (dot-named so the firmware ignores it)
+const SCRATCH_FILE: &str = ".midicaptain-stress-test.bin";
+
+/// Bytes written per cycle. CIRCUITPY has about 1MB free, so keep this small.
+const CYCLE_BYTES: usize = 64 * 1024;
+
+/// Upper bound on cycles per run
+const MAX_CYCLES: u32 = 1000;
+
+/// Runs stop early after this many faults; the device is clearly failing
+const MAX_FAULTS: usize = 20;
+
+/// Something that went wrong in one cycle
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct StressFault {
+    pub cycle: u32,
+    pub message: String,
+}
+
+/// Result of `run_storage_stress_test`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StressTestReport {
+    /// Cycles requested (after clamping)
+    pub cycles: u32,
+    /// Cycles that ran before the test finished or stopped
+    pub completed: u32,
+    pub bytes_per_cycle: usize,
+    /// Average write speed (bytes per second), including the sync
+    pub write_bytes_per_sec: f64,
+    /// Average read speed (bytes per second)
+    pub read_bytes_per_sec: f64,
+    pub faults: Vec<StressFault>,
+}
+
+/// Data for one cycle. Each cycle's data differs, so a read that returns the
+/// previous cycle's data is caught.
+fn pattern(cycle: u32, len: usize) -> Vec<u8> {
+    // xorshift32; the seed must be non-zero
+    let mut state = cycle.wrapping_mul(0x9E37_79B9) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+fn throughput(bytes: usize, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        bytes as f64 / secs
+    } else {
+        0.0
+    }
+}
+
+/// Write, verify and erase a scratch file in `root` `cycles` times
+pub fn stress_test(root: &Path, cycles: u32, bytes_per_cycle: usize) -> StressTestReport {
+    let scratch = root.join(SCRATCH_FILE);
+    let mut faults = Vec::new();
+    let mut completed = 0;
+    let (mut write_time, mut read_time) = (Duration::ZERO, Duration::ZERO);
+    let (mut written, mut read) = (0, 0);
+
+    for cycle in 0..cycles {
+        if faults.len() >= MAX_FAULTS {
+            break;
+        }
+        completed += 1;
+        let mut fault = |message: String| faults.push(StressFault { cycle, message });
+        let data = pattern(cycle, bytes_per_cycle);
+
+        let start = Instant::now();
+        if let Err(e) = write_sync(&scratch, &data) {
+            fault(format!("Write failed: {}", e));
+            continue;
+        }
+        write_time += start.elapsed();
+        written += data.len();
+
+        let start = Instant::now();
+        match fs::read(&scratch) {
+            Ok(back) => {
+                read_time += start.elapsed();
+                read += back.len();
+                if back.len() != data.len() {
+                    fault(format!("Read back {} bytes, wrote {}", back.len(), data.len()));
+                } else if let Some(offset) = back.iter().zip(&data).position(|(a, b)| a != b) {
+                    fault(format!("Read back data differs from what was written at byte {}", offset));
+                }
+            }
+            Err(e) => fault(format!("Read failed: {}", e)),
+        }
+
+        if let Err(e) = fs::remove_file(&scratch) {
+            fault(format!("Erase failed: {}", e));
+        }
+    }
+    // Never leave the scratch file behind on the device
+    let _ = fs::remove_file(&scratch);
+
+    StressTestReport {
+        cycles,
+        completed,
+        bytes_per_cycle,
+        write_bytes_per_sec: throughput(written, write_time),
+        read_bytes_per_sec: throughput(read, read_time),
+        faults,
+    }
+}
+
+/// Repeatedly write, verify and erase a scratch file on the device to find
+/// counterfeit or failing flash. `cycles` is clamped to 1-1000.
+#[command]
+pub fn run_storage_stress_test(device_path: String, cycles: u32) -> Result<StressTestReport, ConfigError> {
+    let root = &validate_device_path(&device_path)?;
+    verify_device_connected(root)?;
+    Ok(stress_test(root, cycles.clamp(1, MAX_CYCLES), CYCLE_BYTES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_differ_between_cycles() {
+        assert_eq!(pattern(3, 64), pattern(3, 64));
+        assert_ne!(pattern(3, 64), pattern(4, 64));
+    }
+
+    #[test]
+    fn test_stress_test_on_healthy_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = stress_test(dir.path(), 5, 4096);
+        assert_eq!(report.completed, 5);
+        assert!(report.faults.is_empty(), "{:?}", report.faults);
+        assert!(!dir.path().join(SCRATCH_FILE).exists());
+    }
+
+    #[test]
+    fn test_stress_test_stops_after_too_many_faults() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("ejected");
+        let report = stress_test(&missing, 100, 4096);
+        assert_eq!(report.completed as usize, MAX_FAULTS);
+        assert!(report.faults[0].message.starts_with("Write failed"));
+    }
+}
@@ -0,0 +1,178 @@
+//! Target profiles: what the receiving device does with each CC
+//!
+//! A profile names the CCs a target (an amp, a modeller, a synth) responds
+//! to and, for switch functions, whether it expects a latching or momentary
+//! footswitch. The config picks one with `_editor.target_profile`; without
+//! one, the MIDI spec's standard controller meanings apply. Built-in
+//! profiles are JSON files under `src-tauri/profiles/`, embedded at build time.
+//! Downloaded profiles (see profile_updates.rs) are swapped in at runtime with
+//! `install_downloaded`, replacing built-ins with the same id.
+
+use crate::config::MidiCaptainConfig;
+use std::sync::Mutex;
+use tauri::command;
+
+/// Profile used when the config doesn't name one
+pub const DEFAULT_PROFILE: &str = "midi-standard";
+
+const BUILTIN_PROFILES: &[&str] = &[include_str!("../profiles/midi-standard.json")];
+
+/// How a target treats a switch function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SwitchBehavior {
+    /// On until switched off (the button should toggle)
+    Latching,
+    /// On only while held (the button should be momentary)
+    Momentary,
+}
+
+/// One CC a target responds to
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CcFunction {
+    pub cc: u8,
+    pub name: String,
+    /// Set for switch functions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub behavior: Option<SwitchBehavior>,
+}
+
+/// CC map of a target device
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TargetProfile {
+    pub id: String,
+    pub name: String,
+    pub controls: Vec<CcFunction>,
+}
+
+impl TargetProfile {
+    pub fn cc(&self, cc: u8) -> Option<&CcFunction> {
+        self.controls.iter().find(|c| c.cc == cc)
+    }
+}
+
+static PROFILES: Mutex<Option<Vec<TargetProfile>>> = Mutex::new(None);
+
+/// The built-in profiles; the tests check that every one parses
+fn builtin_profiles() -> Vec<TargetProfile> {
+    BUILTIN_PROFILES.iter().filter_map(|json| serde_json::from_str(json).ok()).collect()
+}
+
+/// Built-in profiles with `downloaded` added; a downloaded profile replaces
+/// the built-in with the same id
+pub fn merge_profiles(mut profiles: Vec<TargetProfile>, downloaded: Vec<TargetProfile>) -> Vec<TargetProfile> {
+    for profile in downloaded {
+        match profiles.iter_mut().find(|p| p.id == profile.id) {
+            Some(existing) => *existing = profile,
+            None => profiles.push(profile),
+        }
+    }
+    profiles
+}
+
+/// Problems that keep a set of downloaded profiles from being used
+pub fn check_profiles(profiles: &[TargetProfile]) -> Vec<String> {
+    let mut problems = Vec::new();
+    if profiles.is_empty() {
+        problems.push("No profiles".to_string());
+    }
+    let mut ids = std::collections::BTreeSet::new();
+    for profile in profiles {
+        if profile.id.trim().is_empty() {
+            problems.push(format!("Profile '{}' has no id", profile.name));
+        } else if !ids.insert(profile.id.as_str()) {
+            problems.push(format!("Profile id '{}' appears twice", profile.id));
+        }
+        for control in profile.controls.iter().filter(|c| c.cc > 127) {
+            problems.push(format!("Profile '{}': CC {} is invalid (must be 0-127)", profile.id, control.cc));
+        }
+    }
+    problems
+}
+
+/// Swap downloaded profiles in; validation and lookups see them at once
+pub fn install_downloaded(downloaded: Vec<TargetProfile>) {
+    *PROFILES.lock().unwrap() = Some(merge_profiles(builtin_profiles(), downloaded));
+}
+
+/// All loaded profiles
+pub fn profiles() -> Vec<TargetProfile> {
+    PROFILES.lock().unwrap().get_or_insert_with(builtin_profiles).clone()
+}
+
+/// Profile by id
+pub fn find_profile(id: &str) -> Option<TargetProfile> {
+    profiles().into_iter().find(|p| p.id == id)
+}
+
+/// The profile a config targets: its `_editor.target_profile`, or the MIDI
+/// standard profile when unset or unknown
+pub fn profile_for(config: &MidiCaptainConfig) -> Option<TargetProfile> {
+    config
+        .editor
+        .as_ref()
+        .and_then(|e| e.target_profile.as_deref())
+        .and_then(find_profile)
+        .or_else(|| find_profile(DEFAULT_PROFILE))
+}
+
+/// List the available target profiles
+#[command]
+pub fn list_target_profiles() -> Vec<TargetProfile> {
+    profiles()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_profiles_parse() {
+        assert_eq!(builtin_profiles().len(), BUILTIN_PROFILES.len());
+        let standard = find_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(standard.cc(64).unwrap().behavior, Some(SwitchBehavior::Momentary));
+        assert_eq!(standard.cc(11).unwrap().name, "Expression");
+        assert!(standard.cc(20).is_none());
+    }
+
+    #[test]
+    fn test_unknown_profile_falls_back_to_standard() {
+        let config: MidiCaptainConfig =
+            serde_json::from_str(r#"{"buttons": [], "_editor": {"target_profile": "no-such-amp"}}"#).unwrap();
+        assert_eq!(profile_for(&config).unwrap().id, DEFAULT_PROFILE);
+    }
+
+    fn profile(id: &str, ccs: &[u8]) -> TargetProfile {
+        TargetProfile {
+            id: id.to_string(),
+            name: id.to_string(),
+            controls: ccs
+                .iter()
+                .map(|&cc| CcFunction {
+                    cc,
+                    name: format!("CC {}", cc),
+                    behavior: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_downloaded_profiles_replace_builtins_by_id() {
+        let merged = merge_profiles(
+            vec![profile(DEFAULT_PROFILE, &[64]), profile("amp", &[1])],
+            vec![profile("amp", &[1, 2]), profile("synth", &[74])],
+        );
+        let ids: Vec<&str> = merged.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, [DEFAULT_PROFILE, "amp", "synth"]);
+        assert_eq!(merged[1].controls.len(), 2);
+    }
+
+    #[test]
+    fn test_check_profiles() {
+        assert!(check_profiles(&[profile("amp", &[1, 127])]).is_empty());
+        assert!(!check_profiles(&[]).is_empty());
+        assert_eq!(check_profiles(&[profile("amp", &[1]), profile("amp", &[2])]).len(), 1);
+        assert_eq!(check_profiles(&[profile("amp", &[128])]).len(), 1);
+    }
+}
@@ -0,0 +1,125 @@
+//! Built-in config templates for common rigs
+//!
+//! A template is a set of button assignments for a known setup (an HX Stomp,
+//! a looper). Templates are JSON files under `src-tauri/templates/`, embedded
+//! at build time and written for the STD10's ten switches; `apply_template`
+//! fits one to a device, keeping the switches it has and taking everything
+//! else (encoder, pedals) from the factory config.
+
+use crate::commands::ConfigError;
+use crate::config::{generate_default_config, issue_messages, ButtonConfig, DeviceType, MidiCaptainConfig};
+use tauri::command;
+
+const BUILTIN_TEMPLATES: &[&str] = &[
+    include_str!("../templates/hx-stomp-snapshots.json"),
+    include_str!("../templates/generic-looper.json"),
+];
+
+/// A template as stored
+#[derive(Debug, Clone, serde::Deserialize)]
+struct ConfigTemplate {
+    id: String,
+    name: String,
+    description: String,
+    /// Switch assignments in switch order
+    buttons: Vec<ButtonConfig>,
+    #[serde(default)]
+    global_channel: Option<u8>,
+}
+
+/// What `list_templates` shows for a template
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TemplateInfo {
+    /// Passed to `apply_template`
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Switches the template assigns; a device with fewer gets the first ones
+    pub button_count: usize,
+}
+
+/// The built-in templates; the tests check that every one parses
+fn builtin_templates() -> Vec<ConfigTemplate> {
+    BUILTIN_TEMPLATES.iter().filter_map(|json| serde_json::from_str(json).ok()).collect()
+}
+
+/// A template's config for `device`: its switch assignments cut to the
+/// device's switches (extra factory switches fill any the template lacks)
+/// on top of the factory config
+fn fit_template(template: &ConfigTemplate, device: DeviceType) -> MidiCaptainConfig {
+    let mut config = generate_default_config(device);
+    for (button, assigned) in config.buttons.iter_mut().zip(&template.buttons) {
+        *button = assigned.clone();
+    }
+    config.global_channel = template.global_channel;
+    config
+}
+
+/// List the built-in templates
+#[command]
+pub fn list_templates() -> Vec<TemplateInfo> {
+    builtin_templates()
+        .into_iter()
+        .map(|t| TemplateInfo {
+            button_count: t.buttons.len(),
+            id: t.id,
+            name: t.name,
+            description: t.description,
+        })
+        .collect()
+}
+
+/// A validated config for `device_type` from the template with id `name`.
+/// Nothing is written.
+#[command]
+pub fn apply_template(name: String, device_type: DeviceType) -> Result<MidiCaptainConfig, ConfigError> {
+    let template = builtin_templates().into_iter().find(|t| t.id == name).ok_or_else(|| ConfigError {
+        message: format!("No template named '{}'", name),
+        details: None,
+        code: None,
+    })?;
+    let config = fit_template(&template, device_type);
+    config.validate().map_err(|errors| ConfigError {
+        message: format!("Template '{}' does not fit this device", template.name),
+        details: Some(issue_messages(errors)),
+        code: None,
+    })?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_templates_parse() {
+        let templates = list_templates();
+        assert_eq!(templates.len(), BUILTIN_TEMPLATES.len());
+        assert!(templates.iter().any(|t| t.id == "hx-stomp-snapshots" && t.button_count == 10));
+    }
+
+    #[test]
+    fn test_templates_fit_every_device() {
+        for template in list_templates() {
+            for device in DeviceType::ALL {
+                let config = apply_template(template.id.clone(), device.clone())
+                    .unwrap_or_else(|e| panic!("{} on {:?}: {:?}", template.id, device, e.details));
+                assert_eq!(config.buttons.len(), device.button_count());
+                assert_eq!(config.device, device);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mini6_gets_the_first_switches() {
+        let config = apply_template("generic-looper".to_string(), DeviceType::Mini6).unwrap();
+        let labels: Vec<&str> = config.buttons.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, ["REC", "PLAY", "STOP", "UNDO", "REDO", "ODUB"]);
+        assert!(config.encoder.is_none() && config.expression.is_none());
+    }
+
+    #[test]
+    fn test_unknown_template() {
+        assert!(apply_template("nope".to_string(), DeviceType::Std10).unwrap_err().message.contains("No template"));
+    }
+}
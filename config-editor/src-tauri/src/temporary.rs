@@ -0,0 +1,193 @@
+//! Time-boxed temporary configs
+//!
+//! `deploy_temporary` writes a config for a limited time, e.g. to try a new
+//! layout at rehearsal. When the time is up the config that was on the device
+//! before is written back, unless `keep_temporary` confirms the new one
+//! first. The restore goes through the normal verified write, so it is
+//! backed up and logged like any other.
+//!
+//! The timer lives in the app: quitting before it fires leaves the temporary
+//! config on the device (the previous one is in the config backups).
+
+use crate::commands::{validate_device_path, write_config, write_verified_in_guest_mode, ConfigError, ConfigFormat};
+use crate::config::MidiCaptainConfig;
+use crate::firmware::unix_now;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{command, AppHandle, Emitter};
+
+/// Shortest temporary deploy (seconds)
+const MIN_DURATION_SECS: u64 = 10;
+
+/// Longest temporary deploy (seconds): a rehearsal
+const MAX_DURATION_SECS: u64 = 4 * 60 * 60;
+
+/// The temporary config waiting to be restored, if any
+static PENDING: Mutex<Option<Pending>> = Mutex::new(None);
+
+/// Distinguishes one temporary deploy from the next
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+struct Pending {
+    id: u64,
+    path: PathBuf,
+    restore_at: u64,
+    /// Stops the timer (sent by `keep_temporary`)
+    keep: Sender<()>,
+}
+
+/// A temporary config on a device
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TemporaryDeploy {
+    /// Config path it was written to
+    pub path: String,
+    /// Unix timestamp (seconds) when the previous config comes back
+    pub restore_at: u64,
+}
+
+impl Pending {
+    fn report(&self) -> TemporaryDeploy {
+        TemporaryDeploy {
+            path: self.path.to_string_lossy().to_string(),
+            restore_at: self.restore_at,
+        }
+    }
+}
+
+fn check_duration(secs: u64) -> Result<(), ConfigError> {
+    if (MIN_DURATION_SECS..=MAX_DURATION_SECS).contains(&secs) {
+        return Ok(());
+    }
+    Err(ConfigError {
+        message: format!(
+            "Duration {}s is invalid (must be {}s to {} hours)",
+            secs,
+            MIN_DURATION_SECS,
+            MAX_DURATION_SECS / 3600
+        ),
+        details: None,
+        code: None,
+    })
+}
+
+/// Take the pending deploy if it is still `id`; `None` once kept or replaced
+fn take_pending(id: u64) -> Option<Pending> {
+    let mut pending = PENDING.lock().unwrap();
+    if pending.as_ref().is_some_and(|p| p.id == id) {
+        pending.take()
+    } else {
+        None
+    }
+}
+
+/// Put `previous` back at `path` once the temporary deploy `id` expires
+fn restore_when_due(app: AppHandle, id: u64, path: PathBuf, previous: Value, duration: Duration, keep: mpsc::Receiver<()>) {
+    if keep.recv_timeout(duration) != Err(RecvTimeoutError::Timeout) {
+        return;
+    }
+    if take_pending(id).is_none() {
+        return;
+    }
+    match write_verified_in_guest_mode(&path, &previous) {
+        Ok(()) => {
+            let _ = app.emit("temporary-config-restored", path.to_string_lossy().to_string());
+        }
+        Err(e) => {
+            let _ = app.emit("temporary-config-error", e.message);
+        }
+    }
+}
+
+/// Write `config` to `device` (a config path, as for `write_config`) for
+/// `duration` seconds, then put the previous config back. Only one temporary
+/// config can be pending at a time.
+#[command]
+pub fn deploy_temporary(
+    app: AppHandle,
+    config: MidiCaptainConfig,
+    device: String,
+    duration: u64,
+) -> Result<TemporaryDeploy, ConfigError> {
+    check_duration(duration)?;
+    let path = validate_device_path(&device)?;
+    if let Some(pending) = PENDING.lock().unwrap().as_ref() {
+        return Err(ConfigError {
+            message: format!(
+                "A temporary config is already on {}. Keep it or wait for it to be restored first.",
+                pending.path.display()
+            ),
+            details: None,
+            code: None,
+        });
+    }
+    let bytes = fs::read(&path).map_err(|e| ConfigError {
+        message: format!("No current config to go back to: {}", e),
+        details: None,
+        code: None,
+    })?;
+    let previous: Value = ConfigFormat::detect(&bytes).decode(&bytes)?;
+
+    write_config(device, config, None, None, None, None)?.into_result()?;
+
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let (keep, keep_rx) = mpsc::channel();
+    let pending = Pending {
+        id,
+        path: path.clone(),
+        restore_at: unix_now() + duration,
+        keep,
+    };
+    let report = pending.report();
+    *PENDING.lock().unwrap() = Some(pending);
+    std::thread::spawn(move || restore_when_due(app, id, path, previous, Duration::from_secs(duration), keep_rx));
+    Ok(report)
+}
+
+/// Keep the temporary config for good. Returns what was kept, or `None` if
+/// nothing was pending.
+#[command]
+pub fn keep_temporary() -> Option<TemporaryDeploy> {
+    let pending = PENDING.lock().unwrap().take()?;
+    let _ = pending.keep.send(());
+    Some(pending.report())
+}
+
+/// The pending temporary config, for showing a countdown
+#[command]
+pub fn get_temporary() -> Option<TemporaryDeploy> {
+    PENDING.lock().unwrap().as_ref().map(Pending::report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_limits() {
+        assert!(check_duration(600).is_ok());
+        assert!(check_duration(MIN_DURATION_SECS - 1).is_err());
+        assert!(check_duration(MAX_DURATION_SECS + 1).is_err());
+    }
+
+    #[test]
+    fn test_kept_deploy_is_not_restored() {
+        let (keep, _rx) = mpsc::channel();
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        *PENDING.lock().unwrap() = Some(Pending {
+            id,
+            path: PathBuf::from("/Volumes/MIDICAPTAIN/config.json"),
+            restore_at: 0,
+            keep,
+        });
+        assert_eq!(get_temporary().map(|t| t.path), Some("/Volumes/MIDICAPTAIN/config.json".to_string()));
+        assert!(keep_temporary().is_some());
+        // The timer finds nothing left to restore
+        assert!(take_pending(id).is_none());
+        assert!(keep_temporary().is_none());
+    }
+}
@@ -1,28 +1,244 @@
 // Tauri command wrappers
 
-import { invoke } from '@tauri-apps/api/core';
+import { invoke as invokeCommand, type InvokeArgs } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import type { MidiCaptainConfig, DetectedDevice } from './types';
+import type {
+  MidiCaptainConfig,
+  DeviceType,
+  Strictness,
+  DetectedDevice,
+  DeviceDisconnected,
+  PushResult,
+  DeviceFound,
+  DeviceScanComplete,
+  FirmwareSlot,
+  DeployProgress,
+  FirmwareDeploy,
+  DependencyReport,
+  DeviceHealth,
+  DeviceInfo,
+  OrphanFile,
+  CachedDownload,
+  ConsolePort,
+  ReplKey,
+  RebootMethod,
+  BootFailure,
+  ScriptOutput,
+  ChannelRemap,
+  ConfigChange,
+  WriteHistory,
+  TemplateInfo,
+  Confirmable,
+  ValidationReport,
+  DetailedValidation,
+  ExpSample,
+  Calibration,
+  EncoderSample,
+  CrashReport,
+  LibraryHealth,
+  LibraryIndex,
+  PresetMetadata,
+  PresetEntry,
+  SheetFormat,
+  ChartFormat,
+  StorageUsage,
+  MaintenanceSettings,
+  MaintenanceReport,
+  NotificationSettings,
+  SelfTestReport,
+  StressTestReport,
+  AppCapabilities,
+  ControlExplanation,
+  HotkeySettings,
+  HotkeyTarget,
+  HotkeyDeploy,
+  TargetProfile,
+  ProfileUpdateSettings,
+  ProfileUpdate,
+  DeviceSettings,
+  WriteLogEntry,
+  RecoveryPoint,
+  DeviceProfile,
+  InteropIssue,
+  PaintAudioImport,
+  TextFormat,
+  ApiVersion,
+  MidiBroadcastSettings,
+  MidiPortScan,
+  MidiMessage,
+  ConfigBackup,
+  TemporaryDeploy,
+  RepairedConfig,
+  MigratedConfig,
+  ExternalConfigChange,
+  ReadConfig,
+  RawConfig,
+} from './types';
+
+// Command surface this frontend was built against. Keep in step with
+// API_VERSION in src-tauri/src/capabilities.rs.
+export const API_VERSION = 4;
+
+let negotiation: Promise<ApiVersion> | null = null;
+
+// Check API versions once, before the first command. A mismatch rejects with
+// an ApiVersionError (kind 'frontend_too_old' or 'frontend_too_new') and no
+// command is sent.
+export function negotiateApiVersion(): Promise<ApiVersion> {
+  negotiation ??= invokeCommand<ApiVersion>('negotiate_api_version', { frontendVersion: API_VERSION });
+  return negotiation;
+}
+
+async function invoke<T>(cmd: string, args?: InvokeArgs): Promise<T> {
+  await negotiateApiVersion();
+  return invokeCommand<T>(cmd, args);
+}
+
+// Guest mode: while on, the backend refuses every write/deploy/firmware
+// command with a ConfigError whose code is 'read_only_mode'.
+// Turning it on sets `passphrase`; turning it off needs the same one, or fails
+// with code 'guest_passphrase_invalid'.
+export async function getGuestMode(): Promise<boolean> {
+  return invoke('get_guest_mode');
+}
+
+export async function setGuestMode(enabled: boolean, passphrase: string): Promise<void> {
+  return invoke('set_guest_mode', { enabled, passphrase });
+}
 
 // Config operations
-export async function readConfig(path: string): Promise<MidiCaptainConfig> {
+// `path` may also name an entry in a snapshot zip, e.g. `snapshot.zip!/config.json` (read-only)
+// `token` identifies the contents read; pass it to writeConfig as
+// `expectedToken` to fail with config_conflict if the file changed since
+export async function readConfig(path: string): Promise<ReadConfig> {
   return invoke('read_config', { path });
 }
 
-export async function readConfigRaw(path: string): Promise<string> {
+// readConfig plus the schema migrations applied; the upgrade is saved on the next write
+export async function readConfigMigrated(path: string): Promise<MigratedConfig> {
+  return invoke('read_config_migrated', { path });
+}
+
+export async function readConfigRaw(path: string): Promise<RawConfig> {
   return invoke('read_config_raw', { path });
 }
 
-export async function writeConfig(path: string, config: MidiCaptainConfig): Promise<void> {
-  return invoke('write_config', { path, config });
+// Pass `force` to overwrite a config locked with setConfigLocked; a forced
+// overwrite returns a confirmation token first (see Confirmable). With
+// `expectedToken` from the read, a config changed since fails with
+// config_conflict. Resolves to the written config's token.
+export async function writeConfig(
+  path: string,
+  config: MidiCaptainConfig,
+  force = false,
+  confirmation?: string,
+  strictness: Strictness = 'normal',
+  expectedToken?: string
+): Promise<Confirmable<string>> {
+  return invoke('write_config', { path, config, force, confirmation, strictness, expectedToken });
+}
+
+export async function writeConfigRaw(
+  path: string,
+  json: string,
+  force = false,
+  confirmation?: string,
+  strictness: Strictness = 'normal',
+  expectedToken?: string
+): Promise<Confirmable<string>> {
+  return invoke('write_config_raw', { path, json, force, confirmation, strictness, expectedToken });
+}
+
+// Unlocking returns a confirmation token first (see Confirmable). Resolves
+// to the written config's token.
+export async function setConfigLocked(path: string, locked: boolean, confirmation?: string): Promise<Confirmable<string>> {
+  return invoke('set_config_locked', { path, locked, confirmation });
+}
+
+// Resolves with the problems let through (only ever non-empty when permissive)
+export async function validateConfig(json: string, strictness: Strictness = 'normal'): Promise<string[]> {
+  return invoke('validate_config', { json, strictness });
+}
+
+// Factory-style config for a device with no config.json; nothing is written
+export async function generateDefaultConfig(device: DeviceType): Promise<MidiCaptainConfig> {
+  return invoke('generate_default_config', { device });
+}
+
+// Labels transliterated to what the display can draw ("Café" -> "Cafe")
+export async function sanitizeLabels(config: MidiCaptainConfig): Promise<MidiCaptainConfig> {
+  return invoke('sanitize_labels', { config });
+}
+
+// Built-in templates for common rigs; applying one writes nothing
+export async function listTemplates(): Promise<TemplateInfo[]> {
+  return invoke('list_templates');
+}
+
+export async function applyTemplate(name: string, deviceType: DeviceType): Promise<MidiCaptainConfig> {
+  return invoke('apply_template', { name, deviceType });
+}
+
+// Lenient read of a broken config.json; nothing is written. Review the fixes,
+// then save the config with writeConfig.
+export async function repairConfig(path: string): Promise<RepairedConfig> {
+  return invoke('repair_config', { path });
+}
+
+// Temporary deploy: `duration` is in seconds (10 s to 4 h). The previous config
+// is written back when it runs out unless keepTemporary() is called first.
+export async function deployTemporary(
+  device: string,
+  config: MidiCaptainConfig,
+  duration: number
+): Promise<TemporaryDeploy> {
+  return invoke('deploy_temporary', { device, config, duration });
+}
+
+// Resolves with what was kept, or null if nothing was pending
+export async function keepTemporary(): Promise<TemporaryDeploy | null> {
+  return invoke('keep_temporary');
 }
 
-export async function writeConfigRaw(path: string, json: string): Promise<void> {
-  return invoke('write_config_raw', { path, json });
+export async function getTemporary(): Promise<TemporaryDeploy | null> {
+  return invoke('get_temporary');
 }
 
-export async function validateConfig(json: string): Promise<void> {
-  return invoke('validate_config', { json });
+// Config backups (taken automatically before every write)
+export async function listBackups(device?: string): Promise<ConfigBackup[]> {
+  return invoke('list_backups', { device });
+}
+
+// Put back the config from before the last write; returns the new token
+export async function undoLastWrite(
+  path: string,
+  force = false,
+  confirmation?: string
+): Promise<Confirmable<string>> {
+  return invoke('undo_last_write', { path, force, confirmation });
+}
+
+// Reapply the last undone write; a new write clears what can be redone
+export async function redoWrite(
+  path: string,
+  force = false,
+  confirmation?: string
+): Promise<Confirmable<string>> {
+  return invoke('redo', { path, force, confirmation });
+}
+
+export async function getWriteHistory(path: string): Promise<WriteHistory> {
+  return invoke('get_write_history', { path });
+}
+
+// The config being replaced is backed up too, so a restore can be undone
+export async function restoreBackup(
+  id: string,
+  path: string,
+  force = false,
+  confirmation?: string
+): Promise<Confirmable<null>> {
+  return invoke('restore_backup', { id, path, force, confirmation });
 }
 
 // Device operations
@@ -30,10 +246,516 @@ export async function scanDevices(): Promise<DetectedDevice[]> {
   return invoke('scan_devices');
 }
 
+// Volume names recognized as a device: CIRCUITPY, MIDICAPTAIN and any added
+export async function getKnownVolumes(): Promise<string[]> {
+  return invoke('get_known_volumes');
+}
+
+export async function addKnownVolume(name: string): Promise<string[]> {
+  return invoke('add_known_volume', { name });
+}
+
+export async function removeKnownVolume(name: string): Promise<string[]> {
+  return invoke('remove_known_volume', { name });
+}
+
+// Every connected device, as the device watcher tracks them
+export async function listConnectedDevices(): Promise<DetectedDevice[]> {
+  return invoke('list_connected_devices');
+}
+
+// Write one config to every connected device; each gets its own result
+export async function pushConfigToAll(config: MidiCaptainConfig): Promise<PushResult[]> {
+  return invoke('push_config_to_all', { config });
+}
+
+// Returns at once; results arrive via onDeviceFound / onDeviceScanComplete
+export async function scanDevicesAsync(scanId: string): Promise<void> {
+  return invoke('scan_devices_async', { scanId });
+}
+
 export async function startDeviceWatcher(): Promise<void> {
   return invoke('start_device_watcher');
 }
 
+// Firmware rollback
+export async function stashFirmwareForRollback(devicePath: string): Promise<FirmwareSlot> {
+  return invoke('stash_firmware_for_rollback', { devicePath });
+}
+
+export async function getRollbackInfo(devicePath: string): Promise<FirmwareSlot | null> {
+  return invoke('get_rollback_info', { devicePath });
+}
+
+export async function rollbackFirmware(devicePath: string, confirmation?: string): Promise<Confirmable<FirmwareSlot>> {
+  return invoke('rollback_firmware', { devicePath, confirmation });
+}
+
+export async function getInstalledFirmwareVersion(devicePath: string): Promise<string | null> {
+  return invoke('get_installed_firmware_version', { devicePath });
+}
+
+// Install a firmware release zip (or unpacked directory); the current
+// firmware is stashed for rollbackFirmware first. config.json is left alone.
+export async function deployFirmware(devicePath: string, bundlePath: string): Promise<FirmwareDeploy> {
+  return invoke('deploy_firmware', { devicePath, bundlePath });
+}
+
+export async function checkFirmwareDependencies(devicePath: string): Promise<DependencyReport> {
+  return invoke('check_firmware_dependencies', { devicePath });
+}
+
+export async function checkDeviceHealth(path: string): Promise<DeviceHealth> {
+  return invoke('check_device_health', { path });
+}
+
+export async function getDeviceInfo(path: string): Promise<DeviceInfo> {
+  return invoke('get_device_info', { path });
+}
+
+export async function auditDeviceFiles(devicePath: string): Promise<OrphanFile[]> {
+  return invoke('audit_device_files', { devicePath });
+}
+
+// Recovery points (named firmware + config captures, restored exactly)
+export async function createRecoveryPoint(devicePath: string, name: string): Promise<RecoveryPoint> {
+  return invoke('create_recovery_point', { devicePath, name });
+}
+
+export async function listRecoveryPoints(): Promise<RecoveryPoint[]> {
+  return invoke('list_recovery_points');
+}
+
+export async function restoreRecoveryPoint(
+  devicePath: string,
+  name: string,
+  confirmation?: string
+): Promise<Confirmable<RecoveryPoint>> {
+  return invoke('restore_recovery_point', { devicePath, name, confirmation });
+}
+
+export async function deleteRecoveryPoint(name: string, confirmation?: string): Promise<Confirmable<null>> {
+  return invoke('delete_recovery_point', { name, confirmation });
+}
+
+// Profiles stored on the device (profiles/<name>.json); activating one
+// writes it to config.json
+export async function listProfiles(devicePath: string): Promise<DeviceProfile[]> {
+  return invoke('list_profiles', { devicePath });
+}
+
+export async function activateProfile(devicePath: string, name: string): Promise<void> {
+  return invoke('activate_profile', { devicePath, name });
+}
+
+export async function saveProfile(devicePath: string, name: string, config: MidiCaptainConfig): Promise<void> {
+  return invoke('save_profile', { devicePath, name, config });
+}
+
+export async function deleteProfile(devicePath: string, name: string, confirmation?: string): Promise<Confirmable<null>> {
+  return invoke('delete_profile', { devicePath, name, confirmation });
+}
+
+// Download cache
+export async function downloadBundle(url: string, sha256?: string): Promise<CachedDownload> {
+  return invoke('download_bundle', { url, sha256 });
+}
+
+export async function fetchLibraryBundle(devicePath: string): Promise<CachedDownload> {
+  return invoke('fetch_library_bundle', { devicePath });
+}
+
+export async function listDownloadCache(): Promise<CachedDownload[]> {
+  return invoke('list_download_cache');
+}
+
+export async function clearDownloadCache(): Promise<void> {
+  return invoke('clear_download_cache');
+}
+
+// Serial console
+export async function listConsolePorts(): Promise<ConsolePort[]> {
+  return invoke('list_console_ports');
+}
+
+/**
+ * Audition a setting on the running firmware without saving config.json.
+ * Supported paths: 'led.brightness' (0-1), 'buttons.<n>.color|label|off_mode'.
+ */
+export async function setLiveParameter(path: string, value: unknown, port?: string): Promise<void> {
+  return invoke('set_live_parameter', { port, path, value });
+}
+
+/** Show a candidate label on the pedal's screen for a few seconds (control: 'buttons.<n>'). */
+export async function previewLabel(control: string, text: string, port?: string): Promise<void> {
+  return invoke('preview_label', { port, control, text });
+}
+
+/** Watch the serial console after a write/install; results arrive as boot-failed / boot-ok events */
+export async function watchDeviceBoot(
+  port?: string,
+  timeoutMs?: number,
+  reload?: boolean
+): Promise<string> {
+  return invoke('watch_device_boot', { port, timeoutMs, reload });
+}
+
+/** Open an interactive console session; output arrives as serial-output events */
+export async function connectSerial(port?: string): Promise<string> {
+  return invoke('connect_serial', { port });
+}
+
+export async function disconnectSerial(): Promise<void> {
+  return invoke('disconnect_serial');
+}
+
+/** Send a line to the open console session (e.g. at the >>> prompt) */
+export async function sendRepl(command: string): Promise<void> {
+  return invoke('send_repl', { command });
+}
+
+/** Ctrl-C ('interrupt') or Ctrl-D ('soft_reboot') on the open console session */
+export async function sendReplKey(key: ReplKey): Promise<void> {
+  return invoke('send_repl_key', { key });
+}
+
+/**
+ * Restart the firmware so it loads a new config.json: Ctrl-D over the serial
+ * console (device-rebooted / device-reboot-failed follow), or the reboot
+ * sentinel on the drive at `device` when there is no console
+ */
+export async function rebootDevice(device?: string, port?: string, timeoutMs?: number): Promise<RebootMethod> {
+  return invoke('reboot_device', { device, port, timeoutMs });
+}
+
+// Validation report (errors + lint findings, suppressed ones marked)
+export async function getValidationReport(config: MidiCaptainConfig): Promise<ValidationReport> {
+  return invoke('get_validation_report', { config });
+}
+
+// Errors, warnings and hints; only errors should block a save
+export async function validateConfigDetailed(config: MidiCaptainConfig): Promise<DetailedValidation> {
+  return invoke('validate_config_detailed', { config });
+}
+
+// Plain-language description of one control (inspector, cheat sheet)
+export async function explainControl(config: MidiCaptainConfig, controlId: string): Promise<ControlExplanation> {
+  return invoke('explain_control', { config, controlId });
+}
+
+// Target profiles (what the receiving device does with each CC)
+export async function listTargetProfiles(): Promise<TargetProfile[]> {
+  return invoke('list_target_profiles');
+}
+
+export async function updateProfiles(): Promise<ProfileUpdate> {
+  return invoke('update_profiles');
+}
+
+export async function getProfileUpdateSettings(): Promise<ProfileUpdateSettings> {
+  return invoke('get_profile_update_settings');
+}
+
+export async function setProfileUpdateSettings(settings: ProfileUpdateSettings): Promise<void> {
+  return invoke('set_profile_update_settings', { settings });
+}
+
+// Compare
+export async function diffFiles(pathA: string, pathB: string): Promise<ConfigChange[]> {
+  return invoke('diff_files', { pathA, pathB });
+}
+
+// Device config vs. unsaved editor state, for review before writing
+export async function diffConfig(path: string, candidate: MidiCaptainConfig): Promise<ConfigChange[]> {
+  return invoke('diff_config', { path, candidateJson: JSON.stringify(candidate) });
+}
+
+// Channel remaps
+export async function remapChannels(
+  config: MidiCaptainConfig,
+  mapping: Record<number, number>
+): Promise<MidiCaptainConfig> {
+  return invoke('remap_channels', { config, mapping });
+}
+
+export async function listChannelRemaps(): Promise<ChannelRemap[]> {
+  return invoke('list_channel_remaps');
+}
+
+export async function saveChannelRemap(remap: ChannelRemap): Promise<void> {
+  return invoke('save_channel_remap', { remap });
+}
+
+export async function deleteChannelRemap(name: string): Promise<void> {
+  return invoke('delete_channel_remap', { name });
+}
+
+export async function applyChannelRemap(
+  config: MidiCaptainConfig,
+  name: string,
+  revert = false
+): Promise<MidiCaptainConfig> {
+  return invoke('apply_channel_remap', { config, name, revert });
+}
+
+// Configs as JSON, YAML or TOML text (e.g. for a dotfiles repo)
+export async function exportConfig(path: string, format: TextFormat): Promise<string> {
+  return invoke('export_config', { path, format });
+}
+
+// Upgraded to the current schema but not validated; nothing is written
+export async function importConfig(content: string, format: TextFormat): Promise<MidiCaptainConfig> {
+  return invoke('import_config', { content, format });
+}
+
+// Stock PaintAudio page files (supersetup/pageN.txt)
+export async function exportPaintAudio(config: MidiCaptainConfig, path: string): Promise<InteropIssue[]> {
+  return invoke('export_paintaudio', { config, path });
+}
+
+// Nothing is written; review the config and issues before saving
+export async function importPaintAudio(path: string): Promise<PaintAudioImport> {
+  return invoke('import_paintaudio', { path });
+}
+
+// Firmware test fixture: a pytest module for the repo's tests/ directory
+export async function exportPythonFixture(config: MidiCaptainConfig, path: string): Promise<void> {
+  return invoke('export_python_fixture', { config, path });
+}
+
+// Live monitors (stream readings from the device's serial console)
+export async function startExpMonitor(port?: string): Promise<string> {
+  return invoke('start_exp_monitor', { port });
+}
+
+export async function stopExpMonitor(): Promise<void> {
+  return invoke('stop_exp_monitor');
+}
+
+// Stream pedal readings as for startExpMonitor while recording the raw
+// extremes; sweep each pedal heel to toe, then finish
+export async function startExpressionCalibration(port?: string): Promise<string> {
+  return invoke('start_expression_calibration', { port });
+}
+
+// Stop calibrating and write the recorded range for `pedal` into the config
+// at `device`; fails if the pedal barely moved
+export async function finishExpressionCalibration(
+  device: string,
+  pedal: 'exp1' | 'exp2',
+  expectedToken?: string
+): Promise<Calibration> {
+  return invoke('finish_expression_calibration', { device, pedal, expectedToken });
+}
+
+export async function startEncoderMonitor(port?: string): Promise<string> {
+  return invoke('start_encoder_monitor', { port });
+}
+
+export async function stopEncoderMonitor(): Promise<void> {
+  return invoke('stop_encoder_monitor');
+}
+
+// Listens on the device's USB MIDI port rather than the serial console
+export async function startMidiMonitor(port?: string): Promise<string> {
+  return invoke('start_midi_monitor', { port });
+}
+
+export async function stopMidiMonitor(): Promise<void> {
+  return invoke('stop_midi_monitor');
+}
+
+// Automation scripts
+export async function runScript(source: string): Promise<ScriptOutput> {
+  return invoke('run_script', { source });
+}
+
+// Preset library
+export async function listPresets(): Promise<string[]> {
+  return invoke('list_presets');
+}
+
+export async function loadPreset(name: string): Promise<MidiCaptainConfig> {
+  return invoke('load_preset', { name });
+}
+
+export async function savePreset(name: string, config: MidiCaptainConfig): Promise<void> {
+  return invoke('save_preset', { name, config });
+}
+
+// `device` is the config path, as for readConfig
+export async function savePresetFromDevice(name: string, device: string): Promise<void> {
+  return invoke('save_preset_from_device', { name, device });
+}
+
+// Empty query and tags list every preset
+export async function searchPresets(query = '', tags: string[] = []): Promise<PresetEntry[]> {
+  return invoke('search_presets', { query, tags });
+}
+
+export async function pushPreset(
+  name: string,
+  device: string,
+  force = false,
+  confirmation?: string
+): Promise<Confirmable<null>> {
+  return invoke('push_preset', { name, device, force, confirmation });
+}
+
+export async function deletePreset(name: string): Promise<void> {
+  return invoke('delete_preset', { name });
+}
+
+export async function getLibraryHealth(refresh = false): Promise<LibraryHealth> {
+  return invoke('get_library_health', { refresh });
+}
+
+export async function getLibraryIndex(): Promise<LibraryIndex> {
+  return invoke('get_library_index');
+}
+
+export async function setFavorite(name: string, favorite: boolean): Promise<LibraryIndex> {
+  return invoke('set_favorite', { name, favorite });
+}
+
+export async function setQuickDeploySlot(slot: number, name: string | null): Promise<LibraryIndex> {
+  return invoke('set_quick_deploy_slot', { slot, name });
+}
+
+export async function setPresetMetadata(name: string, metadata: PresetMetadata): Promise<LibraryIndex> {
+  return invoke('set_preset_metadata', { name, metadata });
+}
+
+// Printable chart of the config's MIDI assignments; returns the rendered chart
+export async function exportMidiChart(config: MidiCaptainConfig, format: ChartFormat): Promise<string> {
+  return invoke('export_midi_chart', { config, format });
+}
+
+// Returns the rendered sheet; `setlist` is preset names in running order
+export async function exportSetlistSheet(setlist: string[], format: SheetFormat): Promise<string> {
+  return invoke('export_setlist_sheet', { setlist, format });
+}
+
+export async function quickDeploy(
+  slot: number,
+  device: string,
+  force = false,
+  confirmation?: string
+): Promise<Confirmable<string>> {
+  return invoke('quick_deploy', { slot, device, force, confirmation });
+}
+
+// Deploy hotkey (global shortcut that deploys the selected preset)
+export async function setDeployHotkey(shortcut: string): Promise<void> {
+  return invoke('set_deploy_hotkey', { shortcut });
+}
+
+export async function clearDeployHotkey(): Promise<void> {
+  return invoke('clear_deploy_hotkey');
+}
+
+export async function getDeployHotkey(): Promise<HotkeySettings> {
+  return invoke('get_deploy_hotkey');
+}
+
+export async function checkHotkeyConflict(shortcut: string): Promise<string | null> {
+  return invoke('check_hotkey_conflict', { shortcut });
+}
+
+export async function setHotkeyTarget(target: HotkeyTarget | null): Promise<void> {
+  return invoke('set_hotkey_target', { target });
+}
+
+// Per-device settings (by volume name) and the config write log
+export async function getDeviceSettings(device: string): Promise<DeviceSettings> {
+  return invoke('get_device_settings', { device });
+}
+
+export async function setDeviceSettings(device: string, settings: DeviceSettings): Promise<void> {
+  return invoke('set_device_settings', { device, settings });
+}
+
+export async function getWriteLog(): Promise<WriteLogEntry[]> {
+  return invoke('get_write_log');
+}
+
+// MIDI output: "config changed" SysEx broadcast after each write (off until a port is set)
+export async function listMidiOutputs(): Promise<string[]> {
+  return invoke('list_midi_outputs');
+}
+
+export async function scanMidiPorts(): Promise<MidiPortScan> {
+  return invoke('scan_midi_ports');
+}
+
+// Send one CC/PC/note to a host output to check a mapping before writing
+export async function sendTestMessage(port: string, message: MidiMessage): Promise<void> {
+  return invoke('send_test_message', { port, message });
+}
+
+export async function getMidiBroadcast(): Promise<MidiBroadcastSettings> {
+  return invoke('get_midi_broadcast');
+}
+
+export async function setMidiBroadcast(settings: MidiBroadcastSettings): Promise<void> {
+  return invoke('set_midi_broadcast', { settings });
+}
+
+// Storage
+export async function getStorageUsage(): Promise<StorageUsage> {
+  return invoke('get_storage_usage');
+}
+
+export async function runMaintenance(): Promise<MaintenanceReport> {
+  return invoke('run_maintenance');
+}
+
+export async function getMaintenanceSettings(): Promise<MaintenanceSettings> {
+  return invoke('get_maintenance_settings');
+}
+
+export async function setMaintenanceSettings(settings: MaintenanceSettings): Promise<void> {
+  return invoke('set_maintenance_settings', { settings });
+}
+
+// Notifications
+export async function getNotificationSettings(): Promise<NotificationSettings> {
+  return invoke('get_notification_settings');
+}
+
+export async function setNotificationSettings(settings: NotificationSettings): Promise<void> {
+  return invoke('set_notification_settings', { settings });
+}
+
+// Crash reports
+export async function getCrashReports(): Promise<CrashReport[]> {
+  return invoke('get_crash_reports');
+}
+
+export async function clearCrashReports(): Promise<void> {
+  return invoke('clear_crash_reports');
+}
+
+// Self-test: parse the embedded config corpus
+export async function runSelfTest(): Promise<SelfTestReport> {
+  return invoke('run_self_test');
+}
+
+// Write/verify/erase a scratch file on the device to find failing flash
+export async function runStorageStressTest(devicePath: string, cycles: number): Promise<StressTestReport> {
+  return invoke('run_storage_stress_test', { devicePath, cycles });
+}
+
+// API version (see negotiateApiVersion above)
+export async function getApiVersion(): Promise<ApiVersion> {
+  return invokeCommand('get_api_version');
+}
+
+// App capabilities (which optional subsystems this build has)
+export async function getAppCapabilities(): Promise<AppCapabilities> {
+  return invoke('get_app_capabilities');
+}
+
 // Event listeners
 export function onDeviceConnected(callback: (device: DetectedDevice) => void) {
   return listen<DetectedDevice>('device-connected', (event) => {
@@ -41,8 +763,135 @@ export function onDeviceConnected(callback: (device: DetectedDevice) => void) {
   });
 }
 
-export function onDeviceDisconnected(callback: (name: string) => void) {
-  return listen<string>('device-disconnected', (event) => {
+export function onDeviceDisconnected(callback: (device: DeviceDisconnected) => void) {
+  return listen<DeviceDisconnected>('device-disconnected', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onConfigChangedExternally(callback: (change: ExternalConfigChange) => void) {
+  return listen<ExternalConfigChange>('config-changed-externally', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onDeviceFound(callback: (found: DeviceFound) => void) {
+  return listen<DeviceFound>('device-found', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onDeviceScanComplete(callback: (result: DeviceScanComplete) => void) {
+  return listen<DeviceScanComplete>('device-scan-complete', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onBootFailed(callback: (failure: BootFailure) => void) {
+  return listen<BootFailure>('boot-failed', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onBootOk(callback: (port: string) => void) {
+  return listen<string>('boot-ok', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onSerialOutput(callback: (text: string) => void) {
+  return listen<string>('serial-output', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onSerialDisconnected(callback: (error: string) => void) {
+  return listen<string>('serial-disconnected', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onDeviceRebooted(callback: (port: string) => void) {
+  return listen<string>('device-rebooted', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onDeviceRebootFailed(callback: (error: string) => void) {
+  return listen<string>('device-reboot-failed', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onExpMonitor(callback: (sample: ExpSample) => void) {
+  return listen<ExpSample>('exp-monitor', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onEncoderMonitor(callback: (sample: EncoderSample) => void) {
+  return listen<EncoderSample>('encoder-monitor', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onMidiMessage(callback: (message: MidiMessage) => void) {
+  return listen<MidiMessage>('midi-message', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Payload is the config path the previous config was written back to
+export function onTemporaryConfigRestored(callback: (path: string) => void) {
+  return listen<string>('temporary-config-restored', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onTemporaryConfigError(callback: (error: string) => void) {
+  return listen<string>('temporary-config-error', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onMonitorError(callback: (error: string) => void) {
+  return listen<string>('monitor-error', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onLibraryHealth(callback: (health: LibraryHealth) => void) {
+  return listen<LibraryHealth>('library-health', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onMaintenanceReport(callback: (report: MaintenanceReport) => void) {
+  return listen<MaintenanceReport>('maintenance-report', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onFirmwareDeployProgress(callback: (progress: DeployProgress) => void) {
+  return listen<DeployProgress>('firmware-deploy-progress', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onProfilesUpdated(callback: (update: ProfileUpdate) => void) {
+  return listen<ProfileUpdate>('profiles-updated', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onProfileUpdateFailed(callback: (error: string) => void) {
+  return listen<string>('profile-update-failed', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onHotkeyDeploy(callback: (result: HotkeyDeploy) => void) {
+  return listen<HotkeyDeploy>('hotkey-deploy', (event) => {
     callback(event.payload);
   });
 }
@@ -1,5 +1,5 @@
 import { writable, derived, get } from 'svelte/store';
-import type { MidiCaptainConfig, ButtonConfig, EncoderConfig, DeviceType } from './types';
+import { DEVICE_CAPABILITIES, type MidiCaptainConfig, type ButtonConfig, type EncoderConfig, type DeviceType } from './types';
 import { validateConfig } from './validation';
 
 interface FormState {
@@ -230,95 +230,36 @@ function createDefaultButton(index: number): ButtonConfig {
   };
 }
 
-function createDefaultButtons(startIndex: number, endIndex: number): ButtonConfig[] {
-  const defaults: ButtonConfig[] = [];
-  for (let i = startIndex; i <= endIndex; i++) {
-    defaults.push(createDefaultButton(i));
-  }
-  return defaults;
-}
-
 export function setDevice(deviceType: DeviceType) {
   formState.update(state => {
     const newState = { ...state };
-    const currentDevice = state.config.device;
-    
-    // Switching TO Mini6: preserve STD10-only features
-    if (deviceType === 'mini6' && currentDevice === 'std10') {
-      // Preserve buttons 7-10
-      if (state.config.buttons.length > 6) {
-        newState._hiddenButtons = state.config.buttons.slice(6);
-      }
-      
-      // Preserve encoder config
-      if (state.config.encoder) {
-        newState._hiddenEncoder = structuredClone(state.config.encoder);
-      }
-      
-      // Truncate buttons array and disable encoder
-      newState.config = {
-        ...state.config,
-        device: 'mini6',
-        buttons: state.config.buttons.slice(0, 6),
-        encoder: state.config.encoder ? { ...state.config.encoder, enabled: false } : undefined,
-      };
+    const capabilities = DEVICE_CAPABILITIES[deviceType];
+
+    // Buttons beyond the new device's count are kept aside, and come back
+    // when switching to a device with more switches
+    const all = [...state.config.buttons, ...(state._hiddenButtons || [])];
+    const buttons = all.slice(0, capabilities.buttons);
+    while (buttons.length < capabilities.buttons) {
+      buttons.push(createDefaultButton(buttons.length));
     }
-    
-    // Switching TO STD10: restore preserved features
-    else if (deviceType === 'std10' && currentDevice === 'mini6') {
-      // Ensure we have exactly 6 Mini6 buttons before appending 7-10
-      const mini6Buttons = state.config.buttons.slice(0, 6);
-      while (mini6Buttons.length < 6) {
-        mini6Buttons.push(createDefaultButton(mini6Buttons.length));
-      }
-      
-      newState.config = {
-        ...state.config,
-        device: 'std10',
-        buttons: [
-          ...mini6Buttons,
-          ...(state._hiddenButtons || createDefaultButtons(6, 9)),
-        ],
-        encoder: state._hiddenEncoder || state.config.encoder,
-      };
-      
-      // Clear preserved data
+    const hidden = all.slice(capabilities.buttons);
+    if (hidden.length > 0) {
+      newState._hiddenButtons = hidden;
+    } else {
       delete newState._hiddenButtons;
-      delete newState._hiddenEncoder;
     }
-    
-    // First-time Mini6 initialization
-    else if (deviceType === 'mini6' && !currentDevice) {
-      const buttons = state.config.buttons.slice(0, 6);
-      while (buttons.length < 6) {
-        buttons.push(createDefaultButton(buttons.length));
-      }
-      newState.config = {
-        ...state.config,
-        device: 'mini6',
-        buttons,
-        encoder: state.config.encoder ? { ...state.config.encoder, enabled: false } : undefined,
-      };
-    }
-    
-    // First-time STD10 initialization
-    else if (deviceType === 'std10' && !currentDevice) {
-      const buttons = [...state.config.buttons];
-      while (buttons.length < 10) {
-        buttons.push(createDefaultButton(buttons.length));
-      }
-      newState.config = {
-        ...state.config,
-        device: 'std10',
-        buttons,
-      };
-    }
-    
-    // Same device: no-op
-    else {
-      newState.config = { ...state.config, device: deviceType };
+
+    // Likewise the encoder, on a device without one
+    let encoder = state.config.encoder;
+    if (!capabilities.encoder && encoder) {
+      newState._hiddenEncoder = state._hiddenEncoder || structuredClone(encoder);
+      encoder = { ...encoder, enabled: false };
+    } else if (capabilities.encoder && state._hiddenEncoder) {
+      encoder = state._hiddenEncoder;
+      delete newState._hiddenEncoder;
     }
-    
+
+    newState.config = { ...state.config, device: deviceType, buttons, encoder };
     return pushHistory(newState);
   });
 }
@@ -328,7 +269,9 @@ export function setDevice(deviceType: DeviceType) {
 // the user switches a button's type.
 function normalizeButton(btn: ButtonConfig): ButtonConfig {
   const type = btn.type ?? 'cc';
-  const { cc, cc_on, cc_off, note, velocity_on, velocity_off, program, pc_step, flash_ms, ...common } = btn;
+  const { cc, cc_on, cc_off, note, velocity_on, velocity_off, program, pc_step, reset_ccs, steps, flash_ms, tap_tempo, ...rest } = btn;
+  // Tap-tempo settings only mean something in tap-tempo mode
+  const common = { ...rest, ...(btn.mode === 'tap_tempo' && tap_tempo !== undefined && { tap_tempo }) };
 
   switch (type) {
     case 'cc':
@@ -358,6 +301,18 @@ function normalizeButton(btn: ButtonConfig): ButtonConfig {
         ...(pc_step !== undefined && { pc_step }),
         ...(flash_ms !== undefined && { flash_ms }),
       };
+    case 'panic':
+      return {
+        ...common,
+        ...(reset_ccs !== undefined && { reset_ccs }),
+        ...(flash_ms !== undefined && { flash_ms }),
+      };
+    case 'macro':
+      return {
+        ...common,
+        ...(steps !== undefined && { steps }),
+        ...(flash_ms !== undefined && { flash_ms }),
+      };
     default:
       return btn;
   }
@@ -369,6 +324,9 @@ export function normalizeConfig(cfg: MidiCaptainConfig): MidiCaptainConfig {
   if (normalized.display && Object.values(normalized.display).every(v => v === undefined)) {
     delete normalized.display;
   }
+  if (normalized.global && Object.values(normalized.global).every(v => v === undefined)) {
+    delete normalized.global;
+  }
   return normalized;
 }
 
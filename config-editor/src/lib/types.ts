@@ -1,14 +1,92 @@
 // MIDI Captain config types - mirrors Rust structs
 
-export type ButtonColor = 
+export type NamedColor =
   | 'red' | 'green' | 'blue' | 'yellow' 
   | 'cyan' | 'magenta' | 'orange' | 'purple' | 'white';
 
-export type ButtonMode = 'toggle' | 'momentary';
+// Palette name, or any other color as '#RRGGBB'
+export type ButtonColor = NamedColor | `#${string}`;
+
+export type ButtonMode = 'toggle' | 'momentary' | 'tap_tempo';  // tap_tempo: CC buttons only
 export type OffMode = 'dim' | 'off';
-export type MessageType = 'cc' | 'note' | 'pc' | 'pc_inc' | 'pc_dec';
+export type MessageType = 'cc' | 'note' | 'pc' | 'pc_inc' | 'pc_dec' | 'panic' | 'macro';
 export type Polarity = 'normal' | 'inverted';
-export type DeviceType = 'std10' | 'mini6';
+export type CurveShape = 'linear' | 'log' | 'exp';
+// Named shape, or [travel, output] breakpoints (0-127) interpolated linearly
+export type Curve = CurveShape | [number, number][];
+export type DeviceType = 'std10' | 'mini6' | 'nano4' | 'blue';
+
+export interface DeviceCapabilities {
+  name: string;
+  buttons: number;
+  encoder: boolean;     // Rotary encoder with push switch
+  expression: boolean;  // Two expression pedal inputs
+  din: boolean;         // 5-pin DIN MIDI out
+  firmware: boolean;    // The firmware has a device module for it
+}
+
+// Mirrors DeviceType::button_count / has_encoder / has_expression / has_din /
+// firmware_supported in config.rs
+export const DEVICE_CAPABILITIES: Record<DeviceType, DeviceCapabilities> = {
+  std10: { name: 'STD10', buttons: 10, encoder: true, expression: true, din: true, firmware: true },
+  mini6: { name: 'Mini6', buttons: 6, encoder: false, expression: false, din: false, firmware: true },
+  nano4: { name: 'Nano4', buttons: 4, encoder: false, expression: false, din: false, firmware: false },
+  blue: { name: 'Blue', buttons: 10, encoder: false, expression: true, din: true, firmware: false },
+};
+
+// Long-press / double-tap action (its own CC or PC message)
+export type SecondaryType = 'cc' | 'pc';
+
+export interface SecondaryAction {
+  type?: SecondaryType;    // defaults to 'cc'
+  cc?: number;             // Required for type='cc'
+  value?: number;          // CC value sent (firmware default: 127)
+  program?: number;        // Required for type='pc'
+  channel?: number;        // Stored as 0-15; defaults to the button's channel
+}
+
+// Tap tempo (mode 'tap_tempo'): 'cc' sends the button's CC on every tap,
+// 'clock' runs MIDI clock at the tapped tempo
+export type TapOutput = 'cc' | 'clock';
+
+// BPM_LIMITS and the defaults in core/tempo.py
+export const MIN_TAP_BPM = 20;
+export const MAX_TAP_BPM = 300;
+export const DEFAULT_MIN_BPM = 40;
+export const DEFAULT_MAX_BPM = 240;
+
+export interface TapTempoConfig {
+  output?: TapOutput;  // default: 'cc'
+  min_bpm?: number;    // default: 40; a longer gap between taps starts over
+  max_bpm?: number;    // default: 240
+}
+
+// Macro limits the firmware plays (MAX_MACRO_STEPS / MAX_STEP_DELAY_MS in core/macro.py)
+export const MAX_MACRO_STEPS = 16;
+export const MAX_STEP_DELAY_MS = 5000;
+
+export type MacroStepType = 'cc' | 'pc' | 'note';
+
+// One message of a macro button
+export interface MacroStep {
+  type?: MacroStepType;    // defaults to 'cc'
+  cc?: number;             // Required for type='cc'
+  value?: number;          // CC value (firmware default: 127)
+  program?: number;        // Required for type='pc'
+  note?: number;           // Required for type='note'
+  velocity?: number;       // Note On velocity (default: 127; 0 = note off)
+  channel?: number;        // Stored as 0-15; defaults to the button's channel
+  delay_ms?: number;       // Wait after the previous step (default: 0)
+}
+
+// LED colors and brightness (0-100 %), replacing off_mode for the LED;
+// the display keeps showing the button color
+export interface LedConfig {
+  on_color?: ButtonColor;   // defaults to the button (or state) color
+  off_color?: ButtonColor;  // defaults to the on color
+  on_brightness?: number;   // default: 100
+  off_brightness?: number;  // default: 15, or 0 with off_mode 'off'
+}
 
 export interface StateOverride {
   cc?: number;
@@ -28,7 +106,9 @@ export interface ButtonConfig {
   color: ButtonColor;
   type?: MessageType;      // defaults to 'cc'
   mode?: ButtonMode;
+  tap_tempo?: TapTempoConfig;  // Used when mode is 'tap_tempo'
   off_mode?: OffMode;
+  led?: LedConfig;         // Takes over from off_mode on firmware that knows it
   channel?: number;        // Stored as 0-15, displayed as 1-16
   // CC fields (type='cc')
   cc?: number;
@@ -42,11 +122,18 @@ export interface ButtonConfig {
   program?: number;        // Program number 0-127
   // PC inc/dec fields (type='pc_inc' | 'pc_dec')
   pc_step?: number;        // Step size (default: 1)
-  // PC flash feedback (all PC types)
+  // Panic fields (type='panic')
+  reset_ccs?: number[];    // CCs set to 0 on every channel after all-notes/sound-off
+  // Macro fields (type='macro')
+  steps?: MacroStep[];     // Sent in order on press, at most MAX_MACRO_STEPS
+  // PC flash feedback (all PC types, panic and macro)
   flash_ms?: number;       // LED flash duration in ms (default: 200)
   // Keytimes cycling
   keytimes?: number;         // States to cycle through on press (1-99); 1 = no cycling
   states?: StateOverride[];  // Per-state overrides; length should match keytimes
+  // Gestures; when either is set the button's own message goes out on release
+  long_press?: SecondaryAction;
+  double_tap?: SecondaryAction;
 }
 
 export interface EncoderPush {
@@ -59,6 +146,9 @@ export interface EncoderPush {
   cc_off?: number;   // CC value when button is OFF (default: 0)
 }
 
+// Encoder acceleration: 'off' for fine control, 'high' for fast scrolling
+export type Acceleration = 'off' | 'low' | 'high';
+
 export interface EncoderConfig {
   enabled: boolean;
   cc: number;
@@ -69,6 +159,23 @@ export interface EncoderConfig {
   steps?: number | null;
   channel?: number;  // Stored as 0-15, displayed as 1-16
   push?: EncoderPush;
+  acceleration?: Acceleration;  // default: 'off'
+  wrap?: boolean;               // Wrap past max/min (default: false)
+}
+
+export type Destination = 'usb' | 'din' | 'both';
+
+// MIDI outputs the firmware opens at boot
+export interface RoutingConfig {
+  usb?: boolean;  // default: true
+  din?: boolean;  // 5-pin DIN out, STD10 only (default: false)
+}
+
+// Device-wide settings; the default MIDI channel is global_channel
+export interface GlobalConfig {
+  led_brightness?: number;      // Percent 0-100 (firmware default: 30)
+  display_brightness?: number;  // Percent 0-100; backlight left alone when absent
+  sleep_minutes?: number;       // Dark after this long without input (0-240); 0/absent = never
 }
 
 export interface ExpressionConfig {
@@ -80,6 +187,15 @@ export interface ExpressionConfig {
   polarity?: Polarity;
   threshold?: number;
   channel?: number;  // Stored as 0-15, displayed as 1-16
+  destination?: Destination;  // Outputs the CC goes to (default: 'usb')
+  calibration?: Calibration;  // Measured ADC travel; firmware learns it when absent
+  curve?: Curve;  // Response curve (default: 'linear')
+}
+
+// Raw ADC readings (0-65535) at heel and toe, from finish_expression_calibration
+export interface Calibration {
+  raw_min: number;
+  raw_max: number;
 }
 
 export interface ExpressionPedals {
@@ -93,15 +209,35 @@ export interface DisplayConfig {
   expression_text_size?: 'small' | 'medium' | 'large';
 }
 
+export interface Suppression {
+  rule: string;           // Lint rule id, e.g. "duplicate-cc"
+  target?: string;        // e.g. "buttons[2]"; all locations when absent
+  justification: string;  // Required
+}
+
+export interface EditorMetadata {
+  suppressions?: Suppression[];
+  locked?: boolean;  // Writes are refused unless forced
+  permissive_write?: number;  // Unix timestamp of a permissive-mode write
+  target_profile?: string;    // Target profile id; MIDI standard when absent
+}
+
+// Validation level for validate/write; held for the session only
+export type Strictness = 'strict' | 'normal' | 'permissive';
+
 export interface MidiCaptainConfig {
   device?: DeviceType;
+  version?: number;         // Schema version the config was written for; absent = before versioning
   global_channel?: number;  // Stored as 0-15, displayed as 1-16
+  global?: GlobalConfig;
   usb_drive_name?: string;  // Custom USB drive label (max 11 chars, alphanumeric + underscore)
   dev_mode?: boolean;       // true = USB always mounts; false (default) = switch-gated
   buttons: ButtonConfig[];
   encoder?: EncoderConfig;
   expression?: ExpressionPedals;
   display?: DisplayConfig;
+  routing?: RoutingConfig;
+  _editor?: EditorMetadata;  // Editor-only metadata, ignored by firmware
 }
 
 export interface DetectedDevice {
@@ -109,15 +245,408 @@ export interface DetectedDevice {
   path: string;
   config_path: string;
   has_config: boolean;
+  safe_mode: boolean;
+  safe_mode_reason?: string | null;  // From safemode.txt / boot_out.txt
+  validation?: ValidationSummary | null;  // Set on device-connected events
+  model?: DeviceType | null;              // From the files on the drive; null when unknown
+  model_source?: ModelSource | null;      // Null when the model is unknown
+}
+
+// config.json's device field, boot_out.txt's board ID, or stock code.py's LED count
+export type ModelSource = 'config' | 'boot_out' | 'firmware';
+
+// Payload of "device-disconnected"; devices are told apart by path
+export interface DeviceDisconnected {
+  name: string;
+  path: string;
+}
+
+// push_config_to_all, one per connected device
+export interface PushResult {
+  name: string;
+  path: string;
+  token?: string | null;       // Set when the write succeeded
+  error?: ConfigError | null;
+}
+
+// Emitted as "device-found" by scanDevicesAsync
+export interface DeviceFound {
+  scan_id: string;
+  device: DetectedDevice;
+}
+
+// Emitted as "config-changed-externally" when a connected device's
+// config.json is changed by another program or the device itself
+export interface ExternalConfigChange {
+  path: string;
+  hash: string | null;  // SHA-256 of the new contents; null when deleted
+}
+
+// Emitted as "device-scan-complete" when a scanDevicesAsync scan ends
+export interface DeviceScanComplete {
+  scan_id: string;
+  found: number;  // Devices reported by device-found
+}
+
+export interface ValidationSummary {
+  valid: boolean;
+  error_count: number;
+  errors: string[];
+  schema_version: number;  // Schema the config was validated against
+}
+
+export interface DeviceHealth {
+  volume_path: string;
+  safe_mode: boolean;
+  safe_mode_reason?: string | null;
+  free_bytes?: number | null;  // Free space on the volume; null when unknown
+  read_only: boolean;          // Volume mounted read-only
+  config_writable: boolean;    // config.json can be written (or created)
+  suggestions: string[];  // Recovery steps, most relevant first
+}
+
+// Parsed from the device's boot_out.txt
+export interface BootInfo {
+  circuitpython_version?: string | null;  // e.g. "7.3.3"
+  build_date?: string | null;             // e.g. "2022-08-29"
+  board?: string | null;                  // e.g. "Raspberry Pi Pico with rp2040"
+  board_id?: string | null;               // e.g. "raspberry_pi_pico"
+  uid?: string | null;
+}
+
+export interface DeviceInfo {
+  volume_path: string;
+  boot?: BootInfo | null;              // null when boot_out.txt is missing
+  firmware_version?: string | null;    // MIDI Captain firmware (VERSION file)
+  config_version?: number | null;      // config.json schema version (0 = unversioned)
+  warnings: string[];                  // Config schema vs firmware mismatches
+}
+
+export type OrphanKind = 'host_metadata' | 'leftover' | 'stale_source' | 'bank_file' | 'unknown';
+
+export interface OrphanFile {
+  path: string;             // Relative to the volume root
+  kind: OrphanKind;
+  bytes: number;
+  safe_to_delete: boolean;  // Removing it cannot affect firmware or config
+  suggestion: string;
+}
+
+export interface FirmwareSlot {
+  version?: string | null;  // From the device's VERSION file
+  created_at: number;       // Unix timestamp (seconds)
+  entries: string[];        // Top-level files/dirs captured
+  compressed: boolean;      // Stored zstd-compressed (false for older slots)
+}
+
+// One file handled by deployFirmware ("firmware-deploy-progress" event)
+export interface DeployProgress {
+  file: string;      // Relative to the device root, e.g. "lib/neopixel.mpy"
+  done: number;      // Files handled so far, including this one
+  total: number;
+  skipped: boolean;  // Already on the device unchanged
+}
+
+export interface FirmwareDeploy {
+  version?: string | null;           // Installed bundle's VERSION
+  previous_version?: string | null;  // Now in the rollback slot
+  copied: string[];                  // Files written and verified
+  unchanged: number;
+  removed: string[];                 // Stale files removed from core/ and devices/
+}
+
+export interface RecoveryPoint {
+  name: string;
+  device?: string | null;            // Volume name it was captured from
+  created_at: number;                // Unix timestamp (seconds)
+  firmware_version?: string | null;  // From the device's VERSION file
+  has_config: boolean;               // The device had a config.json
+}
+
+// A complete config stored in the device's profiles/ directory
+export interface DeviceProfile {
+  name: string;
+  bytes: number;
+  modified?: number | null;          // Unix timestamp (seconds)
+  active: boolean;                   // config.json currently holds this profile
+}
+
+export interface ConfigBackup {
+  id: string;                        // Pass to restoreBackup
+  device: string;                    // Volume name the config came from
+  file_name: string;                 // Config file that was backed up
+  created_at: number;                // Unix timestamp (seconds)
+  size: number;                      // Compressed size in bytes
+}
+
+export type LibraryState = 'ok' | 'missing' | 'outdated' | 'unknown';
+
+export interface LibraryStatus {
+  name: string;
+  state: LibraryState;
+  installed_version?: string | null;
+  required_version?: string | null;
+  required_by: string[];   // Firmware files (or libraries) that import it
+}
+
+export interface DependencyReport {
+  circuitpython_version?: string | null;
+  libraries: LibraryStatus[];
+  missing: string[];
+  outdated: string[];
+  bundle_url?: string | null;  // Adafruit bundle for the device's CircuitPython major version
+}
+
+export interface CachedDownload {
+  url?: string | null;
+  path: string;
+  sha256: string;
+  size: number;
+  from_cache: boolean;  // Served from the offline cache without network access
+}
+
+// Control keys for send_repl_key: Ctrl-C (stop code.py) and Ctrl-D (soft reboot)
+export type ReplKey = 'interrupt' | 'soft_reboot';
+
+// How reboot_device restarted the firmware; only 'serial' reports back
+// with a device-rebooted event
+export type RebootMethod = 'serial' | 'sentinel';
+
+export interface ConsolePort {
+  port_name: string;
+  product?: string | null;
+  serial_number?: string | null;
+}
+
+// Payload of the 'boot-failed' event
+export interface BootFailure {
+  port: string;
+  traceback: string;
+  error: string;       // Final exception line, e.g. "ImportError: no module named 'x'"
+  suggestion: string;  // Recovery hint (usually rollback)
+}
+
+export interface ChannelRemap {
+  name: string;
+  mapping: Record<number, number>;  // Source → target channel (0-15, as stored)
+}
+
+// Conversion to/from the stock PaintAudio page format
+export type InteropIssueKind = 'dropped' | 'approximated';
+
+export interface InteropIssue {
+  location: string;  // e.g. "buttons[2]" (export) or "key2" (import)
+  kind: InteropIssueKind;
+  message: string;
+}
+
+export interface PaintAudioImport {
+  config: MidiCaptainConfig;
+  issues: InteropIssue[];
+}
+
+// Text format for exportConfig / importConfig
+export type TextFormat = 'json' | 'yaml' | 'toml';
+
+// One fix made by repairConfig
+export interface ConfigFix {
+  location: string;  // "line 12" for text fixes, else a field path, e.g. "buttons[3].cc"
+  message: string;
+}
+
+export interface RepairedConfig {
+  config: MidiCaptainConfig;
+  fixes: ConfigFix[];  // Empty when the file was fine
+}
+
+export interface ConfigChange {
+  path: string;  // e.g. "buttons[2].color"
+  kind: 'added' | 'removed' | 'changed';
+  before?: unknown;
+  after?: unknown;
+}
+
+// A config revision undo/redo can bring back (get_write_history)
+export interface HistoryEntry {
+  created_at: number;        // When it was replaced (unix seconds)
+  changes: ConfigChange[];   // What restoring it would change now
+}
+
+export interface WriteHistory {
+  undo: HistoryEntry[];  // Nearest first
+  redo: HistoryEntry[];
+}
+
+// A built-in config template (list_templates)
+export interface TemplateInfo {
+  id: string;            // Passed to apply_template
+  name: string;
+  description: string;
+  button_count: number;  // Switches it assigns; smaller devices get the first ones
+}
+
+export interface Finding {
+  rule: string;
+  target: string;
+  message: string;
+  severity: Severity;  // 'warning', or 'hint' for advisory rules
+  suppressed: boolean;
+  justification?: string | null;
+}
+
+export type Severity = 'error' | 'warning' | 'hint';
+
+export interface ValidationIssue {
+  path: string;        // Form path of the offending field, e.g. "buttons[3].cc" (0-based)
+  code: string;        // e.g. "out_of_range", "too_long", "button_count"
+  severity: Severity;
+  message: string;
+}
+
+export interface ValidationReport {
+  errors: ValidationIssue[];
+  findings: Finding[];
+}
+
+// validate_config_detailed; suppressed findings are left out
+export interface DetailedValidation {
+  errors: ValidationIssue[];    // Block the save
+  warnings: ValidationIssue[];  // Duplicate CCs, labels cut off, ...
+  hints: ValidationIssue[];
+}
+
+export interface PedalReading {
+  raw: number;     // ADC value (0-65535)
+  mapped: number;  // After calibration/polarity/min-max (0-127)
+  sent: number;    // Last value sent, after the threshold filter
+}
+
+export interface ExpSample {
+  exp1: PedalReading;
+  exp2: PedalReading;
+}
+
+export interface EncoderSample {
+  position: number;       // Raw detent count since boot
+  value: number;          // Internal value (0-127)
+  slot?: number | null;   // Current slot in stepped mode
+  steps?: number | null;  // Number of slots, when stepped
+}
+
+export interface ScriptOutput {
+  result: unknown;   // Value of the script's last expression
+  output: string[];  // Lines written with print/debug
+}
+
+export interface CrashReport {
+  timestamp: number;              // Unix timestamp (seconds)
+  app_version: string;
+  message: string;
+  location?: string | null;       // e.g. "src/serial.rs:120:9"
+  thread?: string | null;
+  command?: string | null;        // Command running when the panic happened
+  args_summary?: string | null;   // Argument names and shapes only
+  backtrace: string;
+}
+
+export type PresetStatus = 'ok' | 'needs_migration' | 'needs_repair';
+
+export interface PresetHealth {
+  name: string;
+  status: PresetStatus;
+  details: string[];              // What needs migrating or repairing
+  permissive: boolean;            // Saved from a permissive-mode write
+}
+
+export interface LibraryHealth {
+  checked_at: number;             // Unix timestamp (seconds)
+  presets: PresetHealth[];
+}
+
+// Song metadata for a preset, printed on setlist sheets
+export interface PresetMetadata {
+  description?: string | null;
+  tags?: string[];        // Free-form labels; searchPresets filters on them
+  song?: string | null;
+  key?: string | null;    // e.g. "E", "F#m"
+  tempo?: number | null;  // BPM, 20-400
+  notes?: string | null;  // Pedal notes for the song
+}
+
+// A preset as listed by searchPresets
+export interface PresetEntry {
+  name: string;
+  favorite: boolean;
+  slot?: number | null;   // Quick-deploy slot holding it
+  metadata: PresetMetadata;
+}
+
+export interface LibraryIndex {
+  favorites: string[];            // Preset names, in the order they were pinned
+  slots: Record<string, string>;  // Quick-deploy slot (1-9) → preset name
+  metadata?: Record<string, PresetMetadata>;  // Preset name → song metadata
+}
+
+export type SheetFormat = 'text' | 'markdown' | 'html';
+
+// Output format of exportMidiChart
+export type ChartFormat = 'markdown' | 'csv' | 'html';
+
+export interface StorageArea {
+  name: string;                   // "presets", "rollback", "downloads", "crashes"
+  path: string;
+  files: number;
+  bytes: number;                  // On disk (compressed size for compressed files)
+}
+
+export interface StorageUsage {
+  areas: StorageArea[];
+  total_bytes: number;
+}
+
+export interface MaintenanceSettings {
+  quota_bytes: number;            // Total storage to stay under
+  retention_days: number;         // Items younger than this are never pruned
+}
+
+// Which events raise a native OS notification (shown only while the editor
+// is in the background)
+export interface NotificationSettings {
+  device_connected: boolean;
+  deploy_finished: boolean;
+  deploy_failed: boolean;
+  firmware_update: boolean;  // Device firmware older than this editor's release
+  sound: boolean;            // Play the system notification sound
+}
+
+export interface PruneCandidate {
+  area: string;                   // "rollback", "downloads" or "crashes"
+  path: string;
+  bytes: number;
+  created_at: number;             // Unix timestamp (seconds)
+}
+
+export interface MaintenanceReport {
+  removed: PruneCandidate[];
+  freed_bytes: number;
+  total_bytes: number;            // Storage used after the run
+  quota_bytes: number;
 }
 
 export interface ConfigError {
   message: string;
   details?: string[];
+  code?: 'read_only_mode' | 'guest_passphrase_invalid' | 'device_full' | 'device_read_only' | 'confirmation_invalid' | 'config_conflict' | 'config_locked';  // Set on errors the UI handles specially
 }
 
+// Outcome of a destructive command: called without a confirmation token it
+// does nothing and returns one; call again with the token to go ahead
+export type Confirmable<T> =
+  | { status: 'confirmation_required'; token: string; summary: string }
+  | { status: 'done'; result: T };
+
 // Color mapping for UI
-export const BUTTON_COLORS: Record<ButtonColor, string> = {
+export const BUTTON_COLORS: Record<NamedColor, string> = {
   red: '#ff0000',
   green: '#00ff00',
   blue: '#0000ff',
@@ -128,3 +657,186 @@ export const BUTTON_COLORS: Record<ButtonColor, string> = {
   purple: '#8000ff',
   white: '#ffffff',
 };
+
+// CSS color for a palette name or '#RRGGBB' value
+export function cssColor(color: ButtonColor): string {
+  return color.startsWith('#') ? color : BUTTON_COLORS[color as NamedColor];
+}
+
+export interface SelfTestFailure {
+  name: string;      // Corpus file, e.g. "firmware/config-mini6.json"
+  errors: string[];
+}
+
+export interface SelfTestReport {
+  total: number;
+  passed: number;
+  failures: SelfTestFailure[];
+}
+
+export interface StressFault {
+  cycle: number;
+  message: string;
+}
+
+export interface StressTestReport {
+  cycles: number;                    // Requested (clamped to 1-1000)
+  completed: number;                 // Run before finishing or stopping on faults
+  bytes_per_cycle: number;
+  write_bytes_per_sec: number;       // Including the sync
+  read_bytes_per_sec: number;
+  faults: StressFault[];
+}
+
+export interface Subsystem {
+  name: string;             // e.g. "serial", "midi"
+  available: boolean;       // Compiled in and usable on this platform
+  version?: string | null;  // Library version, when there is one
+}
+
+// How the device is attached over USB (see scan_midi_ports)
+export type UsbMode = 'disconnected' | 'midi_only' | 'drive_only' | 'midi_and_drive';
+
+export interface MidiPortScan {
+  inputs: string[];   // Device MIDI ports the host can read from
+  outputs: string[];  // Device MIDI ports the host can send to
+  mode: UsbMode;
+}
+
+// Payload of the "midi-message" event, and what sendTestMessage sends; channels are 0-15
+export type MidiMessage =
+  | { type: 'cc'; channel: number; cc: number; value: number }
+  | { type: 'pc'; channel: number; program: number }
+  | { type: 'note'; channel: number; note: number; velocity: number; on: boolean };
+
+export interface MidiBroadcastSettings {
+  port?: string | null;  // MIDI output name; no broadcast when unset
+}
+
+export interface ApiVersion {
+  version: number;
+  min_frontend_version: number;  // Oldest frontend the backend still serves
+}
+
+export type ApiErrorKind = 'frontend_too_old' | 'frontend_too_new';
+
+// Rejection from negotiate_api_version
+export interface ApiVersionError {
+  kind: ApiErrorKind;
+  message: string;
+  backend_version: number;
+  frontend_version: number;
+}
+
+export interface AppCapabilities {
+  app_version: string;
+  tauri_version: string;
+  os: string;
+  arch: string;
+  debug_build: boolean;
+  subsystems: Subsystem[];
+}
+
+export interface ControlExplanation {
+  control_id: string;  // e.g. "buttons[2]", "encoder.push", "expression.exp1"
+  title: string;       // e.g. "Switch 3 (DELAY)"
+  summary: string;     // What the control sends
+  details: string[];   // Further behavior, one fact per line
+}
+
+export interface HotkeySettings {
+  shortcut?: string | null;  // e.g. "CmdOrCtrl+Shift+D"; none when disabled
+  error?: string | null;     // Why it couldn't be registered at startup
+}
+
+export interface HotkeyTarget {
+  preset: string;  // Preset name in the library
+  device: string;  // Device config path
+}
+
+export interface HotkeyDeploy {
+  preset?: string | null;
+  device?: string | null;
+  error?: string | null;  // Set when nothing was written
+}
+
+export type SwitchBehavior = 'latching' | 'momentary';
+
+export interface CcFunction {
+  cc: number;
+  name: string;
+  behavior?: SwitchBehavior;  // Set for switch functions
+}
+
+export interface TargetProfile {
+  id: string;  // e.g. "midi-standard"
+  name: string;
+  controls: CcFunction[];
+}
+
+// Where target profile updates come from (a JSON array of TargetProfile,
+// with its SHA-256 published at `${url}.sha256`)
+export interface ProfileUpdateSettings {
+  url?: string | null;    // https only; no updates while unset
+  interval_hours: number;
+}
+
+export interface ProfileUpdate {
+  sha256: string;
+  count: number;          // Profiles in the downloaded set
+  changed: boolean;       // False when it matched the installed profiles
+  updated_at: number;     // Unix timestamp (seconds)
+}
+
+// Read-back check after a config write: none, SHA-256 compare, or full decode and compare
+export type VerifyLevel = 'none' | 'hash' | 'full';
+
+export interface DeviceSettings {
+  verification: VerifyLevel;  // Default "hash"
+}
+
+export interface WriteLogEntry {
+  timestamp: number;        // Unix timestamp (seconds)
+  path: string;
+  device?: string | null;   // Volume name
+  verification: VerifyLevel;
+  ok: boolean;
+  error?: string | null;
+  problems?: string[];      // Let through by a permissive-mode write
+}
+
+// A config written by deployTemporary, waiting to be restored
+export interface TemporaryDeploy {
+  path: string;
+  restore_at: number;  // Unix timestamp (seconds) when the previous config comes back
+}
+
+// Config schema migrations applied on read (see src-tauri/src/migrations.rs)
+export interface AppliedMigration {
+  name: string;       // e.g. "firmware_defaults"
+  changes: string[];  // e.g. 'buttons[3].color: "Pink" -> "white"'
+}
+
+export interface MigrationReport {
+  from_version: number;  // 0 = unversioned
+  to_version: number;
+  applied: AppliedMigration[];  // Empty when the config was current
+}
+
+export interface MigratedConfig {
+  config: MidiCaptainConfig;
+  migration: MigrationReport;
+  token: string;  // Pass to writeConfig as expectedToken
+}
+
+// readConfig result; `token` identifies the contents read
+export interface ReadConfig {
+  config: MidiCaptainConfig;
+  token: string;
+}
+
+// readConfigRaw result
+export interface RawConfig {
+  json: string;
+  token: string;
+}
@@ -1,4 +1,7 @@
-import type { MidiCaptainConfig } from './types';
+import {
+  BUTTON_COLORS, DEVICE_CAPABILITIES, MAX_MACRO_STEPS, MAX_STEP_DELAY_MS,
+  MIN_TAP_BPM, MAX_TAP_BPM, DEFAULT_MIN_BPM, DEFAULT_MAX_BPM, type MidiCaptainConfig,
+} from './types';
 
 export interface ValidationResult {
   isValid: boolean;
@@ -10,6 +13,13 @@ export interface FieldValidator {
 }
 
 export const validators = {
+  color: (value: string): string | null => {
+    if (value in BUTTON_COLORS || /^#[0-9a-fA-F]{6}$/.test(value)) {
+      return null;
+    }
+    return 'Color must be a palette color or #RRGGBB';
+  },
+
   label: (value: string): string | null => {
     if (!value || value.trim() === '') {
       return 'Label is required';
@@ -17,8 +27,9 @@ export const validators = {
     if (value.length > 6) {
       return 'Label must be 6 characters or less';
     }
-    if (!/^[\w\s-]+$/.test(value)) {
-      return 'Label contains invalid characters';
+    // The device's fonts only cover printable ASCII (sanitizeLabels transliterates)
+    if (!/^[ -~]+$/.test(value)) {
+      return "Label has characters the display can't show";
     }
     return null;
   },
@@ -40,6 +51,22 @@ export const validators = {
     return null;
   },
   
+  // Custom curve breakpoints: travel 0 to 127, rising; output never falling
+  curve: (points: [number, number][]): string | null => {
+    if (points.length < 2) return 'Curve needs at least 2 breakpoints';
+    if (points.some(([t, o]) => !Number.isInteger(t) || !Number.isInteger(o) || t < 0 || t > 127 || o < 0 || o > 127)) {
+      return 'Breakpoints must be whole numbers between 0 and 127';
+    }
+    if (points[0][0] !== 0 || points[points.length - 1][0] !== 127) {
+      return 'Breakpoints must run from travel 0 to 127';
+    }
+    for (let i = 1; i < points.length; i++) {
+      if (points[i][0] <= points[i - 1][0]) return 'Travel must increase at every breakpoint';
+      if (points[i][1] < points[i - 1][1]) return 'Output must not decrease';
+    }
+    return null;
+  },
+
   withinRange: (value: number, min: number, max: number): string | null => {
     if (value < min || value > max) {
       return `Value must be between ${min} and ${max}`;
@@ -94,25 +121,35 @@ export const validators = {
 export function validateConfig(config: MidiCaptainConfig): ValidationResult {
   const errors = new Map<string, string>();
   
-  // Device-specific validation
-  if (config.device === 'mini6') {
-    if (config.buttons.length > 6) {
-      errors.set('device', 'Mini6 supports only 6 buttons');
-    }
-    if (config.encoder?.enabled) {
-      errors.set('encoder.enabled', 'Mini6 does not support encoder');
-    }
-  } else if (config.device === 'std10') {
-    if (config.buttons.length > 10) {
-      errors.set('device', 'STD10 supports only 10 buttons');
+  for (const field of ['led_brightness', 'display_brightness'] as const) {
+    const value = config.global?.[field];
+    if (value !== undefined) {
+      const e = validators.withinRange(value, 0, 100);
+      if (e) errors.set(`global.${field}`, e);
     }
   }
+  if (config.global?.sleep_minutes !== undefined) {
+    const e = validators.withinRange(config.global.sleep_minutes, 0, 240);
+    if (e) errors.set('global.sleep_minutes', e);
+  }
+
+  // Device-specific validation
+  const device = DEVICE_CAPABILITIES[config.device];
+  if (config.buttons.length > device.buttons) {
+    errors.set('device', `${device.name} supports only ${device.buttons} buttons`);
+  }
+  if (config.encoder?.enabled && !device.encoder) {
+    errors.set('encoder.enabled', `${device.name} does not support encoder`);
+  }
   
   // Validate all buttons
   config.buttons.forEach((btn, idx) => {
     const labelError = validators.label(btn.label);
     if (labelError) errors.set(`buttons[${idx}].label`, labelError);
 
+    const colorError = validators.color(btn.color);
+    if (colorError) errors.set(`buttons[${idx}].color`, colorError);
+
     const msgType = btn.type ?? 'cc';
 
     if (msgType === 'cc') {
@@ -120,6 +157,19 @@ export function validateConfig(config: MidiCaptainConfig): ValidationResult {
         const ccError = validators.cc(btn.cc);
         if (ccError) errors.set(`buttons[${idx}].cc`, ccError);
       }
+      if (btn.cc_on !== undefined) {
+        const e = validators.withinRange(btn.cc_on, 0, 127);
+        if (e) errors.set(`buttons[${idx}].cc_on`, e);
+      }
+      if (btn.cc_off !== undefined) {
+        const e = validators.withinRange(btn.cc_off, 0, 127);
+        if (e) errors.set(`buttons[${idx}].cc_off`, e);
+      }
+      if ((btn.cc_on !== undefined || btn.cc_off !== undefined)
+          && (btn.cc_on ?? 127) === (btn.cc_off ?? 0)
+          && !errors.has(`buttons[${idx}].cc_off`)) {
+        errors.set(`buttons[${idx}].cc_off`, 'OFF value must differ from ON value');
+      }
     } else if (msgType === 'note') {
       if (btn.note !== undefined) {
         const noteError = validators.note(btn.note);
@@ -143,6 +193,44 @@ export function validateConfig(config: MidiCaptainConfig): ValidationResult {
         const stepError = validators.pcStep(btn.pc_step);
         if (stepError) errors.set(`buttons[${idx}].pc_step`, stepError);
       }
+    } else if (msgType === 'panic') {
+      btn.reset_ccs?.forEach((cc, r) => {
+        const ccError = validators.cc(cc);
+        if (ccError) errors.set(`buttons[${idx}].reset_ccs[${r}]`, ccError);
+      });
+    } else if (msgType === 'macro') {
+      const steps = btn.steps ?? [];
+      if (steps.length === 0) {
+        errors.set(`buttons[${idx}].steps`, 'Macro needs at least one step');
+      } else if (steps.length > MAX_MACRO_STEPS) {
+        errors.set(`buttons[${idx}].steps`, `At most ${MAX_MACRO_STEPS} steps`);
+      }
+      steps.forEach((step, n) => {
+        const sp = `buttons[${idx}].steps[${n}]`;
+        for (const field of ['cc', 'value', 'program', 'note', 'velocity'] as const) {
+          const value = step[field];
+          if (value !== undefined) {
+            const e = validators.withinRange(value, 0, 127);
+            if (e) errors.set(`${sp}.${field}`, e);
+          }
+        }
+        if (step.channel !== undefined) {
+          const e = validators.channel(step.channel);
+          if (e) errors.set(`${sp}.channel`, e);
+        }
+        if (step.delay_ms !== undefined) {
+          const e = validators.withinRange(step.delay_ms, 0, MAX_STEP_DELAY_MS);
+          if (e) errors.set(`${sp}.delay_ms`, e);
+        }
+        const type = step.type ?? 'cc';
+        if (type === 'cc' && step.cc === undefined) {
+          errors.set(`${sp}.cc`, 'Step needs a CC number');
+        } else if (type === 'pc' && step.program === undefined) {
+          errors.set(`${sp}.program`, 'Step needs a program number');
+        } else if (type === 'note' && step.note === undefined) {
+          errors.set(`${sp}.note`, 'Step needs a note number');
+        }
+      });
     }
 
     if (btn.channel !== undefined) {
@@ -155,6 +243,63 @@ export function validateConfig(config: MidiCaptainConfig): ValidationResult {
       if (fError) errors.set(`buttons[${idx}].flash_ms`, fError);
     }
 
+    if (btn.mode === 'tap_tempo') {
+      const tp = `buttons[${idx}].tap_tempo`;
+      if (msgType !== 'cc') {
+        errors.set(`buttons[${idx}].mode`, 'Tap tempo needs the CC type');
+      }
+      const tap = btn.tap_tempo ?? {};
+      for (const field of ['min_bpm', 'max_bpm'] as const) {
+        const value = tap[field];
+        if (value !== undefined) {
+          const e = validators.withinRange(value, MIN_TAP_BPM, MAX_TAP_BPM);
+          if (e) errors.set(`${tp}.${field}`, e);
+        }
+      }
+      if ((tap.min_bpm ?? DEFAULT_MIN_BPM) >= (tap.max_bpm ?? DEFAULT_MAX_BPM) && !errors.has(`${tp}.max_bpm`)) {
+        errors.set(`${tp}.max_bpm`, 'Max BPM must be above min BPM');
+      }
+    }
+
+    if (btn.led) {
+      for (const field of ['on_brightness', 'off_brightness'] as const) {
+        const value = btn.led[field];
+        if (value !== undefined) {
+          const e = validators.withinRange(value, 0, 100);
+          if (e) errors.set(`buttons[${idx}].led.${field}`, e);
+        }
+      }
+      for (const field of ['on_color', 'off_color'] as const) {
+        const value = btn.led[field];
+        if (value !== undefined) {
+          const e = validators.color(value);
+          if (e) errors.set(`buttons[${idx}].led.${field}`, e);
+        }
+      }
+    }
+
+    for (const [key, name] of [['long_press', 'Long press'], ['double_tap', 'Double tap']] as const) {
+      const action = btn[key];
+      if (!action) continue;
+      const ap = `buttons[${idx}].${key}`;
+      for (const field of ['cc', 'value', 'program'] as const) {
+        const value = action[field];
+        if (value !== undefined) {
+          const e = validators.withinRange(value, 0, 127);
+          if (e) errors.set(`${ap}.${field}`, e);
+        }
+      }
+      if (action.channel !== undefined) {
+        const e = validators.channel(action.channel);
+        if (e) errors.set(`${ap}.channel`, e);
+      }
+      if ((action.type ?? 'cc') === 'cc' && action.cc === undefined) {
+        errors.set(`${ap}.cc`, `${name} needs a CC number`);
+      } else if (action.type === 'pc' && action.program === undefined) {
+        errors.set(`${ap}.program`, `${name} needs a program number`);
+      }
+    }
+
     if (btn.states && (btn.keytimes === undefined || btn.keytimes <= 1)) {
       errors.set(`buttons[${idx}].states`, 'states requires keytimes > 1');
     }
@@ -170,6 +315,10 @@ export function validateConfig(config: MidiCaptainConfig): ValidationResult {
             const e = validators.cc(state.cc);
             if (e) errors.set(`${sp}.cc`, e);
           }
+          if (state.color !== undefined) {
+            const e = validators.color(state.color);
+            if (e) errors.set(`${sp}.color`, e);
+          }
           if (state.cc_on !== undefined) {
             const e = validators.withinRange(state.cc_on, 0, 127);
             if (e) errors.set(`${sp}.cc_on`, e);
@@ -227,6 +376,10 @@ export function validateConfig(config: MidiCaptainConfig): ValidationResult {
       if (initError) errors.set('encoder.initial', `Initial ${initError.toLowerCase()}`);
     }
 
+    if (config.encoder.wrap && !config.encoder.steps && min === max) {
+      errors.set('encoder.wrap', 'Wrap needs max above min');
+    }
+
     if (config.encoder.push?.enabled) {
       const pushCcError = validators.cc(config.encoder.push.cc);
       if (pushCcError) errors.set('encoder.push.cc', pushCcError);
@@ -263,6 +416,26 @@ export function validateConfig(config: MidiCaptainConfig): ValidationResult {
     const max = exp.max ?? 127;
     const rangeError = validators.range(min, max);
     if (rangeError) errors.set(`${p}.range`, rangeError);
+
+    if (exp.calibration && exp.calibration.raw_min >= exp.calibration.raw_max) {
+      errors.set(`${p}.calibration`, 'Calibration raw min must be below raw max');
+    }
+
+    if (Array.isArray(exp.curve)) {
+      const curveError = validators.curve(exp.curve);
+      if (curveError) errors.set(`${p}.curve`, curveError);
+    }
+
+    const destination = exp.destination ?? 'usb';
+    if (destination !== 'din' && config.routing?.usb === false) {
+      errors.set(`${p}.destination`, 'USB output is off in routing');
+    } else if (destination !== 'usb' && !config.routing?.din) {
+      errors.set(`${p}.destination`, 'DIN output is off; turn on DIN MIDI Out');
+    }
+  }
+
+  if (config.routing?.din && !device.din) {
+    errors.set('routing.din', `${device.name} has no DIN MIDI output`);
   }
   
   return {
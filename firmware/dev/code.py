@@ -28,6 +28,9 @@ import usb_midi
 import busio
 import rotaryio
 import json
+import sys
+import supervisor
+import storage
 from analogio import AnalogIn
 from adafruit_display_text import label
 from adafruit_bitmap_font import bitmap_font
@@ -38,11 +41,17 @@ from adafruit_midi.control_change import ControlChange
 from adafruit_midi.program_change import ProgramChange
 from adafruit_midi.note_on import NoteOn
 from adafruit_midi.note_off import NoteOff
+from adafruit_midi.timing_clock import TimingClock
 
 # Import core modules (testable logic)
-from core.colors import COLORS, get_color, dim_color, rgb_to_hex, get_off_color, get_off_color_for_display
-from core.config import load_config as _load_config_from_file, validate_config, get_display_config, get_button_state_config
-from core.button import Switch, ButtonState
+from core.colors import COLORS, get_color, dim_color, rgb_to_hex, get_off_color_for_display, get_led_colors
+from core.config import load_config as _load_config_from_file, validate_config, get_display_config, get_global_config, get_button_state_config, get_routing_config, get_destination, get_calibration
+from core.button import Switch, ButtonState, GestureDetector
+from core.macro import MacroPlayer
+from core.tempo import TapTempo, ClockSender
+from core.encoder import accelerate, step_value
+from core.expression import parse_curve, apply_curve
+from core.live import parse_live_command, validate_live_value, format_live_reply, format_exp_sample, format_enc_sample, MONITOR_INTERVAL_S, PREVIEW_DURATION_S
 
 # =============================================================================
 # Font Size Configuration
@@ -89,6 +98,8 @@ def _read_device_from_config():
             device = json.load(f).get("device")
             if device in ("mini6", "std10"):
                 return device
+            if device:
+                print(f"Device type '{device}' from config is not supported; probing hardware")
     except Exception:
         pass
     return None
@@ -143,9 +154,10 @@ print(f"Hardware detected: {DETECTED_DEVICE}")
 if DETECTED_DEVICE == "mini6":
     from devices.mini6 import (
         LED_PIN, LED_COUNT, SWITCH_PINS, switch_to_led,
-        TFT_DC_PIN, TFT_CS_PIN, TFT_SCK_PIN, TFT_MOSI_PIN,
+        TFT_DC_PIN, TFT_CS_PIN, TFT_SCK_PIN, TFT_MOSI_PIN, TFT_BL_PIN,
         DISPLAY_WIDTH, DISPLAY_HEIGHT, DISPLAY_ROWSTART, DISPLAY_ROTATION,
-        ENCODER_A_PIN, ENCODER_B_PIN, EXP1_PIN, EXP2_PIN, BATTERY_PIN
+        ENCODER_A_PIN, ENCODER_B_PIN, EXP1_PIN, EXP2_PIN, BATTERY_PIN,
+        MIDI_TX_PIN, MIDI_RX_PIN
     )
     BUTTON_COUNT = 6
     HAS_ENCODER = False
@@ -154,9 +166,10 @@ else:
     # Default to STD10
     from devices.std10 import (
         LED_PIN, LED_COUNT, SWITCH_PINS, switch_to_led,
-        TFT_DC_PIN, TFT_CS_PIN, TFT_SCK_PIN, TFT_MOSI_PIN,
+        TFT_DC_PIN, TFT_CS_PIN, TFT_SCK_PIN, TFT_MOSI_PIN, TFT_BL_PIN,
         DISPLAY_WIDTH, DISPLAY_HEIGHT, DISPLAY_ROWSTART, DISPLAY_ROTATION,
-        ENCODER_A_PIN, ENCODER_B_PIN, EXP1_PIN, EXP2_PIN, BATTERY_PIN
+        ENCODER_A_PIN, ENCODER_B_PIN, EXP1_PIN, EXP2_PIN, BATTERY_PIN,
+        MIDI_TX_PIN, MIDI_RX_PIN
     )
     BUTTON_COUNT = 10
     HAS_ENCODER = True
@@ -183,7 +196,7 @@ print(f"Version: {VERSION}")
 # =============================================================================
 # Color Palette - imported from core/colors.py
 # =============================================================================
-# COLORS, get_color, dim_color, rgb_to_hex, get_off_color imported at top
+# COLORS, get_color, dim_color, rgb_to_hex, get_led_colors imported at top
 
 # =============================================================================
 # Configuration
@@ -265,6 +278,12 @@ expression_text_size = display_config["expression_text_size"]
 
 print(f"Display config: button={button_text_size}, status={status_text_size}, expression={expression_text_size}")
 
+# Device-wide settings (brightness, sleep)
+global_settings = get_global_config(config)
+LED_BRIGHTNESS = global_settings["led_brightness"]
+DISPLAY_BRIGHTNESS = global_settings["display_brightness"]  # None: backlight left alone
+SLEEP_S = global_settings["sleep_minutes"] * 60
+
 # Load fonts based on config
 BUTTON_FONT, BUTTON_FONT_HEIGHT = load_font(button_text_size)
 STATUS_FONT, STATUS_FONT_HEIGHT = load_font(status_text_size)
@@ -275,18 +294,23 @@ EXPRESSION_FONT, EXPRESSION_FONT_HEIGHT = load_font(expression_text_size)
 # =============================================================================
 
 # NeoPixels
-pixels = neopixel.NeoPixel(LED_PIN, LED_COUNT, brightness=0.3, auto_write=False)
+pixels = neopixel.NeoPixel(LED_PIN, LED_COUNT, brightness=LED_BRIGHTNESS, auto_write=False)
 
 # Display
 displayio.release_displays()
 spi = busio.SPI(clock=TFT_SCK_PIN, MOSI=TFT_MOSI_PIN)
 display_bus = displayio.FourWire(spi, command=TFT_DC_PIN, chip_select=TFT_CS_PIN)
+# The backlight pin is only driven when the config sets a display brightness
+backlight = {}
+if DISPLAY_BRIGHTNESS is not None:
+    backlight = {"backlight_pin": TFT_BL_PIN, "brightness": DISPLAY_BRIGHTNESS}
 display = ST7789(
     display_bus,
     width=DISPLAY_WIDTH,
     height=DISPLAY_HEIGHT,
     rowstart=DISPLAY_ROWSTART,
     rotation=DISPLAY_ROTATION,
+    **backlight,
 )
 
 # =============================================================================
@@ -315,7 +339,8 @@ if HAS_EXPRESSION:
     exp1 = AnalogIn(EXP1_PIN)
     exp2 = AnalogIn(EXP2_PIN)
     battery = AnalogIn(BATTERY_PIN)
-    # Expression pedal calibration (auto-calibrates during use)
+    # Expression pedal range (auto-calibrates during use unless the config
+    # has a calibration, applied once the config is loaded)
     exp1_min, exp1_max = 2048, 63488
     exp2_min, exp2_max = 2048, 63488
     exp1_last, exp2_last = 0, 0
@@ -341,6 +366,23 @@ midi = adafruit_midi.MIDI(
 )
 print("MIDI initialized")
 
+# Serial (DIN) MIDI out, opened only when the routing block asks for it
+routing = get_routing_config(config)
+din_midi = None
+if routing["din"] and MIDI_TX_PIN is not None:
+    din_uart = busio.UART(MIDI_TX_PIN, MIDI_RX_PIN, baudrate=31250, timeout=0.003)
+    din_midi = adafruit_midi.MIDI(midi_out=din_uart, out_channel=0)
+    print("DIN MIDI initialized")
+
+
+def send_routed(msg, channel, destination):
+    """Send msg to the outputs destination names that routing has open."""
+    if destination in ("usb", "both") and routing["usb"]:
+        midi.send(msg, channel=channel)
+    if destination in ("din", "both") and din_midi is not None:
+        din_midi.send(msg, channel=channel)
+
+
 # Encoder config (from config.json or defaults)
 enc_config = config.get("encoder", {"enabled": True, "cc": 11, "label": "ENC", "min": 0, "max": 127, "initial": 64})
 enc_push_config = enc_config.get("push", {"enabled": True, "cc": 14, "label": "PUSH", "mode": "momentary"})
@@ -363,6 +405,10 @@ ENC_PUSH_CC_OFF = enc_push_config.get("cc_off", 0)
 # Internal encoder tracks 0-127, output only changes at slot boundaries
 ENC_STEPS = enc_config.get("steps", None)
 
+# Acceleration ("off"/"low"/"high") and wrap past max/min; see core/encoder.py
+ENC_ACCELERATION = enc_config.get("acceleration", "off")
+ENC_WRAP = bool(enc_config.get("wrap", False))
+
 # Expression pedal config (from config.json or defaults)
 exp_config = config.get("expression", {})
 exp1_config = exp_config.get("exp1", {"enabled": True, "cc": 12, "label": "EXP1", "min": 0, "max": 127, "polarity": "normal", "threshold": 2})
@@ -371,7 +417,19 @@ exp2_config = exp_config.get("exp2", {"enabled": True, "cc": 13, "label": "EXP2"
 CC_EXP1 = exp1_config.get("cc", 12)
 CC_EXP2 = exp2_config.get("cc", 13)
 EXP1_CHANNEL = exp1_config.get("channel", 0)
+EXP1_DESTINATION = get_destination(exp1_config)
+EXP2_DESTINATION = get_destination(exp2_config)
 EXP2_CHANNEL = exp2_config.get("channel", 0)
+# Measured raw range from the editor's calibration; None = learn it while playing
+EXP1_CALIBRATION = get_calibration(exp1_config)
+EXP2_CALIBRATION = get_calibration(exp2_config)
+if EXP1_CALIBRATION:
+    exp1_min, exp1_max = EXP1_CALIBRATION
+if EXP2_CALIBRATION:
+    exp2_min, exp2_max = EXP2_CALIBRATION
+# Response curves (see core/expression.py)
+EXP1_CURVE = parse_curve(exp1_config.get("curve", "linear"))
+EXP2_CURVE = parse_curve(exp2_config.get("curve", "linear"))
 
 # =============================================================================
 # State
@@ -386,12 +444,37 @@ for i in range(BUTTON_COUNT):
     keytimes = btn_config.get("keytimes", 1)
     button_states.append(ButtonState(cc=cc, mode=mode, keytimes=keytimes))
 
+# Long-press / double-tap detection, only for buttons that define one
+gesture_detectors = []
+for i in range(BUTTON_COUNT):
+    btn_config = buttons[i] if i < len(buttons) else {}
+    long_press = "long_press" in btn_config
+    double_tap = "double_tap" in btn_config
+    gesture_detectors.append(GestureDetector(long_press, double_tap) if long_press or double_tap else None)
+
+# Macro playback, only for macro buttons
+macro_players = []
+for i in range(BUTTON_COUNT):
+    btn_config = buttons[i] if i < len(buttons) else {}
+    macro_players.append(MacroPlayer() if btn_config.get("type") == "macro" else None)
+
+# Tap tempo, only for tap-tempo buttons; MIDI clock is shared by all of them
+tap_tempos = []
+for i in range(BUTTON_COUNT):
+    btn_config = buttons[i] if i < len(buttons) else {}
+    tap = btn_config.get("tap_tempo") if btn_config.get("mode") == "tap_tempo" else None
+    tap_tempos.append(TapTempo(tap["min_bpm"], tap["max_bpm"]) if tap else None)
+midi_clock = ClockSender()
+
 pc_values = [0] * 16                 # Current PC value per MIDI channel (0-15), shared across all pc_inc/pc_dec buttons
 pc_flash_timers = [0.0] * BUTTON_COUNT  # Expiry time (monotonic) for PC button flash; 0 = inactive
 PC_FLASH_DURATION_MS = 200              # Default PC button flash duration in ms
+CC_ALL_SOUND_OFF = 120                  # Channel Mode messages sent by panic buttons
+CC_ALL_NOTES_OFF = 123
 
 encoder_value = ENC_INITIAL  # Internal value 0-127
 encoder_slot = -1  # Current slot (set on first change)
+encoder_last_move = 0.0  # monotonic time of the previous move, for acceleration
 
 # =============================================================================
 # Display Setup
@@ -543,7 +626,8 @@ def set_button_state(switch_idx, on):
     # Update LED
     led_idx = switch_to_led(switch_idx)
     if led_idx is not None:
-        rgb = color_rgb if on else get_off_color(color_rgb, off_mode)
+        on_rgb, off_rgb = get_led_colors(color_rgb, btn_config)
+        rgb = on_rgb if on else off_rgb
         base = led_idx * 3
         for j in range(3):
             if base + j < LED_COUNT:
@@ -581,6 +665,83 @@ def flash_pc_button(button_idx, flash_ms=PC_FLASH_DURATION_MS):
     pc_flash_timers[button_idx - 1] = time.monotonic() + flash_ms / 1000.0
 
 
+def send_panic(reset_ccs):
+    """Silence everything: All Notes Off, All Sound Off and each reset CC
+    (value 0) on all 16 channels. Note buttons that were on are switched off."""
+    for ch in range(16):
+        midi.send(ControlChange(CC_ALL_NOTES_OFF, 0), channel=ch)
+        midi.send(ControlChange(CC_ALL_SOUND_OFF, 0), channel=ch)
+        for cc in reset_ccs:
+            midi.send(ControlChange(cc, 0), channel=ch)
+    for i in range(BUTTON_COUNT):
+        btn_config = buttons[i] if i < len(buttons) else {}
+        if btn_config.get("type") == "note" and button_states[i].state:
+            button_states[i].state = False
+            set_button_state(i + 1, False)
+
+
+last_activity = time.monotonic()
+asleep = False
+sleep_group = displayio.Group()  # Shown while asleep: a blank screen
+
+
+def note_activity():
+    """Restart the sleep timer, waking LEDs and display if asleep."""
+    global last_activity, asleep
+    last_activity = time.monotonic()
+    if asleep:
+        asleep = False
+        pixels.brightness = LED_BRIGHTNESS
+        pixels.show()
+        display.show(main_group)
+
+
+def update_sleep():
+    """Darken LEDs and display after global.sleep_minutes without input. Call each main loop."""
+    global asleep
+    if SLEEP_S and not asleep and time.monotonic() - last_activity >= SLEEP_S:
+        asleep = True
+        pixels.brightness = 0
+        pixels.show()
+        display.show(sleep_group)
+
+
+# The editor touches /.reboot when the user asks for a restart and it can't
+# reach the serial console; a change in its content reloads code.py so a new
+# config.json takes effect. Only watched while the USB drive is exposed
+# (boot.py remounts / read-only for the host to write), so the hidden drive
+# of performance mode can never reload the firmware.
+REBOOT_SENTINEL = "/.reboot"
+REBOOT_CHECK_S = 2.0
+WATCH_REBOOT_SENTINEL = storage.getmount("/").readonly
+
+
+def read_reboot_sentinel():
+    try:
+        with open(REBOOT_SENTINEL, "r") as f:
+            return f.read()
+    except OSError:
+        return None
+
+
+reboot_stamp = read_reboot_sentinel()
+reboot_checked = 0.0
+
+
+def check_reboot_sentinel():
+    """Reload when the editor has touched the reboot sentinel. Call each main loop."""
+    global reboot_checked
+    if not WATCH_REBOOT_SENTINEL:
+        return
+    now = time.monotonic()
+    if now - reboot_checked < REBOOT_CHECK_S:
+        return
+    reboot_checked = now
+    if read_reboot_sentinel() != reboot_stamp:
+        print("[REBOOT] Requested by the editor")
+        supervisor.reload()
+
+
 def update_pc_flash_timers():
     """Turn off LEDs whose flash period has expired. Call each main loop."""
     now = time.monotonic()
@@ -590,6 +751,53 @@ def update_pc_flash_timers():
             set_button_state(i + 1, False)
 
 
+def send_macro_step(step, btn_num):
+    """Send one macro step."""
+    channel = step["channel"]
+    if step["type"] == "pc":
+        midi.send(ProgramChange(step["program"]), channel=channel)
+        print(f"[MIDI TX] Ch{channel+1} PC{step['program']} (switch {btn_num}, macro)")
+        status_label.text = f"TX PC{step['program']}"
+    elif step["type"] == "note":
+        midi.send(NoteOn(step["note"], step["velocity"]), channel=channel)
+        print(f"[MIDI TX] Ch{channel+1} NoteOn{step['note']} vel{step['velocity']} (switch {btn_num}, macro)")
+        status_label.text = f"TX Note{step['note']}"
+    else:
+        midi.send(ControlChange(step["cc"], step["value"]), channel=channel)
+        print(f"[MIDI TX] Ch{channel+1} CC{step['cc']}={step['value']} (switch {btn_num}, macro)")
+        status_label.text = f"TX CC{step['cc']}={step['value']}"
+
+
+def tap_tempo(idx, btn_num, btn_config, cc, val, channel):
+    """Handle a tap on a tap-tempo button: send its CC or retime MIDI clock."""
+    now_ms = time.monotonic() * 1000
+    bpm = tap_tempos[idx].tap(now_ms)
+    if btn_config["tap_tempo"]["output"] == "clock":
+        if bpm is not None:
+            midi_clock.set_bpm(bpm, now_ms)
+    else:
+        midi.send(ControlChange(cc, val), channel=channel)
+        print(f"[MIDI TX] Ch{channel+1} CC{cc}={val} (switch {btn_num}, tap)")
+    status_label.text = f"TAP {bpm} BPM" if bpm is not None else "TAP"
+    flash_pc_button(btn_num, btn_config.get("flash_ms", PC_FLASH_DURATION_MS))
+
+
+def update_midi_clock():
+    """Send a MIDI clock pulse when one is due. Call each main loop."""
+    if midi_clock.tick(time.monotonic() * 1000):
+        midi.send(TimingClock())
+
+
+def update_macros():
+    """Send macro steps whose delay has passed. Call each main loop."""
+    now_ms = time.monotonic() * 1000
+    for i in range(BUTTON_COUNT):
+        player = macro_players[i]
+        if player is not None and player.running:
+            for step in player.due(now_ms):
+                send_macro_step(step, i + 1)
+
+
 # =============================================================================
 # Polling Functions
 # =============================================================================
@@ -641,6 +849,20 @@ def handle_midi():
         status_label.text = f"RX PC{program}"
 
 
+def send_secondary(action, btn_num):
+    """Send a button's long-press or double-tap action and flash its LED."""
+    channel = action["channel"]
+    if action["type"] == "pc":
+        midi.send(ProgramChange(action["program"]), channel=channel)
+        print(f"[MIDI TX] Ch{channel+1} PC{action['program']} (switch {btn_num}, secondary)")
+        status_label.text = f"TX PC{action['program']}"
+    else:
+        midi.send(ControlChange(action["cc"], action["value"]), channel=channel)
+        print(f"[MIDI TX] Ch{channel+1} CC{action['cc']}={action['value']} (switch {btn_num}, secondary)")
+        status_label.text = f"TX CC{action['cc']}={action['value']}"
+    flash_pc_button(btn_num)
+
+
 def handle_switches():
     """Handle footswitch presses with keytimes support."""
     # STD10: index 0 is encoder push, 1-10 are footswitches
@@ -649,100 +871,131 @@ def handle_switches():
     for i in range(start_idx, len(switches)):
         sw = switches[i]
         changed, pressed = sw.changed()
-
         if changed:
-            # Convert to 1-indexed button number
-            btn_num = i if HAS_ENCODER else i + 1
-            idx = btn_num - 1
-            btn_state = button_states[idx]
-            btn_config = buttons[idx] if idx < len(buttons) else {"cc": 20 + idx}
-
-            message_type = btn_config.get("type", "cc")
-            mode = btn_config.get("mode", "toggle")
-            channel = btn_config.get("channel", 0)
-
-            if message_type == "cc":
-                if pressed:
-                    btn_state.advance_keytime()
-                state_cfg = get_button_state_config(btn_config, btn_state.get_keytime())
-                cc = state_cfg.get("cc", 20 + idx)
-                cc_on = state_cfg.get("cc_on", 127)
-                cc_off = state_cfg.get("cc_off", 0)
-                if mode == "momentary":
-                    val = cc_on if pressed else cc_off
-                    set_button_state(btn_num, pressed)
-                    midi.send(ControlChange(cc, val), channel=channel)
-                    print(f"[MIDI TX] Ch{channel+1} CC{cc}={val} (switch {btn_num}, momentary)")
-                    status_label.text = f"TX CC{cc}={val}"
-                elif pressed:
-                    # Keytimes cycling always stays on; standard toggle flips on/off
-                    new_state = True if btn_state.keytimes > 1 else not btn_state.state
-                    btn_state.state = new_state
-                    set_button_state(btn_num, new_state)
-                    val = cc_on if new_state else cc_off
-                    midi.send(ControlChange(cc, val), channel=channel)
-                    print(f"[MIDI TX] Ch{channel+1} CC{cc}={val} (switch {btn_num}, toggle)")
-                    status_label.text = f"TX CC{cc}={'ON' if new_state else 'OFF'}"
-
-            elif message_type == "note":
-                if pressed:
-                    btn_state.advance_keytime()
-                state_cfg = get_button_state_config(btn_config, btn_state.get_keytime())
-                note = state_cfg.get("note", 60)
-                vel_on = state_cfg.get("velocity_on", 127)
-                vel_off = state_cfg.get("velocity_off", 0)
-                if mode == "momentary":
-                    if pressed:
-                        midi.send(NoteOn(note, vel_on), channel=channel)
-                        set_button_state(btn_num, True)
-                        print(f"[MIDI TX] Ch{channel+1} NoteOn{note} vel{vel_on} (switch {btn_num})")
-                        status_label.text = f"TX Note{note}"
-                    else:
-                        midi.send(NoteOff(note, vel_off), channel=channel)
-                        set_button_state(btn_num, False)
-                        print(f"[MIDI TX] Ch{channel+1} NoteOff{note} (switch {btn_num})")
-                elif pressed:
-                    # Keytimes cycling always stays on; standard toggle flips on/off
-                    new_state = True if btn_state.keytimes > 1 else not btn_state.state
-                    btn_state.state = new_state
-                    set_button_state(btn_num, new_state)
-                    if new_state:
-                        midi.send(NoteOn(note, vel_on), channel=channel)
-                        print(f"[MIDI TX] Ch{channel+1} NoteOn{note} vel{vel_on} (switch {btn_num}, toggle on)")
-                        status_label.text = f"TX Note{note} ON"
-                    else:
-                        midi.send(NoteOff(note, vel_off), channel=channel)
-                        print(f"[MIDI TX] Ch{channel+1} NoteOff{note} (switch {btn_num}, toggle off)")
-                        status_label.text = f"TX Note{note} OFF"
-
-            elif message_type == "pc" and pressed:
-                btn_state.advance_keytime()
-                state_cfg = get_button_state_config(btn_config, btn_state.get_keytime())
-                program = state_cfg.get("program", 0)
-                midi.send(ProgramChange(program), channel=channel)
-                print(f"[MIDI TX] Ch{channel+1} PC{program} (switch {btn_num})")
-                status_label.text = f"TX PC{program}"
-                flash_pc_button(btn_num, btn_config.get("flash_ms", PC_FLASH_DURATION_MS))
-
-            elif message_type == "pc_inc" and pressed:
-                btn_state.advance_keytime()
-                state_cfg = get_button_state_config(btn_config, btn_state.get_keytime())
-                step = state_cfg.get("pc_step", 1)
-                pc_values[channel] = clamp_pc_value(pc_values[channel] + step)
-                midi.send(ProgramChange(pc_values[channel]), channel=channel)
-                print(f"[MIDI TX] Ch{channel+1} PC{pc_values[channel]} (switch {btn_num}, inc)")
-                status_label.text = f"TX PC{pc_values[channel]}"
-                flash_pc_button(btn_num, btn_config.get("flash_ms", PC_FLASH_DURATION_MS))
-
-            elif message_type == "pc_dec" and pressed:
-                btn_state.advance_keytime()
-                state_cfg = get_button_state_config(btn_config, btn_state.get_keytime())
-                step = state_cfg.get("pc_step", 1)
-                pc_values[channel] = clamp_pc_value(pc_values[channel] - step)
-                midi.send(ProgramChange(pc_values[channel]), channel=channel)
-                print(f"[MIDI TX] Ch{channel+1} PC{pc_values[channel]} (switch {btn_num}, dec)")
-                status_label.text = f"TX PC{pc_values[channel]}"
-                flash_pc_button(btn_num, btn_config.get("flash_ms", PC_FLASH_DURATION_MS))
+            note_activity()
+        # Convert to 1-indexed button number
+        btn_num = i if HAS_ENCODER else i + 1
+        idx = btn_num - 1
+
+        detector = gesture_detectors[idx]
+        if detector is not None:
+            gesture = detector.update(pressed, time.monotonic() * 1000)
+            if gesture == "tap":
+                # The button's own message, as a complete press and release
+                dispatch_button(idx, btn_num, True)
+                dispatch_button(idx, btn_num, False)
+            elif gesture is not None:
+                send_secondary(buttons[idx][gesture], btn_num)
+        elif changed:
+            dispatch_button(idx, btn_num, pressed)
+
+
+def dispatch_button(idx, btn_num, pressed):
+    """Send a footswitch's own message for a press or release."""
+    btn_state = button_states[idx]
+    btn_config = buttons[idx] if idx < len(buttons) else {"cc": 20 + idx}
+
+    message_type = btn_config.get("type", "cc")
+    mode = btn_config.get("mode", "toggle")
+    channel = btn_config.get("channel", 0)
+
+    if message_type == "cc":
+        if pressed:
+            btn_state.advance_keytime()
+        state_cfg = get_button_state_config(btn_config, btn_state.get_keytime())
+        cc = state_cfg.get("cc", 20 + idx)
+        cc_on = state_cfg.get("cc_on", 127)
+        cc_off = state_cfg.get("cc_off", 0)
+        if mode == "tap_tempo":
+            if pressed:
+                tap_tempo(idx, btn_num, btn_config, cc, cc_on, channel)
+        elif mode == "momentary":
+            val = cc_on if pressed else cc_off
+            set_button_state(btn_num, pressed)
+            midi.send(ControlChange(cc, val), channel=channel)
+            print(f"[MIDI TX] Ch{channel+1} CC{cc}={val} (switch {btn_num}, momentary)")
+            status_label.text = f"TX CC{cc}={val}"
+        elif pressed:
+            # Keytimes cycling always stays on; standard toggle flips on/off
+            new_state = True if btn_state.keytimes > 1 else not btn_state.state
+            btn_state.state = new_state
+            set_button_state(btn_num, new_state)
+            val = cc_on if new_state else cc_off
+            midi.send(ControlChange(cc, val), channel=channel)
+            print(f"[MIDI TX] Ch{channel+1} CC{cc}={val} (switch {btn_num}, toggle)")
+            status_label.text = f"TX CC{cc}={'ON' if new_state else 'OFF'}"
+
+    elif message_type == "note":
+        if pressed:
+            btn_state.advance_keytime()
+        state_cfg = get_button_state_config(btn_config, btn_state.get_keytime())
+        note = state_cfg.get("note", 60)
+        vel_on = state_cfg.get("velocity_on", 127)
+        vel_off = state_cfg.get("velocity_off", 0)
+        if mode == "momentary":
+            if pressed:
+                midi.send(NoteOn(note, vel_on), channel=channel)
+                set_button_state(btn_num, True)
+                print(f"[MIDI TX] Ch{channel+1} NoteOn{note} vel{vel_on} (switch {btn_num})")
+                status_label.text = f"TX Note{note}"
+            else:
+                midi.send(NoteOff(note, vel_off), channel=channel)
+                set_button_state(btn_num, False)
+                print(f"[MIDI TX] Ch{channel+1} NoteOff{note} (switch {btn_num})")
+        elif pressed:
+            # Keytimes cycling always stays on; standard toggle flips on/off
+            new_state = True if btn_state.keytimes > 1 else not btn_state.state
+            btn_state.state = new_state
+            set_button_state(btn_num, new_state)
+            if new_state:
+                midi.send(NoteOn(note, vel_on), channel=channel)
+                print(f"[MIDI TX] Ch{channel+1} NoteOn{note} vel{vel_on} (switch {btn_num}, toggle on)")
+                status_label.text = f"TX Note{note} ON"
+            else:
+                midi.send(NoteOff(note, vel_off), channel=channel)
+                print(f"[MIDI TX] Ch{channel+1} NoteOff{note} (switch {btn_num}, toggle off)")
+                status_label.text = f"TX Note{note} OFF"
+
+    elif message_type == "pc" and pressed:
+        btn_state.advance_keytime()
+        state_cfg = get_button_state_config(btn_config, btn_state.get_keytime())
+        program = state_cfg.get("program", 0)
+        midi.send(ProgramChange(program), channel=channel)
+        print(f"[MIDI TX] Ch{channel+1} PC{program} (switch {btn_num})")
+        status_label.text = f"TX PC{program}"
+        flash_pc_button(btn_num, btn_config.get("flash_ms", PC_FLASH_DURATION_MS))
+
+    elif message_type == "pc_inc" and pressed:
+        btn_state.advance_keytime()
+        state_cfg = get_button_state_config(btn_config, btn_state.get_keytime())
+        step = state_cfg.get("pc_step", 1)
+        pc_values[channel] = clamp_pc_value(pc_values[channel] + step)
+        midi.send(ProgramChange(pc_values[channel]), channel=channel)
+        print(f"[MIDI TX] Ch{channel+1} PC{pc_values[channel]} (switch {btn_num}, inc)")
+        status_label.text = f"TX PC{pc_values[channel]}"
+        flash_pc_button(btn_num, btn_config.get("flash_ms", PC_FLASH_DURATION_MS))
+
+    elif message_type == "pc_dec" and pressed:
+        btn_state.advance_keytime()
+        state_cfg = get_button_state_config(btn_config, btn_state.get_keytime())
+        step = state_cfg.get("pc_step", 1)
+        pc_values[channel] = clamp_pc_value(pc_values[channel] - step)
+        midi.send(ProgramChange(pc_values[channel]), channel=channel)
+        print(f"[MIDI TX] Ch{channel+1} PC{pc_values[channel]} (switch {btn_num}, dec)")
+        status_label.text = f"TX PC{pc_values[channel]}"
+        flash_pc_button(btn_num, btn_config.get("flash_ms", PC_FLASH_DURATION_MS))
+
+    elif message_type == "panic" and pressed:
+        send_panic(btn_config.get("reset_ccs", []))
+        print(f"[MIDI TX] Panic on all channels (switch {btn_num})")
+        status_label.text = "TX PANIC"
+        flash_pc_button(btn_num, btn_config.get("flash_ms", PC_FLASH_DURATION_MS))
+
+    elif message_type == "macro" and pressed:
+        # Steps without a delay go out now; the rest from update_macros()
+        macro_players[idx].start(btn_config.get("steps", []), time.monotonic() * 1000)
+        update_macros()
+        flash_pc_button(btn_num, btn_config.get("flash_ms", PC_FLASH_DURATION_MS))
 
 
 def handle_encoder_button():
@@ -755,6 +1008,7 @@ def handle_encoder_button():
     sw = switches[0]  # Encoder push is switch index 0
     changed, pressed = sw.changed()
     if changed:
+        note_activity()
         if ENC_PUSH_MODE == "toggle":
             # Toggle mode: flip state on press only
             if pressed:
@@ -773,7 +1027,7 @@ def handle_encoder_button():
 
 def handle_encoder():
     """Handle rotary encoder."""
-    global encoder_last_pos, encoder_value, encoder_slot
+    global encoder_last_pos, encoder_value, encoder_slot, encoder_last_move
     
     if not ENC_ENABLED:
         return
@@ -782,11 +1036,18 @@ def handle_encoder():
     if pos != encoder_last_pos:
         delta = pos - encoder_last_pos
         encoder_last_pos = pos
+        note_activity()
         
-        # Update internal value (always 0-127)
-        encoder_value = max(0, min(127, encoder_value + delta))
+        now = time.monotonic()
+        delta = accelerate(delta, now - encoder_last_move, ENC_ACCELERATION)
+        encoder_last_move = now
+
+        # Stepped mode tracks 0-127 internally; normal mode moves within min-max
+        stepped = ENC_STEPS and ENC_STEPS > 1
+        lo, hi = (0, 127) if stepped else (ENC_MIN, ENC_MAX)
+        encoder_value = step_value(encoder_value, delta, lo, hi, ENC_WRAP)
         
-        if ENC_STEPS and ENC_STEPS > 1:
+        if stepped:
             # Stepped mode: calculate which slot we're in
             # Slot boundaries: 0-25=slot0, 26-50=slot1, etc. for 5 slots
             slot_size = 128 // ENC_STEPS
@@ -816,24 +1077,31 @@ def handle_expression():
     # Expression 1
     if exp1_config.get("enabled", True) and exp1 is not None:
         raw1 = exp1.value
-        exp1_max = max(raw1, exp1_max)
-        exp1_min = min(raw1, exp1_min)
+        if EXP1_CALIBRATION:
+            # Fixed range: readings past the calibrated ends are clamped
+            pos1 = max(exp1_min, min(exp1_max, raw1))
+        else:
+            pos1 = raw1
+            exp1_max = max(raw1, exp1_max)
+            exp1_min = min(raw1, exp1_min)
         
         if exp1_max > exp1_min:
             # Map to 0-127, then apply config range
-            normalized = (raw1 - exp1_min) / (exp1_max - exp1_min)
+            normalized = (pos1 - exp1_min) / (exp1_max - exp1_min)
             if exp1_config.get("polarity", "normal") == "reverse":
                 normalized = 1.0 - normalized
+            normalized = apply_curve(normalized, EXP1_CURVE)
             out_min = exp1_config.get("min", 0)
             out_max = exp1_config.get("max", 127)
             val1 = int(out_min + normalized * (out_max - out_min))
             val1 = max(0, min(127, val1))  # Clamp to valid MIDI range
+            exp_readings[0] = (raw1, val1, exp1_last)
             
             # Hysteresis: only send if change exceeds threshold
             threshold = exp1_config.get("threshold", 2)
             if abs(val1 - exp1_last) >= threshold:
                 exp1_last = val1
-                midi.send(ControlChange(CC_EXP1, val1), channel=EXP1_CHANNEL)
+                send_routed(ControlChange(CC_EXP1, val1), EXP1_CHANNEL, EXP1_DESTINATION)
                 lbl = exp1_config.get("label", "EXP1")
                 print(f"[{lbl}] Ch{EXP1_CHANNEL+1} CC{CC_EXP1}={val1}")
                 # Update display
@@ -843,24 +1111,31 @@ def handle_expression():
     # Expression 2
     if exp2_config.get("enabled", True) and exp2 is not None:
         raw2 = exp2.value
-        exp2_max = max(raw2, exp2_max)
-        exp2_min = min(raw2, exp2_min)
+        if EXP2_CALIBRATION:
+            # Fixed range: readings past the calibrated ends are clamped
+            pos2 = max(exp2_min, min(exp2_max, raw2))
+        else:
+            pos2 = raw2
+            exp2_max = max(raw2, exp2_max)
+            exp2_min = min(raw2, exp2_min)
         
         if exp2_max > exp2_min:
             # Map to 0-127, then apply config range
-            normalized = (raw2 - exp2_min) / (exp2_max - exp2_min)
+            normalized = (pos2 - exp2_min) / (exp2_max - exp2_min)
             if exp2_config.get("polarity", "normal") == "reverse":
                 normalized = 1.0 - normalized
+            normalized = apply_curve(normalized, EXP2_CURVE)
             out_min = exp2_config.get("min", 0)
             out_max = exp2_config.get("max", 127)
             val2 = int(out_min + normalized * (out_max - out_min))
             val2 = max(0, min(127, val2))  # Clamp to valid MIDI range
+            exp_readings[1] = (raw2, val2, exp2_last)
             
             # Hysteresis: only send if change exceeds threshold
             threshold = exp2_config.get("threshold", 2)
             if abs(val2 - exp2_last) >= threshold:
                 exp2_last = val2
-                midi.send(ControlChange(CC_EXP2, val2), channel=EXP2_CHANNEL)
+                send_routed(ControlChange(CC_EXP2, val2), EXP2_CHANNEL, EXP2_DESTINATION)
                 lbl = exp2_config.get("label", "EXP2")
                 print(f"[{lbl}] Ch{EXP2_CHANNEL+1} CC{CC_EXP2}={val2}")
                 # Update display
@@ -868,6 +1143,88 @@ def handle_expression():
                     exp2_label.text = f"{lbl}: {val2:3d}"
 
 
+live_buffer = ""
+exp_monitor = False
+enc_monitor = False
+monitor_next = 0.0
+label_previews = {}  # button index -> monotonic time the preview ends
+exp_readings = [(0, 0, 0), (0, 0, 0)]  # (raw, mapped, sent) per pedal, for the monitor
+
+
+def apply_live_parameter(path, value):
+    """Apply a live parameter in memory (config.json is not touched)."""
+    global exp_monitor, enc_monitor
+    if path == "monitor.exp":
+        exp_monitor = value
+        return
+    if path == "monitor.enc":
+        enc_monitor = value
+        return
+    if path.startswith("preview."):
+        idx = int(path.split(".")[2]) - 1
+        if idx < len(button_labels):
+            button_labels[idx].text = value
+            label_previews[idx] = time.monotonic() + PREVIEW_DURATION_S
+        return
+    if path == "led.brightness":
+        pixels.brightness = value
+        pixels.show()
+        return
+    _, num, field = path.split(".")
+    idx = int(num) - 1
+    while len(buttons) <= idx:
+        buttons.append({})
+    buttons[idx][field] = value
+    if field == "label" and idx < len(button_labels):
+        button_labels[idx].text = value
+    set_button_state(idx + 1, button_states[idx].state)
+
+
+def handle_live():
+    """Read live-parameter commands from the serial console (see core/live.py)."""
+    global live_buffer
+    n = supervisor.runtime.serial_bytes_available
+    if not n:
+        return
+    live_buffer += sys.stdin.read(n)
+    while "\n" in live_buffer:
+        line, live_buffer = live_buffer.split("\n", 1)
+        cmd = parse_live_command(line)
+        if cmd is None:
+            continue
+        path, value = cmd
+        error = validate_live_value(path, value, BUTTON_COUNT)
+        if error is None:
+            apply_live_parameter(path, value)
+        print(format_live_reply(path, error))
+
+
+def update_label_previews():
+    """Restore configured labels once their preview has expired."""
+    if not label_previews:
+        return
+    now = time.monotonic()
+    for idx in [i for i, end in label_previews.items() if now >= end]:
+        del label_previews[idx]
+        btn_config = buttons[idx] if idx < len(buttons) else {}
+        button_labels[idx].text = btn_config.get("label", str(idx + 1))[:6]
+
+
+def handle_monitor():
+    """Print pedal/encoder readings while an editor monitor is on."""
+    global monitor_next
+    if not (exp_monitor or enc_monitor):
+        return
+    now = time.monotonic()
+    if now < monitor_next:
+        return
+    monitor_next = now + MONITOR_INTERVAL_S
+    if exp_monitor:
+        print(format_exp_sample(exp_readings))
+    if enc_monitor and HAS_ENCODER:
+        print(format_enc_sample(encoder.position, encoder_value, encoder_slot, ENC_STEPS or 0))
+
+
 # =============================================================================
 # Startup
 # =============================================================================
@@ -904,9 +1261,16 @@ print("\nRunning...")
 while True:
     handle_midi()
     handle_switches()
+    update_macros()
+    update_midi_clock()
     update_pc_flash_timers()
+    update_sleep()
+    check_reboot_sentinel()
     if HAS_ENCODER:
         handle_encoder_button()
         handle_encoder()
     if HAS_EXPRESSION:
         handle_expression()
+    handle_live()
+    handle_monitor()
+    update_label_previews()
@@ -1,9 +1,13 @@
 """
 Button and switch handling for MIDI Captain firmware.
 
-Provides Switch class for input handling and ButtonState for state tracking.
+Provides Switch class for input handling, ButtonState for state tracking
+and GestureDetector for long-press / double-tap detection.
 """
 
+LONG_PRESS_MS = 500  # Hold at least this long for a long press
+DOUBLE_TAP_MS = 300  # Second press within this long of the first release for a double tap
+
 
 class Switch:
     """Footswitch with state tracking and edge detection.
@@ -152,3 +156,58 @@ class ButtonState:
         """Reset keytime cycle back to position 1."""
         self.current_keytime = 1
         self._state = False
+
+
+class GestureDetector:
+    """Tells a tap, a long press and a double tap apart on one switch.
+
+    Only used for buttons with a long_press or double_tap action. The button's
+    own message goes out as a "tap" — on release, or once the double-tap window
+    has passed — so a held or double-pressed switch never also sends it.
+    """
+
+    def __init__(self, long_press=False, double_tap=False, long_ms=LONG_PRESS_MS, double_ms=DOUBLE_TAP_MS):
+        self.long_press = long_press
+        self.double_tap = double_tap
+        self.long_ms = long_ms
+        self.double_ms = double_ms
+        self._pressed_at = None   # When the switch went down, while it is held
+        self._released_at = None  # Release of a tap still waiting for a second press
+        self._consumed = False    # The current press already produced a gesture
+
+    def update(self, pressed, now_ms):
+        """Feed the current switch state; call every loop.
+
+        Returns:
+            "tap", "long_press", "double_tap" or None
+        """
+        if pressed:
+            if self._pressed_at is None:
+                if self._released_at is not None:
+                    if now_ms - self._released_at > self.double_ms:
+                        # Window already over: report the pending tap, take the press next call
+                        self._released_at = None
+                        return "tap"
+                    self._released_at = None
+                    self._pressed_at = now_ms
+                    self._consumed = True
+                    return "double_tap"
+                self._pressed_at = now_ms
+                self._consumed = False
+            elif self.long_press and not self._consumed and now_ms - self._pressed_at >= self.long_ms:
+                self._consumed = True
+                return "long_press"
+            return None
+
+        if self._pressed_at is not None:
+            self._pressed_at = None
+            if self._consumed:
+                return None
+            if self.double_tap:
+                self._released_at = now_ms
+                return None
+            return "tap"
+        if self._released_at is not None and now_ms - self._released_at > self.double_ms:
+            self._released_at = None
+            return "tap"
+        return None
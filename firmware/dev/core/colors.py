@@ -20,14 +20,20 @@ COLORS = {
 
 
 def get_color(name):
-    """Get RGB tuple from color name, with fallback to white.
+    """Get RGB tuple from color name or "#RRGGBB", with fallback to white.
     
     Args:
-        name: Color name (case-insensitive)
+        name: Color name (case-insensitive) or hex color like "#ff8800"
         
     Returns:
         RGB tuple (r, g, b) with values 0-255
     """
+    if len(name) == 7 and name[0] == "#":
+        try:
+            value = int(name[1:], 16)
+        except ValueError:
+            return COLORS["white"]
+        return ((value >> 16) & 0xFF, (value >> 8) & 0xFF, value & 0xFF)
     return COLORS.get(name.lower(), COLORS["white"])
 
 
@@ -85,3 +91,38 @@ def get_off_color_for_display(color_rgb, off_mode="dim"):
     """
     # Always return dim color to keep labels visible on display
     return dim_color(color_rgb)
+
+
+def scale_color(rgb, percent):
+    """Scale an RGB color to a brightness percentage (0-100).
+
+    Args:
+        rgb: RGB tuple (r, g, b)
+        percent: Brightness, 100 for the color as is
+
+    Returns:
+        Scaled RGB tuple
+    """
+    return tuple(c * percent // 100 for c in rgb)
+
+
+def get_led_colors(color_rgb, btn_config):
+    """Get a button's LED colors for the on and off states.
+
+    A button's "led" settings (see validate_button in core/config.py) take
+    over from off_mode. Without them the LED shows the color when on and
+    get_off_color() when off.
+
+    Args:
+        color_rgb: The button's current color
+        btn_config: Validated button config dict
+
+    Returns:
+        (on_rgb, off_rgb) tuple
+    """
+    led = btn_config.get("led")
+    if not led:
+        return color_rgb, get_off_color(color_rgb, btn_config.get("off_mode", "dim"))
+    on_base = get_color(led["on_color"]) if "on_color" in led else color_rgb
+    off_base = get_color(led["off_color"]) if "off_color" in led else on_base
+    return scale_color(on_base, led["on_brightness"]), scale_color(off_base, led["off_brightness"])
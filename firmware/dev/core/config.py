@@ -10,7 +10,15 @@ except ImportError:
     # CircuitPython has json built-in, but just in case
     json = None
 
-VALID_TYPES = ("cc", "note", "pc", "pc_inc", "pc_dec")
+from core.expression import parse_curve
+from core.macro import MAX_MACRO_STEPS, MAX_STEP_DELAY_MS, MACRO_STEP_TYPES
+from core.tempo import DEFAULT_MIN_BPM, DEFAULT_MAX_BPM, BPM_LIMITS, TAP_OUTPUTS
+
+VALID_TYPES = ("cc", "note", "pc", "pc_inc", "pc_dec", "panic", "macro")
+VALID_DESTINATIONS = ("usb", "din", "both")
+SECONDARY_ACTIONS = ("long_press", "double_tap")
+SECONDARY_TYPES = ("cc", "pc")
+LED_COLOR_FIELDS = ("on_color", "off_color")
 STATE_OVERRIDE_FIELDS = ("cc", "cc_on", "cc_off", "note", "velocity_on", "velocity_off", "program", "pc_step", "color", "label")
 
 
@@ -62,6 +70,134 @@ def _clamp_state_field(field, value):
     return value  # color, label — pass through as-is
 
 
+def _validate_reset_ccs(value):
+    """Keep the valid CC numbers (0-127) from a panic button's reset_ccs list."""
+    if not isinstance(value, list):
+        return []
+    return [cc for cc in value if isinstance(cc, int) and 0 <= cc <= 127]
+
+
+def _validate_secondary(action, default_channel):
+    """Validate a long_press/double_tap action. Returns None when unusable.
+
+    A CC action needs "cc" ("value" defaults to 127); a PC action needs "program".
+    """
+    if not isinstance(action, dict):
+        return None
+    msg_type = action.get("type", "cc")
+    if msg_type not in SECONDARY_TYPES:
+        return None
+    channel = action.get("channel", default_channel)
+    if not isinstance(channel, int) or channel < 0 or channel > 15:
+        channel = default_channel
+    validated = {"type": msg_type, "channel": channel}
+    if msg_type == "cc":
+        if "cc" not in action:
+            return None
+        validated["cc"] = _clamp_state_field("cc", action["cc"])
+        validated["value"] = _clamp_state_field("cc_on", action.get("value", 127))
+    else:
+        if "program" not in action:
+            return None
+        validated["program"] = _clamp_state_field("program", action["program"])
+    return validated
+
+
+def _validate_macro_step(step, default_channel):
+    """Validate one macro step. Returns None when unusable.
+
+    A CC step needs "cc" ("value" defaults to 127), a PC step "program" and
+    a note step "note" ("velocity" defaults to 127; 0 is a note off).
+    delay_ms is the wait after the previous step, clamped to MAX_STEP_DELAY_MS.
+    """
+    if not isinstance(step, dict):
+        return None
+    msg_type = step.get("type", "cc")
+    if msg_type not in MACRO_STEP_TYPES:
+        return None
+    channel = step.get("channel", default_channel)
+    if not isinstance(channel, int) or channel < 0 or channel > 15:
+        channel = default_channel
+    delay_ms = step.get("delay_ms", 0)
+    if not isinstance(delay_ms, int):
+        delay_ms = 0
+    validated = {"type": msg_type, "channel": channel, "delay_ms": max(0, min(MAX_STEP_DELAY_MS, delay_ms))}
+    if msg_type == "cc":
+        if "cc" not in step:
+            return None
+        validated["cc"] = _clamp_state_field("cc", step["cc"])
+        validated["value"] = _clamp_state_field("cc_on", step.get("value", 127))
+    elif msg_type == "pc":
+        if "program" not in step:
+            return None
+        validated["program"] = _clamp_state_field("program", step["program"])
+    else:
+        if "note" not in step:
+            return None
+        validated["note"] = _clamp_state_field("note", step["note"])
+        validated["velocity"] = _clamp_state_field("velocity_on", step.get("velocity", 127))
+    return validated
+
+
+def _validate_macro_steps(steps, default_channel):
+    """Keep a macro's usable steps, at most MAX_MACRO_STEPS of them."""
+    if not isinstance(steps, list):
+        return []
+    validated = []
+    for step in steps:
+        step = _validate_macro_step(step, default_channel)
+        if step is not None:
+            validated.append(step)
+    return validated[:MAX_MACRO_STEPS]
+
+
+def _validate_tap_tempo(tap):
+    """Validate a tap-tempo button's settings, filling in defaults.
+
+    "output" is "cc" (the button's CC on every tap) or "clock" (MIDI clock at
+    the tapped tempo). min_bpm/max_bpm are clamped to BPM_LIMITS; a reversed
+    range falls back to the defaults.
+    """
+    if not isinstance(tap, dict):
+        tap = {}
+    output = tap.get("output", "cc")
+    if output not in TAP_OUTPUTS:
+        output = "cc"
+    low, high = BPM_LIMITS
+    bpm = []
+    for field, default in (("min_bpm", DEFAULT_MIN_BPM), ("max_bpm", DEFAULT_MAX_BPM)):
+        value = tap.get(field, default)
+        bpm.append(max(low, min(high, value)) if isinstance(value, int) else default)
+    if bpm[0] >= bpm[1]:
+        bpm = [DEFAULT_MIN_BPM, DEFAULT_MAX_BPM]
+    return {"output": output, "min_bpm": bpm[0], "max_bpm": bpm[1]}
+
+
+def _clamp_brightness(value, default):
+    """Clamp an LED brightness to 0-100, or the default when not a number."""
+    if not isinstance(value, int):
+        return default
+    return max(0, min(100, value))
+
+
+def _validate_led(led, off_mode):
+    """Validate a button's "led" settings. Returns None when not a dict.
+
+    Brightness is a percentage; off_brightness defaults to what off_mode
+    gives (15 for "dim", 0 for "off"). Colors pass through for get_color.
+    """
+    if not isinstance(led, dict):
+        return None
+    validated = {
+        "on_brightness": _clamp_brightness(led.get("on_brightness"), 100),
+        "off_brightness": _clamp_brightness(led.get("off_brightness"), 0 if off_mode == "off" else 15),
+    }
+    for field in LED_COLOR_FIELDS:
+        if isinstance(led.get(field), str):
+            validated[field] = led[field]
+    return validated
+
+
 def validate_button(btn, index=0, global_channel=None):
     """Validate a button config dict, filling in defaults.
 
@@ -79,6 +215,8 @@ def validate_button(btn, index=0, global_channel=None):
         - "pc": Program Change fixed
         - "pc_inc": Program Change increment
         - "pc_dec": Program Change decrement
+        - "panic": All Notes Off + All Sound Off (+ reset_ccs) on every channel
+        - "macro": "steps" sent in order on press, each after its delay_ms
     """
     if global_channel is not None:
         default_channel = global_channel
@@ -109,8 +247,9 @@ def validate_button(btn, index=0, global_channel=None):
     # Type-specific fields
     if msg_type == "cc":
         validated["cc"] = btn.get("cc", 20 + index)
-        validated["cc_on"] = btn.get("cc_on", 127)
-        validated["cc_off"] = btn.get("cc_off", 0)
+        # value_on/value_off are accepted as other names for cc_on/cc_off
+        validated["cc_on"] = btn.get("cc_on", btn.get("value_on", 127))
+        validated["cc_off"] = btn.get("cc_off", btn.get("value_off", 0))
     elif msg_type == "note":
         validated["note"] = btn.get("note", 60)
         validated["velocity_on"] = btn.get("velocity_on", 127)
@@ -119,6 +258,27 @@ def validate_button(btn, index=0, global_channel=None):
         validated["program"] = btn.get("program", 0)
     elif msg_type in ("pc_inc", "pc_dec"):
         validated["pc_step"] = btn.get("pc_step", 1)
+    elif msg_type == "panic":
+        validated["reset_ccs"] = _validate_reset_ccs(btn.get("reset_ccs", []))
+    elif msg_type == "macro":
+        validated["steps"] = _validate_macro_steps(btn.get("steps", []), validated["channel"])
+
+    # Tap tempo: a CC-type button whose presses set a tempo
+    if validated["mode"] == "tap_tempo":
+        validated["tap_tempo"] = _validate_tap_tempo(btn.get("tap_tempo"))
+
+    # LED colors and brightness, replacing off_mode for the LED
+    if "led" in btn:
+        led = _validate_led(btn["led"], validated["off_mode"])
+        if led is not None:
+            validated["led"] = led
+
+    # Long-press / double-tap: kept only when valid, channel defaults to the button's
+    for name in SECONDARY_ACTIONS:
+        if name in btn:
+            action = _validate_secondary(btn[name], validated["channel"])
+            if action is not None:
+                validated[name] = action
 
     # For keytimes > 1, validate and pass through states array
     if keytimes > 1:
@@ -223,6 +383,8 @@ def get_encoder_config(cfg):
         "initial": enc.get("initial", 64),
         "steps": enc.get("steps", None),
         "channel": enc.get("channel", global_channel),
+        "acceleration": enc.get("acceleration", "off"),
+        "wrap": bool(enc.get("wrap", False)),
         "push": {
             "enabled": push.get("enabled", True),
             "cc": push.get("cc", 14),
@@ -259,6 +421,9 @@ def get_expression_config(cfg):
             "polarity": exp1.get("polarity", "normal"),
             "threshold": exp1.get("threshold", 2),
             "channel": exp1.get("channel", global_channel),
+            "destination": get_destination(exp1),
+            "calibration": get_calibration(exp1),
+            "curve": parse_curve(exp1.get("curve", "linear")),
         },
         "exp2": {
             "enabled": exp2.get("enabled", True),
@@ -269,10 +434,102 @@ def get_expression_config(cfg):
             "polarity": exp2.get("polarity", "normal"),
             "threshold": exp2.get("threshold", 2),
             "channel": exp2.get("channel", global_channel),
+            "destination": get_destination(exp2),
+            "calibration": get_calibration(exp2),
+            "curve": parse_curve(exp2.get("curve", "linear")),
         },
     }
 
 
+def get_calibration(pedal):
+    """Measured raw ADC range of an expression pedal.
+
+    Args:
+        pedal: Expression pedal config dict
+
+    Returns:
+        (raw_min, raw_max), or None when the pedal is uncalibrated or the
+        calibration is unusable (the firmware then learns the range itself)
+    """
+    cal = pedal.get("calibration")
+    if not isinstance(cal, dict):
+        return None
+    raw_min = cal.get("raw_min")
+    raw_max = cal.get("raw_max")
+    for value in (raw_min, raw_max):
+        if not isinstance(value, int) or isinstance(value, bool) or not 0 <= value <= 65535:
+            return None
+    if raw_min >= raw_max:
+        return None
+    return (raw_min, raw_max)
+
+
+def get_destination(control):
+    """Outputs a control sends to: "usb", "din" or "both".
+
+    Args:
+        control: Control config dict (e.g. an expression pedal)
+
+    Returns:
+        The control's destination, or "usb" if unset or invalid
+    """
+    destination = control.get("destination", "usb")
+    if destination not in VALID_DESTINATIONS:
+        return "usb"
+    return destination
+
+
+def get_routing_config(cfg):
+    """Extract the MIDI output routing block with defaults.
+
+    Args:
+        cfg: Full config dict
+
+    Returns:
+        Dict with "usb" (default True) and "din" (default False)
+    """
+    routing = cfg.get("routing", {})
+    return {
+        "usb": bool(routing.get("usb", True)),
+        "din": bool(routing.get("din", False)),
+    }
+
+
+MAX_SLEEP_MINUTES = 240
+
+
+def get_global_config(cfg):
+    """Extract device-wide settings with defaults.
+
+    Args:
+        cfg: Full config dict
+
+    Returns:
+        Dict with "led_brightness" (0.0-1.0, default 0.3), "display_brightness"
+        (0.0-1.0, or None to leave the backlight alone) and "sleep_minutes"
+        (0 = never)
+    """
+    settings = cfg.get("global", {})
+    if not isinstance(settings, dict):
+        settings = {}
+
+    def percent(key):
+        value = settings.get(key)
+        if not isinstance(value, int) or isinstance(value, bool):
+            return None
+        return max(0, min(100, value)) / 100
+
+    led = percent("led_brightness")
+    sleep = settings.get("sleep_minutes", 0)
+    if not isinstance(sleep, int) or isinstance(sleep, bool):
+        sleep = 0
+    return {
+        "led_brightness": 0.3 if led is None else led,
+        "display_brightness": percent("display_brightness"),
+        "sleep_minutes": max(0, min(MAX_SLEEP_MINUTES, sleep)),
+    }
+
+
 def get_display_config(cfg):
     """Extract display configuration with defaults.
 
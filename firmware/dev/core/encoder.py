@@ -0,0 +1,42 @@
+"""
+Rotary encoder value handling for MIDI Captain firmware.
+
+Acceleration multiplies a turn by how quickly it follows the previous one, so
+a fast spin covers a long range (patch scrolling) while slow turns still move
+one step at a time. Wrap makes the value continue from the other end of its
+range instead of stopping at min/max.
+"""
+
+# (seconds since the previous move, multiplier), fastest first; slower moves step by 1
+ACCELERATION_CURVES = {
+    "off": (),
+    "low": ((0.03, 3), (0.08, 2)),
+    "high": ((0.03, 8), (0.06, 4), (0.12, 2)),
+}
+
+
+def accelerate(delta, interval_s, acceleration="off"):
+    """Scale an encoder move by how fast the encoder is turning.
+
+    Args:
+        delta: Detents moved since the last read (signed)
+        interval_s: Seconds since the previous move
+        acceleration: "off", "low" or "high" (unknown values act as "off")
+
+    Returns:
+        Signed number of steps to move the value
+    """
+    for limit, multiplier in ACCELERATION_CURVES.get(acceleration, ()):
+        if interval_s < limit:
+            return delta * multiplier
+    return delta
+
+
+def step_value(value, delta, lo, hi, wrap=False):
+    """Move value by delta within lo..hi, clamping or wrapping at the ends."""
+    if hi <= lo:
+        return lo
+    value += delta
+    if wrap:
+        return lo + (value - lo) % (hi - lo + 1)
+    return max(lo, min(hi, value))
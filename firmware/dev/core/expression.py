@@ -0,0 +1,66 @@
+"""
+Expression pedal response curves for MIDI Captain firmware.
+
+A curve reshapes pedal travel before it is scaled to the pedal's min..max.
+"log" rises quickly at the heel and "exp" slowly, so volume swells don't
+jump in the first inch of travel. A breakpoint table of [travel, output]
+pairs (both 0-127, interpolated linearly) gives any other shape.
+"""
+
+import math
+
+CURVE_SHAPES = ("linear", "log", "exp")
+
+
+def parse_curve(curve):
+    """Check a config curve and put it in the form apply_curve() takes.
+
+    Args:
+        curve: "linear", "log", "exp" or a list of [travel, output] pairs
+
+    Returns:
+        The shape name, or a tuple of (travel, output) pairs scaled to 0.0-1.0.
+        Anything unusable (unknown name, breakpoints that don't run from 0 to
+        127 with rising travel and non-decreasing output) gives "linear".
+    """
+    if curve in CURVE_SHAPES:
+        return curve
+    if not isinstance(curve, list) or len(curve) < 2:
+        return "linear"
+    points = []
+    for point in curve:
+        if not isinstance(point, (list, tuple)) or len(point) != 2:
+            return "linear"
+        for value in point:
+            if not isinstance(value, int) or isinstance(value, bool) or not 0 <= value <= 127:
+                return "linear"
+        points.append((point[0], point[1]))
+    if points[0][0] != 0 or points[-1][0] != 127:
+        return "linear"
+    for (t1, o1), (t2, o2) in zip(points, points[1:]):
+        if t2 <= t1 or o2 < o1:
+            return "linear"
+    return tuple((t / 127, o / 127) for t, o in points)
+
+
+def apply_curve(position, curve):
+    """Reshape pedal travel.
+
+    Args:
+        position: Travel from heel (0.0) to toe (1.0)
+        curve: A result of parse_curve()
+
+    Returns:
+        Output fraction 0.0-1.0
+    """
+    position = max(0.0, min(1.0, position))
+    if curve == "log":
+        return math.log(1 + 9 * position) / math.log(10)
+    if curve == "exp":
+        return (math.pow(10, position) - 1) / 9
+    if isinstance(curve, tuple):
+        for (t1, o1), (t2, o2) in zip(curve, curve[1:]):
+            if position <= t2:
+                return o1 + (position - t1) / (t2 - t1) * (o2 - o1)
+        return curve[-1][1]
+    return position
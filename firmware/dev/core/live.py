@@ -0,0 +1,157 @@
+"""
+Live parameter protocol for MIDI Captain firmware.
+
+The config editor can audition settings on a running device without writing
+config.json. It sends one line per change over the USB serial console:
+
+    @live <path> <json value>
+
+e.g. `@live led.brightness 0.5` or `@live buttons.3.color "red"`. The firmware
+applies the value in memory only; a reload restores whatever config.json says.
+
+`@live monitor.exp true` turns on the expression pedal monitor: the firmware
+then prints `@exp <raw1> <mapped1> <sent1> <raw2> <mapped2> <sent2>` lines at
+~30 Hz until it is turned off again. `@live monitor.enc true` does the same
+for the encoder with `@enc <position> <value> <slot> <steps>` lines.
+
+`@live preview.buttons.<n>.label "TEXT"` shows a candidate label for a few
+seconds, then puts the configured label back.
+"""
+
+try:
+    import json
+except ImportError:
+    json = None
+
+LIVE_PREFIX = "@live "
+
+# Numeric parameters and their allowed (min, max) range
+NUMERIC_PARAMETERS = {
+    "led.brightness": (0.0, 1.0),
+}
+
+# On/off switches
+BOOL_PARAMETERS = ("monitor.exp", "monitor.enc")
+
+# Minimum time between monitor lines (~30 Hz)
+MONITOR_INTERVAL_S = 1 / 30
+
+# How long a previewed label stays on screen
+PREVIEW_DURATION_S = 4.0
+
+# Longest preview text accepted (the display clips anyway; this bounds memory)
+PREVIEW_MAX_CHARS = 16
+
+# Per-button fields that can be changed live ("buttons.<n>.<field>", 1-indexed)
+BUTTON_FIELDS = ("color", "label", "off_mode")
+
+
+def parse_live_command(line):
+    """Parse a live-parameter line.
+
+    Args:
+        line: One line read from the serial console
+
+    Returns:
+        (path, value) tuple, or None if the line is not a valid live command
+    """
+    line = line.strip()
+    if not line.startswith(LIVE_PREFIX) or json is None:
+        return None
+    rest = line[len(LIVE_PREFIX):].strip()
+    parts = rest.split(" ", 1)
+    if len(parts) != 2:
+        return None
+    path, raw = parts
+    try:
+        value = json.loads(raw)
+    except ValueError:
+        return None
+    return path, value
+
+
+def validate_live_value(path, value, button_count):
+    """Check a live parameter against the supported set.
+
+    Args:
+        path: Dotted parameter path
+        value: Decoded JSON value
+        button_count: Number of buttons on this device
+
+    Returns:
+        None if valid, otherwise an error message string
+    """
+    if path in NUMERIC_PARAMETERS:
+        if isinstance(value, bool) or not isinstance(value, (int, float)):
+            return "expected a number"
+        lo, hi = NUMERIC_PARAMETERS[path]
+        if value < lo or value > hi:
+            return "out of range {}-{}".format(lo, hi)
+        return None
+
+    if path in BOOL_PARAMETERS:
+        if not isinstance(value, bool):
+            return "expected true or false"
+        return None
+
+    parts = path.split(".")
+    if parts[0] == "preview":
+        if len(parts) != 4 or parts[3] != "label":
+            return "only button labels can be previewed"
+        if isinstance(value, str) and len(value) > PREVIEW_MAX_CHARS:
+            return "preview text longer than {} chars".format(PREVIEW_MAX_CHARS)
+        parts = parts[1:]
+
+    if len(parts) == 3 and parts[0] == "buttons":
+        try:
+            index = int(parts[1])
+        except ValueError:
+            return "invalid button number"
+        if index < 1 or index > button_count:
+            return "button out of range 1-{}".format(button_count)
+        if parts[2] not in BUTTON_FIELDS:
+            return "unsupported button field"
+        if not isinstance(value, str):
+            return "expected a string"
+        if parts[2] == "off_mode" and value not in ("dim", "off"):
+            return "off_mode must be 'dim' or 'off'"
+        return None
+
+    return "unsupported parameter"
+
+
+def format_live_reply(path, error=None):
+    """Build the acknowledgement line the editor waits for."""
+    if error:
+        return "@live err {} {}".format(path, error)
+    return "@live ok {}".format(path)
+
+
+def format_exp_sample(readings):
+    """Build an expression monitor line.
+
+    Args:
+        readings: [(raw, mapped, sent), (raw, mapped, sent)] for EXP1 and EXP2
+
+    Returns:
+        e.g. "@exp 1024 0 0 65535 127 126"
+    """
+    values = []
+    for reading in readings:
+        values.extend(str(int(v)) for v in reading)
+    return "@exp " + " ".join(values)
+
+
+def format_enc_sample(position, value, slot, steps):
+    """Build an encoder monitor line.
+
+    Args:
+        position: Raw rotary position (detent count since boot)
+        value: Internal value (0-127)
+        slot: Current slot in stepped mode, -1 before the first change
+        steps: Number of slots, 0 when not stepped
+
+    Returns:
+        e.g. "@enc 12 76 2 5"
+    """
+    return "@enc {} {} {} {}".format(int(position), int(value), int(slot), int(steps))
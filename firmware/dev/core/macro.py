@@ -0,0 +1,53 @@
+"""
+Macro playback for MIDI Captain firmware.
+
+A macro button sends an ordered list of MIDI messages ("steps") on each
+press. Each step waits its delay_ms after the one before it, so playback is
+driven from the main loop instead of sleeping.
+"""
+
+MAX_MACRO_STEPS = 16       # Steps kept per macro; the rest are dropped
+MAX_STEP_DELAY_MS = 5000   # Longest wait before a step
+MACRO_STEP_TYPES = ("cc", "pc", "note")
+
+
+class MacroPlayer:
+    """Plays one button's macro steps as their delays come due."""
+
+    def __init__(self):
+        self.steps = []
+        self.next_index = 0
+        self.next_due_ms = 0
+
+    @property
+    def running(self):
+        """True while steps are still waiting to go out."""
+        return self.next_index < len(self.steps)
+
+    def start(self, steps, now_ms):
+        """Start the macro from its first step, abandoning any run in progress.
+
+        Args:
+            steps: Validated step dicts (see validate_button in core/config.py)
+            now_ms: Current time in milliseconds
+        """
+        self.steps = steps
+        self.next_index = 0
+        self.next_due_ms = now_ms + (steps[0]["delay_ms"] if steps else 0)
+
+    def due(self, now_ms):
+        """Return the steps whose time has come, in order.
+
+        Args:
+            now_ms: Current time in milliseconds
+
+        Returns:
+            List of step dicts to send now (empty when none are due)
+        """
+        ready = []
+        while self.running and now_ms >= self.next_due_ms:
+            ready.append(self.steps[self.next_index])
+            self.next_index += 1
+            if self.running:
+                self.next_due_ms += self.steps[self.next_index]["delay_ms"]
+        return ready
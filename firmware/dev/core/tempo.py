@@ -0,0 +1,69 @@
+"""
+Tap tempo for MIDI Captain firmware.
+
+A tap-tempo button turns the time between taps into a tempo. It either sends
+its CC on every tap (for devices that time the taps themselves) or drives
+MIDI clock at the tapped tempo.
+"""
+
+DEFAULT_MIN_BPM = 40
+DEFAULT_MAX_BPM = 240
+BPM_LIMITS = (20, 300)       # Widest range min_bpm/max_bpm may span
+TAP_OUTPUTS = ("cc", "clock")
+CLOCKS_PER_BEAT = 24         # MIDI clock resolution (pulses per quarter note)
+TAPS_AVERAGED = 4            # Intervals averaged into the tempo
+
+
+class TapTempo:
+    """Turns taps into a tempo within min_bpm..max_bpm."""
+
+    def __init__(self, min_bpm=DEFAULT_MIN_BPM, max_bpm=DEFAULT_MAX_BPM):
+        self.min_bpm = min_bpm
+        self.max_bpm = max_bpm
+        self.intervals = []
+        self.last_tap_ms = None
+        self.bpm = None
+
+    def tap(self, now_ms):
+        """Register a tap. Returns the tempo in BPM, or None until there is one.
+
+        A gap longer than a beat at min_bpm starts a new count, so a pause
+        between songs doesn't drag the tempo down.
+
+        Args:
+            now_ms: Current time in milliseconds
+        """
+        if self.last_tap_ms is not None:
+            interval = now_ms - self.last_tap_ms
+            if interval > 60000 / self.min_bpm:
+                self.intervals = []
+            elif interval > 0:
+                self.intervals = (self.intervals + [interval])[-TAPS_AVERAGED:]
+                average = sum(self.intervals) / len(self.intervals)
+                self.bpm = max(self.min_bpm, min(self.max_bpm, round(60000 / average)))
+        self.last_tap_ms = now_ms
+        return self.bpm
+
+
+class ClockSender:
+    """Times MIDI clock pulses at a tempo."""
+
+    def __init__(self):
+        self.bpm = None
+        self.next_due_ms = 0
+
+    def set_bpm(self, bpm, now_ms):
+        """Run the clock at bpm, starting a pulse now."""
+        self.bpm = bpm
+        self.next_due_ms = now_ms
+
+    def tick(self, now_ms):
+        """Return True when a pulse is due now (never while stopped)."""
+        if self.bpm is None or now_ms < self.next_due_ms:
+            return False
+        interval = 60000 / (self.bpm * CLOCKS_PER_BEAT)
+        if now_ms - self.next_due_ms >= interval:
+            # Fell behind (a slow loop): resync rather than send a burst
+            self.next_due_ms = now_ms
+        self.next_due_ms += interval
+        return True
@@ -54,11 +54,16 @@ EXP1_PIN = None
 EXP2_PIN = None
 BATTERY_PIN = None
 
+# No serial MIDI port on Mini6
+MIDI_TX_PIN = None
+MIDI_RX_PIN = None
+
 # Display (ST7789 over SPI) - same as STD10
 TFT_DC_PIN = board.GP12
 TFT_CS_PIN = board.GP13
 TFT_SCK_PIN = board.GP14
 TFT_MOSI_PIN = board.GP15
+TFT_BL_PIN = board.GP8  # Backlight (PWM)
 
 # ST7789 parameters - same as STD10
 DISPLAY_WIDTH = 240
@@ -59,11 +59,16 @@ EXP1_PIN = board.A1
 EXP2_PIN = board.A2
 BATTERY_PIN = board.A0  # Battery voltage divider
 
+# Serial (5-pin DIN) MIDI
+MIDI_TX_PIN = board.GP16
+MIDI_RX_PIN = board.GP17
+
 # Display (ST7789 over SPI)
 TFT_DC_PIN = board.GP12
 TFT_CS_PIN = board.GP13
 TFT_SCK_PIN = board.GP14
 TFT_MOSI_PIN = board.GP15
+TFT_BL_PIN = board.GP8  # Backlight (PWM)
 
 # ST7789 parameters
 DISPLAY_WIDTH = 240
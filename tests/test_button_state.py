@@ -10,7 +10,7 @@ from pathlib import Path
 FIRMWARE_DIR = Path(__file__).parent.parent / "firmware" / "dev"
 sys.path.insert(0, str(FIRMWARE_DIR))
 
-from core.button import ButtonState
+from core.button import ButtonState, GestureDetector
 
 
 class TestButtonStateToggle:
@@ -254,3 +254,39 @@ class TestAdvanceKeytime:
         btn.advance_keytime()  # → 2
         btn.on_press()         # should also advance → wrap to 1
         assert btn.get_keytime() == 1
+
+
+class TestGestureDetector:
+    """Tests for long-press / double-tap detection."""
+
+    def feed(self, detector, steps):
+        """Feed (pressed, time_ms) steps and collect the gestures reported."""
+        return [g for g in (detector.update(p, t) for p, t in steps) if g is not None]
+
+    def test_tap_on_release_without_double_tap(self):
+        det = GestureDetector(long_press=True)
+        assert self.feed(det, [(True, 0), (True, 100), (False, 150)]) == ["tap"]
+
+    def test_long_press_fires_while_held_and_suppresses_tap(self):
+        det = GestureDetector(long_press=True)
+        assert self.feed(det, [(True, 0), (True, 499), (True, 500), (True, 900), (False, 1000)]) == ["long_press"]
+
+    def test_long_press_ignored_when_not_configured(self):
+        det = GestureDetector(double_tap=True)
+        assert self.feed(det, [(True, 0), (True, 2000), (False, 2100), (False, 2500)]) == ["tap"]
+
+    def test_double_tap(self):
+        det = GestureDetector(double_tap=True)
+        steps = [(True, 0), (False, 100), (True, 250), (False, 350), (False, 1000)]
+        assert self.feed(det, steps) == ["double_tap"]
+
+    def test_tap_after_double_tap_window(self):
+        det = GestureDetector(double_tap=True)
+        assert self.feed(det, [(True, 0), (False, 100), (False, 300)]) == []
+        assert self.feed(det, [(False, 401)]) == ["tap"]
+
+    def test_late_second_press_is_two_taps(self):
+        """A press after the window (with no loop in between) reports the pending tap first."""
+        det = GestureDetector(double_tap=True)
+        steps = [(True, 0), (False, 100), (True, 500), (True, 510), (False, 600), (False, 1000)]
+        assert self.feed(det, steps) == ["tap", "tap"]
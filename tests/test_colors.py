@@ -12,7 +12,7 @@ from pathlib import Path
 FIRMWARE_DIR = Path(__file__).parent.parent / "firmware" / "dev"
 sys.path.insert(0, str(FIRMWARE_DIR))
 
-from core.colors import COLORS, get_color, dim_color, rgb_to_hex, get_off_color, get_off_color_for_display
+from core.colors import COLORS, get_color, dim_color, rgb_to_hex, get_off_color, get_off_color_for_display, get_led_colors
 
 
 class TestGetColor:
@@ -33,6 +33,14 @@ class TestGetColor:
         assert get_color("chartreuse") == (255, 255, 255)
         assert get_color("") == (255, 255, 255)
 
+    def test_hex_colors(self):
+        assert get_color("#ff8000") == (255, 128, 0)
+        assert get_color("#0A141E") == (10, 20, 30)
+
+    def test_invalid_hex_returns_white(self):
+        assert get_color("#gg0000") == (255, 255, 255)
+        assert get_color("#fff") == (255, 255, 255)
+
 
 class TestDimColor:
     """Tests for dim_color function."""
@@ -113,3 +121,26 @@ class TestGetOffColorForDisplay:
         expected_dim = (38, 38, 38)  # dim_color default factor 0.15
         assert result == expected_dim
         assert result != (0, 0, 0)
+
+
+class TestGetLedColors:
+    """Tests for get_led_colors (per-button LED colors and brightness)."""
+
+    def test_without_led_settings_uses_off_mode(self):
+        color = (255, 0, 0)
+        assert get_led_colors(color, {"off_mode": "dim"}) == (color, dim_color(color))
+        assert get_led_colors(color, {"off_mode": "off"}) == (color, (0, 0, 0))
+
+    def test_brightness_scales_the_color(self):
+        led = {"on_brightness": 50, "off_brightness": 0}
+        assert get_led_colors((255, 0, 0), {"led": led}) == ((127, 0, 0), (0, 0, 0))
+
+    def test_off_color_replaces_the_dimmed_color(self):
+        led = {"on_brightness": 100, "off_brightness": 10, "off_color": "white"}
+        on, off = get_led_colors((255, 0, 0), {"led": led})
+        assert on == (255, 0, 0)
+        assert off == (25, 25, 25)
+
+    def test_on_color_overrides_the_button_color(self):
+        led = {"on_brightness": 100, "off_brightness": 0, "on_color": "#00ff00"}
+        assert get_led_colors((255, 0, 0), {"led": led})[0] == (0, 255, 0)
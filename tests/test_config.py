@@ -20,6 +20,9 @@ from core.config import (
     get_encoder_config,
     get_expression_config,
     get_button_state_config,
+    get_routing_config,
+    get_global_config,
+    get_calibration,
 )
 
 
@@ -327,6 +330,17 @@ class TestButtonMessageTypes:
         assert btn_inc["pc_step"] == 1
         assert btn_dec["pc_step"] == 1
 
+    def test_panic_type(self):
+        btn = validate_button({"type": "panic", "reset_ccs": [121, 64]}, index=0)
+        assert btn["type"] == "panic"
+        assert btn["reset_ccs"] == [121, 64]
+        assert "cc" not in btn
+
+    def test_panic_reset_ccs_drop_invalid_entries(self):
+        assert validate_button({"type": "panic"}, index=0)["reset_ccs"] == []
+        btn = validate_button({"type": "panic", "reset_ccs": [64, 200, "x", -1]}, index=0)
+        assert btn["reset_ccs"] == [64]
+
     def test_invalid_type_falls_back_to_cc(self):
         btn = validate_button({"type": "invalid_type"}, index=0)
         assert btn["type"] == "cc"
@@ -457,6 +471,183 @@ class TestEncoderConfig:
         assert enc["push"]["cc_off"] == 0
 
 
+class TestRoutingConfig:
+    """Test get_routing_config and expression pedal destinations."""
+
+    def test_defaults_to_usb_only(self):
+        """Without a routing block only USB is open."""
+        assert get_routing_config({}) == {"usb": True, "din": False}
+
+    def test_routing_overrides(self):
+        routing = get_routing_config({"routing": {"din": True}})
+        assert routing == {"usb": True, "din": True}
+
+    def test_expression_destination(self):
+        """Pedals default to USB; invalid destinations fall back to USB."""
+        exp = get_expression_config({
+            "expression": {
+                "exp1": {"destination": "both"},
+                "exp2": {"destination": "midi"},
+            }
+        })
+        assert exp["exp1"]["destination"] == "both"
+        assert exp["exp2"]["destination"] == "usb"
+
+
+class TestOnOffValues:
+    """value_on/value_off are other names for cc_on/cc_off."""
+
+    def test_value_aliases(self):
+        result = validate_button({"cc": 20, "value_on": 64, "value_off": 10}, index=0)
+        assert (result["cc_on"], result["cc_off"]) == (64, 10)
+
+    def test_cc_names_win(self):
+        result = validate_button({"cc": 20, "cc_on": 100, "value_on": 64}, index=0)
+        assert result["cc_on"] == 100
+        assert result["cc_off"] == 0
+
+
+class TestSecondaryActions:
+    """Test long_press / double_tap validation in validate_button."""
+
+    def test_cc_and_pc_actions_kept(self):
+        result = validate_button({
+            "channel": 3,
+            "long_press": {"type": "cc", "cc": 64, "value": 100},
+            "double_tap": {"type": "pc", "program": 7, "channel": 9},
+        }, index=0)
+        assert result["long_press"] == {"type": "cc", "channel": 3, "cc": 64, "value": 100}
+        assert result["double_tap"] == {"type": "pc", "channel": 9, "program": 7}
+
+    def test_defaults(self):
+        """Type defaults to cc and value to 127."""
+        result = validate_button({"long_press": {"cc": 10}}, index=0)
+        assert result["long_press"] == {"type": "cc", "channel": 0, "cc": 10, "value": 127}
+
+    def test_out_of_range_values_clamped(self):
+        result = validate_button({"long_press": {"cc": 200, "value": -5, "channel": 20}}, index=0)
+        assert result["long_press"] == {"type": "cc", "channel": 0, "cc": 127, "value": 0}
+
+    def test_unusable_actions_dropped(self):
+        result = validate_button({
+            "long_press": {"type": "pc"},
+            "double_tap": {"type": "note", "cc": 1},
+        }, index=0)
+        assert "long_press" not in result
+        assert "double_tap" not in result
+
+    def test_absent_by_default(self):
+        result = validate_button({}, index=0)
+        assert "long_press" not in result
+        assert "double_tap" not in result
+
+
+class TestMacroSteps:
+    """Test macro step validation in validate_button."""
+
+    def test_steps_kept_with_defaults(self):
+        result = validate_button({
+            "type": "macro",
+            "channel": 2,
+            "steps": [
+                {"type": "pc", "program": 5},
+                {"cc": 64, "delay_ms": 50},
+                {"type": "note", "note": 60, "velocity": 0, "channel": 9},
+            ],
+        }, index=0)
+        assert result["steps"] == [
+            {"type": "pc", "channel": 2, "delay_ms": 0, "program": 5},
+            {"type": "cc", "channel": 2, "delay_ms": 50, "cc": 64, "value": 127},
+            {"type": "note", "channel": 9, "delay_ms": 0, "note": 60, "velocity": 0},
+        ]
+
+    def test_unusable_steps_dropped_and_delays_clamped(self):
+        result = validate_button({
+            "type": "macro",
+            "steps": [{"type": "pc"}, {"type": "sysex"}, "cc", {"cc": 200, "delay_ms": 99999}],
+        }, index=0)
+        assert result["steps"] == [{"type": "cc", "channel": 0, "delay_ms": 5000, "cc": 127, "value": 127}]
+
+    def test_step_count_limited(self):
+        steps = [{"type": "pc", "program": n} for n in range(20)]
+        result = validate_button({"type": "macro", "steps": steps}, index=0)
+        assert len(result["steps"]) == 16
+        assert result["steps"][-1]["program"] == 15
+
+
+class TestTapTempo:
+    """Test tap-tempo settings in validate_button."""
+
+    def test_defaults(self):
+        result = validate_button({"mode": "tap_tempo", "cc": 64}, index=0)
+        assert result["tap_tempo"] == {"output": "cc", "min_bpm": 40, "max_bpm": 240}
+
+    def test_settings_kept_and_clamped(self):
+        result = validate_button({"mode": "tap_tempo", "tap_tempo": {"output": "clock", "min_bpm": 10, "max_bpm": 180}}, index=0)
+        assert result["tap_tempo"] == {"output": "clock", "min_bpm": 20, "max_bpm": 180}
+
+    def test_unusable_settings_fall_back(self):
+        result = validate_button({"mode": "tap_tempo", "tap_tempo": {"output": "midi", "min_bpm": 200, "max_bpm": 100}}, index=0)
+        assert result["tap_tempo"] == {"output": "cc", "min_bpm": 40, "max_bpm": 240}
+
+    def test_only_for_tap_tempo_mode(self):
+        assert "tap_tempo" not in validate_button({"tap_tempo": {"output": "clock"}}, index=0)
+
+
+class TestLedSettings:
+    """Test per-button LED settings in validate_button."""
+
+    def test_defaults_follow_off_mode(self):
+        assert validate_button({"led": {}}, index=0)["led"] == {"on_brightness": 100, "off_brightness": 15}
+        result = validate_button({"off_mode": "off", "led": {"off_color": "white"}}, index=0)
+        assert result["led"] == {"on_brightness": 100, "off_brightness": 0, "off_color": "white"}
+
+    def test_brightness_clamped(self):
+        result = validate_button({"led": {"on_brightness": 150, "off_brightness": -3}}, index=0)
+        assert result["led"] == {"on_brightness": 100, "off_brightness": 0}
+
+    def test_absent_or_unusable(self):
+        assert "led" not in validate_button({}, index=0)
+        assert "led" not in validate_button({"led": "dim"}, index=0)
+
+
+class TestGlobalConfig:
+    """Test get_global_config."""
+
+    def test_defaults(self):
+        assert get_global_config({}) == {"led_brightness": 0.3, "display_brightness": None, "sleep_minutes": 0}
+
+    def test_percentages_scaled_and_clamped(self):
+        settings = get_global_config({"global": {"led_brightness": 80, "display_brightness": 150, "sleep_minutes": 10}})
+        assert settings == {"led_brightness": 0.8, "display_brightness": 1.0, "sleep_minutes": 10}
+
+    def test_invalid_values_use_defaults(self):
+        settings = get_global_config({"global": {"led_brightness": "bright", "sleep_minutes": -5}})
+        assert settings["led_brightness"] == 0.3
+        assert settings["sleep_minutes"] == 0
+        assert get_global_config({"global": []})["sleep_minutes"] == 0
+
+
+class TestCalibration:
+    """Test get_calibration and the calibration entry of get_expression_config."""
+
+    def test_uncalibrated(self):
+        assert get_calibration({"cc": 12}) is None
+        assert get_expression_config({})["exp1"]["calibration"] is None
+
+    def test_calibrated_range(self):
+        exp = get_expression_config({"expression": {"exp2": {"calibration": {"raw_min": 900, "raw_max": 64000}}}})
+        assert exp["exp2"]["calibration"] == (900, 64000)
+        assert exp["exp1"]["calibration"] is None
+
+    def test_unusable_calibration_ignored(self):
+        assert get_calibration({"calibration": {"raw_min": 5000, "raw_max": 5000}}) is None
+        assert get_calibration({"calibration": {"raw_min": -1, "raw_max": 5000}}) is None
+        assert get_calibration({"calibration": {"raw_min": 0, "raw_max": 70000}}) is None
+        assert get_calibration({"calibration": {"raw_min": 0}}) is None
+        assert get_calibration({"calibration": [0, 65535]}) is None
+
+
 class TestGetButtonStateConfig:
     def test_no_states_returns_base_cc_config(self):
         btn = {"type": "cc", "cc": 20, "cc_on": 127, "cc_off": 0, "color": "white"}
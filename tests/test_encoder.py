@@ -0,0 +1,50 @@
+"""
+Tests for encoder acceleration and wrapping from core/encoder.py.
+"""
+
+import pytest
+import sys
+from pathlib import Path
+
+# Add firmware/dev to path
+FIRMWARE_DIR = Path(__file__).parent.parent / "firmware" / "dev"
+sys.path.insert(0, str(FIRMWARE_DIR))
+
+from core.encoder import accelerate, step_value
+
+
+class TestAccelerate:
+    """Tests for accelerate."""
+
+    def test_off_moves_one_step_per_detent(self):
+        assert accelerate(1, 0.001, "off") == 1
+        assert accelerate(-2, 0.001, "off") == -2
+
+    def test_fast_turns_multiply(self):
+        assert accelerate(1, 0.01, "high") == 8
+        assert accelerate(-1, 0.05, "high") == -4
+        assert accelerate(1, 0.01, "low") == 3
+
+    def test_slow_turns_step_by_one(self):
+        assert accelerate(1, 0.5, "high") == 1
+        assert accelerate(1, 0.1, "low") == 1
+
+    def test_unknown_acceleration_is_off(self):
+        assert accelerate(1, 0.01, "turbo") == 1
+
+
+class TestStepValue:
+    """Tests for step_value."""
+
+    def test_clamps_at_range(self):
+        assert step_value(120, 10, 0, 127) == 127
+        assert step_value(5, -10, 3, 100) == 3
+        assert step_value(50, 5, 0, 127) == 55
+
+    def test_wraps_past_max_and_min(self):
+        assert step_value(127, 1, 0, 127, wrap=True) == 0
+        assert step_value(0, -1, 0, 127, wrap=True) == 127
+        assert step_value(9, 3, 1, 10, wrap=True) == 2
+
+    def test_single_value_range(self):
+        assert step_value(5, 3, 5, 5, wrap=True) == 5
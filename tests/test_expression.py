@@ -0,0 +1,56 @@
+"""
+Tests for expression pedal response curves from core/expression.py.
+"""
+
+import pytest
+import sys
+from pathlib import Path
+
+# Add firmware/dev to path
+FIRMWARE_DIR = Path(__file__).parent.parent / "firmware" / "dev"
+sys.path.insert(0, str(FIRMWARE_DIR))
+
+from core.expression import parse_curve, apply_curve
+
+
+class TestParseCurve:
+    """Tests for parse_curve."""
+
+    def test_named_shapes(self):
+        for shape in ("linear", "log", "exp"):
+            assert parse_curve(shape) == shape
+
+    def test_breakpoints_scaled(self):
+        assert parse_curve([[0, 0], [127, 127]]) == ((0.0, 0.0), (1.0, 1.0))
+
+    def test_unusable_curves_are_linear(self):
+        assert parse_curve("s-curve") == "linear"
+        assert parse_curve([[0, 0]]) == "linear"
+        assert parse_curve([[10, 0], [127, 127]]) == "linear"
+        assert parse_curve([[0, 0], [64, 90], [64, 100], [127, 127]]) == "linear"
+        assert parse_curve([[0, 0], [64, 90], [127, 60]]) == "linear"
+        assert parse_curve([[0, 0], [127, 200]]) == "linear"
+        assert parse_curve([[0, 0, 0], [127, 127]]) == "linear"
+
+
+class TestApplyCurve:
+    """Tests for apply_curve."""
+
+    def test_shapes_keep_the_ends(self):
+        for shape in ("linear", "log", "exp"):
+            assert apply_curve(0.0, shape) == pytest.approx(0.0)
+            assert apply_curve(1.0, shape) == pytest.approx(1.0)
+
+    def test_exp_rises_slowly_at_the_heel(self):
+        assert apply_curve(0.25, "exp") < 0.25 < apply_curve(0.25, "log")
+        assert apply_curve(0.5, "linear") == 0.5
+
+    def test_breakpoints_interpolate(self):
+        curve = parse_curve([[0, 0], [64, 20], [127, 127]])
+        assert apply_curve(64 / 127, curve) == pytest.approx(20 / 127)
+        assert apply_curve(32 / 127, curve) == pytest.approx(10 / 127)
+        assert apply_curve(1.0, curve) == pytest.approx(1.0)
+
+    def test_position_is_clamped(self):
+        assert apply_curve(1.5, "linear") == 1.0
+        assert apply_curve(-0.2, "exp") == pytest.approx(0.0)
@@ -0,0 +1,95 @@
+"""
+Tests for the live parameter protocol.
+
+Tests the actual core/live.py module.
+"""
+
+import pytest
+import sys
+from pathlib import Path
+
+# Add firmware/dev to path so we can import core modules
+FIRMWARE_DIR = Path(__file__).parent.parent / "firmware" / "dev"
+sys.path.insert(0, str(FIRMWARE_DIR))
+
+from core.live import parse_live_command, validate_live_value, format_live_reply, format_exp_sample, format_enc_sample
+
+
+class TestParseLiveCommand:
+    """Tests for parse_live_command function."""
+
+    def test_number(self):
+        assert parse_live_command("@live led.brightness 0.5\r\n") == ("led.brightness", 0.5)
+
+    def test_string(self):
+        assert parse_live_command('@live buttons.3.color "red"') == ("buttons.3.color", "red")
+
+    def test_string_with_spaces(self):
+        assert parse_live_command('@live buttons.1.label "BIG MUFF"') == ("buttons.1.label", "BIG MUFF")
+
+    def test_ignores_other_lines(self):
+        assert parse_live_command("print('hi')") is None
+        assert parse_live_command("") is None
+
+    def test_rejects_bad_json(self):
+        assert parse_live_command("@live led.brightness nope") is None
+        assert parse_live_command("@live led.brightness") is None
+
+
+class TestValidateLiveValue:
+    """Tests for validate_live_value function."""
+
+    def test_brightness_in_range(self):
+        assert validate_live_value("led.brightness", 0.3, 10) is None
+        assert validate_live_value("led.brightness", 1, 10) is None
+
+    def test_brightness_out_of_range(self):
+        assert validate_live_value("led.brightness", 1.5, 10) is not None
+        assert validate_live_value("led.brightness", True, 10) is not None
+
+    def test_button_field(self):
+        assert validate_live_value("buttons.6.color", "blue", 6) is None
+        assert validate_live_value("buttons.7.color", "blue", 6) is not None
+        assert validate_live_value("buttons.1.cc", 20, 10) is not None
+        assert validate_live_value("buttons.1.off_mode", "bright", 10) is not None
+
+    def test_monitor_switch(self):
+        assert validate_live_value("monitor.exp", True, 10) is None
+        assert validate_live_value("monitor.exp", 1, 10) == "expected true or false"
+        assert validate_live_value("monitor.enc", False, 10) is None
+
+    def test_label_preview(self):
+        assert validate_live_value("preview.buttons.2.label", "BIG MUFF", 10) is None
+        assert validate_live_value("preview.buttons.2.color", "red", 10) == "only button labels can be previewed"
+        assert validate_live_value("preview.buttons.11.label", "X", 10) is not None
+        assert validate_live_value("preview.buttons.2.label", "X" * 17, 10) is not None
+
+    def test_unsupported(self):
+        assert validate_live_value("display.rotation", 90, 10) == "unsupported parameter"
+
+
+class TestFormatLiveReply:
+    """Tests for format_live_reply function."""
+
+    def test_ok(self):
+        assert format_live_reply("led.brightness") == "@live ok led.brightness"
+
+    def test_error(self):
+        assert format_live_reply("x", "unsupported parameter") == "@live err x unsupported parameter"
+
+
+class TestFormatExpSample:
+    """Tests for format_exp_sample function."""
+
+    def test_format(self):
+        assert format_exp_sample([(1024, 0, 0), (65535, 127, 126)]) == "@exp 1024 0 0 65535 127 126"
+
+
+class TestFormatEncSample:
+    """Tests for format_enc_sample function."""
+
+    def test_format(self):
+        assert format_enc_sample(12, 76, 2, 5) == "@enc 12 76 2 5"
+
+    def test_before_first_change(self):
+        assert format_enc_sample(0, 64, -1, 0) == "@enc 0 64 -1 0"
@@ -0,0 +1,54 @@
+"""
+Tests for the MacroPlayer class from core/macro.py.
+"""
+
+import pytest
+import sys
+from pathlib import Path
+
+# Add firmware/dev to path
+FIRMWARE_DIR = Path(__file__).parent.parent / "firmware" / "dev"
+sys.path.insert(0, str(FIRMWARE_DIR))
+
+from core.macro import MacroPlayer
+
+
+def step(n, delay_ms=0):
+    return {"type": "pc", "channel": 0, "program": n, "delay_ms": delay_ms}
+
+
+class TestMacroPlayer:
+    """Tests for macro step timing."""
+
+    def test_idle_until_started(self):
+        player = MacroPlayer()
+        assert not player.running
+        assert player.due(1000) == []
+
+    def test_undelayed_steps_go_out_at_once(self):
+        player = MacroPlayer()
+        player.start([step(1), step(2)], 1000)
+        assert player.due(1000) == [step(1), step(2)]
+        assert not player.running
+
+    def test_each_delay_follows_the_previous_step(self):
+        player = MacroPlayer()
+        player.start([step(1), step(2, 100), step(3, 50)], 1000)
+        assert player.due(1000) == [step(1)]
+        assert player.due(1099) == []
+        assert player.due(1100) == [step(2, 100)]
+        assert player.running
+        assert player.due(1200) == [step(3, 50)]
+        assert not player.running
+
+    def test_late_poll_sends_everything_due(self):
+        player = MacroPlayer()
+        player.start([step(1, 10), step(2, 10)], 0)
+        assert player.due(500) == [step(1, 10), step(2, 10)]
+
+    def test_restart_abandons_the_run(self):
+        player = MacroPlayer()
+        player.start([step(1), step(2, 100)], 0)
+        player.due(0)
+        player.start([step(3)], 50)
+        assert player.due(200) == [step(3)]
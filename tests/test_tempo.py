@@ -0,0 +1,64 @@
+"""
+Tests for TapTempo and ClockSender from core/tempo.py.
+"""
+
+import pytest
+import sys
+from pathlib import Path
+
+# Add firmware/dev to path
+FIRMWARE_DIR = Path(__file__).parent.parent / "firmware" / "dev"
+sys.path.insert(0, str(FIRMWARE_DIR))
+
+from core.tempo import TapTempo, ClockSender
+
+
+class TestTapTempo:
+    """Tests for turning taps into a tempo."""
+
+    def test_no_tempo_from_one_tap(self):
+        assert TapTempo().tap(0) is None
+
+    def test_steady_taps(self):
+        tapper = TapTempo()
+        for t in (0, 500, 1000, 1500):
+            bpm = tapper.tap(t)
+        assert bpm == 120
+
+    def test_averages_recent_intervals(self):
+        tapper = TapTempo()
+        tapper.tap(0)
+        tapper.tap(500)
+        assert tapper.tap(1100) == 109  # 550 ms average
+
+    def test_clamped_to_range(self):
+        tapper = TapTempo(min_bpm=60, max_bpm=120)
+        tapper.tap(0)
+        assert tapper.tap(200) == 120
+
+    def test_long_pause_starts_over(self):
+        tapper = TapTempo(min_bpm=40)
+        tapper.tap(0)
+        tapper.tap(500)
+        tapper.tap(10000)             # longer than a beat at 40 BPM
+        assert tapper.tap(11000) == 60  # only the new interval counts
+
+
+class TestClockSender:
+    """Tests for MIDI clock pulse timing."""
+
+    def test_stopped_until_tempo_set(self):
+        assert not ClockSender().tick(1000)
+
+    def test_pulses_per_beat(self):
+        clock = ClockSender()
+        clock.set_bpm(125, 0)  # 24 pulses per 480 ms beat: one every 20 ms
+        pulses = sum(clock.tick(t) for t in range(0, 480))
+        assert pulses == 24
+
+    def test_resyncs_after_a_stall(self):
+        clock = ClockSender()
+        clock.set_bpm(125, 0)
+        assert clock.tick(0)
+        assert clock.tick(1000)        # one pulse, not a burst
+        assert not clock.tick(1001)